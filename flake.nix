@@ -26,6 +26,7 @@
               wayland
 
               alsa-lib
+              udev
 
               libGL
               libxkbcommon
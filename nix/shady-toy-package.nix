@@ -9,6 +9,7 @@
 , libXcursor
 
 , alsa-lib
+, udev
 
 , libGL
 , libxkbcommon
@@ -28,6 +29,7 @@ let
     libXrandr
     libXcursor
     alsa-lib
+    udev
     libGL
     libxkbcommon
     vulkan-loader
@@ -1,7 +1,7 @@
-use std::{num::NonZero, ops::Range};
+use std::{num::NonZero, ops::Range, time::Duration};
 
 /// Decides which interpolation strategy for the bars.
-#[derive(Debug, Clone, Copy, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InterpolationVariant {
     /// No interpolation strategy should be used.
     ///
@@ -14,10 +14,16 @@ pub enum InterpolationVariant {
 
     /// Use the cubic spline interpolation (recommended since it's the smoothest).
     CubicSpline,
+
+    /// Use the monotone cubic interpolation.
+    ///
+    /// Almost as smooth as [InterpolationVariant::CubicSpline] but it never over- or undershoots
+    /// the supporting bars (so it can't produce negative bar values).
+    MonotoneCubic,
 }
 
 /// Set the distribution of the bars.
-#[derive(Debug, Clone, Copy, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum BarDistribution {
     /// Tell the [`Barprocessor`] to distribute the bars so that the frequency spectrum
     /// looks like as if it would grow linear or in other words:
@@ -30,6 +36,265 @@ pub enum BarDistribution {
     Natural,
 }
 
+/// Decides how the bar values are scaled to stay within the range `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Normalization {
+    /// Slowly decrease the scaling factor if a bar overshoots and increase it otherwise.
+    #[default]
+    Adaptive,
+
+    /// Scale the bars relative to the (gated) loudness of the audio within the last `window`
+    /// (see [EBU R128]).
+    ///
+    /// This produces a more consistent visual amplitude between quiet and loud songs.
+    ///
+    /// [EBU R128]: https://tech.ebu.ch/docs/tech/tech3341.pdf
+    Loudness {
+        /// The time span which should be considered to measure the loudness.
+        window: Duration,
+
+        /// The level which the bars should have for audio with an average loudness.
+        /// Should be within the range `[0, 1]`.
+        target: f32,
+    },
+
+    /// Scale the bars with a fixed gain which never drifts.
+    ///
+    /// Useful if the bars of different songs (or recordings) should be comparable, for example
+    /// for a calibrated display. The gain has the same unit as [crate::BarProcessor::gain],
+    /// so you can lock the current adaptive gain by passing its value.
+    Manual(f32),
+}
+
+impl Normalization {
+    /// [Normalization::Loudness] with a window of three seconds (the "short-term" loudness of EBU R128)
+    /// and a target level of `0.5`.
+    pub const LOUDNESS: Self = Self::Loudness {
+        window: Duration::from_secs(3),
+        target: 0.5,
+    };
+}
+
+/// Decides how much time is assumed to have passed between two calls of [crate::BarProcessor::process_bars].
+///
+/// The smoothing of the bars (how fast they rise and fall) depends on it.
+/// You can always overwrite it for the next call with [crate::BarProcessor::set_delta_time].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FrameTiming {
+    /// Each call is treated as one frame at 60 FPS.
+    /// So the bars are moving faster with higher frame rates.
+    #[default]
+    Fixed,
+
+    /// Measure the time between two calls so that the bars are moving with the same speed at each frame rate.
+    Measured,
+}
+
+/// Decides how the bars fall down while the audio is silent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SilenceDecay {
+    /// The bars fall like they do if the audio gets quieter.
+    #[default]
+    Gravity,
+
+    /// The bars are multiplied with `factor` each 1/60 second, so a smaller factor
+    /// lets them drop faster. Should be within the range `[0, 1)`.
+    Exponential { factor: f32 },
+
+    /// The bars drop linearly and reach zero after the given time.
+    /// Useful for slow fade-outs (or abrupt cutoffs with [Duration::ZERO]).
+    TimeToZero(Duration),
+}
+
+/// Decides how the magnitudes of the fft bins of a supporting bar are combined into one value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BinAggregation {
+    /// Take the highest magnitude. Best for the narrow bass bars where a single tone
+    /// should reach its full height.
+    #[default]
+    Max,
+
+    /// The average of the magnitudes.
+    Mean,
+
+    /// The root mean square of the magnitudes. Produces steadier bars than [BinAggregation::Max]
+    /// for wide (high frequency) ranges while strong tones still stand out more than with [BinAggregation::Mean].
+    Rms,
+
+    /// The square root of the summed energy of the bins, so wide ranges become higher than narrow ones
+    /// with the same level.
+    Sum,
+}
+
+impl BinAggregation {
+    /// Combines the given magnitudes. Returns `0` if there are none.
+    pub(crate) fn apply(self, magnitudes: impl Iterator<Item = f32>) -> f32 {
+        match self {
+            Self::Max => magnitudes.fold(0., f32::max),
+            Self::Mean => {
+                let (sum, amount) = magnitudes.fold((0., 0usize), |(sum, amount), value| {
+                    (sum + value, amount + 1)
+                });
+                sum / amount.max(1) as f32
+            }
+            Self::Rms => {
+                let (sum, amount) = magnitudes.fold((0., 0usize), |(sum, amount), value| {
+                    (sum + value * value, amount + 1)
+                });
+                (sum / amount.max(1) as f32).sqrt()
+            }
+            Self::Sum => magnitudes.map(|value| value * value).sum::<f32>().sqrt(),
+        }
+    }
+}
+
+/// A soft-knee limiter which keeps sudden loud transients (like notification sounds) from slamming the bars to the top.
+///
+/// Each bar has its own gain which drops as soon as the bar would exceed `threshold` and recovers afterwards.
+/// The adaptive normalization still sees the unlimited bars, but only while the transient lasts
+/// instead of the whole time the bars need to fall down again, so the following seconds aren't dimmed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limiter {
+    /// The highest value of the bars. Should be within the range `(0, 1]`.
+    /// Values below [Limiter::MIN_THRESHOLD] (and `NaN`) are raised to it.
+    pub threshold: f32,
+
+    /// The width of the range around `threshold` within which the limiting sets in gradually.
+    /// `0` limits abruptly.
+    pub knee: f32,
+
+    /// How fast the gain of a bar drops if it gets too loud. [Duration::ZERO] limits immediately.
+    pub attack: Duration,
+
+    /// How fast the gain of a bar recovers after the transient.
+    pub release: Duration,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self {
+            threshold: 1.,
+            knee: 0.2,
+            attack: Duration::ZERO,
+            release: Duration::from_millis(250),
+        }
+    }
+}
+
+impl Limiter {
+    /// The lowest value of [Limiter::threshold]. A threshold of zero would silence the bars completely.
+    pub const MIN_THRESHOLD: f32 = 0.01;
+
+    /// Returns the gain which brings `level` below the threshold.
+    pub(crate) fn target_gain(&self, level: f32) -> f32 {
+        let threshold = self.threshold.max(Self::MIN_THRESHOLD);
+        let knee = self.knee.max(0.);
+        let knee_start = threshold - knee / 2.;
+
+        let limited = if level <= knee_start {
+            level
+        } else if level >= threshold + knee / 2. {
+            threshold
+        } else {
+            // a quadratic transition which reaches the threshold with a slope of zero
+            level - (level - knee_start).powi(2) / (2. * knee)
+        };
+
+        if level > 0. {
+            limited / level
+        } else {
+            1.
+        }
+    }
+
+    /// Moves `gain` towards `target` according to the attack or release time.
+    pub(crate) fn follow(&self, gain: f32, target: f32, delta_time: Duration) -> f32 {
+        let time_constant = if target < gain {
+            self.attack
+        } else {
+            self.release
+        };
+
+        let factor = if time_constant.is_zero() {
+            1.
+        } else {
+            1. - (-delta_time.as_secs_f32() / time_constant.as_secs_f32()).exp()
+        };
+
+        gain + factor * (target - gain)
+    }
+}
+
+/// Decides how the (normalized) bar values are mapped before they are returned.
+///
+/// Each scale maps `0` to `0` and `1` to `1`, so the bars stay within the same range.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ValueScale {
+    /// Return the magnitudes as they are.
+    #[default]
+    Linear,
+
+    /// Convert the values into decibels and map the range `[floor, 0]` dB to `[0, 1]`.
+    /// Values below `floor` (for example `-60.`) become `0`.
+    Db { floor: f32 },
+
+    /// The square root of the values which lifts quiet bars a bit.
+    Sqrt,
+
+    /// `log10(1 + 9 * value)` which lifts quiet bars more than [ValueScale::Sqrt].
+    Log,
+}
+
+impl ValueScale {
+    pub(crate) fn apply(self, values: &mut [f32]) {
+        match self {
+            Self::Linear => {}
+            Self::Db { floor } => {
+                let floor = floor.min(-f32::EPSILON);
+                for value in values.iter_mut() {
+                    let db = 20. * value.log10();
+                    // `log10` of zero (or a negative value) is `-inf` (or `NaN`)
+                    *value = if db > floor {
+                        (db - floor) / -floor
+                    } else {
+                        0.
+                    };
+                }
+            }
+            Self::Sqrt => {
+                for value in values.iter_mut() {
+                    *value = value.max(0.).sqrt();
+                }
+            }
+            Self::Log => {
+                for value in values.iter_mut() {
+                    *value = (1. + 9. * value.max(0.)).log10();
+                }
+            }
+        }
+    }
+}
+
+/// The lowest value of [BarProcessorConfig::sensitivity]. Lower values (including zero) are raised to it.
+pub const MIN_SENSITIVITY: f32 = 0.01;
+
+/// The frequency ranges of [BarProcessorConfig::energy_bands] by default: The bass, the mids and the highs.
+pub const DEFAULT_ENERGY_BANDS: [Range<NonZero<u16>>; 3] = [
+    NonZero::new(20).unwrap()..NonZero::new(250).unwrap(),
+    NonZero::new(250).unwrap()..NonZero::new(4_000).unwrap(),
+    NonZero::new(4_000).unwrap()..NonZero::new(20_000).unwrap(),
+];
+
+/// A frequency range with its own amount of bars (see [BarProcessorConfig::bands]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrequencyBand {
+    /// The frequency range of the band.
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// The amount of bars which the frequency range is split into.
+    pub amount_bars: NonZero<u16>,
+}
+
 /// The config options for [crate::BarProcessor].
 #[derive(Debug, Clone)]
 pub struct BarProcessorConfig {
@@ -43,12 +308,47 @@ pub struct BarProcessorConfig {
     pub interpolation: InterpolationVariant,
 
     /// Control how fast the bars should adjust to their new height.
-    /// Should be within the range `[0, 1]`.
+    /// Should be within the range `[0, 1]`. Values below [MIN_SENSITIVITY] are raised to it.
     pub sensitivity: f32,
 
     /// Set the bar distribution.
     /// In general you needn't use another value than its default.
     pub bar_distribution: BarDistribution,
+
+    /// Set how the bar values should be scaled.
+    pub normalization: Normalization,
+
+    /// Set how the time between two frames should be determined.
+    pub frame_timing: FrameTiming,
+
+    /// Set how the bars should fall down while the audio is silent.
+    pub silence_decay: SilenceDecay,
+
+    /// Blends the bar values between the magnitude (`0`) and the spectral flux (`1`) of their frequencies.
+    ///
+    /// The spectral flux is the increase of a frequency compared to the previous frame, so higher values
+    /// make percussive hits pop while steady tones fade out. Should be within the range `[0, 1]`.
+    pub transient_emphasis: f32,
+
+    /// Split the bars into multiple frequency ranges with their own amount of bars,
+    /// for example to spend more bars on the bass.
+    ///
+    /// The bars of the bands are concatenated in the given order. If it's not empty,
+    /// [BarProcessorConfig::freq_range] is ignored and [BarProcessorConfig::amount_bars] is set to the
+    /// sum of the bars of the bands. Each band normalizes its bars on its own.
+    pub bands: Vec<FrequencyBand>,
+
+    /// Set how the bar values should be mapped before they are returned (for example into decibels).
+    pub value_scale: ValueScale,
+
+    /// Set how the fft bins of a bar are combined into its value.
+    pub bin_aggregation: BinAggregation,
+
+    /// The frequency ranges whose energies are returned by [crate::BarProcessor::process_frame].
+    pub energy_bands: Vec<Range<NonZero<u16>>>,
+
+    /// Limit sudden loud transients, see [Limiter]. `None` disables it.
+    pub limiter: Option<Limiter>,
 }
 
 impl Default for BarProcessorConfig {
@@ -59,6 +359,35 @@ impl Default for BarProcessorConfig {
             freq_range: NonZero::new(50).unwrap()..NonZero::new(10_000).unwrap(),
             sensitivity: 0.77,
             bar_distribution: BarDistribution::Uniform,
+            normalization: Normalization::Adaptive,
+            frame_timing: FrameTiming::Fixed,
+            silence_decay: SilenceDecay::Gravity,
+            transient_emphasis: 0.,
+            bands: Vec::new(),
+            value_scale: ValueScale::Linear,
+            bin_aggregation: BinAggregation::Max,
+            energy_bands: DEFAULT_ENERGY_BANDS.to_vec(),
+            limiter: None,
         }
     }
 }
+
+impl BarProcessorConfig {
+    /// Returns the config of each band with its frequency range and amount of bars
+    /// or the config itself if there are no bands.
+    pub(crate) fn band_configs(&self) -> Vec<Self> {
+        if self.bands.is_empty() {
+            return vec![self.clone()];
+        }
+
+        self.bands
+            .iter()
+            .map(|band| Self {
+                freq_range: band.freq_range.clone(),
+                amount_bars: band.amount_bars,
+                bands: Vec::new(),
+                ..self.clone()
+            })
+            .collect()
+    }
+}
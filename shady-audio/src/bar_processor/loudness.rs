@@ -0,0 +1,331 @@
+//! Loudness measurement based on [EBU R128] to normalize the bar values.
+//!
+//! [EBU R128]: https://tech.ebu.ch/docs/tech/tech3341.pdf
+use std::{collections::VecDeque, f64::consts::PI, time::Duration};
+
+use cpal::SampleRate;
+
+use crate::SampleProcessor;
+
+/// Blocks which are quieter than this are ignored (absolute gate).
+const ABSOLUTE_GATE_LUFS: f32 = -70.;
+/// Blocks which are this much quieter than the (absolute gated) loudness are ignored (relative gate).
+const RELATIVE_GATE_LU: f32 = -10.;
+
+/// A new block starts after each step.
+const STEP: Duration = Duration::from_millis(100);
+/// The amount of steps of a block (400 ms), so the blocks overlap by 75%.
+const STEPS_PER_BLOCK: usize = 4;
+
+pub struct LoudnessMeter {
+    // the K-weighting filters (pre-filter and RLB filter) of each channel
+    filters: Box<[[Biquad; 2]]>,
+
+    // the amount of audio frames of a step
+    step_len: u64,
+    // the amount of audio frames of the current step which have passed so far
+    step_position: u64,
+    // the sum of the squared (weighted) samples of all channels within the current step
+    step_energy: f64,
+    // the amount of frames of the current step which have been seen
+    step_frames: u64,
+    // the mean square of the last steps
+    steps: VecDeque<f32>,
+
+    // the mean square of each block within the window
+    blocks: VecDeque<f32>,
+    max_blocks: usize,
+
+    last_batch: Option<u64>,
+    integrated_power: Option<f32>,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: SampleRate, amount_channels: usize, window: Duration) -> Self {
+        let filters = vec![k_weighting_filters(f64::from(sample_rate.0)); amount_channels];
+        let step_len = (u64::from(sample_rate.0) * STEP.as_millis() as u64 / 1000).max(1);
+        let max_blocks = (window.as_millis() / STEP.as_millis()).max(1) as usize;
+
+        Self {
+            filters: filters.into_boxed_slice(),
+            step_len,
+            step_position: 0,
+            step_energy: 0.,
+            step_frames: 0,
+            steps: VecDeque::with_capacity(STEPS_PER_BLOCK + 1),
+            blocks: VecDeque::with_capacity(max_blocks + 1),
+            max_blocks,
+            last_batch: None,
+            integrated_power: None,
+        }
+    }
+
+    /// Adds the new samples of the processor and returns the gated mean square of each channel within the window.
+    /// Returns `None` if everything has been silent within the window (or if not even one block has been filled).
+    ///
+    /// The window is timed by the amount of processed samples, so the result doesn't depend on how often it's called.
+    pub fn measure(&mut self, processor: &SampleProcessor) -> Option<f32> {
+        let batch = processor.processed_batches();
+        if self.last_batch.replace(batch) != Some(batch) {
+            let (samples, amount_new_samples) = processor.new_samples();
+            self.add_samples(samples, amount_new_samples);
+        }
+
+        self.integrated_power
+            .map(|power| power / self.filters.len().max(1) as f32)
+    }
+
+    /// Returns the integrated loudness of the window in LUFS.
+    pub fn loudness(&self) -> Option<f32> {
+        self.integrated_power.map(lufs)
+    }
+
+    /// Adds the given (interleaved) samples. `amount_new_samples` can be larger than the amount of given samples
+    /// if some new samples didn't fit into the buffer of the fetcher. They are older, so only their time passes.
+    fn add_samples(&mut self, samples: &[f32], amount_new_samples: usize) {
+        let amount_channels = self.filters.len().max(1);
+        let missed_frames = amount_new_samples.saturating_sub(samples.len()) / amount_channels;
+        self.skip_frames(missed_frames as u64);
+
+        for frame in samples.chunks_exact(amount_channels) {
+            for (&sample, [pre_filter, rlb_filter]) in frame.iter().zip(self.filters.iter_mut()) {
+                let weighted = rlb_filter.process(pre_filter.process(f64::from(sample)));
+                self.step_energy += weighted * weighted;
+            }
+            self.step_frames += 1;
+
+            self.step_position += 1;
+            if self.step_position == self.step_len {
+                self.finish_step();
+            }
+        }
+    }
+
+    /// Lets the time of the given amount of frames pass without any samples.
+    fn skip_frames(&mut self, mut frames: u64) {
+        while frames > 0 {
+            let skipped = frames.min(self.step_len - self.step_position);
+            self.step_position += skipped;
+            frames -= skipped;
+
+            if self.step_position == self.step_len {
+                self.finish_step();
+            }
+        }
+    }
+
+    fn finish_step(&mut self) {
+        // steps without any samples (all of them have been missed) can't be measured
+        if self.step_frames > 0 {
+            self.steps
+                .push_back((self.step_energy / self.step_frames as f64) as f32);
+            if self.steps.len() > STEPS_PER_BLOCK {
+                self.steps.pop_front();
+            }
+
+            if self.steps.len() == STEPS_PER_BLOCK {
+                self.blocks
+                    .push_back(self.steps.iter().sum::<f32>() / STEPS_PER_BLOCK as f32);
+                if self.blocks.len() > self.max_blocks {
+                    self.blocks.pop_front();
+                }
+
+                self.integrated_power = self.gated_power();
+            }
+        }
+
+        self.step_position = 0;
+        self.step_energy = 0.;
+        self.step_frames = 0;
+    }
+
+    fn gated_power(&self) -> Option<f32> {
+        let absolute_gated = mean(
+            self.blocks
+                .iter()
+                .copied()
+                .filter(|power| lufs(*power) > ABSOLUTE_GATE_LUFS),
+        )?;
+
+        let relative_gate = lufs(absolute_gated) + RELATIVE_GATE_LU;
+        mean(
+            self.blocks
+                .iter()
+                .copied()
+                .filter(|power| lufs(*power) > relative_gate),
+        )
+    }
+}
+
+fn lufs(power: f32) -> f32 {
+    -0.691 + 10. * power.log10()
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, amount) = values.fold((0., 0usize), |(sum, amount), value| {
+        (sum + value, amount + 1)
+    });
+
+    (amount > 0).then(|| sum / amount as f32)
+}
+
+/// A biquad filter (transposed direct form II).
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    // `a[0]` is normalized to `1`
+    a: [f64; 3],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b,
+            a,
+            state: [0.; 2],
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.state[0];
+        self.state[0] = self.b[1] * input - self.a[1] * output + self.state[1];
+        self.state[1] = self.b[2] * input - self.a[2] * output;
+        output
+    }
+
+    /// Returns `|H(e^{jw})|^2` at the given frequency.
+    #[cfg(test)]
+    fn response(&self, freq: f64, sample_rate: f64) -> f64 {
+        let w = 2. * PI * freq / sample_rate;
+
+        let eval = |c: [f64; 3]| {
+            let re = c[0] + c[1] * w.cos() + c[2] * (2. * w).cos();
+            let im = -c[1] * w.sin() - c[2] * (2. * w).sin();
+            re * re + im * im
+        };
+
+        eval(self.b) / eval(self.a)
+    }
+}
+
+/// Returns the K-weighting filter: The pre-filter (high shelf) and the RLB filter (high pass).
+///
+/// The coefficients are computed for the given sample rate like it's done in [libebur128].
+///
+/// [libebur128]: https://github.com/jiixyj/libebur128
+fn k_weighting_filters(sample_rate: f64) -> [Biquad; 2] {
+    // stage 1: high shelf
+    let shelf = {
+        let f0 = 1681.974450955533;
+        let gain = 3.999843853973347;
+        let q = 0.7071752369554196;
+
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain / 20.);
+        let vb = vh.powf(0.4996667741545416);
+
+        let a0 = 1. + k / q + k * k;
+        let b = [
+            (vh + vb * k / q + k * k) / a0,
+            2. * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ];
+        let a = [1., 2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0];
+
+        Biquad::new(b, a)
+    };
+
+    // stage 2: high pass
+    let high_pass = {
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1. + k / q + k * k;
+
+        let b = [1., -2., 1.];
+        let a = [1., 2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0];
+
+        Biquad::new(b, a)
+    };
+
+    [shelf, high_pass]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|idx| {
+                amplitude * (std::f32::consts::TAU * freq * idx as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn k_weighting_shape() {
+        let sample_rate = 48_000.;
+
+        // roughly 0 dB at 1 kHz, the high pass kills the very low frequencies
+        // and the high shelf boosts the high frequencies by ~4 dB.
+        let [shelf, high_pass] = k_weighting_filters(sample_rate);
+        let db = |freq| {
+            10. * (shelf.response(freq, sample_rate) * high_pass.response(freq, sample_rate))
+                .log10()
+        };
+
+        assert!(db(1_000.).abs() < 1.);
+        assert!(db(10.) < -10.);
+        assert!((db(10_000.) - 4.).abs() < 0.5);
+    }
+
+    #[test]
+    fn gating_ignores_silence() {
+        let mut meter = LoudnessMeter::new(SampleRate(48_000), 1, Duration::from_secs(1));
+
+        meter.blocks.extend([0.1, 0., 0.1]);
+
+        assert_eq!(meter.gated_power(), Some(0.1));
+    }
+
+    #[test]
+    fn full_scale_sine() {
+        let mut meter = LoudnessMeter::new(SampleRate(48_000), 1, Duration::from_secs(3));
+        let samples = sine(1_000., 1., 48_000, 48_000);
+
+        meter.add_samples(&samples, samples.len());
+
+        // a full scale sine of 1 kHz has a loudness of ~-3 LUFS
+        let loudness = meter.loudness().unwrap();
+        assert!((loudness + 3.).abs() < 0.5, "{}", loudness);
+    }
+
+    #[test]
+    fn window_is_timed_by_the_samples() {
+        let samples = sine(1_000., 0.5, 48_000, 10 * 48_000);
+
+        let mut at_once = LoudnessMeter::new(SampleRate(48_000), 1, Duration::from_secs(3));
+        at_once.add_samples(&samples, samples.len());
+
+        // the amount of calls doesn't matter
+        let mut in_chunks = LoudnessMeter::new(SampleRate(48_000), 1, Duration::from_secs(3));
+        for chunk in samples.chunks(777) {
+            in_chunks.add_samples(chunk, chunk.len());
+        }
+
+        assert_eq!(at_once.blocks.len(), 30);
+        assert_eq!(at_once.blocks, in_chunks.blocks);
+        assert_eq!(at_once.loudness(), in_chunks.loudness());
+
+        // missed samples let the time pass
+        let mut missing = LoudnessMeter::new(SampleRate(48_000), 1, Duration::from_secs(3));
+        for chunk in samples.chunks(2 * 777) {
+            missing.add_samples(&chunk[..chunk.len() / 2], chunk.len());
+        }
+        assert_eq!(missing.blocks.len(), 30);
+        assert!((missing.loudness().unwrap() - at_once.loudness().unwrap()).abs() < 0.5);
+    }
+}
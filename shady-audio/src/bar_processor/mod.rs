@@ -1,35 +1,72 @@
 mod config;
+mod loudness;
+mod post_processor;
+mod preset;
+mod transient;
 
-use std::{num::NonZero, ops::Range};
+use std::{
+    num::NonZero,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
-use config::BarDistribution;
-pub use config::{BarProcessorConfig, InterpolationVariant};
+pub use config::{
+    BarDistribution, BarProcessorConfig, BinAggregation, FrameTiming, FrequencyBand,
+    InterpolationVariant, Limiter, Normalization, SilenceDecay, ValueScale, DEFAULT_ENERGY_BANDS,
+    MIN_SENSITIVITY,
+};
 use cpal::SampleRate;
+use loudness::LoudnessMeter;
+pub use post_processor::PostProcessor;
+pub use preset::Preset;
 use realfft::num_complex::Complex32;
 use tracing::debug;
+use transient::SpectralFlux;
 
 use crate::{
     interpolation::{
         CubicSplineInterpolation, Interpolater, InterpolationInner, LinearInterpolation,
-        NothingInterpolation, SupportingPoint,
+        MonotoneCubicInterpolation, NothingInterpolation, SupportingPoint,
     },
-    SampleProcessor, MAX_HUMAN_FREQUENCY, MIN_HUMAN_FREQUENCY,
+    SampleProcessor, SplCalibration, MAX_HUMAN_FREQUENCY, MIN_HUMAN_FREQUENCY,
 };
 
-type ChannelInterpolator = InterpolatorCtx;
+// one interpolator for each band
+type ChannelInterpolator = Box<[InterpolatorCtx]>;
 type ChannelBars = Box<[f32]>;
 
+// how much of the previous value of a bar is kept
+const MEMORY_FACTOR: f32 = 0.77;
+
+// the smoothing factors are tuned for this frame time (60 FPS)
+const REFERENCE_FRAME_TIME: f32 = 1. / 60.;
+// prevent the bars from jumping around after a long pause
+const MAX_TIME_SCALE: f32 = 10.;
+// the difference between the loudness (LUFS) and the level (dBFS) of a sine
+const SINE_LOUDNESS_OFFSET: f32 = 3.01;
+
 struct InterpolatorCtx {
     interpolator: Box<dyn Interpolater>,
     supporting_point_fft_ranges: Box<[Range<usize>]>,
 
     normalize_factor: f32,
     sensitivity: f32,
+    bin_aggregation: BinAggregation,
+    silence_decay: SilenceDecay,
+    transient_emphasis: f32,
+    // only needed if there's a transient emphasis
+    spectral_flux: Option<SpectralFlux>,
+    limiter: Option<Limiter>,
+    // the gain of the limiter of each bar
+    limiter_gains: Box<[f32]>,
 
     prev: Box<[f32]>,
     peak: Box<[f32]>,
     fall: Box<[f32]>,
     mem: Box<[f32]>,
+    // the values of the supporting points when the silence started
+    // (only needed for `SilenceDecay::TimeToZero`)
+    silence_start: Option<Box<[f32]>>,
 }
 
 impl InterpolatorCtx {
@@ -41,17 +78,49 @@ impl InterpolatorCtx {
         let fall = peak.clone();
         let mem = peak.clone();
         let prev = peak.clone();
+        let limiter_gains = vec![1f32; peak.len()].into_boxed_slice();
 
         Self {
             interpolator,
             supporting_point_fft_ranges,
-            normalize_factor: 1.,
-            sensitivity: config.sensitivity,
+            normalize_factor: match config.normalization {
+                Normalization::Manual(gain) => gain,
+                _ => 1.,
+            },
+            sensitivity: config.sensitivity.max(MIN_SENSITIVITY),
+            bin_aggregation: config.bin_aggregation,
+            silence_decay: config.silence_decay,
+            transient_emphasis: config.transient_emphasis.clamp(0., 1.),
+            spectral_flux: (config.transient_emphasis > 0.).then(|| SpectralFlux::new(0)),
+            limiter: config.limiter,
+            limiter_gains,
 
             prev,
             peak,
             fall,
             mem,
+            silence_start: None,
+        }
+    }
+
+    /// Distributes the fft output to the bars according to the (new) frequency range,
+    /// interpolation and bar distribution of `config`.
+    /// The smoothing state of the bars is kept, so they don't snap to the new values.
+    fn reconfigure(
+        &mut self,
+        config: &BarProcessorConfig,
+        sample_rate: SampleRate,
+        fft_size: usize,
+    ) {
+        let (interpolator, supporting_point_fft_ranges) =
+            Self::new_interpolation_data(config, sample_rate, fft_size);
+
+        self.interpolator = interpolator;
+        self.supporting_point_fft_ranges = supporting_point_fft_ranges;
+        self.silence_start = None;
+
+        for (supporting_point, mem) in self.interpolator.supporting_points_mut().zip(&self.mem) {
+            supporting_point.y = *mem;
         }
     }
 
@@ -96,7 +165,10 @@ impl InterpolatorCtx {
                 let is_supporting_point =
                     new_fft_range != prev_fft_range && !new_fft_range.is_empty();
                 if is_supporting_point {
-                    supporting_points.push(SupportingPoint { x: bar_idx, y: 0. });
+                    supporting_points.push(SupportingPoint {
+                        x: bar_idx as f32,
+                        y: 0.,
+                    });
 
                     supporting_point_fft_ranges.push(new_fft_range.clone());
                 }
@@ -114,7 +186,7 @@ impl InterpolatorCtx {
                         .iter_mut()
                         .enumerate()
                     {
-                        supporting_point.x = (idx as f32 * step) as usize;
+                        supporting_point.x = (idx as f32 * step).floor();
                     }
                 }
                 BarDistribution::Natural => {}
@@ -128,14 +200,51 @@ impl InterpolatorCtx {
             InterpolationVariant::None => NothingInterpolation::boxed(supporting_points),
             InterpolationVariant::Linear => LinearInterpolation::boxed(supporting_points),
             InterpolationVariant::CubicSpline => CubicSplineInterpolation::boxed(supporting_points),
+            InterpolationVariant::MonotoneCubic => {
+                MonotoneCubicInterpolation::boxed(supporting_points)
+            }
         };
 
         (interpolator, supporting_point_fft_ranges.into_boxed_slice())
     }
 
-    fn update_supporting_points(&mut self, fft_out: &[Complex32]) {
+    /// Updates the supporting points with the new fft output.
+    ///
+    /// `fixed_gain` overrides the adaptive normalization factor if set.
+    /// `time_scale` is the amount of reference frames which have passed since the last update.
+    fn update_supporting_points(
+        &mut self,
+        fft_out: &[Complex32],
+        fixed_gain: Option<f32>,
+        time_scale: f32,
+    ) {
+        let is_silent = self
+            .supporting_point_fft_ranges
+            .iter()
+            .flat_map(|range| &fft_out[range.clone()])
+            .all(|out| out.norm_sqr() == 0.);
+
+        if !is_silent {
+            self.silence_start = None;
+        } else if self.silence_decay != SilenceDecay::Gravity {
+            self.decay(time_scale);
+            return;
+        }
+
+        if let Some(spectral_flux) = &mut self.spectral_flux {
+            spectral_flux.update(fft_out);
+        }
+
+        if let Some(gain) = fixed_gain {
+            self.normalize_factor = gain;
+        }
+        let normalize_factor = self.normalize_factor;
+        let memory_factor = MEMORY_FACTOR.powf(time_scale);
+        // keeps the value of a constant signal the same, regardless of the time scale
+        let memory_gain = (1. - memory_factor) / (1. - MEMORY_FACTOR);
+
         let mut overshoot = false;
-        let mut is_silent = true;
+        let delta_time = Duration::from_secs_f32(REFERENCE_FRAME_TIME * time_scale);
 
         let amount_bars = self.amount_bars();
 
@@ -148,25 +257,32 @@ impl InterpolatorCtx {
             let x = supporting_point.x;
             let prev_magnitude = supporting_point.y;
             let mut next_magnitude = {
-                let mut raw_bar_val = fft_out[fft_range.clone()]
-                    .iter()
-                    .map(|out| {
-                        let mag = out.norm_sqr();
-                        if mag > 0. {
-                            is_silent = false;
-                        }
-                        mag
-                    })
-                    .max_by(|a, b| a.total_cmp(b))
-                    .unwrap();
-
-                raw_bar_val = raw_bar_val.sqrt();
-
-                raw_bar_val
-                    * self.normalize_factor
-                    * 10f32.powf((x as f32 / amount_bars as f32) - 1.)
+                let mut raw_bar_val = self
+                    .bin_aggregation
+                    .apply(fft_out[fft_range.clone()].iter().map(|out| out.norm()));
+
+                if let Some(spectral_flux) = &self.spectral_flux {
+                    let flux = self
+                        .bin_aggregation
+                        .apply(spectral_flux.flux()[fft_range.clone()].iter().copied());
+
+                    raw_bar_val += self.transient_emphasis * (flux - raw_bar_val);
+                }
+
+                raw_bar_val * normalize_factor * 10f32.powf((x / amount_bars as f32) - 1.)
             };
 
+            if let Some(limiter) = &self.limiter {
+                // the value which the bar reaches if the magnitude stays the same
+                let level = next_magnitude / (1. - MEMORY_FACTOR);
+                let gain = &mut self.limiter_gains[bar_idx];
+                *gain = limiter.follow(*gain, limiter.target_gain(level), delta_time);
+
+                next_magnitude *= *gain;
+                // the normalization only sees the overshoot of the transient itself
+                overshoot |= level > 1.;
+            }
+
             debug_assert!(!prev_magnitude.is_nan());
             debug_assert!(!next_magnitude.is_nan());
 
@@ -179,63 +295,243 @@ impl InterpolatorCtx {
                 if next_magnitude < 0. {
                     next_magnitude = 0.;
                 }
-                self.fall[bar_idx] += 0.028;
+                self.fall[bar_idx] += 0.028 * time_scale;
             } else {
                 self.peak[bar_idx] = next_magnitude;
                 self.fall[bar_idx] = 0.0;
             }
             self.prev[bar_idx] = next_magnitude;
 
-            supporting_point.y = self.mem[bar_idx] * 0.77 + next_magnitude;
+            supporting_point.y = self.mem[bar_idx] * memory_factor + next_magnitude * memory_gain;
             self.mem[bar_idx] = supporting_point.y;
 
-            if supporting_point.y > 1. {
+            if supporting_point.y > 1. && self.limiter.is_none() {
                 overshoot = true;
             }
         }
 
+        #[cfg(feature = "metrics")]
         if overshoot {
-            self.normalize_factor *= 0.98;
+            crate::metrics::record_overshoot();
+        }
+
+        if fixed_gain.is_some() {
+            // the gain isn't adaptive
+        } else if overshoot {
+            self.normalize_factor *= 0.98f32.powf(time_scale);
         } else if !is_silent {
-            self.normalize_factor *= 1.002;
+            self.normalize_factor *= 1.002f32.powf(time_scale);
+        }
+    }
+
+    /// Lets the supporting points fall down according to [SilenceDecay].
+    fn decay(&mut self, time_scale: f32) {
+        let silence_start = self.silence_start.get_or_insert_with(|| {
+            self.interpolator
+                .supporting_points()
+                .iter()
+                .map(|point| point.y)
+                .collect()
+        });
+
+        for (bar_idx, (supporting_point, start)) in self
+            .interpolator
+            .supporting_points_mut()
+            .zip(silence_start.iter())
+            .enumerate()
+        {
+            let value = match self.silence_decay {
+                SilenceDecay::Gravity => unreachable!("gravity is handled by the regular update"),
+                SilenceDecay::Exponential { factor } => {
+                    supporting_point.y * factor.clamp(0., 1.).powf(time_scale)
+                }
+                SilenceDecay::TimeToZero(duration) => {
+                    let frames = duration.as_secs_f32() / REFERENCE_FRAME_TIME;
+                    if frames > 0. {
+                        supporting_point.y - start * time_scale / frames
+                    } else {
+                        0.
+                    }
+                }
+            }
+            .max(0.);
+
+            supporting_point.y = value;
+
+            // continue smoothly once the audio starts again
+            self.prev[bar_idx] = value;
+            self.peak[bar_idx] = value;
+            self.fall[bar_idx] = 0.;
+            self.mem[bar_idx] = value;
         }
     }
 
     fn amount_bars(&self) -> usize {
         self.prev.len()
     }
+
+    /// Returns the amount of octaves which each bar covers.
+    ///
+    /// The bars between the supporting points get a share of the frequencies between them.
+    fn bar_octaves(&self) -> impl Iterator<Item = f32> + '_ {
+        // (bar position, fft bin) pairs where the frequencies of the supporting points start
+        let mut knots: Vec<(f32, f32)> =
+            Vec::with_capacity(self.supporting_point_fft_ranges.len() + 1);
+        for (point, range) in self
+            .interpolator
+            .supporting_points()
+            .iter()
+            .zip(self.supporting_point_fft_ranges.iter())
+        {
+            if knots.last().is_none_or(|&(x, _)| x < point.x) {
+                knots.push((point.x, range.start as f32));
+            }
+        }
+        if let Some(last) = self.supporting_point_fft_ranges.last() {
+            let amount_bars = self.amount_bars() as f32;
+            if knots.last().is_none_or(|&(x, _)| x < amount_bars) {
+                knots.push((amount_bars, last.end as f32));
+            }
+        }
+
+        let bin_at = move |pos: f32| -> f32 {
+            let bin = match knots.iter().position(|&(x, _)| x >= pos) {
+                Some(0) => knots[0].1,
+                Some(idx) => {
+                    let (x0, bin0) = knots[idx - 1];
+                    let (x1, bin1) = knots[idx];
+                    bin0 + (bin1 - bin0) * (pos - x0) / (x1 - x0)
+                }
+                None => knots.last().map_or(1., |&(_, bin)| bin),
+            };
+
+            // the first bin is the DC offset
+            bin.max(1.)
+        };
+
+        (0..self.amount_bars())
+            .map(move |bar_idx| (bin_at(bar_idx as f32 + 1.) / bin_at(bar_idx as f32)).log2())
+    }
+
+    /// Reverts the normalization, the tilt and the smoothing of the bar value `value` at the position `x`
+    /// and returns the level of its frequencies in dBFS.
+    ///
+    /// `full_scale` is the magnitude of a full scale sine (see [SampleProcessor::full_scale_magnitude]).
+    fn dbfs(&self, x: f32, value: f32, full_scale: f32) -> f32 {
+        let tilt = 10f32.powf((x / self.amount_bars() as f32) - 1.);
+        // a constant signal accumulates to `1 / (1 - MEMORY_FACTOR)` times its magnitude
+        let magnitude = value.max(0.) * (1. - MEMORY_FACTOR) / (self.normalize_factor * tilt);
+
+        20. * (magnitude / full_scale).log10()
+    }
+}
+
+/// The bars and the band energies of the same fft frame, see [BarProcessor::process_frame].
+#[derive(Debug, Clone, Copy)]
+pub struct FrameAnalysis<'a> {
+    /// The bar values of each channel, like the ones of [BarProcessor::process_bars].
+    pub bars: &'a [Box<[f32]>],
+
+    /// The energy of each band of [BarProcessorConfig::energy_bands] for each channel.
+    ///
+    /// The energies are the raw power of the frequencies (neither smoothed nor normalized),
+    /// scaled so that a full scale sine within the band has an energy of about `1`.
+    pub band_energies: &'a [Box<[f32]>],
+}
+
+/// Errors which can occur if a [BarProcessorConfig] is invalid.
+#[derive(thiserror::Error, Debug)]
+pub enum BarProcessorConfigError {
+    #[error("The bands have {0} bars in total but at most {max} are supported.", max = u16::MAX)]
+    TooManyBars(usize),
 }
 
 /// The struct which computates the bar values of the samples of the fetcher.
 pub struct BarProcessor {
     bar_values: Box<[Box<[f32]>]>,
-    channels: Box<[InterpolatorCtx]>,
+    // the energies of `config.energy_bands` of each channel (only computed by `process_frame`)
+    band_energies: Box<[Box<[f32]>]>,
+    channels: Box<[ChannelInterpolator]>,
+    loudness_meter: Option<LoudnessMeter>,
+    post_fn: Option<Box<dyn PostProcessor>>,
+
+    last_update: Option<Instant>,
+    next_delta_time: Option<Duration>,
+
+    spl_calibration: Option<SplCalibration>,
+    // the bar values in dB SPL (only computed if there's a calibration)
+    bar_spl: Option<Box<[ChannelBars]>>,
 
     config: BarProcessorConfig,
     sample_rate: SampleRate,
     sample_len: usize,
+    // the amount of samples of the fft whose bins are distributed to the bars
+    // (differs from `sample_len` if the sample processor has a low frequency fft)
+    spectrum_len: usize,
 }
 
 impl BarProcessor {
     /// Creates a new instance.
     ///
     /// See the examples of this crate to see it's usage.
+    ///
+    /// # Panics
+    /// If the config is invalid (see [BarProcessor::try_new]).
     pub fn new(processor: &SampleProcessor, config: BarProcessorConfig) -> Self {
+        Self::try_new(processor, config).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new instance or returns an error if the config is invalid,
+    /// for example if [BarProcessorConfig::bands] have more than [u16::MAX] bars in total.
+    pub fn try_new(
+        processor: &SampleProcessor,
+        mut config: BarProcessorConfig,
+    ) -> Result<Self, BarProcessorConfigError> {
+        if !config.bands.is_empty() {
+            let amount_bars = config
+                .bands
+                .iter()
+                .try_fold(0u16, |sum, band| sum.checked_add(band.amount_bars.get()))
+                .ok_or_else(|| {
+                    BarProcessorConfigError::TooManyBars(
+                        config
+                            .bands
+                            .iter()
+                            .map(|band| usize::from(band.amount_bars.get()))
+                            .sum(),
+                    )
+                })?;
+
+            config.amount_bars = NonZero::new(amount_bars).unwrap();
+        }
+
         let sample_rate = processor.sample_rate();
         let sample_len = processor.fft_size();
+        let spectrum_len = processor.spectrum_size();
         let amount_channels = processor.amount_channels();
 
         let (channels, bar_values) =
-            Self::get_channels_and_bar_values(&config, amount_channels, sample_rate, sample_len);
+            Self::get_channels_and_bar_values(&config, amount_channels, sample_rate, spectrum_len);
+        let loudness_meter = Self::get_loudness_meter(&config, sample_rate, amount_channels);
 
-        Self {
+        Ok(Self {
             config,
             channels,
             bar_values,
+            band_energies: Box::new([]),
+            loudness_meter,
+            post_fn: None,
+
+            last_update: None,
+            next_delta_time: None,
+
+            spl_calibration: None,
+            bar_spl: None,
 
             sample_rate,
             sample_len,
-        }
+            spectrum_len,
+        })
     }
 
     /// Returns the bar values for each channel.
@@ -243,26 +539,278 @@ impl BarProcessor {
     /// If you access the returned value like this: `bar_processor.process_bars(&processor)[i][j]` then this would mean:
     /// You are accessing the `j`th bar value of the `i`th audio channel.
     pub fn process_bars(&mut self, processor: &SampleProcessor) -> &[Box<[f32]>] {
-        for ((channel_idx, channel), fft_ctx) in self
+        let mut bar_values = std::mem::take(&mut self.bar_values);
+        self.process_channels(processor, bar_values.iter_mut().map(|bars| &mut bars[..]));
+        self.bar_values = bar_values;
+
+        &self.bar_values
+    }
+
+    /// Computes the bar values like [BarProcessor::process_bars] and the energies of the frequency bands of
+    /// [BarProcessorConfig::energy_bands] from the same fft output, so both refer to the same moment.
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::{DummyFetcher, Waveform}};
+    ///
+    /// let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+    ///     1,
+    ///     Waveform::Sine { frequency: 100. },
+    /// ));
+    /// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    ///
+    /// sample_processor.process_next_samples();
+    /// let frame = bar_processor.process_frame(&sample_processor);
+    ///
+    /// // the bass band has the most energy
+    /// let energies = &frame.band_energies[0];
+    /// assert!(energies[0] > energies[1] && energies[0] > energies[2]);
+    /// ```
+    pub fn process_frame(&mut self, processor: &SampleProcessor) -> FrameAnalysis<'_> {
+        self.sync_spectrum_len(processor);
+        self.update_band_energies(processor);
+        self.process_bars(processor);
+
+        FrameAnalysis {
+            bars: &self.bar_values,
+            band_energies: &self.band_energies,
+        }
+    }
+
+    /// Computes the bar values like [BarProcessor::process_bars] but writes them into `out` instead.
+    ///
+    /// The bars of each channel are stored one after another in `out`, so the `j`th bar value of the `i`th
+    /// audio channel is stored in `out[i * amount_bars + j]`.
+    ///
+    /// **Panics** if the length of `out` isn't `amount_channels * amount_bars`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher};
+    ///
+    /// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+    /// let mut bar_processor = BarProcessor::new(
+    ///     &sample_processor,
+    ///     BarProcessorConfig {
+    ///         amount_bars: std::num::NonZero::new(10).unwrap(),
+    ///         ..Default::default()
+    ///     }
+    /// );
+    /// sample_processor.process_next_samples();
+    ///
+    /// // two channels with ten bars each
+    /// let mut bars = [0f32; 2 * 10];
+    /// bar_processor.process_bars_into(&sample_processor, &mut bars);
+    /// ```
+    pub fn process_bars_into(&mut self, processor: &SampleProcessor, out: &mut [f32]) {
+        let amount_bars = usize::from(self.config.amount_bars.get());
+        assert_eq!(
+            out.len(),
+            self.channels.len() * amount_bars,
+            "The output buffer must be able to store all bars of all channels."
+        );
+
+        // not every interpolation writes into each bar
+        out.fill(0.);
+
+        self.process_channels(processor, out.chunks_exact_mut(amount_bars));
+    }
+
+    /// Computes the bars of each channel and writes them into the given output of the channel.
+    fn process_channels<'a>(
+        &mut self,
+        processor: &SampleProcessor,
+        outputs: impl Iterator<Item = &'a mut [f32]>,
+    ) {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        self.sync_spectrum_len(processor);
+        let fixed_gain = self.fixed_gain(processor);
+        let time_scale = self.time_scale();
+
+        for (channel_idx, ((bands, spectrum), channel_out)) in self
             .channels
             .iter_mut()
+            .zip(processor.spectra())
+            .zip(outputs)
             .enumerate()
-            .zip(processor.fft_out().iter())
         {
-            channel.update_supporting_points(&fft_ctx.fft_out);
+            process_channel(bands, spectrum, fixed_gain, time_scale, channel_out);
 
-            channel
-                .interpolator
-                .interpolate(&mut self.bar_values[channel_idx]);
+            if let (Some(calibration), Some(bar_spl)) = (self.spl_calibration, &mut self.bar_spl) {
+                channel_spl(
+                    bands,
+                    channel_out,
+                    processor.full_scale_magnitude(),
+                    calibration,
+                    &mut bar_spl[channel_idx],
+                );
+            }
+
+            self.config.value_scale.apply(channel_out);
+
+            if let Some(post_fn) = &mut self.post_fn {
+                post_fn.process(channel_out);
+            }
         }
 
-        &self.bar_values
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bar_update(start.elapsed());
+    }
+
+    /// Returns the integrated loudness (in LUFS) of the audio within the window of [Normalization::Loudness].
+    ///
+    /// Returns `None` if another normalization is used or if the audio has been silent within the window.
+    pub fn loudness(&self) -> Option<f32> {
+        self.loudness_meter
+            .as_ref()
+            .and_then(|meter| meter.loudness())
+    }
+
+    /// Returns [BarProcessor::loudness] in dB SPL (see [BarProcessor::set_spl_calibration]).
+    ///
+    /// Returns `None` if there's no calibration or no loudness.
+    pub fn loudness_spl(&self) -> Option<f32> {
+        let calibration = self.spl_calibration?;
+        self.loudness()
+            .map(|loudness| calibration.spl(loudness + SINE_LOUDNESS_OFFSET))
+    }
+
+    /// Computes the level of each bar in (approximate) dB SPL from now on, see [BarProcessor::bar_spl].
+    /// `None` disables it.
+    pub fn set_spl_calibration(&mut self, calibration: Option<SplCalibration>) {
+        self.spl_calibration = calibration;
+        self.reset_bar_spl();
+    }
+
+    pub fn spl_calibration(&self) -> Option<SplCalibration> {
+        self.spl_calibration
+    }
+
+    /// Returns the level of each bar of each channel of the last update in (approximate) dB SPL.
+    ///
+    /// The levels are computed before [BarProcessorConfig::value_scale] and the post-processing function are applied,
+    /// silent bars are `-inf`. Returns `None` if there's no calibration (see [BarProcessor::set_spl_calibration]).
+    pub fn bar_spl(&self) -> Option<&[Box<[f32]>]> {
+        self.bar_spl.as_deref()
+    }
+
+    /// Returns the relative width of each bar (all widths sum up to `1`), which is proportional
+    /// to the amount of octaves its frequencies span.
+    ///
+    /// Drawing the bars with these widths places them on a logarithmic frequency axis,
+    /// so for example the bass bars become wider. The widths are the same for each channel.
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher};
+    ///
+    /// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// let bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    ///
+    /// let widths = bar_processor.bar_widths();
+    /// assert_eq!(widths.len(), usize::from(bar_processor.config().amount_bars.get()));
+    /// assert!((widths.iter().sum::<f32>() - 1.).abs() < 1e-4);
+    /// ```
+    pub fn bar_widths(&self) -> Box<[f32]> {
+        let mut widths: Box<[f32]> = self
+            .channels
+            .first()
+            .into_iter()
+            .flatten()
+            .flat_map(|band| band.bar_octaves())
+            .collect();
+
+        let sum = widths.iter().sum::<f32>();
+        if sum > 0. {
+            widths.iter_mut().for_each(|width| *width /= sum);
+        } else {
+            let width = 1. / widths.len() as f32;
+            widths.fill(width);
+        }
+
+        widths
+    }
+
+    /// Returns the amount of channels which the bar processor computes bars for.
+    pub fn amount_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns the gain which the bars have been scaled with in the last update (averaged over all channels).
+    ///
+    /// With [Normalization::Adaptive], this is the sensitivity which slowly adapts to the audio.
+    pub fn gain(&self) -> f32 {
+        let interpolators = self.channels.iter().flatten();
+        let amount = interpolators.clone().count();
+        let sum = interpolators
+            .map(|interpolator| interpolator.normalize_factor)
+            .sum::<f32>();
+
+        sum / amount as f32
+    }
+
+    /// Sets the gain of all channels.
+    ///
+    /// With [Normalization::Adaptive], the gain continues to adapt from the given value.
+    /// With [Normalization::Manual], the value of the normalization is replaced.
+    /// With [Normalization::Loudness], the gain is overwritten by the next update.
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, Normalization, fetcher::DummyFetcher};
+    ///
+    /// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// let mut bar_processor = BarProcessor::new(
+    ///     &sample_processor,
+    ///     BarProcessorConfig {
+    ///         normalization: Normalization::Manual(0.5),
+    ///         ..Default::default()
+    ///     }
+    /// );
+    /// assert_eq!(bar_processor.gain(), 0.5);
+    ///
+    /// bar_processor.set_gain(2.);
+    /// assert_eq!(bar_processor.gain(), 2.);
+    /// assert_eq!(bar_processor.config().normalization, Normalization::Manual(2.));
+    /// ```
+    pub fn set_gain(&mut self, gain: f32) {
+        if let Normalization::Manual(manual_gain) = &mut self.config.normalization {
+            *manual_gain = gain;
+        }
+
+        for interpolator in self.channels.iter_mut().flatten() {
+            interpolator.normalize_factor = gain;
+        }
     }
 
     pub fn config(&self) -> &BarProcessorConfig {
         &self.config
     }
 
+    /// Sets the time which has passed since the last call of [BarProcessor::process_bars]
+    /// (or [BarProcessor::process_bars_into]).
+    ///
+    /// It's only used for the next call and overwrites [BarProcessorConfig::frame_timing].
+    /// Useful if you already know your frame time.
+    pub fn set_delta_time(&mut self, delta_time: Duration) {
+        self.next_delta_time = Some(delta_time);
+    }
+
+    /// Registers a function which gets the interpolated bar values of each channel
+    /// before they are returned by [BarProcessor::process_bars] or [BarProcessor::process_bars_into].
+    ///
+    /// Replaces the previously registered function. See [PostProcessor] for an example.
+    pub fn set_post_fn(&mut self, post_fn: impl PostProcessor + 'static) {
+        self.post_fn = Some(Box::new(post_fn));
+    }
+
+    /// Removes the registered post-processing function and returns it.
+    pub fn take_post_fn(&mut self) -> Option<Box<dyn PostProcessor>> {
+        self.post_fn.take()
+    }
+
     /// Change the amount of bars which should be returned.
     ///
     /// # Example
@@ -291,19 +839,257 @@ impl BarProcessor {
     /// assert_eq!(bars.len(), 1);
     /// assert_eq!(bars[0].len(), 20);
     /// ```
+    ///
+    /// If the config has [BarProcessorConfig::bands], they are replaced by one frequency range
+    /// from the lowest to the highest frequency of the bands.
     pub fn set_amount_bars(&mut self, amount_bars: NonZero<u16>) {
+        let bands = std::mem::take(&mut self.config.bands);
+        if let (Some(start), Some(end)) = (
+            bands.iter().map(|band| band.freq_range.start).min(),
+            bands.iter().map(|band| band.freq_range.end).max(),
+        ) {
+            self.config.freq_range = start..end;
+        }
+
         self.config.amount_bars = amount_bars;
         let amount_channels = self.channels.len();
 
-        let (channels, bar_values) = Self::get_channels_and_bar_values(
+        let (mut channels, bar_values) = Self::get_channels_and_bar_values(
             &self.config,
             amount_channels,
             self.sample_rate,
-            self.sample_len,
+            self.spectrum_len,
         );
 
+        // keep the gain which the bars have adapted to
+        for (new, old) in channels
+            .iter_mut()
+            .flatten()
+            .zip(self.channels.iter().flatten())
+        {
+            new.normalize_factor = old.normalize_factor;
+        }
+
         self.channels = channels;
         self.bar_values = bar_values;
+        self.reset_bar_spl();
+    }
+
+    /// Change the frequency range of the bars.
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their smoothing state and gain
+    /// so you can call it every frame to animate the range (for example to zoom into the bass).
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::num::NonZero;
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher};
+    ///
+    /// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    ///
+    /// let bass = NonZero::new(30).unwrap()..NonZero::new(250).unwrap();
+    /// bar_processor.set_freq_range(bass.clone());
+    /// assert_eq!(bar_processor.config().freq_range, bass);
+    /// ```
+    ///
+    /// If the config has [BarProcessorConfig::bands], they are replaced by the given frequency range.
+    pub fn set_freq_range(&mut self, freq_range: Range<NonZero<u16>>) {
+        self.config.freq_range = freq_range;
+
+        if !self.config.bands.is_empty() {
+            // the amount of interpolators per channel changes
+            self.config.bands.clear();
+            self.set_amount_bars(self.config.amount_bars);
+            return;
+        }
+
+        for interpolator in self.channels.iter_mut().flatten() {
+            interpolator.reconfigure(&self.config, self.sample_rate, self.spectrum_len);
+        }
+    }
+
+    /// Change the interpolation between the bars which are directly computed from the fft output.
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their smoothing state and gain.
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, InterpolationVariant, fetcher::DummyFetcher};
+    ///
+    /// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    ///
+    /// bar_processor.set_interpolation(InterpolationVariant::Linear);
+    /// assert_eq!(bar_processor.config().interpolation, InterpolationVariant::Linear);
+    /// ```
+    pub fn set_interpolation(&mut self, interpolation: InterpolationVariant) {
+        self.config.interpolation = interpolation;
+        self.reconfigure_interpolators();
+    }
+
+    /// Change how the bars are distributed over the frequency range.
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their smoothing state and gain.
+    pub fn set_bar_distribution(&mut self, bar_distribution: BarDistribution) {
+        self.config.bar_distribution = bar_distribution;
+        self.reconfigure_interpolators();
+    }
+
+    /// Change how fast the bars fall down, see [BarProcessorConfig::sensitivity].
+    /// Values below [MIN_SENSITIVITY] (and `NaN`) are raised to [MIN_SENSITIVITY].
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their current values and gain.
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        let sensitivity = sensitivity.max(MIN_SENSITIVITY);
+        self.config.sensitivity = sensitivity;
+
+        for interpolator in self.channels.iter_mut().flatten() {
+            interpolator.sensitivity = sensitivity;
+        }
+    }
+
+    /// Change how the fft bins of a bar are combined, see [BarProcessorConfig::bin_aggregation].
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their current values and gain.
+    pub fn set_bin_aggregation(&mut self, bin_aggregation: BinAggregation) {
+        self.config.bin_aggregation = bin_aggregation;
+
+        for interpolator in self.channels.iter_mut().flatten() {
+            interpolator.bin_aggregation = bin_aggregation;
+        }
+    }
+
+    /// Change the limiter of the bars, see [BarProcessorConfig::limiter].
+    ///
+    /// Unlike creating a new [BarProcessor], the bars keep their current values and gain.
+    pub fn set_limiter(&mut self, limiter: Option<Limiter>) {
+        self.config.limiter = limiter;
+
+        for interpolator in self.channels.iter_mut().flatten() {
+            interpolator.limiter = limiter;
+            interpolator.limiter_gains.fill(1.);
+        }
+    }
+
+    /// Redistributes the bins to the bars if the low frequency fft of the sample processor
+    /// has been enabled or disabled (see [SampleProcessor::set_low_frequency_fft]).
+    fn sync_spectrum_len(&mut self, processor: &SampleProcessor) {
+        let spectrum_len = processor.spectrum_size();
+        if spectrum_len == self.spectrum_len {
+            return;
+        }
+        self.spectrum_len = spectrum_len;
+        self.reconfigure_interpolators();
+    }
+
+    fn update_band_energies(&mut self, processor: &SampleProcessor) {
+        let amount_bands = self.config.energy_bands.len();
+        if self.band_energies.len() != self.channels.len()
+            || self
+                .band_energies
+                .first()
+                .is_some_and(|energies| energies.len() != amount_bands)
+        {
+            self.band_energies =
+                vec![vec![0f32; amount_bands].into_boxed_slice(); self.channels.len()]
+                    .into_boxed_slice();
+        }
+
+        // The bins of the regular fft are used since the merged spectrum of the longer fft repeats them.
+        let bins_per_hz = self.sample_len as f32 / self.sample_rate.0 as f32;
+        // Parseval: the positive bins of a sine with the amplitude `a` sum up to
+        // `a² / 4 * sample_len * window_power`
+        let energy_factor = 4. / (self.sample_len as f32 * processor.window_power());
+
+        for (energies, channel) in self.band_energies.iter_mut().zip(processor.fft_out()) {
+            let spectrum = &channel.fft_out;
+            for (energy, band) in energies.iter_mut().zip(self.config.energy_bands.iter()) {
+                let bin = |freq: NonZero<u16>| (freq.get() as f32 * bins_per_hz).ceil() as usize;
+
+                // the first bin is the DC offset
+                let start = bin(band.start).clamp(1, spectrum.len());
+                let end = bin(band.end).clamp(start, spectrum.len());
+
+                *energy = spectrum[start..end]
+                    .iter()
+                    .map(|out| out.norm_sqr())
+                    .sum::<f32>()
+                    * energy_factor;
+            }
+        }
+    }
+
+    fn reset_bar_spl(&mut self) {
+        self.bar_spl = self.spl_calibration.map(|_| {
+            self.bar_values
+                .iter()
+                .map(|bars| vec![f32::NEG_INFINITY; bars.len()].into_boxed_slice())
+                .collect()
+        });
+    }
+
+    /// Applies the current config to the interpolators of all bands while keeping their state.
+    fn reconfigure_interpolators(&mut self) {
+        let band_configs = self.config.band_configs();
+        for channel in self.channels.iter_mut() {
+            for (interpolator, band_config) in channel.iter_mut().zip(band_configs.iter()) {
+                interpolator.reconfigure(band_config, self.sample_rate, self.spectrum_len);
+            }
+        }
+    }
+
+    /// Returns the amount of reference frames which have passed since the last update.
+    fn time_scale(&mut self) -> f32 {
+        let now = Instant::now();
+        let last_update = self.last_update.replace(now);
+
+        let delta_time = self
+            .next_delta_time
+            .take()
+            .or_else(|| match self.config.frame_timing {
+                FrameTiming::Fixed => None,
+                FrameTiming::Measured => last_update.map(|last_update| now - last_update),
+            });
+
+        delta_time
+            .map(|delta_time| {
+                (delta_time.as_secs_f32() / REFERENCE_FRAME_TIME).clamp(0., MAX_TIME_SCALE)
+            })
+            .unwrap_or(1.)
+    }
+
+    /// Returns the gain which should be used instead of the adaptive one.
+    fn fixed_gain(&mut self, processor: &SampleProcessor) -> Option<f32> {
+        let target = match self.config.normalization {
+            Normalization::Adaptive => return None,
+            Normalization::Manual(gain) => return Some(gain),
+            Normalization::Loudness { target, .. } => target,
+        };
+        let meter = self.loudness_meter.as_mut()?;
+
+        // keep the bars down while it's silent
+        let mean_square = meter.measure(processor).unwrap_or(f32::INFINITY);
+
+        // A sine with an amplitude of `A` has a magnitude of `A * fft_size / 4` in its fft bin
+        // (hann window) and a root mean square of `A / sqrt(2)`. So a sine would reach `target`.
+        // Also compensate that the bars are accumulating their previous values.
+        let sine_magnitude =
+            self.sample_len as f32 / 4. * std::f32::consts::SQRT_2 * mean_square.sqrt();
+        Some(target * (1. - MEMORY_FACTOR) / sine_magnitude)
+    }
+
+    fn get_loudness_meter(
+        config: &BarProcessorConfig,
+        sample_rate: SampleRate,
+        amount_channels: usize,
+    ) -> Option<LoudnessMeter> {
+        match config.normalization {
+            Normalization::Adaptive | Normalization::Manual(_) => None,
+            Normalization::Loudness { window, .. } => {
+                Some(LoudnessMeter::new(sample_rate, amount_channels, window))
+            }
+        }
     }
 
     fn get_channels_and_bar_values(
@@ -316,14 +1102,64 @@ impl BarProcessor {
         let bar_values =
             vec![vec![0f32; config.amount_bars.get() as usize].into_boxed_slice(); amount_channels];
 
+        let band_configs = config.band_configs();
         for _ in 0..amount_channels {
-            channels.push(InterpolatorCtx::new(config, sample_rate, sample_len));
+            channels.push(
+                band_configs
+                    .iter()
+                    .map(|band_config| InterpolatorCtx::new(band_config, sample_rate, sample_len))
+                    .collect(),
+            );
         }
 
         (channels.into_boxed_slice(), bar_values.into_boxed_slice())
     }
 }
 
+/// Updates the interpolators of the bands of a channel and writes their bars one after another into `out`.
+fn process_channel(
+    bands: &mut [InterpolatorCtx],
+    fft_out: &[Complex32],
+    fixed_gain: Option<f32>,
+    time_scale: f32,
+    out: &mut [f32],
+) {
+    let mut band_start = 0;
+    for band in bands.iter_mut() {
+        let band_end = band_start + band.amount_bars();
+
+        band.update_supporting_points(fft_out, fixed_gain, time_scale);
+        band.interpolator
+            .interpolate(&mut out[band_start..band_end]);
+
+        band_start = band_end;
+    }
+}
+
+/// Converts the (linear) bar values of the bands of a channel into dB SPL.
+fn channel_spl(
+    bands: &[InterpolatorCtx],
+    bars: &[f32],
+    full_scale: f32,
+    calibration: SplCalibration,
+    out: &mut [f32],
+) {
+    let mut band_start = 0;
+    for band in bands {
+        let band_end = band_start + band.amount_bars();
+
+        for (bar_idx, (value, spl)) in bars[band_start..band_end]
+            .iter()
+            .zip(&mut out[band_start..band_end])
+            .enumerate()
+        {
+            *spl = calibration.spl(band.dbfs(bar_idx as f32, *value, full_scale));
+        }
+
+        band_start = band_end;
+    }
+}
+
 fn exp_fun(x: f32) -> f32 {
     debug_assert!(0. <= x);
     debug_assert!(x <= 1.);
@@ -353,3 +1189,520 @@ fn inv_mel(x: f32) -> f32 {
 
     700. * (10f32.powf(x / 2595.) - 1.)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoothing_is_frame_rate_independent() {
+        let config = BarProcessorConfig {
+            amount_bars: NonZero::new(10).unwrap(),
+            ..Default::default()
+        };
+        let sample_rate = SampleRate(44_100);
+        let fft_size = 1024;
+
+        let fft_out = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+        // disable the adaptive normalization to only compare the smoothing
+        let gain = Some(0.1);
+
+        // two frames at 60 FPS ...
+        let mut ctx_60 = InterpolatorCtx::new(&config, sample_rate, fft_size);
+        ctx_60.update_supporting_points(&fft_out, gain, 1.);
+        ctx_60.update_supporting_points(&fft_out, gain, 1.);
+
+        // ... should have the same result as one frame at 30 FPS
+        let mut ctx_30 = InterpolatorCtx::new(&config, sample_rate, fft_size);
+        ctx_30.update_supporting_points(&fft_out, gain, 2.);
+
+        for (a, b) in ctx_60
+            .interpolator
+            .supporting_points_mut()
+            .zip(ctx_30.interpolator.supporting_points_mut())
+        {
+            assert!((a.y - b.y).abs() < 1e-5, "{} != {}", a.y, b.y);
+        }
+    }
+
+    #[test]
+    fn bands_are_concatenated() {
+        let mut sample_processor = SampleProcessor::new(crate::fetcher::DummyFetcher::new(2));
+        sample_processor.process_next_samples();
+
+        let band = |start, end, amount_bars| FrequencyBand {
+            freq_range: NonZero::new(start).unwrap()..NonZero::new(end).unwrap(),
+            amount_bars: NonZero::new(amount_bars).unwrap(),
+        };
+        let mut bar_processor = BarProcessor::new(
+            &sample_processor,
+            BarProcessorConfig {
+                bands: vec![band(20, 200, 10), band(200, 5_000, 20)],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(bar_processor.config().amount_bars.get(), 30);
+        for channel in bar_processor.process_bars(&sample_processor) {
+            assert_eq!(channel.len(), 30);
+        }
+
+        let mut out = [0f32; 2 * 30];
+        bar_processor.process_bars_into(&sample_processor, &mut out);
+
+        bar_processor.set_amount_bars(NonZero::new(5).unwrap());
+        assert!(bar_processor.config().bands.is_empty());
+        assert_eq!(
+            bar_processor.config().freq_range,
+            NonZero::new(20).unwrap()..NonZero::new(5_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_too_many_bars() {
+        let sample_processor = SampleProcessor::new(crate::fetcher::DummyFetcher::new(1));
+        let band = FrequencyBand {
+            freq_range: NonZero::new(20).unwrap()..NonZero::new(20_000).unwrap(),
+            amount_bars: NonZero::new(u16::MAX / 2 + 1).unwrap(),
+        };
+
+        let result = BarProcessor::try_new(
+            &sample_processor,
+            BarProcessorConfig {
+                bands: vec![band.clone(), band],
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(BarProcessorConfigError::TooManyBars(65_536))
+        ));
+    }
+
+    #[test]
+    fn picks_up_low_frequency_fft() {
+        let mut sample_processor = SampleProcessor::new(crate::fetcher::DummyFetcher::new(2));
+        let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+        bar_processor.set_freq_range(NonZero::new(20).unwrap()..NonZero::new(20_000).unwrap());
+
+        for low_frequency_fft in [Some(crate::LowFrequencyFft::default()), None] {
+            sample_processor.set_low_frequency_fft(low_frequency_fft);
+            sample_processor.process_next_samples();
+
+            bar_processor.process_bars(&sample_processor);
+            assert_eq!(bar_processor.spectrum_len, sample_processor.spectrum_size());
+        }
+    }
+
+    #[test]
+    fn reconfiguring_keeps_the_bars() {
+        let config = BarProcessorConfig {
+            amount_bars: NonZero::new(10).unwrap(),
+            ..Default::default()
+        };
+        let fft_size = 1024;
+        let fft_out = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+
+        let mut ctx = InterpolatorCtx::new(&config, SampleRate(44_100), fft_size);
+        for _ in 0..10 {
+            ctx.update_supporting_points(&fft_out, None, 1.);
+        }
+        let normalize_factor = ctx.normalize_factor;
+        let values = ctx
+            .interpolator
+            .supporting_points()
+            .iter()
+            .map(|point| point.y)
+            .collect::<Vec<f32>>();
+        assert!(values.iter().all(|&value| value > 0.));
+
+        ctx.reconfigure(
+            &BarProcessorConfig {
+                interpolation: InterpolationVariant::Linear,
+                ..config
+            },
+            SampleRate(44_100),
+            fft_size,
+        );
+
+        assert_eq!(ctx.normalize_factor, normalize_factor);
+        for (point, value) in ctx.interpolator.supporting_points().iter().zip(values) {
+            assert_eq!(point.y, value);
+        }
+    }
+
+    #[test]
+    fn sensitivity_stays_positive() {
+        let config = BarProcessorConfig {
+            amount_bars: NonZero::new(10).unwrap(),
+            sensitivity: 0.,
+            ..Default::default()
+        };
+        let fft_size = 1024;
+        let fft_out = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+
+        let mut ctx = InterpolatorCtx::new(&config, SampleRate(44_100), fft_size);
+        assert_eq!(ctx.sensitivity, MIN_SENSITIVITY);
+        for _ in 0..10 {
+            ctx.update_supporting_points(&fft_out, None, 1.);
+        }
+        assert!(ctx
+            .interpolator
+            .supporting_points()
+            .iter()
+            .all(|point| point.y.is_finite()));
+
+        let sample_processor = SampleProcessor::new(crate::fetcher::DummyFetcher::new(1));
+        let mut bar_processor = BarProcessor::new(&sample_processor, config);
+        for sensitivity in [-1., f32::NAN] {
+            bar_processor.set_sensitivity(sensitivity);
+            assert_eq!(bar_processor.config().sensitivity, MIN_SENSITIVITY);
+        }
+    }
+
+    #[test]
+    fn dbfs_of_a_sine() {
+        for amount_channels in [1, 2] {
+            // a full scale sine which lies exactly on a bin of the fft
+            let mut sample_processor =
+                SampleProcessor::new(crate::fetcher::DummyFetcher::with_waveform(
+                    amount_channels,
+                    crate::fetcher::Waveform::Sine {
+                        frequency: 44_100. / 1024. * 23.,
+                    },
+                ));
+            assert_eq!(sample_processor.fft_size(), 1024);
+
+            let mut bar_processor = BarProcessor::new(
+                &sample_processor,
+                BarProcessorConfig {
+                    amount_bars: NonZero::new(10).unwrap(),
+                    interpolation: InterpolationVariant::None,
+                    normalization: Normalization::Manual(0.01),
+                    ..Default::default()
+                },
+            );
+            bar_processor.set_spl_calibration(Some(SplCalibration::from_offset(0.)));
+
+            for _ in 0..100 {
+                sample_processor.process_next_samples();
+                bar_processor.process_bars(&sample_processor);
+            }
+
+            for channel in bar_processor.bar_spl().unwrap() {
+                let dbfs = channel.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                assert!(
+                    dbfs.abs() < 0.5,
+                    "{} channels: {} dBFS",
+                    amount_channels,
+                    dbfs
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bass_bars_are_wider() {
+        let sample_processor = SampleProcessor::new(crate::fetcher::DummyFetcher::new(1));
+        let bar_processor = BarProcessor::new(
+            &sample_processor,
+            BarProcessorConfig {
+                bands: vec![FrequencyBand {
+                    freq_range: NonZero::new(50).unwrap()..NonZero::new(10_000).unwrap(),
+                    amount_bars: NonZero::new(20).unwrap(),
+                }],
+                ..Default::default()
+            },
+        );
+
+        let widths = bar_processor.bar_widths();
+        assert_eq!(widths.len(), 20);
+        assert!(widths.iter().all(|&width| width >= 0.));
+        assert!(widths[0] > widths[19], "{:?}", widths);
+    }
+
+    #[test]
+    fn value_scales() {
+        let scaled = |scale: ValueScale| {
+            let mut values = [0., 0.01, 0.25, 1.];
+            scale.apply(&mut values);
+            values
+        };
+
+        assert_eq!(scaled(ValueScale::Linear), [0., 0.01, 0.25, 1.]);
+        assert_eq!(scaled(ValueScale::Sqrt), [0., 0.1, 0.5, 1.]);
+        assert_eq!(scaled(ValueScale::Log)[0], 0.);
+        assert_eq!(scaled(ValueScale::Log)[3], 1.);
+
+        // 0.01 => -40 dB
+        let db = scaled(ValueScale::Db { floor: -60. });
+        assert_eq!(db[0], 0.);
+        assert!((db[1] - 1. / 3.).abs() < 1e-5, "{}", db[1]);
+        assert_eq!(db[3], 1.);
+    }
+
+    #[test]
+    fn band_energies_of_the_same_frame() {
+        let mut sample_processor =
+            SampleProcessor::new(crate::fetcher::DummyFetcher::with_waveform(
+                1,
+                crate::fetcher::Waveform::Sine { frequency: 1_000. },
+            ));
+        let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+        sample_processor.process_next_samples();
+
+        let frame = bar_processor.process_frame(&sample_processor);
+        assert_eq!(frame.bars.len(), 1);
+        assert_eq!(frame.band_energies.len(), 1);
+
+        let energies = &frame.band_energies[0];
+        assert_eq!(energies.len(), DEFAULT_ENERGY_BANDS.len());
+        // a full scale sine within the mids
+        assert!((energies[1] - 1.).abs() < 0.1, "{:?}", energies);
+        assert!(energies[0] < 0.1 && energies[2] < 0.1, "{:?}", energies);
+    }
+
+    #[test]
+    fn band_energies_of_stereo_and_low_frequency_fft() {
+        for (amount_channels, low_frequency_fft) in [
+            (2, None),
+            (1, Some(crate::LowFrequencyFft::default())),
+            (2, Some(crate::LowFrequencyFft::default())),
+        ] {
+            let mut sample_processor =
+                SampleProcessor::new(crate::fetcher::DummyFetcher::with_waveform(
+                    amount_channels,
+                    crate::fetcher::Waveform::Sine { frequency: 1_000. },
+                ));
+            sample_processor.set_low_frequency_fft(low_frequency_fft);
+            let mut bar_processor =
+                BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+            sample_processor.process_next_samples();
+
+            let frame = bar_processor.process_frame(&sample_processor);
+            assert_eq!(frame.band_energies.len(), usize::from(amount_channels));
+            for energies in frame.band_energies.iter() {
+                // a full scale sine within the mids
+                assert!(
+                    (energies[1] - 1.).abs() < 0.1,
+                    "{} channels, {:?}: {:?}",
+                    amount_channels,
+                    low_frequency_fft,
+                    energies
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bin_aggregations() {
+        let aggregate =
+            |aggregation: BinAggregation| aggregation.apply([3., 0., 4., 1.].into_iter());
+
+        assert_eq!(aggregate(BinAggregation::Max), 4.);
+        assert_eq!(aggregate(BinAggregation::Mean), 2.);
+        assert!((aggregate(BinAggregation::Rms) - 6.5f32.sqrt()).abs() < 1e-6);
+        assert!((aggregate(BinAggregation::Sum) - 26f32.sqrt()).abs() < 1e-6);
+
+        for aggregation in [
+            BinAggregation::Max,
+            BinAggregation::Mean,
+            BinAggregation::Rms,
+            BinAggregation::Sum,
+        ] {
+            assert_eq!(aggregation.apply(std::iter::empty()), 0.);
+        }
+    }
+
+    #[test]
+    fn manual_gain_doesnt_drift() {
+        let config = BarProcessorConfig {
+            amount_bars: NonZero::new(10).unwrap(),
+            ..Default::default()
+        };
+        let fft_size = 1024;
+        // lets the bars overshoot
+        let loud = vec![Complex32::new(1_000., 0.); fft_size / 2 + 1];
+
+        let mut adaptive = InterpolatorCtx::new(&config, SampleRate(44_100), fft_size);
+        let mut manual = InterpolatorCtx::new(&config, SampleRate(44_100), fft_size);
+        for _ in 0..10 {
+            adaptive.update_supporting_points(&loud, None, 1.);
+            manual.update_supporting_points(&loud, Some(2.), 1.);
+        }
+
+        assert!(adaptive.normalize_factor < 1.);
+        assert_eq!(manual.normalize_factor, 2.);
+    }
+
+    #[test]
+    fn transient_emphasis() {
+        let sample_rate = SampleRate(44_100);
+        let fft_size = 1024;
+
+        let steady = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+        let gain = Some(0.1);
+
+        let bars_after = |transient_emphasis: f32, frames: usize| {
+            let config = BarProcessorConfig {
+                amount_bars: NonZero::new(10).unwrap(),
+                transient_emphasis,
+                ..Default::default()
+            };
+            let mut ctx = InterpolatorCtx::new(&config, sample_rate, fft_size);
+            for _ in 0..frames {
+                ctx.update_supporting_points(&steady, gain, 1.);
+            }
+
+            ctx.interpolator
+                .supporting_points()
+                .iter()
+                .map(|point| point.y)
+                .collect::<Vec<f32>>()
+        };
+
+        // the onset is the same ...
+        assert_eq!(bars_after(0., 1), bars_after(1., 1));
+
+        // ... but a steady signal fades out with the full emphasis
+        for (magnitude, flux) in bars_after(0., 30).into_iter().zip(bars_after(1., 30)) {
+            assert!(flux < magnitude * 0.5, "{} >= {}", flux, magnitude * 0.5);
+        }
+    }
+
+    #[test]
+    fn soft_knee() {
+        let limiter = Limiter {
+            threshold: 0.8,
+            knee: 0.2,
+            ..Default::default()
+        };
+
+        assert_eq!(limiter.target_gain(0.5), 1.);
+        assert_eq!(limiter.target_gain(0.), 1.);
+        assert!((limiter.target_gain(2.) * 2. - 0.8).abs() < 1e-6);
+
+        // within the knee
+        let limited = 0.8 * limiter.target_gain(0.8);
+        assert!(limited > 0.7 && limited < 0.8, "{}", limited);
+    }
+
+    #[test]
+    fn limiter_threshold_stays_positive() {
+        for threshold in [0., -1., f32::NAN] {
+            let limiter = Limiter {
+                threshold,
+                knee: 0.,
+                ..Default::default()
+            };
+
+            let gain = limiter.target_gain(0.5);
+            assert!(gain.is_finite() && gain > 0., "{}: {}", threshold, gain);
+            assert!((0.5 * gain - Limiter::MIN_THRESHOLD).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn limiter_catches_transients() {
+        let sample_rate = SampleRate(44_100);
+        let fft_size = 1024;
+
+        let quiet = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+        let ding = vec![Complex32::new(50., 0.); fft_size / 2 + 1];
+
+        let run = |limiter: Option<Limiter>| {
+            let config = BarProcessorConfig {
+                amount_bars: NonZero::new(10).unwrap(),
+                limiter,
+                ..Default::default()
+            };
+            let mut ctx = InterpolatorCtx::new(&config, sample_rate, fft_size);
+            ctx.normalize_factor = 0.02;
+
+            let mut max = 0f32;
+            for frame in 0..60 {
+                let fft_out = if (10..13).contains(&frame) {
+                    &ding
+                } else {
+                    &quiet
+                };
+                ctx.update_supporting_points(fft_out, None, 1.);
+
+                let frame_max = ctx
+                    .interpolator
+                    .supporting_points()
+                    .iter()
+                    .map(|point| point.y)
+                    .fold(0., f32::max);
+                max = max.max(frame_max);
+            }
+
+            (max, ctx.normalize_factor)
+        };
+
+        let (unlimited_max, unlimited_gain) = run(None);
+        let (limited_max, limited_gain) = run(Some(Limiter::default()));
+
+        assert!(unlimited_max > 2., "{}", unlimited_max);
+        assert!(limited_max <= 1., "{}", limited_max);
+        // the gain is dimmed less after the transient
+        assert!(
+            limited_gain > unlimited_gain,
+            "{} <= {}",
+            limited_gain,
+            unlimited_gain
+        );
+    }
+
+    #[test]
+    fn silence_decay() {
+        let sample_rate = SampleRate(44_100);
+        let fft_size = 1024;
+
+        let loud = vec![Complex32::new(1., 0.); fft_size / 2 + 1];
+        let silent = vec![Complex32::new(0., 0.); fft_size / 2 + 1];
+        let gain = Some(0.1);
+
+        let bars_after = |silence_decay: SilenceDecay, time_scales: &[f32]| {
+            let config = BarProcessorConfig {
+                amount_bars: NonZero::new(10).unwrap(),
+                silence_decay,
+                ..Default::default()
+            };
+            let mut ctx = InterpolatorCtx::new(&config, sample_rate, fft_size);
+            ctx.update_supporting_points(&loud, gain, 1.);
+
+            for &time_scale in time_scales {
+                ctx.update_supporting_points(&silent, gain, time_scale);
+            }
+
+            ctx.interpolator
+                .supporting_points()
+                .iter()
+                .map(|point| point.y)
+                .collect::<Vec<f32>>()
+        };
+
+        let start = bars_after(SilenceDecay::Gravity, &[]);
+        assert!(start.iter().all(|&y| y > 0.));
+
+        // frame rate independent
+        let exponential = SilenceDecay::Exponential { factor: 0.5 };
+        let at_60 = bars_after(exponential, &[1., 1.]);
+        let at_30 = bars_after(exponential, &[2.]);
+        for ((a, b), start) in at_60.iter().zip(&at_30).zip(&start) {
+            assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+            assert!((a - start * 0.25).abs() < 1e-5);
+        }
+
+        // 0.5 seconds = 30 reference frames
+        let time_to_zero = SilenceDecay::TimeToZero(Duration::from_millis(500));
+        let half = bars_after(time_to_zero, &[5., 10.]);
+        for (y, start) in half.iter().zip(&start) {
+            assert!((y - start * 0.5).abs() < 1e-5);
+        }
+        assert!(bars_after(time_to_zero, &[10., 10., 10.])
+            .iter()
+            .all(|&y| y == 0.));
+    }
+}
@@ -0,0 +1,39 @@
+/// Shapes the bar values of a channel after they've been interpolated.
+///
+/// It's implemented for every closure which accepts `&mut [f32]`, so you can pass closures
+/// to [crate::BarProcessor::set_post_fn] directly.
+///
+/// # Example
+/// ```rust
+/// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+/// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+///
+/// // apply a gamma correction and keep the bars within `[0, 1]`
+/// bar_processor.set_post_fn(|bars: &mut [f32]| {
+///     for bar in bars.iter_mut() {
+///         *bar = bar.clamp(0., 1.).powf(0.5);
+///     }
+/// });
+///
+/// sample_processor.process_next_samples();
+/// let bars = bar_processor.process_bars(&sample_processor);
+/// assert!(bars[0].iter().all(|bar| (0. ..=1.).contains(bar)));
+/// ```
+pub trait PostProcessor {
+    /// Modifies the bar values of one channel.
+    fn process(&mut self, bars: &mut [f32]);
+}
+
+impl<F: FnMut(&mut [f32])> PostProcessor for F {
+    fn process(&mut self, bars: &mut [f32]) {
+        self(bars)
+    }
+}
+
+impl PostProcessor for Box<dyn PostProcessor> {
+    fn process(&mut self, bars: &mut [f32]) {
+        self.as_mut().process(bars)
+    }
+}
@@ -0,0 +1,104 @@
+use std::num::NonZero;
+
+use super::{BarProcessorConfig, InterpolationVariant, Normalization};
+
+/// Tuned [BarProcessorConfig]s for common visualizer styles.
+///
+/// # Example
+/// ```
+/// use std::num::NonZero;
+/// use shady_audio::{BarProcessor, BarProcessorConfig, Preset, SampleProcessor, fetcher::DummyFetcher};
+///
+/// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+///
+/// let bar_processor = BarProcessor::new(
+///     &sample_processor,
+///     BarProcessorConfig {
+///         sensitivity: 0.5,
+///         ..Preset::Bass.config(NonZero::new(20).unwrap())
+///     },
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Preset {
+    /// Only the kick drums and the bass lines with fast falling bars.
+    Bass,
+
+    /// The frequency range of the human voice.
+    Voice,
+
+    /// The whole hearing range. The bars are scaled relative to the loudness of the audio
+    /// to keep quiet and loud songs comparable.
+    FullSpectrum,
+
+    /// The default config of [BarProcessorConfig].
+    #[default]
+    Classic,
+}
+
+impl Preset {
+    /// All presets.
+    pub const ALL: [Self; 4] = [Self::Bass, Self::Voice, Self::FullSpectrum, Self::Classic];
+
+    /// Returns the config of the preset with the given amount of bars.
+    pub fn config(self, amount_bars: NonZero<u16>) -> BarProcessorConfig {
+        let default = BarProcessorConfig {
+            amount_bars,
+            ..Default::default()
+        };
+
+        match self {
+            Self::Bass => BarProcessorConfig {
+                freq_range: NonZero::new(30).unwrap()..NonZero::new(250).unwrap(),
+                interpolation: InterpolationVariant::MonotoneCubic,
+                sensitivity: 0.6,
+                ..default
+            },
+            Self::Voice => BarProcessorConfig {
+                freq_range: NonZero::new(80).unwrap()..NonZero::new(4_000).unwrap(),
+                interpolation: InterpolationVariant::CubicSpline,
+                sensitivity: 0.8,
+                ..default
+            },
+            Self::FullSpectrum => BarProcessorConfig {
+                freq_range: NonZero::new(crate::MIN_HUMAN_FREQUENCY).unwrap()
+                    ..NonZero::new(crate::MAX_HUMAN_FREQUENCY).unwrap(),
+                interpolation: InterpolationVariant::CubicSpline,
+                normalization: Normalization::LOUDNESS,
+                ..default
+            },
+            Self::Classic => default,
+        }
+    }
+}
+
+impl From<Preset> for BarProcessorConfig {
+    fn from(preset: Preset) -> Self {
+        preset.config(BarProcessorConfig::default().amount_bars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fetcher::DummyFetcher, BarProcessor, SampleProcessor};
+
+    use super::*;
+
+    #[test]
+    fn presets_create_bars() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        sample_processor.process_next_samples();
+
+        for preset in Preset::ALL {
+            let mut bar_processor = BarProcessor::new(&sample_processor, preset.into());
+            let bars = bar_processor.process_bars(&sample_processor);
+
+            assert_eq!(
+                bars[0].len(),
+                usize::from(BarProcessorConfig::default().amount_bars.get()),
+                "{:?}",
+                preset
+            );
+        }
+    }
+}
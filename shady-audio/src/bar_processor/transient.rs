@@ -0,0 +1,86 @@
+use realfft::num_complex::Complex32;
+
+/// Computes the (rectified, complex domain) spectral flux of each frequency bin.
+///
+/// Each bin is compared with the value which is expected if the frequency would continue steadily:
+/// Same magnitude and the same phase advance as in the previous frame. So steady tones have (almost)
+/// no flux, even if their phase is rotating, while onsets (like percussive hits) stand out.
+/// Only increasing magnitudes count as flux, decaying sounds are ignored.
+///
+/// The phase prediction assumes that the same amount of samples has been fetched between the frames.
+pub struct SpectralFlux {
+    prev_bins: Box<[Complex32]>,
+    // the phase advance of each bin between the last two frames
+    prev_phase_advances: Box<[f32]>,
+
+    flux: Box<[f32]>,
+}
+
+impl SpectralFlux {
+    pub fn new(amount_bins: usize) -> Self {
+        Self {
+            prev_bins: vec![Complex32::ZERO; amount_bins].into_boxed_slice(),
+            prev_phase_advances: vec![0.; amount_bins].into_boxed_slice(),
+            flux: vec![0.; amount_bins].into_boxed_slice(),
+        }
+    }
+
+    /// Computes the flux of the new frame.
+    pub fn update(&mut self, bins: &[Complex32]) {
+        if bins.len() != self.flux.len() {
+            *self = Self::new(bins.len());
+        }
+
+        for (((&bin, prev_bin), prev_phase_advance), flux) in bins
+            .iter()
+            .zip(self.prev_bins.iter_mut())
+            .zip(self.prev_phase_advances.iter_mut())
+            .zip(self.flux.iter_mut())
+        {
+            let prev_magnitude = prev_bin.norm();
+            let expected =
+                Complex32::from_polar(prev_magnitude, prev_bin.arg() + *prev_phase_advance);
+
+            *flux = if bin.norm() >= prev_magnitude {
+                (bin - expected).norm()
+            } else {
+                0.
+            };
+
+            *prev_phase_advance = bin.arg() - prev_bin.arg();
+            *prev_bin = bin;
+        }
+    }
+
+    /// Returns the flux of each bin of the last frame.
+    pub fn flux(&self) -> &[f32] {
+        &self.flux
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_tone_has_no_flux() {
+        let mut spectral_flux = SpectralFlux::new(1);
+
+        // the phase rotates by the same amount each frame
+        for frame in 0..5 {
+            spectral_flux.update(&[Complex32::from_polar(2., frame as f32 * 0.3)]);
+        }
+
+        assert!(spectral_flux.flux()[0] < 1e-4);
+    }
+
+    #[test]
+    fn onset_has_flux() {
+        let mut spectral_flux = SpectralFlux::new(2);
+        spectral_flux.update(&[Complex32::ZERO, Complex32::new(1., 0.)]);
+        spectral_flux.update(&[Complex32::new(3., 0.), Complex32::new(0.5, 0.)]);
+
+        // the first bin appeared, the second one is decaying
+        assert_eq!(spectral_flux.flux(), &[3., 0.]);
+    }
+}
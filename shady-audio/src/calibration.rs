@@ -0,0 +1,53 @@
+/// Maps digital levels (in dBFS) to approximate sound pressure levels (in dB SPL).
+///
+/// Play a signal with a known level (for example pink noise at -20 dBFS), measure the sound pressure level
+/// at your listening position with a SPL meter and pass both values to [SplCalibration::new].
+/// The calibration is only valid as long as the volume of your system and your speakers doesn't change.
+///
+/// `0` dBFS is the level of a sine which uses the full range of the samples (`[-1, 1]`).
+///
+/// # Example
+/// ```rust
+/// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, SplCalibration, fetcher::DummyFetcher};
+///
+/// // -20 dBFS pink noise has been measured with 85 dB SPL
+/// let calibration = SplCalibration::new(-20., 85.);
+/// assert_eq!(calibration.spl(-30.), 75.);
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+/// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+/// bar_processor.set_spl_calibration(Some(calibration));
+///
+/// sample_processor.process_next_samples();
+/// bar_processor.process_bars(&sample_processor);
+///
+/// // the dummy is silent
+/// let spl = bar_processor.bar_spl().unwrap();
+/// assert!(spl[0].iter().all(|&level| level == f32::NEG_INFINITY));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplCalibration {
+    offset: f32,
+}
+
+impl SplCalibration {
+    /// Creates a calibration from a signal with a level of `signal_dbfs` which has been measured with `measured_spl`.
+    pub fn new(signal_dbfs: f32, measured_spl: f32) -> Self {
+        Self::from_offset(measured_spl - signal_dbfs)
+    }
+
+    /// Creates a calibration where `0` dBFS has a sound pressure level of `offset` dB SPL.
+    pub fn from_offset(offset: f32) -> Self {
+        Self { offset }
+    }
+
+    /// The sound pressure level of `0` dBFS.
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Converts the given level into dB SPL.
+    pub fn spl(&self, dbfs: f32) -> f32 {
+        dbfs + self.offset
+    }
+}
@@ -0,0 +1,347 @@
+//! Compatibility layer for the config files of [cava].
+//!
+//! Reads the options of a [cava] config which have an equivalent in this crate and translates them
+//! into a [BarProcessorConfig]. All other options (like colors or the output method) are ignored.
+//!
+//! # Example
+//! ```
+//! use shady_audio::{cava::CavaConfig, fetcher::DummyFetcher, BarProcessor, SampleProcessor};
+//!
+//! # fn main() -> Result<(), shady_audio::cava::CavaConfigError> {
+//! let cava_config: CavaConfig = "
+//! [general]
+//! bars = 20
+//! lower_cutoff_freq = 30
+//!
+//! [smoothing]
+//! noise_reduction = 60
+//! "
+//! .parse()?;
+//!
+//! let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+//! let bar_processor = BarProcessor::new(&sample_processor, cava_config.bar_processor_config()?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [cava]: https://github.com/karlstav/cava
+use std::{num::NonZero, path::Path, str::FromStr, time::Duration};
+
+use crate::{BarProcessorConfig, FrameTiming, Normalization, MIN_SENSITIVITY};
+
+/// Errors which can occur while reading a [cava] config.
+///
+/// [cava]: https://github.com/karlstav/cava
+#[derive(thiserror::Error, Debug)]
+pub enum CavaConfigError {
+    #[error("Couldn't read the cava config: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Line {line}: Expected a section (`[name]`) or an option (`key = value`).")]
+    Syntax { line: usize },
+
+    #[error("Line {line}: Invalid value for `{key}`: {value}")]
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+    },
+
+    #[error("`smoothing.noise_reduction` must be within the range [0, 100] but it's {0}.")]
+    NoiseReduction(f32),
+
+    #[error("`general.lower_cutoff_freq` ({lower}) must be lower than `general.higher_cutoff_freq` ({higher}).")]
+    CutoffFreqs {
+        lower: NonZero<u16>,
+        higher: NonZero<u16>,
+    },
+}
+
+/// The options of a [cava] config which can be translated.
+///
+/// The default values are the same as the ones of [cava].
+///
+/// [cava]: https://github.com/karlstav/cava
+#[derive(Debug, Clone, PartialEq)]
+pub struct CavaConfig {
+    /// `general.framerate`
+    pub framerate: NonZero<u16>,
+
+    /// `general.bars`. `None` if cava should decide the amount of bars (`0`).
+    pub bars: Option<NonZero<u16>>,
+
+    /// `general.autosens`
+    pub autosens: bool,
+
+    /// `general.sensitivity` (in percent).
+    pub sensitivity: f32,
+
+    /// `general.lower_cutoff_freq`
+    pub lower_cutoff_freq: NonZero<u16>,
+
+    /// `general.higher_cutoff_freq`
+    pub higher_cutoff_freq: NonZero<u16>,
+
+    /// `smoothing.noise_reduction` (within the range `[0, 100]`).
+    pub noise_reduction: f32,
+
+    /// `smoothing.gravity` (in percent) of older cava versions.
+    pub gravity: f32,
+
+    /// `input.source`. It's up to you to pick the matching device.
+    pub source: Option<String>,
+
+    /// `output.channels`. It's up to you to merge the channels if it's `false`.
+    pub stereo: bool,
+}
+
+impl Default for CavaConfig {
+    fn default() -> Self {
+        Self {
+            framerate: NonZero::new(60).unwrap(),
+            bars: None,
+            autosens: true,
+            sensitivity: 100.,
+            lower_cutoff_freq: NonZero::new(50).unwrap(),
+            higher_cutoff_freq: NonZero::new(10_000).unwrap(),
+            noise_reduction: 77.,
+            gravity: 100.,
+            source: None,
+            stereo: true,
+        }
+    }
+}
+
+impl CavaConfig {
+    /// Reads the cava config at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CavaConfigError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Translates the config into a [BarProcessorConfig].
+    ///
+    /// The bars are smoothed independently of the frame rate ([FrameTiming::Measured]),
+    /// so you can render with [CavaConfig::frame_time] or any other frame rate.
+    ///
+    /// Returns an error if `noise_reduction` is outside of `[0, 100]` or if the cutoff frequencies
+    /// don't form a frequency range.
+    pub fn bar_processor_config(&self) -> Result<BarProcessorConfig, CavaConfigError> {
+        let default = BarProcessorConfig::default();
+
+        if !(0. ..=100.).contains(&self.noise_reduction) {
+            return Err(CavaConfigError::NoiseReduction(self.noise_reduction));
+        }
+
+        if self.lower_cutoff_freq >= self.higher_cutoff_freq {
+            return Err(CavaConfigError::CutoffFreqs {
+                lower: self.lower_cutoff_freq,
+                higher: self.higher_cutoff_freq,
+            });
+        }
+
+        // both of them control how fast the bars are falling
+        let sensitivity =
+            ((self.noise_reduction / 100.) / (self.gravity.max(1.) / 100.)).max(MIN_SENSITIVITY);
+
+        let normalization = if self.autosens {
+            Normalization::Adaptive
+        } else {
+            Normalization::Manual(self.sensitivity / 100.)
+        };
+
+        Ok(BarProcessorConfig {
+            amount_bars: self.bars.unwrap_or(default.amount_bars),
+            freq_range: self.lower_cutoff_freq..self.higher_cutoff_freq,
+            sensitivity,
+            normalization,
+            frame_timing: FrameTiming::Measured,
+            ..default
+        })
+    }
+
+    /// Returns the time between two frames of `general.framerate`.
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_secs_f32(1. / f32::from(self.framerate.get()))
+    }
+
+    fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| value.to_string())
+        }
+
+        fn non_zero(value: &str) -> Result<NonZero<u16>, String> {
+            parse(value)
+        }
+
+        match (section, key) {
+            ("general", "framerate") => self.framerate = non_zero(value)?,
+            ("general", "bars") => self.bars = NonZero::new(parse(value)?),
+            ("general", "autosens") => self.autosens = parse::<u8>(value)? != 0,
+            ("general", "sensitivity") => self.sensitivity = parse(value)?,
+            ("general", "lower_cutoff_freq") => self.lower_cutoff_freq = non_zero(value)?,
+            ("general", "higher_cutoff_freq") => self.higher_cutoff_freq = non_zero(value)?,
+            ("smoothing", "noise_reduction") => self.noise_reduction = parse(value)?,
+            ("smoothing", "gravity") => self.gravity = parse(value)?,
+            ("input", "source") => self.source = Some(value.to_string()),
+            ("output", "channels") => {
+                self.stereo = match value {
+                    "stereo" => true,
+                    "mono" => false,
+                    _ => return Err(value.to_string()),
+                }
+            }
+            // options without an equivalent
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for CavaConfig {
+    type Err = CavaConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+        let mut section = String::new();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line_nr = idx + 1;
+            let line = line
+                .split(['#', ';'])
+                .next()
+                .expect("`split` returns at least one item")
+                .trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(CavaConfigError::Syntax { line: line_nr });
+            };
+            let key = key.trim();
+            let value = value.trim().trim_matches(['"', '\'']);
+
+            config
+                .set(&section, key, value)
+                .map_err(|value| CavaConfigError::InvalidValue {
+                    line: line_nr,
+                    key: key.to_string(),
+                    value,
+                })?;
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commented_config_has_defaults() {
+        let config: CavaConfig = "
+## Configuration file for CAVA.
+[general]
+; framerate = 60
+; bars = 0
+
+[smoothing]
+; noise_reduction = 77
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(config, CavaConfig::default());
+
+        let bar_config = config.bar_processor_config().unwrap();
+        assert_eq!(
+            bar_config.sensitivity,
+            BarProcessorConfig::default().sensitivity
+        );
+        assert_eq!(
+            bar_config.freq_range,
+            BarProcessorConfig::default().freq_range
+        );
+    }
+
+    #[test]
+    fn translate_config() {
+        let config: CavaConfig = "
+[general]
+framerate = 30
+bars = 12 # comment
+autosens = 0
+sensitivity = 150
+lower_cutoff_freq = 40
+higher_cutoff_freq = 8000
+
+[smoothing]
+gravity = 200
+noise_reduction = 60
+
+[input]
+method = pulse
+source = \"alsa_output.monitor\"
+
+[output]
+channels = mono
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(config.frame_time(), Duration::from_secs_f32(1. / 30.));
+        assert_eq!(config.source.as_deref(), Some("alsa_output.monitor"));
+        assert!(!config.stereo);
+
+        let bar_config = config.bar_processor_config().unwrap();
+        assert_eq!(bar_config.amount_bars.get(), 12);
+        assert_eq!(
+            bar_config.freq_range,
+            NonZero::new(40).unwrap()..NonZero::new(8_000).unwrap()
+        );
+        assert_eq!(bar_config.normalization, Normalization::Manual(1.5));
+        assert!((bar_config.sensitivity - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid_value() {
+        let err = "[general]\nbars = many".parse::<CavaConfig>().unwrap_err();
+        assert!(matches!(err, CavaConfigError::InvalidValue { line: 2, .. }));
+
+        let err = "[general]\nbars".parse::<CavaConfig>().unwrap_err();
+        assert!(matches!(err, CavaConfigError::Syntax { line: 2 }));
+    }
+
+    #[test]
+    fn invalid_bar_processor_config() {
+        let config: CavaConfig = "[general]\nlower_cutoff_freq = 500\nhigher_cutoff_freq = 100"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            config.bar_processor_config(),
+            Err(CavaConfigError::CutoffFreqs { .. })
+        ));
+
+        let config: CavaConfig = "[smoothing]\nnoise_reduction = 150".parse().unwrap();
+        assert!(matches!(
+            config.bar_processor_config(),
+            Err(CavaConfigError::NoiseReduction(_))
+        ));
+    }
+
+    #[test]
+    fn no_noise_reduction() {
+        let config: CavaConfig = "[smoothing]\nnoise_reduction = 0".parse().unwrap();
+
+        let bar_config = config.bar_processor_config().unwrap();
+        assert_eq!(bar_config.sensitivity, MIN_SENSITIVITY);
+    }
+}
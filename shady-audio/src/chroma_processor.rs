@@ -0,0 +1,142 @@
+use std::{num::NonZero, ops::Range};
+
+use crate::SampleProcessor;
+
+/// The amount of pitch classes (semitones) within an octave.
+pub const AMOUNT_PITCH_CLASSES: usize = 12;
+
+// the distance of `A` to `C` in semitones
+const A_PITCH_CLASS: i32 = 9;
+
+/// The config options for [ChromaProcessor].
+#[derive(Debug, Clone)]
+pub struct ChromaProcessorConfig {
+    /// The frequency of the `A` above the middle `C` (`A4`) in Hz. The pitch classes are relative to it.
+    pub tuning: f32,
+
+    /// Set the frequency range which should be considered.
+    /// Very low frequencies are quite imprecise since the frequency resolution of the FFT is only a few dozen Hz.
+    pub freq_range: Range<NonZero<u16>>,
+}
+
+impl Default for ChromaProcessorConfig {
+    fn default() -> Self {
+        Self {
+            tuning: 440.,
+            freq_range: NonZero::new(100).unwrap()..NonZero::new(5_000).unwrap(),
+        }
+    }
+}
+
+/// Folds the spectrum of the [SampleProcessor] into the 12 pitch classes of an octave (a.k.a. a chromagram).
+///
+/// Index `0` is `C`, index `1` is `C#` and so on until index `11` which is `B`.
+/// The values are in the range `[0, 1]` where the most present pitch class has the value `1`.
+/// All channels of the [SampleProcessor] are combined.
+///
+/// # Example
+/// ```
+/// use shady_audio::{ChromaProcessor, ChromaProcessorConfig, SampleProcessor, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut chroma_processor = ChromaProcessor::new(&sample_processor, ChromaProcessorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let chroma = chroma_processor.process(&sample_processor);
+///
+/// assert_eq!(chroma.len(), 12);
+/// ```
+pub struct ChromaProcessor {
+    config: ChromaProcessorConfig,
+
+    // the pitch class of each fft bin which is within the frequency range
+    bin_pitch_classes: Box<[(usize, usize)]>,
+    chroma: [f32; AMOUNT_PITCH_CLASSES],
+}
+
+impl ChromaProcessor {
+    /// Creates a new instance which works with the given sample processor.
+    pub fn new(processor: &SampleProcessor, config: ChromaProcessorConfig) -> Self {
+        let freq_resolution = processor.sample_rate().0 as f32 / processor.fft_size() as f32;
+        let amount_bins = processor.fft_size() / 2 + 1;
+
+        let start = (f32::from(config.freq_range.start.get()) / freq_resolution).ceil() as usize;
+        let end = (f32::from(config.freq_range.end.get()) / freq_resolution) as usize;
+
+        let bin_pitch_classes = (start.max(1)..=end.min(amount_bins - 1))
+            .map(|bin_idx| {
+                let freq = bin_idx as f32 * freq_resolution;
+                (bin_idx, pitch_class(freq, config.tuning))
+            })
+            .collect();
+
+        Self {
+            config,
+            bin_pitch_classes,
+            chroma: [0.; AMOUNT_PITCH_CLASSES],
+        }
+    }
+
+    /// Computes the chroma vector of the current samples of the given processor.
+    pub fn process(&mut self, processor: &SampleProcessor) -> &[f32; AMOUNT_PITCH_CLASSES] {
+        self.chroma = [0.; AMOUNT_PITCH_CLASSES];
+
+        for channel in processor.fft_out() {
+            for &(bin_idx, pitch_class) in self.bin_pitch_classes.iter() {
+                self.chroma[pitch_class] += channel.fft_out[bin_idx].norm_sqr();
+            }
+        }
+
+        let max = self.chroma.iter().copied().fold(0., f32::max);
+        if max > 0. {
+            for value in self.chroma.iter_mut() {
+                *value /= max;
+            }
+        }
+
+        &self.chroma
+    }
+
+    /// Returns the config of the processor.
+    pub fn config(&self) -> &ChromaProcessorConfig {
+        &self.config
+    }
+}
+
+/// Returns the pitch class (`0` = `C`, ..., `11` = `B`) of the given frequency.
+fn pitch_class(freq: f32, tuning: f32) -> usize {
+    let semitones_to_a = (12. * (freq / tuning).log2()).round() as i32;
+    (semitones_to_a + A_PITCH_CLASS).rem_euclid(AMOUNT_PITCH_CLASSES as i32) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetcher::{DummyFetcher, Waveform};
+
+    use super::*;
+
+    #[test]
+    fn pitch_classes() {
+        assert_eq!(pitch_class(440., 440.), 9);
+        assert_eq!(pitch_class(261.63, 440.), 0);
+        assert_eq!(pitch_class(523.25, 440.), 0);
+        assert_eq!(pitch_class(246.94, 440.), 11);
+        // a quarter tone higher tuning
+        assert_eq!(pitch_class(453., 453.), 9);
+    }
+
+    #[test]
+    fn detects_a() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+            1,
+            Waveform::Sine { frequency: 880. },
+        ));
+        let mut chroma_processor =
+            ChromaProcessor::new(&sample_processor, ChromaProcessorConfig::default());
+
+        sample_processor.process_next_samples();
+        let chroma = chroma_processor.process(&sample_processor);
+
+        assert_eq!(chroma[9], 1., "{:?}", chroma);
+    }
+}
@@ -0,0 +1,469 @@
+//! Drives music-reactive lights: maps the bar values to DMX channels and transmits them via Art-Net or sACN (E1.31).
+//!
+//! The mapping is described in TOML:
+//!
+//! ```toml
+//! # "art-net" (default) or "sacn"
+//! protocol = "art-net"
+//! # optional, defaults to 0 for Art-Net and 1 for sACN
+//! universe = 0
+//! # optional, Art-Net is broadcasted and sACN is multicasted by default
+//! target = "192.168.1.50"
+//!
+//! # the dimmer follows the average of the first 4 bars (the bass)
+//! [[channels]]
+//! channel = 1
+//! bars = [0, 4]
+//! scale = 1.5
+//! smoothing = 0.5
+//! ```
+//!
+//! # Example
+//! ```rust,no_run
+//! use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, dmx::{DmxMapping, DmxOutput}, fetcher::DummyFetcher};
+//!
+//! let mapping = DmxMapping::from_toml(&std::fs::read_to_string("lights.toml").unwrap()).unwrap();
+//! let mut dmx = DmxOutput::new(mapping).unwrap();
+//!
+//! let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+//! let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+//!
+//! loop {
+//!     sample_processor.process_next_samples();
+//!     let bars = bar_processor.process_bars(&sample_processor);
+//!
+//!     dmx.update(bars);
+//!     dmx.send().unwrap();
+//! #   break;
+//! }
+//! ```
+use std::{
+    io,
+    net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use serde::Deserialize;
+
+/// The amount of channels of a DMX universe.
+pub const DMX_UNIVERSE_SIZE: usize = 512;
+
+const ARTNET_PORT: u16 = 6454;
+const SACN_PORT: u16 = 5568;
+const SACN_PRIORITY: u8 = 100;
+const SACN_SOURCE_NAME: &str = "shady-audio";
+const SACN_UNIVERSES: std::ops::RangeInclusive<u16> = 1..=63999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DmxProtocol {
+    #[default]
+    ArtNet,
+    Sacn,
+}
+
+/// Describes which bars control which DMX channels. See the [module docs](self) for an example.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DmxMapping {
+    #[serde(default)]
+    pub protocol: DmxProtocol,
+
+    /// The Art-Net port address (net, sub-net and universe) or the sACN universe within the range `[1, 63999]`.
+    /// Defaults to `0` for Art-Net and `1` for sACN, see [DmxMapping::universe].
+    pub universe: Option<u16>,
+
+    /// The host (and optionally the port) which should receive the values.
+    pub target: Option<String>,
+
+    pub channels: Vec<ChannelMapping>,
+}
+
+/// Sets a DMX channel to the (averaged) value of some bars.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelMapping {
+    /// The DMX channel within the range `[1, 512]`.
+    pub channel: u16,
+
+    /// The range of the bars (the end is exclusive) whose values are averaged.
+    pub bars: [usize; 2],
+
+    /// The audio channel whose bars are used.
+    #[serde(default)]
+    pub audio_channel: usize,
+
+    /// The averaged value is multiplied with it before it's mapped to `[0, 255]`.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+
+    /// How much of the previous value is kept each update within the range `[0, 1)`.
+    /// Higher values let the lights flicker less.
+    #[serde(default)]
+    pub smoothing: f32,
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+impl DmxMapping {
+    pub fn from_toml(description: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(description)
+    }
+
+    /// Returns the configured universe or the first universe of the protocol.
+    pub fn universe(&self) -> u16 {
+        self.universe.unwrap_or(match self.protocol {
+            DmxProtocol::ArtNet => 0,
+            DmxProtocol::Sacn => *SACN_UNIVERSES.start(),
+        })
+    }
+
+    /// Returns the address which the values are sent to.
+    fn target_addr(&self) -> io::Result<SocketAddr> {
+        let port = match self.protocol {
+            DmxProtocol::ArtNet => ARTNET_PORT,
+            DmxProtocol::Sacn => SACN_PORT,
+        };
+
+        match &self.target {
+            Some(target) => {
+                if let Ok(addr) = target.parse::<SocketAddr>() {
+                    return Ok(addr);
+                }
+                // a bare IPv6 address contains colons as well
+                if let Ok(ip) = target.trim_matches(['[', ']']).parse::<IpAddr>() {
+                    return Ok(SocketAddr::new(ip, port));
+                }
+
+                let with_port = if target.contains(':') {
+                    target.clone()
+                } else {
+                    format!("{}:{}", target, port)
+                };
+
+                with_port.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, format!("Unknown host {}", target))
+                })
+            }
+            None => Ok(match self.protocol {
+                DmxProtocol::ArtNet => SocketAddr::from(([255, 255, 255, 255], port)),
+                // the multicast address of the universe
+                DmxProtocol::Sacn => {
+                    let [hi, lo] = self.universe().to_be_bytes();
+                    SocketAddr::from(([239, 255, hi, lo], port))
+                }
+            }),
+        }
+    }
+}
+
+/// Computes the DMX values of a [DmxMapping] and sends them.
+pub struct DmxOutput {
+    mapping: DmxMapping,
+    socket: UdpSocket,
+    target: SocketAddr,
+
+    // the smoothed values of the channel mappings
+    levels: Box<[f32]>,
+    values: [u8; DMX_UNIVERSE_SIZE],
+    sequence: u8,
+    // identifies the source for sACN receivers
+    cid: [u8; 16],
+}
+
+impl DmxOutput {
+    pub fn new(mapping: DmxMapping) -> io::Result<Self> {
+        if let Some(invalid) = mapping
+            .channels
+            .iter()
+            .find(|mapping| !(1..=DMX_UNIVERSE_SIZE as u16).contains(&mapping.channel))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid DMX channel {}", invalid.channel),
+            ));
+        }
+
+        if mapping.protocol == DmxProtocol::Sacn && !SACN_UNIVERSES.contains(&mapping.universe()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid sACN universe {}", mapping.universe()),
+            ));
+        }
+
+        if let Some(invalid) = mapping
+            .channels
+            .iter()
+            .find(|mapping| !(0. ..1.).contains(&mapping.smoothing))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid smoothing {} of DMX channel {}",
+                    invalid.smoothing, invalid.channel
+                ),
+            ));
+        }
+
+        let target = mapping.target_addr()?;
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_broadcast(true)?;
+
+        Ok(Self {
+            levels: vec![0.; mapping.channels.len()].into_boxed_slice(),
+            mapping,
+            socket,
+            target,
+            values: [0; DMX_UNIVERSE_SIZE],
+            sequence: 0,
+            cid: cid(),
+        })
+    }
+
+    /// Maps the given bars (of each audio channel, see [crate::BarProcessor::process_bars]) to the DMX values.
+    pub fn update(&mut self, bars: &[Box<[f32]>]) -> &[u8; DMX_UNIVERSE_SIZE] {
+        for (mapping, level) in self.mapping.channels.iter().zip(self.levels.iter_mut()) {
+            let channel_bars = bars.get(mapping.audio_channel).map_or(&[][..], |bars| bars);
+
+            let [start, end] = mapping.bars;
+            let end = end.min(channel_bars.len());
+            let selected = &channel_bars[start.min(end)..end];
+            let average = selected.iter().sum::<f32>() / selected.len().max(1) as f32;
+
+            *level = *level * mapping.smoothing + average * (1. - mapping.smoothing);
+
+            let value = (*level * mapping.scale).clamp(0., 1.);
+            self.values[usize::from(mapping.channel) - 1] = (value * 255.).round() as u8;
+        }
+
+        &self.values
+    }
+
+    /// Returns the values of the last update.
+    pub fn values(&self) -> &[u8; DMX_UNIVERSE_SIZE] {
+        &self.values
+    }
+
+    /// Sends the values of the last update.
+    pub fn send(&mut self) -> io::Result<()> {
+        // `0` means that the sequence isn't used for Art-Net
+        self.sequence = self.sequence.checked_add(1).unwrap_or(1);
+
+        let packet = match self.mapping.protocol {
+            DmxProtocol::ArtNet => {
+                artnet_packet(self.mapping.universe(), self.sequence, &self.values)
+            }
+            DmxProtocol::Sacn => sacn_packet(
+                self.mapping.universe(),
+                self.sequence,
+                &self.cid,
+                &self.values,
+            ),
+        };
+
+        self.socket.send_to(&packet, self.target)?;
+        Ok(())
+    }
+}
+
+/// Encodes an ArtDmx packet.
+fn artnet_packet(port_address: u16, sequence: u8, values: &[u8]) -> Vec<u8> {
+    let [net, sub_uni] = (port_address & 0x7fff).to_be_bytes();
+    let length = values.len() as u16;
+
+    let mut packet = Vec::with_capacity(18 + values.len());
+    packet.extend_from_slice(b"Art-Net\0");
+    // the opcode of ArtDmx (little endian)
+    packet.extend_from_slice(&0x5000u16.to_le_bytes());
+    // protocol version 14
+    packet.extend_from_slice(&[0, 14]);
+    packet.extend_from_slice(&[sequence, 0, sub_uni, net]);
+    packet.extend_from_slice(&length.to_be_bytes());
+    packet.extend_from_slice(values);
+
+    packet
+}
+
+/// Encodes an E1.31 data packet.
+fn sacn_packet(universe: u16, sequence: u8, cid: &[u8; 16], values: &[u8]) -> Vec<u8> {
+    let len = 126 + values.len();
+    // the length of each layer (from its start until the end of the packet) with the flags
+    let flags_and_length = |layer_start: usize| (0x7000 | (len - layer_start) as u16).to_be_bytes();
+
+    let mut packet = Vec::with_capacity(len);
+
+    // root layer
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(b"ASC-E1.17\0\0\0");
+    packet.extend_from_slice(&flags_and_length(16));
+    packet.extend_from_slice(&4u32.to_be_bytes());
+    packet.extend_from_slice(cid);
+
+    // framing layer
+    packet.extend_from_slice(&flags_and_length(38));
+    packet.extend_from_slice(&2u32.to_be_bytes());
+    let mut source_name = [0u8; 64];
+    source_name[..SACN_SOURCE_NAME.len()].copy_from_slice(SACN_SOURCE_NAME.as_bytes());
+    packet.extend_from_slice(&source_name);
+    packet.push(SACN_PRIORITY);
+    // no synchronization
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.push(sequence);
+    // options
+    packet.push(0);
+    packet.extend_from_slice(&universe.to_be_bytes());
+
+    // DMP layer
+    packet.extend_from_slice(&flags_and_length(115));
+    packet.push(0x02);
+    packet.push(0xa1);
+    // first property address and address increment
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    // the start code and the values
+    packet.extend_from_slice(&(values.len() as u16 + 1).to_be_bytes());
+    packet.push(0);
+    packet.extend_from_slice(values);
+
+    packet
+}
+
+/// Returns a component identifier which is unique enough to tell multiple instances apart.
+fn cid() -> [u8; 16] {
+    use std::hash::{BuildHasher, RandomState};
+
+    let state = RandomState::new();
+    let mut cid = [0; 16];
+    cid[..8].copy_from_slice(&state.hash_one(std::process::id()).to_be_bytes());
+    cid[8..].copy_from_slice(&state.hash_one(std::time::SystemTime::now()).to_be_bytes());
+    cid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPING: &str = r#"
+        universe = 3
+
+        [[channels]]
+        channel = 1
+        bars = [0, 2]
+
+        [[channels]]
+        channel = 512
+        bars = [2, 3]
+        audio_channel = 1
+        scale = 2.0
+        smoothing = 0.5
+    "#;
+
+    #[test]
+    fn parse_mapping() {
+        let mapping = DmxMapping::from_toml(MAPPING).unwrap();
+
+        assert_eq!(mapping.protocol, DmxProtocol::ArtNet);
+        assert_eq!(mapping.universe(), 3);
+        assert_eq!(mapping.channels[0].scale, 1.);
+        assert_eq!(mapping.channels[1].audio_channel, 1);
+        assert_eq!(
+            mapping.target_addr().unwrap(),
+            SocketAddr::from(([255, 255, 255, 255], ARTNET_PORT))
+        );
+    }
+
+    #[test]
+    fn bars_to_values() {
+        let mapping = DmxMapping::from_toml(MAPPING).unwrap();
+        let mut dmx = DmxOutput::new(mapping).unwrap();
+
+        let bars: [Box<[f32]>; 2] = [Box::new([1., 0., 0.]), Box::new([0., 0., 0.25])];
+        let values = dmx.update(&bars);
+        assert_eq!(values[0], 128);
+        // (0.25 * 0.5) * 2.0
+        assert_eq!(values[511], 64);
+
+        let values = dmx.update(&bars);
+        assert_eq!(values[511], 96);
+    }
+
+    #[test]
+    fn reject_invalid_channels() {
+        let mut mapping = DmxMapping::from_toml(MAPPING).unwrap();
+        mapping.channels[0].channel = 0;
+
+        assert!(DmxOutput::new(mapping).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_smoothing() {
+        for smoothing in [1., -0.5, f32::NAN] {
+            let mut mapping = DmxMapping::from_toml(MAPPING).unwrap();
+            mapping.channels[1].smoothing = smoothing;
+
+            assert!(DmxOutput::new(mapping).is_err(), "{}", smoothing);
+        }
+    }
+
+    #[test]
+    fn ipv6_targets() {
+        let mut mapping = DmxMapping::from_toml(MAPPING).unwrap();
+
+        for (target, expected) in [
+            (
+                "::1",
+                SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], ARTNET_PORT)),
+            ),
+            (
+                "[::1]",
+                SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], ARTNET_PORT)),
+            ),
+            (
+                "[::1]:1234",
+                SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 1234)),
+            ),
+            ("127.0.0.1:1234", SocketAddr::from(([127, 0, 0, 1], 1234))),
+        ] {
+            mapping.target = Some(target.to_string());
+            assert_eq!(mapping.target_addr().unwrap(), expected, "{}", target);
+        }
+    }
+
+    #[test]
+    fn sacn_universes() {
+        let mut mapping = DmxMapping::from_toml("protocol = \"sacn\"\nchannels = []").unwrap();
+        assert_eq!(mapping.universe(), 1);
+        assert_eq!(
+            mapping.target_addr().unwrap(),
+            SocketAddr::from(([239, 255, 0, 1], SACN_PORT))
+        );
+
+        mapping.universe = Some(0);
+        assert!(DmxOutput::new(mapping.clone()).is_err());
+
+        mapping.universe = Some(64000);
+        assert!(DmxOutput::new(mapping).is_err());
+    }
+
+    #[test]
+    fn packet_layout() {
+        let values = [255u8; DMX_UNIVERSE_SIZE];
+
+        let artnet = artnet_packet(0x0123, 7, &values);
+        assert_eq!(artnet.len(), 18 + DMX_UNIVERSE_SIZE);
+        assert_eq!(
+            &artnet[8..18],
+            &[0x00, 0x50, 0, 14, 7, 0, 0x23, 0x01, 0x02, 0x00]
+        );
+
+        let sacn = sacn_packet(1, 7, &[0; 16], &values);
+        assert_eq!(sacn.len(), 638);
+        assert_eq!(&sacn[16..18], &[0x72, 0x6e]);
+        assert_eq!(&sacn[38..40], &[0x72, 0x58]);
+        assert_eq!(&sacn[113..115], &[0, 1]);
+        assert_eq!(&sacn[115..117], &[0x72, 0x0b]);
+        assert_eq!(&sacn[123..126], &[0x02, 0x01, 0]);
+    }
+}
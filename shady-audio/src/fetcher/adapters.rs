@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use cpal::SampleRate;
+
+use super::Fetcher;
+
+/// Adapters which transform the samples of a fetcher, similar to the adapters of [Iterator].
+///
+/// Use [MixFetcher](super::MixFetcher) to combine several (adapted) fetchers into one.
+///
+/// # Example
+/// ```rust
+/// use shady_audio::{SampleProcessor, cpal::SampleRate, fetcher::{DummyFetcher, FetcherExt}};
+///
+/// // only the left channel, a bit louder and with a lower sample rate
+/// let fetcher = DummyFetcher::new(2)
+///     .select_channels(&[0])
+///     .gain(2.)
+///     .resample(SampleRate(22_050));
+///
+/// let sample_processor = SampleProcessor::new(fetcher);
+/// ```
+pub trait FetcherExt: Fetcher + Sized {
+    /// Multiplies the samples with `gain`, see [GainFetcher].
+    fn gain(self, gain: f32) -> Box<GainFetcher<Self>> {
+        GainFetcher::new(self, gain)
+    }
+
+    /// Provides only the given channels (in the given order), see [ChannelSelectFetcher].
+    fn select_channels(self, channels: &[u16]) -> Box<ChannelSelectFetcher<Self>> {
+        ChannelSelectFetcher::new(self, channels)
+    }
+
+    /// Converts the samples to the given sample rate, see [ResampleFetcher].
+    fn resample(self, sample_rate: SampleRate) -> Box<ResampleFetcher<Self>> {
+        ResampleFetcher::new(self, sample_rate)
+    }
+}
+
+impl<F: Fetcher> FetcherExt for F {}
+
+// The fetchers don't tell how many new samples they've inserted, so each adapter keeps the (untransformed)
+// samples of its fetcher and transforms all of them on each fetch.
+
+/// Multiplies the samples of a fetcher with a factor.
+pub struct GainFetcher<F> {
+    fetcher: F,
+    gain: f32,
+    buffer: Vec<f32>,
+}
+
+impl<F: Fetcher> GainFetcher<F> {
+    pub fn new(fetcher: F, gain: f32) -> Box<Self> {
+        Box::new(Self {
+            fetcher,
+            gain,
+            buffer: Vec::new(),
+        })
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the factor which the samples are multiplied with.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// Returns the adapted fetcher.
+    pub fn inner(&self) -> &F {
+        &self.fetcher
+    }
+}
+
+impl<F: Fetcher> Fetcher for GainFetcher<F> {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        self.buffer.resize(buf.len(), 0.);
+        let amount_samples = self.fetcher.fetch_samples(&mut self.buffer);
+
+        for (sample, &source_sample) in buf.iter_mut().zip(self.buffer.iter()) {
+            *sample = self.gain * source_sample;
+        }
+
+        amount_samples
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.fetcher.sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        self.fetcher.channels()
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        self.fetcher.timestamp()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.fetcher.is_finished()
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        self.fetcher.latency()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.fetcher.source_name()
+    }
+}
+
+/// Picks (and reorders) channels of a fetcher.
+///
+/// A channel can be picked several times, for example `[0, 0]` turns a mono source into a stereo one.
+pub struct ChannelSelectFetcher<F> {
+    fetcher: F,
+    // the channels of `fetcher` which are provided
+    channels: Box<[u16]>,
+    buffer: Vec<f32>,
+}
+
+impl<F: Fetcher> ChannelSelectFetcher<F> {
+    /// Provides the given channels of `fetcher`: Channel `n` of this fetcher is channel `channels[n]` of `fetcher`.
+    ///
+    /// # Panics
+    /// If `channels` is empty or if it contains a channel which `fetcher` doesn't have.
+    pub fn new(fetcher: F, channels: &[u16]) -> Box<Self> {
+        assert!(!channels.is_empty(), "At least one channel is required");
+        if let Some(channel) = channels
+            .iter()
+            .find(|&&channel| channel >= fetcher.channels())
+        {
+            panic!(
+                "Invalid channel {}, the fetcher has only {} channels",
+                channel,
+                fetcher.channels()
+            );
+        }
+
+        Box::new(Self {
+            fetcher,
+            channels: channels.into(),
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns the adapted fetcher.
+    pub fn inner(&self) -> &F {
+        &self.fetcher
+    }
+}
+
+impl<F: Fetcher> Fetcher for ChannelSelectFetcher<F> {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let source_channels = usize::from(self.fetcher.channels());
+        let amount_frames = buf.len() / self.channels.len();
+
+        self.buffer.resize(amount_frames * source_channels, 0.);
+        let source_samples = self.fetcher.fetch_samples(&mut self.buffer);
+
+        for (frame, source_frame) in buf
+            .chunks_exact_mut(self.channels.len())
+            .zip(self.buffer.chunks_exact(source_channels))
+        {
+            for (sample, &channel) in frame.iter_mut().zip(self.channels.iter()) {
+                *sample = source_frame[usize::from(channel)];
+            }
+        }
+
+        source_samples / source_channels * self.channels.len()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.fetcher.sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels.len() as u16
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        self.fetcher.timestamp()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.fetcher.is_finished()
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        self.fetcher.latency()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.fetcher.source_name()
+    }
+}
+
+/// Converts the samples of a fetcher to another sample rate with linear interpolation.
+///
+/// Useful to mix fetchers with different sample rates with a [MixFetcher](super::MixFetcher).
+/// There's no low-pass filter, so frequencies above half of the new sample rate are mirrored into the spectrum
+/// when the sample rate is lowered.
+pub struct ResampleFetcher<F> {
+    fetcher: F,
+    sample_rate: SampleRate,
+    buffer: Vec<f32>,
+}
+
+impl<F: Fetcher> ResampleFetcher<F> {
+    /// Provides the samples of `fetcher` with the given sample rate.
+    ///
+    /// # Panics
+    /// If `sample_rate` is zero.
+    pub fn new(fetcher: F, sample_rate: SampleRate) -> Box<Self> {
+        assert!(sample_rate.0 > 0, "The sample rate must be positive");
+
+        Box::new(Self {
+            fetcher,
+            sample_rate,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns the adapted fetcher.
+    pub fn inner(&self) -> &F {
+        &self.fetcher
+    }
+
+    /// The amount of source frames per frame of this fetcher.
+    fn ratio(&self) -> f64 {
+        f64::from(self.fetcher.sample_rate().0) / f64::from(self.sample_rate.0)
+    }
+}
+
+impl<F: Fetcher> Fetcher for ResampleFetcher<F> {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let amount_channels = usize::from(self.fetcher.channels());
+        let amount_frames = buf.len() / amount_channels;
+        let ratio = self.ratio();
+
+        // the last frame needs its following source frame for the interpolation
+        let source_frames = (amount_frames as f64 * ratio).ceil() as usize + 1;
+        self.buffer.resize(source_frames * amount_channels, 0.);
+        let source_samples = self.fetcher.fetch_samples(&mut self.buffer);
+
+        for (frame_idx, frame) in buf.chunks_exact_mut(amount_channels).enumerate() {
+            let position = frame_idx as f64 * ratio;
+            let source_idx = position as usize;
+            let t = (position - source_idx as f64) as f32;
+
+            let prev = &self.buffer[source_idx * amount_channels..];
+            let next = &self.buffer[(source_idx + 1).min(source_frames - 1) * amount_channels..];
+            for (channel_idx, sample) in frame.iter_mut().enumerate() {
+                *sample = prev[channel_idx] + t * (next[channel_idx] - prev[channel_idx]);
+            }
+        }
+
+        let new_frames = (source_samples / amount_channels) as f64 / ratio;
+        new_frames.round() as usize * amount_channels
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.fetcher.channels()
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        self.fetcher.timestamp()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.fetcher.is_finished()
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        self.fetcher.latency()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.fetcher.source_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // provides the frame index (of the source) in each channel, multiplied by the channel index + 1
+    struct RampFetcher {
+        amount_channels: u16,
+        sample_rate: u32,
+    }
+
+    impl RampFetcher {
+        fn new(amount_channels: u16, sample_rate: u32) -> Box<Self> {
+            Box::new(Self {
+                amount_channels,
+                sample_rate,
+            })
+        }
+    }
+
+    impl Fetcher for RampFetcher {
+        fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+            let amount_channels = usize::from(self.amount_channels);
+            for (frame_idx, frame) in buf.chunks_exact_mut(amount_channels).enumerate() {
+                for (channel_idx, sample) in frame.iter_mut().enumerate() {
+                    *sample = (frame_idx * (channel_idx + 1)) as f32;
+                }
+            }
+
+            buf.len()
+        }
+
+        fn sample_rate(&self) -> SampleRate {
+            SampleRate(self.sample_rate)
+        }
+
+        fn channels(&self) -> u16 {
+            self.amount_channels
+        }
+    }
+
+    #[test]
+    fn gain_is_applied_once() {
+        let mut fetcher = RampFetcher::new(1, 1_000).gain(0.5);
+
+        let mut buf = [0f32; 4];
+        fetcher.fetch_samples(&mut buf);
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0., 0.5, 1., 1.5]);
+    }
+
+    #[test]
+    fn select_and_reorder_channels() {
+        let mut fetcher = RampFetcher::new(2, 1_000).select_channels(&[1, 0, 1]);
+        assert_eq!(fetcher.channels(), 3);
+
+        let mut buf = [0f32; 6];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0., 0., 0., 2., 1., 2.]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_missing_channel() {
+        RampFetcher::new(2, 1_000).select_channels(&[2]);
+    }
+
+    #[test]
+    fn resample_interpolates() {
+        let mut fetcher = RampFetcher::new(2, 1_000).resample(SampleRate(2_000));
+        assert_eq!(fetcher.sample_rate(), SampleRate(2_000));
+
+        let mut buf = [0f32; 8];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0., 0., 0.5, 1., 1., 2., 1.5, 3.]);
+
+        let mut fetcher = RampFetcher::new(1, 1_000).resample(SampleRate(500));
+        let mut buf = [0f32; 3];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0., 2., 4.]);
+    }
+
+    #[test]
+    fn adapters_can_be_chained() {
+        let fetcher = RampFetcher::new(2, 48_000)
+            .select_channels(&[1])
+            .resample(SampleRate(44_100))
+            .gain(2.);
+
+        assert_eq!(fetcher.channels(), 1);
+        assert_eq!(fetcher.sample_rate(), SampleRate(44_100));
+        assert_eq!(fetcher.inner().inner().inner().channels(), 2);
+    }
+}
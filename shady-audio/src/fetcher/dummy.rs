@@ -1,26 +1,196 @@
 use super::Fetcher;
 
-/// A dummy fetcher which does... nothing.
+/// The sample rate of the [DummyFetcher].
+const SAMPLE_RATE: u32 = 44_100;
+
+/// The samples which a [DummyFetcher] provides.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    /// Only zeros.
+    Silence,
+
+    /// A sine with the given frequency (in Hz).
+    Sine { frequency: f32 },
+
+    /// A square wave with the given frequency (in Hz).
+    Square { frequency: f32 },
+
+    /// White noise. The same seed always leads to the same samples.
+    Noise { seed: u64 },
+}
+
+/// A dummy fetcher which does... nothing (or provides the samples of a [Waveform]).
 /// Mainly used for docs and tests.
+///
+/// All samples are deterministic: Each fetch provides a whole buffer of new samples which continue the samples
+/// of the previous fetch, independent of how much time has passed in between.
+/// Like the other fetchers, the newest frame is at the beginning of the buffer.
+///
+/// # Example
+/// ```rust
+/// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::{DummyFetcher, Waveform}};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+///     2,
+///     Waveform::Sine { frequency: 440. },
+/// ));
+/// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let bars = bar_processor.process_bars(&sample_processor);
+///
+/// assert!(bars[0].iter().any(|&bar| bar > 0.));
+/// ```
 pub struct DummyFetcher {
     amount_channels: u16,
+    waveform: Waveform,
+
+    // the amount of frames which have been provided so far
+    position: u64,
+    // the state of the noise
+    rng: u64,
 }
 
 impl DummyFetcher {
-    /// Creates a new instance of this struct.
+    /// Creates a new instance of this struct which provides silence.
     pub fn new(amount_channels: u16) -> Box<Self> {
-        Box::new(Self { amount_channels })
+        Self::with_waveform(amount_channels, Waveform::Silence)
+    }
+
+    /// Creates a fetcher which provides the given waveform on each channel.
+    pub fn with_waveform(amount_channels: u16, waveform: Waveform) -> Box<Self> {
+        let rng = match waveform {
+            // xorshift gets stuck at zero
+            Waveform::Noise { seed } => seed ^ 0x9e37_79b9_7f4a_7c15,
+            _ => 0,
+        };
+
+        Box::new(Self {
+            amount_channels,
+            waveform,
+            position: 0,
+            rng,
+        })
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Returns the sample of the next frame.
+    fn next_sample(&mut self, frame: u64) -> f32 {
+        let time = frame as f64 / f64::from(SAMPLE_RATE);
+
+        match self.waveform {
+            Waveform::Silence => 0.,
+            Waveform::Sine { frequency } => {
+                (std::f64::consts::TAU * f64::from(frequency) * time).sin() as f32
+            }
+            Waveform::Square { frequency } => {
+                if (f64::from(frequency) * time).fract() < 0.5 {
+                    1.
+                } else {
+                    -1.
+                }
+            }
+            Waveform::Noise { .. } => {
+                self.rng ^= self.rng << 13;
+                self.rng ^= self.rng >> 7;
+                self.rng ^= self.rng << 17;
+
+                // the upper 24 bits fit into the mantissa
+                (self.rng >> 40) as f32 / (1u64 << 23) as f32 - 1.
+            }
+        }
     }
 }
 
 impl Fetcher for DummyFetcher {
-    fn fetch_samples(&mut self, _buf: &mut [f32]) {}
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        if self.waveform == Waveform::Silence {
+            return 0;
+        }
+
+        // the newest frame belongs to the beginning of the buffer
+        for frame in buf
+            .chunks_exact_mut(usize::from(self.amount_channels.max(1)))
+            .rev()
+        {
+            let position = self.position;
+            self.position += 1;
+
+            match self.waveform {
+                // each channel gets its own noise
+                Waveform::Noise { .. } => {
+                    for sample in frame.iter_mut() {
+                        *sample = self.next_sample(position);
+                    }
+                }
+                _ => frame.fill(self.next_sample(position)),
+            }
+        }
+
+        buf.len()
+    }
 
     fn sample_rate(&self) -> cpal::SampleRate {
-        cpal::SampleRate(44_100)
+        cpal::SampleRate(SAMPLE_RATE)
     }
 
     fn channels(&self) -> u16 {
         self.amount_channels
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetch(fetcher: &mut DummyFetcher, len: usize) -> Vec<f32> {
+        let mut buf = vec![0.; len];
+        fetcher.fetch_samples(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let mut a = DummyFetcher::with_waveform(2, Waveform::Noise { seed: 42 });
+        let mut b = DummyFetcher::with_waveform(2, Waveform::Noise { seed: 42 });
+        let mut c = DummyFetcher::with_waveform(2, Waveform::Noise { seed: 0 });
+
+        let samples = fetch(&mut a, 64);
+        assert_eq!(samples, fetch(&mut b, 64));
+        assert_ne!(samples, fetch(&mut c, 64));
+
+        assert!(samples.iter().all(|sample| (-1. ..1.).contains(sample)));
+        // the next fetch continues the noise
+        assert_ne!(samples, fetch(&mut a, 64));
+    }
+
+    #[test]
+    fn periodic_waveforms() {
+        // a quarter of a period per sample
+        let frequency = SAMPLE_RATE as f32 / 4.;
+
+        // the newest samples are at the beginning
+        let mut sine = DummyFetcher::with_waveform(1, Waveform::Sine { frequency });
+        let samples = fetch(&mut sine, 4);
+        for (sample, expected) in samples.iter().zip([-1., 0., 1., 0.]) {
+            assert!((sample - expected).abs() < 1e-6, "{:?}", samples);
+        }
+
+        let mut square = DummyFetcher::with_waveform(2, Waveform::Square { frequency });
+        assert_eq!(fetch(&mut square, 8), [-1., -1., -1., -1., 1., 1., 1., 1.]);
+        // the next fetch continues with the next period
+        assert_eq!(fetch(&mut square, 4), [1., 1., 1., 1.]);
+    }
+
+    #[test]
+    fn silence_keeps_buffer() {
+        let mut fetcher = DummyFetcher::new(1);
+
+        let mut buf = [0.5; 4];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0.5; 4]);
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    num::NonZero,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use cpal::{
+    traits::{DeviceTrait, StreamTrait},
+    SampleRate,
+};
+use tracing::{debug, warn};
+
+use super::Fetcher;
+
+/// Errors which can occur while creating a [crate::fetcher::FileFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum FileFetcherError {
+    /// The file isn't a wav file. Other formats (like mp3) can't be decoded.
+    #[error("Unsupported audio file {0}: Only wav files are supported.")]
+    UnsupportedFormat(PathBuf),
+
+    /// The file couldn't be read or isn't a (supported) wav file.
+    #[error("Couldn't decode the audio file: {0}")]
+    Decode(#[from] hound::Error),
+
+    #[error("Couldn't get supported output config of device: {0}")]
+    SupportedStreamConfigError(#[from] cpal::SupportedStreamConfigsError),
+
+    #[error("Couldn't build an audio stream:\n{0}")]
+    BuildOutputStreamError(#[from] cpal::BuildStreamError),
+
+    #[error("Couldn't start the playback:\n{0}")]
+    PlayStreamError(#[from] cpal::PlayStreamError),
+}
+
+pub struct Descriptor {
+    /// The path to the audio file. Only wav files are supported.
+    pub path: PathBuf,
+
+    /// The device which should play the file.
+    ///
+    /// If set, the fetcher provides the samples which have been played by the device,
+    /// so the visualization stays in sync with what you hear.
+    /// Otherwise the file is only visualized (in real time).
+    ///
+    /// The file isn't resampled, so it's only visualized as well if the device can't play its amount of channels
+    /// with its sample rate (see [File::is_playing]).
+    pub playback: Option<cpal::Device>,
+
+    /// Drive the fetcher by frames instead of the time, for example to render a video offline.
+    ///
+    /// If set, the `n`-th fetch provides the samples until exactly `n / frame_rate` seconds (the first fetch is
+    /// at the start of the file), regardless of how long the rendering of a frame takes. So the visualization
+    /// is in sync with the audio track sample by sample and it's the same on each run.
+    /// `playback` is ignored in this mode.
+    pub frame_rate: Option<NonZero<u32>>,
+}
+
+impl Descriptor {
+    /// Visualizes the given file without playing it.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            playback: None,
+            frame_rate: None,
+        }
+    }
+}
+
+/// Fetcher for wav files. Other formats aren't supported.
+///
+/// The samples are provided in real time, so the visualization takes as long as the file
+/// (unless [Descriptor::frame_rate] is set).
+///
+/// # Example
+/// ```rust,no_run
+/// use shady_audio::{SampleProcessor, fetcher::{FileFetcher, FileFetcherDescriptor}};
+///
+/// let mut descriptor = FileFetcherDescriptor::new("song.wav");
+/// // play the file on the default output device
+/// descriptor.playback = shady_audio::util::get_default_device(shady_audio::util::DeviceType::Output);
+///
+/// let mut sample_processor = SampleProcessor::new(FileFetcher::new(descriptor).unwrap());
+/// while !sample_processor.is_finished() {
+///     sample_processor.process_next_samples();
+/// }
+/// ```
+pub struct File {
+    name: String,
+
+    // interleaved
+    samples: Arc<[f32]>,
+    sample_rate: SampleRate,
+    channels: u16,
+
+    // the amount of samples (not frames) which have been played
+    position: Arc<AtomicUsize>,
+    // the position of the last call of `fetch_samples`
+    fetched: usize,
+
+    clock: Clock,
+}
+
+enum Clock {
+    // the output stream moves the position forward
+    Playback(cpal::Stream),
+    // the position is computed from the time since the first fetch
+    RealTime(Option<Instant>),
+    // the position is computed from the amount of fetches so far
+    Frames {
+        frame_rate: NonZero<u32>,
+        fetches: u64,
+    },
+}
+
+impl File {
+    pub fn new(desc: Descriptor) -> Result<Box<Self>, FileFetcherError> {
+        let (samples, spec) = decode(&desc.path)?;
+        debug!("Decoded {:?}: {:?}", desc.path, spec);
+
+        let sample_rate = SampleRate(spec.sample_rate);
+        let channels = spec.channels;
+        let position = Arc::new(AtomicUsize::new(0));
+
+        let clock = match (desc.frame_rate, desc.playback) {
+            (Some(frame_rate), _) => Clock::Frames {
+                frame_rate,
+                fetches: 0,
+            },
+            (None, Some(device)) => {
+                match play(
+                    &device,
+                    channels,
+                    sample_rate,
+                    samples.clone(),
+                    position.clone(),
+                )? {
+                    Some(stream) => Clock::Playback(stream),
+                    None => {
+                        warn!(
+                            "The output device can't play {} channel(s) with {} Hz, so {:?} is only visualized.",
+                            channels, sample_rate.0, desc.path
+                        );
+                        Clock::RealTime(None)
+                    }
+                }
+            }
+            (None, None) => Clock::RealTime(None),
+        };
+
+        let name = desc
+            .path
+            .file_name()
+            .unwrap_or(desc.path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+
+        Ok(Box::new(Self {
+            name,
+            samples,
+            sample_rate,
+            channels,
+            position,
+            fetched: 0,
+            clock,
+        }))
+    }
+
+    /// Returns `true` if the file is played on the device of [Descriptor::playback].
+    pub fn is_playing(&self) -> bool {
+        matches!(self.clock, Clock::Playback(_))
+    }
+
+    /// Returns the position (in samples) which should be visualized now.
+    fn current_position(&mut self) -> usize {
+        match &mut self.clock {
+            Clock::Playback(_) => self.position.load(Ordering::Relaxed),
+            Clock::RealTime(start) => {
+                let elapsed = start.get_or_insert_with(Instant::now).elapsed();
+                let frames = (elapsed.as_secs_f64() * f64::from(self.sample_rate.0)) as usize;
+
+                let position = (frames * usize::from(self.channels)).min(self.samples.len());
+                self.position.store(position, Ordering::Relaxed);
+                position
+            }
+            Clock::Frames {
+                frame_rate,
+                fetches,
+            } => {
+                let position =
+                    crate::offline::frame_position(*fetches, self.sample_rate, *frame_rate)
+                        * usize::from(self.channels);
+                *fetches += 1;
+
+                let position = position.min(self.samples.len());
+                self.position.store(position, Ordering::Relaxed);
+                position
+            }
+        }
+    }
+}
+
+impl Drop for File {
+    /// Stops the playback before it gets dropped.
+    fn drop(&mut self) {
+        if let Clock::Playback(stream) = &self.clock {
+            if let Err(err) = stream.pause() {
+                debug!("Couldn't stop the playback: {}", err);
+            }
+        }
+    }
+}
+
+impl Fetcher for File {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let position = self.current_position();
+        let new_samples = &self.samples[self.fetched..position];
+        self.fetched = position;
+
+        // only the newest samples fit into the buffer
+        let amount_samples = buf.len().min(new_samples.len());
+        let inserted = &new_samples[new_samples.len() - amount_samples..];
+
+        let buf_len = buf.len();
+        buf.copy_within(..buf_len - amount_samples, amount_samples);
+        buf[..amount_samples].copy_from_slice(inserted);
+
+        new_samples.len()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        let frames = self.position.load(Ordering::Relaxed) / usize::from(self.channels);
+        Some(Duration::from_secs_f64(
+            frames as f64 / f64::from(self.sample_rate.0),
+        ))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position.load(Ordering::Relaxed) >= self.samples.len()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
+
+/// Decodes the whole file into interleaved `f32` samples.
+///
+/// Returns [FileFetcherError::UnsupportedFormat] if the file has an extension other than `wav`.
+pub(crate) fn decode(path: &Path) -> Result<(Arc<[f32]>, hound::WavSpec), FileFetcherError> {
+    let is_wav = path.extension().is_none_or(|extension| {
+        extension.eq_ignore_ascii_case("wav") || extension.eq_ignore_ascii_case("wave")
+    });
+    if !is_wav {
+        return Err(FileFetcherError::UnsupportedFormat(path.to_path_buf()));
+    }
+
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / max))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok((samples, spec))
+}
+
+/// Starts an output stream which plays the samples and moves `position` forward.
+///
+/// Returns `None` if the device can't play the samples without resampling them.
+fn play(
+    device: &cpal::Device,
+    channels: u16,
+    sample_rate: SampleRate,
+    samples: Arc<[f32]>,
+    position: Arc<AtomicUsize>,
+) -> Result<Option<cpal::Stream>, FileFetcherError> {
+    let Some(stream_config) = device.supported_output_configs()?.find(|conf| {
+        conf.channels() == channels
+            && conf.sample_format() == cpal::SampleFormat::F32
+            && (conf.min_sample_rate()..=conf.max_sample_rate()).contains(&sample_rate)
+    }) else {
+        return Ok(None);
+    };
+    let stream_config = stream_config.with_sample_rate(sample_rate).config();
+
+    debug!("Playback stream config: {:?}", stream_config);
+
+    let stream = device.build_output_stream(
+        &stream_config,
+        move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+            let start = position.load(Ordering::Relaxed);
+            let end = (start + data.len()).min(samples.len());
+            let amount = end - start;
+
+            data[..amount].copy_from_slice(&samples[start..end]);
+            data[amount..].fill(0.);
+
+            position.store(end, Ordering::Relaxed);
+        },
+        |err| panic!("`shady-audio`: {}", err),
+        None,
+    )?;
+    stream.play()?;
+
+    Ok(Some(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a path within a directory which is unique to this test run.
+    fn temp_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("shady-audio-file-fetcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn write_wav(path: &Path, samples: &[i16]) {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 1_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn reject_other_formats() {
+        let path = temp_path("song.mp3");
+        std::fs::write(&path, b"ID3").unwrap();
+
+        let result = File::new(Descriptor::new(&path));
+        assert!(matches!(
+            result,
+            Err(FileFetcherError::UnsupportedFormat(unsupported)) if unsupported == path
+        ));
+    }
+
+    #[test]
+    fn provides_samples_in_real_time() {
+        let path = temp_path("real-time.wav");
+        // 10 frames => 10ms
+        let samples = (0..20).map(|i| i * 1_000).collect::<Vec<i16>>();
+        write_wav(&path, &samples);
+
+        let mut fetcher = File::new(Descriptor::new(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(fetcher.channels(), 2);
+        assert_eq!(fetcher.sample_rate(), SampleRate(1_000));
+
+        let mut buf = [0f32; 8];
+        fetcher.fetch_samples(&mut buf);
+        assert!(!fetcher.is_finished());
+
+        std::thread::sleep(Duration::from_millis(20));
+        fetcher.fetch_samples(&mut buf);
+
+        // the newest samples are at the beginning of the buffer
+        let expected = samples[12..]
+            .iter()
+            .map(|&sample| sample as f32 / 32_768.)
+            .collect::<Vec<f32>>();
+        assert_eq!(buf.as_slice(), expected.as_slice());
+
+        assert!(fetcher.is_finished());
+        assert_eq!(fetcher.timestamp(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn provides_samples_frame_by_frame() {
+        let path = temp_path("frames.wav");
+        // 10 frames => 10ms
+        let samples = (0..20).map(|i| i * 1_000).collect::<Vec<i16>>();
+        write_wav(&path, &samples);
+
+        let mut fetcher = File::new(Descriptor {
+            // 4ms per frame
+            frame_rate: NonZero::new(250),
+            ..Descriptor::new(&path)
+        })
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut buf = [0f32; 4];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(fetcher.timestamp(), Some(Duration::ZERO));
+
+        // doesn't depend on the time between the fetches
+        std::thread::sleep(Duration::from_millis(20));
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(fetcher.timestamp(), Some(Duration::from_millis(4)));
+
+        let expected = samples[4..8]
+            .iter()
+            .map(|&sample| sample as f32 / 32_768.)
+            .collect::<Vec<f32>>();
+        assert_eq!(buf.as_slice(), expected.as_slice());
+        assert!(!fetcher.is_finished());
+
+        for _ in 0..2 {
+            fetcher.fetch_samples(&mut buf);
+        }
+        assert!(fetcher.is_finished());
+    }
+}
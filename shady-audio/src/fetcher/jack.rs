@@ -0,0 +1,290 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+use cpal::SampleRate;
+use jack::{
+    AudioIn, Client, ClientOptions, ClientStatus, Control, Port, PortFlags, PortSpec, ProcessScope,
+};
+use rtrb::{Consumer, Producer, RingBuffer};
+use tracing::{debug, warn};
+
+use super::Fetcher;
+
+const DEFAULT_CLIENT_NAME: &str = "shady-audio";
+
+/// Errors which can occur while creating [crate::fetcher::JackFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum JackError {
+    /// The JACK library couldn't be loaded or the server isn't running.
+    #[error("Couldn't create the JACK client: {0}")]
+    CreateClient(jack::Error),
+
+    #[error("Couldn't register the input port of channel {channel}: {err}")]
+    RegisterPort { channel: usize, err: jack::Error },
+
+    #[error("Couldn't activate the JACK client: {0}")]
+    Activate(jack::Error),
+
+    /// The given channel doesn't exist.
+    #[error("The fetcher doesn't have a channel {0}.")]
+    InvalidChannel(usize),
+
+    #[error("Couldn't connect `{port}` to channel {channel}: {err}")]
+    Connect {
+        port: String,
+        channel: usize,
+        err: jack::Error,
+    },
+}
+
+/// Configures [crate::fetcher::JackFetcher].
+#[derive(Debug, Clone)]
+pub struct Descriptor {
+    /// The name of the JACK client. The server might append a suffix if the name is already taken.
+    pub client_name: String,
+
+    /// The ports to connect to (for example `system:capture_1` or the output of your DAW).
+    ///
+    /// Each port is connected to its own input port, so each port is one channel.
+    /// If it's empty, the fetcher has one unconnected channel which you can connect
+    /// with [JackFetcher::connect] or with a patchbay like `qjackctl`.
+    pub ports: Vec<String>,
+}
+
+impl Default for Descriptor {
+    fn default() -> Self {
+        Self {
+            client_name: DEFAULT_CLIENT_NAME.to_string(),
+            ports: Vec::new(),
+        }
+    }
+}
+
+/// The values which the callbacks of the client report without blocking.
+struct ClientStats {
+    xruns: AtomicU64,
+    // the amount of samples which didn't fit into the ring buffer anymore
+    dropped_samples: AtomicU64,
+    shutdown: AtomicBool,
+}
+
+struct Notifications {
+    stats: Arc<ClientStats>,
+}
+
+impl jack::NotificationHandler for Notifications {
+    fn shutdown(&mut self, status: ClientStatus, reason: &str) {
+        warn!(
+            "`shady-audio`: The JACK server shut the client down ({:?}): {}",
+            status, reason
+        );
+        self.stats.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    fn xrun(&mut self, _: &Client) -> Control {
+        self.stats.xruns.fetch_add(1, Ordering::Relaxed);
+        Control::Continue
+    }
+}
+
+struct Process {
+    ports: Vec<Port<AudioIn>>,
+    producer: Producer<f32>,
+    stats: Arc<ClientStats>,
+}
+
+impl jack::ProcessHandler for Process {
+    fn process(&mut self, _: &Client, ps: &ProcessScope) -> Control {
+        let channels = self.ports.len();
+        let amount_frames = ps.n_frames() as usize;
+
+        // only write whole frames to keep the channels aligned
+        let amount_samples = (self.producer.slots() / channels).min(amount_frames) * channels;
+        if let Ok(chunk) = self.producer.write_chunk_uninit(amount_samples) {
+            let ports = &self.ports;
+            let frames = (0..amount_frames)
+                .flat_map(|frame| ports.iter().map(move |port| port.as_slice(ps)[frame]));
+
+            chunk.fill_from_iter(frames);
+        }
+
+        let dropped = amount_frames * channels - amount_samples;
+        if dropped > 0 {
+            self.stats
+                .dropped_samples
+                .fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+
+        Control::Continue
+    }
+}
+
+/// Fetches the audio of the connected ports of its own JACK client.
+///
+/// The samples are passed from the process callback of JACK through a lock-free ring buffer,
+/// so the callback never waits for the render thread.
+///
+/// # Example
+/// ```no_run
+/// use shady_audio::{SampleProcessor, fetcher::{JackFetcher, JackFetcherDescriptor}};
+///
+/// let fetcher = JackFetcher::new(&JackFetcherDescriptor {
+///     ports: vec!["system:capture_1".into(), "system:capture_2".into()],
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// println!("Available ports: {:?}", fetcher.available_ports());
+/// let mut sample_processor = SampleProcessor::new(fetcher);
+/// ```
+pub struct Jack {
+    client: jack::AsyncClient<Notifications, Process>,
+    samples: Consumer<f32>,
+    stats: Arc<ClientStats>,
+    sample_rate: SampleRate,
+
+    // the full names of the input ports of each channel
+    port_names: Vec<String>,
+    client_name: String,
+}
+
+impl Jack {
+    pub fn new(desc: &Descriptor) -> Result<Box<Self>, JackError> {
+        let (client, status) = Client::new(&desc.client_name, ClientOptions::NO_START_SERVER)
+            .map_err(JackError::CreateClient)?;
+        debug!("JACK client status: {:?}", status);
+
+        let channels = desc.ports.len().max(1);
+        let ports = (0..channels)
+            .map(|channel| {
+                client
+                    .register_port(&format!("in_{}", channel + 1), AudioIn)
+                    .map_err(|err| JackError::RegisterPort { channel, err })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let port_names = ports
+            .iter()
+            .map(|port| port.name().unwrap_or_default())
+            .collect();
+        let client_name = client.name().to_string();
+        let sample_rate = SampleRate(client.sample_rate() as u32);
+
+        // one second of samples
+        let (producer, samples) = RingBuffer::new(sample_rate.0 as usize * channels);
+        let stats = Arc::new(ClientStats {
+            xruns: AtomicU64::new(0),
+            dropped_samples: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let client = client
+            .activate_async(
+                Notifications {
+                    stats: stats.clone(),
+                },
+                Process {
+                    ports,
+                    producer,
+                    stats: stats.clone(),
+                },
+            )
+            .map_err(JackError::Activate)?;
+
+        let fetcher = Self {
+            client,
+            samples,
+            stats,
+            sample_rate,
+            port_names,
+            client_name,
+        };
+
+        for (channel, port) in desc.ports.iter().enumerate() {
+            fetcher.connect(port, channel)?;
+        }
+
+        Ok(Box::new(fetcher))
+    }
+
+    /// Connects the given port (for example `system:capture_1`) to the input port of `channel`.
+    ///
+    /// A channel can be connected to multiple ports, JACK mixes them together.
+    pub fn connect(&self, port: &str, channel: usize) -> Result<(), JackError> {
+        let input = self
+            .port_names
+            .get(channel)
+            .ok_or(JackError::InvalidChannel(channel))?;
+
+        match self.client.as_client().connect_ports_by_name(port, input) {
+            Ok(()) | Err(jack::Error::PortAlreadyConnected(..)) => Ok(()),
+            Err(err) => Err(JackError::Connect {
+                port: port.to_string(),
+                channel,
+                err,
+            }),
+        }
+    }
+
+    /// Returns the names of all audio ports which can be connected to the fetcher.
+    pub fn available_ports(&self) -> Vec<String> {
+        self.client
+            .as_client()
+            .ports(None, Some(AudioIn.jack_port_type()), PortFlags::IS_OUTPUT)
+    }
+
+    /// Returns how many xruns (buffer over- or underruns of the JACK server) have occurred
+    /// since the fetcher has been created.
+    pub fn xruns(&self) -> u64 {
+        self.stats.xruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many samples have been dropped since the fetcher has been created because
+    /// [Fetcher::fetch_samples] hasn't been called often enough.
+    pub fn dropped_samples(&self) -> u64 {
+        self.stats.dropped_samples.load(Ordering::Relaxed)
+    }
+
+    /// Returns `false` if the JACK server has shut the client down. The fetcher provides silence afterwards.
+    pub fn is_running(&self) -> bool {
+        !self.stats.shutdown.load(Ordering::Relaxed)
+    }
+}
+
+impl Fetcher for Jack {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let buf_len = buf.len();
+        // keep the channels of the samples aligned
+        let channels = self.port_names.len();
+        let amount_samples = buf_len.min(self.samples.slots()) / channels * channels;
+
+        let Ok(chunk) = self.samples.read_chunk(amount_samples) else {
+            return 0;
+        };
+
+        // move the previous samples to the right to make space for the new ones
+        buf.copy_within(..buf_len - amount_samples, amount_samples);
+
+        let (first, second) = chunk.as_slices();
+        buf[..first.len()].copy_from_slice(first);
+        buf[first.len()..amount_samples].copy_from_slice(second);
+
+        chunk.commit_all();
+
+        amount_samples
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.port_names.len() as u16
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        Some(&self.client_name)
+    }
+}
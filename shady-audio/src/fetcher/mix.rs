@@ -0,0 +1,304 @@
+use std::time::Duration;
+
+use cpal::SampleRate;
+
+use super::Fetcher;
+
+/// Errors which can occur while creating a [MixFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum MixFetcherError {
+    /// There's nothing to mix.
+    #[error("At least one source is required.")]
+    NoSources,
+
+    /// The sources don't have the same sample rate.
+    #[error("All sources need the same sample rate but got {expected} Hz and {got} Hz.")]
+    SampleRateMismatch { expected: u32, got: u32 },
+}
+
+/// A source of the [MixFetcher].
+pub struct MixSource {
+    /// The fetcher of the source.
+    pub fetcher: Box<dyn Fetcher>,
+
+    /// The factor which the samples of the source are multiplied with.
+    pub gain: f32,
+
+    // the (interleaved) samples of the source
+    buffer: Vec<f32>,
+}
+
+impl MixSource {
+    /// Creates a new source with the given gain.
+    pub fn new(fetcher: Box<dyn Fetcher>, gain: f32) -> Self {
+        Self {
+            fetcher,
+            gain,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Mixes the samples of multiple fetchers (for example a microphone and the desktop audio) into one.
+///
+/// All sources must have the same sample rate. The mix has as many channels as the source with the most channels,
+/// sources with less channels are spread over the channels of the mix (so a mono microphone is audible on each channel).
+///
+/// The mix is finished once all of its sources are finished and its timestamp is the one of the furthest source.
+///
+/// # Example
+/// ```rust
+/// use shady_audio::{SampleProcessor, fetcher::{DummyFetcher, MixFetcher, MixSource}};
+///
+/// let fetcher = MixFetcher::new(vec![
+///     // the desktop audio
+///     MixSource::new(DummyFetcher::new(2), 1.0),
+///     // the microphone a bit louder
+///     MixSource::new(DummyFetcher::new(1), 1.5),
+/// ])
+/// .unwrap();
+///
+/// let sample_processor = SampleProcessor::new(fetcher);
+/// ```
+pub struct MixFetcher {
+    sources: Vec<MixSource>,
+    sample_rate: SampleRate,
+    amount_channels: u16,
+}
+
+impl MixFetcher {
+    /// Creates a new fetcher which mixes the given sources.
+    pub fn new(sources: Vec<MixSource>) -> Result<Box<Self>, MixFetcherError> {
+        let sample_rate = sources
+            .first()
+            .ok_or(MixFetcherError::NoSources)?
+            .fetcher
+            .sample_rate();
+
+        if let Some(source) = sources
+            .iter()
+            .find(|source| source.fetcher.sample_rate() != sample_rate)
+        {
+            return Err(MixFetcherError::SampleRateMismatch {
+                expected: sample_rate.0,
+                got: source.fetcher.sample_rate().0,
+            });
+        }
+
+        let amount_channels = sources
+            .iter()
+            .map(|source| source.fetcher.channels())
+            .max()
+            .unwrap_or(1);
+
+        Ok(Box::new(Self {
+            sources,
+            sample_rate,
+            amount_channels,
+        }))
+    }
+
+    /// Returns the sources of the mix.
+    pub fn sources(&self) -> &[MixSource] {
+        &self.sources
+    }
+
+    /// Sets the gain of the source with the given index.
+    ///
+    /// Returns `false` (and does nothing) if there's no source with the given index.
+    pub fn set_gain(&mut self, source_idx: usize, gain: f32) -> bool {
+        match self.sources.get_mut(source_idx) {
+            Some(source) => {
+                source.gain = gain;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Fetcher for MixFetcher {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let out_channels = usize::from(self.amount_channels);
+        let amount_frames = buf.len() / out_channels;
+
+        buf.fill(0.);
+
+        let mut new_frames = 0;
+        for source in self.sources.iter_mut() {
+            let source_channels = usize::from(source.fetcher.channels());
+            // the buffer keeps the previous samples of the source
+            source.buffer.resize(amount_frames * source_channels, 0.);
+            let source_samples = source.fetcher.fetch_samples(&mut source.buffer);
+            new_frames = new_frames.max(source_samples / source_channels);
+
+            for (out_frame, source_frame) in buf
+                .chunks_exact_mut(out_channels)
+                .zip(source.buffer.chunks_exact(source_channels))
+            {
+                for (channel_idx, sample) in out_frame.iter_mut().enumerate() {
+                    *sample += source.gain * source_frame[channel_idx % source_channels];
+                }
+            }
+        }
+
+        new_frames * out_channels
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.amount_channels
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        self.sources
+            .iter()
+            .filter_map(|source| source.fetcher.timestamp())
+            .max()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.sources
+            .iter()
+            .all(|source| source.fetcher.is_finished())
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        self.sources
+            .iter()
+            .filter_map(|source| source.fetcher.latency())
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ConstFetcher {
+        value: f32,
+        amount_channels: u16,
+        sample_rate: u32,
+    }
+
+    impl ConstFetcher {
+        fn new(value: f32, amount_channels: u16, sample_rate: u32) -> Box<Self> {
+            Box::new(Self {
+                value,
+                amount_channels,
+                sample_rate,
+            })
+        }
+    }
+
+    impl Fetcher for ConstFetcher {
+        fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+            buf.fill(self.value);
+            buf.len()
+        }
+
+        fn sample_rate(&self) -> SampleRate {
+            SampleRate(self.sample_rate)
+        }
+
+        fn channels(&self) -> u16 {
+            self.amount_channels
+        }
+    }
+
+    // provides `len` frames of silence
+    struct FiniteFetcher {
+        len: usize,
+        position: usize,
+    }
+
+    impl FiniteFetcher {
+        fn new(len: usize) -> Box<Self> {
+            Box::new(Self { len, position: 0 })
+        }
+    }
+
+    impl Fetcher for FiniteFetcher {
+        fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+            let amount_frames = buf.len().min(self.len - self.position);
+            buf.copy_within(..buf.len() - amount_frames, amount_frames);
+            buf[..amount_frames].fill(0.);
+
+            self.position += amount_frames;
+            amount_frames
+        }
+
+        fn sample_rate(&self) -> SampleRate {
+            SampleRate(1_000)
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn timestamp(&self) -> Option<Duration> {
+            Some(Duration::from_millis(self.position as u64))
+        }
+
+        fn is_finished(&self) -> bool {
+            self.position == self.len
+        }
+    }
+
+    #[test]
+    fn mixes_with_gain() {
+        let mut fetcher = MixFetcher::new(vec![
+            MixSource::new(ConstFetcher::new(0.5, 2, 44_100), 1.),
+            MixSource::new(ConstFetcher::new(0.25, 1, 44_100), 2.),
+        ])
+        .unwrap();
+
+        assert_eq!(fetcher.channels(), 2);
+
+        let mut buf = [0f32; 8];
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [1.; 8]);
+
+        assert!(fetcher.set_gain(1, 0.));
+        assert!(!fetcher.set_gain(2, 0.));
+        fetcher.fetch_samples(&mut buf);
+        assert_eq!(buf, [0.5; 8]);
+    }
+
+    #[test]
+    fn sample_rate_mismatch() {
+        let result = MixFetcher::new(vec![
+            MixSource::new(ConstFetcher::new(0., 2, 44_100), 1.),
+            MixSource::new(ConstFetcher::new(0., 1, 48_000), 1.),
+        ]);
+
+        assert!(matches!(
+            result,
+            Err(MixFetcherError::SampleRateMismatch {
+                expected: 44_100,
+                got: 48_000
+            })
+        ));
+    }
+
+    #[test]
+    fn finishes_with_all_sources() {
+        let mut fetcher = MixFetcher::new(vec![
+            MixSource::new(FiniteFetcher::new(4), 1.),
+            MixSource::new(FiniteFetcher::new(8), 1.),
+        ])
+        .unwrap();
+
+        let mut buf = [0f32; 4];
+        fetcher.fetch_samples(&mut buf);
+        assert!(!fetcher.is_finished());
+        assert_eq!(fetcher.timestamp(), Some(Duration::from_millis(4)));
+
+        fetcher.fetch_samples(&mut buf);
+        assert!(fetcher.is_finished());
+        assert_eq!(fetcher.timestamp(), Some(Duration::from_millis(8)));
+    }
+}
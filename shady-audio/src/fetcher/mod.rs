@@ -1,13 +1,29 @@
 //! Each struct here can be used to fetch the audio data from various sources.
 //! Pick the one you need to fetch from.
+//!
+//! The samples of a fetcher can be transformed with the adapters of [FetcherExt].
+mod adapters;
 mod dummy;
+mod file;
+#[cfg(feature = "jack")]
+mod jack;
+mod mix;
 mod system_audio;
 
+use std::time::Duration;
+
 use cpal::SampleRate;
 
-pub use dummy::DummyFetcher;
+pub use adapters::{ChannelSelectFetcher, FetcherExt, GainFetcher, ResampleFetcher};
+pub use dummy::{DummyFetcher, Waveform};
+pub(crate) use file::decode;
+pub use file::{Descriptor as FileFetcherDescriptor, File as FileFetcher, FileFetcherError};
+#[cfg(feature = "jack")]
+pub use jack::{Descriptor as JackFetcherDescriptor, Jack as JackFetcher, JackError};
+pub use mix::{MixFetcher, MixFetcherError, MixSource};
 pub use system_audio::{
-    Descriptor as SystemAudioFetcherDescriptor, SystemAudio as SystemAudioFetcher, SystemAudioError,
+    ConnectionState, Descriptor as SystemAudioFetcherDescriptor, SystemAudio as SystemAudioFetcher,
+    SystemAudioError,
 };
 
 /// Interface for all structs (fetchers) which are listed in the [fetcher module](crate::fetcher).
@@ -20,11 +36,127 @@ pub trait Fetcher {
     /// Make space in `buf` for your `n` samples in the beginnig of `buf`:
     /// 1. `buf[n..] = buf[..buf.len() - n]`.
     /// 2. `buf[..n] = your_samples[..]`
-    fn fetch_samples(&mut self, buf: &mut [f32]);
+    ///
+    /// Returns `n`, the amount of new samples. It's larger than `buf.len()` if more samples arrived
+    /// since the last call than fit into `buf` (only the newest ones are inserted then).
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize;
 
     /// Returns the sample rate of the fetcher/audio source.
     fn sample_rate(&self) -> SampleRate;
 
     /// Returns the amount of channels which are used from the fetcher.
     fn channels(&self) -> u16;
+
+    /// Returns the position of the newest fetched sample within the audio stream.
+    ///
+    /// Returns `None` if the source doesn't have a position (for example live audio).
+    fn timestamp(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns `true` if the source won't provide any new samples (for example if the end of an audio file has been reached).
+    ///
+    /// Live sources never finish.
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    /// Returns the time between capturing the newest fetched sample and passing it to the fetcher
+    /// or `None` if the source can't measure it.
+    fn latency(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns a human readable name of the source (like the name of the audio device or the file).
+    fn source_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<F: Fetcher + ?Sized> Fetcher for Box<F> {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        (**self).fetch_samples(buf)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        (**self).sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        (**self).channels()
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        (**self).timestamp()
+    }
+
+    fn is_finished(&self) -> bool {
+        (**self).is_finished()
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        (**self).latency()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        (**self).source_name()
+    }
+}
+
+/// Fills each batch with the samples of a function of the batch index and the frame index within the batch.
+///
+/// Shared by the tests which need a signal which the [DummyFetcher] doesn't provide.
+#[cfg(test)]
+pub(crate) struct FnFetcher {
+    amount_channels: u16,
+    sample: Box<dyn Fn(u32, usize) -> f32>,
+
+    batches: u32,
+    // the timestamp advances by this duration per batch
+    batch_duration: Option<Duration>,
+}
+
+#[cfg(test)]
+impl FnFetcher {
+    pub fn new(amount_channels: u16, sample: impl Fn(u32, usize) -> f32 + 'static) -> Box<Self> {
+        Box::new(Self {
+            amount_channels,
+            sample: Box::new(sample),
+            batches: 0,
+            batch_duration: None,
+        })
+    }
+
+    /// Lets the fetcher provide timestamps which advance by `duration` per batch.
+    pub fn with_batch_duration(mut self: Box<Self>, duration: Duration) -> Box<Self> {
+        self.batch_duration = Some(duration);
+        self
+    }
+}
+
+#[cfg(test)]
+impl Fetcher for FnFetcher {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        for (frame_idx, frame) in buf
+            .chunks_exact_mut(usize::from(self.amount_channels))
+            .enumerate()
+        {
+            frame.fill((self.sample)(self.batches, frame_idx));
+        }
+        self.batches += 1;
+
+        buf.len()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        crate::DEFAULT_SAMPLE_RATE
+    }
+
+    fn channels(&self) -> u16 {
+        self.amount_channels
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        self.batch_duration.map(|duration| duration * self.batches)
+    }
 }
@@ -1,46 +1,74 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use cpal::{
     traits::{DeviceTrait, StreamTrait},
     SampleRate, SupportedStreamConfigRange,
 };
-use tracing::{debug, instrument};
+use rtrb::{Consumer, Producer, RingBuffer};
+use tracing::{debug, instrument, warn};
 
 use crate::DEFAULT_SAMPLE_RATE;
 
 use super::Fetcher;
 
-struct SampleBuffer {
-    buffer: Box<[f32]>,
-    length: usize,
-    capacity: usize,
-}
+// the time between the first reconnection attempts, doubled after each failed attempt
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
 
-impl SampleBuffer {
-    pub fn new(capacity: usize) -> Self {
-        let buffer = vec![0.; capacity].into_boxed_slice();
+// `u64::MAX` nanoseconds in `StreamStats::latency` mean that the latency is unknown
+const UNKNOWN_LATENCY: u64 = u64::MAX;
+
+/// The values which the callback of the stream reports without blocking.
+struct StreamStats {
+    // the latency of the last callback of the stream in nanoseconds
+    latency: AtomicU64,
+    // the amount of samples which didn't fit into the ring buffer anymore
+    dropped_samples: AtomicU64,
+}
 
+impl StreamStats {
+    fn new() -> Self {
         Self {
-            buffer,
-            capacity,
-            length: 0,
+            latency: AtomicU64::new(UNKNOWN_LATENCY),
+            dropped_samples: AtomicU64::new(0),
         }
     }
 
-    /// Pushes the given data to the front of `buffer` and moves the current data to the right.
-    /// Basically a `VecDeque::push_before` just on a `Box<[f32]>`.
-    pub fn push_before(&mut self, data: &[f32]) {
-        let data_len = data.len();
-        let new_len = std::cmp::min(self.capacity, self.length + data_len);
-        let len_new_data = new_len - self.length;
+    fn latency(&self) -> Option<Duration> {
+        match self.latency.load(Ordering::Relaxed) {
+            UNKNOWN_LATENCY => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
 
-        // move the current values to the right
-        self.buffer.copy_within(..self.length, len_new_data);
+    fn set_latency(&self, latency: Option<Duration>) {
+        let nanos = latency.map_or(UNKNOWN_LATENCY, |latency| {
+            u64::try_from(latency.as_nanos()).unwrap_or(UNKNOWN_LATENCY - 1)
+        });
+        self.latency.store(nanos, Ordering::Relaxed);
+    }
+}
 
-        // write the new data into it
-        self.buffer[..len_new_data].copy_from_slice(&data[..len_new_data]);
+/// Writes as many samples into the ring buffer as there are free slots. The remaining ones are counted as dropped.
+///
+/// Called within the audio callback, so it must never block.
+fn push_samples(producer: &mut Producer<f32>, data: &[f32], stats: &StreamStats) {
+    let amount = producer.slots().min(data.len());
+    if let Ok(chunk) = producer.write_chunk_uninit(amount) {
+        chunk.fill_from_iter(data.iter().copied());
+    }
 
-        self.length = new_len;
+    let dropped = data.len() - amount;
+    if dropped > 0 {
+        stats
+            .dropped_samples
+            .fetch_add(dropped as u64, Ordering::Relaxed);
     }
 }
 
@@ -51,6 +79,10 @@ pub enum SystemAudioError {
     #[error("Couldn't retrieve default output dev")]
     NoDefaultDevice,
 
+    /// The device of the fetcher isn't available (anymore).
+    #[error("The audio device isn't available.")]
+    DeviceNotAvailable,
+
     /// No default configuration could be found of the default output device.
     #[error("Couldn't retrieve any config of the output stream of the default device.")]
     NoAvailableOutputConfigs,
@@ -60,48 +92,115 @@ pub enum SystemAudioError {
 
     #[error("Couldn't build an audio stream:\n{0}")]
     BuildOutputStreamError(#[from] cpal::BuildStreamError),
+
+    #[error("Couldn't start the audio stream:\n{0}")]
+    PlayStreamError(#[from] cpal::PlayStreamError),
+}
+
+/// The state of the connection of [crate::fetcher::SystemAudioFetcher] to its device.
+///
+/// See [SystemAudio::subscribe].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The device provides samples (again).
+    Connected,
+
+    /// The stream of the device died (for example because it has been unplugged).
+    /// The fetcher provides silence until it could reconnect.
+    Disconnected,
+
+    /// The given reconnection attempt failed, the next one is done after `retry_in`.
+    ReconnectFailed { attempt: u32, retry_in: Duration },
 }
 
 pub struct Descriptor {
     pub device: cpal::Device,
+    /// The host of the device (see [crate::util::get_device_from_host]). The fetcher reconnects to the device within it.
+    pub host_id: cpal::HostId,
     pub sample_rate: cpal::SampleRate,
     pub sample_format: Option<cpal::SampleFormat>,
     pub amount_channels: Option<u16>,
 }
 
-impl Default for Descriptor {
-    fn default() -> Self {
+impl Descriptor {
+    /// Like [Descriptor::default] but returns an error instead of panicking if the system doesn't have
+    /// a default output device (for example on headless machines).
+    pub fn try_default() -> Result<Self, SystemAudioError> {
         let device = crate::util::get_default_device(crate::util::DeviceType::Output)
-            .expect("Default output device is set in the system");
+            .ok_or(SystemAudioError::NoDefaultDevice)?;
 
-        Self {
+        Ok(Self {
             device,
+            host_id: cpal::default_host().id(),
             sample_rate: DEFAULT_SAMPLE_RATE,
             sample_format: None,
             amount_channels: None,
-        }
+        })
+    }
+}
+
+impl Default for Descriptor {
+    fn default() -> Self {
+        Self::try_default().expect("Default output device is set in the system")
     }
 }
 
 /// Fetcher for the system audio.
 ///
 /// It's recommended to use [SystemAudio::default] to create a new instance of this struct.
+///
+/// If the stream of the device dies (for example because an USB interface has been unplugged), the fetcher
+/// provides silence and tries to reconnect to a device with the same name (with an increasing delay between the attempts)
+/// while [Fetcher::fetch_samples] is called. The device is looked up in a background thread, so the attempts
+/// don't block the caller. Use [SystemAudio::subscribe] to get notified about it.
+///
+/// The samples are passed from the audio callback to the fetcher through a lock-free ring buffer,
+/// so the audio callback never waits for the render thread. If [Fetcher::fetch_samples] isn't called often enough,
+/// the newest samples are dropped (see [SystemAudio::dropped_samples]).
 pub struct SystemAudio {
-    sample_buffer: Arc<Mutex<SampleBuffer>>,
+    samples: Consumer<f32>,
+    stats: Arc<StreamStats>,
     sample_rate: SampleRate,
 
     channels: u16,
 
-    _stream: cpal::Stream,
+    device_name: Option<String>,
+    host_id: cpal::HostId,
+    // whether the device is an output device (captured as loopback) or an input device
+    device_type: crate::util::DeviceType,
+    stream_config: cpal::StreamConfig,
+    connection: Connection,
+}
+
+struct Connection {
+    // `None` while the device is disconnected
+    stream: Option<cpal::Stream>,
+    // set by the error callback of the stream
+    failed: Arc<AtomicBool>,
+
+    failed_attempts: u32,
+    next_attempt: Instant,
+    // the running lookup of the device (enumerating the devices can take a while)
+    lookup: Option<mpsc::Receiver<Option<cpal::Device>>>,
+
+    subscribers: Vec<mpsc::Sender<ConnectionState>>,
+}
+
+impl Connection {
+    fn notify(&mut self, state: ConnectionState) {
+        debug!("Connection state: {:?}", state);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(state).is_ok());
+    }
 }
 
 impl SystemAudio {
     pub fn new(desc: &Descriptor) -> Result<Box<Self>, SystemAudioError> {
         let device = &desc.device;
+        let (configs, device_type) = supported_configs(device)?;
         let stream_config = {
-            let mut matching_configs: Vec<_> = desc
-                .device
-                .supported_output_configs()?
+            let mut matching_configs: Vec<_> = configs
+                .into_iter()
                 .filter(|conf| {
                     let matching_sample_format = desc
                         .sample_format
@@ -133,61 +232,264 @@ impl SystemAudio {
 
         debug!("Stream config: {:?}", stream_config);
 
-        let sample_buffer = {
-            let buffer = SampleBuffer::new(sample_rate.0 as usize);
-            Arc::new(Mutex::new(buffer))
-        };
-
-        let stream = {
-            let stream = device.build_input_stream(
-                &stream_config,
-                {
-                    let buffer = sample_buffer.clone();
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer.lock().unwrap();
-                        buf.push_before(data);
-                    }
-                },
-                |err| panic!("`shady-audio`: {}", err),
-                None,
-            )?;
-            stream.play().expect("Start listening to audio");
-            stream
-        };
+        let stats = Arc::new(StreamStats::new());
+        let failed = Arc::new(AtomicBool::new(false));
+        let (stream, samples) = build_stream(device, &stream_config, &stats, &failed)?;
 
         Ok(Box::new(Self {
-            _stream: stream,
             channels,
-            sample_buffer,
+            samples,
+            stats,
             sample_rate,
+            device_name: device.name().ok(),
+            host_id: desc.host_id,
+            device_type,
+            stream_config,
+            connection: Connection {
+                stream: Some(stream),
+                failed,
+                failed_attempts: 0,
+                next_attempt: Instant::now(),
+                lookup: None,
+                subscribers: Vec::new(),
+            },
         }))
     }
+
+    /// Returns a receiver which gets each change of the [ConnectionState].
+    ///
+    /// The changes are detected while [Fetcher::fetch_samples] is called.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use shady_audio::{SampleProcessor, fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor}};
+    ///
+    /// let mut fetcher = SystemAudioFetcher::new(&SystemAudioFetcherDescriptor::default()).unwrap();
+    /// let connection_states = fetcher.subscribe();
+    ///
+    /// let mut sample_processor = SampleProcessor::new(fetcher);
+    /// loop {
+    ///     sample_processor.process_next_samples();
+    ///
+    ///     for state in connection_states.try_iter() {
+    ///         println!("{:?}", state);
+    ///     }
+    ///     # break;
+    /// }
+    /// ```
+    pub fn subscribe(&mut self) -> mpsc::Receiver<ConnectionState> {
+        let (tx, rx) = mpsc::channel();
+        self.connection.subscribers.push(tx);
+        rx
+    }
+
+    /// Returns how many samples have been dropped since the fetcher has been created because
+    /// [Fetcher::fetch_samples] hasn't been called often enough (an overrun).
+    ///
+    /// A growing value means that the consumer can't keep up with the audio device.
+    pub fn dropped_samples(&self) -> u64 {
+        self.stats.dropped_samples.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the fetcher has a working stream to its device.
+    pub fn is_connected(&self) -> bool {
+        self.connection.stream.is_some()
+    }
+
+    /// Detects a died stream and tries to reconnect if it's time for the next attempt.
+    fn check_connection(&mut self) {
+        let connection = &mut self.connection;
+
+        if connection.failed.swap(false, Ordering::Relaxed) && connection.stream.is_some() {
+            connection.stream = None;
+            connection.failed_attempts = 0;
+            connection.next_attempt = Instant::now();
+            connection.notify(ConnectionState::Disconnected);
+        }
+
+        if connection.stream.is_some() {
+            return;
+        }
+
+        let device = match &connection.lookup {
+            Some(lookup) => match lookup.try_recv() {
+                Ok(device) => device,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => None,
+            },
+            None => {
+                if Instant::now() >= connection.next_attempt {
+                    self.connection.lookup = Some(self.lookup_device());
+                }
+                return;
+            }
+        };
+        self.connection.lookup = None;
+
+        match self.reconnect(device) {
+            Ok((stream, samples)) => {
+                self.samples = samples;
+
+                let connection = &mut self.connection;
+                connection.stream = Some(stream);
+                connection.failed_attempts = 0;
+                connection.notify(ConnectionState::Connected);
+            }
+            Err(err) => {
+                let connection = &mut self.connection;
+                connection.failed_attempts += 1;
+
+                let retry_in = retry_delay(connection.failed_attempts);
+                connection.next_attempt = Instant::now() + retry_in;
+
+                debug!("Couldn't reconnect: {}", err);
+                connection.notify(ConnectionState::ReconnectFailed {
+                    attempt: connection.failed_attempts,
+                    retry_in,
+                });
+            }
+        }
+    }
+
+    /// Looks for the device in a background thread and returns the receiver of the result.
+    fn lookup_device(&self) -> mpsc::Receiver<Option<cpal::Device>> {
+        let (tx, rx) = mpsc::channel();
+        let device_name = self.device_name.clone();
+        let host_id = self.host_id;
+        let device_type = self.device_type;
+
+        std::thread::spawn(move || {
+            let device = match device_name {
+                Some(name) => crate::util::get_device_from_host(host_id, name, device_type).ok(),
+                None => crate::util::get_default_device_from_host(host_id, device_type).ok(),
+            }
+            .flatten();
+
+            // the fetcher might have been dropped in the meantime
+            let _ = tx.send(device);
+        });
+
+        rx
+    }
+
+    fn reconnect(
+        &self,
+        device: Option<cpal::Device>,
+    ) -> Result<(cpal::Stream, Consumer<f32>), SystemAudioError> {
+        let device = device.ok_or(SystemAudioError::DeviceNotAvailable)?;
+
+        build_stream(
+            &device,
+            &self.stream_config,
+            &self.stats,
+            &self.connection.failed,
+        )
+    }
+}
+
+/// Returns the output configs of the device or its input configs if it isn't an output device
+/// (like the monitor sources of PulseAudio/PipeWire).
+fn supported_configs(
+    device: &cpal::Device,
+) -> Result<(Vec<SupportedStreamConfigRange>, crate::util::DeviceType), SystemAudioError> {
+    let output_configs: Vec<_> = device
+        .supported_output_configs()
+        .map(|configs| configs.collect())
+        .unwrap_or_default();
+
+    if !output_configs.is_empty() {
+        return Ok((output_configs, crate::util::DeviceType::Output));
+    }
+
+    Ok((
+        device.supported_input_configs()?.collect(),
+        crate::util::DeviceType::Input,
+    ))
+}
+
+/// Returns the delay after the given amount of failed reconnection attempts.
+fn retry_delay(failed_attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(failed_attempts.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Builds the stream and returns it with the receiving end of its samples.
+fn build_stream(
+    device: &cpal::Device,
+    stream_config: &cpal::StreamConfig,
+    stats: &Arc<StreamStats>,
+    failed: &Arc<AtomicBool>,
+) -> Result<(cpal::Stream, Consumer<f32>), SystemAudioError> {
+    // one second of samples
+    let capacity = stream_config.sample_rate.0 as usize * usize::from(stream_config.channels);
+    let (mut producer, consumer) = RingBuffer::new(capacity);
+
+    let stream = device.build_input_stream(
+        stream_config,
+        {
+            let stats = stats.clone();
+            move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                let timestamp = info.timestamp();
+
+                push_samples(&mut producer, data, &stats);
+                stats.set_latency(timestamp.callback.duration_since(&timestamp.capture));
+            }
+        },
+        {
+            let failed = failed.clone();
+            move |err| {
+                warn!("`shady-audio`: The audio stream failed: {}", err);
+                failed.store(true, Ordering::Relaxed);
+            }
+        },
+        None,
+    )?;
+    stream.play()?;
+
+    Ok((stream, consumer))
 }
 
 impl Drop for SystemAudio {
     /// Closes the audio stream before it gets dropped.
-    ///
-    /// **Panics** if it couldn't close the stream correctly.
     fn drop(&mut self) {
-        self._stream.pause().expect("Stop stream");
+        if let Some(stream) = &self.connection.stream {
+            if let Err(err) = stream.pause() {
+                debug!("Couldn't stop the stream: {}", err);
+            }
+        }
     }
 }
 
 impl Fetcher for SystemAudio {
-    fn fetch_samples(&mut self, buf: &mut [f32]) {
-        let buf_len = buf.len();
-        let mut sample_buffer = self.sample_buffer.lock().unwrap();
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        self.check_connection();
 
-        tracing::debug!("{:?}", sample_buffer.buffer);
+        if !self.is_connected() {
+            // provide silence until the device is back
+            buf.fill(0.);
+            return buf.len();
+        }
+
+        let buf_len = buf.len();
+        // keep the channels of the samples aligned
+        let channels = usize::from(self.channels.max(1));
+        let amount_samples = buf_len.min(self.samples.slots()) / channels * channels;
 
-        let amount_samples = buf_len.min(sample_buffer.length);
-        let new_sample_buffer_len = sample_buffer.length - amount_samples;
+        let Ok(chunk) = self.samples.read_chunk(amount_samples) else {
+            return 0;
+        };
 
+        // move the previous samples to the right to make space for the new ones
         buf.copy_within(..buf_len - amount_samples, amount_samples);
-        buf[..amount_samples]
-            .copy_from_slice(&sample_buffer.buffer[new_sample_buffer_len..sample_buffer.length]);
 
-        sample_buffer.length = new_sample_buffer_len;
+        let (first, second) = chunk.as_slices();
+        buf[..first.len()].copy_from_slice(first);
+        buf[first.len()..amount_samples].copy_from_slice(second);
+
+        chunk.commit_all();
+
+        amount_samples
     }
 
     fn sample_rate(&self) -> SampleRate {
@@ -197,6 +499,14 @@ impl Fetcher for SystemAudio {
     fn channels(&self) -> u16 {
         self.channels
     }
+
+    fn latency(&self) -> Option<Duration> {
+        self.stats.latency()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
 }
 
 #[instrument(skip_all)]
@@ -218,3 +528,38 @@ fn default_output_config(
         .next()
         .ok_or(SystemAudioError::NoAvailableOutputConfigs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_ring_buffer_drops_samples() {
+        let (mut producer, mut consumer) = RingBuffer::new(4);
+        let stats = StreamStats::new();
+
+        push_samples(&mut producer, &[1., 2., 3.], &stats);
+        push_samples(&mut producer, &[4., 5., 6.], &stats);
+        assert_eq!(stats.dropped_samples.load(Ordering::Relaxed), 2);
+
+        let chunk = consumer.read_chunk(4).unwrap();
+        assert_eq!(chunk.into_iter().collect::<Vec<_>>(), [1., 2., 3., 4.]);
+    }
+
+    #[test]
+    fn latency_roundtrip() {
+        let stats = StreamStats::new();
+        assert_eq!(stats.latency(), None);
+
+        stats.set_latency(Some(Duration::from_millis(20)));
+        assert_eq!(stats.latency(), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn retry_delay_grows_up_to_max() {
+        assert_eq!(retry_delay(1), INITIAL_RETRY_DELAY);
+        assert_eq!(retry_delay(2), INITIAL_RETRY_DELAY * 2);
+        assert_eq!(retry_delay(3), INITIAL_RETRY_DELAY * 4);
+        assert_eq!(retry_delay(100), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,322 @@
+use std::{num::NonZero, ops::Range};
+
+use crate::SampleProcessor;
+
+// how much the peak which normalizes the bars decreases each frame
+const PEAK_DECAY: f32 = 0.995;
+
+/// The config options for [HpssProcessor].
+#[derive(Debug, Clone)]
+pub struct HpssProcessorConfig {
+    /// The amount of past spectra (including the current one) whose median is the harmonic part of a bin.
+    ///
+    /// Longer histories separate better but sustained tones need longer until they're recognized as harmonic.
+    pub history: NonZero<usize>,
+
+    /// The amount of neighbouring bins (including the bin itself) whose median is the percussive part of a bin.
+    pub freq_kernel: NonZero<usize>,
+
+    /// The exponent of the soft masks. Higher values separate harder (`1` is the plain ratio, `2` is a Wiener filter).
+    pub mask_power: f32,
+
+    /// The amount of bars of each part.
+    pub amount_bars: NonZero<u16>,
+
+    /// Set the frequency range which the bars should cover.
+    pub freq_range: Range<NonZero<u16>>,
+}
+
+impl Default for HpssProcessorConfig {
+    fn default() -> Self {
+        Self {
+            history: NonZero::new(17).unwrap(),
+            freq_kernel: NonZero::new(17).unwrap(),
+            mask_power: 2.,
+            amount_bars: NonZero::new(32).unwrap(),
+            freq_range: NonZero::new(50).unwrap()..NonZero::new(15_000).unwrap(),
+        }
+    }
+}
+
+/// Splits the spectrum of the [SampleProcessor] into a harmonic part (sustained tones) and a percussive part
+/// (drum hits and other transients) by median filtering the spectrogram (HPSS).
+///
+/// Sustained tones are horizontal lines in the spectrogram, so the median of a bin over time keeps them while it
+/// removes short hits. Hits are vertical lines which cover many bins at once, so the median of the neighbouring
+/// bins keeps them while it removes single tones. Both medians decide how much of each bin belongs to which part.
+///
+/// Only past spectra are known, so a tone is harmonic once it lasted for about half of the history.
+/// All channels of the [SampleProcessor] are combined.
+///
+/// # Example
+/// ```
+/// use shady_audio::{HpssProcessor, HpssProcessorConfig, SampleProcessor, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut hpss_processor = HpssProcessor::new(&sample_processor, HpssProcessorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// hpss_processor.process(&sample_processor);
+///
+/// assert_eq!(hpss_processor.harmonic_bars().len(), 32);
+/// assert_eq!(hpss_processor.percussive_bars().len(), 32);
+/// ```
+pub struct HpssProcessor {
+    config: HpssProcessorConfig,
+
+    // the combined magnitudes of the last spectra, `history_idx` is the oldest one
+    history: Box<[Box<[f32]>]>,
+    history_idx: usize,
+
+    harmonic: Box<[f32]>,
+    percussive: Box<[f32]>,
+
+    // the bins of each bar
+    bar_ranges: Box<[Range<usize>]>,
+    harmonic_bars: Box<[f32]>,
+    percussive_bars: Box<[f32]>,
+    peak: f32,
+
+    scratch: Vec<f32>,
+}
+
+impl HpssProcessor {
+    /// Creates a new instance which works with the given sample processor.
+    pub fn new(processor: &SampleProcessor, config: HpssProcessorConfig) -> Self {
+        let amount_bins = processor.fft_size() / 2 + 1;
+        let freq_resolution = processor.sample_rate().0 as f32 / processor.fft_size() as f32;
+
+        let bar_ranges = bar_ranges(
+            &config.freq_range,
+            config.amount_bars.get() as usize,
+            freq_resolution,
+            amount_bins,
+        );
+
+        let amount_bars = bar_ranges.len();
+        let spectrum = || vec![0.; amount_bins].into_boxed_slice();
+
+        Self {
+            history: (0..config.history.get()).map(|_| spectrum()).collect(),
+            history_idx: 0,
+
+            harmonic: spectrum(),
+            percussive: spectrum(),
+
+            bar_ranges,
+            harmonic_bars: vec![0.; amount_bars].into_boxed_slice(),
+            percussive_bars: vec![0.; amount_bars].into_boxed_slice(),
+            peak: 0.,
+
+            scratch: Vec::with_capacity(config.history.get().max(config.freq_kernel.get())),
+
+            config,
+        }
+    }
+
+    /// Separates the current spectrum of the given processor.
+    pub fn process(&mut self, processor: &SampleProcessor) {
+        let channels = processor.fft_out();
+        let amount_channels = channels.len().max(1) as f32;
+
+        let current = &mut self.history[self.history_idx];
+        for (bin_idx, magnitude) in current.iter_mut().enumerate() {
+            *magnitude = channels
+                .iter()
+                .map(|channel| channel.fft_out[bin_idx].norm())
+                .sum::<f32>()
+                / amount_channels;
+        }
+        let current_idx = self.history_idx;
+        self.history_idx = (self.history_idx + 1) % self.history.len();
+
+        let half_kernel = self.config.freq_kernel.get() / 2;
+        let amount_bins = self.harmonic.len();
+        for bin_idx in 0..amount_bins {
+            self.scratch.clear();
+            self.scratch
+                .extend(self.history.iter().map(|spectrum| spectrum[bin_idx]));
+            let harmonic = median(&mut self.scratch);
+
+            let current = &self.history[current_idx];
+            let neighbours =
+                bin_idx.saturating_sub(half_kernel)..(bin_idx + half_kernel + 1).min(amount_bins);
+            self.scratch.clear();
+            self.scratch.extend_from_slice(&current[neighbours]);
+            let percussive = median(&mut self.scratch);
+
+            let harmonic = harmonic.powf(self.config.mask_power);
+            let percussive = percussive.powf(self.config.mask_power);
+            let sum = harmonic + percussive;
+            let harmonic_mask = if sum > 0. { harmonic / sum } else { 0. };
+            let percussive_mask = if sum > 0. { percussive / sum } else { 0. };
+
+            self.harmonic[bin_idx] = harmonic_mask * current[bin_idx];
+            self.percussive[bin_idx] = percussive_mask * current[bin_idx];
+        }
+
+        self.update_bars();
+    }
+
+    /// Returns the magnitude of each fft bin which belongs to sustained tones.
+    pub fn harmonic(&self) -> &[f32] {
+        &self.harmonic
+    }
+
+    /// Returns the magnitude of each fft bin which belongs to transients like drum hits.
+    pub fn percussive(&self) -> &[f32] {
+        &self.percussive
+    }
+
+    /// Returns the bars of the harmonic part. They are within `[0, 1]`.
+    ///
+    /// The harmonic and percussive bars are normalized by the same (slowly decreasing) peak,
+    /// so they can be compared with each other.
+    pub fn harmonic_bars(&self) -> &[f32] {
+        &self.harmonic_bars
+    }
+
+    /// Returns the bars of the percussive part. They are within `[0, 1]`, see [HpssProcessor::harmonic_bars].
+    pub fn percussive_bars(&self) -> &[f32] {
+        &self.percussive_bars
+    }
+
+    /// Returns the config of the processor.
+    pub fn config(&self) -> &HpssProcessorConfig {
+        &self.config
+    }
+}
+
+impl HpssProcessor {
+    fn update_bars(&mut self) {
+        for ((range, harmonic), percussive) in self
+            .bar_ranges
+            .iter()
+            .zip(self.harmonic_bars.iter_mut())
+            .zip(self.percussive_bars.iter_mut())
+        {
+            let len = range.len() as f32;
+            *harmonic = self.harmonic[range.clone()].iter().sum::<f32>() / len;
+            *percussive = self.percussive[range.clone()].iter().sum::<f32>() / len;
+        }
+
+        let max = self
+            .harmonic_bars
+            .iter()
+            .chain(self.percussive_bars.iter())
+            .copied()
+            .fold(0., f32::max);
+        self.peak = (self.peak * PEAK_DECAY).max(max);
+
+        if self.peak > 0. {
+            for bar in self
+                .harmonic_bars
+                .iter_mut()
+                .chain(self.percussive_bars.iter_mut())
+            {
+                *bar /= self.peak;
+            }
+        }
+    }
+}
+
+/// Returns the (logarithmically distributed) bins of each bar. Each bar has at least one bin.
+fn bar_ranges(
+    freq_range: &Range<NonZero<u16>>,
+    amount_bars: usize,
+    freq_resolution: f32,
+    amount_bins: usize,
+) -> Box<[Range<usize>]> {
+    let start = f32::from(freq_range.start.get());
+    let end = f32::from(freq_range.end.get()).max(start);
+
+    let bin = |bar_idx: usize| {
+        let freq = start * (end / start).powf(bar_idx as f32 / amount_bars as f32);
+        ((freq / freq_resolution) as usize).clamp(1, amount_bins - 1)
+    };
+
+    (0..amount_bars)
+        .map(|bar_idx| {
+            let first = bin(bar_idx);
+            first..bin(bar_idx + 1).max(first + 1)
+        })
+        .collect()
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    let mid = values.len() / 2;
+    *values.select_nth_unstable_by(mid, f32::total_cmp).1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetcher::{DummyFetcher, FnFetcher, Waveform};
+
+    use super::*;
+
+    fn energies(processor: &HpssProcessor) -> (f32, f32) {
+        (
+            processor.harmonic().iter().sum(),
+            processor.percussive().iter().sum(),
+        )
+    }
+
+    #[test]
+    fn sine_is_harmonic() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+            1,
+            Waveform::Sine { frequency: 440. },
+        ));
+        let mut hpss_processor =
+            HpssProcessor::new(&sample_processor, HpssProcessorConfig::default());
+
+        for _ in 0..20 {
+            sample_processor.process_next_samples();
+            hpss_processor.process(&sample_processor);
+        }
+
+        let (harmonic, percussive) = energies(&hpss_processor);
+        assert!(harmonic > 10. * percussive, "{} {}", harmonic, percussive);
+    }
+
+    #[test]
+    fn click_is_percussive() {
+        let mut sample_processor = SampleProcessor::new(FnFetcher::new(1, |batch, idx| {
+            // silence with a click in the center of the window every few batches
+            if batch % 8 == 7 && idx == 512 {
+                1.
+            } else {
+                0.
+            }
+        }));
+        let mut hpss_processor =
+            HpssProcessor::new(&sample_processor, HpssProcessorConfig::default());
+
+        for _ in 0..24 {
+            sample_processor.process_next_samples();
+            hpss_processor.process(&sample_processor);
+        }
+
+        let (harmonic, percussive) = energies(&hpss_processor);
+        assert!(percussive > 10. * harmonic, "{} {}", harmonic, percussive);
+        assert!(hpss_processor
+            .percussive_bars()
+            .iter()
+            .any(|&bar| bar > 0.5));
+    }
+
+    #[test]
+    fn bars_cover_the_range() {
+        let ranges = bar_ranges(
+            &(NonZero::new(50).unwrap()..NonZero::new(15_000).unwrap()),
+            32,
+            44_100. / 1024.,
+            513,
+        );
+
+        assert_eq!(ranges.len(), 32);
+        assert!(ranges.iter().all(|range| !range.is_empty()));
+        assert_eq!(ranges[0].start, 1);
+        assert!(ranges.last().unwrap().end <= 513);
+    }
+}
@@ -1,19 +1,19 @@
 use tracing::debug;
 
-use super::{InterpolationSection, SupportingPoint};
+use super::{first_index_after, to_index, Float, InterpolationSection, SupportingPoint};
 
 #[derive(Clone)]
-pub struct InterpolationCtx {
-    pub supporting_points: Box<[SupportingPoint]>,
+pub struct InterpolationCtx<T: Float> {
+    pub supporting_points: Box<[SupportingPoint<T>]>,
     pub sections: Box<[InterpolationSection]>,
 }
 
 /// Constructing stuff
-impl InterpolationCtx {
-    pub fn new(supporting_points: impl IntoIterator<Item = super::SupportingPoint>) -> Self {
+impl<T: Float> InterpolationCtx<T> {
+    pub fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self {
         let supporting_points = supporting_points
             .into_iter()
-            .collect::<Vec<SupportingPoint>>()
+            .collect::<Vec<SupportingPoint<T>>>()
             .into_boxed_slice();
 
         let sections = {
@@ -23,7 +23,15 @@ impl InterpolationCtx {
                 for (i, supporting_point) in supporting_points[1..].iter().enumerate() {
                     let prev_supporting_point = supporting_points.get(i).unwrap();
 
-                    let gap_size = supporting_point.x - prev_supporting_point.x - 1;
+                    assert!(
+                        prev_supporting_point.x < supporting_point.x,
+                        "The x values of the supporting points must be strictly increasing."
+                    );
+
+                    // the (non-negative) indices between the two supporting points
+                    let gap_size = to_index(supporting_point.x.ceil())
+                        .unwrap_or(0)
+                        .saturating_sub(first_index_after(prev_supporting_point.x));
                     let there_is_a_gap = gap_size > 0;
                     if there_is_a_gap {
                         sections.push(InterpolationSection {
@@ -48,7 +56,59 @@ impl InterpolationCtx {
     }
 }
 
-impl std::fmt::Debug for InterpolationCtx {
+/// Evaluating stuff
+impl<T: Float> InterpolationCtx<T> {
+    /// Returns the value of `value_in_section` at `x` or `None` if `x` is outside of the range of the supporting points.
+    ///
+    /// `value_in_section` gets the index of the left supporting point of the section which contains `x` and `x`.
+    pub fn value_at(&self, x: T, value_in_section: impl Fn(usize, T) -> T) -> Option<T> {
+        let first = self.supporting_points.first()?;
+        let last = self.supporting_points.last()?;
+
+        if x < first.x || x > last.x {
+            return None;
+        } else if self.supporting_points.len() == 1 {
+            return Some(first.y);
+        }
+
+        // the index of the first supporting point which isn't left of `x`
+        let right_idx = self
+            .supporting_points
+            .partition_point(|point| point.x < x)
+            .max(1);
+
+        Some(value_in_section(right_idx - 1, x))
+    }
+
+    /// Stores the values of the supporting points with an integer `x` and the interpolated values
+    /// of `value_in_section` at the indices between them in the buffer.
+    ///
+    /// `value_in_section` gets the index of the left supporting point of the section and the x value.
+    pub fn fill(&self, buffer: &mut [T], value_in_section: impl Fn(usize, T) -> T) {
+        for point in self.supporting_points.iter() {
+            if point.x.fract() != T::zero() {
+                continue;
+            }
+
+            if let Some(idx) = to_index(point.x) {
+                buffer[idx] = point.y;
+            }
+        }
+
+        for section in self.sections.iter() {
+            let left = &self.supporting_points[section.left_supporting_point_idx];
+            let first_idx = first_index_after(left.x);
+
+            let indices = first_idx..first_idx + section.amount;
+            for (idx, value) in indices.clone().zip(&mut buffer[indices]) {
+                *value =
+                    value_in_section(section.left_supporting_point_idx, super::cast(idx as f64));
+            }
+        }
+    }
+}
+
+impl<T: Float> std::fmt::Debug for InterpolationCtx<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut sp_iter = self.supporting_points.iter().enumerate().peekable();
         let mut s_iter = self.sections.iter().peekable();
@@ -97,7 +157,7 @@ mod tests {
 
     #[test]
     fn no_points_no_sections() {
-        let ctx = InterpolationCtx::new([]);
+        let ctx = InterpolationCtx::<f32>::new([]);
 
         assert!(ctx.supporting_points.is_empty());
         assert!(ctx.sections.is_empty());
@@ -105,7 +165,7 @@ mod tests {
 
     #[test]
     fn one_point_no_sections() {
-        let supporting_points = [SupportingPoint { x: 0, y: 0.0 }];
+        let supporting_points = [SupportingPoint { x: 0., y: 0.0 }];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
 
@@ -116,8 +176,8 @@ mod tests {
     #[test]
     fn two_points_no_sections() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 1, y: 1.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 1., y: 1.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -129,8 +189,8 @@ mod tests {
     #[test]
     fn two_points_one_section() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 5, y: 1.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 5., y: 1.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -148,9 +208,9 @@ mod tests {
     #[test]
     fn three_points_one_section_at_the_beginning() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 2, y: 0.0 },
-            SupportingPoint { x: 3, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 2., y: 0.0 },
+            SupportingPoint { x: 3., y: 0.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -168,9 +228,9 @@ mod tests {
     #[test]
     fn three_points_one_section_in_the_end() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 1, y: 0.0 },
-            SupportingPoint { x: 3, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 1., y: 0.0 },
+            SupportingPoint { x: 3., y: 0.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -188,9 +248,9 @@ mod tests {
     #[test]
     fn three_points_two_sections() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 2, y: 0.0 },
-            SupportingPoint { x: 4, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 2., y: 0.0 },
+            SupportingPoint { x: 4., y: 0.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -214,9 +274,9 @@ mod tests {
     #[test]
     fn three_points_two_big_sections() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 5, y: 0.0 },
-            SupportingPoint { x: 10, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 5., y: 0.0 },
+            SupportingPoint { x: 10., y: 0.0 },
         ];
 
         let ctx = InterpolationCtx::new(supporting_points.clone());
@@ -236,12 +296,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn negative_points_no_sections() {
+        let supporting_points = [
+            SupportingPoint { x: -3., y: 0.0 },
+            SupportingPoint { x: -2., y: 0.0 },
+            SupportingPoint { x: -0.5, y: 0.0 },
+        ];
+
+        let ctx = InterpolationCtx::new(supporting_points);
+
+        assert!(ctx.sections.is_empty());
+    }
+
+    #[test]
+    fn fractional_points_with_sections() {
+        let supporting_points = [
+            SupportingPoint { x: -1.5, y: 0.0 },
+            SupportingPoint { x: 1.5, y: 0.0 },
+            SupportingPoint { x: 2.5, y: 0.0 },
+            SupportingPoint { x: 4., y: 0.0 },
+        ];
+
+        let ctx = InterpolationCtx::new(supporting_points);
+
+        assert_eq!(
+            ctx.sections.as_ref(),
+            &[
+                InterpolationSection {
+                    left_supporting_point_idx: 0,
+                    amount: 2
+                },
+                InterpolationSection {
+                    left_supporting_point_idx: 1,
+                    amount: 1
+                },
+                InterpolationSection {
+                    left_supporting_point_idx: 2,
+                    amount: 1
+                }
+            ]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn invalid_supporting_points_ordering() {
         let supporting_points = [
-            SupportingPoint { x: 1, y: 0.0 },
-            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 1., y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
         ];
 
         InterpolationCtx::new(supporting_points);
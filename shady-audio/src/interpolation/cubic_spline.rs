@@ -1,22 +1,25 @@
-use nalgebra::{Cholesky, DMatrix, DVector, Dyn};
+use std::slice::IterMut;
 
-use super::{context::InterpolationCtx, Interpolater, InterpolationInner};
+use nalgebra::{Cholesky, DMatrix, DVector, Dyn};
 
-type Width = usize;
+use super::{
+    cast, context::InterpolationCtx, Float, Interpolater, InterpolationInner, SupportingPoint,
+};
 
 #[derive(Debug, Clone)]
-pub struct CubicSplineInterpolation {
-    ctx: InterpolationCtx,
+pub struct CubicSplineInterpolation<T: Float = f32> {
+    ctx: InterpolationCtx<T>,
 
-    section_widths: Box<[Width]>,
+    section_widths: Box<[T]>,
 
-    matrix: Cholesky<f32, Dyn>,
-    gradients: Box<[f32]>,
-    gradient_diffs: Box<[f32]>,
+    matrix: Cholesky<T, Dyn>,
+    gradients: Box<[T]>,
+    gradient_diffs: Box<[T]>,
+    gammas: Box<[T]>,
 }
 
-impl InterpolationInner for CubicSplineInterpolation {
-    fn new(supporting_points: impl IntoIterator<Item = super::SupportingPoint>) -> Self {
+impl<T: Float> InterpolationInner<T> for CubicSplineInterpolation<T> {
+    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self {
         let ctx = InterpolationCtx::new(supporting_points);
 
         let section_widths = if ctx.supporting_points.len() >= 2 {
@@ -39,10 +42,11 @@ impl InterpolationInner for CubicSplineInterpolation {
         let matrix = {
             let matrix = get_matrix(&section_widths);
 
-            ((1. / 6.) * matrix.clone()).cholesky().unwrap_or_else(|| panic!("Hold up! Looks like my numeric knowledge isn't really numericing ;-----;\nThe matrix which got calculated is: {}", matrix))
+            (matrix.clone() * cast::<T>(1. / 6.)).cholesky().unwrap_or_else(|| panic!("Hold up! Looks like my numeric knowledge isn't really numericing ;-----;\nThe matrix which got calculated is: {}", matrix))
         };
-        let gradients = vec![0f32; amount_sections].into_boxed_slice();
-        let gradient_diffs = vec![0f32; amount_sections].into_boxed_slice();
+        let gradients = vec![T::zero(); amount_sections].into_boxed_slice();
+        let gradient_diffs = vec![T::zero(); amount_sections].into_boxed_slice();
+        let gammas = vec![T::zero(); amount_sections].into_boxed_slice();
 
         Self {
             ctx,
@@ -50,16 +54,13 @@ impl InterpolationInner for CubicSplineInterpolation {
             matrix,
             gradients,
             gradient_diffs,
+            gammas,
         }
     }
 }
 
-impl Interpolater for CubicSplineInterpolation {
-    fn interpolate(&mut self, buffer: &mut [f32]) {
-        for point in self.ctx.supporting_points.iter() {
-            buffer[point.x] = point.y;
-        }
-
+impl<T: Float> Interpolater<T> for CubicSplineInterpolation<T> {
+    fn update(&mut self) {
         if self.ctx.supporting_points.len() < 2 {
             return;
         }
@@ -77,7 +78,7 @@ impl Interpolater for CubicSplineInterpolation {
             let gradient_iter = self.gradients.iter_mut();
 
             for ((gradient, prev), next) in gradient_iter.zip(prev_iter).zip(next_iter) {
-                *gradient = (prev.y - next.y) / (prev.x as f32 - next.x as f32);
+                *gradient = (prev.y - next.y) / (prev.x - next.x);
             }
         }
 
@@ -91,62 +92,71 @@ impl Interpolater for CubicSplineInterpolation {
                 let next_iter = prev_iter.clone().skip(1);
 
                 for ((diff, prev), next) in diff_iter.zip(prev_iter).zip(next_iter) {
-                    *diff = next - prev;
+                    *diff = *next - *prev;
                 }
             }
 
-            *self.gradient_diffs.last_mut().unwrap() = -self.gradients.last().unwrap();
+            *self.gradient_diffs.last_mut().unwrap() = -*self.gradients.last().unwrap();
         }
 
         // solve gamma
         let gammas = self
             .matrix
             .solve(&DVector::from_column_slice(&self.gradient_diffs));
+        self.gammas.copy_from_slice(gammas.as_slice());
+    }
+
+    fn value_at(&self, x: T) -> Option<T> {
+        self.ctx.value_at(x, |idx, x| self.value_in_section(idx, x))
+    }
 
-        // == interpolation ==
-        for section in self.ctx.sections.iter() {
-            let n = section.left_supporting_point_idx + 1;
+    fn interpolate(&mut self, buffer: &mut [T]) {
+        self.update();
+        self.ctx
+            .fill(buffer, |idx, x| self.value_in_section(idx, x));
+    }
 
-            let left = &self.ctx.supporting_points[n - 1];
-            let right = &self.ctx.supporting_points[n];
+    fn supporting_points(&self) -> &[SupportingPoint<T>] {
+        &self.ctx.supporting_points
+    }
 
-            let prev_gamma = gammas[n - 1];
-            // `None` appears, if we are in the last section.
-            let next_gamma = gammas.get(n).cloned().unwrap_or(0.);
+    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint<T>> {
+        self.ctx.supporting_points.iter_mut()
+    }
+}
 
-            let gradient = self.gradients[n - 1];
-            let section_width = self.section_widths[n - 1];
+impl<T: Float> CubicSplineInterpolation<T> {
+    fn value_in_section(&self, left_idx: usize, x: T) -> T {
+        let n = left_idx + 1;
 
-            let amount = section.amount;
-            for interpolated_idx in 0..amount {
-                let bar_idx = interpolated_idx + 1 + left.x;
-                let x = bar_idx as f32;
+        let left = &self.ctx.supporting_points[n - 1];
+        let right = &self.ctx.supporting_points[n];
 
-                let interpolated_value = left.y
-                    + (x - left.x as f32) * gradient
-                    + ((x - left.x as f32) * (x - right.x as f32)) / (6. * section_width as f32)
-                        * ((prev_gamma + 2. * next_gamma) * (x - left.x as f32)
-                            - (2. * prev_gamma + next_gamma) * (x - right.x as f32));
+        let prev_gamma = self.gammas[n - 1];
+        // `None` appears, if we are in the last section.
+        let next_gamma = self.gammas.get(n).cloned().unwrap_or(T::zero());
 
-                buffer[bar_idx] = interpolated_value;
-            }
-        }
-    }
+        let gradient = self.gradients[n - 1];
+        let section_width = self.section_widths[n - 1];
 
-    fn supporting_points_mut(&mut self) -> std::slice::IterMut<'_, super::SupportingPoint> {
-        self.ctx.supporting_points.iter_mut()
+        let two = cast::<T>(2.);
+        left.y
+            + (x - left.x) * gradient
+            + ((x - left.x) * (x - right.x)) / (cast::<T>(6.) * section_width)
+                * ((prev_gamma + two * next_gamma) * (x - left.x)
+                    - (two * prev_gamma + next_gamma) * (x - right.x))
     }
 }
 
-fn get_matrix(section_widths: &[usize]) -> DMatrix<f32> {
+fn get_matrix<T: Float>(section_widths: &[T]) -> DMatrix<T> {
     let dimension = section_widths.len();
 
     let mut matrix = DMatrix::zeros(dimension, dimension);
 
     for n in 0..dimension {
         let mut row = matrix.row_mut(n);
-        let prev_width = section_widths[n.saturating_sub(1)] as f32;
-        let curr_width = section_widths[n] as f32;
+        let prev_width = section_widths[n.saturating_sub(1)];
+        let curr_width = section_widths[n];
 
         let is_in_first_row = n == 0;
         let is_in_last_row = n + 1 == dimension;
@@ -156,9 +166,9 @@ fn get_matrix(section_widths: &[usize]) -> DMatrix<f32> {
         }
 
         if is_in_first_row || is_in_last_row {
-            row[n] = 2. * curr_width;
+            row[n] = cast::<T>(2.) * curr_width;
         } else {
-            row[n] = 2. * (prev_width + curr_width);
+            row[n] = cast::<T>(2.) * (prev_width + curr_width);
         }
 
         if !is_in_last_row {
@@ -183,7 +193,7 @@ mod tests {
 
     #[test]
     fn no_supporting_points() {
-        let mut interpolator = CubicSplineInterpolation::new([]);
+        let mut interpolator = CubicSplineInterpolation::<f32>::new([]);
         let mut buffer = vec![];
 
         interpolator.interpolate(&mut buffer);
@@ -193,9 +203,9 @@ mod tests {
 
     #[test]
     fn one_supporting_point() {
-        let supporting_points = [SupportingPoint { x: 0, y: 1.0 }];
+        let supporting_points = [SupportingPoint { x: 0., y: 1.0 }];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = CubicSplineInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -206,11 +216,11 @@ mod tests {
     #[test]
     fn two_supporting_points() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0. },
-            SupportingPoint { x: 5, y: 1.0 },
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 5., y: 1.0 },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = CubicSplineInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -221,12 +231,12 @@ mod tests {
     #[test]
     fn three_supporting_points() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0. },
-            SupportingPoint { x: 5, y: 0.25 },
-            SupportingPoint { x: 10, y: 1. },
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 5., y: 0.25 },
+            SupportingPoint { x: 10., y: 1. },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = CubicSplineInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -237,14 +247,14 @@ mod tests {
     #[test]
     fn multiple_supporting_points() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0. },
-            SupportingPoint { x: 5, y: 0.25 },
-            SupportingPoint { x: 10, y: 0.3 },
-            SupportingPoint { x: 15, y: 0.6 },
-            SupportingPoint { x: 20, y: 1. },
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 5., y: 0.25 },
+            SupportingPoint { x: 10., y: 0.3 },
+            SupportingPoint { x: 15., y: 0.6 },
+            SupportingPoint { x: 20., y: 1. },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = CubicSplineInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -257,7 +267,7 @@ mod tests {
 
         #[test]
         fn no_sections() {
-            let matrix = get_matrix(&[]);
+            let matrix = get_matrix::<f32>(&[]);
             let expected_matrix = DMatrix::from_row_slice(0, 0, &[]);
 
             assert_eq!(
@@ -270,7 +280,7 @@ mod tests {
         #[test]
         fn one_section() {
             const DIMENSION: usize = 1;
-            let matrix = get_matrix(&[1]);
+            let matrix = get_matrix(&[1f32]);
             let expected_matrix = DMatrix::from_row_slice(DIMENSION, DIMENSION, &[2.]);
 
             assert_eq!(
@@ -283,7 +293,7 @@ mod tests {
         #[test]
         fn two_sections() {
             const DIMENSION: usize = 2;
-            let matrix = get_matrix(&[1; DIMENSION]);
+            let matrix = get_matrix(&[1f32; DIMENSION]);
             #[rustfmt::skip]
             let expected_matrix = DMatrix::from_row_slice(DIMENSION, DIMENSION,
                 &[
@@ -302,7 +312,7 @@ mod tests {
         #[test]
         fn three_sections() {
             const DIMENSION: usize = 3;
-            let matrix = get_matrix(&[1; DIMENSION]);
+            let matrix = get_matrix(&[1f32; DIMENSION]);
             #[rustfmt::skip]
             let expected_matrix = DMatrix::from_row_slice(DIMENSION, DIMENSION,
                 &[
@@ -322,7 +332,7 @@ mod tests {
         #[test]
         fn ten_sections() {
             const DIMENSION: usize = 10;
-            let matrix = get_matrix(&[1; DIMENSION]);
+            let matrix = get_matrix(&[1f32; DIMENSION]);
             #[rustfmt::skip]
             let expected_matrix = DMatrix::from_row_slice(DIMENSION, DIMENSION,
                 &[
@@ -1,56 +1,58 @@
 use std::slice::IterMut;
 
-use tracing::debug;
-
-use super::{context::InterpolationCtx, Interpolater, InterpolationInner, SupportingPoint};
+use super::{context::InterpolationCtx, Float, Interpolater, InterpolationInner, SupportingPoint};
 
 #[derive(Debug)]
-pub struct LinearInterpolation {
-    ctx: InterpolationCtx,
+pub struct LinearInterpolation<T: Float = f32> {
+    ctx: InterpolationCtx<T>,
 }
 
-impl InterpolationInner for LinearInterpolation {
-    fn new(supporting_points: impl IntoIterator<Item = super::SupportingPoint>) -> Self {
+impl<T: Float> InterpolationInner<T> for LinearInterpolation<T> {
+    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self {
         let ctx = InterpolationCtx::new(supporting_points);
 
         Self { ctx }
     }
 }
 
-impl Interpolater for LinearInterpolation {
-    fn interpolate(&mut self, buffer: &mut [f32]) {
-        for point in self.ctx.supporting_points.iter() {
-            buffer[point.x] = point.y;
-        }
-
-        debug!("{:?}", self.ctx);
+impl<T: Float> Interpolater<T> for LinearInterpolation<T> {
+    fn update(&mut self) {}
 
-        for section in self.ctx.sections.iter() {
-            let left = &self.ctx.supporting_points[section.left_supporting_point_idx];
-            let right = &self.ctx.supporting_points[section.left_supporting_point_idx + 1];
+    fn value_at(&self, x: T) -> Option<T> {
+        self.ctx.value_at(x, |idx, x| self.value_in_section(idx, x))
+    }
 
-            let amount = section.amount;
-            for interpolate_idx in 0..amount {
-                let t = (interpolate_idx + 1) as f32 / (amount + 1) as f32;
+    fn interpolate(&mut self, buffer: &mut [T]) {
+        self.ctx
+            .fill(buffer, |idx, x| self.value_in_section(idx, x));
+    }
 
-                let idx = left.x + interpolate_idx + 1;
-                buffer[idx] = t * right.y + (1. - t) * left.y;
-            }
-        }
+    fn supporting_points(&self) -> &[SupportingPoint<T>] {
+        &self.ctx.supporting_points
     }
 
-    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint> {
+    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint<T>> {
         self.ctx.supporting_points.iter_mut()
     }
 }
 
+impl<T: Float> LinearInterpolation<T> {
+    fn value_in_section(&self, left_idx: usize, x: T) -> T {
+        let left = &self.ctx.supporting_points[left_idx];
+        let right = &self.ctx.supporting_points[left_idx + 1];
+
+        let t = (x - left.x) / (right.x - left.x);
+        t * right.y + (T::one() - t) * left.y
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn zero_supporting_points_and_zero_sections() {
-        let mut interpolator = LinearInterpolation::new([]);
+        let mut interpolator = LinearInterpolation::<f32>::new([]);
         let mut buffer = vec![];
 
         interpolator.interpolate(&mut buffer);
@@ -59,7 +61,7 @@ mod tests {
 
     #[test]
     fn one_supporting_point_and_zero_sections() {
-        let supporting_points = [SupportingPoint { x: 0, y: 0.5 }];
+        let supporting_points = [SupportingPoint { x: 0., y: 0.5 }];
 
         let mut interpolator = LinearInterpolation::new(supporting_points);
         let mut buffer = [0f32];
@@ -72,11 +74,11 @@ mod tests {
     #[test]
     fn two_supporting_points_and_one_section() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 4, y: 1.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 4., y: 1.0 },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = LinearInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -87,12 +89,12 @@ mod tests {
     #[test]
     fn three_supporting_points_and_one_section() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 2, y: 1.0 },
-            SupportingPoint { x: 3, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 2., y: 1.0 },
+            SupportingPoint { x: 3., y: 0.0 },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = LinearInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
@@ -103,16 +105,69 @@ mod tests {
     #[test]
     fn three_supporting_points_and_two_sections() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 2, y: 1.0 },
-            SupportingPoint { x: 6, y: 0.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 2., y: 1.0 },
+            SupportingPoint { x: 6., y: 0.0 },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = LinearInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
 
         assert_eq!(&buffer, &[0.0, 0.5, 1.0, 0.75, 0.5, 0.25, 0.0],);
     }
+
+    #[test]
+    fn fractional_supporting_points() {
+        let supporting_points = [
+            SupportingPoint { x: 0.5f64, y: 1.0 },
+            SupportingPoint { x: 2.5, y: 0.0 },
+        ];
+
+        let mut buffer = vec![-1f64; 4];
+        let mut interpolator = LinearInterpolation::new(supporting_points);
+
+        interpolator.interpolate(&mut buffer);
+
+        // only the indices within the range of the supporting points are touched
+        assert_eq!(&buffer, &[-1., 0.75, 0.25, -1.]);
+    }
+
+    #[test]
+    fn negative_supporting_points() {
+        let supporting_points = [
+            SupportingPoint { x: -3., y: 0.0 },
+            SupportingPoint { x: -2., y: 1.0 },
+            SupportingPoint { x: -0.5, y: 0.0 },
+            SupportingPoint { x: 1.5, y: 1.0 },
+        ];
+
+        let mut buffer = vec![-1f32; 3];
+        let mut interpolator = LinearInterpolation::new(supporting_points);
+
+        interpolator.interpolate(&mut buffer);
+
+        assert_eq!(&buffer, &[0.25, 0.75, -1.]);
+        assert_eq!(interpolator.value_at(-2.5), Some(0.5));
+    }
+
+    #[test]
+    fn value_at() {
+        let supporting_points = [
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 2., y: 1.0 },
+            SupportingPoint { x: 6., y: 0.0 },
+        ];
+
+        let interpolator = LinearInterpolation::new(supporting_points);
+
+        assert_eq!(interpolator.value_at(-0.1), None);
+        assert_eq!(interpolator.value_at(0.), Some(0.));
+        assert_eq!(interpolator.value_at(1.5), Some(0.75));
+        assert_eq!(interpolator.value_at(2.), Some(1.));
+        assert_eq!(interpolator.value_at(5.), Some(0.25));
+        assert_eq!(interpolator.value_at(6.), Some(0.));
+        assert_eq!(interpolator.value_at(6.1), None);
+    }
 }
@@ -1,37 +1,98 @@
 //! Everything related to the interpolation calculation.
+//!
+//! The interpolations are used to compute the bars between the supporting points of the [crate::BarProcessor]
+//! but they can be used for anything else (like shaping an envelope) as well.
+//!
+//! # Example
+//! ```
+//! use shady_audio::interpolation::{
+//!     Interpolater, InterpolationInner, LinearInterpolation, SupportingPoint,
+//! };
+//!
+//! let mut interpolation = LinearInterpolation::new([
+//!     SupportingPoint { x: 0., y: 0. },
+//!     SupportingPoint { x: 0.5, y: 1. },
+//!     SupportingPoint { x: 2.5, y: 0. },
+//! ]);
+//!
+//! // evaluate the interpolation at any point ...
+//! interpolation.update();
+//! assert_eq!(interpolation.value_at(0.25), Some(0.5));
+//! assert_eq!(interpolation.value_at(5.), None);
+//!
+//! // ... or at each index of a buffer
+//! let mut buffer = [0f32; 3];
+//! interpolation.interpolate(&mut buffer);
+//! assert_eq!(buffer, [0., 0.75, 0.25]);
+//! ```
 
 mod context;
 mod cubic_spline;
 mod linear;
+mod monotone_cubic;
 mod nothing;
 
 use std::slice::IterMut;
 
 pub use cubic_spline::CubicSplineInterpolation;
 pub use linear::LinearInterpolation;
+pub use monotone_cubic::MonotoneCubicInterpolation;
 pub use nothing::NothingInterpolation;
 
-pub trait Interpolater {
-    fn interpolate(&mut self, buffer: &mut [f32]);
+/// The floating point types which can be interpolated (`f32` and `f64`).
+pub trait Float: nalgebra::RealField + Copy {}
 
-    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint>;
+impl<T: nalgebra::RealField + Copy> Float for T {}
+
+/// The general interface of each interpolation.
+pub trait Interpolater<T: Float = f32> {
+    /// Recomputes the interpolation with the current values of the supporting points.
+    ///
+    /// Needs to be called after the supporting points have been changed with [Interpolater::supporting_points_mut]
+    /// to update the values of [Interpolater::value_at].
+    fn update(&mut self);
+
+    /// Returns the interpolated value at `x` or `None` if `x` is outside of the range of the supporting points.
+    ///
+    /// The value refers to the supporting points of the last call of [Interpolater::update] or [Interpolater::interpolate].
+    fn value_at(&self, x: T) -> Option<T>;
+
+    /// Updates the interpolation and stores the value at `x = i` in `buffer[i]` for each index `i`
+    /// within the range of the supporting points. The other values of the buffer are left untouched.
+    ///
+    /// # Panics
+    /// If the buffer is too short for the supporting points.
+    fn interpolate(&mut self, buffer: &mut [T]);
+
+    /// Returns the supporting points.
+    fn supporting_points(&self) -> &[SupportingPoint<T>];
+
+    /// Returns the supporting points to change their values.
+    ///
+    /// Only the `y` values should be changed. The `x` values are fixed after creating the interpolation.
+    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint<T>>;
 }
 
-pub trait InterpolationInner: Interpolater + Sized {
-    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint>) -> Self;
+/// Creates an interpolation.
+pub trait InterpolationInner<T: Float = f32>: Interpolater<T> + Sized {
+    /// Creates the interpolation with the given supporting points.
+    ///
+    /// # Panics
+    /// If the `x` values of the supporting points aren't strictly increasing.
+    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self;
 
-    fn boxed(supporting_points: impl IntoIterator<Item = SupportingPoint>) -> Box<Self> {
+    fn boxed(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Box<Self> {
         Box::new(Self::new(supporting_points))
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct SupportingPoint {
+pub struct SupportingPoint<T: Float = f32> {
     /// The x value of the supporting point
-    pub x: usize,
+    pub x: T,
 
     /// The y value of the supporting point
-    pub y: f32,
+    pub y: T,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,7 +101,22 @@ struct InterpolationSection {
     // The attribute stores the index of the supporting point within the data sturcture.
     pub left_supporting_point_idx: usize,
 
-    /// the amount of points which need to be interpolated
+    /// the amount of (buffer) indices which need to be interpolated
     /// within this section (up to the next supporting point)
     pub amount: usize,
 }
+
+fn cast<T: Float>(value: f64) -> T {
+    nalgebra::convert(value)
+}
+
+/// Returns the buffer index of `value` or `None` if `value` is negative.
+fn to_index<T: Float>(value: T) -> Option<usize> {
+    let value = nalgebra::try_convert::<T, f64>(value).unwrap();
+    (value >= 0.).then_some(value as usize)
+}
+
+/// Returns the first buffer index which is right of `x`.
+fn first_index_after<T: Float>(x: T) -> usize {
+    to_index(x.floor() + T::one()).unwrap_or(0)
+}
@@ -0,0 +1,230 @@
+use std::slice::IterMut;
+
+use super::{
+    cast, context::InterpolationCtx, Float, Interpolater, InterpolationInner, SupportingPoint,
+};
+
+/// Piecewise cubic hermite interpolation with the tangents of the [Fritsch–Carlson] method.
+///
+/// In contrast to [super::CubicSplineInterpolation], the interpolated values never leave the range
+/// of their two neighbouring supporting points, so no bar can become negative.
+///
+/// [Fritsch–Carlson]: https://en.wikipedia.org/wiki/Monotone_cubic_interpolation
+#[derive(Debug, Clone)]
+pub struct MonotoneCubicInterpolation<T: Float = f32> {
+    ctx: InterpolationCtx<T>,
+
+    // the gradient between each pair of neighbouring supporting points
+    secants: Box<[T]>,
+    // the gradient of the curve at each supporting point
+    tangents: Box<[T]>,
+}
+
+impl<T: Float> InterpolationInner<T> for MonotoneCubicInterpolation<T> {
+    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self {
+        let ctx = InterpolationCtx::new(supporting_points);
+
+        let amount_points = ctx.supporting_points.len();
+        let secants = vec![T::zero(); amount_points.saturating_sub(1)].into_boxed_slice();
+        let tangents = vec![T::zero(); amount_points].into_boxed_slice();
+
+        Self {
+            ctx,
+            secants,
+            tangents,
+        }
+    }
+}
+
+impl<T: Float> Interpolater<T> for MonotoneCubicInterpolation<T> {
+    fn update(&mut self) {
+        if self.ctx.supporting_points.len() < 2 {
+            return;
+        }
+
+        self.update_tangents();
+    }
+
+    fn value_at(&self, x: T) -> Option<T> {
+        self.ctx.value_at(x, |idx, x| self.value_in_section(idx, x))
+    }
+
+    fn interpolate(&mut self, buffer: &mut [T]) {
+        self.update();
+        self.ctx
+            .fill(buffer, |idx, x| self.value_in_section(idx, x));
+    }
+
+    fn supporting_points(&self) -> &[SupportingPoint<T>] {
+        &self.ctx.supporting_points
+    }
+
+    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint<T>> {
+        self.ctx.supporting_points.iter_mut()
+    }
+}
+
+impl<T: Float> MonotoneCubicInterpolation<T> {
+    fn value_in_section(&self, idx: usize, x: T) -> T {
+        let left = &self.ctx.supporting_points[idx];
+        let right = &self.ctx.supporting_points[idx + 1];
+
+        let width = right.x - left.x;
+        let left_tangent = self.tangents[idx] * width;
+        let right_tangent = self.tangents[idx + 1] * width;
+
+        let t = (x - left.x) / width;
+
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        let (two, three) = (cast::<T>(2.), cast::<T>(3.));
+
+        // hermite basis functions
+        let h00 = two * t3 - three * t2 + T::one();
+        let h10 = t3 - two * t2 + t;
+        let h01 = -two * t3 + three * t2;
+        let h11 = t3 - t2;
+
+        h00 * left.y + h10 * left_tangent + h01 * right.y + h11 * right_tangent
+    }
+
+    fn update_tangents(&mut self) {
+        let points = &self.ctx.supporting_points;
+
+        for (secant, pair) in self.secants.iter_mut().zip(points.windows(2)) {
+            *secant = (pair[1].y - pair[0].y) / (pair[1].x - pair[0].x);
+        }
+
+        let last = self.tangents.len() - 1;
+        self.tangents[0] = self.secants[0];
+        self.tangents[last] = self.secants[last - 1];
+
+        for k in 1..last {
+            let (prev, next) = (self.secants[k - 1], self.secants[k]);
+
+            // local extremum => flat tangent
+            self.tangents[k] = if prev * next <= T::zero() {
+                T::zero()
+            } else {
+                (prev + next) / cast(2.)
+            };
+        }
+
+        // restrict the tangents to prevent overshooting
+        for (k, &secant) in self.secants.iter().enumerate() {
+            if secant == T::zero() {
+                self.tangents[k] = T::zero();
+                self.tangents[k + 1] = T::zero();
+                continue;
+            }
+
+            let alpha = self.tangents[k] / secant;
+            let beta = self.tangents[k + 1] / secant;
+
+            let radius = alpha * alpha + beta * beta;
+            if radius > cast(9.) {
+                let tau = cast::<T>(3.) / radius.sqrt();
+                self.tangents[k] = tau * alpha * secant;
+                self.tangents[k + 1] = tau * beta * secant;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate_interpolation(supporting_points: &[SupportingPoint], interpolation: &[f32]) {
+        for pair in supporting_points.windows(2) {
+            // allow some floating point imprecision
+            let min = pair[0].y.min(pair[1].y) - f32::EPSILON;
+            let max = pair[0].y.max(pair[1].y) + f32::EPSILON;
+
+            for value in &interpolation[pair[0].x as usize..=pair[1].x as usize] {
+                assert!(
+                    (min..=max).contains(value),
+                    "{} is outside of [{}, {}]: {:?}",
+                    value,
+                    min,
+                    max,
+                    interpolation
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn no_supporting_points() {
+        let mut interpolator = MonotoneCubicInterpolation::<f32>::new([]);
+        let mut buffer = vec![];
+
+        interpolator.interpolate(&mut buffer);
+
+        assert_eq!(&buffer, &[]);
+    }
+
+    #[test]
+    fn one_supporting_point() {
+        let mut interpolator = MonotoneCubicInterpolation::new([SupportingPoint { x: 0., y: 1.0 }]);
+        let mut buffer = vec![0f32; 1];
+
+        interpolator.interpolate(&mut buffer);
+
+        assert_eq!(&buffer, &[1.]);
+    }
+
+    #[test]
+    fn two_supporting_points() {
+        let supporting_points = [
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 4., y: 1.0 },
+        ];
+
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
+        let mut interpolator = MonotoneCubicInterpolation::new(supporting_points.clone());
+
+        interpolator.interpolate(&mut buffer);
+
+        // both tangents are equal to the secant => it's a straight line
+        assert_eq!(&buffer, &[0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn no_undershoot_after_peak() {
+        // the cubic spline dips below zero after the peak
+        let supporting_points = [
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 5., y: 1. },
+            SupportingPoint { x: 10., y: 0. },
+            SupportingPoint { x: 15., y: 0. },
+            SupportingPoint { x: 20., y: 0.1 },
+        ];
+
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
+        let mut interpolator = MonotoneCubicInterpolation::new(supporting_points.clone());
+
+        interpolator.interpolate(&mut buffer);
+
+        validate_interpolation(&supporting_points, &buffer);
+    }
+
+    #[test]
+    fn steep_steps() {
+        let supporting_points = [
+            SupportingPoint { x: 0., y: 0. },
+            SupportingPoint { x: 2., y: 0.01 },
+            SupportingPoint { x: 4., y: 1. },
+            SupportingPoint { x: 6., y: 1. },
+            SupportingPoint { x: 12., y: 0.3 },
+        ];
+
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
+        let mut interpolator = MonotoneCubicInterpolation::new(supporting_points.clone());
+
+        interpolator.interpolate(&mut buffer);
+
+        validate_interpolation(&supporting_points, &buffer);
+    }
+}
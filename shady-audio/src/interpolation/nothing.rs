@@ -1,26 +1,54 @@
-use super::{context::InterpolationCtx, Interpolater, InterpolationInner};
+use std::slice::IterMut;
 
+use super::{context::InterpolationCtx, Float, Interpolater, InterpolationInner, SupportingPoint};
+
+/// Doesn't interpolate at all: Only the supporting points are stored in the buffer and
+/// [Interpolater::value_at] returns the value of the supporting point to the left of `x`.
 #[derive(Debug)]
-pub struct NothingInterpolation {
-    ctx: InterpolationCtx,
+pub struct NothingInterpolation<T: Float = f32> {
+    ctx: InterpolationCtx<T>,
 }
 
-impl InterpolationInner for NothingInterpolation {
-    fn new(supporting_points: impl IntoIterator<Item = super::SupportingPoint>) -> Self {
+impl<T: Float> InterpolationInner<T> for NothingInterpolation<T> {
+    fn new(supporting_points: impl IntoIterator<Item = SupportingPoint<T>>) -> Self {
         let ctx = InterpolationCtx::new(supporting_points);
 
         Self { ctx }
     }
 }
 
-impl Interpolater for NothingInterpolation {
-    fn interpolate(&mut self, buffer: &mut [f32]) {
+impl<T: Float> Interpolater<T> for NothingInterpolation<T> {
+    fn update(&mut self) {}
+
+    fn value_at(&self, x: T) -> Option<T> {
+        self.ctx.value_at(x, |idx, x| {
+            let right = &self.ctx.supporting_points[idx + 1];
+
+            if x == right.x {
+                right.y
+            } else {
+                self.ctx.supporting_points[idx].y
+            }
+        })
+    }
+
+    fn interpolate(&mut self, buffer: &mut [T]) {
         for point in self.ctx.supporting_points.iter() {
-            buffer[point.x] = point.y;
+            if point.x.fract() != T::zero() {
+                continue;
+            }
+
+            if let Some(idx) = super::to_index(point.x) {
+                buffer[idx] = point.y;
+            }
         }
     }
 
-    fn supporting_points_mut(&mut self) -> std::slice::IterMut<'_, super::SupportingPoint> {
+    fn supporting_points(&self) -> &[SupportingPoint<T>] {
+        &self.ctx.supporting_points
+    }
+
+    fn supporting_points_mut(&mut self) -> IterMut<'_, SupportingPoint<T>> {
         self.ctx.supporting_points.iter_mut()
     }
 }
@@ -34,12 +62,12 @@ mod tests {
     #[test]
     fn general() {
         let supporting_points = [
-            SupportingPoint { x: 0, y: 0.0 },
-            SupportingPoint { x: 3, y: 0.5 },
-            SupportingPoint { x: 4, y: 1.0 },
+            SupportingPoint { x: 0., y: 0.0 },
+            SupportingPoint { x: 3., y: 0.5 },
+            SupportingPoint { x: 4., y: 1.0 },
         ];
 
-        let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
+        let mut buffer = vec![0f32; supporting_points.last().unwrap().x as usize + 1];
         let mut interpolator = NothingInterpolation::new(supporting_points);
 
         interpolator.interpolate(&mut buffer);
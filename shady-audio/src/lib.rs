@@ -7,6 +7,16 @@
 //! This crate also re-exports [cpal] so there's no need to add [cpal] exclusively
 //! to your dependency list.
 //!
+//! # Feature flags
+//! - `metrics`: Emits counters and histograms (processed frames, silence, overshooting bars and
+//!   the duration of the bar updates) via the [metrics](https://docs.rs/metrics) facade.
+//!   See the `metrics` module for their names.
+//! - `jack`: Adds `fetcher::JackFetcher` which fetches the audio of specific JACK ports
+//!   (for example `system:capture_1` or the output of a DAW) and reports xruns.
+//!   Also enables the JACK host of [cpal] (see `util::get_hosts`).
+//! - `dmx`: Adds the `dmx` module which maps the bars to DMX channels (described in TOML)
+//!   and sends them to lights via Art-Net or sACN.
+//!
 //! # Example
 //!
 //! ## Simple workflow
@@ -90,16 +100,40 @@
 //!     break;
 //! }
 //! ```
+pub mod cava;
+#[cfg(feature = "dmx")]
+pub mod dmx;
 pub mod fetcher;
+pub mod interpolation;
+pub mod mesh;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod util;
 
 mod bar_processor;
-mod interpolation;
+mod calibration;
+mod chroma_processor;
+mod hpss_processor;
+mod offline;
+mod peak_hold;
+mod pitch_detector;
 mod sample_processor;
+mod slow_spectrum;
 
-pub use bar_processor::{BarProcessor, BarProcessorConfig, InterpolationVariant};
+pub use bar_processor::{
+    BarDistribution, BarProcessor, BarProcessorConfig, BarProcessorConfigError, BinAggregation,
+    FrameAnalysis, FrameTiming, FrequencyBand, InterpolationVariant, Limiter, Normalization,
+    PostProcessor, Preset, SilenceDecay, ValueScale, DEFAULT_ENERGY_BANDS, MIN_SENSITIVITY,
+};
+pub use calibration::SplCalibration;
+pub use chroma_processor::{ChromaProcessor, ChromaProcessorConfig, AMOUNT_PITCH_CLASSES};
 pub use cpal;
-pub use sample_processor::SampleProcessor;
+pub use hpss_processor::{HpssProcessor, HpssProcessorConfig};
+pub use offline::{analyze_file, analyze_samples, BarFrames, OfflineError};
+pub use peak_hold::{PeakHold, DEFAULT_PEAK_FALL_SPEED, DEFAULT_PEAK_HOLD_TIME};
+pub use pitch_detector::{Pitch, PitchDetector, PitchDetectorConfig};
+pub use sample_processor::{LowFrequencyFft, SampleProcessor, SILENCE_THRESHOLD};
+pub use slow_spectrum::{SlowSpectrum, DEFAULT_SLOW_TIME_CONSTANT};
 
 use cpal::SampleRate;
 
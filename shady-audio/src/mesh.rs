@@ -0,0 +1,348 @@
+//! Converts bar values into geometry for 3D scenes (for example with [bevy] or [wgpu]).
+//!
+//! The bars are placed next to each other along the x-axis (centered around the origin) and grow along the y-axis.
+//! The attributes are written into your buffer according to the [BufferLayout], so you can upload it directly
+//! as a vertex or instance buffer.
+//!
+//! # Example
+//! ```
+//! use shady_audio::mesh::{self, BarGeometry, BarShape, BufferLayout};
+//!
+//! // position (`vec3<f32>`) followed by the normal (`vec3<f32>`)
+//! let layout = BufferLayout {
+//!     stride: 6 * std::mem::size_of::<f32>(),
+//!     position: Some(0),
+//!     normal: Some(3 * std::mem::size_of::<f32>()),
+//!     ..Default::default()
+//! };
+//!
+//! let bars = [0.5, 1.0];
+//! let shape = BarShape::Quad;
+//!
+//! let mut vertices = vec![0u8; mesh::buffer_size(bars.len(), shape, &layout)];
+//! let amount_vertices = mesh::write_vertices(&bars, &BarGeometry::default(), shape, &layout, &mut vertices);
+//! assert_eq!(amount_vertices, 2 * 6);
+//! ```
+//!
+//! [bevy]: https://bevyengine.org/
+//! [wgpu]: https://wgpu.rs/
+use std::{f32::consts::TAU, num::NonZero};
+
+/// Where the bars are placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarGeometry {
+    /// The width of all bars together.
+    pub width: f32,
+
+    /// The height of a bar with the value `1`.
+    pub max_height: f32,
+
+    /// The portion of the space of each bar which is left empty within the range `[0, 1)`.
+    pub gap: f32,
+}
+
+impl Default for BarGeometry {
+    fn default() -> Self {
+        Self {
+            width: 2.,
+            max_height: 1.,
+            gap: 0.2,
+        }
+    }
+}
+
+impl BarGeometry {
+    /// Returns the x-coordinate of the center of the given bar and the width of each bar.
+    fn placement(&self, bar_idx: usize, amount_bars: usize) -> (f32, f32) {
+        let slot_width = self.width / amount_bars.max(1) as f32;
+        let center = -self.width / 2. + slot_width * (bar_idx as f32 + 0.5);
+
+        (center, slot_width * (1. - self.gap.clamp(0., 1.)))
+    }
+}
+
+/// The shape of each bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarShape {
+    /// A rectangle facing the positive z-axis.
+    Quad,
+
+    /// A cylinder with a top cap whose diameter is the width of the bar.
+    Cylinder { segments: NonZero<u16> },
+}
+
+impl BarShape {
+    /// Returns the amount of vertices which [write_vertices] writes for each bar.
+    pub fn vertices_per_bar(&self) -> usize {
+        match self {
+            // two triangles
+            Self::Quad => 6,
+            // two triangles for the side and one for the cap of each segment
+            Self::Cylinder { segments } => 9 * usize::from(segments.get()),
+        }
+    }
+}
+
+/// The byte offsets of the attributes within each vertex (or instance) of your buffer.
+///
+/// Each attribute is optional, set it to `None` if your buffer doesn't have it.
+/// All attributes are stored as (native endian) `f32`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferLayout {
+    /// The amount of bytes between the starts of two vertices (or instances).
+    pub stride: usize,
+
+    /// `vec3<f32>`: The position of the vertex or the bottom center of the bar for instances.
+    pub position: Option<usize>,
+
+    /// `vec3<f32>`: The normal of the vertex (ignored for instances).
+    pub normal: Option<usize>,
+
+    /// `f32`: The height of the bar (`value * max_height`).
+    pub height: Option<usize>,
+
+    /// `f32`: The value of the bar.
+    pub value: Option<usize>,
+}
+
+/// Returns the amount of bytes which [write_vertices] needs for the given amount of bars.
+pub fn buffer_size(amount_bars: usize, shape: BarShape, layout: &BufferLayout) -> usize {
+    amount_bars * shape.vertices_per_bar() * layout.stride
+}
+
+/// Writes the vertices of all bars (as a triangle list) into `out` and returns the amount of written vertices.
+///
+/// **Panics** if `out` is smaller than [buffer_size].
+pub fn write_vertices(
+    values: &[f32],
+    geometry: &BarGeometry,
+    shape: BarShape,
+    layout: &BufferLayout,
+    out: &mut [u8],
+) -> usize {
+    assert!(
+        out.len() >= buffer_size(values.len(), shape, layout),
+        "The output buffer must be able to store the vertices of all bars."
+    );
+
+    let mut vertices = out.chunks_exact_mut(layout.stride.max(1));
+    let mut amount_vertices = 0;
+    let mut write = |position: [f32; 3], normal: [f32; 3], value: f32| {
+        let vertex = vertices.next().expect("Buffer size has been checked");
+        write_attributes(vertex, layout, position, normal, value, geometry);
+        amount_vertices += 1;
+    };
+
+    for (bar_idx, &value) in values.iter().enumerate() {
+        let (center, bar_width) = geometry.placement(bar_idx, values.len());
+        let height = value * geometry.max_height;
+
+        match shape {
+            BarShape::Quad => {
+                let (left, right) = (center - bar_width / 2., center + bar_width / 2.);
+                let normal = [0., 0., 1.];
+
+                for [x, y] in [
+                    [left, 0.],
+                    [right, 0.],
+                    [right, height],
+                    [left, 0.],
+                    [right, height],
+                    [left, height],
+                ] {
+                    write([x, y, 0.], normal, value);
+                }
+            }
+            BarShape::Cylinder { segments } => {
+                let radius = bar_width / 2.;
+                let segments = segments.get();
+                // counter clockwise if you look from above
+                let direction = |segment: u16| {
+                    let angle = TAU * f32::from(segment) / f32::from(segments);
+                    [angle.cos(), 0., -angle.sin()]
+                };
+                let point = |[x, _, z]: [f32; 3], y: f32| [center + radius * x, y, radius * z];
+
+                for segment in 0..segments {
+                    let (dir0, dir1) = (direction(segment), direction(segment + 1));
+
+                    // side
+                    write(point(dir0, 0.), dir0, value);
+                    write(point(dir1, 0.), dir1, value);
+                    write(point(dir1, height), dir1, value);
+                    write(point(dir0, 0.), dir0, value);
+                    write(point(dir1, height), dir1, value);
+                    write(point(dir0, height), dir0, value);
+
+                    // cap
+                    let up = [0., 1., 0.];
+                    write([center, height, 0.], up, value);
+                    write(point(dir0, height), up, value);
+                    write(point(dir1, height), up, value);
+                }
+            }
+        }
+    }
+
+    amount_vertices
+}
+
+/// Writes one instance per bar into `out` and returns the amount of written instances.
+///
+/// Use it if you draw the same mesh (for example a unit cube) for each bar and scale it with the height.
+///
+/// **Panics** if `out` can't store `values.len()` instances.
+pub fn write_instances(
+    values: &[f32],
+    geometry: &BarGeometry,
+    layout: &BufferLayout,
+    out: &mut [u8],
+) -> usize {
+    assert!(
+        out.len() >= values.len() * layout.stride,
+        "The output buffer must be able to store the instances of all bars."
+    );
+
+    let instances = out.chunks_exact_mut(layout.stride.max(1));
+    for ((bar_idx, &value), instance) in values.iter().enumerate().zip(instances) {
+        let (center, _) = geometry.placement(bar_idx, values.len());
+
+        let layout = BufferLayout {
+            normal: None,
+            ..*layout
+        };
+        write_attributes(
+            instance,
+            &layout,
+            [center, 0., 0.],
+            [0.; 3],
+            value,
+            geometry,
+        );
+    }
+
+    values.len()
+}
+
+fn write_attributes(
+    out: &mut [u8],
+    layout: &BufferLayout,
+    position: [f32; 3],
+    normal: [f32; 3],
+    value: f32,
+    geometry: &BarGeometry,
+) {
+    let attributes: [(Option<usize>, &[f32]); 4] = [
+        (layout.position, &position),
+        (layout.normal, &normal),
+        (layout.height, &[value * geometry.max_height]),
+        (layout.value, &[value]),
+    ];
+
+    for (offset, values) in attributes {
+        let Some(offset) = offset else {
+            continue;
+        };
+
+        for (idx, value) in values.iter().enumerate() {
+            let start = offset + idx * std::mem::size_of::<f32>();
+            out[start..start + std::mem::size_of::<f32>()].copy_from_slice(&value.to_ne_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const F32: usize = std::mem::size_of::<f32>();
+
+    fn floats(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .as_chunks::<F32>()
+            .0
+            .iter()
+            .map(|bytes| f32::from_ne_bytes(*bytes))
+            .collect()
+    }
+
+    #[test]
+    fn quads() {
+        let layout = BufferLayout {
+            stride: 4 * F32,
+            position: Some(0),
+            value: Some(3 * F32),
+            ..Default::default()
+        };
+        let geometry = BarGeometry {
+            width: 4.,
+            max_height: 2.,
+            gap: 0.,
+        };
+
+        let mut out = vec![0u8; buffer_size(2, BarShape::Quad, &layout)];
+        let amount = write_vertices(&[0.5, 1.], &geometry, BarShape::Quad, &layout, &mut out);
+        assert_eq!(amount, 12);
+
+        let vertices = floats(&out);
+        // top right vertex of the first bar
+        assert_eq!(&vertices[2 * 4..3 * 4], &[0., 1., 0., 0.5]);
+        // top left vertex of the second bar
+        assert_eq!(&vertices[11 * 4..12 * 4], &[0., 2., 0., 1.]);
+    }
+
+    #[test]
+    fn cylinder_normals_point_outwards() {
+        let layout = BufferLayout {
+            stride: 6 * F32,
+            position: Some(0),
+            normal: Some(3 * F32),
+            ..Default::default()
+        };
+        let shape = BarShape::Cylinder {
+            segments: NonZero::new(8).unwrap(),
+        };
+
+        let mut out = vec![0u8; buffer_size(1, shape, &layout)];
+        let amount = write_vertices(&[1.], &BarGeometry::default(), shape, &layout, &mut out);
+        assert_eq!(amount, 8 * 9);
+
+        for triangle in floats(&out).as_chunks::<{ 3 * 6 }>().0 {
+            let vertex = |idx: usize| &triangle[idx * 6..idx * 6 + 3];
+            let edge = |idx: usize| [0, 1, 2].map(|axis| vertex(idx)[axis] - vertex(0)[axis]);
+            let (a, b) = (edge(1), edge(2));
+            let face_normal = [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ];
+
+            // the winding order matches the normals
+            let normal = &triangle[3..6];
+            let dot = (0..3)
+                .map(|axis| face_normal[axis] * normal[axis])
+                .sum::<f32>();
+            assert!(dot > 0., "{:?}", triangle);
+        }
+    }
+
+    #[test]
+    fn instances() {
+        let layout = BufferLayout {
+            stride: 5 * F32,
+            position: Some(0),
+            height: Some(3 * F32),
+            value: Some(4 * F32),
+            ..Default::default()
+        };
+        let geometry = BarGeometry {
+            width: 2.,
+            max_height: 3.,
+            gap: 0.5,
+        };
+
+        let mut out = vec![0u8; 2 * layout.stride];
+        assert_eq!(write_instances(&[0., 1.], &geometry, &layout, &mut out), 2);
+        assert_eq!(floats(&out), [-0.5, 0., 0., 0., 0., 0.5, 0., 0., 3., 1.]);
+    }
+}
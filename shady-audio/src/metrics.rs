@@ -0,0 +1,36 @@
+//! The metrics which are emitted via the [metrics] facade if the `metrics` feature is enabled.
+//!
+//! Nothing is recorded until you install a recorder, for example the one of
+//! [metrics-exporter-prometheus](https://docs.rs/metrics-exporter-prometheus).
+//! The silence ratio of the audio is `SILENT_FRAMES / FRAMES_PROCESSED`.
+use std::time::Duration;
+
+/// Counter: The amount of calls of [crate::SampleProcessor::process_next_samples].
+pub const FRAMES_PROCESSED: &str = "shady_audio_frames_processed_total";
+
+/// Counter: The amount of processed frames whose samples were silent (see [crate::SILENCE_THRESHOLD]).
+pub const SILENT_FRAMES: &str = "shady_audio_silent_frames_total";
+
+/// Counter: How often the bars of a channel have exceeded `1`
+/// (the adaptive normalization lowers its gain afterwards).
+pub const BAR_OVERSHOOTS: &str = "shady_audio_bar_overshoots_total";
+
+/// Histogram: How long (in seconds) [crate::BarProcessor::process_bars]
+/// (or [crate::BarProcessor::process_bars_into]) took.
+pub const BAR_UPDATE_DURATION: &str = "shady_audio_bar_update_seconds";
+
+pub(crate) fn record_frame(is_silent: bool) {
+    metrics::counter!(FRAMES_PROCESSED).increment(1);
+
+    if is_silent {
+        metrics::counter!(SILENT_FRAMES).increment(1);
+    }
+}
+
+pub(crate) fn record_overshoot() {
+    metrics::counter!(BAR_OVERSHOOTS).increment(1);
+}
+
+pub(crate) fn record_bar_update(duration: Duration) {
+    metrics::histogram!(BAR_UPDATE_DURATION).record(duration);
+}
@@ -0,0 +1,343 @@
+//! Computes the bars of a whole track at once (faster than real time), for example to pre-render
+//! the visuals of a music video or to feed the bars into a non-realtime pipeline.
+use std::{num::NonZero, path::Path, sync::Arc, time::Duration};
+
+use cpal::SampleRate;
+
+use crate::{
+    fetcher::{Fetcher, FileFetcherError},
+    BarProcessor, BarProcessorConfig, SampleProcessor,
+};
+
+/// Errors which can occur while analyzing a track, see [analyze_samples] and [analyze_file].
+#[derive(thiserror::Error, Debug)]
+pub enum OfflineError {
+    /// The samples don't have any channels.
+    #[error("The audio doesn't have any channels")]
+    NoChannels,
+
+    #[error(transparent)]
+    File(#[from] FileFetcherError),
+}
+
+/// The bar values of each channel for each frame of a track, see [analyze_samples].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarFrames {
+    frame_rate: NonZero<u32>,
+    amount_channels: usize,
+    amount_bars: usize,
+
+    // frame by frame, the channels of a frame one after another
+    values: Box<[f32]>,
+}
+
+impl BarFrames {
+    pub fn frame_rate(&self) -> NonZero<u32> {
+        self.frame_rate
+    }
+
+    pub fn amount_channels(&self) -> usize {
+        self.amount_channels
+    }
+
+    pub fn amount_bars(&self) -> usize {
+        self.amount_bars
+    }
+
+    /// Returns the amount of frames.
+    pub fn len(&self) -> usize {
+        self.values.len() / (self.amount_channels * self.amount_bars).max(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bars of all channels of the given frame, one channel after another.
+    ///
+    /// # Panics
+    /// If `frame` is out of range.
+    pub fn frame(&self, frame: usize) -> &[f32] {
+        let frame_len = self.amount_channels * self.amount_bars;
+        &self.values[frame * frame_len..(frame + 1) * frame_len]
+    }
+
+    /// Returns the bars of `channel` at the given frame.
+    ///
+    /// # Panics
+    /// If `frame` or `channel` are out of range.
+    pub fn bars(&self, frame: usize, channel: usize) -> &[f32] {
+        assert!(
+            channel < self.amount_channels,
+            "Invalid channel {}",
+            channel
+        );
+        &self.frame(frame)[channel * self.amount_bars..(channel + 1) * self.amount_bars]
+    }
+
+    /// Returns the index of the frame which should be shown at `time` (clamped to the last frame).
+    pub fn frame_at(&self, time: Duration) -> usize {
+        let frame = (time.as_secs_f64() * f64::from(self.frame_rate.get())) as usize;
+        frame.min(self.len().saturating_sub(1))
+    }
+
+    /// Iterates over the frames (see [BarFrames::frame]).
+    pub fn iter(&self) -> impl Iterator<Item = &[f32]> {
+        (0..self.len()).map(|frame| self.frame(frame))
+    }
+}
+
+/// Computes the bars of the given interleaved samples with `frame_rate` frames per second.
+///
+/// Frame `n` contains the bars of the audio until `n / frame_rate` seconds, like they would have been computed
+/// while the track is playing. The time between two frames is passed to the bar processor
+/// (see [BarProcessor::set_delta_time]), so the result doesn't depend on [BarProcessorConfig::frame_timing]
+/// and it's the same on each run.
+///
+/// # Example
+/// ```rust
+/// use std::num::NonZero;
+/// use shady_audio::{analyze_samples, BarProcessorConfig, cpal::SampleRate};
+///
+/// // one second of silence (mono)
+/// let samples = vec![0.; 44_100];
+///
+/// let frames = analyze_samples(
+///     samples,
+///     1,
+///     SampleRate(44_100),
+///     BarProcessorConfig::default(),
+///     NonZero::new(30).unwrap(),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(frames.len(), 31);
+/// assert!(frames.bars(30, 0).iter().all(|&bar| bar == 0.));
+/// ```
+///
+/// Returns [OfflineError::NoChannels] if `amount_channels` is `0`.
+pub fn analyze_samples(
+    samples: impl Into<Arc<[f32]>>,
+    amount_channels: u16,
+    sample_rate: SampleRate,
+    config: BarProcessorConfig,
+    frame_rate: NonZero<u32>,
+) -> Result<BarFrames, OfflineError> {
+    if amount_channels == 0 {
+        return Err(OfflineError::NoChannels);
+    }
+
+    let fetcher = Box::new(SliceFetcher::new(
+        samples.into(),
+        amount_channels,
+        sample_rate,
+        frame_rate,
+    ));
+    let amount_frames = fetcher.amount_frames();
+
+    let mut sample_processor = SampleProcessor::new(fetcher);
+    let mut bar_processor = BarProcessor::new(&sample_processor, config);
+    let delta_time = Duration::from_secs_f64(1. / f64::from(frame_rate.get()));
+
+    let mut values = Vec::new();
+    for _ in 0..amount_frames {
+        sample_processor.process_next_samples();
+
+        bar_processor.set_delta_time(delta_time);
+        for channel in bar_processor.process_bars(&sample_processor) {
+            values.extend_from_slice(channel);
+        }
+    }
+
+    Ok(BarFrames {
+        frame_rate,
+        amount_channels: usize::from(amount_channels),
+        amount_bars: usize::from(bar_processor.config().amount_bars.get()),
+        values: values.into_boxed_slice(),
+    })
+}
+
+/// Like [analyze_samples] but decodes the given audio file (currently only wav files) first.
+pub fn analyze_file(
+    path: &Path,
+    config: BarProcessorConfig,
+    frame_rate: NonZero<u32>,
+) -> Result<BarFrames, OfflineError> {
+    let (samples, spec) = crate::fetcher::decode(path)?;
+
+    analyze_samples(
+        samples,
+        spec.channels,
+        SampleRate(spec.sample_rate),
+        config,
+        frame_rate,
+    )
+}
+
+/// Returns the amount of audio frames until the given (video) frame.
+pub(crate) fn frame_position(
+    frame: u64,
+    sample_rate: SampleRate,
+    frame_rate: NonZero<u32>,
+) -> usize {
+    (frame * u64::from(sample_rate.0) / u64::from(frame_rate.get())) as usize
+}
+
+/// Provides the samples of one frame on each fetch.
+struct SliceFetcher {
+    // interleaved
+    samples: Arc<[f32]>,
+    amount_channels: u16,
+    sample_rate: SampleRate,
+    frame_rate: NonZero<u32>,
+
+    // the amount of fetches so far
+    fetches: u64,
+    // the amount of samples (not frames) which have been fetched
+    position: usize,
+}
+
+impl SliceFetcher {
+    fn new(
+        samples: Arc<[f32]>,
+        amount_channels: u16,
+        sample_rate: SampleRate,
+        frame_rate: NonZero<u32>,
+    ) -> Self {
+        Self {
+            samples,
+            amount_channels,
+            sample_rate,
+            frame_rate,
+            fetches: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns the amount of frames which are needed to cover all samples.
+    fn amount_frames(&self) -> u64 {
+        let audio_frames = (self.samples.len() / usize::from(self.amount_channels.max(1))) as u64;
+
+        // the first frame is at the start of the track
+        (audio_frames * u64::from(self.frame_rate.get())).div_ceil(u64::from(self.sample_rate.0))
+            + 1
+    }
+
+    /// Returns the position (in samples) at the time of the given frame.
+    fn position_of(&self, frame: u64) -> usize {
+        let position = frame_position(frame, self.sample_rate, self.frame_rate)
+            * usize::from(self.amount_channels);
+
+        position.min(self.samples.len())
+    }
+}
+
+impl Fetcher for SliceFetcher {
+    fn fetch_samples(&mut self, buf: &mut [f32]) -> usize {
+        let position = self.position_of(self.fetches);
+        let new_samples = &self.samples[self.position..position];
+        self.position = position;
+        self.fetches += 1;
+
+        // only the newest samples fit into the buffer
+        let amount_samples = buf.len().min(new_samples.len());
+        let inserted = &new_samples[new_samples.len() - amount_samples..];
+
+        let buf_len = buf.len();
+        buf.copy_within(..buf_len - amount_samples, amount_samples);
+        buf[..amount_samples].copy_from_slice(inserted);
+
+        new_samples.len()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.amount_channels
+    }
+
+    fn timestamp(&self) -> Option<Duration> {
+        let frames = self.position / usize::from(self.amount_channels.max(1));
+        Some(Duration::from_secs_f64(
+            frames as f64 / f64::from(self.sample_rate.0),
+        ))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= self.samples.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|idx| (std::f32::consts::TAU * freq * idx as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn frames_cover_the_whole_track() {
+        let fetcher = SliceFetcher::new(
+            vec![0.; 2 * 44_100].into(),
+            2,
+            SampleRate(44_100),
+            NonZero::new(60).unwrap(),
+        );
+
+        assert_eq!(fetcher.amount_frames(), 61);
+        assert_eq!(fetcher.position_of(30), 44_100);
+        assert_eq!(fetcher.position_of(61), 2 * 44_100);
+    }
+
+    #[test]
+    fn analysis_is_deterministic() {
+        // silence followed by a sine
+        let mut samples = vec![0.; 22_050];
+        samples.extend(sine(440., 44_100, 22_050));
+
+        let analyze = || {
+            analyze_samples(
+                samples.as_slice(),
+                1,
+                SampleRate(44_100),
+                BarProcessorConfig::default(),
+                NonZero::new(10).unwrap(),
+            )
+            .unwrap()
+        };
+
+        let frames = analyze();
+        assert_eq!(frames.len(), 11);
+        assert_eq!(frames.amount_channels(), 1);
+        assert_eq!(
+            frames.frame(0).len(),
+            usize::from(BarProcessorConfig::default().amount_bars.get())
+        );
+
+        assert!(frames
+            .bars(frames.frame_at(Duration::from_millis(400)), 0)
+            .iter()
+            .all(|&bar| bar == 0.));
+        assert!(frames.bars(10, 0).iter().any(|&bar| bar > 0.));
+
+        assert_eq!(frames, analyze());
+    }
+
+    #[test]
+    fn zero_channels_are_rejected() {
+        let result = analyze_samples(
+            vec![0.; 44_100],
+            0,
+            SampleRate(44_100),
+            BarProcessorConfig::default(),
+            NonZero::new(30).unwrap(),
+        );
+
+        assert!(matches!(result, Err(OfflineError::NoChannels)));
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+/// The default time which [PeakHold] keeps a peak before it starts to fall.
+pub const DEFAULT_PEAK_HOLD_TIME: Duration = Duration::from_millis(500);
+
+/// The default speed (in bar values per second) with which the peaks of [PeakHold] fall.
+pub const DEFAULT_PEAK_FALL_SPEED: f32 = 0.5;
+
+/// Keeps the highest value of each bar for a while and lets it fall down slowly afterwards,
+/// like the floating peak caps of classic spectrum analyzers.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, PeakHold, fetcher::{DummyFetcher, Waveform}};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(1, Waveform::Sine { frequency: 440. }));
+/// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+/// let mut peak_hold = PeakHold::new(Duration::from_secs(1), 0.25);
+///
+/// sample_processor.process_next_samples();
+/// let bars = bar_processor.process_bars(&sample_processor);
+/// let peaks = peak_hold.update(&bars[0], Duration::from_millis(16));
+///
+/// // the peaks are never below their bars
+/// assert!(peaks.iter().zip(bars[0].iter()).all(|(peak, bar)| peak >= bar));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PeakHold {
+    hold_time: Duration,
+    fall_speed: f32,
+
+    peaks: Box<[f32]>,
+    // the time since each peak has been reached
+    ages: Box<[Duration]>,
+}
+
+impl PeakHold {
+    /// Creates a new instance which keeps each peak for `hold_time` and lets it fall with `fall_speed`
+    /// (in bar values per second) afterwards.
+    pub fn new(hold_time: Duration, fall_speed: f32) -> Self {
+        Self {
+            hold_time,
+            fall_speed,
+            peaks: Box::new([]),
+            ages: Box::new([]),
+        }
+    }
+
+    /// Raises the peaks to the given bars and lets the older peaks fall according to the time which has passed
+    /// since the last update.
+    ///
+    /// The peaks are reset if the amount of bars changes.
+    pub fn update(&mut self, bars: &[f32], delta_time: Duration) -> &[f32] {
+        if self.peaks.len() != bars.len() {
+            self.peaks = vec![0.; bars.len()].into_boxed_slice();
+            self.ages = vec![Duration::ZERO; bars.len()].into_boxed_slice();
+        }
+
+        for ((peak, age), &bar) in self.peaks.iter_mut().zip(self.ages.iter_mut()).zip(bars) {
+            let prev_age = *age;
+            *age = age.saturating_add(delta_time);
+
+            // only the time after the hold time lets the peak fall
+            let fall_time = age.saturating_sub(self.hold_time.max(prev_age));
+            *peak -= self.fall_speed * fall_time.as_secs_f32();
+
+            if bar >= *peak {
+                *peak = bar;
+                *age = Duration::ZERO;
+            }
+        }
+
+        &self.peaks
+    }
+
+    /// Returns the peaks of the last update.
+    pub fn values(&self) -> &[f32] {
+        &self.peaks
+    }
+
+    pub fn hold_time(&self) -> Duration {
+        self.hold_time
+    }
+
+    pub fn fall_speed(&self) -> f32 {
+        self.fall_speed
+    }
+
+    /// Changes how long the peaks are kept and how fast they fall afterwards. The current peaks are kept.
+    pub fn set_timing(&mut self, hold_time: Duration, fall_speed: f32) {
+        self.hold_time = hold_time;
+        self.fall_speed = fall_speed;
+    }
+}
+
+impl Default for PeakHold {
+    fn default() -> Self {
+        Self::new(DEFAULT_PEAK_HOLD_TIME, DEFAULT_PEAK_FALL_SPEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hold_then_fall() {
+        let mut peak_hold = PeakHold::new(Duration::from_millis(100), 1.);
+        peak_hold.update(&[1., 0.5], Duration::ZERO);
+
+        // still held
+        assert_eq!(
+            peak_hold.update(&[0., 0.], Duration::from_millis(100)),
+            &[1., 0.5]
+        );
+
+        // falls for 50ms
+        let peaks = peak_hold.update(&[0., 0.], Duration::from_millis(50));
+        assert!((peaks[0] - 0.95).abs() < 1e-6);
+
+        // caught by its bar
+        assert_eq!(peak_hold.update(&[0., 0.2], Duration::from_secs(1))[1], 0.2);
+    }
+
+    #[test]
+    fn new_peak_restarts_hold() {
+        let mut peak_hold = PeakHold::new(Duration::from_millis(100), 1.);
+        peak_hold.update(&[0.5], Duration::ZERO);
+        peak_hold.update(&[0.8], Duration::from_millis(90));
+
+        assert_eq!(peak_hold.update(&[0.], Duration::from_millis(90)), &[0.8]);
+    }
+}
@@ -0,0 +1,221 @@
+use std::{num::NonZero, ops::Range};
+
+use crate::{SampleProcessor, SILENCE_THRESHOLD};
+
+/// The config options for [PitchDetector].
+#[derive(Debug, Clone)]
+pub struct PitchDetectorConfig {
+    /// The range where the fundamental frequency is searched in.
+    ///
+    /// The lowest detectable frequency is limited by the amount of samples which the [SampleProcessor]
+    /// analyzes at once (roughly `170` Hz for two channels with a sample rate of `44_100` Hz).
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// The threshold of the YIN algorithm within the range `(0, 1)`.
+    /// Lower values pick fewer but more reliable periods.
+    pub threshold: f32,
+}
+
+impl Default for PitchDetectorConfig {
+    fn default() -> Self {
+        Self {
+            freq_range: NonZero::new(80).unwrap()..NonZero::new(2_000).unwrap(),
+            threshold: 0.15,
+        }
+    }
+}
+
+/// The detected fundamental frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pitch {
+    /// The fundamental frequency in Hz.
+    pub frequency: f32,
+
+    /// How periodic the samples are within the range `[0, 1]`.
+    /// Noise and chords have a low confidence, a clean tone is close to `1`.
+    pub confidence: f32,
+}
+
+/// Detects the fundamental frequency of the samples of the [SampleProcessor] with the [YIN] algorithm.
+///
+/// All channels of the [SampleProcessor] are combined.
+///
+/// # Example
+/// ```
+/// use shady_audio::{PitchDetector, PitchDetectorConfig, SampleProcessor, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut pitch_detector = PitchDetector::new(&sample_processor, PitchDetectorConfig::default());
+///
+/// sample_processor.process_next_samples();
+///
+/// // the dummy fetcher is silent
+/// assert!(pitch_detector.process(&sample_processor).is_none());
+/// ```
+///
+/// [YIN]: http://audition.ens.fr/adc/pdf/2002_JASA_YIN.pdf
+pub struct PitchDetector {
+    config: PitchDetectorConfig,
+    sample_rate: f32,
+
+    // the samples of all channels combined
+    samples: Box<[f32]>,
+    // the cumulative mean normalized difference of each lag
+    differences: Box<[f32]>,
+    lag_range: Range<usize>,
+}
+
+impl PitchDetector {
+    /// Creates a new instance which works with the given sample processor.
+    pub fn new(processor: &SampleProcessor, config: PitchDetectorConfig) -> Self {
+        let sample_rate = processor.sample_rate().0 as f32;
+        let amount_samples = processor.fft_size() / processor.amount_channels().max(1);
+
+        // the samples are compared with the samples which are one lag later
+        let window = amount_samples / 2;
+        let max_lag = amount_samples - window;
+
+        let lag_range = {
+            let start = (sample_rate / f32::from(config.freq_range.end.get())).floor() as usize;
+            let end = (sample_rate / f32::from(config.freq_range.start.get())).ceil() as usize;
+
+            start.max(2)..end.min(max_lag)
+        };
+
+        Self {
+            config,
+            sample_rate,
+            samples: vec![0.; amount_samples].into_boxed_slice(),
+            differences: vec![0.; max_lag + 1].into_boxed_slice(),
+            lag_range,
+        }
+    }
+
+    /// Detects the fundamental frequency of the current samples of the given processor.
+    ///
+    /// Returns `None` if the samples are silent.
+    pub fn process(&mut self, processor: &SampleProcessor) -> Option<Pitch> {
+        let amount_channels = processor.amount_channels().max(1);
+        for (sample, frame) in self
+            .samples
+            .iter_mut()
+            .zip(processor.raw_samples().chunks_exact(amount_channels))
+        {
+            *sample = frame.iter().sum::<f32>() / amount_channels as f32;
+        }
+
+        if self
+            .samples
+            .iter()
+            .all(|sample| sample.abs() < SILENCE_THRESHOLD)
+        {
+            return None;
+        }
+
+        self.update_differences();
+        let lag = self.best_lag()?;
+
+        Some(Pitch {
+            frequency: self.sample_rate / self.refine_lag(lag),
+            confidence: (1. - self.differences[lag]).clamp(0., 1.),
+        })
+    }
+
+    /// Returns the config of the processor.
+    pub fn config(&self) -> &PitchDetectorConfig {
+        &self.config
+    }
+
+    /// Computes the cumulative mean normalized difference function (step 2 and 3 of YIN).
+    fn update_differences(&mut self) {
+        let window = self.samples.len() / 2;
+
+        self.differences[0] = 1.;
+        let mut sum = 0.;
+        for lag in 1..self.differences.len() {
+            let difference = (0..window)
+                .map(|idx| {
+                    let delta = self.samples[idx] - self.samples[idx + lag];
+                    delta * delta
+                })
+                .sum::<f32>();
+
+            sum += difference;
+            self.differences[lag] = if sum > 0. {
+                difference * lag as f32 / sum
+            } else {
+                1.
+            };
+        }
+    }
+
+    /// Returns the first lag whose difference is below the threshold (step 4 of YIN)
+    /// or the lag with the smallest difference if there's none.
+    fn best_lag(&self) -> Option<usize> {
+        let lags = self.lag_range.clone();
+
+        let below_threshold = lags
+            .clone()
+            .find(|&lag| self.differences[lag] < self.config.threshold)
+            .map(|mut lag| {
+                // walk down to the local minimum
+                while lag + 1 < lags.end && self.differences[lag + 1] < self.differences[lag] {
+                    lag += 1;
+                }
+                lag
+            });
+
+        below_threshold
+            .or_else(|| lags.min_by(|&a, &b| self.differences[a].total_cmp(&self.differences[b])))
+    }
+
+    /// Interpolates the position of the minimum around `lag` with a parabola (step 5 of YIN).
+    fn refine_lag(&self, lag: usize) -> f32 {
+        let Some(&next) = self.differences.get(lag + 1) else {
+            return lag as f32;
+        };
+        let prev = self.differences[lag - 1];
+        let curr = self.differences[lag];
+
+        let denominator = prev - 2. * curr + next;
+        if denominator.abs() < f32::EPSILON {
+            return lag as f32;
+        }
+
+        lag as f32 + (prev - next) / (2. * denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetcher::{DummyFetcher, Waveform};
+
+    use super::*;
+
+    fn detect(freq: f32, channels: u16) -> Pitch {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+            channels,
+            Waveform::Sine { frequency: freq },
+        ));
+        let mut pitch_detector =
+            PitchDetector::new(&sample_processor, PitchDetectorConfig::default());
+
+        sample_processor.process_next_samples();
+        pitch_detector.process(&sample_processor).unwrap()
+    }
+
+    #[test]
+    fn detects_sine() {
+        for (freq, channels) in [(220., 1), (440., 2), (1_000., 1)] {
+            let pitch = detect(freq, channels);
+
+            assert!(
+                (pitch.frequency - freq).abs() < freq * 0.01,
+                "{} Hz: {:?}",
+                freq,
+                pitch
+            );
+            assert!(pitch.confidence > 0.9, "{} Hz: {:?}", freq, pitch);
+        }
+    }
+}
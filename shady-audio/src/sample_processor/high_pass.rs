@@ -0,0 +1,70 @@
+use std::f32::consts::PI;
+
+/// A first order high-pass filter to remove the DC offset and subsonic rumble of the samples.
+///
+/// The samples of the [crate::SampleProcessor] are a sliding window, so the filter is applied
+/// to the whole window instead of keeping a state between two calls. The mean of the window is removed
+/// beforehand so that a DC offset doesn't cause a transient at the start of the window.
+#[derive(Debug, Clone, Copy)]
+pub struct HighPassFilter {
+    alpha: f32,
+}
+
+impl HighPassFilter {
+    pub fn new(cutoff: f32, sample_rate: u32) -> Self {
+        let rc = 1. / (2. * PI * cutoff);
+        let dt = 1. / sample_rate as f32;
+
+        Self {
+            alpha: rc / (rc + dt),
+        }
+    }
+
+    /// Filters the given samples. The newest sample is expected to be at the front.
+    pub fn apply(&self, samples: &mut [f32]) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+
+        // start with the oldest sample
+        let mut prev_input = samples[samples.len() - 1] - mean;
+        let mut prev_output = 0.;
+        for sample in samples.iter_mut().rev() {
+            let input = *sample - mean;
+            prev_output = self.alpha * (prev_output + input - prev_input);
+            prev_input = input;
+
+            *sample = prev_output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44_100;
+
+    #[test]
+    fn removes_dc_offset() {
+        let mut samples = [0.3; 1024];
+        HighPassFilter::new(20., SAMPLE_RATE).apply(&mut samples);
+
+        assert!(samples.iter().all(|sample| sample.abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn keeps_audible_frequencies() {
+        let sine = |idx: usize| (2. * PI * 1_000. * idx as f32 / SAMPLE_RATE as f32).sin();
+
+        let mut samples = std::array::from_fn::<f32, 4096, _>(|idx| sine(idx) + 0.5);
+        HighPassFilter::new(20., SAMPLE_RATE).apply(&mut samples);
+
+        // ignore the oldest samples where the filter settles
+        for (idx, sample) in samples.iter().enumerate().take(2048) {
+            assert!((sample - sine(idx)).abs() < 0.05, "{}: {}", idx, sample);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+mod high_pass;
+
+use std::{
+    num::NonZero,
+    time::{Duration, Instant},
+};
+
+use cpal::SampleRate;
+use high_pass::HighPassFilter;
+use realfft::{num_complex::Complex32, RealFftPlanner};
+
+use crate::{fetcher::Fetcher, MIN_HUMAN_FREQUENCY};
+
+/// Samples whose amplitude is below this value are treated as silence.
+pub const SILENCE_THRESHOLD: f32 = 1e-4;
+
+/// Configures the additional longer FFT for the low frequencies (see [SampleProcessor::set_low_frequency_fft]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LowFrequencyFft {
+    /// The amount of samples of each channel which are analyzed by the longer FFT.
+    ///
+    /// It's rounded up to the next power of two and it's at least as long as the regular FFT.
+    pub size: usize,
+
+    /// The frequency (in Hz) below which the bins of the longer FFT are used.
+    pub crossover: NonZero<u16>,
+}
+
+impl Default for LowFrequencyFft {
+    fn default() -> Self {
+        Self {
+            size: 8192,
+            crossover: NonZero::new(200).unwrap(),
+        }
+    }
+}
+
+/// Prepares the samples of the fetcher for the [crate::BarProcessor].
+pub struct SampleProcessor {
+    planner: RealFftPlanner<f32>,
+    hann_window: Box<[f32]>,
+
+    fft_in_raw: Box<[f32]>,
+    high_pass: Option<(NonZero<u16>, HighPassFilter)>,
+
+    channels: Box<[FftContext]>,
+    low_frequency: Option<LowFrequencyAnalysis>,
+
+    fft_size: usize,
+    fetcher: Box<dyn Fetcher>,
+
+    processed_batches: u64,
+    // the amount of new samples of the last batch (see `Fetcher::fetch_samples`)
+    amount_new_samples: usize,
+
+    // used as the clock if the fetcher doesn't provide timestamps
+    created: Instant,
+    // the time of the first batch of the current silence
+    silent_since: Option<Duration>,
+    silence_duration: Duration,
+}
+
+impl SampleProcessor {
+    /// Creates a new instance with the given fetcher where the audio samples are fetched from.
+    ///
+    /// Frequencies below [MIN_HUMAN_FREQUENCY] are filtered out by default, see [SampleProcessor::set_high_pass_cutoff].
+    pub fn new(fetcher: Box<dyn Fetcher>) -> Self {
+        let fft_size = {
+            let sample_rate = fetcher.sample_rate().0;
+            let factor = if sample_rate < 8_125 {
+                1
+            } else if sample_rate <= 16_250 {
+                2
+            } else if sample_rate <= 32_500 {
+                4
+            } else if sample_rate <= 75_000 {
+                8
+            } else if sample_rate <= 150_000 {
+                16
+            } else if sample_rate <= 300_000 {
+                32
+            } else {
+                64
+            };
+
+            factor * 128
+        };
+        let fft_out_size = fft_size / 2 + 1;
+
+        let hann_window = apodize::hanning_iter(fft_size)
+            .map(|val| val as f32)
+            .collect::<Vec<f32>>()
+            .into_boxed_slice();
+
+        let fft_in_raw = vec![0.; fft_size].into_boxed_slice();
+
+        let channels = vec![FftContext::new(fft_size, fft_out_size); fetcher.channels() as usize]
+            .into_boxed_slice();
+
+        let high_pass_cutoff = NonZero::new(MIN_HUMAN_FREQUENCY).unwrap();
+        let high_pass =
+            HighPassFilter::new(f32::from(high_pass_cutoff.get()), fetcher.sample_rate().0);
+
+        Self {
+            planner: RealFftPlanner::new(),
+            hann_window,
+            fft_in_raw,
+            high_pass: Some((high_pass_cutoff, high_pass)),
+
+            channels,
+            low_frequency: None,
+
+            fft_size,
+            fetcher,
+
+            processed_batches: 0,
+            amount_new_samples: 0,
+
+            created: Instant::now(),
+            silent_since: None,
+            silence_duration: Duration::ZERO,
+        }
+    }
+
+    /// Tell the processor to take some samples of the fetcher and prepare them
+    /// for the [crate::BarProcessor]s.
+    pub fn process_next_samples(&mut self) {
+        self.amount_new_samples = match &mut self.low_frequency {
+            Some(low_frequency) => {
+                // the newest samples are at the front, so the regular fft gets the same samples as without the longer fft
+                let amount_new_samples = self.fetcher.fetch_samples(&mut low_frequency.samples);
+                self.fft_in_raw
+                    .copy_from_slice(&low_frequency.samples[..self.fft_size]);
+                amount_new_samples
+            }
+            None => self.fetcher.fetch_samples(&mut self.fft_in_raw),
+        };
+        self.update_silence();
+
+        let amount_channels = self.fetcher.channels() as usize;
+        for (sample_idx, samples) in self.fft_in_raw.chunks_exact(amount_channels).enumerate() {
+            for (channel_idx, channel) in self.channels.iter_mut().enumerate() {
+                channel.fft_in[sample_idx] = samples[channel_idx];
+            }
+        }
+
+        for channel in self.channels.iter_mut() {
+            if let Some((_, high_pass)) = &self.high_pass {
+                high_pass.apply(&mut channel.fft_in);
+            }
+
+            for (sample, window) in channel.fft_in.iter_mut().zip(self.hann_window.iter()) {
+                *sample *= window;
+            }
+        }
+
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        for channel in self.channels.iter_mut() {
+            fft.process_with_scratch(
+                channel.fft_in.as_mut(),
+                channel.fft_out.as_mut(),
+                channel.scratch_buffer.as_mut(),
+            )
+            .unwrap();
+        }
+
+        if let Some(low_frequency) = &mut self.low_frequency {
+            let high_pass = self.high_pass.map(|(_, high_pass)| high_pass);
+            low_frequency.process(&mut self.planner, high_pass, &self.channels);
+        }
+
+        self.processed_batches = self.processed_batches.wrapping_add(1);
+    }
+
+    /// Returns how often [SampleProcessor::process_next_samples] has been called.
+    ///
+    /// Useful if multiple consumers share the output of this processor and want to know
+    /// if there are new samples since they've looked the last time.
+    pub fn processed_batches(&self) -> u64 {
+        self.processed_batches
+    }
+
+    /// Returns how long the fetched samples have been silent (see [SILENCE_THRESHOLD]) until the last call of
+    /// [SampleProcessor::process_next_samples]. It's [Duration::ZERO] if the last batch wasn't silent.
+    ///
+    /// The duration is measured with the timestamps of the fetcher if it provides them
+    /// (see [Fetcher::timestamp]), otherwise with the wall clock.
+    pub fn silence_duration(&self) -> Duration {
+        self.silence_duration
+    }
+
+    /// Returns `true` if the fetcher won't provide any new samples, for example because it reached the end of an audio file.
+    ///
+    /// Useful to stop rendering once a file-driven visualization is done.
+    pub fn is_finished(&self) -> bool {
+        self.fetcher.is_finished()
+    }
+
+    /// Returns the position of the newest fetched sample within the audio stream (if the fetcher has one).
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.fetcher.timestamp()
+    }
+
+    /// Returns the estimated time between capturing a sound and its appearance in the fft output.
+    ///
+    /// It's the latency of the fetcher (if it can measure it, see [Fetcher::latency]) plus half of the
+    /// time span of the samples which are analyzed at once since the window of the fft emphasizes its center.
+    pub fn latency(&self) -> Duration {
+        let window_frames = self.fft_size / self.amount_channels().max(1);
+        let window =
+            Duration::from_secs_f64(window_frames as f64 / f64::from(self.sample_rate().0));
+
+        self.fetcher.latency().unwrap_or_default() + window / 2
+    }
+
+    /// Returns the name of the audio source (see [Fetcher::source_name]).
+    pub fn source_name(&self) -> Option<&str> {
+        self.fetcher.source_name()
+    }
+
+    /// Returns the sample rate of the fetcher.
+    pub fn sample_rate(&self) -> SampleRate {
+        self.fetcher.sample_rate()
+    }
+
+    /// Returns the amount of audio channels of the fetcher.
+    pub fn amount_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Sets the cutoff frequency of the high-pass filter which is applied to the samples before the FFT.
+    /// It removes the DC offset and subsonic rumble of some devices which would otherwise inflate the lowest bars.
+    ///
+    /// `None` disables the filter.
+    pub fn set_high_pass_cutoff(&mut self, cutoff: Option<NonZero<u16>>) {
+        self.high_pass = cutoff.map(|cutoff| {
+            let filter = HighPassFilter::new(f32::from(cutoff.get()), self.sample_rate().0);
+            (cutoff, filter)
+        });
+    }
+
+    /// Returns the cutoff frequency of the high-pass filter (if it's enabled).
+    pub fn high_pass_cutoff(&self) -> Option<NonZero<u16>> {
+        self.high_pass.map(|(cutoff, _)| cutoff)
+    }
+
+    /// Enables an additional longer FFT whose bins are used for the frequencies below [LowFrequencyFft::crossover].
+    ///
+    /// The regular FFT is too short to distinguish the low frequencies (for example with a sample rate of 44.1kHz,
+    /// each bin covers ~43Hz). The longer FFT gives the bass its resolution while the higher frequencies
+    /// still come from the regular FFT, so they don't react slower. The [crate::BarProcessor]s pick it up automatically.
+    ///
+    /// `None` disables the longer FFT (default).
+    ///
+    /// # Example
+    /// ```rust
+    /// use shady_audio::{SampleProcessor, LowFrequencyFft, fetcher::DummyFetcher};
+    ///
+    /// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// sample_processor.set_low_frequency_fft(Some(LowFrequencyFft::default()));
+    /// ```
+    pub fn set_low_frequency_fft(&mut self, config: Option<LowFrequencyFft>) {
+        self.low_frequency = config.map(|config| {
+            LowFrequencyAnalysis::new(
+                config,
+                self.fft_size,
+                self.amount_channels(),
+                self.sample_rate(),
+            )
+        });
+    }
+
+    /// Returns the configuration of the longer FFT (if it's enabled).
+    pub fn low_frequency_fft(&self) -> Option<LowFrequencyFft> {
+        self.low_frequency.as_ref().map(|analysis| analysis.config)
+    }
+}
+
+impl SampleProcessor {
+    fn update_silence(&mut self) {
+        let is_silent = self
+            .fft_in_raw
+            .iter()
+            .all(|sample| sample.abs() < SILENCE_THRESHOLD);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_frame(is_silent);
+
+        if !is_silent {
+            self.silent_since = None;
+            self.silence_duration = Duration::ZERO;
+            return;
+        }
+
+        let now = self
+            .fetcher
+            .timestamp()
+            .unwrap_or_else(|| self.created.elapsed());
+
+        let silent_since = *self.silent_since.get_or_insert(now);
+        self.silence_duration = now.saturating_sub(silent_since);
+    }
+
+    pub(crate) fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Returns the interleaved samples of the last batch.
+    pub(crate) fn raw_samples(&self) -> &[f32] {
+        &self.fft_in_raw
+    }
+
+    /// Returns the (interleaved) new samples of the last batch which fit into the buffer of the fetcher
+    /// and the amount of all new samples of the last batch.
+    pub(crate) fn new_samples(&self) -> (&[f32], usize) {
+        let samples = match &self.low_frequency {
+            Some(low_frequency) => &low_frequency.samples,
+            None => &self.fft_in_raw,
+        };
+
+        (
+            &samples[..self.amount_new_samples.min(samples.len())],
+            self.amount_new_samples,
+        )
+    }
+
+    pub(crate) fn fft_out(&self) -> &[FftContext] {
+        &self.channels
+    }
+
+    /// Returns the magnitude of a full scale sine in its bin of the regular fft.
+    pub(crate) fn full_scale_magnitude(&self) -> f32 {
+        // A sine with the amplitude `a` has a magnitude of `a / 2 * window_sum` in its bin.
+        // Each channel gets `fft_size / amount_channels` samples, so only this part of the window is used.
+        self.hann_window[..self.fft_size / self.amount_channels().max(1)]
+            .iter()
+            .sum::<f32>()
+            / 2.
+    }
+
+    /// Returns the sum of the squared window values which are applied to the samples of one channel
+    /// of the regular fft.
+    ///
+    /// Each channel gets `fft_size / amount_channels` samples, so only this part of the window is used.
+    pub(crate) fn window_power(&self) -> f32 {
+        self.hann_window[..self.fft_size / self.amount_channels().max(1)]
+            .iter()
+            .map(|value| value * value)
+            .sum()
+    }
+
+    /// Returns the amount of samples of the fft whose bins are returned by [SampleProcessor::spectra].
+    pub(crate) fn spectrum_size(&self) -> usize {
+        self.low_frequency
+            .as_ref()
+            .map_or(self.fft_size, |analysis| analysis.fft_size)
+    }
+
+    /// Returns the bins of each channel, including the bins of the longer fft if it's enabled.
+    ///
+    /// The magnitudes have the same scale as the ones of the regular fft.
+    pub(crate) fn spectra(&self) -> impl Iterator<Item = &[Complex32]> {
+        (0..self.channels.len()).map(|channel| match &self.low_frequency {
+            Some(analysis) => analysis.spectra[channel].as_ref(),
+            None => self.channels[channel].fft_out.as_ref(),
+        })
+    }
+}
+
+/// The longer fft of [SampleProcessor::set_low_frequency_fft].
+struct LowFrequencyAnalysis {
+    config: LowFrequencyFft,
+    fft_size: usize,
+    hann_window: Box<[f32]>,
+    // the bins below this index are taken from the longer fft
+    crossover_bin: usize,
+    // scales the magnitudes of the longer fft to the ones of the regular fft
+    gain: f32,
+    // how many bins of the longer fft share one bin of the regular fft
+    ratio: usize,
+
+    // the interleaved samples of all channels, the newest ones first
+    samples: Box<[f32]>,
+    channels: Box<[FftContext]>,
+    // the bins of the longer fft merged with the ones of the regular fft
+    spectra: Box<[Box<[Complex32]>]>,
+}
+
+impl LowFrequencyAnalysis {
+    fn new(
+        config: LowFrequencyFft,
+        regular_fft_size: usize,
+        amount_channels: usize,
+        sample_rate: SampleRate,
+    ) -> Self {
+        let fft_size = config.size.max(regular_fft_size).next_power_of_two();
+        let fft_out_size = fft_size / 2 + 1;
+
+        let hann_window = apodize::hanning_iter(fft_size)
+            .map(|val| val as f32)
+            .collect::<Box<[f32]>>();
+
+        let freq_resolution = sample_rate.0 as f32 / fft_size as f32;
+        let crossover_bin = (f32::from(config.crossover.get()) / freq_resolution).ceil() as usize;
+
+        // The regular fft gets `regular_fft_size / amount_channels` new samples of each channel
+        // (see `SampleProcessor::process_next_samples`) and the magnitude of a bin grows with the
+        // sum of the window.
+        let regular_window_sum = apodize::hanning_iter(regular_fft_size)
+            .take(regular_fft_size / amount_channels.max(1))
+            .sum::<f64>() as f32;
+        let gain = regular_window_sum / hann_window.iter().sum::<f32>();
+
+        Self {
+            config,
+            fft_size,
+            hann_window,
+            crossover_bin,
+            gain,
+            // both sizes are powers of two
+            ratio: fft_size / regular_fft_size,
+
+            samples: vec![0.; fft_size * amount_channels].into_boxed_slice(),
+            channels: vec![FftContext::new(fft_size, fft_out_size); amount_channels]
+                .into_boxed_slice(),
+            spectra: vec![vec![Complex32::ZERO; fft_out_size].into_boxed_slice(); amount_channels]
+                .into_boxed_slice(),
+        }
+    }
+
+    fn process(
+        &mut self,
+        planner: &mut RealFftPlanner<f32>,
+        high_pass: Option<HighPassFilter>,
+        regular: &[FftContext],
+    ) {
+        let amount_channels = self.channels.len();
+        for (sample_idx, samples) in self.samples.chunks_exact(amount_channels).enumerate() {
+            for (channel_idx, channel) in self.channels.iter_mut().enumerate() {
+                channel.fft_in[sample_idx] = samples[channel_idx];
+            }
+        }
+
+        let fft = planner.plan_fft_forward(self.fft_size);
+        for channel in self.channels.iter_mut() {
+            if let Some(high_pass) = &high_pass {
+                high_pass.apply(&mut channel.fft_in);
+            }
+
+            for (sample, window) in channel.fft_in.iter_mut().zip(self.hann_window.iter()) {
+                *sample *= window;
+            }
+
+            fft.process_with_scratch(
+                channel.fft_in.as_mut(),
+                channel.fft_out.as_mut(),
+                channel.scratch_buffer.as_mut(),
+            )
+            .unwrap();
+        }
+
+        let ratio = self.ratio;
+        for ((spectrum, channel), regular) in self
+            .spectra
+            .iter_mut()
+            .zip(self.channels.iter())
+            .zip(regular.iter())
+        {
+            for (bin_idx, bin) in spectrum.iter_mut().enumerate() {
+                *bin = if bin_idx < self.crossover_bin {
+                    channel.fft_out[bin_idx] * self.gain
+                } else {
+                    // the nearest bin of the regular fft
+                    let regular_idx = (bin_idx + ratio / 2) / ratio;
+                    regular.fft_out[regular_idx.min(regular.fft_out.len() - 1)]
+                };
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FftContext {
+    fft_in: Box<[f32]>,
+    pub fft_out: Box<[Complex32]>,
+    scratch_buffer: Box<[Complex32]>,
+}
+
+impl FftContext {
+    fn new(fft_size: usize, fft_out_size: usize) -> Self {
+        let fft_in = vec![0.; fft_size].into_boxed_slice();
+        let fft_out = vec![Complex32::ZERO; fft_out_size].into_boxed_slice();
+        let scratch_buffer = fft_out.clone();
+
+        Self {
+            fft_in,
+            fft_out,
+            scratch_buffer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetcher::FnFetcher;
+
+    use super::*;
+
+    fn low_bins_magnitude(processor: &SampleProcessor) -> f32 {
+        processor.fft_out()[0].fft_out[..3]
+            .iter()
+            .map(|bin| bin.norm())
+            .sum()
+    }
+
+    #[test]
+    fn high_pass_removes_dc_offset() {
+        let mut processor = SampleProcessor::new(FnFetcher::new(1, |_, _| 0.5));
+        processor.process_next_samples();
+        assert!(low_bins_magnitude(&processor) < 1e-3);
+
+        processor.set_high_pass_cutoff(None);
+        processor.process_next_samples();
+        assert!(low_bins_magnitude(&processor) > 1.);
+    }
+
+    #[test]
+    fn silence_duration() {
+        // the music starts after three batches
+        let mut processor = SampleProcessor::new(
+            FnFetcher::new(1, |batch, _| if batch < 3 { 0. } else { 0.5 })
+                .with_batch_duration(Duration::from_secs(1)),
+        );
+        assert_eq!(processor.silence_duration(), Duration::ZERO);
+
+        for _ in 0..3 {
+            processor.process_next_samples();
+        }
+        assert_eq!(processor.silence_duration(), Duration::from_secs(2));
+
+        processor.process_next_samples();
+        assert_eq!(processor.silence_duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn low_frequency_fft_separates_bass() {
+        let mut processor = SampleProcessor::new(FnFetcher::new(1, |_, idx| {
+            let time = idx as f32 / crate::DEFAULT_SAMPLE_RATE.0 as f32;
+            [50., 90.]
+                .iter()
+                .map(|freq| (std::f32::consts::TAU * freq * time).sin())
+                .sum()
+        }));
+        processor.set_low_frequency_fft(Some(LowFrequencyFft::default()));
+        processor.process_next_samples();
+
+        assert_eq!(processor.spectrum_size(), 8192);
+        let spectrum = processor.spectra().next().unwrap();
+        assert_eq!(spectrum.len(), 8192 / 2 + 1);
+
+        // each bin covers ~5.4Hz
+        let magnitude = |freq: f32| spectrum[(freq / 5.38).round() as usize].norm();
+        assert!(magnitude(70.) < magnitude(50.) / 2.);
+        assert!(magnitude(70.) < magnitude(90.) / 2.);
+
+        // the magnitudes have the scale of the regular fft
+        let regular_peak = processor.fft_out()[0].fft_out[1..4]
+            .iter()
+            .map(|bin| bin.norm())
+            .fold(0., f32::max);
+        assert!((magnitude(50.) / regular_peak - 1.).abs() < 0.5);
+
+        // the high frequencies come from the regular fft
+        assert_eq!(spectrum[8 * 100], processor.fft_out()[0].fft_out[100]);
+    }
+}
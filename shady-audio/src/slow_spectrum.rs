@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+/// The default time constant of [SlowSpectrum].
+pub const DEFAULT_SLOW_TIME_CONSTANT: Duration = Duration::from_secs(4);
+
+/// Averages the bar values over a long time (an exponential moving average), so they evolve slowly.
+///
+/// Combine it with the instantaneous bars of the [crate::BarProcessor] to have punchy bars and slowly
+/// changing ambient fields (like the colors of the background) at the same time.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use shady_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, SlowSpectrum, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+/// let mut bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+/// let mut slow_spectrum = SlowSpectrum::new(Duration::from_secs(5));
+///
+/// sample_processor.process_next_samples();
+/// let bars = bar_processor.process_bars(&sample_processor);
+/// let slow_bars = slow_spectrum.update(&bars[0], Duration::from_millis(16));
+/// assert_eq!(slow_bars.len(), bars[0].len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlowSpectrum {
+    time_constant: Duration,
+    values: Box<[f32]>,
+}
+
+impl SlowSpectrum {
+    /// Creates a new instance whose values need roughly `time_constant` to reach ~63% of a new constant value.
+    pub fn new(time_constant: Duration) -> Self {
+        Self {
+            time_constant,
+            values: Box::new([]),
+        }
+    }
+
+    /// Moves the averaged values towards `bars` according to the time which has passed since the last update.
+    ///
+    /// The values start at `0` and they are reset if the amount of bars changes.
+    pub fn update(&mut self, bars: &[f32], delta_time: Duration) -> &[f32] {
+        if self.values.len() != bars.len() {
+            self.values = vec![0.; bars.len()].into_boxed_slice();
+        }
+
+        // independent of the frame rate
+        let factor = if self.time_constant.is_zero() {
+            1.
+        } else {
+            1. - (-delta_time.as_secs_f32() / self.time_constant.as_secs_f32()).exp()
+        };
+
+        for (value, bar) in self.values.iter_mut().zip(bars) {
+            *value += factor * (bar - *value);
+        }
+
+        &self.values
+    }
+
+    /// Returns the averaged values of the last update.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn time_constant(&self) -> Duration {
+        self.time_constant
+    }
+
+    /// Changes how slowly the values follow the bars. The current values are kept.
+    pub fn set_time_constant(&mut self, time_constant: Duration) {
+        self.time_constant = time_constant;
+    }
+}
+
+impl Default for SlowSpectrum {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_TIME_CONSTANT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_rate_independent() {
+        let mut fast = SlowSpectrum::new(Duration::from_secs(2));
+        let mut slow = fast.clone();
+
+        for _ in 0..120 {
+            fast.update(&[1.], Duration::from_secs_f32(1. / 60.));
+        }
+        for _ in 0..30 {
+            slow.update(&[1.], Duration::from_secs_f32(1. / 15.));
+        }
+
+        // after one time constant
+        assert!((fast.values()[0] - (1. - (-1f32).exp())).abs() < 1e-3);
+        assert!((fast.values()[0] - slow.values()[0]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn resets_on_new_amount_of_bars() {
+        let mut spectrum = SlowSpectrum::new(Duration::ZERO);
+        assert_eq!(spectrum.update(&[1., 0.5], Duration::ZERO), &[1., 0.5]);
+        assert_eq!(spectrum.update(&[0.], Duration::ZERO), &[0.]);
+    }
+}
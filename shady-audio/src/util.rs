@@ -11,6 +11,16 @@ pub enum DeviceType {
     Output,
 }
 
+/// The errors of the functions which look for devices of a specific host (like [`get_device_from_host`]).
+#[derive(thiserror::Error, Debug)]
+pub enum HostError {
+    #[error("The audio host isn't available: {0}")]
+    Unavailable(#[from] cpal::HostUnavailable),
+
+    #[error(transparent)]
+    Devices(#[from] cpal::DevicesError),
+}
+
 /// Returns the given output/input device with the given name.
 /// You can retrieve a list of available names by using the [`get_device_names`] function.
 ///
@@ -20,38 +30,405 @@ pub fn get_device<S: AsRef<str>>(
     name: S,
     device_type: DeviceType,
 ) -> Result<Option<cpal::Device>, cpal::DevicesError> {
-    let mut devices = get_devices(device_type)?;
+    find_device(&cpal::default_host(), name.as_ref(), device_type)
+}
+
+/// Returns the default device of he given device type (if available).
+pub fn get_default_device(device_type: DeviceType) -> Option<cpal::Device> {
+    default_device(&cpal::default_host(), device_type)
+}
+
+/// Returns a list of device names which you can use for [`get_device`].
+/// Retunrs `Err` if there's a problem retrieving an output/input device.
+pub fn get_device_names(device_type: DeviceType) -> Result<Vec<String>, cpal::DevicesError> {
+    device_names(&cpal::default_host(), device_type)
+}
+
+/// The (lowercase) parts of the names of devices which capture the audio that is played,
+/// like the ALSA loopback card (`hw:CARD=Loopback,DEV=1`), "Stereo Mix" on Windows or BlackHole on macOS.
+const LOOPBACK_NAMES: &[&str] = &[
+    "loopback",
+    "stereo mix",
+    "what u hear",
+    "wave out mix",
+    "blackhole",
+    "soundflower",
+];
+
+/// Returns `true` if the device with the given name captures the audio that is played (see [`get_default_loopback_device`]).
+pub fn is_loopback_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_NAMES.iter().any(|part| name.contains(part))
+}
+
+/// Returns a device which captures what you hear, so you don't need to know the name of the monitor device of your system.
+///
+/// On Windows, that's the default output device since WASAPI can capture the audio of output devices.
+/// On macOS, it's an input device of `BlackHole` or `Soundflower`, preferring the one which is the default output.
+///
+/// On Linux, the ALSA host of [cpal] only lists the ALSA PCM ids (like `pipewire`, `pulse`, `default`
+/// or `hw:CARD=PCH,DEV=0`), so the monitor sources of PulseAudio/PipeWire can't be found. Only the capture side of
+/// the ALSA loopback card (`snd-aloop`, like `plughw:CARD=Loopback,DEV=1`) is detected. To capture a monitor source,
+/// make it the default source (for example with `pactl set-default-source <sink>.monitor`) and use the `pulse`
+/// or `pipewire` device instead.
+///
+/// Returns `None` if the default host doesn't expose such a device.
+///
+/// Input devices work with [crate::fetcher::SystemAudioFetcher] as well.
+///
+/// # Example
+/// ```no_run
+/// use shady_audio::util::{self, DeviceType};
+///
+/// let device = util::get_default_loopback_device()
+///     .or_else(|| util::get_default_device(DeviceType::Output))
+///     .unwrap();
+/// ```
+pub fn get_default_loopback_device() -> Option<cpal::Device> {
+    if cfg!(target_os = "windows") {
+        return get_default_device(DeviceType::Output);
+    }
+
+    let host = cpal::default_host();
+    let default_output_name = host
+        .default_output_device()
+        .and_then(|device| device.name().ok());
+
+    let devices: Vec<(String, cpal::Device)> = get_devices(&host, DeviceType::Input)
+        .ok()?
+        .filter_map(|device| Some((device.name().ok()?, device)))
+        .collect();
+
+    let idx = pick_loopback(
+        devices.iter().map(|(name, _)| name.as_str()),
+        default_output_name.as_deref(),
+    )?;
+    devices.into_iter().nth(idx).map(|(_, device)| device)
+}
+
+/// Returns the index of the loopback device within `names`.
+///
+/// Prefers the device which is the given output device as well (macOS) and the capture side of the
+/// ALSA loopback card (device `1`, which captures what's played on device `0`) with format conversion.
+fn pick_loopback<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    output_name: Option<&str>,
+) -> Option<usize> {
+    let score = |name: &str| {
+        let lowercase = name.to_lowercase();
+
+        let is_output = output_name == Some(name);
+        let is_alsa_capture = lowercase.contains("card=loopback") && lowercase.contains("dev=1");
+        let converts_format = lowercase.starts_with("plughw:");
+
+        (is_output, is_alsa_capture, converts_format)
+    };
+
+    names
+        .into_iter()
+        .enumerate()
+        .filter(|(_, name)| is_loopback_name(name))
+        // the first one of equally good devices
+        .max_by_key(|&(idx, name)| (score(name), std::cmp::Reverse(idx)))
+        .map(|(idx, _)| idx)
+}
+
+/// Returns the audio hosts (like ALSA or JACK on Linux, WASAPI or ASIO on Windows) which are available on this system.
+///
+/// The default host (see [cpal::default_host]) doesn't always expose the device you need, for example
+/// the monitor of a JACK server. Use the hosts with [`get_device_from_host`] instead.
+/// Some hosts are only compiled in with a feature flag (`jack` for JACK, `asio` of [cpal] for ASIO).
+pub fn get_hosts() -> Vec<cpal::HostId> {
+    cpal::available_hosts()
+}
+
+/// Returns the available host with the given name (like `alsa`, `jack` or `wasapi`), ignoring the case.
+///
+/// # Example
+/// ```
+/// use shady_audio::util;
+///
+/// for host_id in util::get_hosts() {
+///     assert_eq!(util::get_host_id(host_id.name()), Some(host_id));
+/// }
+/// ```
+pub fn get_host_id(name: &str) -> Option<cpal::HostId> {
+    get_hosts()
+        .into_iter()
+        .find(|host_id| host_id.name().eq_ignore_ascii_case(name))
+}
+
+/// Like [`get_device`] but looks for the device within the given host.
+///
+/// # Example
+/// ```no_run
+/// use shady_audio::util::{self, DeviceType};
+///
+/// let host_id = util::get_host_id("jack").expect("JACK isn't available");
+/// let device = util::get_device_from_host(host_id, "cpal_client_out", DeviceType::Output).unwrap();
+/// ```
+pub fn get_device_from_host<S: AsRef<str>>(
+    host_id: cpal::HostId,
+    name: S,
+    device_type: DeviceType,
+) -> Result<Option<cpal::Device>, HostError> {
+    let host = cpal::host_from_id(host_id)?;
+
+    Ok(find_device(&host, name.as_ref(), device_type)?)
+}
+
+/// Like [`get_default_device`] but returns the default device of the given host.
+pub fn get_default_device_from_host(
+    host_id: cpal::HostId,
+    device_type: DeviceType,
+) -> Result<Option<cpal::Device>, cpal::HostUnavailable> {
+    let host = cpal::host_from_id(host_id)?;
+
+    Ok(default_device(&host, device_type))
+}
+
+/// Like [`get_device_names`] but returns the names of the devices of the given host.
+pub fn get_device_names_from_host(
+    host_id: cpal::HostId,
+    device_type: DeviceType,
+) -> Result<Vec<String>, HostError> {
+    let host = cpal::host_from_id(host_id)?;
+
+    Ok(device_names(&host, device_type)?)
+}
+
+fn find_device(
+    host: &cpal::Host,
+    name: &str,
+    device_type: DeviceType,
+) -> Result<Option<cpal::Device>, cpal::DevicesError> {
+    let mut devices = get_devices(host, device_type)?;
 
     Ok(devices.find(|d| {
         d.name()
-            .map(|d_name| d_name.as_str() == name.as_ref())
+            .map(|d_name| d_name.as_str() == name)
             .unwrap_or(false)
     }))
 }
 
-/// Returns the default device of he given device type (if available).
-pub fn get_default_device(device_type: DeviceType) -> Option<cpal::Device> {
-    let host = cpal::default_host();
-
+fn default_device(host: &cpal::Host, device_type: DeviceType) -> Option<cpal::Device> {
     match device_type {
         DeviceType::Input => host.default_input_device(),
         DeviceType::Output => host.default_output_device(),
     }
 }
 
-fn get_devices(device_type: DeviceType) -> Result<Devices, cpal::DevicesError> {
-    let host = cpal::default_host();
+fn device_names(
+    host: &cpal::Host,
+    device_type: DeviceType,
+) -> Result<Vec<String>, cpal::DevicesError> {
+    let devices = get_devices(host, device_type)?;
 
+    Ok(devices.filter_map(|d| d.name().ok()).collect())
+}
+
+fn get_devices(host: &cpal::Host, device_type: DeviceType) -> Result<Devices, cpal::DevicesError> {
     match device_type {
         DeviceType::Input => host.input_devices(),
         DeviceType::Output => host.output_devices(),
     }
 }
 
-/// Returns a list of device names which you can use for [`get_device`].
-/// Retunrs `Err` if there's a problem retrieving an output/input device.
-pub fn get_device_names(device_type: DeviceType) -> Result<Vec<String>, cpal::DevicesError> {
-    let devices = get_devices(device_type)?;
+/// A configuration range which is supported by a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupportedConfig {
+    /// The amount of channels.
+    pub channels: u16,
+    /// The minimal supported sample rate.
+    pub min_sample_rate: cpal::SampleRate,
+    /// The maximal supported sample rate.
+    pub max_sample_rate: cpal::SampleRate,
+    /// The format of the samples.
+    pub sample_format: cpal::SampleFormat,
+}
 
-    Ok(devices.filter_map(|d| d.name().ok()).collect())
+impl SupportedConfig {
+    /// Returns `true` if the given sample rate is within the supported sample rate range.
+    pub fn supports_sample_rate(&self, sample_rate: cpal::SampleRate) -> bool {
+        self.min_sample_rate <= sample_rate && sample_rate <= self.max_sample_rate
+    }
+}
+
+impl From<cpal::SupportedStreamConfigRange> for SupportedConfig {
+    fn from(range: cpal::SupportedStreamConfigRange) -> Self {
+        Self {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate(),
+            max_sample_rate: range.max_sample_rate(),
+            sample_format: range.sample_format(),
+        }
+    }
+}
+
+/// Describes what a device is able to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    /// The name of the device (if available).
+    pub name: Option<String>,
+    /// All configurations which the device supports.
+    pub configs: Vec<SupportedConfig>,
+}
+
+impl DeviceCapabilities {
+    /// Returns all supported amount of channels (sorted and without duplicates).
+    pub fn channels(&self) -> Vec<u16> {
+        let mut channels: Vec<u16> = self.configs.iter().map(|c| c.channels).collect();
+        channels.sort_unstable();
+        channels.dedup();
+        channels
+    }
+
+    /// Returns all supported sample formats (without duplicates).
+    pub fn sample_formats(&self) -> Vec<cpal::SampleFormat> {
+        let mut formats = Vec::new();
+        for config in self.configs.iter() {
+            if !formats.contains(&config.sample_format) {
+                formats.push(config.sample_format);
+            }
+        }
+        formats
+    }
+
+    /// Returns `true` if any config supports the given sample rate.
+    pub fn supports_sample_rate(&self, sample_rate: cpal::SampleRate) -> bool {
+        self.configs
+            .iter()
+            .any(|c| c.supports_sample_rate(sample_rate))
+    }
+}
+
+/// Returns the configurations which are supported by the given device.
+///
+/// Use this to figure out why a fetcher couldn't be created with the settings you've chosen.
+///
+/// # Example
+/// ```no_run
+/// use shady_audio::util::{self, DeviceType};
+///
+/// let device = util::get_default_device(DeviceType::Output).unwrap();
+/// let capabilities = util::get_device_capabilities(&device, DeviceType::Output).unwrap();
+///
+/// println!("Supported channels: {:?}", capabilities.channels());
+/// ```
+pub fn get_device_capabilities(
+    device: &cpal::Device,
+    device_type: DeviceType,
+) -> Result<DeviceCapabilities, cpal::SupportedStreamConfigsError> {
+    let configs = match device_type {
+        DeviceType::Input => device
+            .supported_input_configs()?
+            .map(SupportedConfig::from)
+            .collect(),
+        DeviceType::Output => device
+            .supported_output_configs()?
+            .map(SupportedConfig::from)
+            .collect(),
+    };
+
+    Ok(DeviceCapabilities {
+        name: device.name().ok(),
+        configs,
+    })
+}
+
+/// Returns the capabilities of each device of the given device type.
+/// Devices whose configurations couldn't be retrieved are skipped.
+pub fn get_all_device_capabilities(
+    device_type: DeviceType,
+) -> Result<Vec<DeviceCapabilities>, cpal::DevicesError> {
+    let devices = get_devices(&cpal::default_host(), device_type)?;
+
+    Ok(devices
+        .filter_map(|device| get_device_capabilities(&device, device_type).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(channels: u16, sample_format: cpal::SampleFormat) -> SupportedConfig {
+        SupportedConfig {
+            channels,
+            min_sample_rate: cpal::SampleRate(8_000),
+            max_sample_rate: cpal::SampleRate(48_000),
+            sample_format,
+        }
+    }
+
+    #[test]
+    fn loopback_names() {
+        assert!(is_loopback_name("hw:CARD=Loopback,DEV=1"));
+        assert!(is_loopback_name("Stereo Mix (Realtek(R) Audio)"));
+        assert!(is_loopback_name("BlackHole 2ch"));
+        assert!(!is_loopback_name("pipewire"));
+        assert!(!is_loopback_name("sysdefault:CARD=PCH"));
+    }
+
+    #[test]
+    fn pick_the_alsa_loopback_capture_device() {
+        // the input devices of the ALSA host of cpal with the `snd-aloop` module loaded
+        let names = [
+            "pipewire",
+            "pulse",
+            "default",
+            "sysdefault:CARD=PCH",
+            "front:CARD=PCH,DEV=0",
+            "hw:CARD=PCH,DEV=0",
+            "plughw:CARD=PCH,DEV=0",
+            "sysdefault:CARD=Loopback",
+            "hw:CARD=Loopback,DEV=0",
+            "plughw:CARD=Loopback,DEV=0",
+            "hw:CARD=Loopback,DEV=1",
+            "plughw:CARD=Loopback,DEV=1",
+        ];
+
+        assert_eq!(pick_loopback(names, Some("default")), Some(11));
+        // without the loopback card
+        assert_eq!(
+            pick_loopback(names[..7].iter().copied(), Some("default")),
+            None
+        );
+    }
+
+    #[test]
+    fn prefer_the_loopback_of_the_output() {
+        // the input devices of CoreAudio
+        let names = ["MacBook Pro Microphone", "BlackHole 16ch", "BlackHole 2ch"];
+
+        assert_eq!(pick_loopback(names, Some("BlackHole 2ch")), Some(2));
+        assert_eq!(pick_loopback(names, Some("MacBook Pro Speakers")), Some(1));
+        assert_eq!(pick_loopback(names, None), Some(1));
+        assert_eq!(pick_loopback(names[..1].iter().copied(), None), None);
+    }
+
+    #[test]
+    fn unknown_host() {
+        assert_eq!(get_host_id("no such host"), None);
+    }
+
+    #[test]
+    fn capabilities_summary() {
+        let capabilities = DeviceCapabilities {
+            name: None,
+            configs: vec![
+                config(2, cpal::SampleFormat::F32),
+                config(1, cpal::SampleFormat::I16),
+                config(2, cpal::SampleFormat::I16),
+            ],
+        };
+
+        assert_eq!(capabilities.channels(), vec![1, 2]);
+        assert_eq!(
+            capabilities.sample_formats(),
+            vec![cpal::SampleFormat::F32, cpal::SampleFormat::I16]
+        );
+        assert!(capabilities.supports_sample_rate(cpal::SampleRate(44_100)));
+        assert!(!capabilities.supports_sample_rate(cpal::SampleRate(96_000)));
+    }
 }
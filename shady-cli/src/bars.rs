@@ -0,0 +1,266 @@
+use std::ops::RangeInclusive;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Direction, Rect},
+    style::{Modifier, Style},
+    widgets::{Bar, BarChart, BarGroup, Widget},
+};
+
+const FULL_BLOCK: &str = "█";
+const UPPER_HALF_BLOCK: &str = "▀";
+const LOWER_HALF_BLOCK: &str = "▄";
+const LOWER_ONE_EIGHTH_BLOCK: &str = "▁";
+const UPPER_ONE_EIGHTH_BLOCK: &str = "▔";
+const LEFT_ONE_EIGHTH_BLOCK: &str = "▏";
+
+/// Decides in which direction the bars grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Orientation {
+    /// The bars grow from the bottom to the top.
+    #[default]
+    BottomUp,
+
+    /// The bars grow from the top to the bottom.
+    TopDown,
+
+    /// The bars grow from the left to the right.
+    Horizontal,
+
+    /// The bars grow from the middle to the top and bottom (like a VU meter).
+    Centered,
+}
+
+impl Orientation {
+    pub fn next(self) -> Self {
+        match self {
+            Self::BottomUp => Self::TopDown,
+            Self::TopDown => Self::Horizontal,
+            Self::Horizontal => Self::Centered,
+            Self::Centered => Self::BottomUp,
+        }
+    }
+
+    /// Returns the cell along which the bars are placed next to each other.
+    pub fn cell(self, column: u16, row: u16) -> u16 {
+        match self {
+            Self::Horizontal => row,
+            _ => column,
+        }
+    }
+
+    /// Returns the amount of cells where the bars are placed next to each other.
+    pub fn bar_space(self, area: Rect) -> u16 {
+        match self {
+            Self::Horizontal => area.height,
+            _ => area.width,
+        }
+    }
+}
+
+/// Draws the bars with the given orientation.
+pub struct Bars<'a> {
+    pub values: &'a [u64],
+    pub max: u64,
+    pub bar_width: u16,
+    pub bar_gap: u16,
+    pub style: Style,
+    pub selected: Option<RangeInclusive<usize>>,
+    pub orientation: Orientation,
+    /// Draws a cap above each bar at the height of its peak.
+    pub peaks: Option<&'a [u64]>,
+}
+
+impl Bars<'_> {
+    fn bar_style(&self, bar_idx: usize) -> Style {
+        match &self.selected {
+            Some(selected) if selected.contains(&bar_idx) => {
+                self.style.add_modifier(Modifier::REVERSED)
+            }
+            _ => self.style,
+        }
+    }
+
+    /// Returns the height of the bar in half cells if it has `cells` cells available.
+    fn half_cells(&self, value: u64, cells: u16) -> u16 {
+        let ratio = value.min(self.max) as f32 / self.max as f32;
+        (ratio * f32::from(cells) * 2.).round() as u16
+    }
+
+    fn render_chart(&self, area: Rect, buf: &mut Buffer, direction: Direction) {
+        let bars = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(bar_idx, &value)| {
+                Bar::default()
+                    .value(value)
+                    .text_value(String::new())
+                    .style(self.bar_style(bar_idx))
+            })
+            .collect::<Vec<_>>();
+
+        BarChart::default()
+            .direction(direction)
+            .bar_width(self.bar_width)
+            .bar_gap(self.bar_gap)
+            .bar_style(self.style)
+            .data(BarGroup::default().label("".into()).bars(&bars))
+            .max(self.max)
+            .render(area, buf);
+    }
+
+    /// Returns the first cell above the bar if it has `cells` cells available.
+    fn cells_above(&self, value: u64, cells: u16) -> u16 {
+        let ratio = value.min(self.max) as f32 / self.max as f32;
+        (ratio * f32::from(cells)).ceil() as u16
+    }
+
+    /// Renders each bar column by column. `draw_bar` gets the x coordinate and the value of the bar.
+    fn render_columns(
+        &self,
+        values: &[u64],
+        area: Rect,
+        mut draw_bar: impl FnMut(u16, u64, Style),
+    ) {
+        for (bar_idx, &value) in values.iter().enumerate() {
+            let x = area.x + bar_idx as u16 * (self.bar_width + self.bar_gap);
+            if x + self.bar_width > area.right() {
+                break;
+            }
+
+            for column in x..x + self.bar_width {
+                draw_bar(column, value, self.bar_style(bar_idx));
+            }
+        }
+    }
+
+    fn render_top_down(&self, area: Rect, buf: &mut Buffer) {
+        self.render_columns(self.values, area, |x, value, style| {
+            let half_cells = self.half_cells(value, area.height);
+
+            for y in area.y..area.y + half_cells / 2 {
+                buf[(x, y)].set_symbol(FULL_BLOCK).set_style(style);
+            }
+
+            if half_cells % 2 == 1 {
+                buf[(x, area.y + half_cells / 2)]
+                    .set_symbol(UPPER_HALF_BLOCK)
+                    .set_style(style);
+            }
+        });
+    }
+
+    fn render_centered(&self, area: Rect, buf: &mut Buffer) {
+        let upper_cells = area.height / 2;
+        let lower_cells = area.height - upper_cells;
+        let center = area.y + upper_cells;
+
+        self.render_columns(self.values, area, |x, value, style| {
+            // the upper half grows upwards ...
+            let half_cells = self.half_cells(value, upper_cells);
+            for y in center - half_cells / 2..center {
+                buf[(x, y)].set_symbol(FULL_BLOCK).set_style(style);
+            }
+            if half_cells % 2 == 1 {
+                buf[(x, center - half_cells / 2 - 1)]
+                    .set_symbol(LOWER_HALF_BLOCK)
+                    .set_style(style);
+            }
+
+            // ... and the lower half downwards
+            let half_cells = self.half_cells(value, lower_cells);
+            for y in center..center + half_cells / 2 {
+                buf[(x, y)].set_symbol(FULL_BLOCK).set_style(style);
+            }
+            if half_cells % 2 == 1 {
+                buf[(x, center + half_cells / 2)]
+                    .set_symbol(UPPER_HALF_BLOCK)
+                    .set_style(style);
+            }
+        });
+    }
+
+    /// Draws a thin cap right above the peak of each bar.
+    fn render_peaks(&self, peaks: &[u64], area: Rect, buf: &mut Buffer) {
+        match self.orientation {
+            Orientation::BottomUp => {
+                // the bar chart keeps the last row for the (empty) labels
+                let height = area.height.saturating_sub(1);
+                self.render_columns(peaks, area, |x, peak, style| {
+                    let cells = self.cells_above(peak, height);
+                    if cells < height {
+                        buf[(x, area.y + height - cells - 1)]
+                            .set_symbol(LOWER_ONE_EIGHTH_BLOCK)
+                            .set_style(style);
+                    }
+                });
+            }
+            Orientation::TopDown => self.render_columns(peaks, area, |x, peak, style| {
+                let cells = self.cells_above(peak, area.height);
+                if cells < area.height {
+                    buf[(x, area.y + cells)]
+                        .set_symbol(UPPER_ONE_EIGHTH_BLOCK)
+                        .set_style(style);
+                }
+            }),
+            Orientation::Centered => {
+                let upper_cells = area.height / 2;
+                let lower_cells = area.height - upper_cells;
+                let center = area.y + upper_cells;
+
+                self.render_columns(peaks, area, |x, peak, style| {
+                    let cells = self.cells_above(peak, upper_cells);
+                    if cells < upper_cells {
+                        buf[(x, center - cells - 1)]
+                            .set_symbol(LOWER_ONE_EIGHTH_BLOCK)
+                            .set_style(style);
+                    }
+
+                    let cells = self.cells_above(peak, lower_cells);
+                    if cells < lower_cells {
+                        buf[(x, center + cells)]
+                            .set_symbol(UPPER_ONE_EIGHTH_BLOCK)
+                            .set_style(style);
+                    }
+                });
+            }
+            Orientation::Horizontal => {
+                for (bar_idx, &peak) in peaks.iter().enumerate() {
+                    let y = area.y + bar_idx as u16 * (self.bar_width + self.bar_gap);
+                    if y + self.bar_width > area.bottom() {
+                        break;
+                    }
+
+                    let cells = self.cells_above(peak, area.width);
+                    if cells < area.width {
+                        for row in y..y + self.bar_width {
+                            buf[(area.x + cells, row)]
+                                .set_symbol(LEFT_ONE_EIGHTH_BLOCK)
+                                .set_style(self.bar_style(bar_idx));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Widget for Bars<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.max == 0 {
+            return;
+        }
+
+        match self.orientation {
+            Orientation::BottomUp => self.render_chart(area, buf, Direction::Vertical),
+            Orientation::Horizontal => self.render_chart(area, buf, Direction::Horizontal),
+            Orientation::TopDown => self.render_top_down(area, buf),
+            Orientation::Centered => self.render_centered(area, buf),
+        }
+
+        if let Some(peaks) = self.peaks {
+            self.render_peaks(peaks, area, buf);
+        }
+    }
+}
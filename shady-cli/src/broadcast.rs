@@ -0,0 +1,221 @@
+//! Sends the bar values to other programs over the network (see `--broadcast`).
+//!
+//! - WebSocket (`ws://<address>:<port>`): `shady-cli` listens on the address and sends each frame as a json array
+//!   to all connected clients: one array per pane, which contains one array of bar values per channel.
+//! - OSC (`osc://<host>:<port>`): `shady-cli` sends one message per pane and channel via UDP to the given host
+//!   with the address `/shady/<pane>/<channel>` and the bar values as floats.
+//!
+//! The bar values are clamped to the range `[0, 1]`.
+use std::{
+    fmt::Write as _,
+    io,
+    net::{TcpListener, TcpStream, UdpSocket},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tracing::{debug, info};
+use tungstenite::{Message, WebSocket};
+
+// a slow client shouldn't block the visualizer
+const WRITE_TIMEOUT: Duration = Duration::from_millis(20);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where the bar values should be sent to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastTarget {
+    /// The address where the WebSocket server should listen on.
+    WebSocket(String),
+
+    /// The address of the OSC receiver.
+    Osc(String),
+}
+
+impl FromStr for BroadcastTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = s.strip_prefix("ws://") {
+            Ok(Self::WebSocket(address.trim_end_matches('/').to_string()))
+        } else if let Some(address) = s.strip_prefix("osc://") {
+            Ok(Self::Osc(address.trim_end_matches('/').to_string()))
+        } else {
+            Err(format!(
+                "Unknown broadcast target \"{}\", expected `ws://<address>:<port>` or `osc://<host>:<port>`.",
+                s
+            ))
+        }
+    }
+}
+
+pub enum Broadcaster {
+    WebSocket(WebSocketServer),
+    Osc(UdpSocket),
+}
+
+impl Broadcaster {
+    pub fn new(target: &BroadcastTarget) -> io::Result<Self> {
+        match target {
+            BroadcastTarget::WebSocket(address) => {
+                WebSocketServer::listen(address).map(Self::WebSocket)
+            }
+            BroadcastTarget::Osc(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(address)?;
+                Ok(Self::Osc(socket))
+            }
+        }
+    }
+
+    /// Sends the bars of each pane (a list of the bars of each channel).
+    pub fn send<'a>(&self, panes: impl IntoIterator<Item = Vec<&'a [f32]>>) {
+        match self {
+            Self::WebSocket(server) => server.send(&json_message(panes)),
+            Self::Osc(socket) => {
+                for (pane_idx, channels) in panes.into_iter().enumerate() {
+                    for (channel_idx, bars) in channels.into_iter().enumerate() {
+                        let address = format!("/shady/{}/{}", pane_idx, channel_idx);
+
+                        // the receiver might not be running (yet)
+                        if let Err(err) = socket.send(&osc_message(&address, bars)) {
+                            debug!("Couldn't send the OSC message: {}", err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct WebSocketServer {
+    clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+}
+
+impl WebSocketServer {
+    /// Accepts the clients in the background.
+    fn listen(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        info!("Broadcasting the bars on ws://{}", listener.local_addr()?);
+
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted_clients = clients.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                let _ = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                match tungstenite::accept(stream) {
+                    Ok(client) => accepted_clients.lock().unwrap().push(client),
+                    Err(err) => debug!("WebSocket handshake failed: {}", err),
+                }
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    /// Sends the message to all clients and drops the ones which have disconnected.
+    fn send(&self, message: &str) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain_mut(|client| client.send(Message::text(message)).is_ok());
+    }
+}
+
+fn json_message<'a>(panes: impl IntoIterator<Item = Vec<&'a [f32]>>) -> String {
+    let mut message = String::from("[");
+
+    for (pane_idx, channels) in panes.into_iter().enumerate() {
+        if pane_idx > 0 {
+            message.push(',');
+        }
+
+        message.push('[');
+        for (channel_idx, bars) in channels.into_iter().enumerate() {
+            if channel_idx > 0 {
+                message.push(',');
+            }
+
+            message.push('[');
+            for (bar_idx, bar) in bars.iter().enumerate() {
+                if bar_idx > 0 {
+                    message.push(',');
+                }
+                write!(message, "{:.4}", clamp_bar(*bar)).unwrap();
+            }
+            message.push(']');
+        }
+        message.push(']');
+    }
+
+    message.push(']');
+    message
+}
+
+/// Encodes an OSC message with the given float arguments.
+fn osc_message(address: &str, values: &[f32]) -> Vec<u8> {
+    let type_tags = format!(",{}", "f".repeat(values.len()));
+
+    let mut message = Vec::with_capacity(address.len() + type_tags.len() + 8 + 4 * values.len());
+    write_osc_string(&mut message, address);
+    write_osc_string(&mut message, &type_tags);
+    for value in values {
+        message.extend_from_slice(&clamp_bar(*value).to_be_bytes());
+    }
+
+    message
+}
+
+/// Clamps the bar value to `[0, 1]`. `NaN` becomes `0` (json doesn't know `NaN`).
+fn clamp_bar(bar: f32) -> f32 {
+    if bar.is_nan() {
+        0.
+    } else {
+        bar.clamp(0., 1.)
+    }
+}
+
+/// OSC strings are null terminated and padded to a multiple of 4 bytes.
+fn write_osc_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(s.as_bytes());
+
+    let padding = 4 - s.len() % 4;
+    buf.extend(std::iter::repeat_n(0, padding));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_messages() {
+        let left = [0., 0.5];
+        let right = [1., 0.25];
+        let out_of_range = [f32::NAN, -1., 2., f32::INFINITY];
+
+        assert_eq!(json_message(Vec::<Vec<&[f32]>>::new()), "[]");
+        assert_eq!(
+            json_message([vec![&left[..], &right[..]], vec![&out_of_range[..]]]),
+            "[[[0.0000,0.5000],[1.0000,0.2500]],[[0.0000,0.0000,1.0000,1.0000]]]"
+        );
+    }
+
+    #[test]
+    fn osc_messages() {
+        let message = osc_message("/shady/0/1", &[0.5, 2.]);
+
+        let mut expected = b"/shady/0/1\0\0,ff\0".to_vec();
+        expected.extend_from_slice(&0.5f32.to_be_bytes());
+        expected.extend_from_slice(&1f32.to_be_bytes());
+        assert_eq!(message, expected);
+
+        // the strings are padded to a multiple of 4 bytes (at least one null byte)
+        let message = osc_message("/abc", &[]);
+        assert_eq!(message, b"/abc\0\0\0\0,\0\0\0");
+    }
+}
@@ -0,0 +1,75 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, List, ListState},
+    Frame,
+};
+use shady_audio::util::DeviceType;
+
+const MAX_WIDTH: u16 = 60;
+
+/// A popup to choose the output device of a new pane.
+pub struct DeviceChooser {
+    names: Vec<String>,
+    state: ListState,
+
+    /// Shown if the chosen device couldn't be opened.
+    pub error: Option<String>,
+}
+
+impl DeviceChooser {
+    pub fn new() -> Self {
+        let names = shady_audio::util::get_device_names(DeviceType::Output).unwrap_or_default();
+
+        Self {
+            names,
+            state: ListState::default().with_selected(Some(0)),
+            error: None,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.state.select_previous();
+    }
+
+    /// Returns the name of the selected device.
+    pub fn selected(&self) -> Option<&str> {
+        let idx = self.state.selected()?.min(self.names.len().checked_sub(1)?);
+        Some(&self.names[idx])
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let area = popup_area(frame.area(), self.names.len() as u16 + 2);
+
+        let mut block = Block::bordered().title(" Add a pane (enter: choose, esc: cancel) ");
+        if let Some(error) = &self.error {
+            block =
+                block.title_bottom(Line::raw(error.as_str()).style(Style::new().fg(Color::Red)));
+        }
+
+        let list = List::new(self.names.iter().map(String::as_str))
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
+
+/// Returns a centered area with the given height.
+fn popup_area(area: Rect, height: u16) -> Rect {
+    let width = area.width.min(MAX_WIDTH);
+    let height = area.height.min(height);
+
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
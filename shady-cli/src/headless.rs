@@ -0,0 +1,226 @@
+//! Runs the audio pipeline without the TUI and serves the energy of some frequency bands and the loudness
+//! as Prometheus metrics over HTTP (see `--headless`).
+//!
+//! The metrics (all gauges):
+//! - `shady_band_energy{band, channel}`: The average bar value of the band within `[0, 1]`, relative to the loudness.
+//! - `shady_loudness_lufs`: The loudness of the last three seconds. Missing while it's silent.
+//! - `shady_silence_seconds`: How long the audio has been silent.
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write as _},
+    net::{TcpListener, TcpStream},
+    num::NonZero,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use shady_audio::{
+    fetcher::Fetcher, BarProcessor, BarProcessorConfig, FrequencyBand, Normalization,
+    SampleProcessor,
+};
+use tracing::{debug, info};
+
+use crate::broadcast::Broadcaster;
+
+pub const DEFAULT_METRICS_ADDRESS: &str = "0.0.0.0:9184";
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis(1000 / 60);
+// each scraper gets its own thread, so a stalled one only blocks its thread until the timeout
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+const BARS_PER_BAND: u16 = 8;
+// the name and the frequency range (in Hz) of each band
+const BANDS: [(&str, u16, u16); 4] = [
+    ("bass", 20, 250),
+    ("low_mid", 250, 2_000),
+    ("high_mid", 2_000, 6_000),
+    ("treble", 6_000, 20_000),
+];
+
+/// Processes the audio of the fetcher until it's finished and serves the metrics on the given address.
+pub fn run(
+    fetcher: Box<dyn Fetcher>,
+    address: &str,
+    broadcasters: &[Broadcaster],
+) -> io::Result<()> {
+    let metrics = Arc::new(Mutex::new(String::new()));
+    serve(address, metrics.clone())?;
+
+    let mut sample_processor = SampleProcessor::new(fetcher);
+    let mut bar_processor = BarProcessor::new(&sample_processor, config());
+
+    let amount_channel_bars = usize::from(bar_processor.config().amount_bars.get());
+    let mut bars = vec![0.; bar_processor.amount_channels() * amount_channel_bars];
+
+    while !sample_processor.is_finished() {
+        sample_processor.process_next_samples();
+        bar_processor.process_bars_into(&sample_processor, &mut bars);
+
+        let channel_bars = bars.chunks(amount_channel_bars);
+        for broadcaster in broadcasters {
+            broadcaster.send([channel_bars.clone().collect()]);
+        }
+
+        let text = render(
+            channel_bars,
+            bar_processor.loudness(),
+            sample_processor.silence_duration(),
+        );
+        *metrics.lock().unwrap() = text;
+
+        std::thread::sleep(UPDATE_INTERVAL);
+    }
+
+    Ok(())
+}
+
+fn config() -> BarProcessorConfig {
+    BarProcessorConfig {
+        normalization: Normalization::LOUDNESS,
+        bands: BANDS
+            .iter()
+            .map(|&(_, start, end)| FrequencyBand {
+                freq_range: NonZero::new(start).unwrap()..NonZero::new(end).unwrap(),
+                amount_bars: NonZero::new(BARS_PER_BAND).unwrap(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Renders the metrics in the text format of Prometheus.
+fn render<'a>(
+    bars: impl IntoIterator<Item = &'a [f32]>,
+    loudness: Option<f32>,
+    silence: Duration,
+) -> String {
+    let mut text = String::new();
+
+    text.push_str("# HELP shady_band_energy The average bar value of the frequency band, relative to the loudness.\n");
+    text.push_str("# TYPE shady_band_energy gauge\n");
+    for (channel_idx, channel_bars) in bars.into_iter().enumerate() {
+        for ((name, _, _), band_bars) in BANDS
+            .iter()
+            .zip(channel_bars.chunks(usize::from(BARS_PER_BAND)))
+        {
+            let energy = band_bars.iter().sum::<f32>() / band_bars.len() as f32;
+            writeln!(
+                text,
+                "shady_band_energy{{band=\"{}\",channel=\"{}\"}} {}",
+                name,
+                channel_idx,
+                if energy.is_finite() { energy } else { 0. }
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(loudness) = loudness {
+        text.push_str("# HELP shady_loudness_lufs The loudness of the last three seconds.\n");
+        text.push_str("# TYPE shady_loudness_lufs gauge\n");
+        writeln!(text, "shady_loudness_lufs {}", loudness).unwrap();
+    }
+
+    text.push_str("# HELP shady_silence_seconds How long the audio has been silent.\n");
+    text.push_str("# TYPE shady_silence_seconds gauge\n");
+    writeln!(text, "shady_silence_seconds {}", silence.as_secs_f32()).unwrap();
+
+    text
+}
+
+/// Answers the requests of the scrapers in the background.
+fn serve(address: &str, metrics: Arc<Mutex<String>>) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    info!(
+        "Serving the metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+
+            let metrics = metrics.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = respond(stream, &metrics) {
+                    debug!("Couldn't answer the request: {}", err);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, metrics: &Mutex<String>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    // only the request line is relevant: `GET <path> HTTP/1.1`
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = match path {
+        "/" | "/metrics" => ("200 OK", metrics.lock().unwrap().clone()),
+        _ => ("404 Not Found", String::from("Not found\n")),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_metrics() {
+        let bars_per_channel = BANDS.len() * usize::from(BARS_PER_BAND);
+        let mut left = vec![0.; bars_per_channel];
+        left[..usize::from(BARS_PER_BAND)].fill(1.);
+        left[bars_per_channel - 1] = f32::NAN;
+        let right = vec![0.5; bars_per_channel];
+
+        let text = render(
+            [&left[..], &right[..]],
+            Some(-14.),
+            Duration::from_millis(1500),
+        );
+
+        let expected = "\
+# HELP shady_band_energy The average bar value of the frequency band, relative to the loudness.
+# TYPE shady_band_energy gauge
+shady_band_energy{band=\"bass\",channel=\"0\"} 1
+shady_band_energy{band=\"low_mid\",channel=\"0\"} 0
+shady_band_energy{band=\"high_mid\",channel=\"0\"} 0
+shady_band_energy{band=\"treble\",channel=\"0\"} 0
+shady_band_energy{band=\"bass\",channel=\"1\"} 0.5
+shady_band_energy{band=\"low_mid\",channel=\"1\"} 0.5
+shady_band_energy{band=\"high_mid\",channel=\"1\"} 0.5
+shady_band_energy{band=\"treble\",channel=\"1\"} 0.5
+# HELP shady_loudness_lufs The loudness of the last three seconds.
+# TYPE shady_loudness_lufs gauge
+shady_loudness_lufs -14
+# HELP shady_silence_seconds How long the audio has been silent.
+# TYPE shady_silence_seconds gauge
+shady_silence_seconds 1.5
+";
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn render_metrics_while_silent() {
+        let text = render(Vec::<&[f32]>::new(), None, Duration::ZERO);
+
+        assert!(!text.contains("shady_loudness_lufs"));
+        assert!(!text.contains("shady_band_energy{"));
+        assert!(text.ends_with("shady_silence_seconds 0\n"));
+    }
+}
@@ -1,20 +1,61 @@
+mod bars;
+mod broadcast;
+mod device_chooser;
+mod headless;
+mod pane;
+
+use bars::{Bars, Orientation};
+use broadcast::{BroadcastTarget, Broadcaster};
 use clap::Parser;
-use std::{fs::File, num::NonZero, time::Duration};
+use device_chooser::DeviceChooser;
+use pane::Pane;
+use std::{
+    fs::File,
+    num::NonZero,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, MouseButton,
+    MouseEvent, MouseEventKind,
+};
 use ratatui::{
-    style::{Color, Style},
-    widgets::{Bar, BarChart, BarGroup},
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
     Frame,
 };
 use shady_audio::{
-    fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
+    cpal::{self, traits::DeviceTrait},
+    fetcher::{
+        Fetcher, FileFetcher, FileFetcherDescriptor, SystemAudioError, SystemAudioFetcher,
+        SystemAudioFetcherDescriptor,
+    },
     util::DeviceType,
-    BarProcessor, BarProcessorConfig, InterpolationVariant, SampleProcessor,
+    BarDistribution, BarProcessor, BarProcessorConfig, InterpolationVariant, Preset,
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 const HEIGHT: u64 = 1000;
+const BAR_GAP: u16 = 1;
+// how much a new frame time influences the displayed FPS
+const FPS_SMOOTHING: f32 = 0.1;
+// how long the audio has to be silent until the bars are redrawn with `--idle-fps`
+const IDLE_AFTER: Duration = Duration::from_secs(5);
+// how long a changed value is shown instead of the status line
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+const SENSITIVITY_STEP: f32 = 0.05;
+const SENSITIVITY_RANGE: RangeInclusive<f32> = 0.05..=1.;
+// the frequency ranges (in Hz) which can be cycled through with `[` and `]`
+const FREQ_RANGES: [(u16, u16); 5] = [
+    (20, 250),
+    (20, 1_000),
+    (80, 4_000),
+    (50, 10_000),
+    (20, 20_000),
+];
 
 #[derive(clap::Parser, Debug)]
 #[command(version, about)]
@@ -31,187 +72,730 @@ struct Cli {
     /// Choose the output device `shady-cli` should use. You can get a list of devices by invoking `shady-cli` with the `--show-output-devices` argument.
     #[arg(long)]
     pub output_device: Option<String>,
+
+    /// The direction in which the bars grow.
+    #[arg(long, value_enum, default_value_t = Orientation::BottomUp)]
+    pub orientation: Orientation,
+
+    /// Visualize (and play) the given wav file instead of the system audio. Other formats aren't supported.
+    /// The file is played on the output device. It's only visualized if the device can't play
+    /// its sample rate or amount of channels.
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+
+    /// Only visualize the file of `--file` without playing it.
+    #[arg(long, requires = "file")]
+    pub no_playback: bool,
+
+    /// Send the bar values to other programs: `ws://<address>:<port>` serves them over WebSocket
+    /// and `osc://<host>:<port>` sends them as OSC messages. Can be given multiple times.
+    #[arg(long, value_name = "URL")]
+    pub broadcast: Vec<BroadcastTarget>,
+
+    /// How often the bars are redrawn per second.
+    #[arg(long, default_value_t = NonZero::new(60).unwrap())]
+    pub fps: NonZero<u32>,
+
+    /// How often the bars are redrawn per second after the audio of all panes has been silent for a few seconds,
+    /// to save power. Set it to the value of `--fps` to disable it.
+    #[arg(long, default_value_t = NonZero::new(5).unwrap())]
+    pub idle_fps: NonZero<u32>,
+
+    /// Draw a cap at the peak of each bar which falls down slowly (toggle it with `c`).
+    #[arg(long)]
+    pub peaks: bool,
+
+    /// Run without the TUI and serve the energy of some frequency bands and the loudness as Prometheus metrics
+    /// on `--metrics-address`. Useful as a (systemd) service. The logs are written to stderr.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// The address where the metrics of `--headless` are served (at `/metrics`).
+    #[arg(long, value_name = "ADDRESS", default_value = headless::DEFAULT_METRICS_ADDRESS)]
+    pub metrics_address: String,
 }
 
-struct Ctx<'a> {
+struct Ctx {
     bar_width: u16,
-    bars: Vec<Bar<'a>>,
     color: Color,
-    amount_channels: u16,
+    orientation: Orientation,
+    show_peaks: bool,
+
+    panes: Vec<Pane>,
+    // the index of the pane which the keybindings (and the status line) refer to
+    focused: usize,
+    // the areas of the bars of each pane within the last frame
+    pane_areas: Vec<Rect>,
+    device_chooser: Option<DeviceChooser>,
+    broadcasters: Vec<Broadcaster>,
 
-    sample_processor: SampleProcessor,
-    bar_processor: BarProcessor,
     interpolation: InterpolationVariant,
+    preset: Preset,
+
+    // the cells (columns or rows, depending on the orientation) of the focused pane
+    // where the user started and currently is with dragging
+    selection: Option<(u16, u16)>,
+
+    show_info: bool,
+    // a changed value which is shown briefly instead of the status line
+    notice: Option<(String, Instant)>,
+    last_frame: Option<Instant>,
+    fps: f32,
+    target_fps: NonZero<u32>,
+    idle_fps: NonZero<u32>,
 }
 
-impl<'a> Ctx<'a> {
-    fn amount_bars(&self, columns: u16) -> NonZero<u16> {
-        NonZero::new(columns / self.bar_width).unwrap()
+impl Ctx {
+    fn pane(&self) -> &Pane {
+        &self.panes[self.focused]
     }
 
-    fn set_bars(&mut self, columns: u16) {
-        let amount_bars = self.amount_bars(columns);
+    /// Splits the given area into the panes and updates their amount of bars if their size has changed.
+    fn layout_panes(&mut self, chart_area: Rect) {
+        self.pane_areas = pane_areas(chart_area, self.orientation, self.panes.len());
+
+        let amount_panes = self.panes.len();
+        for (pane, &area) in self.panes.iter_mut().zip(self.pane_areas.iter()) {
+            let bar_space = self.orientation.bar_space(bar_area(area, amount_panes));
+            if pane.bar_space != bar_space {
+                pane.bar_space = bar_space;
+                let amount_bars = NonZero::new((bar_space / self.bar_width).max(1)).unwrap();
+                pane.set_bars(amount_bars);
+            }
+        }
+    }
 
-        self.bars.resize(
-            amount_bars.get() as usize,
-            Bar::default().text_value("".to_string()),
-        );
+    /// Recomputes the amount of bars of each pane within the next frame.
+    fn invalidate_bars(&mut self) {
+        for pane in self.panes.iter_mut() {
+            pane.bar_space = 0;
+        }
+    }
 
-        self.bar_processor = BarProcessor::new(
-            &self.sample_processor,
-            BarProcessorConfig {
-                amount_bars: NonZero::new(amount_bars.get() / self.amount_channels).unwrap(),
-                ..self.bar_processor.config().clone()
-            },
-        );
+    fn set_bar_width(&mut self, bar_width: u16) {
+        self.bar_width = bar_width.max(1);
+        self.invalidate_bars();
     }
 
-    fn get_bars(&mut self) -> &[Bar<'a>] {
-        self.sample_processor.process_next_samples();
-        let bar_values = self.bar_processor.process_bars(&self.sample_processor);
+    fn update_values(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame.replace(now) {
+            let fps = 1. / (now - last_frame).as_secs_f32().max(f32::EPSILON);
+            self.fps += FPS_SMOOTHING * (fps - self.fps);
+        }
 
-        let mut bar_idx = 0;
-        for channel_bars in bar_values {
-            for value in channel_bars.iter() {
-                self.bars[bar_idx] = self.bars[bar_idx]
-                    .clone()
-                    .value((HEIGHT as f32 * value) as u64);
-                bar_idx += 1;
-            }
+        for pane in self.panes.iter_mut() {
+            pane.update_values(HEIGHT);
+        }
+
+        for broadcaster in self.broadcasters.iter() {
+            broadcaster.send(self.panes.iter().map(Pane::channel_bars));
+        }
+    }
+
+    /// Returns `true` if the audio of all panes has been silent for [IDLE_AFTER].
+    fn is_idle(&self) -> bool {
+        self.panes
+            .iter()
+            .all(|pane| pane.sample_processor().silence_duration() >= IDLE_AFTER)
+    }
+
+    /// Returns how long to wait for the next frame. It's longer while the audio is silent.
+    fn frame_time(&self) -> Duration {
+        let fps = if self.is_idle() {
+            self.idle_fps.min(self.target_fps)
+        } else {
+            self.target_fps
+        };
+
+        Duration::from_secs(1) / fps.get()
+    }
+
+    fn bars(&self, pane_idx: usize) -> Bars<'_> {
+        let selected = match self.selection {
+            Some((start, end)) if pane_idx == self.focused => Some(self.selected_bars(start, end)),
+            _ => None,
+        };
+
+        Bars {
+            values: self.panes[pane_idx].values(),
+            max: HEIGHT,
+            bar_width: self.bar_width,
+            bar_gap: BAR_GAP,
+            style: Style::new().fg(self.color),
+            selected,
+            orientation: self.orientation,
+            peaks: self.show_peaks.then(|| self.panes[pane_idx].peaks()),
         }
+    }
+
+    /// Adds a pane which visualizes the output device with the given name.
+    fn add_pane(&mut self, device_name: &str) -> Result<(), String> {
+        let device = shady_audio::util::get_device(device_name, DeviceType::Output)
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("The device \"{}\" isn't available.", device_name))?;
 
-        self.bars.as_slice()
+        let fetcher = system_audio_fetcher(device).map_err(|err| err.to_string())?;
+        let config = self.pane().config().clone();
+
+        self.panes
+            .push(Pane::new(device_name.to_string(), fetcher, config));
+        self.focused = self.panes.len() - 1;
+        self.selection = None;
+        self.invalidate_bars();
+
+        Ok(())
+    }
+
+    /// Removes the focused pane unless it's the last one.
+    fn remove_pane(&mut self) {
+        if self.panes.len() <= 1 {
+            return;
+        }
+
+        self.panes.remove(self.focused);
+        self.focused = self.focused.min(self.panes.len() - 1);
+        self.selection = None;
+        self.invalidate_bars();
+    }
+
+    fn focus_next_pane(&mut self) {
+        self.focused = (self.focused + 1) % self.panes.len();
+        self.selection = None;
+    }
+
+    /// Focuses the pane at the given position and returns the cell of the position within the pane.
+    fn focus_pane_at(&mut self, column: u16, row: u16) -> Option<u16> {
+        let position = Position::new(column, row);
+        let (idx, area) = self
+            .pane_areas
+            .iter()
+            .enumerate()
+            .find(|(_, area)| area.contains(position))?;
+
+        if self.focused != idx {
+            self.focused = idx;
+            self.selection = None;
+        }
+
+        let area = bar_area(*area, self.panes.len());
+        Some(
+            self.orientation
+                .cell(column.saturating_sub(area.x), row.saturating_sub(area.y)),
+        )
+    }
+
+    /// Changes the config of the bar processors of all panes.
+    fn update_configs(&mut self, update: impl Fn(BarProcessorConfig) -> BarProcessorConfig) {
+        for pane in self.panes.iter_mut() {
+            pane.set_config(update(pane.config().clone()));
+        }
+    }
+
+    /// Reconfigures the bar processors of all panes in place, so the bars keep their values.
+    fn update_bar_processors(&mut self, update: impl Fn(&mut BarProcessor)) {
+        for pane in self.panes.iter_mut() {
+            update(pane.bar_processor_mut());
+        }
     }
 
     fn next_interpolation(&mut self) {
         self.interpolation = match self.interpolation {
             InterpolationVariant::None => InterpolationVariant::Linear,
             InterpolationVariant::Linear => InterpolationVariant::CubicSpline,
-            InterpolationVariant::CubicSpline => InterpolationVariant::None,
+            InterpolationVariant::CubicSpline => InterpolationVariant::MonotoneCubic,
+            InterpolationVariant::MonotoneCubic => InterpolationVariant::None,
+        };
+
+        let interpolation = self.interpolation;
+        self.update_bar_processors(|bar_processor| bar_processor.set_interpolation(interpolation));
+    }
+
+    fn next_preset(&mut self) {
+        let idx = Preset::ALL
+            .iter()
+            .position(|&preset| preset == self.preset)
+            .unwrap_or(0);
+        self.preset = Preset::ALL[(idx + 1) % Preset::ALL.len()];
+
+        let preset = self.preset;
+        self.update_configs(|config| preset.config(config.amount_bars));
+        self.interpolation = self.pane().config().interpolation;
+    }
+
+    /// Switches the focused pane to the next (or previous) range of [FREQ_RANGES].
+    fn cycle_freq_range(&mut self, forward: bool) {
+        let current = &self.pane().config().freq_range;
+        let idx = FREQ_RANGES
+            .iter()
+            .position(|&(start, end)| current.start.get() == start && current.end.get() == end);
+
+        let idx = match (idx, forward) {
+            (Some(idx), true) => (idx + 1) % FREQ_RANGES.len(),
+            (Some(idx), false) => (idx + FREQ_RANGES.len() - 1) % FREQ_RANGES.len(),
+            (None, true) => 0,
+            (None, false) => FREQ_RANGES.len() - 1,
+        };
+
+        let (start, end) = FREQ_RANGES[idx];
+        self.set_freq_range(NonZero::new(start).unwrap()..NonZero::new(end).unwrap());
+        self.notify(format!("frequency range: {} Hz - {} Hz", start, end));
+    }
+
+    /// Changes the sensitivity of all panes by the given amount.
+    fn change_sensitivity(&mut self, delta: f32) {
+        let sensitivity = (self.pane().config().sensitivity + delta)
+            .clamp(*SENSITIVITY_RANGE.start(), *SENSITIVITY_RANGE.end());
+
+        self.update_bar_processors(|bar_processor| bar_processor.set_sensitivity(sensitivity));
+        self.notify(format!("sensitivity: {:.2}", sensitivity));
+    }
+
+    fn toggle_bar_distribution(&mut self) {
+        let bar_distribution = match self.pane().config().bar_distribution {
+            BarDistribution::Uniform => BarDistribution::Natural,
+            BarDistribution::Natural => BarDistribution::Uniform,
+        };
+
+        self.update_bar_processors(|bar_processor| {
+            bar_processor.set_bar_distribution(bar_distribution)
+        });
+        self.notify(format!("bar distribution: {:?}", bar_distribution));
+    }
+
+    /// Shows the given text for [NOTICE_DURATION] instead of the status line.
+    fn notify(&mut self, text: String) {
+        self.notice = Some((text, Instant::now()));
+    }
+
+    fn reset_freq_range(&mut self) {
+        let amount_bars = self.pane().config().amount_bars;
+        self.set_freq_range(self.preset.config(amount_bars).freq_range);
+    }
+
+    /// Sets the frequency range of the focused pane.
+    fn set_freq_range(&mut self, freq_range: Range<NonZero<u16>>) {
+        self.panes[self.focused]
+            .bar_processor_mut()
+            .set_freq_range(freq_range);
+    }
+
+    /// Zooms into the frequencies of the bars between the two given cells.
+    fn zoom(&mut self, start_cell: u16, end_cell: u16) {
+        let (start_freq, end_freq) = self.selected_freq_range(start_cell, end_cell);
+
+        // the bar processor needs at least one frequency
+        let start =
+            NonZero::new((start_freq as u16).min(u16::MAX - 1)).unwrap_or(NonZero::<u16>::MIN);
+        let end = NonZero::new(end_freq as u16)
+            .unwrap_or(NonZero::<u16>::MIN)
+            .max(start.saturating_add(1));
+
+        self.set_freq_range(start..end);
+    }
+
+    /// Returns the indices of the bars of the focused pane between the two given cells.
+    fn selected_bars(&self, start_cell: u16, end_cell: u16) -> RangeInclusive<usize> {
+        let bar_idx = |cell: u16| {
+            let idx = usize::from(cell / (self.bar_width + BAR_GAP));
+            idx.min(self.pane().values().len().saturating_sub(1))
+        };
+
+        bar_idx(start_cell.min(end_cell))..=bar_idx(start_cell.max(end_cell))
+    }
+
+    /// Returns the (approximated) frequency range of the bars between the two given cells.
+    ///
+    /// The bars of each channel are distributed logarithmically over the frequency range.
+    fn selected_freq_range(&self, start_cell: u16, end_cell: u16) -> (f32, f32) {
+        let amount_channel_bars = self.pane().config().amount_bars.get() as usize;
+        let selected_bars = self.selected_bars(start_cell, end_cell);
+
+        // each channel has its own bars for the whole frequency range
+        let start_bar = selected_bars.start() % amount_channel_bars;
+        let end_bar = if selected_bars.start() / amount_channel_bars
+            == selected_bars.end() / amount_channel_bars
+        {
+            selected_bars.end() % amount_channel_bars
+        } else {
+            amount_channel_bars - 1
+        };
+
+        let range = &self.pane().config().freq_range;
+        let min = range.start.get() as f32;
+        let max = range.end.get() as f32;
+        let freq_at = |bar_idx: usize| {
+            let t = bar_idx as f32 / amount_channel_bars as f32;
+            min * (max / min).powf(t)
         };
 
-        self.bar_processor = BarProcessor::new(
-            &self.sample_processor,
-            BarProcessorConfig {
-                interpolation: self.interpolation,
-                ..self.bar_processor.config().clone()
-            },
+        (freq_at(start_bar), freq_at(end_bar + 1))
+    }
+
+    fn info_line(&self) -> String {
+        let sample_processor = self.pane().sample_processor();
+
+        format!(
+            "source: {} | {} Hz | bars: {} | {:.0} FPS{} | latency: ~{} ms",
+            sample_processor.source_name().unwrap_or("unknown"),
+            sample_processor.sample_rate().0,
+            self.pane().values().len(),
+            self.fps,
+            if self.is_idle() { " (idle)" } else { "" },
+            sample_processor.latency().as_millis()
+        )
+    }
+
+    fn status_line(&self) -> String {
+        if let Some((notice, since)) = &self.notice {
+            if since.elapsed() < NOTICE_DURATION {
+                return notice.clone();
+            }
+        }
+
+        let range = &self.pane().config().freq_range;
+
+        let mut status = format!(
+            "{} Hz - {} Hz | bar width: {} | interpolation: {:?} | preset: {:?} | orientation: {:?}",
+            range.start,
+            range.end,
+            self.bar_width,
+            self.interpolation,
+            self.preset,
+            self.orientation
         );
+
+        if let Some((start, end)) = self.selection {
+            let (start_freq, end_freq) = self.selected_freq_range(start, end);
+            status.push_str(&format!(
+                " | selection: {:.0} Hz - {:.0} Hz",
+                start_freq, end_freq
+            ));
+        }
+
+        status
     }
 }
 
 fn main() -> std::io::Result<()> {
-    init_logger();
-
     let cli = Cli::parse();
+    init_logger(cli.headless);
+
     if cli.show_output_devices {
         print_available_output_devices();
         println!("Choose one of them and add it to the cli as an argument.");
         return Ok(());
     }
 
-    let mut ctx = {
-        let device = match cli.output_device {
-            Some(device_name) => {
-                match shady_audio::util::get_device(&device_name, DeviceType::Output)
-                    .expect("Host has output devices")
-                {
-                    Some(device) => device,
-                    None => {
-                        print_available_output_devices();
-                        panic!(
-                            "There isn't an output device called: \"{}\".\nChoose another one.",
-                            &device_name
-                        );
-                    }
-                }
-            }
-            None => shady_audio::util::get_default_device(DeviceType::Output)
-                .expect("Default output device exists"),
-        };
-
-        let descriptor = SystemAudioFetcherDescriptor {
-            device,
-            amount_channels: Some(2),
-            ..Default::default()
-        };
-
-        let sample_processor = SampleProcessor::new(SystemAudioFetcher::new(&descriptor).unwrap());
-        let bar_processor = BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    let broadcasters = cli
+        .broadcast
+        .iter()
+        .map(|target| {
+            Broadcaster::new(target).map_err(|err| {
+                std::io::Error::new(
+                    err.kind(),
+                    format!("Couldn't start the broadcast to {:?}: {}", target, err),
+                )
+            })
+        })
+        .collect::<std::io::Result<Vec<Broadcaster>>>()?;
+
+    let (name, fetcher) = fetcher(&cli);
+
+    if cli.headless {
+        return headless::run(fetcher, &cli.metrics_address, &broadcasters);
+    }
 
+    let mut ctx = {
         Ctx {
             bar_width: 3,
-            amount_channels: 2,
-            bars: Vec::new(),
             color: cli.color,
-            sample_processor,
-            bar_processor,
+            orientation: cli.orientation,
+            show_peaks: cli.peaks,
+            panes: vec![Pane::new(name, fetcher, BarProcessorConfig::default())],
+            focused: 0,
+            pane_areas: Vec::new(),
+            device_chooser: None,
+            broadcasters,
             interpolation: InterpolationVariant::CubicSpline,
+            preset: Preset::Classic,
+            selection: None,
+            show_info: false,
+            notice: None,
+            last_frame: None,
+            fps: 0.,
+            target_fps: cli.fps,
+            idle_fps: cli.idle_fps,
         }
     };
 
     let mut terminal = ratatui::init();
+    crossterm::execute!(std::io::stdout(), EnableMouseCapture)?;
 
-    let mut prev_columns = 0;
     loop {
-        let window_size = crossterm::terminal::window_size()?;
-        if prev_columns != window_size.columns {
-            prev_columns = window_size.columns;
-            ctx.set_bars(window_size.columns);
-        }
+        ctx.layout_panes(chart_area(terminal.get_frame().area(), ctx.show_info));
 
         terminal
             .draw(|frame| draw(frame, &mut ctx))
             .expect("Render frame");
 
-        if event::poll(Duration::from_millis(1000 / 60))? {
-            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-                match code {
+        if event::poll(ctx.frame_time())? {
+            let event = event::read()?;
+
+            if let Some(device_chooser) = &mut ctx.device_chooser {
+                if let Event::Key(KeyEvent { code, .. }) = event {
+                    match code {
+                        KeyCode::Esc => ctx.device_chooser = None,
+                        KeyCode::Down | KeyCode::Char('j') => device_chooser.select_next(),
+                        KeyCode::Up | KeyCode::Char('k') => device_chooser.select_previous(),
+                        KeyCode::Enter => {
+                            if let Some(name) = device_chooser.selected().map(str::to_string) {
+                                match ctx.add_pane(&name) {
+                                    Ok(()) => ctx.device_chooser = None,
+                                    Err(err) => {
+                                        if let Some(device_chooser) = &mut ctx.device_chooser {
+                                            device_chooser.error = Some(err);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            match event {
+                Event::Key(KeyEvent { code, .. }) => match code {
                     KeyCode::Char('q') => break,
                     KeyCode::Char('+') => {
-                        ctx.bar_width += 1;
-                        ctx.set_bars(window_size.columns);
+                        ctx.set_bar_width(ctx.bar_width + 1);
                     }
                     KeyCode::Char('-') => {
-                        ctx.bar_width = 1.max(ctx.bar_width - 1);
-                        ctx.set_bars(window_size.columns);
+                        ctx.set_bar_width(ctx.bar_width.saturating_sub(1));
                     }
                     KeyCode::Char('i') => {
                         ctx.next_interpolation();
                     }
+                    KeyCode::Char('p') => {
+                        ctx.next_preset();
+                    }
+                    KeyCode::Char('r') => {
+                        ctx.reset_freq_range();
+                    }
+                    KeyCode::Char(']') | KeyCode::Right => {
+                        ctx.cycle_freq_range(true);
+                    }
+                    KeyCode::Char('[') | KeyCode::Left => {
+                        ctx.cycle_freq_range(false);
+                    }
+                    KeyCode::Up => {
+                        ctx.change_sensitivity(SENSITIVITY_STEP);
+                    }
+                    KeyCode::Down => {
+                        ctx.change_sensitivity(-SENSITIVITY_STEP);
+                    }
+                    KeyCode::Char('d') => {
+                        ctx.toggle_bar_distribution();
+                    }
+                    KeyCode::Char('?') => {
+                        ctx.show_info = !ctx.show_info;
+                    }
+                    KeyCode::Char('o') => {
+                        ctx.orientation = ctx.orientation.next();
+                        ctx.invalidate_bars();
+                    }
+                    KeyCode::Char('c') => {
+                        ctx.show_peaks = !ctx.show_peaks;
+                    }
+                    KeyCode::Char('a') => {
+                        ctx.device_chooser = Some(DeviceChooser::new());
+                    }
+                    KeyCode::Char('x') => {
+                        ctx.remove_pane();
+                    }
+                    KeyCode::Tab => {
+                        ctx.focus_next_pane();
+                    }
                     _ => {}
-                }
+                },
+                Event::Mouse(MouseEvent {
+                    kind, column, row, ..
+                }) => match kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        if let Some(cell) = ctx.focus_pane_at(column, row) {
+                            ctx.selection = Some((cell, cell));
+                        }
+                    }
+                    MouseEventKind::Drag(MouseButton::Left) => {
+                        let area = bar_area(ctx.pane_areas[ctx.focused], ctx.panes.len());
+                        let cell = ctx
+                            .orientation
+                            .cell(column.saturating_sub(area.x), row.saturating_sub(area.y));
+                        if let Some((_, end)) = &mut ctx.selection {
+                            *end = cell;
+                        }
+                    }
+                    MouseEventKind::Up(MouseButton::Left) => {
+                        if let Some((start, end)) = ctx.selection.take() {
+                            ctx.zoom(start, end);
+                        }
+                    }
+                    MouseEventKind::Down(MouseButton::Right) => {
+                        if ctx.focus_pane_at(column, row).is_some() {
+                            ctx.reset_freq_range();
+                        }
+                    }
+                    MouseEventKind::ScrollUp => {
+                        ctx.set_bar_width(ctx.bar_width + 1);
+                    }
+                    MouseEventKind::ScrollDown => {
+                        ctx.set_bar_width(ctx.bar_width.saturating_sub(1));
+                    }
+                    _ => {}
+                },
+                _ => {}
             }
         }
     }
 
+    crossterm::execute!(std::io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
     Ok(())
 }
 
 fn draw(frame: &mut Frame, ctx: &mut Ctx) {
-    let bar_chart = BarChart::default()
-        .bar_width(ctx.bar_width)
-        .bar_gap(1)
-        .bar_style(Style::new().fg(ctx.color))
-        .data(BarGroup::default().label("".into()).bars(ctx.get_bars()))
-        .max(HEIGHT);
-
-    frame.render_widget(&bar_chart, frame.area());
+    let [_, status_area, info_area] = layout(frame.area(), ctx.show_info);
+
+    ctx.update_values();
+
+    let amount_panes = ctx.panes.len();
+    for (idx, &area) in ctx.pane_areas.iter().enumerate() {
+        if amount_panes > 1 {
+            let style = if idx == ctx.focused {
+                Style::new().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::new()
+            };
+            frame.render_widget(Line::raw(ctx.panes[idx].name.as_str()).style(style), area);
+        }
+
+        frame.render_widget(ctx.bars(idx), bar_area(area, amount_panes));
+    }
+
+    frame.render_widget(Line::raw(ctx.status_line()), status_area);
+    if ctx.show_info {
+        frame.render_widget(Line::raw(ctx.info_line()), info_area);
+    }
+
+    if let Some(device_chooser) = &mut ctx.device_chooser {
+        device_chooser.draw(frame);
+    }
 }
 
-fn init_logger() {
-    let file = File::create("/tmp/shady-cli.log").unwrap();
+/// Returns the area of the bars, the status line and the info line (empty if it's hidden).
+fn layout(area: Rect, show_info: bool) -> [Rect; 3] {
+    Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+        Constraint::Length(u16::from(show_info)),
+    ])
+    .areas(area)
+}
 
+fn chart_area(area: Rect, show_info: bool) -> Rect {
+    layout(area, show_info)[0]
+}
+
+/// Splits the area of the bars into the given amount of panes.
+///
+/// The panes are placed next to each other along the direction in which the bars grow.
+fn pane_areas(chart_area: Rect, orientation: Orientation, amount_panes: usize) -> Vec<Rect> {
+    let constraints = vec![Constraint::Fill(1); amount_panes];
+    let layout = match orientation {
+        Orientation::Horizontal => Layout::horizontal(constraints),
+        _ => Layout::vertical(constraints),
+    };
+
+    layout.split(chart_area).to_vec()
+}
+
+/// Returns the area of the bars within a pane. If there are multiple panes, the first line shows the name of the device.
+fn bar_area(pane_area: Rect, amount_panes: usize) -> Rect {
+    if amount_panes <= 1 {
+        return pane_area;
+    }
+
+    let [_, bar_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(pane_area);
+    bar_area
+}
+
+/// Returns the name of the audio source of the arguments and its fetcher.
+fn fetcher(cli: &Cli) -> (String, Box<dyn Fetcher>) {
+    let device = match &cli.output_device {
+        Some(device_name) => {
+            match shady_audio::util::get_device(device_name, DeviceType::Output)
+                .expect("Host has output devices")
+            {
+                Some(device) => device,
+                None => {
+                    print_available_output_devices();
+                    panic!(
+                        "There isn't an output device called: \"{}\".\nChoose another one.",
+                        device_name
+                    );
+                }
+            }
+        }
+        None => shady_audio::util::get_default_device(DeviceType::Output)
+            .expect("Default output device exists"),
+    };
+
+    match &cli.file {
+        Some(path) => {
+            let name = path.to_string_lossy().to_string();
+            let descriptor = FileFetcherDescriptor {
+                path: path.clone(),
+                playback: (!cli.no_playback).then_some(device),
+                frame_rate: None,
+            };
+
+            let fetcher = FileFetcher::new(descriptor).unwrap_or_else(|err| panic!("{}", err));
+            (name, fetcher)
+        }
+        None => {
+            let name = device.name().unwrap_or_default();
+            (name, system_audio_fetcher(device).unwrap())
+        }
+    }
+}
+
+fn system_audio_fetcher(device: cpal::Device) -> Result<Box<dyn Fetcher>, SystemAudioError> {
+    let descriptor = SystemAudioFetcherDescriptor {
+        device,
+        amount_channels: Some(2),
+        ..Default::default()
+    };
+
+    Ok(SystemAudioFetcher::new(&descriptor)?)
+}
+
+/// Logs into `/tmp/shady-cli.log` (the TUI occupies the terminal) or to stderr in headless mode.
+fn init_logger(headless: bool) {
     let fmt_layer = tracing_subscriber::fmt::layer()
         .with_target(false)
-        .with_writer(file)
         .without_time();
 
-    tracing_subscriber::registry()
-        .with(fmt_layer)
-        .with(EnvFilter::from_env(EnvFilter::DEFAULT_ENV))
-        .init();
+    let registry = tracing_subscriber::registry().with(EnvFilter::from_env(EnvFilter::DEFAULT_ENV));
+
+    if headless {
+        registry.with(fmt_layer.with_writer(std::io::stderr)).init();
+    } else {
+        let file = File::create("/tmp/shady-cli.log").unwrap();
+        registry.with(fmt_layer.with_writer(file)).init();
+    }
 }
 
 fn print_available_output_devices() {
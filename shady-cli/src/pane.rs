@@ -0,0 +1,116 @@
+use std::{num::NonZero, time::Instant};
+
+use shady_audio::{fetcher::Fetcher, BarProcessor, BarProcessorConfig, PeakHold, SampleProcessor};
+
+/// The audio of one device which is visualized in its own pane.
+pub struct Pane {
+    /// The name of the device (or file) which is visualized.
+    pub name: String,
+
+    /// The amount of cells where the bars have been placed the last time.
+    /// Set it to `0` to force to recompute the amount of bars.
+    pub bar_space: u16,
+
+    amount_channels: u16,
+    values: Vec<u64>,
+    bar_values: Vec<f32>,
+    peaks: Vec<u64>,
+
+    sample_processor: SampleProcessor,
+    bar_processor: BarProcessor,
+    peak_hold: PeakHold,
+    last_update: Option<Instant>,
+}
+
+impl Pane {
+    pub fn new(name: String, fetcher: Box<dyn Fetcher>, config: BarProcessorConfig) -> Self {
+        let amount_channels = fetcher.channels();
+        let sample_processor = SampleProcessor::new(fetcher);
+        let bar_processor = BarProcessor::new(&sample_processor, config);
+
+        Self {
+            name,
+            bar_space: 0,
+            amount_channels,
+            values: Vec::new(),
+            bar_values: Vec::new(),
+            peaks: Vec::new(),
+            sample_processor,
+            bar_processor,
+            peak_hold: PeakHold::default(),
+            last_update: None,
+        }
+    }
+
+    /// The heights of the bars of all channels.
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    /// The heights of the peaks of the bars of all channels (see [PeakHold]).
+    pub fn peaks(&self) -> &[u64] {
+        &self.peaks
+    }
+
+    /// The bars of each channel within the range `[0, 1]`.
+    pub fn channel_bars(&self) -> Vec<&[f32]> {
+        let amount_channel_bars = usize::from(self.config().amount_bars.get());
+        self.bar_values.chunks(amount_channel_bars).collect()
+    }
+
+    pub fn sample_processor(&self) -> &SampleProcessor {
+        &self.sample_processor
+    }
+
+    pub fn config(&self) -> &BarProcessorConfig {
+        self.bar_processor.config()
+    }
+
+    /// Use it to change the config in place, so the bars keep their values.
+    pub fn bar_processor_mut(&mut self) -> &mut BarProcessor {
+        &mut self.bar_processor
+    }
+
+    pub fn set_config(&mut self, config: BarProcessorConfig) {
+        self.bar_processor = BarProcessor::new(&self.sample_processor, config);
+    }
+
+    /// Sets the amount of bars of all channels together.
+    pub fn set_bars(&mut self, amount_bars: NonZero<u16>) {
+        self.values.resize(usize::from(amount_bars.get()), 0);
+        self.peaks.resize(usize::from(amount_bars.get()), 0);
+
+        let amount_channel_bars = (amount_bars.get() / self.amount_channels.max(1)).max(1);
+        self.set_config(BarProcessorConfig {
+            amount_bars: NonZero::new(amount_channel_bars).unwrap(),
+            ..self.config().clone()
+        });
+    }
+
+    /// Processes the next samples and scales the bars to the range `[0, max]`.
+    pub fn update_values(&mut self, max: u64) {
+        self.sample_processor.process_next_samples();
+        let bar_values = self.bar_processor.process_bars(&self.sample_processor);
+
+        self.bar_values.clear();
+        self.bar_values.extend(
+            bar_values
+                .iter()
+                .flat_map(|channel_bars| channel_bars.iter()),
+        );
+        for (value, bar_value) in self.values.iter_mut().zip(self.bar_values.iter()) {
+            *value = (max as f32 * bar_value) as u64;
+        }
+
+        let now = Instant::now();
+        let delta_time = self
+            .last_update
+            .replace(now)
+            .map_or(Default::default(), |last_update| now - last_update);
+
+        let peaks = self.peak_hold.update(&self.bar_values, delta_time);
+        for (value, peak) in self.peaks.iter_mut().zip(peaks.iter()) {
+            *value = (max as f32 * peak) as u64;
+        }
+    }
+}
@@ -3,10 +3,7 @@ use std::{borrow::Cow, sync::Arc};
 
 use pollster::FutureExt;
 use shady::{Shady, ShadyDescriptor, ShadyRenderPipeline};
-use shady_audio::{
-    fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
-    SampleProcessor,
-};
+use shady_audio::SampleProcessor;
 use wgpu::{
     Backends, Device, Instance, Queue, ShaderSource, Surface, SurfaceConfiguration,
     TextureViewDescriptor,
@@ -69,7 +66,7 @@ impl<'a> State<'a> {
 
             let size = window.clone().inner_size();
 
-            let config = wgpu::SurfaceConfiguration {
+            wgpu::SurfaceConfiguration {
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
                 format: surface_format,
                 width: size.width,
@@ -78,9 +75,7 @@ impl<'a> State<'a> {
                 alpha_mode: surface_caps.alpha_modes[0],
                 view_formats: vec![],
                 desired_maximum_frame_latency: 2,
-            };
-
-            config
+            }
         };
 
         // SHADY
@@ -97,14 +92,15 @@ impl<'a> State<'a> {
         };
 
         // SHADY
-        let sample_processor = SampleProcessor::new(
-            SystemAudioFetcher::new(&SystemAudioFetcherDescriptor::default()).unwrap(),
-        );
+        let sample_processor = shady::default_sample_processor();
         // SHADY
         let shady = Shady::new(ShadyDescriptor {
             device: &device,
             sample_processor: &sample_processor,
-        });
+            audio_handle: None,
+            clock: None,
+        })
+        .unwrap();
 
         Self {
             surface,
@@ -127,11 +123,11 @@ impl<'a> State<'a> {
 
             self.sample_processor.process_next_samples();
             self.shady
-                .update_audio_buffer(&mut self.queue, &self.sample_processor);
-            self.shady.update_frame_buffer(&mut self.queue);
-            self.shady.update_mouse_buffer(&mut self.queue);
-            self.shady.update_resolution_buffer(&mut self.queue);
-            self.shady.update_time_buffer(&mut self.queue);
+                .update_audio_buffer(&self.queue, &self.sample_processor);
+            self.shady.update_frame_buffer(&self.queue);
+            self.shady.update_mouse_buffer(&self.queue);
+            self.shady.update_resolution_buffer(&self.queue);
+            self.shady.update_time_buffer(&self.queue);
         }
 
         self.surface.configure(&self.device, &self.config);
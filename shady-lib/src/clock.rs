@@ -0,0 +1,143 @@
+//! The clocks which can drive `iTime` (see [crate::ShadyDescriptor::clock]).
+//!
+//! By default, `iTime` follows the wall clock ([RealTimeClock]). For tests or deterministic offscreen
+//! rendering you can let it advance by a fixed step per frame ([FixedStepClock]) or set it yourself ([ManualClock]).
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Provides the playback time for `iTime`.
+pub trait Clock: Send {
+    /// Returns the time which has passed since the clock has been started.
+    fn elapsed(&self) -> Duration;
+
+    /// Called once per frame by [crate::Shady::update_time_buffer] before the time is read.
+    fn tick(&mut self) {}
+}
+
+/// Follows the wall clock.
+#[derive(Debug, Clone, Copy)]
+pub struct RealTimeClock {
+    start: Instant,
+}
+
+impl RealTimeClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealTimeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RealTimeClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Advances by the same step each frame, regardless of how long a frame takes.
+///
+/// The first frame has the time `0`.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedStepClock {
+    step: Duration,
+    elapsed: Duration,
+    started: bool,
+}
+
+impl FixedStepClock {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            elapsed: Duration::ZERO,
+            started: false,
+        }
+    }
+
+    /// Creates a clock which advances by `1 / fps` seconds per frame.
+    pub fn with_fps(fps: u32) -> Self {
+        Self::new(Duration::from_secs(1) / fps.max(1))
+    }
+}
+
+impl Clock for FixedStepClock {
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    fn tick(&mut self) {
+        if self.started {
+            self.elapsed += self.step;
+        }
+        self.started = true;
+    }
+}
+
+/// Only changes if you set it.
+///
+/// Clones share the same time, so keep a clone to control the clock after passing it to [crate::Shady].
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use shady::clock::{Clock, ManualClock};
+///
+/// let clock = ManualClock::default();
+/// let handle = clock.clone();
+///
+/// handle.advance(Duration::from_millis(500));
+/// assert_eq!(clock.elapsed(), Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn set(&self, elapsed: Duration) {
+        *self.elapsed.lock().unwrap() = elapsed;
+    }
+
+    pub fn advance(&self, delta: Duration) {
+        *self.elapsed.lock().unwrap() += delta;
+    }
+}
+
+impl Clock for ManualClock {
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_step() {
+        let mut clock = FixedStepClock::with_fps(4);
+
+        let times = (0..3)
+            .map(|_| {
+                clock.tick();
+                clock.elapsed()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            times,
+            [
+                Duration::ZERO,
+                Duration::from_millis(250),
+                Duration::from_millis(500)
+            ]
+        );
+    }
+}
@@ -0,0 +1,428 @@
+//! Compute shaders which have access to the resources of `shady`.
+//!
+//! Some effects (like particle systems or cellular automata) are easier to express as a compute shader.
+//! The compute shader of a [ShadyComputePipeline] writes each frame into a storage texture which is then
+//! drawn onto the render target by [crate::Shady::add_compute_pass].
+//!
+//! Besides the resources of `shady` in `@group(0)`, the shader gets the following bindings in `@group(1)`:
+//!
+//! ```wgsl
+//! // the texture of the current frame
+//! @group(1) @binding(0)
+//! var output: texture_storage_2d<rgba16float, write>;
+//!
+//! // the texture of the previous frame (read it with `textureLoad`)
+//! @group(1) @binding(1)
+//! var previous: texture_2d<f32>;
+//! ```
+//!
+//! The entrypoint has to be called `main` and must have a workgroup size of [WORKGROUP_SIZE].
+//! Use [wgsl_template] to get started.
+use std::fmt;
+
+use wgpu::{CommandEncoder, Device, ShaderSource, TextureView};
+
+use crate::{
+    resources::{Resources, COMPUTE_RESOURCE_VISIBILITY},
+    template::TemplateGenerator,
+    BIND_GROUP_INDEX,
+};
+
+/// The name of the entrypoint function of the compute shader.
+pub const COMPUTE_ENTRYPOINT: &str = "main";
+
+/// The workgroup size (`@workgroup_size(x, y)`) which the compute shader must have.
+pub const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+
+/// The format of the storage textures.
+pub const OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+const OUTPUT_BIND_GROUP_INDEX: u32 = BIND_GROUP_INDEX + 1;
+const OUTPUT_BINDING: u32 = 0;
+const PREVIOUS_BINDING: u32 = 1;
+
+const DEFAULT_TEMPLATE_WGSL_BODY: &str = "
+    let size = textureDimensions(output);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+
+    let uv = vec2<f32>(id.xy) / iResolution.xy;
+    let col = 0.5 + 0.5 * cos(iTime + uv.xyx + vec3<f32>(0.0, 2.0, 4.0));
+
+    textureStore(output, id.xy, vec4<f32>(col, 1.0));
+";
+
+/// A compute pipeline whose output is presented by [crate::Shady::add_compute_pass].
+///
+/// It holds two storage textures which are swapped each frame so the shader can read the previous frame.
+pub struct ShadyComputePipeline {
+    compute: wgpu::ComputePipeline,
+    present: wgpu::RenderPipeline,
+
+    output_layout: wgpu::BindGroupLayout,
+    present_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+
+    size: (u32, u32),
+    // one per storage texture
+    output_bind_groups: [wgpu::BindGroup; 2],
+    present_bind_groups: [wgpu::BindGroup; 2],
+    // the index of the texture which is written next
+    current: usize,
+}
+
+impl ShadyComputePipeline {
+    /// Creates the pipeline with storage textures of the given size.
+    ///
+    /// `texture_format` is the format of the render target which the output is presented on.
+    pub fn new(
+        device: &Device,
+        shader_source: ShaderSource<'_>,
+        texture_format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let size = (size.0.max(1), size.1.max(1));
+        let shady_layout = Resources::bind_group_layout(device, COMPUTE_RESOURCE_VISIBILITY);
+        let output_layout = output_bind_group_layout(device);
+        let present_layout = present_bind_group_layout(device);
+
+        let compute = {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shady compute shader"),
+                source: shader_source,
+            });
+
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Shady compute pipeline layout"),
+                bind_group_layouts: &[&shady_layout, &output_layout],
+                push_constant_ranges: &[],
+            });
+
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Shady compute pipeline"),
+                layout: Some(&layout),
+                module: &module,
+                entry_point: Some(COMPUTE_ENTRYPOINT),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+
+        let present = {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shady present shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("present_shader.wgsl").into()),
+            });
+
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Shady present pipeline layout"),
+                bind_group_layouts: &[&present_layout],
+                push_constant_ranges: &[],
+            });
+
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Shady present pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: Some("vertex_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: Some("fragment_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: texture_format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shady present sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let (output_bind_groups, present_bind_groups) =
+            create_bind_groups(device, &output_layout, &present_layout, &sampler, size);
+
+        Self {
+            compute,
+            present,
+            output_layout,
+            present_layout,
+            sampler,
+            size,
+            output_bind_groups,
+            present_bind_groups,
+            current: 0,
+        }
+    }
+
+    /// Recreates the storage textures with the given size. Their content gets lost.
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+
+        (self.output_bind_groups, self.present_bind_groups) = create_bind_groups(
+            device,
+            &self.output_layout,
+            &self.present_layout,
+            &self.sampler,
+            size,
+        );
+        self.size = size;
+        self.current = 0;
+    }
+
+    /// Returns the size of the storage textures.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Runs the compute shader over the current storage texture.
+    pub(crate) fn dispatch(
+        &self,
+        encoder: &mut CommandEncoder,
+        shady_bind_group: &wgpu::BindGroup,
+    ) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Shady compute pass"),
+            timestamp_writes: None,
+        });
+
+        let (width, height) = self.size;
+        compute_pass.set_pipeline(&self.compute);
+        compute_pass.set_bind_group(BIND_GROUP_INDEX, shady_bind_group, &[]);
+        compute_pass.set_bind_group(
+            OUTPUT_BIND_GROUP_INDEX,
+            &self.output_bind_groups[self.current],
+            &[],
+        );
+        compute_pass.dispatch_workgroups(
+            width.div_ceil(WORKGROUP_SIZE.0),
+            height.div_ceil(WORKGROUP_SIZE.1),
+            1,
+        );
+    }
+
+    /// Draws the current storage texture onto `texture_view` and swaps the storage textures.
+    pub(crate) fn present(&mut self, encoder: &mut CommandEncoder, texture_view: &TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shady present pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.present);
+        render_pass.set_bind_group(0, &self.present_bind_groups[self.current], &[]);
+        // a triangle which covers the whole target
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        self.current = 1 - self.current;
+    }
+}
+
+/// Creates a template for a compute shader in wgsl with the resources of `shady` and the bindings of the storage textures.
+///
+/// # Arguments
+/// - `body`: Optional shadercode which should be pasted into the main function. `id` is the global invocation id.
+///
+/// # Example
+/// ```
+/// let template = shady::compute::wgsl_template(None).unwrap();
+/// ```
+pub fn wgsl_template(body: Option<&str>) -> Result<String, fmt::Error> {
+    let mut template = String::new();
+    Resources::write_wgsl_template(&mut template, BIND_GROUP_INDEX)?;
+
+    fmt::Write::write_fmt(
+        &mut template,
+        format_args!(
+            "
+// the texture of the current frame
+@group({group}) @binding({output})
+var output: texture_storage_2d<rgba16float, write>;
+
+// the texture of the previous frame
+@group({group}) @binding({previous})
+var previous: texture_2d<f32>;
+
+@compute @workgroup_size({x}, {y})
+fn {entrypoint}(@builtin(global_invocation_id) id: vec3<u32>) {{
+{body}
+}}
+",
+            group = OUTPUT_BIND_GROUP_INDEX,
+            output = OUTPUT_BINDING,
+            previous = PREVIOUS_BINDING,
+            x = WORKGROUP_SIZE.0,
+            y = WORKGROUP_SIZE.1,
+            entrypoint = COMPUTE_ENTRYPOINT,
+            body = body.unwrap_or(DEFAULT_TEMPLATE_WGSL_BODY),
+        ),
+    )?;
+
+    Ok(template)
+}
+
+fn output_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Shady compute output bind group layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: OUTPUT_BINDING,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: OUTPUT_FORMAT,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: PREVIOUS_BINDING,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+fn present_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Shady present bind group layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Creates the two storage textures and returns the bind groups for the compute shader and the present shader.
+fn create_bind_groups(
+    device: &Device,
+    output_layout: &wgpu::BindGroupLayout,
+    present_layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    (width, height): (u32, u32),
+) -> ([wgpu::BindGroup; 2], [wgpu::BindGroup; 2]) {
+    let views = [0, 1].map(|_| {
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Shady compute storage texture"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: OUTPUT_FORMAT,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    });
+
+    let output_bind_groups = [0, 1].map(|current| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shady compute output bind group"),
+            layout: output_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: OUTPUT_BINDING,
+                    resource: wgpu::BindingResource::TextureView(&views[current]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: PREVIOUS_BINDING,
+                    resource: wgpu::BindingResource::TextureView(&views[1 - current]),
+                },
+            ],
+        })
+    });
+
+    let present_bind_groups = [0, 1].map(|current| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shady present bind group"),
+            layout: present_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&views[current]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    });
+
+    (output_bind_groups, present_bind_groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check if the generated default template is valid
+    #[test]
+    fn valid_wgsl_template() {
+        let template = wgsl_template(None).unwrap();
+
+        let module = match wgpu::naga::front::wgsl::parse_str(&template) {
+            Ok(module) => module,
+            Err(err) => panic!("{}", err.emit_to_string(&template)),
+        };
+
+        let entry_point = &module.entry_points[0];
+        assert_eq!(entry_point.name, COMPUTE_ENTRYPOINT);
+        assert_eq!(
+            entry_point.workgroup_size,
+            [WORKGROUP_SIZE.0, WORKGROUP_SIZE.1, 1]
+        );
+    }
+}
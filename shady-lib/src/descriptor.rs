@@ -1,6 +1,12 @@
+#[cfg(feature = "audio")]
 use shady_audio::SampleProcessor;
 use wgpu::Device;
 
+#[cfg(feature = "time")]
+use crate::clock::Clock;
+#[cfg(feature = "audio")]
+use crate::AudioHandle;
+
 /// Describes [Shady] for [Shady::new]
 ///
 /// [Shady]: crate::Shady
@@ -11,4 +17,17 @@ pub struct ShadyDescriptor<'a> {
 
     #[cfg(feature = "audio")]
     pub sample_processor: &'a SampleProcessor,
+
+    /// The audio state which should be shared with other [Shady] instances.
+    /// Set it to `None` if [Shady] should create its own one.
+    ///
+    /// [Shady]: crate::Shady
+    #[cfg(feature = "audio")]
+    pub audio_handle: Option<AudioHandle>,
+
+    /// The clock which drives `iTime`. Defaults to the wall clock ([crate::clock::RealTimeClock]) if it's `None`.
+    ///
+    /// See [crate::clock] for clocks which are useful for tests.
+    #[cfg(feature = "time")]
+    pub clock: Option<Box<dyn Clock>>,
 }
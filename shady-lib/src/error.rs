@@ -0,0 +1,7 @@
+/// Errors which can occur while creating [crate::Shady].
+#[derive(thiserror::Error, Debug)]
+pub enum ShadyError {
+    /// The device couldn't create the buffers or the bind group of the resources.
+    #[error("Couldn't create the resources of shady: {0}")]
+    Device(#[from] wgpu::Error),
+}
@@ -0,0 +1,245 @@
+//! A compatibility pre-pass for [shadertoy]-flavored glsl code.
+//!
+//! Fragment shaders which are copy-pasted from [shadertoy] (or other GLSL ES 1.0 code) often use
+//! constructs which the glsl frontend doesn't understand. [make_compatible] rewrites the most common ones:
+//!
+//! - `#version` directives are removed.
+//! - `gl_FragColor` is replaced by the `fragColor` output (which gets declared if needed).
+//! - `texture2D` and `textureCube` calls are replaced by `texture`.
+//! - `iGlobalTime` is replaced by `iTime`.
+//! - The declarations of the resources are added if the code doesn't declare any bindings.
+//!   With the `channels` feature, `iChannel0` to `iChannel3` are combined with `iChannelSampler` in this case.
+//! - If the code only has a `mainImage` function, a `main` function which calls it gets added.
+//!
+//! [shadertoy]: https://www.shadertoy.com/
+use crate::{
+    resources::Resources, template::TemplateGenerator, BIND_GROUP_INDEX, FRAGMENT_ENTRYPOINT,
+};
+
+const FRAG_COLOR: &str = "fragColor";
+const FRAG_COLOR_DECLARATION: &str = "
+// the color which the pixel should have
+layout(location = 0) out vec4 fragColor;
+";
+
+const RENAMED_IDENTIFIERS: &[(&str, &str)] =
+    &[("gl_FragColor", FRAG_COLOR), ("iGlobalTime", "iTime")];
+
+// only renamed if they are called, since `texture2D` is a type as well
+const RENAMED_FUNCTIONS: &[(&str, &str)] = &[("texture2D", "texture"), ("textureCube", "texture")];
+
+/// Rewrites [shadertoy]-flavored glsl code so that it can be used with `shady`.
+/// See the [module docs](crate::glsl_compat) for the list of rewrites.
+///
+/// # Example
+/// ```
+/// let shadertoy_code = "
+/// void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+///     fragColor = vec4(fragCoord / iResolution.xy, 0.5 + 0.5 * sin(iTime), 1.0);
+/// }
+/// ";
+///
+/// let code = shady::glsl_compat::make_compatible(shadertoy_code);
+/// assert!(code.contains("void main()"));
+/// ```
+///
+/// [shadertoy]: https://www.shadertoy.com/
+pub fn make_compatible(code: &str) -> String {
+    let mut body = code
+        .split_inclusive('\n')
+        .filter(|line| !line.trim_start().starts_with("#version"))
+        .collect::<String>();
+
+    for (from, to) in RENAMED_IDENTIFIERS {
+        body = replace_identifier(&body, from, to);
+    }
+
+    for (from, to) in RENAMED_FUNCTIONS {
+        body = replace_function(&body, from, to);
+    }
+
+    let mut output = String::new();
+
+    let declares_bindings = body
+        .lines()
+        .any(|line| line.contains("layout") && line.contains("binding"));
+    if !declares_bindings {
+        Resources::write_glsl_template(&mut output, BIND_GROUP_INDEX)
+            .expect("Write resources into string");
+
+        // shadertoy's channels are combined samplers
+        #[cfg(feature = "channels")]
+        for channel in 0..crate::AMOUNT_CHANNELS {
+            let name = format!("iChannel{}", channel);
+            body = replace_identifier(
+                &body,
+                &name,
+                &format!("sampler2D({}, iChannelSampler)", name),
+            );
+        }
+    }
+
+    let declares_output = body
+        .lines()
+        .any(|line| line.contains("layout") && line.contains(" out "));
+    if !declares_output {
+        output.push_str(FRAG_COLOR_DECLARATION);
+    }
+
+    output.push_str(&body);
+
+    if contains_identifier(&body, "mainImage") && !contains_identifier(&body, FRAGMENT_ENTRYPOINT) {
+        output.push_str(&format!(
+            "\n\nvoid {}() {{\n    mainImage({}, gl_FragCoord.xy);\n}}\n",
+            FRAGMENT_ENTRYPOINT, FRAG_COLOR
+        ));
+    }
+
+    output
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns the byte offsets of each occurence of `identifier` as a whole word in `code`.
+fn identifier_positions<'a>(
+    code: &'a str,
+    identifier: &'a str,
+) -> impl Iterator<Item = usize> + 'a {
+    code.match_indices(identifier).filter_map(move |(idx, _)| {
+        let before = code[..idx].chars().next_back();
+        let after = code[idx + identifier.len()..].chars().next();
+
+        let is_whole_word =
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char);
+
+        is_whole_word.then_some(idx)
+    })
+}
+
+fn contains_identifier(code: &str, identifier: &str) -> bool {
+    identifier_positions(code, identifier).next().is_some()
+}
+
+fn replace_identifier(code: &str, from: &str, to: &str) -> String {
+    replace_positions(code, identifier_positions(code, from), from, to)
+}
+
+/// Replaces the calls of the function `from` with `to`.
+fn replace_function(code: &str, from: &str, to: &str) -> String {
+    let calls = identifier_positions(code, from)
+        .filter(|idx| code[idx + from.len()..].trim_start().starts_with('('));
+
+    replace_positions(code, calls, from, to)
+}
+
+fn replace_positions(
+    code: &str,
+    positions: impl Iterator<Item = usize>,
+    from: &str,
+    to: &str,
+) -> String {
+    let mut output = String::with_capacity(code.len());
+    let mut last_end = 0;
+
+    for idx in positions {
+        output.push_str(&code[last_end..idx]);
+        output.push_str(to);
+        last_end = idx + from.len();
+    }
+
+    output.push_str(&code[last_end..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::naga::{front::glsl::Options, ShaderStage};
+
+    use super::*;
+
+    fn assert_valid(code: &str) {
+        let mut parser = wgpu::naga::front::glsl::Frontend::default();
+        if let Err(err) = parser.parse(&Options::from(ShaderStage::Fragment), code) {
+            panic!("{}", err.emit_to_string(code));
+        }
+    }
+
+    #[test]
+    fn replaces_whole_identifiers_only() {
+        assert_eq!(
+            replace_identifier(
+                "texture2D(a); my_texture2D(b); texture2Dx",
+                "texture2D",
+                "texture"
+            ),
+            "texture(a); my_texture2D(b); texture2Dx"
+        );
+    }
+
+    #[test]
+    fn replaces_function_calls_only() {
+        assert_eq!(
+            replace_function(
+                "texture2D (a); uniform texture2D b;",
+                "texture2D",
+                "texture"
+            ),
+            "texture (a); uniform texture2D b;"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channels")]
+    fn shadertoy_channels() {
+        let code = make_compatible(
+            "
+            void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+                vec2 uv = fragCoord / iChannelResolution[1].xy;
+                fragColor = texture2D(iChannel0, uv) + texelFetch(iChannel1, ivec2(0), 0);
+            }
+            ",
+        );
+
+        assert!(code.contains("texture(sampler2D(iChannel0, iChannelSampler), uv)"));
+        assert_valid(&code);
+    }
+
+    #[test]
+    fn shadertoy_main_image() {
+        let code = make_compatible(
+            "
+            void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+                vec2 uv = fragCoord / iResolution.xy;
+                fragColor = vec4(uv, 0.5 + 0.5 * sin(iGlobalTime), 1.0);
+            }
+            ",
+        );
+
+        assert_valid(&code);
+    }
+
+    #[test]
+    fn glsl_es_1() {
+        let code = make_compatible(
+            "
+            #version 100
+            precision mediump float;
+
+            void main() {
+                gl_FragColor = vec4(gl_FragCoord.xy / iResolution.xy, 0.0, 1.0);
+            }
+            ",
+        );
+
+        assert_valid(&code);
+    }
+
+    #[test]
+    fn keeps_shady_code_untouched() {
+        let template = crate::TemplateLang::Glsl.generate_to_string(None).unwrap();
+
+        assert_eq!(make_compatible(&template), template);
+    }
+}
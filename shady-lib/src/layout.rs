@@ -0,0 +1,161 @@
+//! A description of the resources which are enabled by the features of the crate, see [crate::Shady::resource_layout].
+//!
+//! Useful to generate documentation of the resources or to check hand-written shaders against the actual layout.
+use crate::{resources::Resources, template::TemplateGenerator, ResourceBinding};
+
+/// Describes a resource of `shady` within its bind group.
+#[derive(Debug, Clone)]
+pub struct ResourceInfo {
+    pub resource: ResourceBinding,
+
+    /// The name of the resource within the shaders, like `iTime`.
+    pub name: &'static str,
+
+    /// The binding index within the bind group.
+    pub binding: u32,
+
+    /// The type of the binding, like a uniform buffer or a texture.
+    pub binding_type: wgpu::BindingType,
+
+    /// The declaration of the resource within a wgsl shader (the same as within the template).
+    pub wgsl_declaration: String,
+
+    /// The declaration of the resource within a glsl shader (the same as within the template).
+    pub glsl_declaration: String,
+}
+
+/// The resources of `shady` within a bind group.
+///
+/// # Example
+/// ```rust
+/// use shady::Shady;
+///
+/// for resource in Shady::resource_layout().iter() {
+///     println!("{} (binding {}):\n{}", resource.name, resource.binding, resource.wgsl_declaration);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResourceLayout {
+    bind_group_index: u32,
+    resources: Vec<ResourceInfo>,
+}
+
+impl ResourceLayout {
+    /// Describes the resources if they are placed into the bind group with the given index
+    /// (see [crate::RenderPipelineOptions::bind_group_index]).
+    pub fn with_bind_group_index(bind_group_index: u32) -> Self {
+        let mut wgsl = String::new();
+        let mut glsl = String::new();
+        Resources::write_wgsl_template(&mut wgsl, bind_group_index).expect("Write wgsl template");
+        Resources::write_glsl_template(&mut glsl, bind_group_index).expect("Write glsl template");
+
+        let resources = ResourceBinding::ALL
+            .iter()
+            .map(|&resource| {
+                let binding = resource.default_binding();
+
+                ResourceInfo {
+                    resource,
+                    name: resource.name(),
+                    binding,
+                    binding_type: resource.layout_entry(binding).ty,
+                    wgsl_declaration: declaration(&wgsl, &format!("@binding({})", binding)),
+                    glsl_declaration: declaration(&glsl, &format!("binding = {})", binding)),
+                }
+            })
+            .collect();
+
+        Self {
+            bind_group_index,
+            resources,
+        }
+    }
+
+    pub fn bind_group_index(&self) -> u32 {
+        self.bind_group_index
+    }
+
+    /// Iterates over the resources in the order of their bindings.
+    pub fn iter(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.resources.iter()
+    }
+
+    /// Returns the resource with the given name (like `iTime`).
+    pub fn get(&self, name: &str) -> Option<&ResourceInfo> {
+        self.resources.iter().find(|resource| resource.name == name)
+    }
+}
+
+impl<'a> IntoIterator for &'a ResourceLayout {
+    type Item = &'a ResourceInfo;
+    type IntoIter = std::slice::Iter<'a, ResourceInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.resources.iter()
+    }
+}
+
+/// Returns the declaration within the template which starts at the line with `marker`.
+///
+/// The declaration ends with the first `;` which isn't within a block (like the ones of glsl buffers).
+// `unused`: Without any resource feature, there's no declaration.
+#[allow(unused)]
+fn declaration(template: &str, marker: &str) -> String {
+    let mut lines = Vec::new();
+    let mut depth = 0i32;
+
+    for line in template.lines().skip_while(|line| !line.contains(marker)) {
+        lines.push(line);
+
+        depth += line.matches('{').count() as i32;
+        depth -= line.matches('}').count() as i32;
+        if depth <= 0 && line.trim_end().ends_with(';') {
+            break;
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_resource_has_its_declaration() {
+        let layout = ResourceLayout::with_bind_group_index(2);
+
+        assert_eq!(layout.iter().count(), ResourceBinding::ALL.len());
+        for resource in layout.iter() {
+            for declaration in [&resource.wgsl_declaration, &resource.glsl_declaration] {
+                assert!(
+                    declaration.contains(resource.name),
+                    "{}: {}",
+                    resource.name,
+                    declaration
+                );
+                assert!(declaration.ends_with(';'), "{}", declaration);
+            }
+
+            assert!(resource
+                .wgsl_declaration
+                .starts_with(&format!("@group(2) @binding({})", resource.binding)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "state")]
+    fn glsl_blocks_are_complete() {
+        let layout = ResourceLayout::with_bind_group_index(0);
+        let state = layout.get("iState").unwrap();
+
+        assert_eq!(state.glsl_declaration.lines().count(), 3);
+        assert!(matches!(
+            state.binding_type,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                ..
+            }
+        ));
+    }
+}
@@ -2,19 +2,45 @@
 //! It provides functions to setup the following uniform buffers (which will be also called `Resources` within this doc):
 //!
 //! - `iAudio`: Contains frequency bars of an audio source.
+//! - `iAudioSlow`: The bars of `iAudio` averaged over a few seconds (disabled by default, enable the `slow-audio` feature).
+//! - `iAudioPeaks`: The peaks of the bars of `iAudio` which are kept for a moment and fall down slowly afterwards, to draw
+//!   floating peak caps (disabled by default, enable the `audio-peaks` feature).
+//! - `iAudioWidths`: The relative width of each bar of `iAudio` according to the octaves it spans, to draw the bars on a logarithmic
+//!   frequency axis (disabled by default, enable the `audio-widths` feature).
+//! - `iAudioTexture` and `iAudioSampler`: The bars as a 1D texture, so sampling it interpolates between the bars
+//!   by the hardware (disabled by default, enable the `audio-texture` feature). The bars are uploaded into it instead of `iAudio`
+//!   after [Shady::set_audio_representation] with [AudioRepresentation::Texture].
 //! - `iFrame`: Contains the current frame count.
 //! - `iMouse`: Contains the coordinate points of the user's mouse.
+//! - `iMouseMotion`: Contains the scroll distance of the mouse wheel and the distance the mouse has been dragged.
 //! - `iResolution`: Contains the height and width of the surface which will be drawed on.
 //! - `iTime`: The playback time of the shader.
+//! - `iTimeline`: The values of the tracks of a [timeline::Timeline] (disabled by default, enable the `timeline` feature).
+//! - `iIdle`: Ramps from `0` to `1` while the audio is silent (disabled by default, enable the `idle` feature).
+//! - `iChannel0` to `iChannel3`: Textures which you can set with [Shady::set_channel] (disabled by default, enable the `channels` feature).
+//! - `iTrackTime`, `iTrackDuration` and `iPlaying`: The state of the track of a music player which you can set with [Shady::set_track]
+//!   (disabled by default, enable the `track` feature).
+//! - `iState`: Values which persist across frames and which your shader can write to, for example for feedback effects
+//!   or particles (disabled by default, enable the `state` feature).
+//! - `iGamepadAxes` and `iGamepadButtons`: The sticks and buttons of a gamepad which you can set with [Shady::set_gamepad_stick]
+//!   and [Shady::set_gamepad_button] (disabled by default, enable the `gamepad` feature).
+//! - `iParams`: Custom values which your application computes each frame and sets with [Shady::set_params]
+//!   (disabled by default, enable the `params` feature).
 //!
 //! **Note:**
 //! - You should be familiar with [wgpu] code in order to be able to use this.
 //! - `shady` is not compatible with [shadertoy]'s shaders so you can't simply copy+paste the fragment code from [shadertoy] to
 //!   application which are using `shady` (but porting them should be very easy in general).
 //!
+//! Besides fragment shaders, the resources can be used in compute shaders as well (see [compute]).
+//! If you switch between a few shaders regularly, [shader_cache] keeps their pipelines around.
+//!
 //! # Feature flags
 //! Each resource is behind a feature gate so if you don't want to use some of them, just disable their feature gate.
 //!
+//! The `offscreen` feature (disabled by default) adds the `offscreen` module to render shaders without a window,
+//! for example to write golden-image tests for your shaders.
+//!
 //! # Example
 //! An "mini" example can be seen here: <https://github.com/TornaxO7/shady/blob/main/shady-lib/examples/mini-simple.rs>
 //! I tried to make it as small as possible.
@@ -23,23 +49,62 @@
 //!
 //! [shadertoy]: https://www.shadertoy.com/
 //! [wgpu]: https://crates.io/crates/wgpu
+#[cfg(feature = "time")]
+pub mod clock;
+pub mod compute;
 mod descriptor;
+mod error;
+pub mod glsl_compat;
+mod layout;
+mod multisample;
+#[cfg(feature = "offscreen")]
+pub mod offscreen;
+mod pipeline;
 mod resources;
+pub mod shader_cache;
+mod stats;
 mod template;
+#[cfg(feature = "timeline")]
+pub mod timeline;
 mod vertices;
+mod viewport;
+
+use std::{cell::RefCell, time::Instant};
 
-use resources::{Resource, Resources};
+use compute::ShadyComputePipeline;
+use multisample::MultisampleTarget;
+use resources::{
+    BindGroups, Resource, Resources, COMPUTE_RESOURCE_VISIBILITY, RESOURCE_VISIBILITY,
+    VERTEX_RESOURCE_VISIBILITY,
+};
+use stats::{GpuTimer, StatsRecorder};
 use tracing::instrument;
 use wgpu::{CommandEncoder, Device, ShaderSource, TextureView};
 
 pub use descriptor::ShadyDescriptor;
+pub use error::ShadyError;
+pub use layout::{ResourceInfo, ResourceLayout};
+pub use pipeline::{BlendMode, ColorSpace, RenderPipelineOptions, VertexShader};
+pub use stats::FrameStats;
 
+#[cfg(feature = "audio-texture")]
+pub use resources::AudioRepresentation;
+#[cfg(feature = "audio")]
+pub use resources::{amount_bars_for_width, default_sample_processor, AudioHandle};
 #[cfg(feature = "audio")]
 pub use shady_audio;
 
 #[cfg(feature = "mouse")]
 pub use resources::MouseState;
+pub use resources::ResourceBinding;
+#[cfg(feature = "channels")]
+pub use resources::AMOUNT_CHANNELS;
+#[cfg(feature = "state")]
+pub use resources::DEFAULT_STATE_SIZE;
+#[cfg(feature = "gamepad")]
+pub use resources::{GamepadButton, GamepadStick, AMOUNT_GAMEPAD_BUTTONS};
 pub use template::TemplateLang;
+pub use viewport::Viewport;
 
 /// The name of the entrypoint function of the fragment shader for `shady`.
 pub const FRAGMENT_ENTRYPOINT: &str = "main";
@@ -49,7 +114,15 @@ const VBUFFER_INDEX: u32 = 0;
 
 /// A wrapper around [wgpu::RenderPipeline].
 #[derive(Debug, Clone)]
-pub struct ShadyRenderPipeline(wgpu::RenderPipeline);
+pub struct ShadyRenderPipeline {
+    pipeline: wgpu::RenderPipeline,
+    sample_count: u32,
+    bind_group_index: u32,
+    // the shader stages which can access the resources
+    visibility: wgpu::ShaderStages,
+    // the amount of vertices and instances of a custom vertex shader
+    vertices: Option<(u32, u32)>,
+}
 
 impl AsRef<ShadyRenderPipeline> for ShadyRenderPipeline {
     fn as_ref(&self) -> &Self {
@@ -66,7 +139,17 @@ impl AsRef<ShadyRenderPipeline> for ShadyRenderPipeline {
 /// [mini-simple.rs]: https://github.com/TornaxO7/shady/blob/main/shady-lib/examples/mini-simple.rs
 pub struct Shady {
     resources: Resources,
-    bind_group: wgpu::BindGroup,
+    bind_groups: BindGroups,
+    multisample: Option<MultisampleTarget>,
+    stats: RefCell<StatsRecorder>,
+    blend_constant: f64,
+    // `None` keeps the content of the render target
+    clear_color: Option<wgpu::Color>,
+    // `false` while the render target isn't visible (see `Shady::set_active`)
+    active: bool,
+    // the pause state of the user, which is restored when `shady` becomes active again
+    #[cfg(feature = "time")]
+    time_paused: bool,
 
     vbuffer: wgpu::Buffer,
     ibuffer: wgpu::Buffer,
@@ -75,49 +158,472 @@ pub struct Shady {
 // General functions
 impl Shady {
     /// Create a new instance of `Shady`.
+    ///
+    /// Returns an error if the device couldn't create the buffers of the resources.
     #[instrument(level = "trace", skip_all)]
-    pub fn new<'a>(desc: ShadyDescriptor) -> Self {
+    pub fn new<'a>(desc: ShadyDescriptor) -> Result<Self, ShadyError> {
+        // `unused_mut`: Only needed if the `time` feature is enabled.
+        #[allow(unused_mut)]
+        let mut desc = desc;
+        #[cfg(feature = "time")]
+        let clock = desc.clock.take();
+
         let ShadyDescriptor { device, .. } = &desc;
 
-        let resources = Resources::new(&desc);
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        // `unused_mut`: Only needed if the `time` feature is enabled.
+        #[allow(unused_mut)]
+        let mut resources = Resources::new(&desc);
+        #[cfg(feature = "time")]
+        if let Some(clock) = clock {
+            resources.time.set_clock(clock);
+        }
 
-        let bind_group = resources.bind_group(device);
+        let bind_groups = resources.bind_groups(device);
+        let vbuffer = vertices::vertex_buffer(device);
+        let ibuffer = vertices::index_buffer(device);
 
-        Self {
-            resources,
-            bind_group,
-            vbuffer: vertices::vertex_buffer(device),
-            ibuffer: vertices::index_buffer(device),
+        let validation_error = pollster::block_on(device.pop_error_scope());
+        let out_of_memory_error = pollster::block_on(device.pop_error_scope());
+        if let Some(err) = validation_error.or(out_of_memory_error) {
+            return Err(err.into());
         }
+
+        Ok(Self {
+            resources,
+            bind_groups,
+            multisample: None,
+            stats: RefCell::default(),
+            blend_constant: 1.,
+            clear_color: Some(wgpu::Color::TRANSPARENT),
+            active: true,
+            #[cfg(feature = "time")]
+            time_paused: false,
+            vbuffer,
+            ibuffer,
+        })
     }
 
     /// Add a render pass to the given `encoder` and `texture_view`.
+    ///
+    /// If multisampling is enabled (see [Shady::set_multisample]), the frame is rendered into an internal
+    /// multisampled texture and resolved into `texture_view`.
+    ///
+    /// Use [Shady::draw] if your pipelines need your own bind groups.
+    ///
+    /// Each call finishes the current frame of [Shady::frame_stats]. If GPU timing is enabled,
+    /// submit `encoder` before calling this function again.
+    #[instrument(level = "trace", skip_all)]
     pub fn add_render_pass(
         &self,
         encoder: &mut CommandEncoder,
         texture_view: &TextureView,
         pipelines: impl IntoIterator<Item = impl AsRef<ShadyRenderPipeline>>,
     ) {
+        self.render_pass(encoder, texture_view, None, |render_pass| {
+            self.draw(render_pass, pipelines)
+        });
+    }
+
+    /// Like [Shady::add_render_pass] but attaches your depth (and stencil) texture, so the pipelines can be
+    /// tested against the depth of the geometry of your scene. The pipelines need a matching
+    /// [RenderPipelineOptions::depth_stencil] state.
+    ///
+    /// If multisampling is enabled (see [Shady::set_multisample]), the depth texture needs the same sample count.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // the pipeline has been created with `depth_stencil: Some(&depth_stencil_state)`
+    /// shady.add_render_pass_with_depth(
+    ///     &mut encoder,
+    ///     &view,
+    ///     wgpu::RenderPassDepthStencilAttachment {
+    ///         view: &depth_view,
+    ///         // keep the depth of the scene
+    ///         depth_ops: Some(wgpu::Operations {
+    ///             load: wgpu::LoadOp::Load,
+    ///             store: wgpu::StoreOp::Store,
+    ///         }),
+    ///         stencil_ops: None,
+    ///     },
+    ///     [&pipeline],
+    /// );
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub fn add_render_pass_with_depth(
+        &self,
+        encoder: &mut CommandEncoder,
+        texture_view: &TextureView,
+        depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment<'_>,
+        pipelines: impl IntoIterator<Item = impl AsRef<ShadyRenderPipeline>>,
+    ) {
+        self.render_pass(
+            encoder,
+            texture_view,
+            Some(depth_stencil_attachment),
+            |render_pass| self.draw(render_pass, pipelines),
+        );
+    }
+
+    /// Like [Shady::add_render_pass] but draws the fullscreen quad of each pipeline `instances` times,
+    /// for example to blend a few layers of the same shader or to render both eyes of a stereo image.
+    ///
+    /// The fragment shader gets the index of the instance (`0..instances`) as a flat input at location `0`:
+    /// `@location(0) @interpolate(flat) instance: u32` in wgsl and `layout(location = 0) flat in uint instance;` in glsl.
+    /// The layers are combined with the [BlendMode] of the pipeline.
+    /// Pipelines with their own [VertexShader] keep their [VertexShader::instance_count].
+    ///
+    /// # Example
+    /// ```ignore
+    /// // the fragment shader:
+    /// //
+    /// // @fragment
+    /// // fn main(@builtin(position) pos: vec4<f32>, @location(0) @interpolate(flat) instance: u32) -> @location(0) vec4<f32> {
+    /// //     // the left half shows the left eye, the right half the right eye
+    /// //     let is_left = pos.x < iResolution.x / 2.0;
+    /// //     if (instance == 0u) != is_left {
+    /// //         discard;
+    /// //     }
+    /// //     ...
+    /// // }
+    /// shady.add_render_pass_instanced(&mut encoder, &view, [&pipeline], 2);
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub fn add_render_pass_instanced(
+        &self,
+        encoder: &mut CommandEncoder,
+        texture_view: &TextureView,
+        pipelines: impl IntoIterator<Item = impl AsRef<ShadyRenderPipeline>>,
+        instances: u32,
+    ) {
+        self.render_pass(encoder, texture_view, None, |render_pass| {
+            self.draw_instances(render_pass, pipelines, instances)
+        });
+    }
+
+    /// Like [Shady::add_render_pass] but draws the pipeline into each of the given viewports of `texture_view`
+    /// (for example the cells of [Viewport::grid]) within one render pass.
+    ///
+    /// `target_size` is the size of `texture_view`, viewports outside of it are clipped.
+    /// `set_bind_groups` is called with the index of each viewport before the pipeline is drawn into it:
+    /// Set your own bind groups there to give each viewport different values (for example to compare
+    /// the parameters of a shader side by side).
+    ///
+    /// Note: The resources are the same for each viewport and `@builtin(position)` (`gl_FragCoord`) refers to
+    /// the whole texture, so pass the offset and size of the viewport in your own bind group if the shader needs them.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let cells = Viewport::grid((width, height), NonZero::new(3).unwrap(), NonZero::new(3).unwrap());
+    ///
+    /// // the pipeline has been created with `bind_group_layouts: &[&cell_layout]` and `bind_group_index: 1`
+    /// shady.add_viewports_render_pass(&mut encoder, &view, (width, height), &cells, &pipeline, |idx, render_pass| {
+    ///     render_pass.set_bind_group(0, &cell_bind_groups[idx], &[]);
+    /// });
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub fn add_viewports_render_pass(
+        &self,
+        encoder: &mut CommandEncoder,
+        texture_view: &TextureView,
+        target_size: (u32, u32),
+        viewports: &[Viewport],
+        pipeline: impl AsRef<ShadyRenderPipeline>,
+        mut set_bind_groups: impl FnMut(usize, &mut wgpu::RenderPass<'_>),
+    ) {
+        let pipeline = pipeline.as_ref();
+
+        self.render_pass(encoder, texture_view, None, |render_pass| {
+            for (idx, viewport) in viewports.iter().enumerate() {
+                let Some(viewport) = viewport.clamp(target_size) else {
+                    continue;
+                };
+
+                render_pass.set_viewport(
+                    viewport.x as f32,
+                    viewport.y as f32,
+                    viewport.width as f32,
+                    viewport.height as f32,
+                    0.,
+                    1.,
+                );
+                render_pass.set_scissor_rect(
+                    viewport.x,
+                    viewport.y,
+                    viewport.width,
+                    viewport.height,
+                );
+
+                set_bind_groups(idx, render_pass);
+                self.draw(render_pass, [pipeline]);
+            }
+        });
+    }
+
+    /// Runs the compute shader of `pipeline` and draws its output onto `texture_view`.
+    ///
+    /// The output is drawn without multisampling (see [Shady::set_multisample]) and the duration
+    /// of the passes isn't measured on the GPU ([FrameStats::gpu_render_pass]).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut pipeline = ShadyComputePipeline::new(&device, shader_source, surface_format, (width, height));
+    ///
+    /// shady.add_compute_pass(&mut encoder, &view, &mut pipeline);
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub fn add_compute_pass(
+        &self,
+        encoder: &mut CommandEncoder,
+        texture_view: &TextureView,
+        pipeline: &mut ShadyComputePipeline,
+    ) {
+        let start = Instant::now();
+
+        pipeline.dispatch(encoder, self.bind_groups.get(COMPUTE_RESOURCE_VISIBILITY));
+        pipeline.present(encoder, texture_view);
+
+        self.stats.borrow_mut().finish_frame(start.elapsed());
+    }
+
+    /// Begins a render pass on `texture_view` (or the multisample target) and records its stats.
+    fn render_pass(
+        &self,
+        encoder: &mut CommandEncoder,
+        texture_view: &TextureView,
+        depth_stencil_attachment: Option<wgpu::RenderPassDepthStencilAttachment<'_>>,
+        draw: impl FnOnce(&mut wgpu::RenderPass<'_>),
+    ) {
+        let load = match self.clear_color {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+
+        let (view, resolve_target, store) = match &self.multisample {
+            // the multisampled values aren't needed anymore after resolving them (unless they are loaded again)
+            Some(target) if self.clear_color.is_some() => {
+                (target.view(), Some(texture_view), wgpu::StoreOp::Discard)
+            }
+            Some(target) => (target.view(), Some(texture_view), wgpu::StoreOp::Store),
+            None => (texture_view, None, wgpu::StoreOp::Store),
+        };
+
+        let start = Instant::now();
+        let mut stats = self.stats.borrow_mut();
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: texture_view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                    store: wgpu::StoreOp::Store,
-                },
+                view,
+                resolve_target,
+                ops: wgpu::Operations { load, store },
             })],
+            depth_stencil_attachment,
+            timestamp_writes: stats.timestamp_writes(),
             ..Default::default()
         });
 
-        render_pass.set_bind_group(BIND_GROUP_INDEX, &self.bind_group, &[]);
+        draw(&mut render_pass);
+        drop(render_pass);
+
+        stats.resolve_timestamps(encoder);
+        stats.finish_frame(start.elapsed());
+    }
+
+    /// Returns the timings of the last frame (the last call of [Shady::add_render_pass]).
+    ///
+    /// Useful to find out where the time goes if a shader is slow. Enable [Shady::enable_gpu_timing]
+    /// to measure the time which the GPU needs for the shader as well.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.stats.borrow().stats()
+    }
+
+    /// Measures the time which the GPU needs to execute the render pass of [Shady::add_render_pass]
+    /// (see [FrameStats::gpu_render_pass]).
+    ///
+    /// Returns `false` if the device doesn't support [wgpu::Features::TIMESTAMP_QUERY]
+    /// (it needs to be requested while creating the device).
+    pub fn enable_gpu_timing(&mut self, device: &Device, queue: &wgpu::Queue) -> bool {
+        let gpu_timer = GpuTimer::new(device, queue);
+        let is_supported = gpu_timer.is_some();
+
+        self.stats.get_mut().set_gpu_timer(gpu_timer);
+        is_supported
+    }
+
+    /// Stops measuring the GPU time of the render pass.
+    pub fn disable_gpu_timing(&mut self) {
+        self.stats.get_mut().set_gpu_timer(None);
+    }
+
+    /// Sets how much of the output of pipelines with [BlendMode::Constant] is mixed into the render target
+    /// within the range `[0, 1]` (`1` by default).
+    ///
+    /// # Example
+    /// ```ignore
+    /// // halfway between the old and the new shader (`new_pipeline` uses `BlendMode::Constant`)
+    /// shady.set_blend_constant(0.5);
+    /// shady.add_render_pass(&mut encoder, &view, [&old_pipeline, &new_pipeline]);
+    /// ```
+    pub fn set_blend_constant(&mut self, blend_constant: f64) {
+        self.blend_constant = blend_constant.clamp(0., 1.);
+    }
+
+    /// Sets the color which the render target is cleared with at the start of each render pass
+    /// ([wgpu::Color::TRANSPARENT] by default).
+    ///
+    /// `None` keeps the content of the render target, so the output of the pipelines is blended
+    /// onto it (see [BlendMode]). Useful to composite the shader into an overlay or onto your own scene.
+    /// With multisampling (see [Shady::set_multisample]), the content of the internal multisampled
+    /// texture is kept instead, which is the previous frame of `shady`.
+    ///
+    /// Note: The alpha channel only shows up if the surface supports a transparent
+    /// [wgpu::CompositeAlphaMode] (for example `PreMultiplied`).
+    ///
+    /// # Example
+    /// ```ignore
+    /// // a black background for the first pass
+    /// shady.set_clear_color(Some(wgpu::Color::BLACK));
+    /// shady.add_render_pass(&mut encoder, &view, [&background]);
+    ///
+    /// // draw the second pass above the first one
+    /// shady.set_clear_color(None);
+    /// shady.add_render_pass(&mut encoder, &view, [&foreground]);
+    /// ```
+    pub fn set_clear_color(&mut self, clear_color: Option<wgpu::Color>) {
+        self.clear_color = clear_color;
+    }
+
+    /// Suspends (`false`) or resumes (`true`) `shady`, for example while the window is minimized or occluded.
+    ///
+    /// While `shady` is suspended, the playback time stops and [Shady::update_audio_buffer] doesn't process
+    /// the audio anymore. The playback time continues where it stopped once `shady` is active again
+    /// (unless it has been paused with [Shady::set_time_paused]).
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+
+        #[cfg(feature = "time")]
+        self.resources
+            .time
+            .set_paused(self.time_paused || !self.active);
+    }
+
+    /// Returns `false` if `shady` has been suspended with [Shady::set_active].
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Describes the resources which are enabled by the features of the crate: Their names, bindings, binding types
+    /// and their declarations within wgsl and glsl shaders.
+    ///
+    /// The bindings are the ones within the bind group of `shady`, use [ResourceLayout::with_bind_group_index]
+    /// if you've set [RenderPipelineOptions::bind_group_index].
+    pub fn resource_layout() -> ResourceLayout {
+        ResourceLayout::with_bind_group_index(BIND_GROUP_INDEX)
+    }
+
+    /// Returns the layout entries of the given resources at your own binding indices, so you can place only the resources
+    /// you need within your own bind group layout instead of using the bind group of `shady`.
+    ///
+    /// The resources are only visible to fragment shaders, see [ResourceBinding::layout_entry_with_visibility]
+    /// if your vertex shader uses them as well.
+    ///
+    /// Declare the resources in your shader yourself: The templates (see [TemplateLang]) show their types but
+    /// they use the binding indices of `shady` (see [ResourceBinding::default_binding]).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let bindings = [(ResourceBinding::Audio, 3), (ResourceBinding::Time, 4)];
+    ///
+    /// let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    ///     label: Some("My layout"),
+    ///     entries: &[my_entries.as_slice(), &Shady::bind_group_layout_entries(&bindings)].concat(),
+    /// });
+    ///
+    /// let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    ///     label: Some("My bind group"),
+    ///     layout: &layout,
+    ///     entries: &[my_bind_group_entries.as_slice(), &shady.bind_group_entries(&bindings)].concat(),
+    /// });
+    /// ```
+    pub fn bind_group_layout_entries(
+        bindings: &[(ResourceBinding, u32)],
+    ) -> Vec<wgpu::BindGroupLayoutEntry> {
+        Resources::layout_entries(bindings)
+    }
+
+    /// Returns the bind group entries of the given resources at your own binding indices
+    /// (see [Shady::bind_group_layout_entries]). The buffers are still updated by the `update_*_buffer` functions.
+    ///
+    /// Some functions replace the buffers or textures (for example [Shady::set_audio_bars] or [Shady::set_channel]),
+    /// so create your bind group again after calling them.
+    pub fn bind_group_entries(
+        &self,
+        bindings: &[(ResourceBinding, u32)],
+    ) -> Vec<wgpu::BindGroupEntry<'_>> {
+        self.resources.bind_group_entries(bindings)
+    }
+
+    /// Draws the given pipelines into an existing render pass.
+    ///
+    /// This is useful if your pipelines have their own bind groups (see [RenderPipelineOptions::bind_group_layouts]):
+    /// Set them on the render pass before calling this function. The bind group of `shady` is set at the
+    /// [RenderPipelineOptions::bind_group_index] of each pipeline.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut render_pass = encoder.begin_render_pass(...);
+    ///
+    /// // the pipeline has been created with `bind_group_layouts: &[&my_layout]` and `bind_group_index: 1`
+    /// render_pass.set_bind_group(0, &my_bind_group, &[]);
+    /// shady.draw(&mut render_pass, [&pipeline]);
+    /// ```
+    pub fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        pipelines: impl IntoIterator<Item = impl AsRef<ShadyRenderPipeline>>,
+    ) {
+        self.draw_instances(render_pass, pipelines, 1);
+    }
+
+    /// Like [Shady::draw] but draws the fullscreen quad `instances` times (see [Shady::add_render_pass_instanced]).
+    pub fn draw_instances(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        pipelines: impl IntoIterator<Item = impl AsRef<ShadyRenderPipeline>>,
+        instances: u32,
+    ) {
         render_pass.set_vertex_buffer(VBUFFER_INDEX, self.vbuffer.slice(..));
         render_pass.set_index_buffer(self.ibuffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_blend_constant(wgpu::Color {
+            r: self.blend_constant,
+            g: self.blend_constant,
+            b: self.blend_constant,
+            a: self.blend_constant,
+        });
 
         for pipeline in pipelines.into_iter() {
-            render_pass.set_pipeline(&pipeline.as_ref().0);
-            render_pass.draw_indexed(vertices::index_buffer_range(), 0, 0..1);
+            let pipeline = pipeline.as_ref();
+            debug_assert_eq!(
+                pipeline.sample_count,
+                self.sample_count(),
+                "The sample count of the pipeline must match the one of `Shady`."
+            );
+
+            render_pass.set_bind_group(
+                pipeline.bind_group_index,
+                self.bind_groups.get(pipeline.visibility),
+                &[],
+            );
+            render_pass.set_pipeline(&pipeline.pipeline);
+            match pipeline.vertices {
+                Some((vertex_count, instance_count)) => {
+                    render_pass.draw(0..vertex_count, 0..instance_count)
+                }
+                None => render_pass.draw_indexed(vertices::index_buffer_range(), 0, 0..instances),
+            }
         }
     }
 }
@@ -149,6 +655,34 @@ impl Shady {
         self.resources.resolution.set(width, height);
     }
 
+    /// Enables multisampling (MSAA) with `sample_count` samples per pixel.
+    /// A `sample_count` of `1` disables it.
+    ///
+    /// `format` and `(width, height)` must match the texture view which is passed to [Shady::add_render_pass],
+    /// so call it again if your surface gets resized.
+    /// Your pipelines must be created with the same sample count (see [RenderPipelineOptions::sample_count]).
+    ///
+    /// The supported sample counts depend on the adapter and the format,
+    /// see [wgpu::TextureFormatFeatureFlags::sample_count_supported].
+    pub fn set_multisample(
+        &mut self,
+        device: &Device,
+        sample_count: u32,
+        format: wgpu::TextureFormat,
+        (width, height): (u32, u32),
+    ) {
+        self.multisample = (sample_count > 1)
+            .then(|| MultisampleTarget::new(device, sample_count, format, (width, height)));
+    }
+
+    /// Returns the amount of samples per pixel which are used for rendering.
+    pub fn sample_count(&self) -> u32 {
+        self.multisample
+            .as_ref()
+            .map(MultisampleTarget::sample_count)
+            .unwrap_or(1)
+    }
+
     /// Set the mouse state.
     ///
     /// # Affected uniform buffer
@@ -169,6 +703,45 @@ impl Shady {
         self.resources.mouse.set_pos(x, y);
     }
 
+    /// Returns the current position of the mouse (see [Shady::set_mouse_pos]).
+    #[inline]
+    #[cfg(feature = "mouse")]
+    pub fn mouse_pos(&self) -> (f32, f32) {
+        self.resources.mouse.pos()
+    }
+
+    /// Adds the given scroll distance of the mouse wheel.
+    ///
+    /// # Affected uniform buffer
+    /// `iMouseMotion`
+    #[inline]
+    #[cfg(feature = "mouse")]
+    pub fn scroll_mouse(&mut self, delta_x: f32, delta_y: f32) {
+        self.resources.mouse.scroll(delta_x, delta_y);
+    }
+
+    /// Set the accumulated scroll distance of the mouse wheel (for example to reset it).
+    ///
+    /// # Affected uniform buffer
+    /// `iMouseMotion`
+    #[inline]
+    #[cfg(feature = "mouse")]
+    pub fn set_mouse_wheel(&mut self, x: f32, y: f32) {
+        self.resources.mouse.set_wheel(x, y);
+    }
+
+    /// Set the accumulated distance which the mouse has been dragged (for example to reset it).
+    ///
+    /// Moving the mouse with [Shady::set_mouse_pos] while it's pressed adds to it.
+    ///
+    /// # Affected uniform buffer
+    /// `iMouseMotion`
+    #[inline]
+    #[cfg(feature = "mouse")]
+    pub fn set_mouse_drag(&mut self, x: f32, y: f32) {
+        self.resources.mouse.set_drag(x, y);
+    }
+
     /// Increment the frame counter.
     ///
     /// # Affected uniform buffer
@@ -179,6 +752,36 @@ impl Shady {
         self.resources.frame.inc();
     }
 
+    /// Set the playback time (in seconds).
+    ///
+    /// # Affected uniform buffer
+    /// `iTime`
+    #[inline]
+    #[cfg(feature = "time")]
+    pub fn set_time(&mut self, time: f32) {
+        self.resources.time.set(time);
+    }
+
+    /// Returns the current playback time (in seconds).
+    #[inline]
+    #[cfg(feature = "time")]
+    pub fn time(&self) -> f32 {
+        self.resources.time.elapsed()
+    }
+
+    /// Stops (`true`) or continues (`false`) the playback time.
+    ///
+    /// # Affected uniform buffer
+    /// `iTime`
+    #[inline]
+    #[cfg(feature = "time")]
+    pub fn set_time_paused(&mut self, paused: bool) {
+        self.time_paused = paused;
+        self.resources
+            .time
+            .set_paused(self.time_paused || !self.active);
+    }
+
     /// Set the frequency range which [Shady] should listen to from the sample fetcher.
     ///
     /// # Affected uniform buffer
@@ -195,8 +798,230 @@ impl Shady {
             .set_frequency_range(sample_processor, freq_range);
     }
 
+    /// Smoothly changes the frequency range which [Shady] listens to within `duration`,
+    /// for example to zoom into the bass during a drop.
+    ///
+    /// The range is interpolated logarithmically and updated by [Shady::update_audio_buffer],
+    /// so the bars don't snap to the new range.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`
+    #[inline]
+    #[cfg(feature = "audio")]
+    pub fn animate_audio_frequency_range(
+        &mut self,
+        freq_range: std::ops::Range<std::num::NonZeroU16>,
+        duration: std::time::Duration,
+    ) {
+        self.resources
+            .audio
+            .animate_frequency_range(freq_range, duration);
+    }
+
+    /// Set the timeline which animates the values of `iTimeline`.
+    ///
+    /// # Affected uniform buffer
+    /// `iTimeline`
+    #[inline]
+    #[cfg(feature = "timeline")]
+    pub fn set_timeline(&mut self, device: &Device, timeline: timeline::Timeline) {
+        self.resources.timeline.set_timeline(device, timeline);
+        // the timeline buffer will change => needs to be rebinded
+        self.bind_groups = self.resources.bind_groups(device);
+    }
+
+    /// Sets the values of `iParams`.
+    ///
+    /// The buffer is only replaced if the amount of values changes, so it's fine to call it each frame.
+    ///
+    /// # Affected storage buffer
+    /// `iParams`
+    #[inline]
+    #[cfg(feature = "params")]
+    pub fn set_params(&mut self, device: &Device, values: &[f32]) {
+        if self.resources.params.set_values(device, values) {
+            // the params buffer has changed => needs to be rebinded
+            self.bind_groups = self.resources.bind_groups(device);
+        }
+    }
+
+    /// Returns the current values of `iParams`.
+    #[inline]
+    #[cfg(feature = "params")]
+    pub fn params(&self) -> &[f32] {
+        self.resources.params.values()
+    }
+
+    /// Sets the amount of values of `iState` (defaults to [DEFAULT_STATE_SIZE]).
+    ///
+    /// All values are reset to `0`.
+    ///
+    /// # Affected storage buffer
+    /// `iState`
+    #[inline]
+    #[cfg(feature = "state")]
+    pub fn set_state_size(&mut self, device: &Device, amount_values: usize) {
+        self.resources.state.set_size(device, amount_values);
+        // the state buffer will change => needs to be rebinded
+        self.bind_groups = self.resources.bind_groups(device);
+    }
+
+    /// Resets all values of `iState` to `0` before the next passes of `encoder` are executed.
+    ///
+    /// # Affected storage buffer
+    /// `iState`
+    #[inline]
+    #[cfg(feature = "state")]
+    pub fn clear_state(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.resources.state.clear(encoder);
+    }
+
+    /// Sets how long the audio has to be silent before `iIdle` starts to ramp up (`delay`)
+    /// and how long it needs to ramp up from `0` to `1` (`ramp`).
+    ///
+    /// Defaults to a delay of 2 seconds and a ramp of 3 seconds.
+    ///
+    /// # Affected uniform buffer
+    /// `iIdle`
+    #[inline]
+    #[cfg(feature = "idle")]
+    pub fn set_idle_timing(&mut self, delay: std::time::Duration, ramp: std::time::Duration) {
+        self.resources.idle.set_timing(delay, ramp);
+    }
+
+    /// Uploads the given image into the texture of `iChannel<channel>` and its resolution into `iChannelResolution`.
+    ///
+    /// `rgba` contains the pixels row by row with 4 bytes (red, green, blue, alpha) per pixel.
+    ///
+    /// # Panics
+    /// If `channel` isn't smaller than [AMOUNT_CHANNELS] or `rgba` doesn't match the given size.
+    ///
+    /// # Affected uniform buffer
+    /// `iChannel<channel>`, `iChannelResolution`
+    #[cfg(feature = "channels")]
+    pub fn set_channel(
+        &mut self,
+        device: &Device,
+        queue: &wgpu::Queue,
+        channel: usize,
+        size: (u32, u32),
+        rgba: &[u8],
+    ) {
+        self.resources
+            .channels
+            .set_rgba(device, queue, channel, size, rgba);
+        self.resources.channels.update_buffer(queue);
+        // the texture has changed => needs to be rebinded
+        self.bind_groups = self.resources.bind_groups(device);
+    }
+
+    /// Set the state of the current track of a music player.
+    ///
+    /// `position` is the playback position within the track and `duration` the length of the track (if known).
+    ///
+    /// # Affected uniform buffer
+    /// `iTrackTime`, `iTrackDuration`, `iPlaying`
+    #[inline]
+    #[cfg(feature = "track")]
+    pub fn set_track(
+        &mut self,
+        position: std::time::Duration,
+        duration: Option<std::time::Duration>,
+        playing: bool,
+    ) {
+        self.resources.track.set(position, duration, playing);
+    }
+
+    /// Set the position of a stick of the gamepad. Both coordinates are within `[-1, 1]` and up is positive.
+    ///
+    /// # Affected uniform buffer
+    /// `iGamepadAxes`
+    #[inline]
+    #[cfg(feature = "gamepad")]
+    pub fn set_gamepad_stick(&mut self, stick: GamepadStick, x: f32, y: f32) {
+        self.resources.gamepad.set_stick(stick, x, y);
+    }
+
+    /// Set the value of a button of the gamepad within `[0, 1]` (`1` while it's pressed).
+    ///
+    /// # Affected uniform buffer
+    /// `iGamepadButtons`
+    #[inline]
+    #[cfg(feature = "gamepad")]
+    pub fn set_gamepad_button(&mut self, button: GamepadButton, value: f32) {
+        self.resources.gamepad.set_button(button, value);
+    }
+
+    /// Centers the sticks and releases all buttons of the gamepad, for example if it has been disconnected.
+    ///
+    /// # Affected uniform buffer
+    /// `iGamepadAxes`, `iGamepadButtons`
+    #[inline]
+    #[cfg(feature = "gamepad")]
+    pub fn reset_gamepad(&mut self) {
+        self.resources.gamepad.reset();
+    }
+
+    /// Registers a function which shapes the bar values (for example a gamma correction)
+    /// before they are written into the buffer.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`
+    #[inline]
+    #[cfg(feature = "audio")]
+    pub fn set_audio_post_fn(&mut self, post_fn: impl shady_audio::PostProcessor + 'static) {
+        self.resources.audio.set_post_fn(post_fn);
+    }
+
+    /// Overwrites the values of `iAudio` with your own values (for example a spectrum which you've already computed),
+    /// bypassing the bar processor.
+    ///
+    /// Values beyond the amount of bars (see [Shady::set_audio_bars]) are ignored and missing values are set to `0`.
+    /// Write them into the buffer with [Shady::update_audio_values_buffer] since [Shady::update_audio_buffer]
+    /// would replace them with the bars of the sample processor.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`
+    #[inline]
+    #[cfg(feature = "audio")]
+    pub fn set_audio_values(&mut self, values: &[f32]) {
+        self.resources.audio.set_values(values);
+    }
+
+    /// Returns the current values of `iAudio` (the bars or the values of [Shady::set_audio_values]).
+    #[inline]
+    #[cfg(feature = "audio")]
+    pub fn audio_values(&self) -> Box<[f32]> {
+        self.resources.audio.values()
+    }
+
+    /// Sets how slowly `iAudioSlow` follows the bars of `iAudio`
+    /// (see [shady_audio::SlowSpectrum], [shady_audio::DEFAULT_SLOW_TIME_CONSTANT] by default).
+    ///
+    /// # Affected uniform buffer
+    /// `iAudioSlow`
+    #[inline]
+    #[cfg(feature = "slow-audio")]
+    pub fn set_audio_slow_time_constant(&mut self, time_constant: std::time::Duration) {
+        self.resources.audio.set_slow_time_constant(time_constant);
+    }
+
+    /// Sets how long the peaks of `iAudioPeaks` are kept and how fast they fall afterwards (in bar values per second),
+    /// see [shady_audio::PeakHold]. [shady_audio::DEFAULT_PEAK_HOLD_TIME] and [shady_audio::DEFAULT_PEAK_FALL_SPEED] by default.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudioPeaks`
+    #[inline]
+    #[cfg(feature = "audio-peaks")]
+    pub fn set_audio_peak_timing(&mut self, hold_time: std::time::Duration, fall_speed: f32) {
+        self.resources.audio.set_peak_timing(hold_time, fall_speed);
+    }
+
     /// Sets the amount of bar-values.
     ///
+    /// The bars of the previous amount fade into the new ones (see [Shady::set_audio_bar_transition]),
+    /// so resizing a window doesn't let the whole visualization collapse.
+    ///
     /// # Affected uniform buffer
     /// `iAudio`
     #[inline]
@@ -204,63 +1029,275 @@ impl Shady {
     pub fn set_audio_bars(&mut self, device: &Device, amount_bars: std::num::NonZero<u16>) {
         self.resources.audio.set_bars(device, amount_bars);
         // audio buffer will change => needs to be rebinded
-        self.bind_group = self.resources.bind_group(device);
+        self.bind_groups = self.resources.bind_groups(device);
+    }
+
+    /// Sets how long the bars fade from the previous amount of bars to the new one after [Shady::set_audio_bars]
+    /// (250ms by default). [std::time::Duration::ZERO] lets the bars restart from zero.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`
+    #[inline]
+    #[cfg(feature = "audio")]
+    pub fn set_audio_bar_transition(&mut self, duration: std::time::Duration) {
+        self.resources.audio.set_bar_transition(duration);
+    }
+
+    /// Sets whether the bars are uploaded into the storage buffer `iAudio` (default) or into the texture `iAudioTexture`.
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`, `iAudioTexture`
+    #[inline]
+    #[cfg(feature = "audio-texture")]
+    pub fn set_audio_representation(
+        &mut self,
+        device: &Device,
+        representation: AudioRepresentation,
+    ) {
+        self.resources
+            .audio
+            .set_representation(device, representation);
+        // the texture will change => needs to be rebinded
+        self.bind_groups = self.resources.bind_groups(device);
+    }
+
+    /// Sets the amount of bar-values to the amount of bars with a width of `bar_px` and a gap of `gap_px`
+    /// between them which fit into `pixel_width` and returns it.
+    ///
+    /// All sizes are in physical pixels, so multiply your logical sizes with the scale factor of the window.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // 4px wide bars with a gap of 1px on a 1920px wide window with a scale factor of 2
+    /// let amount_bars = shady.set_audio_bars_for_width(&device, 3840, 4. * 2., 1. * 2.);
+    /// assert_eq!(amount_bars.get(), 384);
+    /// ```
+    ///
+    /// # Affected uniform buffer
+    /// `iAudio`
+    #[cfg(feature = "audio")]
+    pub fn set_audio_bars_for_width(
+        &mut self,
+        device: &Device,
+        pixel_width: u32,
+        bar_px: f32,
+        gap_px: f32,
+    ) -> std::num::NonZero<u16> {
+        let amount_bars = amount_bars_for_width(pixel_width, bar_px, gap_px);
+        self.set_audio_bars(device, amount_bars);
+        amount_bars
     }
 }
 
 /// Methods to overwrite/update the responding uniform buffer for the next time you render a frame with [Shady].
 impl Shady {
-    /// Updates the `iAudio` uniform buffer with new values.
+    /// Updates the `iAudio` (and `iAudioSlow`, `iAudioPeaks`) uniform buffer with new values.
+    ///
+    /// Does nothing while `shady` is suspended (see [Shady::set_active]).
     #[inline]
+    #[instrument(level = "trace", skip_all)]
     #[cfg(feature = "audio")]
     pub fn update_audio_buffer(
         &mut self,
         queue: &wgpu::Queue,
         sample_processor: &shady_audio::SampleProcessor,
     ) {
-        self.resources.audio.fetch_audio(sample_processor);
-        self.resources.audio.update_buffer(queue);
+        if !self.active {
+            return;
+        }
+
+        self.record_buffer_update(|resources| {
+            resources.audio.fetch_audio(sample_processor);
+            resources.audio.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iAudio` uniform buffer with the values of [Shady::set_audio_values].
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "audio")]
+    pub fn update_audio_values_buffer(&mut self, queue: &wgpu::Queue) {
+        self.record_buffer_update(|resources| {
+            resources.audio.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iIdle` uniform buffer with the silence duration of the sample processor
+    /// (see [shady_audio::SampleProcessor::silence_duration]).
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "idle")]
+    pub fn update_idle_buffer(
+        &mut self,
+        queue: &wgpu::Queue,
+        sample_processor: &shady_audio::SampleProcessor,
+    ) {
+        self.record_buffer_update(|resources| {
+            resources
+                .idle
+                .set_silence(sample_processor.silence_duration());
+            resources.idle.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iTrackTime`, `iTrackDuration` and `iPlaying` uniform buffers.
+    ///
+    /// `iTrackTime` keeps advancing while the track is playing, so you needn't call [Shady::set_track] each frame.
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "track")]
+    pub fn update_track_buffer(&mut self, queue: &wgpu::Queue) {
+        self.record_buffer_update(|resources| {
+            resources.track.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iGamepadAxes` and `iGamepadButtons` uniform buffers with new values.
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "gamepad")]
+    pub fn update_gamepad_buffer(&mut self, queue: &wgpu::Queue) {
+        self.record_buffer_update(|resources| {
+            resources.gamepad.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iParams` storage buffer with the values of [Shady::set_params].
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "params")]
+    pub fn update_params_buffer(&mut self, queue: &wgpu::Queue) {
+        self.record_buffer_update(|resources| {
+            resources.params.update_buffer(queue);
+        });
     }
 
     /// Updates the `iFrame` uniform buffer with new values.
     #[inline]
+    #[instrument(level = "trace", skip_all)]
     #[cfg(feature = "frame")]
     pub fn update_frame_buffer(&mut self, queue: &wgpu::Queue) {
-        self.resources.frame.update_buffer(queue);
+        self.record_buffer_update(|resources| {
+            resources.frame.update_buffer(queue);
+        });
     }
 
-    /// Updates the `iMouse` uniform buffer with new values.
+    /// Updates the `iMouse` and `iMouseMotion` uniform buffers with new values.
     #[inline]
+    #[instrument(level = "trace", skip_all)]
     #[cfg(feature = "mouse")]
     pub fn update_mouse_buffer(&mut self, queue: &wgpu::Queue) {
-        self.resources.mouse.update_buffer(queue);
+        self.record_buffer_update(|resources| {
+            resources.mouse.update_buffer(queue);
+        });
     }
 
     /// Updates the `iResolution` uniform buffer with new values.
     #[inline]
+    #[instrument(level = "trace", skip_all)]
     #[cfg(feature = "resolution")]
     pub fn update_resolution_buffer(&mut self, queue: &wgpu::Queue) {
-        self.resources.resolution.update_buffer(queue);
+        self.record_buffer_update(|resources| {
+            resources.resolution.update_buffer(queue);
+        });
     }
 
     /// Updates the `iTime` uniform buffer with new values.
+    ///
+    /// Lets the clock advance to the next frame (see [clock::Clock::tick]), so call it once per frame.
     #[inline]
+    #[instrument(level = "trace", skip_all)]
     #[cfg(feature = "time")]
     pub fn update_time_buffer(&mut self, queue: &wgpu::Queue) {
-        self.resources.time.update_buffer(queue);
+        self.record_buffer_update(|resources| {
+            resources.time.tick();
+            resources.time.update_buffer(queue);
+        });
+    }
+
+    /// Updates the `iTimeline` storage buffer with the values of the timeline at the current `iTime`.
+    #[inline]
+    #[instrument(level = "trace", skip_all)]
+    #[cfg(feature = "timeline")]
+    pub fn update_timeline_buffer(&mut self, queue: &wgpu::Queue) {
+        self.record_buffer_update(|resources| {
+            let time = resources.time.elapsed();
+            resources.timeline.update(time);
+            resources.timeline.update_buffer(queue);
+        });
+    }
+
+    // `unused`: Without any resource feature, there's no buffer to update.
+    #[allow(unused)]
+    fn record_buffer_update(&mut self, update: impl FnOnce(&mut Resources)) {
+        let start = Instant::now();
+        update(&mut self.resources);
+        self.stats.get_mut().add_buffer_update(start.elapsed());
     }
 }
 
 /// Creates a pre-configured pipeline which can then be used in [Shady::add_render_pass].
+///
+/// It uses the default [RenderPipelineOptions]. Use [create_render_pipeline_with_options] to change them.
 pub fn create_render_pipeline<'a>(
     device: &Device,
     shader_source: ShaderSource<'a>,
     texture_format: &'a wgpu::TextureFormat,
 ) -> ShadyRenderPipeline {
-    let bind_group_layout = Resources::bind_group_layout(device);
-    let pipeline = get_render_pipeline(device, shader_source, bind_group_layout, texture_format);
+    create_render_pipeline_with_options(
+        device,
+        shader_source,
+        texture_format,
+        &RenderPipelineOptions::default(),
+    )
+}
 
-    ShadyRenderPipeline(pipeline)
+/// Like [create_render_pipeline] but with the given options for blending, culling, multisampling
+/// and your own bind groups.
+///
+/// # Example
+/// ```ignore
+/// let pipeline = shady::create_render_pipeline_with_options(
+///     &device,
+///     shader_source,
+///     &surface_format,
+///     &RenderPipelineOptions {
+///         blend_mode: BlendMode::Additive,
+///         ..Default::default()
+///     },
+/// );
+/// ```
+pub fn create_render_pipeline_with_options<'a>(
+    device: &Device,
+    shader_source: ShaderSource<'a>,
+    texture_format: &'a wgpu::TextureFormat,
+    options: &RenderPipelineOptions,
+) -> ShadyRenderPipeline {
+    // only custom vertex shaders can use the resources
+    let visibility = if options.vertex_shader.is_some() {
+        VERTEX_RESOURCE_VISIBILITY
+    } else {
+        RESOURCE_VISIBILITY
+    };
+
+    let bind_group_layout = Resources::bind_group_layout(device, visibility);
+    let pipeline = get_render_pipeline(
+        device,
+        shader_source,
+        bind_group_layout,
+        texture_format,
+        options,
+    );
+
+    ShadyRenderPipeline {
+        pipeline,
+        sample_count: options.sample_count,
+        bind_group_index: options.bind_group_index,
+        visibility,
+        vertices: options
+            .vertex_shader
+            .map(|vertex_shader| (vertex_shader.vertex_count, vertex_shader.instance_count)),
+    }
 }
 
 fn get_render_pipeline(
@@ -268,11 +1305,31 @@ fn get_render_pipeline(
     shader_source: ShaderSource<'_>,
     bind_group_layout: wgpu::BindGroupLayout,
     texture_format: &wgpu::TextureFormat,
+    options: &RenderPipelineOptions,
 ) -> wgpu::RenderPipeline {
-    let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Shady vertex shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("vertex_shader.wgsl").into()),
-    });
+    let default_vertex_shader;
+    let (vertex_module, vertex_entry_point, vertex_buffers, topology) = match &options.vertex_shader
+    {
+        Some(vertex_shader) => (
+            vertex_shader.module,
+            vertex_shader.entry_point,
+            &[][..],
+            vertex_shader.topology,
+        ),
+        None => {
+            default_vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shady vertex shader"),
+                source: wgpu::ShaderSource::Wgsl(vertices::VERTEX_SHADER.into()),
+            });
+
+            (
+                &default_vertex_shader,
+                "vertex_main",
+                &[vertices::BUFFER_LAYOUT][..],
+                wgpu::PrimitiveTopology::TriangleList,
+            )
+        }
+    };
 
     let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Shady fragment shader"),
@@ -281,7 +1338,7 @@ fn get_render_pipeline(
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Shady pipeline layout"),
-        bind_group_layouts: &[&bind_group_layout],
+        bind_group_layouts: &options.pipeline_bind_group_layouts(&bind_group_layout),
         push_constant_ranges: &[],
     });
 
@@ -289,23 +1346,23 @@ fn get_render_pipeline(
         label: Some("Shady render pipeline"),
         layout: Some(&pipeline_layout),
         vertex: wgpu::VertexState {
-            module: &vertex_shader,
-            entry_point: Some("vertex_main"),
-            buffers: &[vertices::BUFFER_LAYOUT],
+            module: vertex_module,
+            entry_point: Some(vertex_entry_point),
+            buffers: vertex_buffers,
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         },
         primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
+            topology,
             strip_index_format: None,
             front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
+            cull_mode: options.cull_mode,
             polygon_mode: wgpu::PolygonMode::Fill,
             unclipped_depth: false,
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: options.depth_stencil.cloned(),
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: options.sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -313,13 +1370,15 @@ fn get_render_pipeline(
             module: &fragment_shader,
             entry_point: Some("main"),
             targets: &[Some(wgpu::ColorTargetState {
-                format: *texture_format,
-                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                format: options.color_space.map_or(*texture_format, |color_space| {
+                    color_space.view_format(*texture_format)
+                }),
+                blend: options.blend_mode.blend_state(),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         }),
         multiview: None,
-        cache: None,
+        cache: options.cache,
     })
 }
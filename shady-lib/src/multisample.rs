@@ -0,0 +1,44 @@
+use wgpu::{Device, TextureView};
+
+/// The multisampled color target which gets resolved into the texture view of the caller.
+pub struct MultisampleTarget {
+    view: TextureView,
+    sample_count: u32,
+}
+
+impl MultisampleTarget {
+    pub fn new(
+        device: &Device,
+        sample_count: u32,
+        format: wgpu::TextureFormat,
+        (width, height): (u32, u32),
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shady multisample texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            sample_count,
+        }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+}
@@ -0,0 +1,639 @@
+//! Render shaders without a window.
+//!
+//! This is mainly useful to write tests for your shaders: Render a frame with fixed values for the
+//! resources and compare the output with an image which is known to be correct ("golden image").
+//! See [golden_test] for a macro which does exactly that.
+//!
+//! # Example
+//! ```no_run
+//! use shady::offscreen::{self, Uniforms};
+//!
+//! let shader = "
+//!     @fragment
+//!     fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+//!         return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+//!     }
+//! ";
+//!
+//! let image = offscreen::render_once(
+//!     wgpu::ShaderSource::Wgsl(shader.into()),
+//!     (64, 64),
+//!     &Uniforms::default(),
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+//! ```
+//!
+//! [golden_test]: crate::golden_test
+use std::path::Path;
+
+use image::RgbaImage;
+use pollster::FutureExt;
+use wgpu::ShaderSource;
+
+use crate::{ColorSpace, RenderPipelineOptions, Shady, ShadyDescriptor};
+
+/// The texture format of the rendered images.
+///
+/// The fragment shader renders into a view whose format depends on the [ColorSpace]
+/// (see [OffscreenRenderer::with_color_space]).
+pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+// the features which are used if the adapter supports them, like the window of `shady-toy` does
+#[cfg(feature = "audio-texture")]
+const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::FLOAT32_FILTERABLE;
+#[cfg(not(feature = "audio-texture"))]
+const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::empty();
+
+/// Errors which can occur while rendering offscreen.
+#[derive(thiserror::Error, Debug)]
+pub enum OffscreenError {
+    #[error("Couldn't find a graphics adapter.")]
+    NoAdapter,
+
+    #[error(transparent)]
+    RequestDevice(#[from] wgpu::RequestDeviceError),
+
+    #[error("Couldn't read the rendered frame: {0}")]
+    BufferMap(#[from] wgpu::BufferAsyncError),
+
+    #[error("Width and height must be greater than zero.")]
+    InvalidSize,
+
+    #[error(transparent)]
+    Shady(#[from] crate::ShadyError),
+}
+
+/// The values of the resources which should be used for the frame.
+#[derive(Debug, Clone, Default)]
+pub struct Uniforms {
+    /// The value of `iTime`.
+    #[cfg(feature = "time")]
+    pub time: f32,
+
+    /// The value of `iFrame`.
+    #[cfg(feature = "frame")]
+    pub frame: u32,
+
+    /// The position where the (pressed) mouse is located. `None` if the mouse isn't pressed.
+    #[cfg(feature = "mouse")]
+    pub mouse: Option<[f32; 2]>,
+
+    /// The values of `iAudio` (for example the bars of the frame which you've computed with
+    /// [shady_audio::analyze_file]). Missing values are `0`, so it's silent by default.
+    ///
+    /// See [OffscreenRenderer::set_audio_bars] for the amount of values.
+    #[cfg(feature = "audio")]
+    pub audio: Vec<f32>,
+}
+
+/// Renders one frame of the given fragment shader with a size of `(width, height)` and returns it.
+///
+/// `iResolution` is set to the given size. If the `audio` feature is enabled, `iAudio` is silent
+/// unless [Uniforms::audio] is set. Use [OffscreenRenderer] if you want to render multiple frames.
+pub fn render_once(
+    shader_source: ShaderSource<'_>,
+    size: (u32, u32),
+    uniforms: &Uniforms,
+) -> Result<RgbaImage, OffscreenError> {
+    OffscreenRenderer::new(shader_source, size)?.render(uniforms)
+}
+
+/// Renders multiple frames of the same fragment shader without a window.
+///
+/// The device and the pipeline are only created once, so this is much faster than calling
+/// [render_once] for each frame.
+///
+/// # Example
+/// ```no_run
+/// use shady::offscreen::{OffscreenRenderer, Uniforms};
+///
+/// let shader = "
+///     @fragment
+///     fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+///         return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+///     }
+/// ";
+///
+/// let mut renderer =
+///     OffscreenRenderer::new(wgpu::ShaderSource::Wgsl(shader.into()), (64, 64)).unwrap();
+///
+/// for frame in 0..10 {
+///     let image = renderer
+///         .render(&Uniforms {
+///             time: frame as f32 / 30.,
+///             frame,
+///             ..Default::default()
+///         })
+///         .unwrap();
+/// }
+/// ```
+pub struct OffscreenRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    shady: Shady,
+    pipeline: crate::ShadyRenderPipeline,
+
+    texture: wgpu::Texture,
+    view_format: wgpu::TextureFormat,
+    output_buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+}
+
+impl OffscreenRenderer {
+    /// Creates a renderer for images with a size of `(width, height)`.
+    ///
+    /// `iResolution` is set to the given size. If the `audio` feature is enabled, `iAudio` is silent
+    /// unless [Uniforms::audio] is set. The colors of the shader are treated as sRGB encoded
+    /// ([ColorSpace::default]), see [OffscreenRenderer::with_color_space].
+    pub fn new(shader_source: ShaderSource<'_>, size: (u32, u32)) -> Result<Self, OffscreenError> {
+        Self::with_color_space(shader_source, size, ColorSpace::default())
+    }
+
+    /// Like [OffscreenRenderer::new] but with the given color space of the output of the fragment shader
+    /// (see [RenderPipelineOptions::color_space]), so the images look like the ones of a window with the same color space.
+    pub fn with_color_space(
+        shader_source: ShaderSource<'_>,
+        size: (u32, u32),
+        color_space: ColorSpace,
+    ) -> Result<Self, OffscreenError> {
+        Self::with_features(shader_source, size, color_space, OPTIONAL_FEATURES)
+    }
+
+    /// Requests the given features of the device as far as the adapter supports them.
+    fn with_features(
+        shader_source: ShaderSource<'_>,
+        (width, height): (u32, u32),
+        color_space: ColorSpace,
+        features: wgpu::Features,
+    ) -> Result<Self, OffscreenError> {
+        if width == 0 || height == 0 {
+            return Err(OffscreenError::InvalidSize);
+        }
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::PRIMARY,
+            ..Default::default()
+        });
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .block_on()
+            .ok_or(OffscreenError::NoAdapter)?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: adapter.features() & features,
+                    ..Default::default()
+                },
+                None,
+            )
+            .block_on()?;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shady offscreen texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[color_space.view_format(TEXTURE_FORMAT)],
+        });
+
+        // https://www.w3.org/TR/webgpu/#gputexelcopybufferinfo
+        let padded_bytes_per_row =
+            (width * BYTES_PER_PIXEL).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shady offscreen output buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        #[cfg(feature = "audio")]
+        let sample_processor =
+            shady_audio::SampleProcessor::new(shady_audio::fetcher::DummyFetcher::new(1));
+
+        let shady = Shady::new(ShadyDescriptor {
+            device: &device,
+            #[cfg(feature = "audio")]
+            sample_processor: &sample_processor,
+            #[cfg(feature = "audio")]
+            audio_handle: None,
+            #[cfg(feature = "time")]
+            clock: None,
+        })?;
+
+        let pipeline = crate::create_render_pipeline_with_options(
+            &device,
+            shader_source,
+            &TEXTURE_FORMAT,
+            &RenderPipelineOptions {
+                color_space: Some(color_space),
+                ..Default::default()
+            },
+        );
+
+        Ok(Self {
+            device,
+            queue,
+            shady,
+            pipeline,
+            texture,
+            view_format: color_space.view_format(TEXTURE_FORMAT),
+            output_buffer,
+            padded_bytes_per_row,
+        })
+    }
+
+    /// Sets the amount of values of `iAudio` (60 by default).
+    #[cfg(feature = "audio")]
+    pub fn set_audio_bars(&mut self, amount_bars: std::num::NonZero<u16>) {
+        self.shady.set_audio_bars(&self.device, amount_bars);
+    }
+
+    /// Sets whether the values of [Uniforms::audio] are uploaded into `iAudio` or `iAudioTexture`
+    /// (see [Shady::set_audio_representation]).
+    #[cfg(feature = "audio-texture")]
+    pub fn set_audio_representation(&mut self, representation: crate::AudioRepresentation) {
+        self.shady
+            .set_audio_representation(&self.device, representation);
+    }
+
+    /// Renders one frame with the given values of the resources.
+    pub fn render(&mut self, uniforms: &Uniforms) -> Result<RgbaImage, OffscreenError> {
+        let texture_extent = self.texture.size();
+        let (width, height) = (texture_extent.width, texture_extent.height);
+
+        apply_uniforms(&mut self.shady, (width, height), uniforms);
+        update_buffers(&mut self.shady, &self.queue);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Shady offscreen command encoder"),
+            });
+
+        let texture_view = self.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(self.view_format),
+            ..Default::default()
+        });
+        self.shady
+            .add_render_pass(&mut encoder, &texture_view, std::iter::once(&self.pipeline));
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                aspect: wgpu::TextureAspect::All,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                texture: &self.texture,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.output_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture_extent,
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = self.output_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).unwrap();
+        });
+
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap()?;
+
+        // remove the padding of each row
+        let unpadded_bytes_per_row = (width * BYTES_PER_PIXEL) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+        {
+            let data = buffer_slice.get_mapped_range();
+            for row in data.chunks_exact(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.output_buffer.unmap();
+
+        Ok(RgbaImage::from_raw(width, height, pixels)
+            .expect("Pixel buffer has the size of the image"))
+    }
+}
+
+/// Compares `image` with the image stored at `golden_path`.
+///
+/// If the environment variable `SHADY_UPDATE_GOLDEN` is set, `image` is stored as the new golden
+/// image instead.
+///
+/// **Panics** if the images differ (each color channel is allowed to differ by `tolerance`),
+/// if the golden image doesn't exist or if it couldn't be read/written.
+pub fn assert_golden(golden_path: impl AsRef<Path>, image: &RgbaImage, tolerance: u8) {
+    let golden_path = golden_path.as_ref();
+
+    if std::env::var_os("SHADY_UPDATE_GOLDEN").is_some() {
+        if let Some(parent) = golden_path.parent() {
+            std::fs::create_dir_all(parent).expect("Create directory of golden image");
+        }
+
+        image.save(golden_path).expect("Store golden image");
+        return;
+    }
+
+    assert!(
+        golden_path.exists(),
+        "The golden image {:?} doesn't exist. Set `SHADY_UPDATE_GOLDEN` to create it.",
+        golden_path
+    );
+
+    let golden = image::open(golden_path)
+        .unwrap_or_else(|err| panic!("Couldn't open golden image {:?}: {}", golden_path, err))
+        .into_rgba8();
+
+    assert_eq!(
+        golden.dimensions(),
+        image.dimensions(),
+        "The rendered image has a different size than the golden image {:?}",
+        golden_path
+    );
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let expected = golden.get_pixel(x, y);
+
+        let is_similar = pixel
+            .0
+            .iter()
+            .zip(expected.0.iter())
+            .all(|(a, b)| a.abs_diff(*b) <= tolerance);
+
+        assert!(
+            is_similar,
+            "Pixel ({}, {}) differs from the golden image {:?}: got {:?}, expected {:?}",
+            x, y, golden_path, pixel.0, expected.0
+        );
+    }
+}
+
+/// Creates a test which renders the given shader and compares it with a golden image.
+///
+/// The path of the golden image is relative to the directory of the `Cargo.toml` of your crate.
+/// The test fails if the golden image doesn't exist. Set the environment variable
+/// `SHADY_UPDATE_GOLDEN` to create or overwrite the golden images.
+///
+/// # Example
+/// ```ignore
+/// shady::golden_test!(
+///     #[ignore] // requires a GPU
+///     red_screen,
+///     shader: wgpu::ShaderSource::Wgsl(include_str!("red.wgsl").into()),
+///     size: (64, 64),
+///     uniforms: shady::offscreen::Uniforms::default(),
+///     golden: "tests/golden/red_screen.png",
+/// );
+/// ```
+#[macro_export]
+macro_rules! golden_test {
+    (
+        $(#[$meta:meta])*
+        $name:ident,
+        shader: $shader:expr,
+        size: $size:expr,
+        uniforms: $uniforms:expr,
+        golden: $golden:expr $(,)?
+    ) => {
+        $(#[$meta])*
+        #[test]
+        fn $name() {
+            let image = $crate::offscreen::render_once($shader, $size, &$uniforms)
+                .expect("Render shader offscreen");
+
+            $crate::offscreen::assert_golden(
+                ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($golden),
+                &image,
+                1,
+            );
+        }
+    };
+}
+
+// `unused`: Depending on the enabled features, some arguments might not be used.
+#[allow(unused)]
+fn apply_uniforms(shady: &mut Shady, (width, height): (u32, u32), uniforms: &Uniforms) {
+    #[cfg(feature = "resolution")]
+    shady.set_resolution(width, height);
+
+    #[cfg(feature = "time")]
+    shady.resources.time.set_fixed(Some(uniforms.time));
+
+    #[cfg(feature = "frame")]
+    shady.resources.frame.set(uniforms.frame);
+
+    #[cfg(feature = "mouse")]
+    if let Some([x, y]) = uniforms.mouse {
+        shady.set_mouse_state(crate::MouseState::Pressed);
+        shady.set_mouse_pos(x, y);
+    } else {
+        shady.set_mouse_state(crate::MouseState::Released);
+    }
+
+    #[cfg(feature = "audio")]
+    shady.set_audio_values(&uniforms.audio);
+}
+
+// `unused`: Depending on the enabled features, some arguments might not be used.
+#[allow(unused)]
+fn update_buffers(shady: &mut Shady, queue: &wgpu::Queue) {
+    #[cfg(feature = "audio")]
+    shady.update_audio_values_buffer(queue);
+    #[cfg(feature = "frame")]
+    shady.update_frame_buffer(queue);
+    #[cfg(feature = "mouse")]
+    shady.update_mouse_buffer(queue);
+    #[cfg(feature = "resolution")]
+    shady.update_resolution_buffer(queue);
+    #[cfg(feature = "time")]
+    shady.update_time_buffer(queue);
+    #[cfg(feature = "timeline")]
+    shady.update_timeline_buffer(queue);
+    #[cfg(feature = "track")]
+    shady.update_track_buffer(queue);
+    #[cfg(feature = "gamepad")]
+    shady.update_gamepad_buffer(queue);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore] // requires a GPU
+    fn red_screen_with_padded_rows() {
+        let shader = "
+            @fragment
+            fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+            }
+        ";
+
+        // 10 pixels per row aren't aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+        let image = render_once(
+            ShaderSource::Wgsl(shader.into()),
+            (10, 3),
+            &Uniforms::default(),
+        )
+        .unwrap();
+
+        assert_eq!(image.dimensions(), (10, 3));
+        for pixel in image.pixels() {
+            assert_eq!(pixel.0, [255, 0, 0, 255]);
+        }
+    }
+
+    #[test]
+    #[ignore] // requires a GPU
+    fn render_multiple_frames() {
+        let shader = "
+            @fragment
+            fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+                return vec4<f32>(0.0, 0.0, 1.0, 1.0);
+            }
+        ";
+
+        let mut renderer =
+            OffscreenRenderer::new(ShaderSource::Wgsl(shader.into()), (4, 4)).unwrap();
+
+        let first = renderer.render(&Uniforms::default()).unwrap();
+        let second = renderer.render(&Uniforms::default()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    #[ignore] // requires a GPU
+    fn clear_color() {
+        let shader = "
+            @fragment
+            fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+                discard;
+            }
+        ";
+
+        let mut renderer =
+            OffscreenRenderer::new(ShaderSource::Wgsl(shader.into()), (4, 4)).unwrap();
+        let transparent = renderer.render(&Uniforms::default()).unwrap();
+        assert!(transparent.pixels().all(|pixel| pixel.0 == [0, 0, 0, 0]));
+
+        renderer.shady.set_clear_color(Some(wgpu::Color::GREEN));
+        let green = renderer.render(&Uniforms::default()).unwrap();
+        assert!(green.pixels().all(|pixel| pixel.0 == [0, 255, 0, 255]));
+
+        // the previous frame is kept
+        renderer.shady.set_clear_color(None);
+        assert_eq!(renderer.render(&Uniforms::default()).unwrap(), green);
+    }
+
+    #[test]
+    #[ignore] // requires a GPU
+    fn color_spaces() {
+        let shader = "
+            @fragment
+            fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+                return vec4<f32>(0.5, 0.5, 0.5, 1.0);
+            }
+        ";
+
+        let render = |color_space| {
+            OffscreenRenderer::with_color_space(
+                ShaderSource::Wgsl(shader.into()),
+                (1, 1),
+                color_space,
+            )
+            .unwrap()
+            .render(&Uniforms::default())
+            .unwrap()
+            .get_pixel(0, 0)
+            .0
+        };
+
+        // written as it is
+        assert_eq!(render(ColorSpace::Srgb)[0], 128);
+        // encoded to sRGB
+        assert_eq!(render(ColorSpace::Linear)[0], 188);
+    }
+
+    #[test]
+    #[ignore] // requires a GPU
+    #[cfg(feature = "audio-texture")]
+    fn audio_texture() {
+        let shader = format!(
+            "
+            @group({group}) @binding({texture})
+            var iAudioTexture: texture_1d<f32>;
+            @group({group}) @binding({sampler})
+            var iAudioSampler: sampler;
+
+            @fragment
+            fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {{
+                let value = textureSample(iAudioTexture, iAudioSampler, pos.x / 4.0).r;
+                return vec4<f32>(value, 0.0, 0.0, 1.0);
+            }}
+            ",
+            group = crate::BIND_GROUP_INDEX,
+            texture = crate::ResourceBinding::AudioTexture.default_binding(),
+            sampler = crate::ResourceBinding::AudioSampler.default_binding(),
+        );
+
+        // `r16float` without the feature and `r32float` with it (if the adapter supports it)
+        for features in [wgpu::Features::empty(), wgpu::Features::FLOAT32_FILTERABLE] {
+            let mut renderer = OffscreenRenderer::with_features(
+                ShaderSource::Wgsl(shader.as_str().into()),
+                (4, 1),
+                ColorSpace::Srgb,
+                features,
+            )
+            .unwrap();
+            renderer.set_audio_representation(crate::AudioRepresentation::Texture);
+            renderer.set_audio_bars(std::num::NonZero::new(2).unwrap());
+
+            let image = renderer
+                .render(&Uniforms {
+                    audio: vec![0., 1.],
+                    ..Default::default()
+                })
+                .unwrap();
+
+            // the texels are at `0.25` and `0.75`, the pixels in between are interpolated
+            let reds: Vec<u8> = image.pixels().map(|pixel| pixel.0[0]).collect();
+            for (red, expected) in reds.iter().zip([0, 64, 191, 255]) {
+                assert!(
+                    red.abs_diff(expected) <= 2,
+                    "{:?} ({:?})",
+                    reds,
+                    renderer.device.features()
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't exist")]
+    fn missing_golden_image() {
+        let golden_path = std::env::temp_dir().join("shady-missing-golden/does-not-exist.png");
+        assert_golden(&golden_path, &RgbaImage::new(1, 1), 0);
+    }
+}
@@ -0,0 +1,219 @@
+/// Describes how the output of the fragment shader is combined with the content of the render target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Overwrite the content of the render target.
+    Opaque,
+
+    /// Blend with the content of the render target according to the alpha value of the output.
+    #[default]
+    Alpha,
+
+    /// Add the output onto the content of the render target (useful for glow effects).
+    Additive,
+
+    /// Like [BlendMode::Alpha] but the color values of the output are already multiplied with their alpha value.
+    Premultiplied,
+
+    /// Mix the output with the content of the render target by the blend constant (see [crate::Shady::set_blend_constant]),
+    /// regardless of the alpha value of the output. Useful to crossfade from one pipeline to another.
+    Constant,
+}
+
+impl BlendMode {
+    /// Returns the responding blend state of [wgpu]. `None` means that no blending is done.
+    pub fn blend_state(&self) -> Option<wgpu::BlendState> {
+        match self {
+            Self::Opaque => None,
+            Self::Alpha => Some(wgpu::BlendState::ALPHA_BLENDING),
+            Self::Additive => Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            }),
+            Self::Premultiplied => Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            Self::Constant => {
+                let component = wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Constant,
+                    dst_factor: wgpu::BlendFactor::OneMinusConstant,
+                    operation: wgpu::BlendOperation::Add,
+                };
+
+                Some(wgpu::BlendState {
+                    color: component,
+                    alpha: component,
+                })
+            }
+        }
+    }
+}
+
+/// Decides in which color space the colors of the fragment shader are, see [RenderPipelineOptions::color_space].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    /// The colors are already sRGB encoded (like the ones of shadertoy), so they're written into the render target
+    /// as they are.
+    #[default]
+    Srgb,
+
+    /// The colors are linear and are encoded to sRGB when they're written into the render target.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Returns the format which the view of a render target with the given format needs, so that the colors
+    /// are displayed correctly.
+    ///
+    /// Both formats only differ by their `Srgb` suffix, so add the returned format to the `view_formats`
+    /// of the texture (or the surface configuration) and render into a view with it.
+    ///
+    /// # Example
+    /// ```
+    /// use shady::ColorSpace;
+    ///
+    /// let format = wgpu::TextureFormat::Bgra8UnormSrgb;
+    /// assert_eq!(ColorSpace::Srgb.view_format(format), wgpu::TextureFormat::Bgra8Unorm);
+    /// assert_eq!(ColorSpace::Linear.view_format(format), format);
+    /// ```
+    pub fn view_format(self, format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+        match self {
+            Self::Srgb => format.remove_srgb_suffix(),
+            Self::Linear => format.add_srgb_suffix(),
+        }
+    }
+}
+
+/// A vertex shader which replaces the fullscreen quad of `shady`, for example to draw a point cloud
+/// or instances whose positions depend on `iAudio`.
+///
+/// The vertex shader has access to the resources of `shady` (see [TemplateLang::generate_resources]).
+/// No vertex buffers are bound, so derive the vertices from `@builtin(vertex_index)` and `@builtin(instance_index)`
+/// (`gl_VertexIndex` and `gl_InstanceIndex` in glsl).
+///
+/// # Example
+/// ```ignore
+/// let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+///     label: Some("Point cloud"),
+///     source: wgpu::ShaderSource::Glsl {
+///         shader: vertex_code.into(),
+///         stage: wgpu::naga::ShaderStage::Vertex,
+///         defines: Default::default(),
+///     },
+/// });
+///
+/// let options = RenderPipelineOptions {
+///     vertex_shader: Some(VertexShader {
+///         module: &module,
+///         entry_point: "main",
+///         topology: wgpu::PrimitiveTopology::PointList,
+///         vertex_count: 1024,
+///         instance_count: 1,
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [TemplateLang::generate_resources]: crate::TemplateLang::generate_resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VertexShader<'a> {
+    pub module: &'a wgpu::ShaderModule,
+
+    /// The name of the entrypoint function of the vertex shader.
+    pub entry_point: &'a str,
+
+    /// How the vertices are assembled into primitives.
+    pub topology: wgpu::PrimitiveTopology,
+
+    /// The amount of vertices which are drawn per instance.
+    pub vertex_count: u32,
+
+    /// The amount of instances which are drawn.
+    pub instance_count: u32,
+}
+
+/// Options to configure the pipeline which is created by [crate::create_render_pipeline_with_options].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderPipelineOptions<'a> {
+    /// How the output should be combined with the content of the render target.
+    pub blend_mode: BlendMode,
+
+    /// Which faces of the fullscreen quad should be culled.
+    pub cull_mode: Option<wgpu::Face>,
+
+    /// The amount of samples per pixel. Must be supported by the texture format of the render target.
+    pub sample_count: u32,
+
+    /// The pipeline cache which should be used to speed up the creation of the pipeline.
+    /// Requires [wgpu::Features::PIPELINE_CACHE].
+    pub cache: Option<&'a wgpu::PipelineCache>,
+
+    /// Your own bind group layouts which should be part of the pipeline layout.
+    /// They are placed around the bind group of `shady` in the given order.
+    pub bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+
+    /// The index of the bind group of `shady`'s resources (`@group(<index>)` in wgsl and `set = <index>` in glsl).
+    /// Must not be greater than the amount of `bind_group_layouts`.
+    pub bind_group_index: u32,
+
+    /// Your own vertex shader instead of the fullscreen quad of `shady`.
+    pub vertex_shader: Option<VertexShader<'a>>,
+
+    /// Tests (and writes) the depth of the fragments against a depth texture, for example to composite
+    /// the shader into a 3D scene. Draw the pipeline with [crate::Shady::add_render_pass_with_depth]
+    /// and a depth texture of the same format.
+    ///
+    /// The fullscreen quad lies on the near plane (depth `0`), so write `@builtin(frag_depth)` (`gl_FragDepth` in glsl)
+    /// in your fragment shader or use your own [VertexShader] to place it within the scene.
+    pub depth_stencil: Option<&'a wgpu::DepthStencilState>,
+
+    /// The color space of the output of the fragment shader. The pipeline renders into views with the format
+    /// of [ColorSpace::view_format] of the given texture format instead, which has to be one of the
+    /// `view_formats` of the render target (and the format of [crate::Shady::set_multisample]).
+    ///
+    /// `None` uses the given texture format as it is, so the output is treated as linear for sRGB formats
+    /// and as sRGB for the other ones. That's why shaders might look washed out (or too dark) depending on the
+    /// format of the surface.
+    pub color_space: Option<ColorSpace>,
+}
+
+impl RenderPipelineOptions<'_> {
+    /// Returns the bind group layouts of the pipeline with the one of `shady` at [RenderPipelineOptions::bind_group_index].
+    pub(crate) fn pipeline_bind_group_layouts<'a>(
+        &'a self,
+        shady_layout: &'a wgpu::BindGroupLayout,
+    ) -> Vec<&'a wgpu::BindGroupLayout> {
+        let index = self.bind_group_index as usize;
+        assert!(
+            index <= self.bind_group_layouts.len(),
+            "The bind group index of shady ({}) leaves a gap since only {} other bind group layout(s) are given.",
+            index,
+            self.bind_group_layouts.len()
+        );
+
+        let mut layouts = self.bind_group_layouts.to_vec();
+        layouts.insert(index, shady_layout);
+        layouts
+    }
+}
+
+impl Default for RenderPipelineOptions<'_> {
+    fn default() -> Self {
+        Self {
+            blend_mode: BlendMode::Alpha,
+            cull_mode: Some(wgpu::Face::Back),
+            sample_count: 1,
+            cache: None,
+            bind_group_layouts: &[],
+            bind_group_index: crate::BIND_GROUP_INDEX,
+            vertex_shader: None,
+            depth_stencil: None,
+            color_space: None,
+        }
+    }
+}
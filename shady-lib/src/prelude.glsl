@@ -0,0 +1,48 @@
+// Converts a color from HSV to RGB (all components are within [0, 1]).
+vec3 hsv2rgb(vec3 c) {
+    vec3 p = abs(fract(c.xxx + vec3(1.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0);
+    return c.z * mix(vec3(1.0), clamp(p - 1.0, 0.0, 1.0), c.y);
+}
+
+// A pseudo random value within [0, 1) for the given point.
+float hash21(vec2 p) {
+    return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+// Smooth value noise within [0, 1).
+float noise(vec2 p) {
+    vec2 i = floor(p);
+    vec2 f = fract(p);
+    vec2 u = f * f * (3.0 - 2.0 * f);
+
+    float bottom = mix(hash21(i), hash21(i + vec2(1.0, 0.0)), u.x);
+    float top = mix(hash21(i + vec2(0.0, 1.0)), hash21(i + vec2(1.0, 1.0)), u.x);
+    return mix(bottom, top, u.y);
+}
+
+// Rotates a point counterclockwise by `angle` (in radians) if it's multiplied with it: `rotate2d(angle) * p`.
+mat2 rotate2d(float angle) {
+    float c = cos(angle);
+    float s = sin(angle);
+    return mat2(c, s, -s, c);
+}
+
+// Signed distance functions: The distance of `p` to the shape which is centered at the origin
+// (negative inside of the shape).
+float sdCircle(vec2 p, float radius) {
+    return length(p) - radius;
+}
+
+float sdBox(vec2 p, vec2 half_size) {
+    vec2 d = abs(p) - half_size;
+    return length(max(d, vec2(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+float sdSphere(vec3 p, float radius) {
+    return length(p) - radius;
+}
+
+float sdBox3(vec3 p, vec3 half_size) {
+    vec3 d = abs(p) - half_size;
+    return length(max(d, vec3(0.0))) + min(max(d.x, max(d.y, d.z)), 0.0);
+}
@@ -0,0 +1,48 @@
+// Converts a color from HSV to RGB (all components are within [0, 1]).
+fn hsv2rgb(c: vec3<f32>) -> vec3<f32> {
+    let p = abs(fract(c.xxx + vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0);
+    return c.z * mix(vec3<f32>(1.0), clamp(p - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)), c.y);
+}
+
+// A pseudo random value within [0, 1) for the given point.
+fn hash21(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+// Smooth value noise within [0, 1).
+fn noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+
+    let bottom = mix(hash21(i), hash21(i + vec2<f32>(1.0, 0.0)), u.x);
+    let top = mix(hash21(i + vec2<f32>(0.0, 1.0)), hash21(i + vec2<f32>(1.0, 1.0)), u.x);
+    return mix(bottom, top, u.y);
+}
+
+// Rotates a point counterclockwise by `angle` (in radians) if it's multiplied with it: `rotate2d(angle) * p`.
+fn rotate2d(angle: f32) -> mat2x2<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return mat2x2<f32>(c, s, -s, c);
+}
+
+// Signed distance functions: The distance of `p` to the shape which is centered at the origin
+// (negative inside of the shape).
+fn sdCircle(p: vec2<f32>, radius: f32) -> f32 {
+    return length(p) - radius;
+}
+
+fn sdBox(p: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let d = abs(p) - half_size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+fn sdSphere(p: vec3<f32>, radius: f32) -> f32 {
+    return length(p) - radius;
+}
+
+fn sdBox3(p: vec3<f32>, half_size: vec3<f32>) -> f32 {
+    let d = abs(p) - half_size;
+    return length(max(d, vec3<f32>(0.0))) + min(max(d.x, max(d.y, d.z)), 0.0);
+}
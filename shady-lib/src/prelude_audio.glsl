@@ -0,0 +1,24 @@
+
+// The average of `iAudio` within the relative frequency range [lo, hi] (0 is the lowest and 1 the highest frequency).
+// For example `audioAvg(0.0, 0.1)` for the bass.
+float audioAvg(float lo, float hi) {
+    int amount_bars = freqs.length();
+    int start = min(int(clamp(lo, 0.0, 1.0) * float(amount_bars)), amount_bars - 1);
+    int end = max(int(clamp(hi, 0.0, 1.0) * float(amount_bars)), start + 1);
+
+    float sum = 0.0;
+    for (int bar = start; bar < end; bar++) {
+        sum += freqs[bar];
+    }
+    return sum / float(end - start);
+}
+
+// `iAudio` at the relative position `x` within [0, 1], linearly interpolated between the bars.
+float audioAt(float x) {
+    int amount_bars = freqs.length();
+    float pos = clamp(x, 0.0, 1.0) * float(amount_bars - 1);
+    int bar = min(int(pos), amount_bars - 1);
+    int next = min(bar + 1, amount_bars - 1);
+
+    return mix(freqs[bar], freqs[next], fract(pos));
+}
@@ -0,0 +1,24 @@
+
+// The average of `iAudio` within the relative frequency range [lo, hi] (0 is the lowest and 1 the highest frequency).
+// For example `audioAvg(0.0, 0.1)` for the bass.
+fn audioAvg(lo: f32, hi: f32) -> f32 {
+    let amount_bars = arrayLength(&iAudio);
+    let start = min(u32(clamp(lo, 0.0, 1.0) * f32(amount_bars)), amount_bars - 1u);
+    let end = max(u32(clamp(hi, 0.0, 1.0) * f32(amount_bars)), start + 1u);
+
+    var sum = 0.0;
+    for (var bar = start; bar < end; bar++) {
+        sum += iAudio[bar];
+    }
+    return sum / f32(end - start);
+}
+
+// `iAudio` at the relative position `x` within [0, 1], linearly interpolated between the bars.
+fn audioAt(x: f32) -> f32 {
+    let amount_bars = arrayLength(&iAudio);
+    let pos = clamp(x, 0.0, 1.0) * f32(amount_bars - 1u);
+    let bar = min(u32(pos), amount_bars - 1u);
+    let next = min(bar + 1u, amount_bars - 1u);
+
+    return mix(iAudio[bar], iAudio[next], fract(pos));
+}
@@ -1,6 +1,17 @@
-use std::{fmt, num::NonZero, ops::Range};
+use std::{
+    cell::{RefCell, RefMut},
+    fmt,
+    num::NonZero,
+    ops::Range,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use shady_audio::{BarProcessor, BarProcessorConfig, SampleProcessor};
+use shady_audio::{
+    fetcher::{DummyFetcher, SystemAudioFetcher, SystemAudioFetcherDescriptor},
+    BarProcessor, BarProcessorConfig, PostProcessor, SampleProcessor,
+};
+use tracing::warn;
 use wgpu::Device;
 
 use crate::{template::TemplateGenerator, ShadyDescriptor};
@@ -8,70 +19,721 @@ use crate::{template::TemplateGenerator, ShadyDescriptor};
 use super::Resource;
 
 const DEFAULT_AMOUNT_BARS: usize = 60;
+const DEFAULT_BAR_TRANSITION: Duration = Duration::from_millis(250);
 const DESCRIPTION: &str = "\
 // It contains the 'presence' of a frequency. The lower the index the lower is its frequency and the other way round.
 // So for example, if you are interested in the bass, choose the lower indices.";
+#[cfg(feature = "slow-audio")]
+const SLOW_DESCRIPTION: &str = "\
+// The values of `iAudio` averaged over a few seconds. Useful for slowly evolving colors or backgrounds.";
+#[cfg(feature = "audio-peaks")]
+const PEAKS_DESCRIPTION: &str = "\
+// The peak of each bar of `iAudio`: It stays at the highest value for a moment and falls down slowly afterwards.
+// Draw it as a small cap above the bar.";
+#[cfg(feature = "audio-widths")]
+const WIDTHS_DESCRIPTION: &str = "\
+// The relative width of each bar of `iAudio` (all widths sum up to 1). It's proportional to the octaves which the bar spans,
+// so the bass bars are usually wider. Bar `i` starts at the sum of the widths of the bars before it, for example:
+//
+//     var start = 0.;
+//     var bar = 0u;
+//     while (bar + 1u < arrayLength(&iAudioWidths) && start + iAudioWidths[bar] < uv.x) {
+//         start += iAudioWidths[bar];
+//         bar += 1u;
+//     }
+//     let presence = iAudio[bar];";
+#[cfg(feature = "audio-widths")]
+const GLSL_WIDTHS_DESCRIPTION: &str = "\
+// The relative width of each bar of `iAudio` (all widths sum up to 1). It's proportional to the octaves which the bar spans,
+// so the bass bars are usually wider. Bar `i` starts at the sum of the widths of the bars before it, for example:
+//
+//     float start = 0.;
+//     int bar = 0;
+//     while (bar + 1 < widths.length() && start + widths[bar] < uv.x) {
+//         start += widths[bar];
+//         bar++;
+//     }
+//     float presence = freqs[bar];";
 
-pub struct Audio {
-    bar_processor: BarProcessor,
+#[cfg(feature = "audio-texture")]
+const TEXTURE_DESCRIPTION: &str = "\
+// The bars as a texture if they are uploaded into it instead of `iAudio` (see the audio representation of shady).
+// Sampling it interpolates linearly between the bars, for example
+// `textureSample(iAudioTexture, iAudioSampler, uv.x).r` where `0` is the lowest and `1` the highest frequency.";
+#[cfg(feature = "audio-texture")]
+const GLSL_TEXTURE_DESCRIPTION: &str = "\
+// The bars as a texture if they are uploaded into it instead of `iAudio` (see the audio representation of shady).
+// Sampling it interpolates linearly between the bars, for example
+// `texture(sampler1D(iAudioTexture, iAudioSampler), uv.x).r` where `0` is the lowest and `1` the highest frequency.";
+
+/// Where the bars are uploaded to (see [Shady::set_audio_representation]).
+///
+/// The bindings of both exist in the bind group regardless of the representation, so the pipelines don't depend on it.
+/// The texture has only a single texel while the bars are uploaded into the storage buffer.
+///
+/// [Shady::set_audio_representation]: crate::Shady::set_audio_representation
+#[cfg(feature = "audio-texture")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioRepresentation {
+    /// The bars are uploaded into the storage buffer `iAudio`.
+    #[default]
+    StorageBuffer,
+
+    /// The bars are uploaded into the 1D texture `iAudioTexture`, `iAudio` isn't updated.
+    ///
+    /// Sampling the texture with `iAudioSampler` interpolates linearly between the bars.
+    Texture,
+}
 
+struct AudioState {
+    bar_processor: BarProcessor,
     bar_values: Box<[f32]>,
 
+    // the batch of the sample processor which got processed the last time
+    last_batch: Option<u64>,
+    freq_animation: Option<FrequencyAnimation>,
+    bar_transition: Option<BarTransition>,
+    bar_transition_duration: Duration,
+
+    #[cfg(feature = "slow-audio")]
+    slow_spectrum: shady_audio::SlowSpectrum,
+    #[cfg(feature = "audio-peaks")]
+    peak_hold: shady_audio::PeakHold,
+    // the time when the bars have been computed the last time
+    #[cfg(any(feature = "slow-audio", feature = "audio-peaks"))]
+    last_update: Option<Instant>,
+    // `None` if the layout of the bars has changed since they have been computed
+    #[cfg(feature = "audio-widths")]
+    bar_widths: Option<Box<[f32]>>,
+}
+
+/// Interpolates the frequency range of the bars from `from` to `to`.
+struct FrequencyAnimation {
+    from: Range<NonZero<u16>>,
+    to: Range<NonZero<u16>>,
+    start: Instant,
+    duration: Duration,
+}
+
+impl FrequencyAnimation {
+    /// Returns the frequency range after `elapsed` and whether the animation has finished.
+    fn range_at(&self, elapsed: Duration) -> (Range<NonZero<u16>>, bool) {
+        let progress = if self.duration.is_zero() {
+            1.
+        } else {
+            (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.)
+        };
+        // ease in and out
+        let t = progress * progress * (3. - 2. * progress);
+
+        let start = lerp_frequency(self.from.start, self.to.start, t);
+        let end = lerp_frequency(self.from.end, self.to.end, t).max(start.saturating_add(1));
+
+        (start..end, progress >= 1.)
+    }
+}
+
+/// Fades from the bars before the amount of bars has changed (resampled to the new amount) to the new bars,
+/// since the bar processor starts from zero with the new amount of bars.
+struct BarTransition {
+    from: Box<[f32]>,
+    start: Instant,
+    duration: Duration,
+}
+
+impl BarTransition {
+    /// Blends the previous bars into `bars` according to `elapsed` and returns whether the transition has finished.
+    fn apply(&self, elapsed: Duration, bars: &mut [f32]) -> bool {
+        let progress = if self.duration.is_zero() {
+            1.
+        } else {
+            (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.)
+        };
+
+        for (bar, from) in bars.iter_mut().zip(self.from.iter()) {
+            *bar = from + (*bar - from) * progress;
+        }
+
+        progress >= 1.
+    }
+}
+
+/// Stretches (or squeezes) `values` linearly to `len` values.
+fn resample(values: &[f32], len: usize) -> Box<[f32]> {
+    if values.is_empty() || len <= 1 {
+        return vec![values.first().copied().unwrap_or(0.); len].into_boxed_slice();
+    }
+
+    let step = (values.len() - 1) as f32 / (len - 1) as f32;
+    (0..len)
+        .map(|idx| {
+            let pos = idx as f32 * step;
+            let left = (pos.floor() as usize).min(values.len() - 1);
+            let right = (left + 1).min(values.len() - 1);
+
+            values[left] + (values[right] - values[left]) * pos.fract()
+        })
+        .collect()
+}
+
+// Interpolates logarithmically, like we perceive frequencies.
+fn lerp_frequency(from: NonZero<u16>, to: NonZero<u16>, t: f32) -> NonZero<u16> {
+    let from = f32::from(from.get()).ln();
+    let to = f32::from(to.get()).ln();
+
+    let freq = (from + (to - from) * t).exp().round();
+    NonZero::new(freq.clamp(1., f32::from(u16::MAX)) as u16).expect("Frequency is at least one")
+}
+
+/// Creates a [SampleProcessor] which fetches the audio of the default output device.
+///
+/// Falls back to silence if the system doesn't have any usable output device (for example on headless or CI machines),
+/// so `iAudio` just stays zero instead of panicking.
+pub fn default_sample_processor() -> SampleProcessor {
+    let fetcher =
+        SystemAudioFetcherDescriptor::try_default().and_then(|desc| SystemAudioFetcher::new(&desc));
+
+    match fetcher {
+        Ok(fetcher) => SampleProcessor::new(fetcher),
+        Err(err) => {
+            warn!(
+                "Couldn't fetch the system audio, falling back to silence: {}",
+                err
+            );
+            SampleProcessor::new(DummyFetcher::new(1))
+        }
+    }
+}
+
+/// A handle to the audio state (the bar processor and its latest bar values) of [Shady].
+///
+/// Cloning the handle is cheap and every clone refers to the same audio state.
+/// Pass a clone into the [ShadyDescriptor] of each [Shady] instance which should share the
+/// same bars. The bars are only computed once per batch of the [SampleProcessor], regardless of how many
+/// [Shady] instances are calling [Shady::update_audio_buffer].
+///
+/// # Example
+/// ```ignore
+/// let audio = AudioHandle::new(&sample_processor);
+///
+/// let shady1 = Shady::new(ShadyDescriptor {
+///     device: &device,
+///     sample_processor: &sample_processor,
+///     audio_handle: Some(audio.clone()),
+///     clock: None,
+/// })?;
+///
+/// let shady2 = Shady::new(ShadyDescriptor {
+///     device: &device,
+///     sample_processor: &sample_processor,
+///     audio_handle: Some(audio),
+///     clock: None,
+/// })?;
+/// ```
+///
+/// [Shady]: crate::Shady
+/// [Shady::update_audio_buffer]: crate::Shady::update_audio_buffer
+#[derive(Clone)]
+pub struct AudioHandle(Rc<RefCell<AudioState>>);
+
+impl AudioHandle {
+    /// Creates a new audio state with the default settings of [Shady].
+    ///
+    /// [Shady]: crate::Shady
+    pub fn new(sample_processor: &SampleProcessor) -> Self {
+        Self::with_config(
+            sample_processor,
+            BarProcessorConfig {
+                amount_bars: NonZero::new(DEFAULT_AMOUNT_BARS as u16).unwrap(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new audio state with the given config for the bar processor.
+    pub fn with_config(sample_processor: &SampleProcessor, config: BarProcessorConfig) -> Self {
+        let bar_values = vec![0.; usize::from(config.amount_bars.get())].into_boxed_slice();
+        let bar_processor = BarProcessor::new(sample_processor, config);
+
+        Self(Rc::new(RefCell::new(AudioState {
+            bar_processor,
+            bar_values,
+            last_batch: None,
+            freq_animation: None,
+            bar_transition: None,
+            bar_transition_duration: DEFAULT_BAR_TRANSITION,
+            #[cfg(feature = "slow-audio")]
+            slow_spectrum: shady_audio::SlowSpectrum::default(),
+            #[cfg(feature = "audio-peaks")]
+            peak_hold: shady_audio::PeakHold::default(),
+            #[cfg(any(feature = "slow-audio", feature = "audio-peaks"))]
+            last_update: None,
+            #[cfg(feature = "audio-widths")]
+            bar_widths: None,
+        })))
+    }
+
+    /// Returns the amount of bars which are currently computed.
+    pub fn amount_bars(&self) -> NonZero<u16> {
+        self.state().bar_processor.config().amount_bars
+    }
+
+    /// Registers a function which shapes the bar values before they are written into `iAudio`.
+    /// See [PostProcessor] for more information.
+    pub fn set_post_fn(&self, post_fn: impl PostProcessor + 'static) {
+        self.state().bar_processor.set_post_fn(post_fn);
+    }
+
+    /// Overwrites the bar values with the given values.
+    ///
+    /// Values beyond the amount of bars are ignored and missing values are set to `0`.
+    pub fn set_values(&self, values: &[f32]) {
+        let mut state = self.state();
+
+        let amount_values = values.len().min(state.bar_values.len());
+        state.bar_values[..amount_values].copy_from_slice(&values[..amount_values]);
+        state.bar_values[amount_values..].fill(0.);
+    }
+
+    /// Returns the current bar values.
+    pub fn values(&self) -> Box<[f32]> {
+        self.state().bar_values.clone()
+    }
+
+    /// Computes the bar values of the current batch of `sample_processor`.
+    /// Does nothing if the batch has already been processed.
+    pub fn process_bars(&self, sample_processor: &SampleProcessor) {
+        let mut state = self.state();
+        let batch = sample_processor.processed_batches();
+
+        if state.last_batch == Some(batch) {
+            return;
+        }
+
+        let AudioState {
+            bar_processor,
+            bar_values,
+            freq_animation,
+            bar_transition,
+            #[cfg(feature = "audio-widths")]
+            bar_widths,
+            ..
+        } = &mut *state;
+
+        if let Some(animation) = freq_animation {
+            let (freq_range, finished) = animation.range_at(animation.start.elapsed());
+
+            if bar_processor.config().freq_range != freq_range {
+                bar_processor.set_freq_range(freq_range);
+                #[cfg(feature = "audio-widths")]
+                {
+                    *bar_widths = None;
+                }
+            }
+            if finished {
+                *freq_animation = None;
+            }
+        }
+
+        let bars = bar_processor.process_bars(sample_processor);
+        bar_values.copy_from_slice(&bars[0]);
+
+        if let Some(transition) = bar_transition {
+            if transition.apply(transition.start.elapsed(), bar_values) {
+                *bar_transition = None;
+            }
+        }
+        state.last_batch = Some(batch);
+
+        #[cfg(any(feature = "slow-audio", feature = "audio-peaks"))]
+        {
+            let now = Instant::now();
+            let delta_time = state
+                .last_update
+                .replace(now)
+                .map_or(Duration::ZERO, |last_update| now - last_update);
+
+            let AudioState {
+                #[cfg(feature = "slow-audio")]
+                slow_spectrum,
+                #[cfg(feature = "audio-peaks")]
+                peak_hold,
+                bar_values,
+                ..
+            } = &mut *state;
+
+            #[cfg(feature = "slow-audio")]
+            slow_spectrum.update(bar_values, delta_time);
+            #[cfg(feature = "audio-peaks")]
+            peak_hold.update(bar_values, delta_time);
+        }
+    }
+
+    /// Changes the amount of bars. The bars of the previous layout fade into the new ones
+    /// (see [AudioHandle::set_bar_transition]).
+    pub fn set_amount_bars(&self, amount_bars: NonZero<u16>) {
+        let mut state = self.state();
+        if state.bar_processor.config().amount_bars == amount_bars {
+            return;
+        }
+
+        let amount_bars_usize = usize::from(amount_bars.get());
+        state.bar_processor.set_amount_bars(amount_bars);
+        #[cfg(feature = "audio-widths")]
+        {
+            state.bar_widths = None;
+        }
+
+        let duration = state.bar_transition_duration;
+        if duration.is_zero() {
+            state.bar_values = vec![0.; amount_bars_usize].into_boxed_slice();
+            state.bar_transition = None;
+        } else {
+            let from = resample(&state.bar_values, amount_bars_usize);
+            state.bar_values = from.clone();
+            state.bar_transition = Some(BarTransition {
+                from,
+                start: Instant::now(),
+                duration,
+            });
+        }
+
+        #[cfg(feature = "slow-audio")]
+        {
+            let values = resample(state.slow_spectrum.values(), amount_bars_usize);
+            // an infinite time step jumps to the given values
+            state.slow_spectrum.update(&values, Duration::MAX);
+        }
+
+        #[cfg(feature = "audio-peaks")]
+        {
+            let values = resample(state.peak_hold.values(), amount_bars_usize);
+            state.peak_hold.update(&values, Duration::ZERO);
+        }
+    }
+
+    /// Sets how long the bars fade from the previous amount of bars to the new one after
+    /// [AudioHandle::set_amount_bars]. [Duration::ZERO] lets them restart from zero.
+    pub fn set_bar_transition(&self, duration: Duration) {
+        self.state().bar_transition_duration = duration;
+    }
+
+    /// Sets how slowly the averaged bars follow the bars (see [shady_audio::SlowSpectrum]).
+    #[cfg(feature = "slow-audio")]
+    pub fn set_slow_time_constant(&self, time_constant: Duration) {
+        self.state().slow_spectrum.set_time_constant(time_constant);
+    }
+
+    /// Sets how long the peaks are kept and how fast they fall afterwards (see [shady_audio::PeakHold]).
+    #[cfg(feature = "audio-peaks")]
+    pub fn set_peak_timing(&self, hold_time: Duration, fall_speed: f32) {
+        self.state().peak_hold.set_timing(hold_time, fall_speed);
+    }
+
+    /// Returns the peaks of the bars (see [shady_audio::PeakHold]).
+    #[cfg(feature = "audio-peaks")]
+    pub fn peaks(&self) -> Box<[f32]> {
+        self.state().peak_hold.values().into()
+    }
+
+    /// Smoothly changes the frequency range of the bars to `freq_range` within `duration`.
+    /// The range is updated whenever the bars are computed.
+    ///
+    /// Replaces a running animation which continues from its current range.
+    pub fn animate_frequency_range(&self, freq_range: Range<NonZero<u16>>, duration: Duration) {
+        let mut state = self.state();
+
+        let from = state.bar_processor.config().freq_range.clone();
+        state.freq_animation = Some(FrequencyAnimation {
+            from,
+            to: freq_range,
+            start: Instant::now(),
+            duration,
+        });
+    }
+
+    /// Returns the relative width of each bar (see [BarProcessor::bar_widths]).
+    #[cfg(feature = "audio-widths")]
+    pub fn bar_widths(&self) -> Box<[f32]> {
+        let mut state = self.state();
+        let AudioState {
+            bar_processor,
+            bar_widths,
+            ..
+        } = &mut *state;
+
+        bar_widths
+            .get_or_insert_with(|| bar_processor.bar_widths())
+            .clone()
+    }
+
+    fn state(&self) -> RefMut<'_, AudioState> {
+        self.0.borrow_mut()
+    }
+}
+
+pub struct Audio {
+    handle: AudioHandle,
+
     buffer: wgpu::Buffer,
+    #[cfg(feature = "slow-audio")]
+    slow_buffer: wgpu::Buffer,
+    #[cfg(feature = "audio-peaks")]
+    peaks_buffer: wgpu::Buffer,
+    #[cfg(feature = "audio-widths")]
+    widths_buffer: wgpu::Buffer,
+    #[cfg(feature = "audio-texture")]
+    texture: wgpu::Texture,
+    #[cfg(feature = "audio-texture")]
+    texture_view: wgpu::TextureView,
+    #[cfg(feature = "audio-texture")]
+    sampler: wgpu::Sampler,
+    #[cfg(feature = "audio-texture")]
+    representation: AudioRepresentation,
 }
 
 impl Audio {
     pub fn fetch_audio(&mut self, sample_processor: &SampleProcessor) {
-        let bars = self.bar_processor.process_bars(sample_processor);
-        self.bar_values.copy_from_slice(&bars[0]);
+        self.handle.process_bars(sample_processor);
+    }
+
+    pub fn set_post_fn(&mut self, post_fn: impl PostProcessor + 'static) {
+        self.handle.set_post_fn(post_fn);
+    }
+
+    pub fn set_values(&mut self, values: &[f32]) {
+        self.handle.set_values(values);
+    }
+
+    pub fn values(&self) -> Box<[f32]> {
+        self.handle.values()
+    }
+
+    pub fn animate_frequency_range(&mut self, freq_range: Range<NonZero<u16>>, duration: Duration) {
+        self.handle.animate_frequency_range(freq_range, duration);
+    }
+
+    #[cfg(feature = "slow-audio")]
+    pub fn set_slow_time_constant(&mut self, time_constant: Duration) {
+        self.handle.set_slow_time_constant(time_constant);
+    }
+
+    pub fn set_bar_transition(&mut self, duration: Duration) {
+        self.handle.set_bar_transition(duration);
+    }
+
+    #[cfg(feature = "audio-peaks")]
+    pub fn set_peak_timing(&mut self, hold_time: Duration, fall_speed: f32) {
+        self.handle.set_peak_timing(hold_time, fall_speed);
+    }
+
+    /// Writes `values` to the start of `buffer`.
+    fn write_values(&self, queue: &wgpu::Queue, buffer: &wgpu::Buffer, values: &[f32]) {
+        queue.write_buffer(buffer, 0, bytemuck::cast_slice(values));
+    }
+
+    #[cfg(feature = "slow-audio")]
+    pub fn slow_binding() -> u32 {
+        super::ResourceBinding::AudioSlow as u32
+    }
+
+    #[cfg(feature = "slow-audio")]
+    pub fn slow_buffer(&self) -> &wgpu::Buffer {
+        &self.slow_buffer
+    }
+
+    #[cfg(feature = "audio-peaks")]
+    pub fn peaks_binding() -> u32 {
+        super::ResourceBinding::AudioPeaks as u32
+    }
+
+    #[cfg(feature = "audio-peaks")]
+    pub fn peaks_buffer(&self) -> &wgpu::Buffer {
+        &self.peaks_buffer
+    }
+
+    #[cfg(feature = "audio-widths")]
+    pub fn widths_binding() -> u32 {
+        super::ResourceBinding::AudioWidths as u32
+    }
+
+    #[cfg(feature = "audio-widths")]
+    pub fn widths_buffer(&self) -> &wgpu::Buffer {
+        &self.widths_buffer
+    }
+
+    #[cfg(feature = "audio-texture")]
+    pub fn texture_binding() -> u32 {
+        super::ResourceBinding::AudioTexture as u32
+    }
+
+    #[cfg(feature = "audio-texture")]
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.texture_view
+    }
+
+    #[cfg(feature = "audio-texture")]
+    pub fn sampler_binding() -> u32 {
+        super::ResourceBinding::AudioSampler as u32
+    }
+
+    #[cfg(feature = "audio-texture")]
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    #[cfg(feature = "audio-texture")]
+    pub fn set_representation(&mut self, device: &Device, representation: AudioRepresentation) {
+        self.representation = representation;
+        self.texture = create_texture(device, self.texture_width());
+        self.texture_view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+    }
+
+    /// The texture only needs one texel per bar if the bars are uploaded into it.
+    #[cfg(feature = "audio-texture")]
+    fn texture_width(&self) -> u32 {
+        match self.representation {
+            AudioRepresentation::StorageBuffer => 1,
+            AudioRepresentation::Texture => u32::from(self.handle.amount_bars().get()),
+        }
+    }
+
+    #[cfg(feature = "audio-texture")]
+    fn write_texture(&self, queue: &wgpu::Queue, values: &[f32]) {
+        let width = self.texture.width().min(values.len() as u32);
+        let values = &values[..width as usize];
+
+        let half_values: Vec<u16>;
+        let data: &[u8] = match self.texture.format() {
+            wgpu::TextureFormat::R32Float => bytemuck::cast_slice(values),
+            _ => {
+                half_values = values
+                    .iter()
+                    .map(|&value| half::f16::from_f32(value).to_bits())
+                    .collect();
+                bytemuck::cast_slice(&half_values)
+            }
+        };
+
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: None,
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
     }
 
     pub fn set_bars(&mut self, device: &Device, amount_bars: NonZero<u16>) {
-        self.bar_processor.set_amount_bars(amount_bars);
-        self.bar_values = vec![0.; usize::from(u16::from(amount_bars) - 8)].into_boxed_slice();
+        // does nothing if another instance which shares the handle has already set it
+        self.handle.set_amount_bars(amount_bars);
 
         self.buffer = Self::create_storage_buffer(
             device,
             (std::mem::size_of::<f32>() * usize::from(u16::from(amount_bars))) as u64,
         );
+        #[cfg(feature = "slow-audio")]
+        {
+            self.slow_buffer = Self::create_storage_buffer(device, self.buffer.size());
+        }
+        #[cfg(feature = "audio-peaks")]
+        {
+            self.peaks_buffer = Self::create_storage_buffer(device, self.buffer.size());
+        }
+        #[cfg(feature = "audio-widths")]
+        {
+            self.widths_buffer = Self::create_storage_buffer(device, self.buffer.size());
+        }
+        #[cfg(feature = "audio-texture")]
+        {
+            self.texture = create_texture(device, self.texture_width());
+            self.texture_view = self
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+        }
     }
 
+    // `unused`: The bars are reconfigured in place, the sample processor is kept for API compatibility.
+    #[allow(unused)]
     pub fn set_frequency_range(
         &mut self,
         sample_processor: &SampleProcessor,
         freq_range: Range<NonZero<u16>>,
     ) {
-        self.bar_processor = BarProcessor::new(
-            sample_processor,
-            BarProcessorConfig {
-                freq_range,
-                ..self.bar_processor.config().clone()
-            },
-        );
+        let mut state = self.handle.state();
+        state.freq_animation = None;
+
+        // keeps the current bar values instead of letting them restart from zero
+        state.bar_processor.set_freq_range(freq_range);
+        #[cfg(feature = "audio-widths")]
+        {
+            state.bar_widths = None;
+        }
     }
 }
 
 impl Resource for Audio {
     fn new(desc: &ShadyDescriptor) -> Self {
+        let handle = desc
+            .audio_handle
+            .clone()
+            .unwrap_or_else(|| AudioHandle::new(desc.sample_processor));
+
         let buffer = Self::create_storage_buffer(
             desc.device,
-            std::mem::size_of::<[f32; DEFAULT_AMOUNT_BARS]>() as u64,
-        );
-
-        let bar_processor = BarProcessor::new(
-            desc.sample_processor,
-            BarProcessorConfig {
-                amount_bars: NonZero::new(DEFAULT_AMOUNT_BARS as u16).unwrap(),
-                ..Default::default()
-            },
+            (std::mem::size_of::<f32>() * usize::from(handle.amount_bars().get())) as u64,
         );
-
-        let audio_buffer = Box::new([0.; DEFAULT_AMOUNT_BARS]);
+        #[cfg(feature = "slow-audio")]
+        let slow_buffer = Self::create_storage_buffer(desc.device, buffer.size());
+        #[cfg(feature = "audio-peaks")]
+        let peaks_buffer = Self::create_storage_buffer(desc.device, buffer.size());
+        #[cfg(feature = "audio-widths")]
+        let widths_buffer = Self::create_storage_buffer(desc.device, buffer.size());
+        // the bars are uploaded into the storage buffer by default
+        #[cfg(feature = "audio-texture")]
+        let texture = create_texture(desc.device, 1);
+        #[cfg(feature = "audio-texture")]
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        #[cfg(feature = "audio-texture")]
+        let sampler = desc.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shady iAudioSampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
 
         Self {
-            bar_processor,
-            bar_values: audio_buffer,
+            handle,
             buffer,
+            #[cfg(feature = "slow-audio")]
+            slow_buffer,
+            #[cfg(feature = "audio-peaks")]
+            peaks_buffer,
+            #[cfg(feature = "audio-widths")]
+            widths_buffer,
+            #[cfg(feature = "audio-texture")]
+            texture,
+            #[cfg(feature = "audio-texture")]
+            texture_view,
+            #[cfg(feature = "audio-texture")]
+            sampler,
+            #[cfg(feature = "audio-texture")]
+            representation: AudioRepresentation::default(),
         }
     }
 
@@ -88,11 +750,53 @@ impl Resource for Audio {
     }
 
     fn binding() -> u32 {
-        super::BindingValue::Audio as u32
+        super::ResourceBinding::Audio as u32
     }
 
     fn update_buffer(&self, queue: &wgpu::Queue) {
-        queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&self.bar_values));
+        // the widths only change with the layout of the bars but another instance could have changed it
+        #[cfg(feature = "audio-widths")]
+        {
+            let widths = self.handle.bar_widths();
+            let amount_values = widths
+                .len()
+                .min(self.widths_buffer.size() as usize / std::mem::size_of::<f32>());
+            self.write_values(queue, &self.widths_buffer, &widths[..amount_values]);
+        }
+
+        let state = self.handle.state();
+
+        // the handle could have been resized by another instance in the meantime
+        let amount_values = state
+            .bar_values
+            .len()
+            .min(self.buffer.size() as usize / std::mem::size_of::<f32>());
+
+        #[cfg(feature = "audio-texture")]
+        match self.representation {
+            AudioRepresentation::StorageBuffer => {
+                self.write_values(queue, self.buffer(), &state.bar_values[..amount_values])
+            }
+            AudioRepresentation::Texture => {
+                self.write_texture(queue, &state.bar_values[..amount_values])
+            }
+        }
+        #[cfg(not(feature = "audio-texture"))]
+        self.write_values(queue, self.buffer(), &state.bar_values[..amount_values]);
+
+        #[cfg(feature = "slow-audio")]
+        {
+            let slow_values = state.slow_spectrum.values();
+            let amount_values = slow_values.len().min(amount_values);
+            self.write_values(queue, &self.slow_buffer, &slow_values[..amount_values]);
+        }
+
+        #[cfg(feature = "audio-peaks")]
+        {
+            let peaks = state.peak_hold.values();
+            let amount_values = peaks.len().min(amount_values);
+            self.write_values(queue, &self.peaks_buffer, &peaks[..amount_values]);
+        }
     }
 }
 
@@ -110,19 +814,358 @@ var<storage, read> iAudio: array<f32>;
             DESCRIPTION,
             bind_group_index,
             Self::binding(),
-        ))
+        ))?;
+
+        #[cfg(feature = "slow-audio")]
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read> iAudioSlow: array<f32>;
+",
+            SLOW_DESCRIPTION,
+            bind_group_index,
+            Self::slow_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-peaks")]
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read> iAudioPeaks: array<f32>;
+",
+            PEAKS_DESCRIPTION,
+            bind_group_index,
+            Self::peaks_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-widths")]
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read> iAudioWidths: array<f32>;
+",
+            WIDTHS_DESCRIPTION,
+            bind_group_index,
+            Self::widths_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-texture")]
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var iAudioTexture: texture_1d<f32>;
+@group({}) @binding({})
+var iAudioSampler: sampler;
+",
+            TEXTURE_DESCRIPTION,
+            bind_group_index,
+            Self::texture_binding(),
+            bind_group_index,
+            Self::sampler_binding(),
+        ))?;
+
+        Ok(())
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         writer.write_fmt(format_args!(
             "
 {}
-layout(binding = {}) readonly buffer iAudio {{
+layout(set = {}, binding = {}) readonly buffer iAudio {{
     float[] freqs;
 }};
 ",
             DESCRIPTION,
+            bind_group_index,
             Self::binding(),
-        ))
+        ))?;
+
+        #[cfg(feature = "slow-audio")]
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) readonly buffer iAudioSlow {{
+    float[] slowFreqs;
+}};
+",
+            SLOW_DESCRIPTION,
+            bind_group_index,
+            Self::slow_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-peaks")]
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) readonly buffer iAudioPeaks {{
+    float[] peaks;
+}};
+",
+            PEAKS_DESCRIPTION,
+            bind_group_index,
+            Self::peaks_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-widths")]
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) readonly buffer iAudioWidths {{
+    float[] widths;
+}};
+",
+            GLSL_WIDTHS_DESCRIPTION,
+            bind_group_index,
+            Self::widths_binding(),
+        ))?;
+
+        #[cfg(feature = "audio-texture")]
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) uniform texture1D iAudioTexture;
+layout(set = {}, binding = {}) uniform sampler iAudioSampler;
+",
+            GLSL_TEXTURE_DESCRIPTION,
+            bind_group_index,
+            Self::texture_binding(),
+            bind_group_index,
+            Self::sampler_binding(),
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// Creates the texture of `iAudioTexture` with the given width (as far as the limits of the device allow it).
+///
+/// `r32float` textures can only be sampled linearly with [wgpu::Features::FLOAT32_FILTERABLE],
+/// so `r16float` is used if the device doesn't support it.
+#[cfg(feature = "audio-texture")]
+fn create_texture(device: &Device, width: u32) -> wgpu::Texture {
+    let format = if device
+        .features()
+        .contains(wgpu::Features::FLOAT32_FILTERABLE)
+    {
+        wgpu::TextureFormat::R32Float
+    } else {
+        wgpu::TextureFormat::R16Float
+    };
+
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Shady iAudioTexture"),
+        size: wgpu::Extent3d {
+            width: width.min(device.limits().max_texture_dimension_1d),
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D1,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+/// Returns how many bars with a width of `bar_px` and a gap of `gap_px` between them fit into `pixel_width`.
+pub fn amount_bars_for_width(pixel_width: u32, bar_px: f32, gap_px: f32) -> NonZero<u16> {
+    let gap_px = gap_px.max(0.);
+    // there's one gap less than bars
+    let amount = (pixel_width as f32 + gap_px) / (bar_px.max(1.) + gap_px);
+
+    NonZero::new(amount.floor().clamp(1., f32::from(u16::MAX)) as u16)
+        .expect("The amount of bars is at least one")
+}
+
+#[cfg(test)]
+mod tests {
+    use shady_audio::fetcher::DummyFetcher;
+
+    use super::*;
+
+    #[test]
+    fn shared_handle_processes_batch_only_once() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        let handle = AudioHandle::new(&sample_processor);
+        let handle2 = handle.clone();
+
+        // counts how often the bar processor computed the bars
+        let processed = Rc::new(std::cell::Cell::new(0));
+        {
+            let processed = processed.clone();
+            handle.set_post_fn(move |_bars: &mut [f32]| processed.set(processed.get() + 1));
+        }
+
+        sample_processor.process_next_samples();
+        handle.process_bars(&sample_processor);
+        assert_eq!(processed.get(), 1);
+        assert_eq!(
+            handle2.state().last_batch,
+            Some(sample_processor.processed_batches())
+        );
+
+        // the second handle should reuse the bars instead of computing them again
+        handle2.process_bars(&sample_processor);
+        assert_eq!(processed.get(), 1);
+        assert_eq!(handle.values(), handle2.values());
+        assert_eq!(handle.state().last_batch, Some(1));
+
+        // a new batch is processed again
+        sample_processor.process_next_samples();
+        handle2.process_bars(&sample_processor);
+        handle.process_bars(&sample_processor);
+        assert_eq!(processed.get(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "slow-audio")]
+    fn slow_spectrum_follows_bars() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        let handle = AudioHandle::new(&sample_processor);
+        handle.set_slow_time_constant(Duration::ZERO);
+
+        sample_processor.process_next_samples();
+        handle.process_bars(&sample_processor);
+
+        let state = handle.state();
+        assert_eq!(state.slow_spectrum.values(), &*state.bar_values);
+    }
+
+    #[test]
+    #[cfg(feature = "audio-peaks")]
+    fn peaks_stay_above_bars() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::with_waveform(
+            1,
+            shady_audio::fetcher::Waveform::Noise { seed: 7 },
+        ));
+        let handle = AudioHandle::new(&sample_processor);
+
+        for _ in 0..3 {
+            sample_processor.process_next_samples();
+            handle.process_bars(&sample_processor);
+
+            let state = handle.state();
+            let peaks = state.peak_hold.values();
+            assert_eq!(peaks.len(), state.bar_values.len());
+            assert!(peaks
+                .iter()
+                .zip(state.bar_values.iter())
+                .all(|(peak, bar)| peak >= bar));
+        }
+
+        handle.set_amount_bars(NonZero::new(8).unwrap());
+        assert_eq!(handle.peaks().len(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "audio-widths")]
+    fn widths_follow_amount_bars() {
+        let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        let handle = AudioHandle::new(&sample_processor);
+        assert_eq!(handle.bar_widths().len(), DEFAULT_AMOUNT_BARS);
+
+        handle.set_amount_bars(NonZero::new(10).unwrap());
+        let widths = handle.bar_widths();
+        assert_eq!(widths.len(), 10);
+        assert!((widths.iter().sum::<f32>() - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn frequency_animation() {
+        let freq = |freq| NonZero::new(freq).unwrap();
+        let animation = FrequencyAnimation {
+            from: freq(50)..freq(10_000),
+            to: freq(30)..freq(250),
+            start: Instant::now(),
+            duration: Duration::from_secs(2),
+        };
+
+        assert_eq!(
+            animation.range_at(Duration::ZERO),
+            (freq(50)..freq(10_000), false)
+        );
+        assert_eq!(
+            animation.range_at(Duration::from_secs(2)),
+            (freq(30)..freq(250), true)
+        );
+        assert_eq!(
+            animation.range_at(Duration::from_secs(3)),
+            (freq(30)..freq(250), true)
+        );
+
+        // halfway in between on a logarithmic scale
+        let (range, finished) = animation.range_at(Duration::from_secs(1));
+        assert_eq!(range, freq(39)..freq(1_581));
+        assert!(!finished);
+    }
+
+    #[test]
+    fn resample_bars() {
+        assert_eq!(&*resample(&[0., 1.], 3), &[0., 0.5, 1.]);
+        assert_eq!(&*resample(&[0., 0.5, 1.], 2), &[0., 1.]);
+        assert_eq!(&*resample(&[0.25], 2), &[0.25, 0.25]);
+        assert!(resample(&[], 2).iter().all(|&value| value == 0.));
+    }
+
+    #[test]
+    fn bars_fade_into_new_amount() {
+        let mut sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        let handle = AudioHandle::with_config(
+            &sample_processor,
+            BarProcessorConfig {
+                amount_bars: NonZero::new(2).unwrap(),
+                ..Default::default()
+            },
+        );
+        handle.set_values(&[1., 1.]);
+        handle.set_bar_transition(Duration::from_secs(60));
+
+        handle.set_amount_bars(NonZero::new(4).unwrap());
+        assert_eq!(&*handle.state().bar_values, &[1.; 4]);
+
+        // the dummy is silent, so the bars fade to zero
+        sample_processor.process_next_samples();
+        handle.process_bars(&sample_processor);
+        assert!(handle.state().bar_values.iter().all(|&value| value > 0.9));
+
+        let transition = handle.state().bar_transition.take().unwrap();
+        let mut bars = [0.; 4];
+        assert!(transition.apply(Duration::from_secs(60), &mut bars));
+        assert_eq!(bars, [0.; 4]);
+    }
+
+    #[test]
+    fn bars_fitting_into_width() {
+        assert_eq!(amount_bars_for_width(3840, 8., 2.).get(), 384);
+        // the last bar doesn't need a gap
+        assert_eq!(amount_bars_for_width(10, 4., 2.).get(), 2);
+        assert_eq!(amount_bars_for_width(0, 4., 2.).get(), 1);
+        assert_eq!(amount_bars_for_width(u32::MAX, 1., 0.).get(), u16::MAX);
+    }
+
+    #[test]
+    fn set_values() {
+        let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+        let handle = AudioHandle::with_config(
+            &sample_processor,
+            BarProcessorConfig {
+                amount_bars: NonZero::new(3).unwrap(),
+                ..Default::default()
+            },
+        );
+
+        handle.set_values(&[1., 2., 3., 4.]);
+        assert_eq!(&*handle.state().bar_values, &[1., 2., 3.]);
+
+        handle.set_values(&[0.5]);
+        assert_eq!(&*handle.state().bar_values, &[0.5, 0., 0.]);
     }
 }
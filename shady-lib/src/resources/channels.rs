@@ -0,0 +1,212 @@
+use std::fmt;
+
+use wgpu::{util::DeviceExt, Device, Queue};
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::{Resource, ResourceBinding};
+
+/// The amount of texture channels (`iChannel0` to `iChannel3`).
+pub const AMOUNT_CHANNELS: usize = 4;
+
+const CHANNEL_BINDINGS: [ResourceBinding; AMOUNT_CHANNELS] = [
+    ResourceBinding::Channel0,
+    ResourceBinding::Channel1,
+    ResourceBinding::Channel2,
+    ResourceBinding::Channel3,
+];
+
+const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+const DESCRIPTION: &str = "\
+// The textures of the channels (black if they aren't set), their sampler and their resolutions (in pixels).
+// Sample them with `textureSample(iChannel0, iChannelSampler, uv)` (wgsl)
+// or `texture(sampler2D(iChannel0, iChannelSampler), uv)` (glsl).";
+
+pub struct Channels {
+    views: [wgpu::TextureView; AMOUNT_CHANNELS],
+    sampler: wgpu::Sampler,
+
+    // `vec4` instead of `vec3` because of the alignment of arrays in uniform buffers
+    resolutions: [[f32; 4]; AMOUNT_CHANNELS],
+    buffer: wgpu::Buffer,
+}
+
+impl Channels {
+    /// Uploads the given RGBA8 image into the texture of the given channel.
+    pub fn set_rgba(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        channel: usize,
+        (width, height): (u32, u32),
+        rgba: &[u8],
+    ) {
+        assert!(channel < AMOUNT_CHANNELS, "There's no iChannel{}", channel);
+        assert_eq!(
+            rgba.len(),
+            width as usize * height as usize * 4,
+            "The data doesn't match the size of the image."
+        );
+
+        let texture = create_texture(device, width, height);
+        queue.write_texture(
+            texture.as_image_copy(),
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            texture.size(),
+        );
+
+        self.views[channel] = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.resolutions[channel] = [width as f32, height as f32, 1., 0.];
+    }
+
+    pub fn views(&self) -> &[wgpu::TextureView; AMOUNT_CHANNELS] {
+        &self.views
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    pub fn channel_binding(channel: usize) -> u32 {
+        CHANNEL_BINDINGS[channel] as u32
+    }
+
+    pub fn sampler_binding() -> u32 {
+        ResourceBinding::ChannelSampler as u32
+    }
+}
+
+impl Resource for Channels {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let views = std::array::from_fn(|_| {
+            create_texture(desc.device, 1, 1).create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        let sampler = desc.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shady iChannelSampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        // the size of the (black) default textures
+        let resolutions = [[1., 1., 1., 0.]; AMOUNT_CHANNELS];
+        let buffer = desc
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(Self::buffer_label()),
+                contents: bytemuck::cast_slice(&resolutions),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        Self {
+            views,
+            sampler,
+            resolutions,
+            buffer,
+        }
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iChannelResolution buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Uniform
+    }
+
+    fn binding() -> u32 {
+        ResourceBinding::ChannelResolution as u32
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&self.resolutions));
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Channels {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!("\n{}\n", DESCRIPTION))?;
+
+        for channel in 0..AMOUNT_CHANNELS {
+            writer.write_fmt(format_args!(
+                "@group({}) @binding({})\nvar iChannel{}: texture_2d<f32>;\n",
+                bind_group_index,
+                Self::channel_binding(channel),
+                channel
+            ))?;
+        }
+
+        writer.write_fmt(format_args!(
+            "@group({}) @binding({})
+var iChannelSampler: sampler;
+@group({}) @binding({})
+var<uniform> iChannelResolution: array<vec4<f32>, {}>;
+",
+            bind_group_index,
+            Self::sampler_binding(),
+            bind_group_index,
+            Self::binding(),
+            AMOUNT_CHANNELS
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!("\n{}\n", DESCRIPTION))?;
+
+        for channel in 0..AMOUNT_CHANNELS {
+            writer.write_fmt(format_args!(
+                "layout(set = {}, binding = {}) uniform texture2D iChannel{};\n",
+                bind_group_index,
+                Self::channel_binding(channel),
+                channel
+            ))?;
+        }
+
+        writer.write_fmt(format_args!(
+            "layout(set = {}, binding = {}) uniform sampler iChannelSampler;
+layout(set = {}, binding = {}) uniform vec4 iChannelResolution[{}];
+",
+            bind_group_index,
+            Self::sampler_binding(),
+            bind_group_index,
+            Self::binding(),
+            AMOUNT_CHANNELS
+        ))
+    }
+}
+
+fn create_texture(device: &Device, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Shady iChannel texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TEXTURE_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
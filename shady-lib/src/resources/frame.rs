@@ -14,6 +14,12 @@ impl Frame {
     pub fn inc(&mut self) {
         (self.value, _) = self.value.overflowing_add(1)
     }
+
+    // `unused`: Only used by the `offscreen` feature.
+    #[allow(unused)]
+    pub fn set(&mut self, value: u32) {
+        self.value = value;
+    }
 }
 
 impl Resource for Frame {
@@ -24,7 +30,7 @@ impl Resource for Frame {
     }
 
     fn binding() -> u32 {
-        super::BindingValue::Frame as u32
+        super::ResourceBinding::Frame as u32
     }
 
     fn buffer_label() -> &'static str {
@@ -59,11 +65,15 @@ var<uniform> iFrame: u32;
         ))
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         writer.write_fmt(format_args!(
             "
-layout(binding = {}) uniform uint iFrame;
+layout(set = {}, binding = {}) uniform uint iFrame;
 ",
+            bind_group_index,
             Self::binding()
         ))
     }
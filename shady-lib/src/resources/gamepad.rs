@@ -0,0 +1,184 @@
+use std::fmt;
+
+use tracing::instrument;
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::{Resource, ResourceBinding};
+
+/// The amount of buttons within `iGamepadButtons`.
+pub const AMOUNT_GAMEPAD_BUTTONS: usize = 16;
+
+const AXES_DESC: &str = "\
+// xy (index 0 and 1): The position of the left stick within [-1, 1] (up is positive)
+// zw (index 2 and 3): The position of the right stick within [-1, 1] (up is positive)";
+
+const BUTTONS_DESC: &str = "\
+// The state of the gamepad buttons within [0, 1] (`1` while pressed, the triggers are analog):
+// [0]: south (A), east (B), north (Y), west (X)
+// [1]: left bumper, right bumper, left trigger, right trigger
+// [2]: select, start, left stick, right stick
+// [3]: dpad up, dpad down, dpad left, dpad right";
+
+/// The buttons of a gamepad. The value of each variant is its index within `iGamepadButtons`
+/// (for example `iGamepadButtons[1][2]` is the left trigger).
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// The sticks of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+pub struct Gamepad {
+    // the left stick in `xy` and the right one in `zw`
+    axes: [f32; 4],
+    buttons: [f32; AMOUNT_GAMEPAD_BUTTONS],
+
+    buffer: wgpu::Buffer,
+    buttons_buffer: wgpu::Buffer,
+}
+
+impl Gamepad {
+    #[instrument(skip(self), level = "trace")]
+    pub fn set_stick(&mut self, stick: GamepadStick, x: f32, y: f32) {
+        let offset = match stick {
+            GamepadStick::Left => 0,
+            GamepadStick::Right => 2,
+        };
+
+        self.axes[offset] = x.clamp(-1., 1.);
+        self.axes[offset + 1] = y.clamp(-1., 1.);
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub fn set_button(&mut self, button: GamepadButton, value: f32) {
+        self.buttons[button as usize] = value.clamp(0., 1.);
+    }
+
+    /// Centers the sticks and releases all buttons.
+    pub fn reset(&mut self) {
+        self.axes = [0.; 4];
+        self.buttons = [0.; AMOUNT_GAMEPAD_BUTTONS];
+    }
+
+    pub fn buttons_binding() -> u32 {
+        ResourceBinding::GamepadButtons as u32
+    }
+
+    pub fn buttons_buffer(&self) -> &wgpu::Buffer {
+        &self.buttons_buffer
+    }
+}
+
+impl Resource for Gamepad {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let buffer =
+            Self::create_uniform_buffer(desc.device, std::mem::size_of::<[f32; 4]>() as u64);
+        let buttons_buffer = Self::create_uniform_buffer(
+            desc.device,
+            std::mem::size_of::<[f32; AMOUNT_GAMEPAD_BUTTONS]>() as u64,
+        );
+
+        Self {
+            axes: [0.; 4],
+            buttons: [0.; AMOUNT_GAMEPAD_BUTTONS],
+
+            buffer,
+            buttons_buffer,
+        }
+    }
+
+    fn binding() -> u32 {
+        ResourceBinding::GamepadAxes as u32
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iGamepad buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Uniform
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&self.axes));
+        queue.write_buffer(
+            self.buttons_buffer(),
+            0,
+            bytemuck::cast_slice(&self.buttons),
+        );
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Gamepad {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<uniform> iGamepadAxes: vec4<f32>;
+
+{}
+@group({}) @binding({})
+var<uniform> iGamepadButtons: array<vec4<f32>, {}>;
+",
+            AXES_DESC,
+            bind_group_index,
+            Self::binding(),
+            BUTTONS_DESC,
+            bind_group_index,
+            Self::buttons_binding(),
+            AMOUNT_GAMEPAD_BUTTONS / 4,
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) uniform vec4 iGamepadAxes;
+
+{}
+layout(set = {}, binding = {}) uniform vec4 iGamepadButtons[{}];
+",
+            AXES_DESC,
+            bind_group_index,
+            Self::binding(),
+            BUTTONS_DESC,
+            bind_group_index,
+            Self::buttons_binding(),
+            AMOUNT_GAMEPAD_BUTTONS / 4,
+        ))
+    }
+}
@@ -0,0 +1,161 @@
+use std::{fmt, time::Duration};
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::Resource;
+
+const DEFAULT_DELAY: Duration = Duration::from_secs(2);
+const DEFAULT_RAMP: Duration = Duration::from_secs(3);
+
+const DESCRIPTION: &str = "\
+// It's `0` while audio is playing and ramps up to `1` while the audio is silent.
+// Useful to switch to an idle animation if no music is playing.";
+
+pub struct Idle {
+    value: f32,
+
+    // how long the audio has to be silent before the value starts to ramp up
+    delay: Duration,
+    // how long the value needs to ramp up from `0` to `1`
+    ramp: Duration,
+
+    buffer: wgpu::Buffer,
+}
+
+impl Idle {
+    pub fn set_timing(&mut self, delay: Duration, ramp: Duration) {
+        self.delay = delay;
+        self.ramp = ramp;
+    }
+
+    pub fn set_silence(&mut self, silence: Duration) {
+        self.value = idle_value(silence, self.delay, self.ramp);
+    }
+}
+
+fn idle_value(silence: Duration, delay: Duration, ramp: Duration) -> f32 {
+    let ramped = silence.saturating_sub(delay);
+
+    if ramp.is_zero() {
+        // jump to `1` once the delay is over
+        return if !silence.is_zero() && silence >= delay {
+            1.
+        } else {
+            0.
+        };
+    }
+
+    (ramped.as_secs_f32() / ramp.as_secs_f32()).min(1.)
+}
+
+impl Resource for Idle {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let buffer = Self::create_uniform_buffer(desc.device, std::mem::size_of::<f32>() as u64);
+
+        Self {
+            value: 0.,
+            delay: DEFAULT_DELAY,
+            ramp: DEFAULT_RAMP,
+            buffer,
+        }
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iIdle buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Uniform
+    }
+
+    fn binding() -> u32 {
+        super::ResourceBinding::Idle as u32
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&[self.value]));
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Idle {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<uniform> iIdle: f32;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding()
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) uniform float iIdle;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_up_after_delay() {
+        let value = |secs: f32| {
+            idle_value(
+                Duration::from_secs_f32(secs),
+                Duration::from_secs(2),
+                Duration::from_secs(4),
+            )
+        };
+
+        assert_eq!(value(0.), 0.);
+        assert_eq!(value(2.), 0.);
+        assert_eq!(value(3.), 0.25);
+        assert_eq!(value(6.), 1.);
+        assert_eq!(value(10.), 1.);
+    }
+
+    #[test]
+    fn without_ramp() {
+        assert_eq!(
+            idle_value(Duration::ZERO, Duration::ZERO, Duration::ZERO),
+            0.
+        );
+        assert_eq!(
+            idle_value(
+                Duration::from_secs(2),
+                Duration::from_secs(2),
+                Duration::ZERO
+            ),
+            1.
+        );
+        assert_eq!(
+            idle_value(
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::ZERO
+            ),
+            0.
+        );
+    }
+}
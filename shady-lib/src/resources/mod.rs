@@ -1,47 +1,379 @@
 #[cfg(feature = "audio")]
 mod audio;
+#[cfg(feature = "channels")]
+mod channels;
 #[cfg(feature = "frame")]
 mod frame;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+#[cfg(feature = "idle")]
+mod idle;
 #[cfg(feature = "mouse")]
 mod mouse;
+#[cfg(feature = "params")]
+mod params;
 #[cfg(feature = "resolution")]
 mod resolution;
+#[cfg(feature = "state")]
+mod state;
 #[cfg(feature = "time")]
 mod time;
+#[cfg(feature = "timeline")]
+mod timeline;
+#[cfg(feature = "track")]
+mod track;
 
 use std::fmt;
 
 #[cfg(feature = "audio")]
 use audio::Audio;
+#[cfg(feature = "channels")]
+use channels::Channels;
 #[cfg(feature = "frame")]
 use frame::Frame;
+#[cfg(feature = "gamepad")]
+use gamepad::Gamepad;
+#[cfg(feature = "idle")]
+use idle::Idle;
 #[cfg(feature = "mouse")]
 use mouse::Mouse;
+#[cfg(feature = "params")]
+use params::Params;
 #[cfg(feature = "resolution")]
 use resolution::Resolution;
+#[cfg(feature = "state")]
+use state::State;
 #[cfg(feature = "time")]
 use time::Time;
+#[cfg(feature = "timeline")]
+use timeline::Timeline;
+#[cfg(feature = "track")]
+use track::Track;
 
 use tracing::instrument;
 use wgpu::Device;
 
+#[cfg(feature = "audio-texture")]
+pub use audio::AudioRepresentation;
+#[cfg(feature = "audio")]
+pub use audio::{amount_bars_for_width, default_sample_processor, AudioHandle};
+#[cfg(feature = "channels")]
+pub use channels::AMOUNT_CHANNELS;
+#[cfg(feature = "gamepad")]
+pub use gamepad::{GamepadButton, GamepadStick, AMOUNT_GAMEPAD_BUTTONS};
 #[cfg(feature = "mouse")]
 pub use mouse::MouseState;
+#[cfg(feature = "state")]
+pub use state::DEFAULT_STATE_SIZE;
 
 use crate::{template::TemplateGenerator, ShadyDescriptor};
 
+/// The bindings of the resources of `shady`. The value of each variant is its binding index
+/// within the bind group of `shady`.
+///
+/// Use them with [crate::Shady::bind_group_layout_entries] and [crate::Shady::bind_group_entries]
+/// to place only some resources at your own binding indices within your own bind group.
 #[repr(u32)]
-enum BindingValue {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResourceBinding {
+    /// `iAudio`
     #[cfg(feature = "audio")]
     Audio,
+    /// `iAudioSlow`
+    #[cfg(feature = "slow-audio")]
+    AudioSlow,
+    /// `iAudioPeaks`
+    #[cfg(feature = "audio-peaks")]
+    AudioPeaks,
+    /// `iAudioWidths`
+    #[cfg(feature = "audio-widths")]
+    AudioWidths,
+    /// `iFrame`
     #[cfg(feature = "frame")]
     Frame,
+    /// `iMouse`
     #[cfg(feature = "mouse")]
     Mouse,
+    /// `iMouseMotion`
+    #[cfg(feature = "mouse")]
+    MouseMotion,
+    /// `iResolution`
     #[cfg(feature = "resolution")]
     Resolution,
+    /// `iTime`
     #[cfg(feature = "time")]
     Time,
+    /// `iTimeline`
+    #[cfg(feature = "timeline")]
+    Timeline,
+    /// `iIdle`
+    #[cfg(feature = "idle")]
+    Idle,
+    /// `iChannelResolution`
+    #[cfg(feature = "channels")]
+    ChannelResolution,
+    /// The sampler of the texture channels.
+    #[cfg(feature = "channels")]
+    ChannelSampler,
+    /// `iChannel0`
+    #[cfg(feature = "channels")]
+    Channel0,
+    /// `iChannel1`
+    #[cfg(feature = "channels")]
+    Channel1,
+    /// `iChannel2`
+    #[cfg(feature = "channels")]
+    Channel2,
+    /// `iChannel3`
+    #[cfg(feature = "channels")]
+    Channel3,
+    /// `iTrackTime`
+    #[cfg(feature = "track")]
+    TrackTime,
+    /// `iTrackDuration`
+    #[cfg(feature = "track")]
+    TrackDuration,
+    /// `iPlaying`
+    #[cfg(feature = "track")]
+    Playing,
+    /// `iState`
+    #[cfg(feature = "state")]
+    State,
+    /// `iGamepadAxes`
+    #[cfg(feature = "gamepad")]
+    GamepadAxes,
+    /// `iGamepadButtons`
+    #[cfg(feature = "gamepad")]
+    GamepadButtons,
+    /// `iParams`
+    #[cfg(feature = "params")]
+    Params,
+    /// `iAudioTexture`
+    #[cfg(feature = "audio-texture")]
+    AudioTexture,
+    /// The sampler of `iAudioTexture`.
+    #[cfg(feature = "audio-texture")]
+    AudioSampler,
+}
+
+impl ResourceBinding {
+    /// All resources which are enabled by the features of the crate.
+    pub const ALL: &[Self] = &[
+        #[cfg(feature = "audio")]
+        Self::Audio,
+        #[cfg(feature = "slow-audio")]
+        Self::AudioSlow,
+        #[cfg(feature = "audio-peaks")]
+        Self::AudioPeaks,
+        #[cfg(feature = "audio-widths")]
+        Self::AudioWidths,
+        #[cfg(feature = "frame")]
+        Self::Frame,
+        #[cfg(feature = "mouse")]
+        Self::Mouse,
+        #[cfg(feature = "mouse")]
+        Self::MouseMotion,
+        #[cfg(feature = "resolution")]
+        Self::Resolution,
+        #[cfg(feature = "time")]
+        Self::Time,
+        #[cfg(feature = "timeline")]
+        Self::Timeline,
+        #[cfg(feature = "idle")]
+        Self::Idle,
+        #[cfg(feature = "channels")]
+        Self::ChannelResolution,
+        #[cfg(feature = "channels")]
+        Self::ChannelSampler,
+        #[cfg(feature = "channels")]
+        Self::Channel0,
+        #[cfg(feature = "channels")]
+        Self::Channel1,
+        #[cfg(feature = "channels")]
+        Self::Channel2,
+        #[cfg(feature = "channels")]
+        Self::Channel3,
+        #[cfg(feature = "track")]
+        Self::TrackTime,
+        #[cfg(feature = "track")]
+        Self::TrackDuration,
+        #[cfg(feature = "track")]
+        Self::Playing,
+        #[cfg(feature = "state")]
+        Self::State,
+        #[cfg(feature = "gamepad")]
+        Self::GamepadAxes,
+        #[cfg(feature = "gamepad")]
+        Self::GamepadButtons,
+        #[cfg(feature = "params")]
+        Self::Params,
+        #[cfg(feature = "audio-texture")]
+        Self::AudioTexture,
+        #[cfg(feature = "audio-texture")]
+        Self::AudioSampler,
+    ];
+
+    /// The binding index within the bind group of `shady`.
+    pub fn default_binding(self) -> u32 {
+        self as u32
+    }
+
+    /// The name of the resource within the shaders.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "audio")]
+            Self::Audio => "iAudio",
+            #[cfg(feature = "slow-audio")]
+            Self::AudioSlow => "iAudioSlow",
+            #[cfg(feature = "audio-peaks")]
+            Self::AudioPeaks => "iAudioPeaks",
+            #[cfg(feature = "audio-widths")]
+            Self::AudioWidths => "iAudioWidths",
+            #[cfg(feature = "frame")]
+            Self::Frame => "iFrame",
+            #[cfg(feature = "mouse")]
+            Self::Mouse => "iMouse",
+            #[cfg(feature = "mouse")]
+            Self::MouseMotion => "iMouseMotion",
+            #[cfg(feature = "resolution")]
+            Self::Resolution => "iResolution",
+            #[cfg(feature = "time")]
+            Self::Time => "iTime",
+            #[cfg(feature = "timeline")]
+            Self::Timeline => "iTimeline",
+            #[cfg(feature = "idle")]
+            Self::Idle => "iIdle",
+            #[cfg(feature = "channels")]
+            Self::ChannelResolution => "iChannelResolution",
+            #[cfg(feature = "channels")]
+            Self::ChannelSampler => "iChannelSampler",
+            #[cfg(feature = "channels")]
+            Self::Channel0 => "iChannel0",
+            #[cfg(feature = "channels")]
+            Self::Channel1 => "iChannel1",
+            #[cfg(feature = "channels")]
+            Self::Channel2 => "iChannel2",
+            #[cfg(feature = "channels")]
+            Self::Channel3 => "iChannel3",
+            #[cfg(feature = "track")]
+            Self::TrackTime => "iTrackTime",
+            #[cfg(feature = "track")]
+            Self::TrackDuration => "iTrackDuration",
+            #[cfg(feature = "track")]
+            Self::Playing => "iPlaying",
+            #[cfg(feature = "state")]
+            Self::State => "iState",
+            #[cfg(feature = "gamepad")]
+            Self::GamepadAxes => "iGamepadAxes",
+            #[cfg(feature = "gamepad")]
+            Self::GamepadButtons => "iGamepadButtons",
+            #[cfg(feature = "params")]
+            Self::Params => "iParams",
+            #[cfg(feature = "audio-texture")]
+            Self::AudioTexture => "iAudioTexture",
+            #[cfg(feature = "audio-texture")]
+            Self::AudioSampler => "iAudioSampler",
+        }
+    }
+
+    /// Returns the layout entry of the resource at the given binding index which is visible to fragment shaders.
+    pub fn layout_entry(self, binding: u32) -> wgpu::BindGroupLayoutEntry {
+        self.layout_entry_with_visibility(binding, RESOURCE_VISIBILITY)
+    }
+
+    /// Like [ResourceBinding::layout_entry] but visible to the given shader stages,
+    /// for example if your vertex shader uses the resource as well.
+    pub fn layout_entry_with_visibility(
+        self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayoutEntry {
+        let buffer_type = match self {
+            #[cfg(feature = "audio")]
+            Self::Audio => Audio::buffer_type(),
+            #[cfg(feature = "slow-audio")]
+            Self::AudioSlow => Audio::buffer_type(),
+            #[cfg(feature = "audio-peaks")]
+            Self::AudioPeaks => Audio::buffer_type(),
+            #[cfg(feature = "audio-widths")]
+            Self::AudioWidths => Audio::buffer_type(),
+            #[cfg(feature = "frame")]
+            Self::Frame => Frame::buffer_type(),
+            #[cfg(feature = "mouse")]
+            Self::Mouse | Self::MouseMotion => Mouse::buffer_type(),
+            #[cfg(feature = "resolution")]
+            Self::Resolution => Resolution::buffer_type(),
+            #[cfg(feature = "time")]
+            Self::Time => Time::buffer_type(),
+            #[cfg(feature = "timeline")]
+            Self::Timeline => Timeline::buffer_type(),
+            #[cfg(feature = "idle")]
+            Self::Idle => Idle::buffer_type(),
+            #[cfg(feature = "channels")]
+            Self::ChannelResolution => Channels::buffer_type(),
+            #[cfg(feature = "channels")]
+            Self::ChannelSampler => {
+                return wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                }
+            }
+            #[cfg(feature = "channels")]
+            Self::Channel0 | Self::Channel1 | Self::Channel2 | Self::Channel3 => {
+                return wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }
+            }
+            #[cfg(feature = "track")]
+            Self::TrackTime | Self::TrackDuration | Self::Playing => Track::buffer_type(),
+            #[cfg(feature = "state")]
+            Self::State => {
+                return wgpu::BindGroupLayoutEntry {
+                    // vertex shaders aren't allowed to write into storage buffers
+                    visibility: visibility.difference(wgpu::ShaderStages::VERTEX),
+                    ..bind_group_layout_entry(binding, State::buffer_type(), visibility)
+                };
+            }
+            #[cfg(feature = "gamepad")]
+            Self::GamepadAxes | Self::GamepadButtons => Gamepad::buffer_type(),
+            #[cfg(feature = "params")]
+            Self::Params => Params::buffer_type(),
+            #[cfg(feature = "audio-texture")]
+            Self::AudioTexture => {
+                return wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D1,
+                        multisampled: false,
+                    },
+                    count: None,
+                }
+            }
+            #[cfg(feature = "audio-texture")]
+            Self::AudioSampler => {
+                return wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                }
+            }
+        };
+
+        bind_group_layout_entry(binding, buffer_type, visibility)
+    }
 }
 
 pub trait Resource: TemplateGenerator {
@@ -91,6 +423,20 @@ pub struct Resources {
     pub resolution: Resolution,
     #[cfg(feature = "time")]
     pub time: Time,
+    #[cfg(feature = "timeline")]
+    pub timeline: Timeline,
+    #[cfg(feature = "idle")]
+    pub idle: Idle,
+    #[cfg(feature = "channels")]
+    pub channels: Channels,
+    #[cfg(feature = "track")]
+    pub track: Track,
+    #[cfg(feature = "state")]
+    pub state: State,
+    #[cfg(feature = "gamepad")]
+    pub gamepad: Gamepad,
+    #[cfg(feature = "params")]
+    pub params: Params,
 }
 
 impl Resources {
@@ -107,67 +453,162 @@ impl Resources {
             resolution: Resolution::new(desc),
             #[cfg(feature = "time")]
             time: Time::new(desc),
+            #[cfg(feature = "timeline")]
+            timeline: Timeline::new(desc),
+            #[cfg(feature = "idle")]
+            idle: Idle::new(desc),
+            #[cfg(feature = "channels")]
+            channels: Channels::new(desc),
+            #[cfg(feature = "track")]
+            track: Track::new(desc),
+            #[cfg(feature = "state")]
+            state: State::new(desc),
+            #[cfg(feature = "gamepad")]
+            gamepad: Gamepad::new(desc),
+            #[cfg(feature = "params")]
+            params: Params::new(desc),
         }
     }
 }
 
 /// Methods regarding bind groups
 impl Resources {
+    /// Returns the layout of the bind group of `shady` whose resources are visible to the given shader stages.
     #[instrument(level = "trace")]
-    pub fn bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+    pub fn bind_group_layout(
+        device: &Device,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Shady bind group layout"),
-            entries: &[
-                #[cfg(feature = "audio")]
-                bind_group_layout_entry(Audio::binding(), Audio::buffer_type()),
-                #[cfg(feature = "frame")]
-                bind_group_layout_entry(Frame::binding(), Frame::buffer_type()),
-                #[cfg(feature = "mouse")]
-                bind_group_layout_entry(Mouse::binding(), Mouse::buffer_type()),
-                #[cfg(feature = "resolution")]
-                bind_group_layout_entry(Resolution::binding(), Resolution::buffer_type()),
-                #[cfg(feature = "time")]
-                bind_group_layout_entry(Time::binding(), Time::buffer_type()),
-            ],
+            entries: &Self::default_bindings()
+                .into_iter()
+                .map(|(resource, binding)| {
+                    resource.layout_entry_with_visibility(binding, visibility)
+                })
+                .collect::<Vec<_>>(),
         })
     }
 
     #[instrument(skip(self), level = "trace")]
-    pub fn bind_group(&self, device: &Device) -> wgpu::BindGroup {
-        let layout = Self::bind_group_layout(device);
+    pub fn bind_group(&self, device: &Device, visibility: wgpu::ShaderStages) -> wgpu::BindGroup {
+        let layout = Self::bind_group_layout(device, visibility);
 
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Shady bind group"),
             layout: &layout,
-            entries: &[
-                #[cfg(feature = "audio")]
-                wgpu::BindGroupEntry {
-                    binding: Audio::binding(),
-                    resource: self.audio.buffer().as_entire_binding(),
-                },
-                #[cfg(feature = "frame")]
-                wgpu::BindGroupEntry {
-                    binding: Frame::binding(),
-                    resource: self.frame.buffer().as_entire_binding(),
-                },
-                #[cfg(feature = "mouse")]
-                wgpu::BindGroupEntry {
-                    binding: Mouse::binding(),
-                    resource: self.mouse.buffer().as_entire_binding(),
-                },
-                #[cfg(feature = "resolution")]
-                wgpu::BindGroupEntry {
-                    binding: Resolution::binding(),
-                    resource: self.resolution.buffer().as_entire_binding(),
-                },
-                #[cfg(feature = "time")]
-                wgpu::BindGroupEntry {
-                    binding: Time::binding(),
-                    resource: self.time.buffer().as_entire_binding(),
-                },
-            ],
+            entries: &self.bind_group_entries(&Self::default_bindings()),
         })
     }
+
+    /// Creates the bind groups for each kind of pipeline.
+    pub fn bind_groups(&self, device: &Device) -> BindGroups {
+        BindGroups {
+            fragment: self.bind_group(device, RESOURCE_VISIBILITY),
+            vertex: self.bind_group(device, VERTEX_RESOURCE_VISIBILITY),
+            compute: self.bind_group(device, COMPUTE_RESOURCE_VISIBILITY),
+        }
+    }
+
+    /// Returns the layout entries of the given resources at the given binding indices.
+    pub fn layout_entries(bindings: &[(ResourceBinding, u32)]) -> Vec<wgpu::BindGroupLayoutEntry> {
+        bindings
+            .iter()
+            .map(|&(resource, binding)| resource.layout_entry(binding))
+            .collect()
+    }
+
+    /// Returns the bind group entries of the given resources at the given binding indices.
+    pub fn bind_group_entries(
+        &self,
+        bindings: &[(ResourceBinding, u32)],
+    ) -> Vec<wgpu::BindGroupEntry<'_>> {
+        bindings
+            .iter()
+            .map(|&(resource, binding)| wgpu::BindGroupEntry {
+                binding,
+                resource: self.binding_resource(resource),
+            })
+            .collect()
+    }
+
+    fn default_bindings() -> Vec<(ResourceBinding, u32)> {
+        ResourceBinding::ALL
+            .iter()
+            .map(|&resource| (resource, resource.default_binding()))
+            .collect()
+    }
+
+    // `unused`: Without any resource feature, there's no resource to bind.
+    #[allow(unused)]
+    fn binding_resource(&self, resource: ResourceBinding) -> wgpu::BindingResource<'_> {
+        match resource {
+            #[cfg(feature = "audio")]
+            ResourceBinding::Audio => self.audio.buffer().as_entire_binding(),
+            #[cfg(feature = "slow-audio")]
+            ResourceBinding::AudioSlow => self.audio.slow_buffer().as_entire_binding(),
+            #[cfg(feature = "audio-peaks")]
+            ResourceBinding::AudioPeaks => self.audio.peaks_buffer().as_entire_binding(),
+            #[cfg(feature = "audio-widths")]
+            ResourceBinding::AudioWidths => self.audio.widths_buffer().as_entire_binding(),
+            #[cfg(feature = "frame")]
+            ResourceBinding::Frame => self.frame.buffer().as_entire_binding(),
+            #[cfg(feature = "mouse")]
+            ResourceBinding::Mouse => self.mouse.buffer().as_entire_binding(),
+            #[cfg(feature = "mouse")]
+            ResourceBinding::MouseMotion => self.mouse.motion_buffer().as_entire_binding(),
+            #[cfg(feature = "resolution")]
+            ResourceBinding::Resolution => self.resolution.buffer().as_entire_binding(),
+            #[cfg(feature = "time")]
+            ResourceBinding::Time => self.time.buffer().as_entire_binding(),
+            #[cfg(feature = "timeline")]
+            ResourceBinding::Timeline => self.timeline.buffer().as_entire_binding(),
+            #[cfg(feature = "idle")]
+            ResourceBinding::Idle => self.idle.buffer().as_entire_binding(),
+            #[cfg(feature = "channels")]
+            ResourceBinding::ChannelResolution => self.channels.buffer().as_entire_binding(),
+            #[cfg(feature = "channels")]
+            ResourceBinding::ChannelSampler => {
+                wgpu::BindingResource::Sampler(self.channels.sampler())
+            }
+            #[cfg(feature = "channels")]
+            ResourceBinding::Channel0 => {
+                wgpu::BindingResource::TextureView(&self.channels.views()[0])
+            }
+            #[cfg(feature = "channels")]
+            ResourceBinding::Channel1 => {
+                wgpu::BindingResource::TextureView(&self.channels.views()[1])
+            }
+            #[cfg(feature = "channels")]
+            ResourceBinding::Channel2 => {
+                wgpu::BindingResource::TextureView(&self.channels.views()[2])
+            }
+            #[cfg(feature = "channels")]
+            ResourceBinding::Channel3 => {
+                wgpu::BindingResource::TextureView(&self.channels.views()[3])
+            }
+            #[cfg(feature = "track")]
+            ResourceBinding::TrackTime => self.track.buffer().as_entire_binding(),
+            #[cfg(feature = "track")]
+            ResourceBinding::TrackDuration => self.track.duration_buffer().as_entire_binding(),
+            #[cfg(feature = "track")]
+            ResourceBinding::Playing => self.track.playing_buffer().as_entire_binding(),
+            #[cfg(feature = "state")]
+            ResourceBinding::State => self.state.buffer().as_entire_binding(),
+            #[cfg(feature = "gamepad")]
+            ResourceBinding::GamepadAxes => self.gamepad.buffer().as_entire_binding(),
+            #[cfg(feature = "gamepad")]
+            ResourceBinding::GamepadButtons => self.gamepad.buttons_buffer().as_entire_binding(),
+            #[cfg(feature = "params")]
+            ResourceBinding::Params => self.params.buffer().as_entire_binding(),
+            #[cfg(feature = "audio-texture")]
+            ResourceBinding::AudioTexture => {
+                wgpu::BindingResource::TextureView(self.audio.texture_view())
+            }
+            #[cfg(feature = "audio-texture")]
+            ResourceBinding::AudioSampler => wgpu::BindingResource::Sampler(self.audio.sampler()),
+        }
+    }
 }
 
 impl TemplateGenerator for Resources {
@@ -185,34 +626,95 @@ impl TemplateGenerator for Resources {
         Resolution::write_wgsl_template(writer, bind_group_index)?;
         #[cfg(feature = "time")]
         Time::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "timeline")]
+        Timeline::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "idle")]
+        Idle::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "channels")]
+        Channels::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "track")]
+        Track::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "state")]
+        State::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "gamepad")]
+        Gamepad::write_wgsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "params")]
+        Params::write_wgsl_template(writer, bind_group_index)?;
 
         Ok(())
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         #[cfg(feature = "audio")]
-        Audio::write_glsl_template(writer)?;
+        Audio::write_glsl_template(writer, bind_group_index)?;
         #[cfg(feature = "frame")]
-        Frame::write_glsl_template(writer)?;
+        Frame::write_glsl_template(writer, bind_group_index)?;
         #[cfg(feature = "mouse")]
-        Mouse::write_glsl_template(writer)?;
+        Mouse::write_glsl_template(writer, bind_group_index)?;
         #[cfg(feature = "resolution")]
-        Resolution::write_glsl_template(writer)?;
+        Resolution::write_glsl_template(writer, bind_group_index)?;
         #[cfg(feature = "time")]
-        Time::write_glsl_template(writer)?;
+        Time::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "timeline")]
+        Timeline::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "idle")]
+        Idle::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "channels")]
+        Channels::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "track")]
+        Track::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "state")]
+        State::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "gamepad")]
+        Gamepad::write_glsl_template(writer, bind_group_index)?;
+        #[cfg(feature = "params")]
+        Params::write_glsl_template(writer, bind_group_index)?;
 
         Ok(())
     }
 }
 
+// the resources are only used by the fragment shader, unless the pipeline has a custom vertex shader
+// (see `crate::VertexShader`) or it's a compute pipeline (see `crate::compute`)
+pub(crate) const RESOURCE_VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::FRAGMENT;
+pub(crate) const VERTEX_RESOURCE_VISIBILITY: wgpu::ShaderStages =
+    wgpu::ShaderStages::VERTEX.union(wgpu::ShaderStages::FRAGMENT);
+pub(crate) const COMPUTE_RESOURCE_VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::COMPUTE;
+
+/// The bind groups of the resources for each visibility of the pipelines.
+///
+/// The layout of a bind group has to match the one of the pipeline exactly, including the visibility.
+pub struct BindGroups {
+    fragment: wgpu::BindGroup,
+    vertex: wgpu::BindGroup,
+    compute: wgpu::BindGroup,
+}
+
+impl BindGroups {
+    /// Returns the bind group whose resources are visible to the given shader stages.
+    pub fn get(&self, visibility: wgpu::ShaderStages) -> &wgpu::BindGroup {
+        if visibility == COMPUTE_RESOURCE_VISIBILITY {
+            &self.compute
+        } else if visibility.contains(wgpu::ShaderStages::VERTEX) {
+            &self.vertex
+        } else {
+            &self.fragment
+        }
+    }
+}
+
 #[instrument(level = "trace")]
 fn bind_group_layout_entry(
     binding: u32,
     ty: wgpu::BufferBindingType,
+    visibility: wgpu::ShaderStages,
 ) -> wgpu::BindGroupLayoutEntry {
     wgpu::BindGroupLayoutEntry {
         binding,
-        visibility: wgpu::ShaderStages::FRAGMENT,
+        visibility,
         ty: wgpu::BindingType::Buffer {
             ty,
             has_dynamic_offset: false,
@@ -221,3 +723,50 @@ fn bind_group_layout_entry(
         count: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_follow_the_variants() {
+        for (idx, resource) in ResourceBinding::ALL.iter().enumerate() {
+            assert_eq!(resource.default_binding(), idx as u32);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn fragment_visibility_by_default() {
+        let entry = ResourceBinding::Time.layout_entry(0);
+        assert_eq!(entry.visibility, wgpu::ShaderStages::FRAGMENT);
+
+        let entry =
+            ResourceBinding::Time.layout_entry_with_visibility(0, VERTEX_RESOURCE_VISIBILITY);
+        assert_eq!(entry.visibility, VERTEX_RESOURCE_VISIBILITY);
+    }
+
+    #[test]
+    #[cfg(feature = "state")]
+    fn state_is_never_visible_to_vertex_shaders() {
+        let entry =
+            ResourceBinding::State.layout_entry_with_visibility(0, VERTEX_RESOURCE_VISIBILITY);
+        assert_eq!(entry.visibility, wgpu::ShaderStages::FRAGMENT);
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn custom_binding_index() {
+        let entries = Resources::layout_entries(&[(ResourceBinding::Time, 7)]);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].binding, 7);
+        assert!(matches!(
+            entries[0].ty,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                ..
+            }
+        ));
+    }
+}
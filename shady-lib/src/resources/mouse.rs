@@ -4,12 +4,16 @@ use tracing::instrument;
 
 use crate::{template::TemplateGenerator, ShadyDescriptor};
 
-use super::Resource;
+use super::{Resource, ResourceBinding};
 
 const DESC: &str = "\
 // xy (index 0 and 1): The xy coordinate of the mouse while the user holds the left button
 // zw (index 2 and 3): The xy coordinate of the mouse where the user starts holding the left button";
 
+const MOTION_DESC: &str = "\
+// xy (index 0 and 1): The accumulated (horizontal and vertical) scroll distance of the mouse wheel
+// zw (index 2 and 3): The accumulated distance (in pixels) which the mouse has been dragged while holding the left button";
+
 #[derive(Default, Debug, Clone, Copy)]
 struct Coord {
     pub x: f32,
@@ -35,19 +39,55 @@ pub struct Mouse {
     pressed_pos: Coord,
     first_click_coord: Coord,
 
+    wheel: Coord,
+    drag: Coord,
+
     buffer: wgpu::Buffer,
+    motion_buffer: wgpu::Buffer,
 }
 
 impl Mouse {
     #[instrument(skip(self), level = "trace")]
     pub fn set_pos(&mut self, x: f32, y: f32) {
+        let prev_pos = self.pos;
         self.pos = Coord { x, y };
 
         if self.curr_state == MouseState::Pressed {
             self.pressed_pos = self.pos;
+
+            self.drag.x += self.pos.x - prev_pos.x;
+            self.drag.y += self.pos.y - prev_pos.y;
         }
     }
 
+    pub fn pos(&self) -> (f32, f32) {
+        (self.pos.x, self.pos.y)
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub fn scroll(&mut self, delta_x: f32, delta_y: f32) {
+        self.wheel.x += delta_x;
+        self.wheel.y += delta_y;
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub fn set_wheel(&mut self, x: f32, y: f32) {
+        self.wheel = Coord { x, y };
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub fn set_drag(&mut self, x: f32, y: f32) {
+        self.drag = Coord { x, y };
+    }
+
+    pub fn motion_binding() -> u32 {
+        ResourceBinding::MouseMotion as u32
+    }
+
+    pub fn motion_buffer(&self) -> &wgpu::Buffer {
+        &self.motion_buffer
+    }
+
     #[instrument(skip(self), level = "trace")]
     pub fn set_state(&mut self, state: MouseState) {
         if self.curr_state == MouseState::Pressed && self.prev_state == MouseState::Released {
@@ -63,21 +103,27 @@ impl Resource for Mouse {
     fn new(desc: &ShadyDescriptor) -> Self {
         let buffer =
             Self::create_uniform_buffer(desc.device, std::mem::size_of::<[f32; 4]>() as u64);
+        let motion_buffer =
+            Self::create_uniform_buffer(desc.device, std::mem::size_of::<[f32; 4]>() as u64);
 
         Self {
             pos: Coord::default(),
             first_click_coord: Coord::default(),
             pressed_pos: Coord::default(),
 
+            wheel: Coord::default(),
+            drag: Coord::default(),
+
             prev_state: MouseState::Released,
             curr_state: MouseState::Released,
 
             buffer,
+            motion_buffer,
         }
     }
 
     fn binding() -> u32 {
-        super::BindingValue::Mouse as u32
+        super::ResourceBinding::Mouse as u32
     }
 
     fn buffer_label() -> &'static str {
@@ -97,6 +143,9 @@ impl Resource for Mouse {
         ];
 
         queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&data));
+
+        let motion = [self.wheel.x, self.wheel.y, self.drag.x, self.drag.y];
+        queue.write_buffer(self.motion_buffer(), 0, bytemuck::cast_slice(&motion));
     }
 
     fn buffer(&self) -> &wgpu::Buffer {
@@ -114,21 +163,38 @@ impl TemplateGenerator for Mouse {
 {}
 @group({}) @binding({})
 var<uniform> iMouse: vec4<f32>;
+
+{}
+@group({}) @binding({})
+var<uniform> iMouseMotion: vec4<f32>;
 ",
             DESC,
             bind_group_index,
-            Self::binding()
+            Self::binding(),
+            MOTION_DESC,
+            bind_group_index,
+            Self::motion_binding()
         ))
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         writer.write_fmt(format_args!(
             "
 {}
-layout(binding = {}) uniform vec4 iMouse;
+layout(set = {}, binding = {}) uniform vec4 iMouse;
+
+{}
+layout(set = {}, binding = {}) uniform vec4 iMouseMotion;
 ",
             DESC,
-            Self::binding()
+            bind_group_index,
+            Self::binding(),
+            MOTION_DESC,
+            bind_group_index,
+            Self::motion_binding()
         ))
     }
 }
@@ -0,0 +1,109 @@
+use std::fmt;
+
+use wgpu::Device;
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::Resource;
+
+const DESCRIPTION: &str = "\
+// Custom values which are computed by the application each frame (for example parameters which react to the audio).
+// The application tells you which value is stored at which index.";
+
+pub struct Params {
+    values: Box<[f32]>,
+
+    buffer: wgpu::Buffer,
+}
+
+impl Params {
+    /// Sets the values and returns `true` if the buffer had to be replaced because the amount of values has changed.
+    pub fn set_values(&mut self, device: &Device, values: &[f32]) -> bool {
+        let resized = values.len() != self.values.len();
+        if resized {
+            self.values = values.into();
+            self.buffer = Self::create_storage_buffer(device, Self::buffer_size(values.len()));
+        } else {
+            self.values.copy_from_slice(values);
+        }
+
+        resized
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    // storage buffers are not allowed to be empty
+    fn buffer_size(amount_values: usize) -> u64 {
+        (std::mem::size_of::<f32>() * amount_values.max(1)) as u64
+    }
+}
+
+impl Resource for Params {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let buffer = Self::create_storage_buffer(desc.device, Self::buffer_size(0));
+
+        Self {
+            values: Box::new([]),
+            buffer,
+        }
+    }
+
+    fn binding() -> u32 {
+        super::ResourceBinding::Params as u32
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iParams buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Storage { read_only: true }
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        if !self.values.is_empty() {
+            queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&self.values));
+        }
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Params {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read> iParams: array<f32>;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) readonly buffer iParams {{
+    float[] params;
+}};
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+}
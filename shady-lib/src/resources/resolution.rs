@@ -42,7 +42,7 @@ impl Resource for Resolution {
     }
 
     fn binding() -> u32 {
-        super::BindingValue::Resolution as u32
+        super::ResourceBinding::Resolution as u32
     }
 
     fn update_buffer(&self, queue: &wgpu::Queue) {
@@ -78,13 +78,17 @@ var<uniform> iResolution: vec2<f32>;
         ))
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         writer.write_fmt(format_args!(
             "
 // x: width
 // y: height
-layout(binding = {}) uniform vec2 iResolution;
+layout(set = {}, binding = {}) uniform vec2 iResolution;
 ",
+            bind_group_index,
             Self::binding()
         ))
     }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use wgpu::Device;
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::Resource;
+
+/// The amount of values of `iState` if it hasn't been set with [crate::Shady::set_state_size].
+pub const DEFAULT_STATE_SIZE: usize = 1024;
+
+const DESCRIPTION: &str = "\
+// Values which persist across frames. They are initialised with `0` and you can read and write them,
+// for example to implement feedback effects, particles or counters.
+// Each invocation runs in parallel, so let only one invocation write to the same value.";
+
+pub struct State {
+    buffer: wgpu::Buffer,
+}
+
+impl State {
+    /// Replaces the buffer by a new one with `amount_values` zeroed values.
+    pub fn set_size(&mut self, device: &Device, amount_values: usize) {
+        self.buffer = Self::create_storage_buffer(device, Self::buffer_size(amount_values));
+    }
+
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.clear_buffer(&self.buffer, 0, None);
+    }
+
+    // storage buffers are not allowed to be empty
+    fn buffer_size(amount_values: usize) -> u64 {
+        (std::mem::size_of::<f32>() * amount_values.max(1)) as u64
+    }
+}
+
+impl Resource for State {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let buffer =
+            Self::create_storage_buffer(desc.device, Self::buffer_size(DEFAULT_STATE_SIZE));
+
+        Self { buffer }
+    }
+
+    fn binding() -> u32 {
+        super::ResourceBinding::State as u32
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iState buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Storage { read_only: false }
+    }
+
+    fn update_buffer(&self, _queue: &wgpu::Queue) {
+        // only the shaders are writing into the buffer
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for State {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read_write> iState: array<f32>;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) buffer iState {{
+    float[] values;
+}};
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+}
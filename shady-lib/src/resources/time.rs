@@ -1,22 +1,76 @@
-use std::{fmt, time::Instant};
+use std::fmt;
 
-use crate::{template::TemplateGenerator, ShadyDescriptor};
+use crate::{
+    clock::{Clock, RealTimeClock},
+    template::TemplateGenerator,
+    ShadyDescriptor,
+};
 
 use super::Resource;
 
-#[derive(Debug)]
 pub struct Time {
-    time: Instant,
+    clock: Box<dyn Clock>,
+    // added to the time of the clock (see `Time::set`)
+    offset: f32,
+    fixed: Option<f32>,
 
     buffer: wgpu::Buffer,
 }
 
+impl Time {
+    /// Returns the playback time in seconds.
+    pub fn elapsed(&self) -> f32 {
+        self.fixed
+            .unwrap_or_else(|| self.clock.elapsed().as_secs_f32() + self.offset)
+    }
+
+    /// Sets the playback time to the given value (in seconds).
+    pub fn set(&mut self, time: f32) {
+        match &mut self.fixed {
+            Some(fixed) => *fixed = time,
+            None => self.offset = time.max(0.) - self.clock.elapsed().as_secs_f32(),
+        }
+    }
+
+    /// Replaces the clock. The playback time starts with the time of the new clock.
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+        self.offset = 0.;
+    }
+
+    /// Lets the clock advance to the next frame.
+    pub fn tick(&mut self) {
+        self.clock.tick();
+    }
+
+    /// Stops (`true`) or continues (`false`) the playback time where it currently is.
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.fixed) {
+            (true, None) => self.fixed = Some(self.elapsed()),
+            (false, Some(time)) => {
+                self.fixed = None;
+                self.set(time);
+            }
+            _ => {}
+        }
+    }
+
+    /// Freezes the playback time at the given value. `None` lets the time run again.
+    // `unused`: Only used by the `offscreen` feature.
+    #[allow(unused)]
+    pub fn set_fixed(&mut self, time: Option<f32>) {
+        self.fixed = time;
+    }
+}
+
 impl Resource for Time {
     fn new(desc: &ShadyDescriptor) -> Self {
         let buffer = Self::create_uniform_buffer(desc.device, std::mem::size_of::<f32>() as u64);
 
         Self {
-            time: Instant::now(),
+            clock: Box::new(RealTimeClock::new()),
+            offset: 0.,
+            fixed: None,
             buffer,
         }
     }
@@ -30,11 +84,11 @@ impl Resource for Time {
     }
 
     fn binding() -> u32 {
-        super::BindingValue::Time as u32
+        super::ResourceBinding::Time as u32
     }
 
     fn update_buffer(&self, queue: &wgpu::Queue) {
-        let elapsed_time = self.time.elapsed().as_secs_f32();
+        let elapsed_time = self.elapsed();
         queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&[elapsed_time]));
     }
 
@@ -58,11 +112,15 @@ var<uniform> iTime: f32;
         ))
     }
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error> {
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
         writer.write_fmt(format_args!(
             "
-layout(binding = {}) uniform float iTime;
+layout(set = {}, binding = {}) uniform float iTime;
 ",
+            bind_group_index,
             Self::binding()
         ))
     }
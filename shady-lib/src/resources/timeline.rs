@@ -0,0 +1,104 @@
+use std::fmt;
+
+use wgpu::Device;
+
+use crate::{template::TemplateGenerator, timeline, ShadyDescriptor};
+
+use super::Resource;
+
+const DESCRIPTION: &str = "\
+// Contains the current value of each track of the timeline.
+// The index of a value is the same as the index of its track in the timeline description.";
+
+pub struct Timeline {
+    timeline: timeline::Timeline,
+    values: Box<[f32]>,
+
+    buffer: wgpu::Buffer,
+}
+
+impl Timeline {
+    pub fn set_timeline(&mut self, device: &Device, timeline: timeline::Timeline) {
+        self.values = vec![0.; timeline.len()].into_boxed_slice();
+        self.timeline = timeline;
+        self.buffer = Self::create_storage_buffer(device, Self::buffer_size(self.values.len()));
+    }
+
+    pub fn update(&mut self, time: f32) {
+        self.timeline.sample_into(time, &mut self.values);
+    }
+
+    // storage buffers are not allowed to be empty
+    fn buffer_size(amount_values: usize) -> u64 {
+        (std::mem::size_of::<f32>() * amount_values.max(1)) as u64
+    }
+}
+
+impl Resource for Timeline {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let buffer = Self::create_storage_buffer(desc.device, Self::buffer_size(0));
+
+        Self {
+            timeline: timeline::Timeline::default(),
+            values: Box::new([]),
+            buffer,
+        }
+    }
+
+    fn binding() -> u32 {
+        super::ResourceBinding::Timeline as u32
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iTimeline buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Storage { read_only: true }
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        if !self.values.is_empty() {
+            queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&self.values));
+        }
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Timeline {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<storage, read> iTimeline: array<f32>;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) readonly buffer iTimeline {{
+    float[] values;
+}};
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+        ))
+    }
+}
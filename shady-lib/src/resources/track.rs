@@ -0,0 +1,194 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{template::TemplateGenerator, ShadyDescriptor};
+
+use super::{Resource, ResourceBinding};
+
+const DESCRIPTION: &str = "\
+// The playback position and the duration (`0` if unknown) of the current track in seconds
+// and whether it's playing (`1`) or not (`0`). Useful to react to track changes or to display the progress.";
+
+pub struct Track {
+    position: Duration,
+    duration: Option<Duration>,
+    playing: bool,
+    // when `position` has been reported
+    updated: Instant,
+
+    buffer: wgpu::Buffer,
+    duration_buffer: wgpu::Buffer,
+    playing_buffer: wgpu::Buffer,
+}
+
+impl Track {
+    pub fn set(&mut self, position: Duration, duration: Option<Duration>, playing: bool) {
+        self.position = position;
+        self.duration = duration;
+        self.playing = playing;
+        self.updated = Instant::now();
+    }
+
+    pub fn duration_binding() -> u32 {
+        ResourceBinding::TrackDuration as u32
+    }
+
+    pub fn playing_binding() -> u32 {
+        ResourceBinding::Playing as u32
+    }
+
+    pub fn duration_buffer(&self) -> &wgpu::Buffer {
+        &self.duration_buffer
+    }
+
+    pub fn playing_buffer(&self) -> &wgpu::Buffer {
+        &self.playing_buffer
+    }
+
+    /// Returns the position of the track now, assuming that it kept playing since it has been reported.
+    fn current_position(&self) -> Duration {
+        extrapolate(
+            self.position,
+            self.updated.elapsed(),
+            self.playing,
+            self.duration,
+        )
+    }
+}
+
+fn extrapolate(
+    position: Duration,
+    since_update: Duration,
+    playing: bool,
+    duration: Option<Duration>,
+) -> Duration {
+    let position = if playing {
+        position + since_update
+    } else {
+        position
+    };
+
+    match duration {
+        Some(duration) => position.min(duration),
+        None => position,
+    }
+}
+
+impl Resource for Track {
+    fn new(desc: &ShadyDescriptor) -> Self {
+        let size = std::mem::size_of::<f32>() as u64;
+
+        Self {
+            position: Duration::ZERO,
+            duration: None,
+            playing: false,
+            updated: Instant::now(),
+
+            buffer: Self::create_uniform_buffer(desc.device, size),
+            duration_buffer: Self::create_uniform_buffer(desc.device, size),
+            playing_buffer: Self::create_uniform_buffer(desc.device, size),
+        }
+    }
+
+    fn binding() -> u32 {
+        ResourceBinding::TrackTime as u32
+    }
+
+    fn buffer_label() -> &'static str {
+        "Shady iTrack buffer"
+    }
+
+    fn buffer_type() -> wgpu::BufferBindingType {
+        wgpu::BufferBindingType::Uniform
+    }
+
+    fn update_buffer(&self, queue: &wgpu::Queue) {
+        let position = self.current_position().as_secs_f32();
+        let duration = self.duration.unwrap_or_default().as_secs_f32();
+        let playing = if self.playing { 1f32 } else { 0. };
+
+        queue.write_buffer(self.buffer(), 0, bytemuck::cast_slice(&[position]));
+        queue.write_buffer(self.duration_buffer(), 0, bytemuck::cast_slice(&[duration]));
+        queue.write_buffer(self.playing_buffer(), 0, bytemuck::cast_slice(&[playing]));
+    }
+
+    fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl TemplateGenerator for Track {
+    fn write_wgsl_template(
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+@group({}) @binding({})
+var<uniform> iTrackTime: f32;
+
+@group({}) @binding({})
+var<uniform> iTrackDuration: f32;
+
+@group({}) @binding({})
+var<uniform> iPlaying: f32;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+            bind_group_index,
+            Self::duration_binding(),
+            bind_group_index,
+            Self::playing_binding(),
+        ))
+    }
+
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        writer.write_fmt(format_args!(
+            "
+{}
+layout(set = {}, binding = {}) uniform float iTrackTime;
+layout(set = {}, binding = {}) uniform float iTrackDuration;
+layout(set = {}, binding = {}) uniform float iPlaying;
+",
+            DESCRIPTION,
+            bind_group_index,
+            Self::binding(),
+            bind_group_index,
+            Self::duration_binding(),
+            bind_group_index,
+            Self::playing_binding(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_advances_while_playing() {
+        let secs = Duration::from_secs;
+
+        assert_eq!(
+            extrapolate(secs(10), secs(2), true, Some(secs(60))),
+            secs(12)
+        );
+        assert_eq!(
+            extrapolate(secs(10), secs(2), false, Some(secs(60))),
+            secs(10)
+        );
+        // doesn't run past the end of the track
+        assert_eq!(
+            extrapolate(secs(59), secs(2), true, Some(secs(60))),
+            secs(60)
+        );
+        assert_eq!(extrapolate(secs(59), secs(2), true, None), secs(61));
+    }
+}
@@ -0,0 +1,376 @@
+//! Caches the fragment shaders which have been parsed and validated by [naga] and the pipelines which have been created
+//! from them, so switching between a small set of shaders (for example the shaders of a playlist) doesn't parse
+//! and validate them again each time.
+//!
+//! # Example
+//! ```ignore
+//! let mut cache = ShaderCache::default();
+//!
+//! // parses, validates and creates the pipeline
+//! let pipeline = cache.render_pipeline(&device, ShaderSource::Wgsl(code.into()), &format, &options)?;
+//! // returns the pipeline of the first call
+//! let pipeline = cache.render_pipeline(&device, ShaderSource::Wgsl(code.into()), &format, &options)?;
+//! ```
+//!
+//! [naga]: wgpu::naga
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    num::NonZero,
+};
+
+use tracing::debug;
+use wgpu::{
+    naga::{
+        self,
+        valid::{Capabilities, ValidationFlags, Validator},
+    },
+    Device, ShaderSource,
+};
+
+use crate::{RenderPipelineOptions, ShadyRenderPipeline};
+
+/// The amount of modules and pipelines which [ShaderCache::default] keeps.
+pub const DEFAULT_SHADER_CACHE_CAPACITY: NonZero<usize> = NonZero::new(8).unwrap();
+
+/// The shader couldn't be parsed or validated. Contains the report of [naga].
+///
+/// [naga]: wgpu::naga
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShaderCacheError {
+    #[error("{0}")]
+    Parse(String),
+
+    #[error("{0}")]
+    Validation(String),
+
+    #[error("Only wgsl, glsl and naga sources can be turned into a module.")]
+    UnsupportedSource,
+}
+
+/// Keeps the most recently used shader modules and pipelines. See the [module docs](self) for an example.
+///
+/// Only wgsl and glsl sources are cached, other sources are passed through.
+/// The pipelines belong to the device which created them, so use one cache per device.
+pub struct ShaderCache {
+    modules: Lru<SourceKey, naga::Module>,
+    pipelines: Lru<PipelineKey, ShadyRenderPipeline>,
+}
+
+impl ShaderCache {
+    /// Creates a cache which keeps up to `capacity` modules and pipelines.
+    pub fn new(capacity: NonZero<usize>) -> Self {
+        Self {
+            modules: Lru::new(capacity),
+            pipelines: Lru::new(capacity),
+        }
+    }
+
+    /// Returns the parsed and validated module of the given source.
+    ///
+    /// Returns [ShaderCacheError::UnsupportedSource] for sources which aren't wgsl, glsl or naga (like SPIR-V).
+    pub fn module<'a>(
+        &'a mut self,
+        source: &ShaderSource<'_>,
+    ) -> Result<Cow<'a, naga::Module>, ShaderCacheError> {
+        let Some(key) = SourceKey::new(source) else {
+            return match source {
+                ShaderSource::Naga(module) => Ok(Cow::Owned(module.as_ref().clone())),
+                _ => Err(ShaderCacheError::UnsupportedSource),
+            };
+        };
+
+        if self.modules.get(&key).is_none() {
+            debug!("Parsing shader {:x}", key.hash);
+            let module = parse(source)?;
+            self.modules.insert(key.clone(), module);
+        }
+
+        Ok(Cow::Borrowed(self.modules.get(&key).unwrap()))
+    }
+
+    /// Like [crate::create_render_pipeline_with_options] but returns the cached pipeline if it has been created
+    /// with the same source, texture format and options before.
+    pub fn render_pipeline(
+        &mut self,
+        device: &Device,
+        source: ShaderSource<'_>,
+        texture_format: &wgpu::TextureFormat,
+        options: &RenderPipelineOptions,
+    ) -> Result<ShadyRenderPipeline, ShaderCacheError> {
+        let Some(source) = SourceKey::new(&source) else {
+            // other sources (like SPIR-V) are handed to wgpu without caching them
+            return Ok(crate::create_render_pipeline_with_options(
+                device,
+                source,
+                texture_format,
+                options,
+            ));
+        };
+
+        let key = PipelineKey {
+            options_hash: {
+                let mut hasher = DefaultHasher::new();
+                options.hash(&mut hasher);
+                hasher.finish()
+            },
+            texture_format: *texture_format,
+            source,
+        };
+
+        if let Some(pipeline) = self.pipelines.get(&key) {
+            return Ok(pipeline.clone());
+        }
+
+        let module = self.module(&key.source.to_shader_source())?.into_owned();
+        let pipeline = crate::create_render_pipeline_with_options(
+            device,
+            ShaderSource::Naga(Cow::Owned(module)),
+            texture_format,
+            options,
+        );
+
+        self.pipelines.insert(key, pipeline.clone());
+
+        Ok(pipeline)
+    }
+
+    /// Removes all modules and pipelines.
+    pub fn clear(&mut self) {
+        self.modules.clear();
+        self.pipelines.clear();
+    }
+}
+
+impl Default for ShaderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHADER_CACHE_CAPACITY)
+    }
+}
+
+/// The code of a cached shader.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CachedSource {
+    Wgsl(String),
+    Glsl {
+        shader: String,
+        stage: naga::ShaderStage,
+        // sorted, because the iteration order of the map isn't stable
+        defines: Vec<(String, String)>,
+    },
+}
+
+/// Identifies a cached shader. The hash is compared first, the code only if the hashes are equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourceKey {
+    hash: u64,
+    source: CachedSource,
+}
+
+impl SourceKey {
+    /// Returns `None` if the source can't be cached.
+    fn new(source: &ShaderSource<'_>) -> Option<Self> {
+        let source = match source {
+            ShaderSource::Wgsl(code) => CachedSource::Wgsl(code.to_string()),
+            ShaderSource::Glsl {
+                shader,
+                stage,
+                defines,
+            } => {
+                let mut defines = defines
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<Vec<_>>();
+                defines.sort();
+
+                CachedSource::Glsl {
+                    shader: shader.to_string(),
+                    stage: *stage,
+                    defines,
+                }
+            }
+            _ => return None,
+        };
+
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+
+        Some(Self {
+            hash: hasher.finish(),
+            source,
+        })
+    }
+
+    fn to_shader_source(&self) -> ShaderSource<'_> {
+        match &self.source {
+            CachedSource::Wgsl(code) => ShaderSource::Wgsl(code.into()),
+            CachedSource::Glsl {
+                shader,
+                stage,
+                defines,
+            } => ShaderSource::Glsl {
+                shader: shader.into(),
+                stage: *stage,
+                defines: defines.iter().cloned().collect(),
+            },
+        }
+    }
+}
+
+/// Identifies a cached pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PipelineKey {
+    // the options contain references, so only their hash is stored
+    options_hash: u64,
+    texture_format: wgpu::TextureFormat,
+    source: SourceKey,
+}
+
+fn parse(source: &ShaderSource<'_>) -> Result<naga::Module, ShaderCacheError> {
+    let (module, code) = match source {
+        ShaderSource::Wgsl(code) => (
+            naga::front::wgsl::parse_str(code)
+                .map_err(|err| ShaderCacheError::Parse(err.emit_to_string(code)))?,
+            code,
+        ),
+        ShaderSource::Glsl {
+            shader,
+            stage,
+            defines,
+        } => {
+            let options = naga::front::glsl::Options {
+                stage: *stage,
+                defines: defines.clone(),
+            };
+
+            (
+                naga::front::glsl::Frontend::default()
+                    .parse(&options, shader)
+                    .map_err(|err| ShaderCacheError::Parse(err.emit_to_string(shader)))?,
+                shader,
+            )
+        }
+        _ => unreachable!("Only wgsl and glsl sources are parsed"),
+    };
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| ShaderCacheError::Validation(err.emit_to_string(code)))?;
+
+    Ok(module)
+}
+
+/// The least recently used entry is dropped if a new entry doesn't fit anymore.
+struct Lru<K, V> {
+    capacity: NonZero<usize>,
+    // the least recently used entry first
+    entries: VecDeque<(K, V)>,
+}
+
+impl<K: PartialEq, V> Lru<K, V> {
+    fn new(capacity: NonZero<usize>) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity.get()),
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let idx = self.entries.iter().position(|(k, _)| k == key)?;
+
+        let entry = self.entries.remove(idx).unwrap();
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, value)| value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() == self.capacity.get() {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back((key, value));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAGMENT_CODE: &str = "
+        @fragment
+        fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+            return vec4<f32>(1.);
+        }
+    ";
+
+    #[test]
+    fn modules_are_reused() {
+        let mut cache = ShaderCache::default();
+        let source = ShaderSource::Wgsl(FRAGMENT_CODE.into());
+
+        for _ in 0..2 {
+            assert!(matches!(cache.module(&source), Ok(Cow::Borrowed(_))));
+        }
+        assert_eq!(cache.modules.entries.len(), 1);
+
+        // the same code in another language is another module
+        let source = ShaderSource::Glsl {
+            shader: FRAGMENT_CODE.into(),
+            stage: naga::ShaderStage::Fragment,
+            defines: Default::default(),
+        };
+        assert!(cache.module(&source).is_err());
+        assert_eq!(cache.modules.entries.len(), 1);
+    }
+
+    #[test]
+    fn invalid_shaders_are_rejected() {
+        let mut cache = ShaderCache::default();
+
+        let source = ShaderSource::Wgsl("fn main( {}".into());
+        assert!(matches!(
+            cache.module(&source),
+            Err(ShaderCacheError::Parse(_))
+        ));
+
+        // parses but returns the wrong type
+        let source = ShaderSource::Wgsl("fn main() -> f32 { return vec2<f32>(1.); }".into());
+        assert!(matches!(
+            cache.module(&source),
+            Err(ShaderCacheError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn least_recently_used_is_dropped() {
+        let mut lru = Lru::new(NonZero::new(2).unwrap());
+        lru.insert(1, 'a');
+        lru.insert(2, 'b');
+
+        assert_eq!(lru.get(&1), Some(&'a'));
+        lru.insert(3, 'c');
+
+        assert_eq!(lru.get(&2), None);
+        assert_eq!(lru.get(&1), Some(&'a'));
+        assert_eq!(lru.get(&3), Some(&'c'));
+    }
+
+    #[test]
+    fn hash_collisions_are_detected() {
+        let mut cache = ShaderCache::default();
+        let source = ShaderSource::Wgsl(FRAGMENT_CODE.into());
+        assert!(cache.module(&source).is_ok());
+
+        // another shader with the same hash
+        let mut key = SourceKey::new(&ShaderSource::Wgsl("fn main() {}".into())).unwrap();
+        key.hash = SourceKey::new(&source).unwrap().hash;
+        assert!(cache.modules.get(&key).is_none());
+
+        assert!(matches!(cache.module(&source), Ok(Cow::Borrowed(_))));
+    }
+}
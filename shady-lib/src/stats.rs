@@ -0,0 +1,242 @@
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use wgpu::{CommandEncoder, Device, Queue};
+
+// one timestamp at the beginning and one at the end of the render pass
+const AMOUNT_QUERIES: u32 = 2;
+const QUERY_BUFFER_SIZE: u64 = AMOUNT_QUERIES as u64 * std::mem::size_of::<u64>() as u64;
+
+// results of mapping the readback buffer
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// The timings of the last frame which has been rendered by [crate::Shady].
+///
+/// See [crate::Shady::frame_stats].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// The CPU time which has been spent within the `update_*_buffer` functions of the frame.
+    pub buffer_updates: Duration,
+
+    /// The CPU time which has been spent to encode the render pass within [crate::Shady::add_render_pass].
+    pub render_pass_encoding: Duration,
+
+    /// The time which the GPU needed to execute the render pass (see [crate::Shady::enable_gpu_timing]).
+    ///
+    /// It lags a few frames behind since the timestamps are read back asynchronously.
+    /// `None` if GPU timing isn't enabled or no timestamps have been read back yet.
+    pub gpu_render_pass: Option<Duration>,
+}
+
+/// Collects the timings of the current frame.
+#[derive(Default)]
+pub struct StatsRecorder {
+    stats: FrameStats,
+    buffer_updates: Duration,
+    gpu_timer: Option<GpuTimer>,
+}
+
+impl StatsRecorder {
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    // `unused`: Without any resource feature, no buffer gets updated.
+    #[allow(unused)]
+    pub fn add_buffer_update(&mut self, duration: Duration) {
+        self.buffer_updates += duration;
+    }
+
+    /// Finishes the current frame.
+    pub fn finish_frame(&mut self, render_pass_encoding: Duration) {
+        self.stats.buffer_updates = std::mem::take(&mut self.buffer_updates);
+        self.stats.render_pass_encoding = render_pass_encoding;
+    }
+
+    pub fn set_gpu_timer(&mut self, gpu_timer: Option<GpuTimer>) {
+        self.stats.gpu_render_pass = None;
+        self.gpu_timer = gpu_timer;
+    }
+
+    /// Returns the timestamp writes for the next render pass if the timestamps can be written this frame.
+    pub fn timestamp_writes(&mut self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let gpu_timer = self.gpu_timer.as_mut()?;
+
+        if let Some(duration) = gpu_timer.poll() {
+            self.stats.gpu_render_pass = Some(duration);
+        }
+
+        gpu_timer.timestamp_writes()
+    }
+
+    /// Copies the timestamps of the render pass into the readback buffer.
+    pub fn resolve_timestamps(&mut self, encoder: &mut CommandEncoder) {
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.resolve(encoder);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    /// The timestamps can be written.
+    Idle,
+
+    /// The timestamps have been written within the last (hopefully submitted) command encoder.
+    Resolved,
+
+    /// Waiting for the readback buffer to be mapped.
+    Mapping,
+}
+
+/// Measures the duration of the render pass on the GPU with timestamp queries.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+
+    // nanoseconds per timestamp tick
+    period: f32,
+    state: ReadbackState,
+    map_result: Arc<AtomicU8>,
+}
+
+impl GpuTimer {
+    /// Returns `None` if the device doesn't support [wgpu::Features::TIMESTAMP_QUERY].
+    pub fn new(device: &Device, queue: &Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Shady timestamp queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: AMOUNT_QUERIES,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shady timestamp resolve buffer"),
+            size: QUERY_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shady timestamp readback buffer"),
+            size: QUERY_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            state: ReadbackState::Idle,
+            map_result: Arc::new(AtomicU8::new(MAP_PENDING)),
+        })
+    }
+
+    /// Progresses the readback of the timestamps. Returns the duration of the render pass once they're read.
+    fn poll(&mut self) -> Option<Duration> {
+        match self.state {
+            ReadbackState::Idle => None,
+            ReadbackState::Resolved => {
+                // the encoder with the copy command has been submitted in the meantime
+                let map_result = self.map_result.clone();
+                self.readback_buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let value = if result.is_ok() { MAP_OK } else { MAP_FAILED };
+                        map_result.store(value, Ordering::Release);
+                    });
+
+                self.state = ReadbackState::Mapping;
+                None
+            }
+            ReadbackState::Mapping => {
+                match self.map_result.swap(MAP_PENDING, Ordering::Acquire) {
+                    MAP_PENDING => return None,
+                    MAP_FAILED => {
+                        // start over with the next render pass
+                        self.readback_buffer.unmap();
+                        self.state = ReadbackState::Idle;
+                        return None;
+                    }
+                    _ => {}
+                }
+
+                let timestamps: [u64; AMOUNT_QUERIES as usize] = {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    bytemuck::pod_read_unaligned(&data)
+                };
+                self.readback_buffer.unmap();
+                self.state = ReadbackState::Idle;
+
+                let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                Some(Duration::from_nanos(
+                    (ticks as f64 * self.period as f64) as u64,
+                ))
+            }
+        }
+    }
+
+    fn timestamp_writes(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        (self.state == ReadbackState::Idle).then_some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        })
+    }
+
+    fn resolve(&mut self, encoder: &mut CommandEncoder) {
+        if self.state != ReadbackState::Idle {
+            return;
+        }
+
+        encoder.resolve_query_set(&self.query_set, 0..AMOUNT_QUERIES, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            QUERY_BUFFER_SIZE,
+        );
+
+        self.state = ReadbackState::Resolved;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_updates_are_summed_per_frame() {
+        let mut recorder = StatsRecorder::default();
+
+        recorder.add_buffer_update(Duration::from_millis(1));
+        recorder.add_buffer_update(Duration::from_millis(2));
+        recorder.finish_frame(Duration::from_millis(4));
+
+        assert_eq!(
+            recorder.stats(),
+            FrameStats {
+                buffer_updates: Duration::from_millis(3),
+                render_pass_encoding: Duration::from_millis(4),
+                gpu_render_pass: None,
+            }
+        );
+
+        recorder.finish_frame(Duration::ZERO);
+        assert_eq!(recorder.stats().buffer_updates, Duration::ZERO);
+    }
+}
@@ -21,6 +21,13 @@ pub const DEFAULT_TEMPLATE_GLSL_BODY: &str = "
     fragColor = vec4(col,1.0);      
 ";
 
+const WGSL_PRELUDE: &str = include_str!("prelude.wgsl");
+const GLSL_PRELUDE: &str = include_str!("prelude.glsl");
+#[cfg(feature = "audio")]
+const WGSL_AUDIO_PRELUDE: &str = include_str!("prelude_audio.wgsl");
+#[cfg(feature = "audio")]
+const GLSL_AUDIO_PRELUDE: &str = include_str!("prelude_audio.glsl");
+
 /// The shader languages where the templates can be generated for.
 ///
 /// # Example
@@ -47,7 +54,10 @@ pub(crate) trait TemplateGenerator {
         bind_group_index: u32,
     ) -> Result<(), fmt::Error>;
 
-    fn write_glsl_template(writer: &mut dyn fmt::Write) -> Result<(), fmt::Error>;
+    fn write_glsl_template(
+        writer: &mut dyn fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error>;
 }
 
 impl TemplateLang {
@@ -93,9 +103,132 @@ impl TemplateLang {
         writer: &mut dyn std::fmt::Write,
         body: Option<&str>,
     ) -> Result<(), fmt::Error> {
+        self.generate_with_bind_group_index(writer, body, BIND_GROUP_INDEX)
+    }
+
+    /// Like [TemplateLang::generate] but the template also contains the helper functions of the prelude
+    /// (see [TemplateLang::generate_prelude]).
+    ///
+    /// # Example
+    /// ```
+    /// use shady::TemplateLang;
+    ///
+    /// let mut template = String::new();
+    /// TemplateLang::Wgsl
+    ///     .generate_with_prelude(&mut template, None)
+    ///     .unwrap();
+    ///
+    /// assert!(template.contains("fn hsv2rgb"));
+    /// ```
+    pub fn generate_with_prelude(
+        self,
+        writer: &mut dyn std::fmt::Write,
+        body: Option<&str>,
+    ) -> Result<(), fmt::Error> {
+        self.write_template(writer, body, BIND_GROUP_INDEX, true)
+    }
+
+    /// Only writes the helper functions which are often needed in shaders:
+    ///
+    /// - `hsv2rgb`: Converts a color from HSV to RGB.
+    /// - `hash21` and `noise`: Pseudo random values and smooth value noise for a 2D point.
+    /// - `rotate2d`: A matrix which rotates a 2D point.
+    /// - `sdCircle`, `sdBox`, `sdSphere` and `sdBox3`: Signed distance functions of basic shapes.
+    /// - `audioAvg(lo, hi)` and `audioAt(x)`: Sample `iAudio` at relative positions (only with the `audio` feature).
+    ///
+    /// The audio helpers use the resources, so they have to be declared before the prelude.
+    ///
+    /// # Example
+    /// ```
+    /// use shady::TemplateLang;
+    ///
+    /// let mut shader = String::new();
+    /// TemplateLang::Glsl.generate_resources(&mut shader, 0).unwrap();
+    /// TemplateLang::Glsl.generate_prelude(&mut shader).unwrap();
+    /// ```
+    pub fn generate_prelude(self, writer: &mut dyn std::fmt::Write) -> Result<(), fmt::Error> {
         match self {
             TemplateLang::Wgsl => {
-                Resources::write_wgsl_template(writer, BIND_GROUP_INDEX)?;
+                writer.write_str(WGSL_PRELUDE)?;
+                #[cfg(feature = "audio")]
+                writer.write_str(WGSL_AUDIO_PRELUDE)?;
+            }
+            TemplateLang::Glsl => {
+                writer.write_str(GLSL_PRELUDE)?;
+                #[cfg(feature = "audio")]
+                writer.write_str(GLSL_AUDIO_PRELUDE)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Only writes the declarations of the resources in the bind group with the given index
+    /// so you can use them in other shader stages (for example in a [crate::VertexShader]).
+    ///
+    /// # Example
+    /// ```
+    /// use shady::TemplateLang;
+    ///
+    /// let mut vertex_shader = String::new();
+    /// TemplateLang::Glsl
+    ///     .generate_resources(&mut vertex_shader, 0)
+    ///     .unwrap();
+    ///
+    /// vertex_shader.push_str("
+    /// void main() {
+    ///     gl_Position = vec4(iTime, 0.0, 0.0, 1.0);
+    /// }
+    /// ");
+    /// ```
+    pub fn generate_resources(
+        self,
+        writer: &mut dyn std::fmt::Write,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        match self {
+            TemplateLang::Wgsl => Resources::write_wgsl_template(writer, bind_group_index),
+            TemplateLang::Glsl => Resources::write_glsl_template(writer, bind_group_index),
+        }
+    }
+
+    /// Like [TemplateLang::generate] but the resources are declared in the bind group with the given index.
+    /// Use it if you've set [crate::RenderPipelineOptions::bind_group_index].
+    ///
+    /// # Example
+    /// ```
+    /// use shady::TemplateLang;
+    ///
+    /// let mut template = String::new();
+    ///
+    /// // the resources of shady are in `@group(1)`
+    /// TemplateLang::Wgsl
+    ///     .generate_with_bind_group_index(&mut template, None, 1)
+    ///     .unwrap();
+    /// ```
+    pub fn generate_with_bind_group_index(
+        self,
+        writer: &mut dyn std::fmt::Write,
+        body: Option<&str>,
+        bind_group_index: u32,
+    ) -> Result<(), fmt::Error> {
+        self.write_template(writer, body, bind_group_index, false)
+    }
+
+    fn write_template(
+        self,
+        writer: &mut dyn std::fmt::Write,
+        body: Option<&str>,
+        bind_group_index: u32,
+        with_prelude: bool,
+    ) -> Result<(), fmt::Error> {
+        match self {
+            TemplateLang::Wgsl => {
+                Resources::write_wgsl_template(writer, bind_group_index)?;
+                if with_prelude {
+                    writer.write_str("\n")?;
+                    self.generate_prelude(writer)?;
+                }
 
                 writer.write_fmt(format_args!(
                     "
@@ -110,7 +243,11 @@ fn {}(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {{
             }
 
             TemplateLang::Glsl => {
-                Resources::write_glsl_template(writer)?;
+                Resources::write_glsl_template(writer, bind_group_index)?;
+                if with_prelude {
+                    writer.write_str("\n")?;
+                    self.generate_prelude(writer)?;
+                }
 
                 writer.write_fmt(format_args!(
                     "
@@ -159,4 +296,68 @@ mod tests {
             panic!("{}", msg);
         }
     }
+
+    #[test]
+    fn valid_wgsl_template_with_prelude() {
+        let mut template = String::new();
+        TemplateLang::Wgsl
+            .generate_with_prelude(&mut template, None)
+            .unwrap();
+
+        if let Err(err) = wgpu::naga::front::wgsl::parse_str(&template) {
+            let msg = err.emit_to_string(&template);
+            panic!("{}", msg);
+        }
+    }
+
+    #[test]
+    fn valid_glsl_template_with_prelude() {
+        let mut template = String::new();
+        TemplateLang::Glsl
+            .generate_with_prelude(&mut template, None)
+            .unwrap();
+
+        let mut parser = wgpu::naga::front::glsl::Frontend::default();
+        if let Err(err) = parser.parse(&Options::from(ShaderStage::Fragment), &template) {
+            let msg = err.emit_to_string(&template);
+            panic!("{}", msg);
+        }
+    }
+
+    #[test]
+    fn glsl_vertex_shader_with_resources() {
+        let mut template = String::new();
+        TemplateLang::Glsl
+            .generate_resources(&mut template, 0)
+            .unwrap();
+        template.push_str(
+            "
+void main() {
+    gl_Position = vec4(iTime, 0.0, 0.0, 1.0);
+}
+",
+        );
+
+        let mut parser = wgpu::naga::front::glsl::Frontend::default();
+        if let Err(err) = parser.parse(&Options::from(ShaderStage::Vertex), &template) {
+            let msg = err.emit_to_string(&template);
+            panic!("{}", msg);
+        }
+    }
+
+    #[test]
+    fn glsl_template_with_bind_group_index() {
+        let mut template = String::new();
+        TemplateLang::Glsl
+            .generate_with_bind_group_index(&mut template, None, 1)
+            .unwrap();
+
+        assert!(template.contains("set = 1"));
+
+        let mut parser = wgpu::naga::front::glsl::Frontend::default();
+        if let Err(err) = parser.parse(&Options::from(ShaderStage::Fragment), &template) {
+            let msg = err.emit_to_string(&template);
+            panic!("{}", msg);
+        }
+    }
 }
@@ -0,0 +1,294 @@
+//! Keyframe based automation of custom shader parameters.
+//!
+//! A [Timeline] contains multiple [Track]s where each track animates one value over time.
+//! The values of all tracks are written into the `iTimeline` storage buffer, in the same order as the tracks are listed.
+//! The time is the same as the one of `iTime` so your animations are synchronized with it.
+//!
+//! # Example
+//! ```
+//! use shady::timeline::Timeline;
+//!
+//! let timeline = Timeline::from_toml(r#"
+//! repeat = true
+//!
+//! [[tracks]]
+//! name = "brightness"
+//! keyframes = [
+//!     { time = 0.0, value = 0.0 },
+//!     { time = 2.0, value = 1.0, easing = "ease-in-out" },
+//!     { time = 4.0, value = 0.0 },
+//! ]
+//! "#).unwrap();
+//!
+//! assert_eq!(timeline.sample(1.0), vec![0.5]);
+//! // `repeat` is set so we're at the beginning again
+//! assert_eq!(timeline.sample(4.0), vec![0.0]);
+//! ```
+use serde::{Deserialize, Serialize};
+
+/// Describes how the value should move from the previous keyframe to the next keyframe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    /// Keep the value of the previous keyframe until the next keyframe is reached.
+    Step,
+
+    /// Move with a constant speed to the next value.
+    #[default]
+    Linear,
+
+    /// Start slow and get faster.
+    EaseIn,
+
+    /// Start fast and get slower.
+    EaseOut,
+
+    /// Start slow, get faster and slow down in the end again.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the given progress `t` (within `[0, 1]`) to the eased progress.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Self::Step => 0.,
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1. - (1. - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+/// A value at a given point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// The point in time (in seconds) of this keyframe.
+    pub time: f32,
+
+    /// The value of the track at [Keyframe::time].
+    pub value: f32,
+
+    /// How the value of the previous keyframe should move to the value of this keyframe.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// The keyframes of one animated value.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Track {
+    /// An optional name of the track to make the description more readable.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The keyframes of the track. They are sorted by their time when the timeline gets created.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Returns the value of the track at the given `time`.
+    ///
+    /// Before the first keyframe the value of the first keyframe is returned and after
+    /// the last keyframe the value of the last keyframe.
+    /// Tracks without any keyframes are always `0`.
+    pub fn sample(&self, time: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+            return 0.;
+        };
+
+        if time <= first.time {
+            return first.value;
+        } else if time >= last.time {
+            return last.value;
+        }
+
+        let next_idx = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let prev = &self.keyframes[next_idx - 1];
+        let next = &self.keyframes[next_idx];
+
+        let t = (time - prev.time) / (next.time - prev.time);
+        let t = next.easing.apply(t);
+
+        prev.value + (next.value - prev.value) * t
+    }
+
+    /// Returns the time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|k| k.time).unwrap_or(0.)
+    }
+}
+
+/// Errors which can occur while creating a [Timeline].
+#[derive(thiserror::Error, Debug)]
+pub enum TimelineError {
+    #[error("Couldn't parse the timeline description: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Keyframe {keyframe_idx} of track {track_idx} has an invalid time: {time}")]
+    InvalidKeyframeTime {
+        track_idx: usize,
+        keyframe_idx: usize,
+        time: f32,
+    },
+}
+
+/// A collection of [Track]s.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    /// Start from the beginning after the last keyframe of all tracks has been reached.
+    #[serde(default)]
+    pub repeat: bool,
+
+    /// The tracks of the timeline.
+    #[serde(default)]
+    pub tracks: Vec<Track>,
+}
+
+impl Timeline {
+    /// Creates a new timeline out of the given tracks.
+    pub fn new(tracks: Vec<Track>, repeat: bool) -> Result<Self, TimelineError> {
+        let mut timeline = Self { repeat, tracks };
+        timeline.prepare()?;
+        Ok(timeline)
+    }
+
+    /// Parses the timeline from the given toml description.
+    /// See the [module docs](crate::timeline) for an example.
+    pub fn from_toml(description: &str) -> Result<Self, TimelineError> {
+        let mut timeline: Self = toml::from_str(description)?;
+        timeline.prepare()?;
+        Ok(timeline)
+    }
+
+    /// Returns the amount of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Returns `true` if the timeline doesn't have any tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Returns the time of the last keyframe of all tracks.
+    pub fn duration(&self) -> f32 {
+        self.tracks.iter().map(Track::duration).fold(0., f32::max)
+    }
+
+    /// Returns the values of each track at the given `time`.
+    pub fn sample(&self, time: f32) -> Vec<f32> {
+        let mut values = vec![0.; self.len()];
+        self.sample_into(time, &mut values);
+        values
+    }
+
+    /// Writes the values of each track at the given `time` into `values`.
+    pub fn sample_into(&self, time: f32, values: &mut [f32]) {
+        let duration = self.duration();
+        let time = if self.repeat && duration > 0. {
+            time.rem_euclid(duration)
+        } else {
+            time
+        };
+
+        for (value, track) in values.iter_mut().zip(self.tracks.iter()) {
+            *value = track.sample(time);
+        }
+    }
+
+    fn prepare(&mut self) -> Result<(), TimelineError> {
+        for (track_idx, track) in self.tracks.iter_mut().enumerate() {
+            if let Some((keyframe_idx, keyframe)) = track
+                .keyframes
+                .iter()
+                .enumerate()
+                .find(|(_, keyframe)| !keyframe.time.is_finite() || keyframe.time < 0.)
+            {
+                return Err(TimelineError::InvalidKeyframeTime {
+                    track_idx,
+                    keyframe_idx,
+                    time: keyframe.time,
+                });
+            }
+
+            track.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(keyframes: &[(f32, f32, Easing)]) -> Track {
+        Track {
+            name: None,
+            keyframes: keyframes
+                .iter()
+                .map(|&(time, value, easing)| Keyframe {
+                    time,
+                    value,
+                    easing,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn empty_track() {
+        assert_eq!(Track::default().sample(1.), 0.);
+    }
+
+    #[test]
+    fn clamps_outside_of_keyframes() {
+        let track = track(&[(1., 2., Easing::Linear), (2., 4., Easing::Linear)]);
+
+        assert_eq!(track.sample(0.), 2.);
+        assert_eq!(track.sample(3.), 4.);
+    }
+
+    #[test]
+    fn linear_and_step() {
+        let track = track(&[
+            (0., 0., Easing::Linear),
+            (1., 1., Easing::Linear),
+            (2., 0., Easing::Step),
+        ]);
+
+        assert_eq!(track.sample(0.5), 0.5);
+        assert_eq!(track.sample(1.5), 1.);
+    }
+
+    #[test]
+    fn unsorted_keyframes_get_sorted() {
+        let timeline = Timeline::new(
+            vec![track(&[(2., 1., Easing::Linear), (0., 0., Easing::Linear)])],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(timeline.sample(1.), vec![0.5]);
+    }
+
+    #[test]
+    fn invalid_keyframe_time() {
+        let result = Timeline::new(vec![track(&[(-1., 1., Easing::Linear)])], false);
+
+        assert!(matches!(
+            result,
+            Err(TimelineError::InvalidKeyframeTime { .. })
+        ));
+    }
+}
@@ -2,10 +2,16 @@ struct VertexInput {
     @location(0) position: vec2<f32>,
 }
 
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // the instance of `Shady::add_render_pass_instanced`
+    @location(0) @interpolate(flat) instance: u32,
+}
+
 @vertex
 fn vertex_main(
     model: VertexInput,
-) -> @builtin(position) vec4<f32> {
-    return vec4<f32>(model.position, 0.0, 1.0);
+    @builtin(instance_index) instance: u32,
+) -> VertexOutput {
+    return VertexOutput(vec4<f32>(model.position, 0.0, 1.0), instance);
 }
-
@@ -5,6 +5,9 @@ use wgpu::util::DeviceExt;
 
 type VertexCoord = [f32; 2];
 
+/// Draws the fullscreen quad and passes the instance index to the fragment shader.
+pub const VERTEX_SHADER: &str = include_str!("vertex_shader.wgsl");
+
 const TOP_LEFT_CORNER: VertexCoord = [-1.0, 1.0];
 const BOTTOM_LEFT_CORNER: VertexCoord = [-1.0, -1.0];
 const BOTTOM_RIGHT_CORNER: VertexCoord = [1.0, -1.0];
@@ -57,3 +60,36 @@ pub fn index_buffer(device: &wgpu::Device) -> wgpu::Buffer {
 pub const fn index_buffer_range() -> Range<u32> {
     0..INDICES.len() as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use wgpu::naga::{
+        front::wgsl,
+        valid::{Capabilities, ValidationFlags, Validator},
+    };
+
+    use super::*;
+
+    #[test]
+    fn vertex_shader_passes_the_instance() {
+        let module = wgsl::parse_str(VERTEX_SHADER).unwrap();
+        Validator::new(ValidationFlags::all(), Capabilities::empty())
+            .validate(&module)
+            .unwrap();
+
+        let entry_point = &module.entry_points[0];
+        let output = &module.types[entry_point.function.result.as_ref().unwrap().ty];
+        let wgpu::naga::TypeInner::Struct { members, .. } = &output.inner else {
+            panic!("The vertex shader should return a struct");
+        };
+
+        assert!(members.iter().any(|member| matches!(
+            member.binding,
+            Some(wgpu::naga::Binding::Location {
+                location: 0,
+                interpolation: Some(wgpu::naga::Interpolation::Flat),
+                ..
+            })
+        )));
+    }
+}
@@ -0,0 +1,144 @@
+use std::num::NonZero;
+
+/// A rectangle of the render target (in pixels) where a pipeline should be drawn into.
+///
+/// See [crate::Shady::add_viewports_render_pass].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Viewport {
+    /// The x coordinate of the top left corner.
+    pub x: u32,
+
+    /// The y coordinate of the top left corner.
+    pub y: u32,
+
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Splits an area of `(width, height)` pixels into a grid with the given amount of columns and rows.
+    ///
+    /// The cells are returned row by row, starting at the top left corner. If the size isn't divisible
+    /// by the amount of columns (or rows), the cells in the last column (or row) get the remaining pixels.
+    ///
+    /// # Example
+    /// ```
+    /// use std::num::NonZero;
+    /// use shady::Viewport;
+    ///
+    /// let cells = Viewport::grid((100, 50), NonZero::new(2).unwrap(), NonZero::new(1).unwrap());
+    /// assert_eq!(
+    ///     cells,
+    ///     [
+    ///         Viewport { x: 0, y: 0, width: 50, height: 50 },
+    ///         Viewport { x: 50, y: 0, width: 50, height: 50 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn grid(
+        (width, height): (u32, u32),
+        columns: NonZero<u32>,
+        rows: NonZero<u32>,
+    ) -> Vec<Self> {
+        let (xs, widths) = split(width, columns.get());
+        let (ys, heights) = split(height, rows.get());
+
+        ys.zip(heights)
+            .flat_map(|(y, height)| {
+                xs.clone().zip(widths.clone()).map(move |(x, width)| Self {
+                    x,
+                    y,
+                    width,
+                    height,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the part of the viewport which lies within a render target of the given size
+    /// or `None` if nothing of it is visible.
+    pub(crate) fn clamp(&self, (width, height): (u32, u32)) -> Option<Self> {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        let clamped = Self {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        };
+
+        (clamped.width > 0 && clamped.height > 0).then_some(clamped)
+    }
+}
+
+/// Returns the offsets and the lengths of `amount` parts of `length`.
+fn split(
+    length: u32,
+    amount: u32,
+) -> (
+    impl Iterator<Item = u32> + Clone,
+    impl Iterator<Item = u32> + Clone,
+) {
+    let step = length / amount;
+    let offsets = (0..amount).map(move |idx| idx * step);
+    let lengths = (0..amount).map(move |idx| {
+        if idx + 1 == amount {
+            length - idx * step
+        } else {
+            step
+        }
+    });
+
+    (offsets, lengths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_with_remainder() {
+        let cells = Viewport::grid((10, 7), NonZero::new(3).unwrap(), NonZero::new(2).unwrap());
+
+        assert_eq!(cells.len(), 6);
+        assert_eq!(
+            cells[2],
+            Viewport {
+                x: 6,
+                y: 0,
+                width: 4,
+                height: 3
+            }
+        );
+        assert_eq!(
+            cells[3],
+            Viewport {
+                x: 0,
+                y: 3,
+                width: 3,
+                height: 4
+            }
+        );
+    }
+
+    #[test]
+    fn clamp_to_target() {
+        let viewport = Viewport {
+            x: 8,
+            y: 2,
+            width: 4,
+            height: 4,
+        };
+
+        assert_eq!(
+            viewport.clamp((10, 10)),
+            Some(Viewport {
+                x: 8,
+                y: 2,
+                width: 2,
+                height: 4
+            })
+        );
+        assert_eq!(viewport.clamp((8, 10)), None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use wgpu::{Adapter, Backends, Instance, Surface};
+
+/// The graphics backends which the user can choose from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+
+impl From<Backend> for Backends {
+    fn from(backend: Backend) -> Self {
+        match backend {
+            Backend::Vulkan => Backends::VULKAN,
+            Backend::Metal => Backends::METAL,
+            Backend::Dx12 => Backends::DX12,
+            Backend::Gl => Backends::GL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PowerPreference {
+    /// Prefer the integrated GPU.
+    Low,
+    /// Prefer the dedicated GPU.
+    High,
+}
+
+impl From<PowerPreference> for wgpu::PowerPreference {
+    fn from(pref: PowerPreference) -> Self {
+        match pref {
+            PowerPreference::Low => wgpu::PowerPreference::LowPower,
+            PowerPreference::High => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
+
+/// Selects an adapter either by its index or by (a part of) its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl From<&str> for AdapterSelector {
+    fn from(value: &str) -> Self {
+        match value.parse::<usize>() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(value.to_string()),
+        }
+    }
+}
+
+impl AdapterSelector {
+    fn matches(&self, index: usize, adapter: &Adapter) -> bool {
+        match self {
+            Self::Index(i) => *i == index,
+            Self::Name(name) => adapter
+                .get_info()
+                .name
+                .to_lowercase()
+                .contains(&name.to_lowercase()),
+        }
+    }
+}
+
+/// Describes how `shady-toy` should pick its graphics adapter.
+#[derive(Debug, Clone, Default)]
+pub struct AdapterConfig {
+    pub backend: Option<Backend>,
+    pub adapter: Option<AdapterSelector>,
+    // only used if `adapter` isn't set
+    pub power_preference: Option<PowerPreference>,
+}
+
+impl AdapterConfig {
+    pub fn backends(&self) -> Backends {
+        self.backend
+            .map(Backends::from)
+            .unwrap_or(Backends::PRIMARY)
+    }
+
+    pub fn instance(&self) -> Instance {
+        Instance::new(&wgpu::InstanceDescriptor {
+            backends: self.backends(),
+            ..Default::default()
+        })
+    }
+
+    /// Returns the adapter which fits to the config the most.
+    pub fn request_adapter(
+        &self,
+        instance: &Instance,
+        compatible_surface: Option<&Surface>,
+    ) -> Result<Adapter, AdapterError> {
+        match &self.adapter {
+            Some(selector) => {
+                let adapters = instance.enumerate_adapters(self.backends());
+
+                let names = adapters
+                    .iter()
+                    .map(|adapter| adapter.get_info().name)
+                    .collect::<Vec<String>>();
+
+                adapters
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, adapter)| {
+                        compatible_surface
+                            .map(|surface| adapter.is_surface_supported(surface))
+                            .unwrap_or(true)
+                    })
+                    .find(|(index, adapter)| selector.matches(*index, adapter))
+                    .map(|(_, adapter)| adapter)
+                    .ok_or(AdapterError::NoMatchingAdapter(names))
+            }
+            None => pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.power_preference.map(Into::into).unwrap_or_default(),
+                compatible_surface,
+                ..Default::default()
+            }))
+            .ok_or(AdapterError::NoAdapter),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdapterError {
+    #[error("Couldn't find any suitable graphics adapter.")]
+    NoAdapter,
+
+    #[error("Couldn't find the requested graphics adapter. Available adapters: {}", AvailableAdapters(.0))]
+    NoMatchingAdapter(Vec<String>),
+}
+
+struct AvailableAdapters<'a>(&'a [String]);
+
+impl fmt::Display for AvailableAdapters<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, name) in self.0.iter().enumerate() {
+            write!(f, "\n    {}: {}", index, name)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapter_selector_from_str() {
+        assert_eq!(AdapterSelector::from("1"), AdapterSelector::Index(1));
+        assert_eq!(
+            AdapterSelector::from("NVIDIA"),
+            AdapterSelector::Name("NVIDIA".to_string())
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! The textures which can be passed to the `iChannel*` inputs of the shader.
+
+use std::{convert::Infallible, path::PathBuf, str::FromStr};
+
+/// The size of the built-in textures.
+const BUILTIN_SIZE: u32 = 256;
+
+/// Where the texture of a channel comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelSource {
+    /// Random (but always the same) values in each color channel.
+    Noise,
+
+    /// Red grows from left to right and green from top to bottom.
+    Gradient,
+
+    /// An image file.
+    Image(PathBuf),
+}
+
+impl FromStr for ChannelSource {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "noise" => Self::Noise,
+            "gradient" => Self::Gradient,
+            path => Self::Image(PathBuf::from(path)),
+        })
+    }
+}
+
+/// The pixels of a channel texture.
+#[derive(Debug, Clone)]
+pub struct ChannelImage {
+    pub size: (u32, u32),
+
+    /// 4 bytes (red, green, blue, alpha) per pixel, row by row.
+    pub rgba: Vec<u8>,
+}
+
+impl ChannelSource {
+    pub fn load(&self) -> Result<ChannelImage, image::ImageError> {
+        let image = match self {
+            Self::Noise => {
+                let mut state = 0x2545_f491u32;
+                let rgba = (0..BUILTIN_SIZE * BUILTIN_SIZE * 4)
+                    .map(|_| {
+                        // xorshift
+                        state ^= state << 13;
+                        state ^= state >> 17;
+                        state ^= state << 5;
+                        (state >> 24) as u8
+                    })
+                    .collect();
+
+                ChannelImage {
+                    size: (BUILTIN_SIZE, BUILTIN_SIZE),
+                    rgba,
+                }
+            }
+            Self::Gradient => {
+                let rgba = (0..BUILTIN_SIZE)
+                    .flat_map(|y| (0..BUILTIN_SIZE).map(move |x| [x as u8, y as u8, 0, u8::MAX]))
+                    .flatten()
+                    .collect();
+
+                ChannelImage {
+                    size: (BUILTIN_SIZE, BUILTIN_SIZE),
+                    rgba,
+                }
+            }
+            Self::Image(path) => {
+                let image = image::open(path)?.into_rgba8();
+
+                ChannelImage {
+                    size: image.dimensions(),
+                    rgba: image.into_raw(),
+                }
+            }
+        };
+
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_source() {
+        assert_eq!("noise".parse(), Ok(ChannelSource::Noise));
+        assert_eq!("gradient".parse(), Ok(ChannelSource::Gradient));
+        assert_eq!(
+            "./noise.png".parse(),
+            Ok(ChannelSource::Image(PathBuf::from("./noise.png")))
+        );
+    }
+
+    #[test]
+    fn builtin_sizes() {
+        for source in [ChannelSource::Noise, ChannelSource::Gradient] {
+            let image = source.load().unwrap();
+            assert_eq!(image.rgba.len(), (image.size.0 * image.size.1 * 4) as usize);
+        }
+    }
+}
@@ -1,10 +1,23 @@
-use std::path::PathBuf;
+use std::{num::NonZero, path::PathBuf};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::adapter::{AdapterConfig, AdapterSelector, Backend, PowerPreference};
+#[cfg(feature = "channels")]
+use crate::channels::ChannelSource;
+use crate::frontend::ShaderLanguage;
 
 #[derive(Parser)]
-#[command(version, about)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Path to the shaderfile.
     ///
     /// Must end with one of the following extensions:
@@ -17,15 +30,306 @@ pub struct Args {
     ///
     /// So for example, if you use `/dir1/dir2/fragment_shader.glsl` Shady-App will treat the given file
     /// as a `glsl` shader.
-    pub fragment_path: PathBuf,
+    ///
+    /// Use `-` to read the shader from stdin (requires `--lang`).
+    /// Send `SIGHUP` (or press `r` in the window) to read it again.
+    ///
+    /// A `.toml` file is treated as a project file which bundles a shader with its settings (see the README).
+    /// Arguments on the command line take precedence over the settings of the project.
+    #[arg(required = true)]
+    pub fragment_path: Option<PathBuf>,
+
+    /// The shader language of the fragment code. Overwrites the detection by the file extension.
+    #[arg(long, value_enum)]
+    pub lang: Option<ShaderLanguage>,
 
     /// Insert template to given shader.
     ///
     /// If enabled, the given shader will be prelpared for you so that you can immediately start writing your shader.
+    /// Pick one of the example shaders by its name or use `gallery` to list them and choose one.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "EXAMPLE",
+        num_args = 0..=1,
+        default_missing_value = "default"
+    )]
+    pub template: Option<Template>,
+
+    /// Add helper functions (like `hsv2rgb`, `noise`, `rotate2d`, signed distance functions and `audioAvg`)
+    /// to the template.
+    #[arg(long, requires = "template")]
+    pub prelude: bool,
+
+    /// Path to a toml file which describes a timeline to animate the values in `iTimeline`.
+    #[cfg(feature = "timeline")]
+    #[arg(long)]
+    pub timeline: Option<PathBuf>,
+
+    /// Path to a file which maps the audio to the values of `iParams`, for example `uniform speed = bass * 2.0 + 0.5`.
+    ///
+    /// See the README for the available variables and functions.
+    #[cfg(feature = "mapping")]
+    #[arg(long)]
+    pub mapping: Option<PathBuf>,
+
+    /// Check the watched files for changes every given amount of milliseconds instead of relying on
+    /// the events of the OS.
+    ///
+    /// Use it if the shader isn't reloaded after saving it, for example on network filesystems (NFS, SSHFS).
+    #[arg(long, value_name = "MS")]
+    pub poll: Option<NonZero<u64>>,
+
+    /// Keep running in the background and listen for commands on a unix socket.
+    ///
+    /// Closing the window only hides it. See the README for the available commands.
+    #[cfg(unix)]
+    #[arg(long)]
+    pub daemon: bool,
+
+    /// The path of the unix socket for `--daemon`.
+    ///
+    /// Defaults to `$XDG_RUNTIME_DIR/shady-toy.sock`.
+    #[cfg(unix)]
+    #[arg(long, requires = "daemon")]
+    pub socket: Option<PathBuf>,
+
+    /// The graphics backend which should be used.
+    ///
+    /// If not set, the primary backends of your platform are tried.
+    #[arg(long, value_enum)]
+    pub backend: Option<Backend>,
+
+    /// The graphics adapter which should be used, either by its index or (a part of) its name.
+    ///
+    /// Useful if your system has multiple GPUs and the wrong one is picked.
     #[arg(long)]
-    pub template: bool,
+    pub adapter: Option<String>,
+
+    /// Whether the integrated (`low`) or the dedicated (`high`) GPU should be preferred.
+    /// Can't be combined with `--adapter`.
+    #[arg(long, value_enum, conflicts_with = "adapter")]
+    pub power_preference: Option<PowerPreference>,
+
+    /// The name of the output device whose audio should be visualized. Defaults to the default output device.
+    #[arg(long, value_name = "NAME")]
+    pub audio_device: Option<String>,
+
+    /// The texture of `iChannel0`: Either `noise`, `gradient` or the path to an image (png or jpeg).
+    #[cfg(feature = "channels")]
+    #[arg(long, value_name = "SOURCE")]
+    pub channel0: Option<ChannelSource>,
+
+    /// The texture of `iChannel1` (see `--channel0`).
+    #[cfg(feature = "channels")]
+    #[arg(long, value_name = "SOURCE")]
+    pub channel1: Option<ChannelSource>,
+
+    /// The texture of `iChannel2` (see `--channel0`).
+    #[cfg(feature = "channels")]
+    #[arg(long, value_name = "SOURCE")]
+    pub channel2: Option<ChannelSource>,
+
+    /// The texture of `iChannel3` (see `--channel0`).
+    #[cfg(feature = "channels")]
+    #[arg(long, value_name = "SOURCE")]
+    pub channel3: Option<ChannelSource>,
+
+    /// Whether the colors of the shader are `srgb` encoded (like the ones of shadertoy) or `linear`.
+    /// Linear colors are converted to sRGB, otherwise they'd look too dark. Applies to `export` as well.
+    #[arg(long, value_enum, global = true, default_value_t = ColorSpace::Srgb)]
+    pub color_space: ColorSpace,
+
+    /// Upload the bars into `iAudioTexture` instead of `iAudio`. Applies to `export` as well.
+    #[cfg(feature = "audio-texture")]
+    #[arg(long, global = true)]
+    pub audio_texture: bool,
+
+    /// The frame rate which should be reached.
+    ///
+    /// If set, the frame rate is lowered automatically (down to a quarter of it) if the shader is too heavy
+    /// to reach it and restored once the GPU has enough headroom again.
+    /// Requires an adapter which supports GPU timestamp queries.
+    #[arg(long)]
+    pub target_fps: Option<NonZero<u32>>,
+
+    /// Crossfade from the previous shader to the new one over the given amount of seconds
+    /// after the shader has been changed. `0` switches immediately.
+    #[arg(long, value_name = "SECONDS", default_value_t = 0., value_parser = parse_seconds)]
+    pub crossfade: f32,
+
+    /// Write the mouse and keyboard input and the playback time of each frame into the given file.
+    #[arg(long, value_name = "PATH", conflicts_with = "replay_input")]
+    pub record_input: Option<PathBuf>,
+
+    /// Replay the input and the playback time of a file of `--record-input` frame by frame,
+    /// so the shader renders the same frames as during the recording (`iAudio` is still live).
+    #[arg(long, value_name = "PATH")]
+    pub replay_input: Option<PathBuf>,
+
+    /// Start with the uniforms (`iTime`, `iMouse`, `iParams` and `iAudio`) of a snapshot
+    /// which has been saved by pressing `s` in the window.
+    ///
+    /// The rendering is paused so the frame of the snapshot stays visible.
+    #[arg(long, value_name = "PATH")]
+    pub load_snapshot: Option<PathBuf>,
+
+    /// Connect to MPD to set `iTrackTime`, `iTrackDuration` and `iPlaying` with the state of the current track.
+    ///
+    /// Defaults to `localhost:6600` if no address is given.
+    #[cfg(feature = "track")]
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        num_args = 0..=1,
+        default_missing_value = crate::mpd::DEFAULT_ADDRESS
+    )]
+    pub mpd: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Download a shader from shadertoy and convert it into a glsl file which can be rendered.
+    ///
+    /// Only the `Image` (and `Common`) pass is converted. Audio inputs are mapped to `iAudio`,
+    /// other inputs (textures, buffers, ...) are black.
+    Import {
+        /// The url (like `https://www.shadertoy.com/view/XsXXDn`) or the id of the shader.
+        shader: String,
+
+        /// The path where the converted shader should be stored.
+        ///
+        /// Defaults to `<shader id>.glsl` in the current directory.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Your shadertoy API key (see <https://www.shadertoy.com/howto#q2>).
+        #[arg(long, env = "SHADERTOY_API_KEY", hide_env_values = true)]
+        api_key: String,
+    },
+
+    /// Render the first seconds of a shader into an animated gif or a sequence of png files.
+    ///
+    /// `iTime` advances by exactly one frame per image, so the result doesn't depend on the speed of your GPU.
+    /// `iAudio` is silent (unless `--audio` is given) and the texture channels are black.
+    #[cfg(feature = "export")]
+    Export {
+        /// Path to the shader file.
+        fragment_path: PathBuf,
+
+        /// Where the frames should be stored.
+        ///
+        /// Paths ending with `.gif` create an animated gif, other paths are treated as a directory
+        /// which gets one png file per frame (`00000.png`, `00001.png`, ...).
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// How many seconds should be rendered.
+        #[arg(long, default_value_t = 5.)]
+        duration: f32,
+
+        /// The amount of frames per second.
+        #[arg(long, default_value_t = NonZero::new(30).unwrap())]
+        fps: NonZero<u32>,
+
+        /// The width of the frames in pixels.
+        #[arg(long, default_value_t = 640)]
+        width: u32,
+
+        /// The height of the frames in pixels.
+        #[arg(long, default_value_t = 360)]
+        height: u32,
+
+        /// The shader language of the fragment code. Overwrites the detection by the file extension.
+        #[arg(long, value_enum)]
+        lang: Option<ShaderLanguage>,
+
+        /// Fill `iAudio` with the bars of the given audio file (wav).
+        ///
+        /// The bars of each frame are computed from the samples until exactly the time of the frame,
+        /// so the frames are in sync with the audio track (for example in a video editor).
+        #[cfg(feature = "audio")]
+        #[arg(long, value_name = "PATH")]
+        audio: Option<PathBuf>,
+    },
+}
+
+/// The shaders which `--template` can insert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Template {
+    /// A simple gradient.
+    Default,
+    /// List the examples and choose one of them.
+    Gallery,
+    /// Colorful waves which flow over the screen.
+    Plasma,
+    /// The bars of a spectrum analyzer.
+    AudioBars,
+    /// A raymarched sphere which pulses with the bass.
+    Raymarch,
+    /// An oscilloscope-like line which swings with the music.
+    Scope,
+}
+
+/// The color space of the output of the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+impl From<ColorSpace> for shady::ColorSpace {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Srgb => Self::Srgb,
+            ColorSpace::Linear => Self::Linear,
+        }
+    }
+}
+
+impl Args {
+    pub fn adapter_config(&self) -> AdapterConfig {
+        AdapterConfig {
+            backend: self.backend,
+            adapter: self.adapter.as_deref().map(AdapterSelector::from),
+            power_preference: self.power_preference,
+        }
+    }
+
+    /// Returns the sources of `iChannel0` to `iChannel3`.
+    #[cfg(feature = "channels")]
+    pub fn channels(&self) -> [Option<&ChannelSource>; shady::AMOUNT_CHANNELS] {
+        [
+            self.channel0.as_ref(),
+            self.channel1.as_ref(),
+            self.channel2.as_ref(),
+            self.channel3.as_ref(),
+        ]
+    }
+
+    /// Returns where the bars should be uploaded to.
+    #[cfg(feature = "audio-texture")]
+    pub fn audio_representation(&self) -> shady::AudioRepresentation {
+        if self.audio_texture {
+            shady::AudioRepresentation::Texture
+        } else {
+            shady::AudioRepresentation::StorageBuffer
+        }
+    }
 }
 
 pub fn parse() -> Args {
     Args::parse()
 }
+
+/// Parses a finite, non-negative amount of seconds.
+fn parse_seconds(value: &str) -> Result<f32, String> {
+    let secs: f32 = value.parse().map_err(|err| format!("{}", err))?;
+    if !secs.is_finite() || secs < 0. {
+        return Err(String::from(
+            "expected a finite, non-negative amount of seconds",
+        ));
+    }
+
+    Ok(secs)
+}
@@ -0,0 +1,270 @@
+//! Renders a shader into an animated gif or a sequence of png files.
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::BufWriter,
+    num::NonZero,
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, RgbaImage,
+};
+use shady::offscreen::{OffscreenError, OffscreenRenderer, Uniforms};
+#[cfg(feature = "audio")]
+use shady::shady_audio::{
+    fetcher::{FileFetcher, FileFetcherDescriptor, FileFetcherError},
+    BarProcessor, BarProcessorConfig, SampleProcessor,
+};
+use tracing::debug;
+use wgpu::ShaderSource;
+
+use crate::frontend::{ParseError, ShaderLanguage};
+
+const GIF_EXTENSION: &str = "gif";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error(transparent)]
+    Parsing(#[from] ParseError),
+
+    #[error(transparent)]
+    Offscreen(#[from] OffscreenError),
+
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Couldn't encode the frame: {0}")]
+    Encode(#[from] image::ImageError),
+
+    #[cfg(feature = "audio")]
+    #[error(transparent)]
+    Audio(#[from] FileFetcherError),
+}
+
+/// Where the frames should be stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    /// An animated gif which loops forever.
+    Gif(PathBuf),
+
+    /// A directory which gets one png file per frame (`00000.png`, `00001.png`, ...).
+    PngSequence(PathBuf),
+}
+
+impl From<PathBuf> for ExportTarget {
+    fn from(path: PathBuf) -> Self {
+        if path.extension().is_some_and(|ext| ext == GIF_EXTENSION) {
+            Self::Gif(path)
+        } else {
+            Self::PngSequence(path)
+        }
+    }
+}
+
+pub struct ExportOptions {
+    pub size: (u32, u32),
+
+    /// The amount of frames per second of shader time.
+    pub fps: NonZero<u32>,
+
+    /// How many seconds of the shader should be rendered.
+    pub duration: f32,
+
+    /// The color space of the output of the shader (like `--color-space` of the window).
+    pub color_space: shady::ColorSpace,
+
+    /// Whether the bars are uploaded into `iAudio` or `iAudioTexture` (like `--audio-texture` of the window).
+    #[cfg(feature = "audio-texture")]
+    pub audio_representation: shady::AudioRepresentation,
+
+    /// The audio file (wav) whose bars should be in `iAudio`.
+    #[cfg(feature = "audio")]
+    pub audio: Option<PathBuf>,
+}
+
+impl ExportOptions {
+    pub fn amount_frames(&self) -> u32 {
+        (self.duration * self.fps.get() as f32).ceil() as u32
+    }
+
+    /// Returns the values of the resources of the given frame.
+    ///
+    /// `iTime` advances by exactly one frame time, regardless of how long the rendering takes.
+    fn uniforms(&self, frame: u32) -> Uniforms {
+        // `unused`: Only needed if the `time` feature is enabled.
+        #[allow(unused)]
+        let time = frame as f32 / self.fps.get() as f32;
+
+        Uniforms {
+            #[cfg(feature = "time")]
+            time,
+            #[cfg(feature = "frame")]
+            frame,
+            ..Default::default()
+        }
+    }
+}
+
+/// Renders the fragment code with the given options and stores the frames in `target`.
+pub fn export(
+    fragment_code: &str,
+    lang: ShaderLanguage,
+    target: &ExportTarget,
+    options: &ExportOptions,
+) -> Result<(), ExportError> {
+    let module = lang.parse(fragment_code)?;
+    let mut renderer = OffscreenRenderer::with_color_space(
+        ShaderSource::Naga(Cow::Owned(module)),
+        options.size,
+        options.color_space,
+    )?;
+    #[cfg(feature = "audio-texture")]
+    renderer.set_audio_representation(options.audio_representation);
+
+    #[cfg(feature = "audio")]
+    let mut audio = match &options.audio {
+        Some(path) => {
+            renderer.set_audio_bars(crate::states::window_state::AUDIO_BARS);
+            Some(AudioTrack::open(path, options.fps)?)
+        }
+        None => None,
+    };
+
+    let frames = (0..options.amount_frames()).map(|frame| {
+        debug!("Rendering frame {}", frame);
+
+        // `unused_mut`: Only needed if the `audio` feature is enabled.
+        #[allow(unused_mut)]
+        let mut uniforms = options.uniforms(frame);
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &mut audio {
+            uniforms.audio = audio.next_frame();
+        }
+
+        renderer.render(&uniforms)
+    });
+
+    match target {
+        ExportTarget::Gif(path) => write_gif(path, options.fps, frames),
+        ExportTarget::PngSequence(dir) => write_png_sequence(dir, frames),
+    }
+}
+
+/// Computes the bars of an audio file frame by frame.
+///
+/// The samples of a frame are determined by its index and the frame rate instead of the time it took to render it,
+/// so `iAudio` is in sync with the audio track sample by sample.
+#[cfg(feature = "audio")]
+struct AudioTrack {
+    sample_processor: SampleProcessor,
+    bar_processor: BarProcessor,
+    frame_time: std::time::Duration,
+}
+
+#[cfg(feature = "audio")]
+impl AudioTrack {
+    fn open(path: &Path, fps: NonZero<u32>) -> Result<Self, FileFetcherError> {
+        let fetcher = FileFetcher::new(FileFetcherDescriptor {
+            frame_rate: Some(fps),
+            ..FileFetcherDescriptor::new(path)
+        })?;
+
+        let sample_processor = SampleProcessor::new(fetcher);
+        // the same bars as within the window
+        let bar_processor = BarProcessor::new(
+            &sample_processor,
+            BarProcessorConfig {
+                amount_bars: crate::states::window_state::AUDIO_BARS,
+                freq_range: crate::states::window_state::AUDIO_FREQ_RANGE,
+                ..Default::default()
+            },
+        );
+
+        Ok(Self {
+            sample_processor,
+            bar_processor,
+            frame_time: std::time::Duration::from_secs_f64(1. / f64::from(fps.get())),
+        })
+    }
+
+    /// Returns the bars of the next frame.
+    fn next_frame(&mut self) -> Vec<f32> {
+        self.sample_processor.process_next_samples();
+
+        self.bar_processor.set_delta_time(self.frame_time);
+        self.bar_processor.process_bars(&self.sample_processor)[0].to_vec()
+    }
+}
+
+fn write_gif(
+    path: &Path,
+    fps: NonZero<u32>,
+    frames: impl Iterator<Item = Result<RgbaImage, OffscreenError>>,
+) -> Result<(), ExportError> {
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms(1_000, fps.get());
+    for image in frames {
+        encoder.encode_frame(Frame::from_parts(image?, 0, 0, delay))?;
+    }
+
+    Ok(())
+}
+
+fn write_png_sequence(
+    dir: &Path,
+    frames: impl Iterator<Item = Result<RgbaImage, OffscreenError>>,
+) -> Result<(), ExportError> {
+    std::fs::create_dir_all(dir)?;
+
+    for (idx, image) in frames.enumerate() {
+        image?.save(dir.join(format!("{:05}.png", idx)))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_from_extension() {
+        assert_eq!(
+            ExportTarget::from(PathBuf::from("preview.gif")),
+            ExportTarget::Gif(PathBuf::from("preview.gif"))
+        );
+        assert_eq!(
+            ExportTarget::from(PathBuf::from("frames")),
+            ExportTarget::PngSequence(PathBuf::from("frames"))
+        );
+    }
+
+    #[test]
+    fn deterministic_time_steps() {
+        let options = ExportOptions {
+            size: (1, 1),
+            fps: NonZero::new(25).unwrap(),
+            duration: 2.,
+            color_space: shady::ColorSpace::default(),
+            #[cfg(feature = "audio-texture")]
+            audio_representation: shady::AudioRepresentation::default(),
+            #[cfg(feature = "audio")]
+            audio: None,
+        };
+
+        assert_eq!(options.amount_frames(), 50);
+
+        // `unused`: Depending on the enabled features, no uniform might be checked.
+        #[allow(unused)]
+        let uniforms = options.uniforms(10);
+        #[cfg(feature = "time")]
+        assert_eq!(uniforms.time, 0.4);
+        #[cfg(feature = "frame")]
+        assert_eq!(uniforms.frame, 10);
+    }
+}
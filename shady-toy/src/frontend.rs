@@ -1,14 +1,67 @@
 use std::path::Path;
 
+use tracing::debug;
+use wgpu::naga::{
+    front::{glsl, wgsl},
+    Module, ShaderStage,
+};
+
 const WGSL_EXTENSION: &str = "wgsl";
 const GLSL_EXTENSION: &str = "glsl";
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ShaderLanguage {
     Wgsl,
     Glsl,
 }
 
+/// The fragment code couldn't be parsed. Contains the (colored) report of the parser.
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error("{0}")]
+    Wgsl(String),
+
+    #[error("{0}")]
+    Glsl(String),
+}
+
+impl ShaderLanguage {
+    /// Parses the given fragment code.
+    ///
+    /// If glsl code can't be parsed, it's tried again with the compatibility pre-pass for shadertoy shaders.
+    pub fn parse(self, fragment_code: &str) -> Result<Module, ParseError> {
+        match self {
+            Self::Wgsl => {
+                debug!("Parsing with wgsl parser");
+                let mut frontend = wgsl::Frontend::new();
+
+                frontend
+                    .parse(fragment_code)
+                    .map_err(|err| ParseError::Wgsl(err.emit_to_string(fragment_code)))
+            }
+            Self::Glsl => {
+                debug!("Parsing with glsl parser");
+                let mut frontend = glsl::Frontend::default();
+                let options = glsl::Options::from(ShaderStage::Fragment);
+
+                match frontend.parse(&options, fragment_code) {
+                    Ok(module) => Ok(module),
+                    Err(err) => {
+                        // maybe it's a shadertoy shader => try again with the compatibility pre-pass
+                        debug!("Parsing with glsl parser and shadertoy compatibility");
+                        let compat_code = shady::glsl_compat::make_compatible(fragment_code);
+
+                        frontend
+                            .parse(&options, &compat_code)
+                            .map_err(|_| ParseError::Glsl(err.emit_to_string(fragment_code)))
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl TryFrom<&Path> for ShaderLanguage {
     type Error = String;
 
@@ -0,0 +1,296 @@
+//! Example shaders which can be used as a starting point with `--template`.
+use std::io::{BufRead, Write};
+
+use shady::TemplateLang;
+
+use crate::frontend::ShaderLanguage;
+
+/// A shader of the gallery. The bodies are placed into the main function of the template.
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    wgsl_body: &'static str,
+    glsl_body: &'static str,
+}
+
+impl Example {
+    /// Returns the whole shader (the template with the body of the example).
+    pub fn generate(&self, lang: ShaderLanguage, with_prelude: bool) -> String {
+        let (template_lang, body) = match lang {
+            ShaderLanguage::Wgsl => (TemplateLang::Wgsl, self.wgsl_body),
+            ShaderLanguage::Glsl => (TemplateLang::Glsl, self.glsl_body),
+        };
+
+        generate_template(template_lang, Some(body), with_prelude)
+    }
+}
+
+/// Generates the template with the given body (or the default one).
+pub fn generate_template(lang: TemplateLang, body: Option<&str>, with_prelude: bool) -> String {
+    let mut template = String::new();
+    if with_prelude {
+        lang.generate_with_prelude(&mut template, body)
+    } else {
+        lang.generate(&mut template, body)
+    }
+    .expect("Generate template");
+
+    template
+}
+
+pub const PLASMA: Example = Example {
+    name: "plasma",
+    description: "Colorful waves which flow over the screen.",
+    wgsl_body: "
+    let uv = pos.xy / iResolution.xy;
+
+    var v = sin(uv.x * 10.0 + iTime);
+    v += sin(uv.y * 10.0 + iTime * 1.3);
+    v += sin((uv.x + uv.y) * 10.0 + iTime * 0.7);
+    v += sin(length(uv - 0.5) * 20.0 - iTime * 2.0);
+
+    let col = 0.5 + 0.5 * cos(3.14159 * v + vec3<f32>(0.0, 2.0, 4.0));
+    return vec4<f32>(col, 1.0);
+",
+    glsl_body: "
+    vec2 uv = gl_FragCoord.xy / iResolution.xy;
+
+    float v = sin(uv.x * 10.0 + iTime);
+    v += sin(uv.y * 10.0 + iTime * 1.3);
+    v += sin((uv.x + uv.y) * 10.0 + iTime * 0.7);
+    v += sin(length(uv - 0.5) * 20.0 - iTime * 2.0);
+
+    vec3 col = 0.5 + 0.5 * cos(3.14159 * v + vec3(0.0, 2.0, 4.0));
+    fragColor = vec4(col, 1.0);
+",
+};
+
+pub const AUDIO_BARS: Example = Example {
+    name: "audio-bars",
+    description: "The bars of a spectrum analyzer (`iAudio`).",
+    wgsl_body: "
+    let uv = pos.xy / iResolution.xy;
+    let amount_bars = arrayLength(&iAudio);
+
+    let x = uv.x * f32(amount_bars);
+    let bar = min(u32(x), amount_bars - 1u);
+    // the bars grow from the bottom
+    let height = 1.0 - uv.y;
+
+    // leave a small gap between the bars
+    let inside = step(height, iAudio[bar]) * step(0.1, fract(x));
+    let bar_col = mix(vec3<f32>(0.2, 0.4, 1.0), vec3<f32>(1.0, 0.3, 0.5), height);
+
+    return vec4<f32>(mix(vec3<f32>(0.05), bar_col, inside), 1.0);
+",
+    glsl_body: "
+    vec2 uv = gl_FragCoord.xy / iResolution.xy;
+    int amount_bars = freqs.length();
+
+    float x = uv.x * float(amount_bars);
+    int bar = min(int(x), amount_bars - 1);
+    // the bars grow from the bottom
+    float height = 1.0 - uv.y;
+
+    // leave a small gap between the bars
+    float inside = step(height, freqs[bar]) * step(0.1, fract(x));
+    vec3 bar_col = mix(vec3(0.2, 0.4, 1.0), vec3(1.0, 0.3, 0.5), height);
+
+    fragColor = vec4(mix(vec3(0.05), bar_col, inside), 1.0);
+",
+};
+
+pub const RAYMARCH: Example = Example {
+    name: "raymarch",
+    description: "A raymarched sphere which pulses with the bass.",
+    wgsl_body: "
+    let uv = (2.0 * pos.xy - iResolution.xy) / iResolution.y * vec2<f32>(1.0, -1.0);
+
+    let origin = vec3<f32>(0.0, 0.0, -3.0);
+    let dir = normalize(vec3<f32>(uv, 1.5));
+    let radius = 1.0 + 0.3 * iAudio[0];
+
+    var t = 0.0;
+    var hit = false;
+    for (var i = 0; i < 64; i++) {
+        let dist = length(origin + t * dir) - radius;
+        if (dist < 0.001) {
+            hit = true;
+            break;
+        }
+        t += dist;
+        if (t > 20.0) {
+            break;
+        }
+    }
+
+    if (!hit) {
+        return vec4<f32>(vec3<f32>(0.05, 0.05, 0.1) * (1.0 - uv.y * 0.5), 1.0);
+    }
+
+    let normal = normalize(origin + t * dir);
+    let light = normalize(vec3<f32>(cos(iTime), 1.0, sin(iTime) - 1.0));
+    let diffuse = max(dot(normal, light), 0.0);
+    let col = vec3<f32>(0.9, 0.4, 0.2) * (0.1 + diffuse);
+
+    return vec4<f32>(col, 1.0);
+",
+    glsl_body: "
+    vec2 uv = (2.0 * gl_FragCoord.xy - iResolution.xy) / iResolution.y * vec2(1.0, -1.0);
+
+    vec3 origin = vec3(0.0, 0.0, -3.0);
+    vec3 dir = normalize(vec3(uv, 1.5));
+    float radius = 1.0 + 0.3 * freqs[0];
+
+    float t = 0.0;
+    bool hit = false;
+    for (int i = 0; i < 64; i++) {
+        float dist = length(origin + t * dir) - radius;
+        if (dist < 0.001) {
+            hit = true;
+            break;
+        }
+        t += dist;
+        if (t > 20.0) {
+            break;
+        }
+    }
+
+    if (!hit) {
+        fragColor = vec4(vec3(0.05, 0.05, 0.1) * (1.0 - uv.y * 0.5), 1.0);
+        return;
+    }
+
+    vec3 normal = normalize(origin + t * dir);
+    vec3 light = normalize(vec3(cos(iTime), 1.0, sin(iTime) - 1.0));
+    float diffuse = max(dot(normal, light), 0.0);
+    vec3 col = vec3(0.9, 0.4, 0.2) * (0.1 + diffuse);
+
+    fragColor = vec4(col, 1.0);
+",
+};
+
+pub const SCOPE: Example = Example {
+    name: "scope",
+    description: "A glowing line like the one of an oscilloscope which swings with the music.",
+    wgsl_body: "
+    let uv = pos.xy / iResolution.xy;
+    let amount_bars = arrayLength(&iAudio);
+
+    // interpolate between the bars so the line is smooth
+    let x = uv.x * f32(amount_bars - 1u);
+    let bar = min(u32(x), amount_bars - 2u);
+    let presence = mix(iAudio[bar], iAudio[bar + 1u], fract(x));
+
+    let wave = 0.5 + 0.4 * presence * sin(uv.x * 40.0 - iTime * 5.0);
+    let glow = 0.002 / abs(uv.y - wave);
+
+    return vec4<f32>(vec3<f32>(0.2, 1.0, 0.4) * glow, 1.0);
+",
+    glsl_body: "
+    vec2 uv = gl_FragCoord.xy / iResolution.xy;
+    int amount_bars = freqs.length();
+
+    // interpolate between the bars so the line is smooth
+    float x = uv.x * float(amount_bars - 1);
+    int bar = min(int(x), amount_bars - 2);
+    float presence = mix(freqs[bar], freqs[bar + 1], fract(x));
+
+    float wave = 0.5 + 0.4 * presence * sin(uv.x * 40.0 - iTime * 5.0);
+    float glow = 0.002 / abs(uv.y - wave);
+
+    fragColor = vec4(vec3(0.2, 1.0, 0.4) * glow, 1.0);
+",
+};
+
+pub const EXAMPLES: [Example; 4] = [PLASMA, AUDIO_BARS, RAYMARCH, SCOPE];
+
+/// Lists the examples and lets the user pick one (by its number or its name).
+///
+/// Returns `None` if the input ended before a valid example has been picked.
+pub fn choose(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> std::io::Result<Option<&'static Example>> {
+    writeln!(output, "Available examples:")?;
+    for (idx, example) in EXAMPLES.iter().enumerate() {
+        writeln!(
+            output,
+            "  {}) {:<12} {}",
+            idx + 1,
+            example.name,
+            example.description
+        )?;
+    }
+
+    let mut line = String::new();
+    loop {
+        write!(output, "Choose an example [1-{}]: ", EXAMPLES.len())?;
+        output.flush()?;
+
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let choice = line.trim();
+        let example = match choice.parse::<usize>() {
+            Ok(number) => number.checked_sub(1).and_then(|idx| EXAMPLES.get(idx)),
+            Err(_) => EXAMPLES.iter().find(|example| example.name == choice),
+        };
+
+        match example {
+            Some(example) => return Ok(Some(example)),
+            None => writeln!(output, "Unknown example: \"{}\"", choice)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    use super::*;
+
+    #[test]
+    fn valid_examples() {
+        for example in EXAMPLES.iter() {
+            for lang in [ShaderLanguage::Wgsl, ShaderLanguage::Glsl] {
+                for with_prelude in [true, false] {
+                    let module = match lang.parse(&example.generate(lang, with_prelude)) {
+                        Ok(module) => module,
+                        Err(err) => panic!(
+                            "{} ({:?}, prelude: {}):\n{}",
+                            example.name, lang, with_prelude, err
+                        ),
+                    };
+
+                    if let Err(err) = Validator::new(ValidationFlags::all(), Capabilities::all())
+                        .validate(&module)
+                    {
+                        panic!(
+                            "{} ({:?}, prelude: {}): {:?}",
+                            example.name, lang, with_prelude, err
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn choose_by_number_or_name() {
+        let mut output = Vec::new();
+
+        let mut input = "5\nfoo\n2\n".as_bytes();
+        let example = choose(&mut input, &mut output).unwrap().unwrap();
+        assert_eq!(example.name, AUDIO_BARS.name);
+
+        let mut input = "scope\n".as_bytes();
+        let example = choose(&mut input, &mut output).unwrap().unwrap();
+        assert_eq!(example.name, SCOPE.name);
+
+        let mut input = "0\n".as_bytes();
+        assert!(choose(&mut input, &mut output).unwrap().is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Polls the gamepads for the `iGamepadAxes` and `iGamepadButtons` uniforms.
+use gilrs::{Axis, Button, EventType, Gilrs};
+use shady::{GamepadButton, GamepadStick, AMOUNT_GAMEPAD_BUTTONS};
+use tracing::{debug, warn};
+use winit::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+// the buttons of gilrs in the order of `GamepadButton`
+const BUTTONS: [(Button, GamepadButton); AMOUNT_GAMEPAD_BUTTONS] = [
+    (Button::South, GamepadButton::South),
+    (Button::East, GamepadButton::East),
+    (Button::North, GamepadButton::North),
+    (Button::West, GamepadButton::West),
+    (Button::LeftTrigger, GamepadButton::LeftBumper),
+    (Button::RightTrigger, GamepadButton::RightBumper),
+    (Button::LeftTrigger2, GamepadButton::LeftTrigger),
+    (Button::RightTrigger2, GamepadButton::RightTrigger),
+    (Button::Select, GamepadButton::Select),
+    (Button::Start, GamepadButton::Start),
+    (Button::LeftThumb, GamepadButton::LeftStick),
+    (Button::RightThumb, GamepadButton::RightStick),
+    (Button::DPadUp, GamepadButton::DPadUp),
+    (Button::DPadDown, GamepadButton::DPadDown),
+    (Button::DPadLeft, GamepadButton::DPadLeft),
+    (Button::DPadRight, GamepadButton::DPadRight),
+];
+
+/// The state of the gamepad which has been used last.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GamepadState {
+    pub left_stick: [f32; 2],
+    pub right_stick: [f32; 2],
+
+    /// The value of each button in the order of [shady::GamepadButton].
+    pub buttons: [f32; AMOUNT_GAMEPAD_BUTTONS],
+}
+
+impl GamepadState {
+    pub fn apply(&self, shady: &mut shady::Shady) {
+        let [x, y] = self.left_stick;
+        shady.set_gamepad_stick(GamepadStick::Left, x, y);
+        let [x, y] = self.right_stick;
+        shady.set_gamepad_stick(GamepadStick::Right, x, y);
+
+        for ((_, button), &value) in BUTTONS.iter().zip(self.buttons.iter()) {
+            shady.set_gamepad_button(*button, value);
+        }
+    }
+
+    fn from_gamepad(gamepad: gilrs::Gamepad) -> Self {
+        let mut buttons = [0.; AMOUNT_GAMEPAD_BUTTONS];
+        for (value, (button, _)) in buttons.iter_mut().zip(BUTTONS.iter()) {
+            *value = gamepad
+                .button_data(*button)
+                .map(|data| data.value())
+                .unwrap_or_default();
+        }
+
+        Self {
+            left_stick: [
+                gamepad.value(Axis::LeftStickX),
+                gamepad.value(Axis::LeftStickY),
+            ],
+            right_stick: [
+                gamepad.value(Axis::RightStickX),
+                gamepad.value(Axis::RightStickY),
+            ],
+            buttons,
+        }
+    }
+}
+
+/// Sends the state of the gamepad which has been used last to the event loop whenever it changes.
+///
+/// Returns if gamepads aren't supported or the event loop is gone.
+pub fn poll(proxy: EventLoopProxy<UserEvent>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(err) => {
+            warn!("Gamepads are not available: {}", err);
+            return;
+        }
+    };
+
+    for (_, gamepad) in gilrs.gamepads() {
+        debug!("Found gamepad: {}", gamepad.name());
+    }
+
+    let mut prev_state = GamepadState::default();
+    loop {
+        let Some(event) = gilrs.next_event_blocking(None) else {
+            continue;
+        };
+
+        let state = match event.event {
+            // the released buttons of the gamepad would be stuck otherwise
+            EventType::Disconnected => {
+                debug!("Gamepad {} has been disconnected", event.id);
+                GamepadState::default()
+            }
+            EventType::Dropped | EventType::ForceFeedbackEffectCompleted => continue,
+            _ => GamepadState::from_gamepad(gilrs.gamepad(event.id)),
+        };
+
+        if state == prev_state {
+            continue;
+        }
+        prev_state = state;
+
+        if proxy.send_event(UserEvent::Gamepad(state)).is_err() {
+            return;
+        }
+    }
+}
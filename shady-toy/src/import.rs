@@ -0,0 +1,365 @@
+//! Import shaders from [shadertoy].
+//!
+//! The shader is downloaded with the [shadertoy API] and its `Image` pass is converted into a glsl file which
+//! `shady-toy` can render. Audio inputs (`iChannel*` with music or microphone input) are mapped to `iAudio`,
+//! all other inputs (textures, buffers, keyboard, ...) aren't supported and are black.
+//!
+//! [shadertoy]: https://www.shadertoy.com/
+//! [shadertoy API]: https://www.shadertoy.com/howto#q2
+use std::fmt::Write;
+
+use serde::Deserialize;
+
+const API_URL: &str = "https://www.shadertoy.com/api/v1/shaders";
+const AMOUNT_CHANNELS: usize = 4;
+
+// the functions of shadertoy which read from an `iChannel`
+const SAMPLE_FUNCTIONS: &[&str] = &["texture", "texture2D", "textureLod"];
+const FETCH_FUNCTION: &str = "texelFetch";
+
+// width of the audio texture of shadertoy
+const SHADERTOY_AUDIO_WIDTH: u32 = 512;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportError {
+    #[error("Couldn't download the shader: {0}")]
+    Http(#[from] Box<ureq::Error>),
+
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Couldn't parse the response of shadertoy: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Shadertoy returned an error: {0}")]
+    Api(String),
+
+    #[error("The shader doesn't have an `Image` pass.")]
+    NoImagePass,
+
+    #[error("\"{0}\" is neither a shadertoy url nor a shader id.")]
+    InvalidId(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Shader {
+        #[serde(rename = "Shader")]
+        shader: Shader,
+    },
+    Error {
+        #[serde(rename = "Error")]
+        error: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Shader {
+    pub info: Info,
+
+    #[serde(rename = "renderpass")]
+    pub render_passes: Vec<RenderPass>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Info {
+    pub id: String,
+    pub name: String,
+
+    #[serde(default)]
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenderPass {
+    #[serde(default)]
+    pub inputs: Vec<Input>,
+
+    pub code: String,
+
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Input {
+    pub channel: usize,
+
+    pub ctype: String,
+}
+
+impl Input {
+    fn is_audio(&self) -> bool {
+        matches!(self.ctype.as_str(), "music" | "musicstream" | "mic")
+    }
+}
+
+/// Extracts the id of the shader out of a shadertoy url (like `https://www.shadertoy.com/view/XsXXDn`)
+/// or returns the given string if it's already an id.
+pub fn parse_id(url_or_id: &str) -> Result<String, ImportError> {
+    let id = match url_or_id.split_once("/view/") {
+        Some((_, path)) => path.split(['/', '?', '#']).next().unwrap_or_default(),
+        None => url_or_id,
+    };
+
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(id.to_string())
+    } else {
+        Err(ImportError::InvalidId(url_or_id.to_string()))
+    }
+}
+
+/// Downloads the shader with the given id.
+pub fn download(id: &str, api_key: &str) -> Result<Shader, ImportError> {
+    let response = ureq::get(&format!("{}/{}", API_URL, id))
+        .query("key", api_key)
+        .call()
+        .map_err(Box::new)?
+        .into_string()?;
+
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> Result<Shader, ImportError> {
+    match serde_json::from_str(response)? {
+        Response::Shader { shader } => Ok(shader),
+        Response::Error { error } => Err(ImportError::Api(error)),
+    }
+}
+
+/// Converts the shader into glsl code which `shady-toy` can render.
+///
+/// Returns the code and a list of warnings about things which couldn't be converted.
+pub fn convert(shader: &Shader) -> Result<(String, Vec<String>), ImportError> {
+    let image = shader
+        .render_passes
+        .iter()
+        .find(|pass| pass.kind == "image")
+        .ok_or(ImportError::NoImagePass)?;
+
+    let mut warnings = Vec::new();
+    for pass in shader.render_passes.iter() {
+        if !matches!(pass.kind.as_str(), "image" | "common") {
+            warnings.push(format!(
+                "The `{}` pass isn't supported, its output is black.",
+                pass.kind
+            ));
+        }
+    }
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "// \"{}\" by {}: https://www.shadertoy.com/view/{}",
+        shader.info.name, shader.info.username, shader.info.id
+    )
+    .unwrap();
+
+    for channel in 0..AMOUNT_CHANNELS {
+        let input = image.inputs.iter().find(|input| input.channel == channel);
+
+        if let Some(input) = input.filter(|input| !input.is_audio()) {
+            #[cfg(feature = "channels")]
+            warnings.push(format!(
+                "iChannel{} ({}) isn't downloaded, set it with `--channel{}`.",
+                channel, input.ctype, channel
+            ));
+            #[cfg(not(feature = "channels"))]
+            warnings.push(format!(
+                "iChannel{} ({}) isn't supported, it's black.",
+                channel, input.ctype
+            ));
+        }
+
+        write_channel(&mut code, channel, input.is_some_and(Input::is_audio));
+    }
+
+    // the `channels` feature declares `iChannelResolution` already
+    if cfg!(not(feature = "channels")) && image.code.contains("iChannelResolution") {
+        code.push_str(&format!(
+            "\nconst vec3 iChannelResolution[{}] = vec3[](vec3(0.), vec3(0.), vec3(0.), vec3(0.));\n",
+            AMOUNT_CHANNELS
+        ));
+    }
+
+    if let Some(common) = shader
+        .render_passes
+        .iter()
+        .find(|pass| pass.kind == "common")
+    {
+        code.push('\n');
+        code.push_str(&replace_channel_access(&common.code));
+    }
+
+    code.push('\n');
+    code.push_str(&replace_channel_access(&image.code));
+
+    Ok((shady::glsl_compat::make_compatible(&code), warnings))
+}
+
+/// Writes the functions which replace the texture accesses of the given channel.
+///
+/// Other channels than the audio channel sample the texture of `iChannel<channel>` with the `channels` feature
+/// and are black otherwise.
+fn write_channel(code: &mut String, channel: usize, is_audio: bool) {
+    let (sample, sample_bias, fetch) = if is_audio {
+        (
+            "int idx = int(clamp(uv.x, 0.0, 1.0) * float(freqs.length() - 1));\n    return vec4(freqs[idx]);".to_string(),
+            format!("return shady_iChannel{}(uv);", channel),
+            format!(
+                "int idx = clamp(p.x * freqs.length() / {}, 0, freqs.length() - 1);\n    return vec4(freqs[idx]);",
+                SHADERTOY_AUDIO_WIDTH
+            ),
+        )
+    } else if cfg!(feature = "channels") {
+        // `glsl_compat` combines `iChannel<channel>` with `iChannelSampler`
+        (
+            format!("return texture(iChannel{}, uv);", channel),
+            format!("return texture(iChannel{}, uv, bias);", channel),
+            format!("return texelFetch(iChannel{}, p, lod);", channel),
+        )
+    } else {
+        (
+            "return vec4(0.0);".to_string(),
+            "return vec4(0.0);".to_string(),
+            "return vec4(0.0);".to_string(),
+        )
+    };
+
+    write!(
+        code,
+        "
+vec4 shady_iChannel{channel}(vec2 uv) {{
+    {sample}
+}}
+
+vec4 shady_iChannel{channel}(vec2 uv, float bias) {{
+    {sample_bias}
+}}
+
+vec4 shady_iChannel{channel}_fetch(ivec2 p, int lod) {{
+    {fetch}
+}}
+"
+    )
+    .unwrap();
+}
+
+/// Replaces calls like `texture(iChannel0, uv)` with `shady_iChannel0(uv)`.
+fn replace_channel_access(code: &str) -> String {
+    let mut output = String::with_capacity(code.len());
+    let mut rest = code;
+
+    while let Some((start, end, replacement)) = next_channel_access(rest) {
+        output.push_str(&rest[..start]);
+        output.push_str(&replacement);
+        rest = &rest[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Returns the byte range and the replacement of the next `<function>(iChannel<N>,` in `code`.
+fn next_channel_access(code: &str) -> Option<(usize, usize, String)> {
+    let mut candidates = code
+        .match_indices("iChannel")
+        .filter_map(|(channel_idx, _)| {
+            let channel = code[channel_idx + "iChannel".len()..].chars().next()?;
+            let channel = channel
+                .to_digit(10)
+                .filter(|&c| (c as usize) < AMOUNT_CHANNELS)?;
+            let after_channel = channel_idx + "iChannel".len() + 1;
+
+            // `<function>(` before the channel
+            let before = code[..channel_idx].trim_end().strip_suffix('(')?.trim_end();
+            let function = before
+                .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .next()?;
+            let start = before.len() - function.len();
+
+            // `,` after the channel (including the whitespace around it)
+            let rest = code[after_channel..].trim_start().strip_prefix(',')?;
+            let end = code.len() - rest.trim_start().len();
+
+            let replacement = if SAMPLE_FUNCTIONS.contains(&function) {
+                format!("shady_iChannel{}(", channel)
+            } else if function == FETCH_FUNCTION {
+                format!("shady_iChannel{}_fetch(", channel)
+            } else {
+                return None;
+            };
+
+            Some((start, end, replacement))
+        });
+
+    candidates.next()
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::naga::{front::glsl::Options, ShaderStage};
+
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+        "Shader": {
+            "info": { "id": "abc123", "name": "Test", "username": "someone" },
+            "renderpass": [
+                {
+                    "inputs": [
+                        { "id": 1, "channel": 0, "ctype": "music" },
+                        { "id": 2, "channel": 1, "ctype": "texture" }
+                    ],
+                    "code": "void mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    vec2 uv = fragCoord / iResolution.xy;\n    float fft = texture( iChannel0 , vec2(uv.x, 0.25)).x;\n    float wave = texelFetch(iChannel0, ivec2(10, 1), 0).x;\n    vec3 tex = texture2D(iChannel1, uv).rgb;\n    fragColor = vec4(tex + fft + wave, 1.0);\n}",
+                    "type": "image"
+                },
+                { "inputs": [], "code": "void mainImage(out vec4 c, in vec2 p) { c = vec4(0.); }", "type": "buffer" }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn parse_ids() {
+        assert_eq!(
+            parse_id("https://www.shadertoy.com/view/XsXXDn").unwrap(),
+            "XsXXDn"
+        );
+        assert_eq!(
+            parse_id("https://www.shadertoy.com/view/XsXXDn/?foo=bar").unwrap(),
+            "XsXXDn"
+        );
+        assert_eq!(parse_id("XsXXDn").unwrap(), "XsXXDn");
+        assert!(parse_id("https://www.shadertoy.com/view/").is_err());
+    }
+
+    #[test]
+    fn api_error() {
+        assert!(matches!(
+            parse_response(r#"{ "Error": "Shader not found" }"#),
+            Err(ImportError::Api(_))
+        ));
+    }
+
+    #[test]
+    fn convert_shader() {
+        let shader = parse_response(RESPONSE).unwrap();
+        let (code, warnings) = convert(&shader).unwrap();
+
+        assert!(code.contains("shady_iChannel0(vec2(uv.x, 0.25))"));
+        assert!(code.contains("shady_iChannel0_fetch(ivec2(10, 1), 0)"));
+        assert!(code.contains("shady_iChannel1(uv)"));
+        #[cfg(feature = "channels")]
+        assert!(code.contains("texture(sampler2D(iChannel1, iChannelSampler), uv)"));
+        #[cfg(not(feature = "channels"))]
+        assert!(!code.contains("texture(iChannel1"));
+        // the texture and the buffer pass
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+
+        let mut parser = wgpu::naga::front::glsl::Frontend::default();
+        if let Err(err) = parser.parse(&Options::from(ShaderStage::Fragment), &code) {
+            panic!("{}", err.emit_to_string(&code));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Records the input (mouse and keyboard) and the playback time of each frame into a file and replays it,
+//! so an interactive shader renders the same frames each time (for example for a video or regression tests).
+//!
+//! The file contains one json object per line, the input of a frame is followed by its [InputEvent::Frame].
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(thiserror::Error, Debug)]
+pub enum InputError {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Invalid input event in line {line}: {err}")]
+    Json { line: usize, err: serde_json::Error },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum InputEvent {
+    /// A frame has been rendered with the given playback time (in seconds).
+    Frame {
+        time: f32,
+    },
+
+    /// The position of the cursor in physical pixels.
+    CursorMoved {
+        x: f32,
+        y: f32,
+    },
+
+    MouseButton {
+        pressed: bool,
+    },
+
+    /// The distance in lines.
+    Scroll {
+        x: f32,
+        y: f32,
+    },
+
+    /// A key with the given text has been pressed.
+    Key {
+        text: String,
+    },
+}
+
+/// Writes the events into a file.
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, event: &InputEvent) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        writeln!(self.writer)
+    }
+}
+
+/// Returns the recorded events frame by frame.
+#[derive(Debug)]
+pub struct InputReplay {
+    events: VecDeque<InputEvent>,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self, InputError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(recording: &str) -> Result<Self, InputError> {
+        let events = recording
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|err| InputError::Json { line: idx + 1, err })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { events })
+    }
+
+    /// Returns the input of the next frame and its playback time.
+    ///
+    /// Returns `None` if all frames have been replayed.
+    pub fn next_frame(&mut self) -> Option<(Vec<InputEvent>, f32)> {
+        let mut input = Vec::new();
+
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                InputEvent::Frame { time } => return Some((input, time)),
+                event => input.push(event),
+            }
+        }
+
+        // the input after the last frame has never been rendered
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_frame_by_frame() {
+        let recording = r#"
+            {"type":"frame","time":0.0}
+            {"type":"cursor-moved","x":10.0,"y":20.0}
+            {"type":"mouse-button","pressed":true}
+            {"type":"frame","time":0.016}
+            {"type":"key","text":"r"}
+        "#;
+        let mut replay = InputReplay::parse(recording).unwrap();
+
+        assert_eq!(replay.next_frame(), Some((vec![], 0.)));
+        assert_eq!(
+            replay.next_frame(),
+            Some((
+                vec![
+                    InputEvent::CursorMoved { x: 10., y: 20. },
+                    InputEvent::MouseButton { pressed: true }
+                ],
+                0.016
+            ))
+        );
+        assert_eq!(replay.next_frame(), None);
+    }
+
+    #[test]
+    fn recorded_events_can_be_replayed() {
+        let events = [
+            InputEvent::Scroll { x: 0., y: -1. },
+            InputEvent::Key { text: "q".into() },
+            InputEvent::Frame { time: 1.5 },
+        ];
+
+        let recording = events
+            .iter()
+            .map(|event| serde_json::to_string(event).unwrap() + "\n")
+            .collect::<String>();
+
+        let mut replay = InputReplay::parse(&recording).unwrap();
+        assert_eq!(replay.next_frame(), Some((events[..2].to_vec(), 1.5)));
+    }
+
+    #[test]
+    fn report_line_of_invalid_event() {
+        let err = InputReplay::parse("{\"type\":\"frame\",\"time\":0.0}\n{\"type\":\"jump\"}")
+            .unwrap_err();
+        assert!(matches!(err, InputError::Json { line: 2, .. }));
+    }
+}
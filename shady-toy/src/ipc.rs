@@ -0,0 +1,277 @@
+//! Remote control of `shady-toy` in daemon mode.
+//!
+//! Each line which is sent to the unix socket is one command. `shady-toy` answers
+//! with `ok` or `error: <reason>` for each command. See [Command] for the available commands.
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::mpsc,
+};
+
+use tracing::{debug, warn};
+use winit::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+const SOCKET_NAME: &str = "shady-toy.sock";
+
+/// A command which can be sent to `shady-toy`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `load <path>`: Render the given shader file from now on.
+    Load(PathBuf),
+
+    /// `pause`: Stop rendering new frames.
+    Pause,
+
+    /// `resume`: Continue rendering new frames.
+    Resume,
+
+    /// `toggle-pause`: Pause if it's running, resume if it's paused.
+    TogglePause,
+
+    /// `set <uniform> <values...>`: Overwrite the value of a uniform buffer.
+    Set(Uniform),
+
+    /// `show`: Make the window visible.
+    Show,
+
+    /// `hide`: Hide the window (`shady-toy` keeps running).
+    Hide,
+
+    /// `quit`: Stop `shady-toy`.
+    Quit,
+}
+
+/// The uniform buffers which can be set with [Command::Set].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Uniform {
+    /// `set time <seconds>`
+    #[cfg(feature = "time")]
+    Time(f32),
+
+    /// `set mouse <x> <y>`
+    #[cfg(feature = "mouse")]
+    Mouse(f32, f32),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+
+        let expect_no_args = |cmd: Self| {
+            if args.is_empty() {
+                Ok(cmd)
+            } else {
+                Err(format!("`{}` doesn't take any arguments.", name))
+            }
+        };
+
+        match name {
+            "load" if args.is_empty() => Err("`load` needs the path to a shader file.".into()),
+            "load" => Ok(Self::Load(PathBuf::from(args))),
+            "pause" => expect_no_args(Self::Pause),
+            "resume" => expect_no_args(Self::Resume),
+            "toggle-pause" => expect_no_args(Self::TogglePause),
+            "set" => args.parse().map(Self::Set),
+            "show" => expect_no_args(Self::Show),
+            "hide" => expect_no_args(Self::Hide),
+            "quit" => expect_no_args(Self::Quit),
+            _ => Err(format!("Unknown command: `{}`", name)),
+        }
+    }
+}
+
+impl FromStr for Uniform {
+    type Err = String;
+
+    fn from_str(args: &str) -> Result<Self, Self::Err> {
+        let mut args = args.split_whitespace();
+        let name = args.next().ok_or("`set` needs the name of a uniform.")?;
+        let values = args
+            .map(|value| {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("`{}` isn't a number.", value))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+
+        // `unused`: Depending on the enabled features, the values might not be used.
+        #[allow(unused)]
+        let expect_values = |amount: usize| {
+            if values.len() == amount {
+                Ok(())
+            } else {
+                Err(format!(
+                    "`{}` needs {} value(s) but got {}.",
+                    name,
+                    amount,
+                    values.len()
+                ))
+            }
+        };
+
+        match name {
+            #[cfg(feature = "time")]
+            "time" => expect_values(1).map(|_| Self::Time(values[0])),
+            #[cfg(feature = "mouse")]
+            "mouse" => expect_values(2).map(|_| Self::Mouse(values[0], values[1])),
+            _ => Err(format!("Unknown uniform: `{}`", name)),
+        }
+    }
+}
+
+/// Returns the path of the socket if none is given by the user.
+pub fn default_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(SOCKET_NAME)
+}
+
+/// Creates the socket at the given path.
+///
+/// A leftover socket of a previous instance gets replaced but it fails if another instance is still listening on it.
+/// Other files at the path are never removed.
+pub fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if UnixStream::connect(path).is_ok() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    format!("Another instance is already listening on {:?}", path),
+                ));
+            }
+
+            std::fs::remove_file(path)?;
+        }
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{:?} already exists and isn't a socket", path),
+            ))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    UnixListener::bind(path)
+}
+
+/// Accepts connections and forwards their commands to the event loop.
+pub fn listen(listener: UnixListener, proxy: EventLoopProxy<UserEvent>) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let proxy = proxy.clone();
+                std::thread::spawn(move || {
+                    if let Err(err) = handle_client(stream, &proxy) {
+                        warn!("IPC client: {}", err);
+                    }
+                });
+            }
+            Err(err) => warn!("Couldn't accept IPC connection: {}", err),
+        }
+    }
+}
+
+fn handle_client(stream: UnixStream, proxy: &EventLoopProxy<UserEvent>) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        debug!("IPC command: {}", line);
+        let response = match line.parse::<Command>() {
+            Ok(command) => {
+                let (tx, rx) = mpsc::channel();
+
+                if proxy.send_event(UserEvent::Ipc(command, tx)).is_err() {
+                    // the event loop is gone => we're shutting down
+                    return Ok(());
+                }
+
+                rx.recv().unwrap_or(Ok(()))
+            }
+            Err(err) => Err(err),
+        };
+
+        match response {
+            Ok(()) => writeln!(writer, "ok")?,
+            Err(err) => writeln!(writer, "error: {}", err)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            "load /tmp/a shader.glsl".parse(),
+            Ok(Command::Load(PathBuf::from("/tmp/a shader.glsl")))
+        );
+        assert_eq!("  pause\n".parse(), Ok(Command::Pause));
+        assert_eq!("toggle-pause".parse(), Ok(Command::TogglePause));
+        assert!("load".parse::<Command>().is_err());
+        assert!("quit now".parse::<Command>().is_err());
+        assert!("dance".parse::<Command>().is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "time", feature = "mouse"))]
+    fn parse_uniforms() {
+        assert_eq!("set time 4.5".parse(), Ok(Command::Set(Uniform::Time(4.5))));
+        assert_eq!(
+            "set mouse 10 20".parse(),
+            Ok(Command::Set(Uniform::Mouse(10., 20.)))
+        );
+        assert!("set mouse 10".parse::<Command>().is_err());
+        assert!("set time soon".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn bind_replaces_only_leftover_sockets() {
+        let dir = std::env::temp_dir().join(format!("shady-ipc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // a regular file is kept
+        let file = dir.join("file");
+        std::fs::write(&file, "content").unwrap();
+        assert_eq!(
+            bind(&file).unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "content");
+
+        // another instance is listening
+        let socket = dir.join("socket");
+        let listener = bind(&socket).unwrap();
+        assert_eq!(
+            bind(&socket).unwrap_err().kind(),
+            std::io::ErrorKind::AddrInUse
+        );
+
+        // a leftover socket
+        drop(listener);
+        assert!(bind(&socket).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
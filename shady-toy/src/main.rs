@@ -1,8 +1,31 @@
+mod adapter;
+#[cfg(feature = "channels")]
+mod channels;
 mod cli;
+#[cfg(feature = "export")]
+mod export;
 mod frontend;
+mod gallery;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod import;
+mod input;
+#[cfg(unix)]
+mod ipc;
 mod logger;
+#[cfg(feature = "mapping")]
+mod mapping;
+#[cfg(feature = "track")]
+mod mpd;
+mod overlay;
+mod pipeline_cache;
+mod project;
+mod quality;
 mod renderer;
+mod snapshot;
+mod source;
 mod states;
+mod watcher;
 
 use std::{
     path::{Path, PathBuf},
@@ -12,13 +35,14 @@ use std::{
 use anyhow::Result;
 use ariadne::Fmt;
 use frontend::ShaderLanguage;
-use notify::{Event, EventKind, RecursiveMode, Watcher};
+use project::Project;
 use renderer::Renderer;
 use shady::TemplateLang;
-use tracing::{debug, debug_span};
+use source::FragmentSource;
+use watcher::{WatchedFile, WatcherEvent};
 use winit::{
     error::EventLoopError,
-    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+    event_loop::{ControlFlow, EventLoop},
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -38,43 +62,163 @@ pub enum Error {
     #[error("{0}")]
     UnknownShaderFileExtension(String),
 
+    #[error("The shader language of stdin can't be detected, please set it with `--lang`.")]
+    MissingLanguage,
+
+    #[error("A template can only be inserted into a file.")]
+    TemplateWithoutFile,
+
+    #[error("No example has been chosen.")]
+    NoExampleChosen,
+
     #[error(transparent)]
     IO(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Import(#[from] import::ImportError),
+
+    #[error(transparent)]
+    Project(#[from] project::ProjectError),
+
+    #[error("Couldn't replay the input: {0}")]
+    InputReplay(#[from] input::InputError),
+
+    #[error("Couldn't load the snapshot: {0}")]
+    Snapshot(#[from] snapshot::SnapshotError),
+
+    #[cfg(feature = "export")]
+    #[error(transparent)]
+    Export(#[from] export::ExportError),
+
+    #[cfg(feature = "timeline")]
+    #[error(transparent)]
+    Timeline(#[from] shady::timeline::TimelineError),
+
+    #[cfg(feature = "mapping")]
+    #[error("Invalid mapping: {0}")]
+    Mapping(#[from] mapping::MappingError),
+
+    #[cfg(feature = "channels")]
+    #[error("Couldn't load the texture of iChannel{0}: {1}")]
+    Channel(usize, image::ImageError),
 }
 
-#[derive(Debug, Clone, Copy)]
-enum UserEvent {
-    UpdatePath,
+#[derive(Debug)]
+pub enum UserEvent {
+    /// The watched file has changed.
+    FileChanged(WatchedFile),
+
+    /// Read the fragment code again (`SIGHUP` or `r` in the window).
+    Reload,
+
+    /// New fragment code from stdin.
+    FragmentCode(String),
+
+    /// The pipeline of the shader with the given generation has been compiled.
+    PipelineReady(states::window_state::ShaderPipelines, u64),
+
+    /// The new state of the track of the music player.
+    #[cfg(feature = "track")]
+    Track(mpd::TrackStatus),
+
+    /// The new state of the gamepad.
+    #[cfg(feature = "gamepad")]
+    Gamepad(gamepad::GamepadState),
+
+    /// A command from the unix socket. The result is sent back to the client.
+    #[cfg(unix)]
+    Ipc(ipc::Command, mpsc::Sender<Result<(), String>>),
 }
 
 fn main() -> Result<()> {
     logger::init();
-    let args = cli::parse();
+    let mut args = cli::parse();
 
-    if args.template {
-        add_template_to_file(&args.fragment_path)?;
+    match &args.command {
+        Some(cli::Command::Import {
+            shader,
+            output,
+            api_key,
+        }) => return Ok(import_shader(shader, output.clone(), api_key)?),
+        #[cfg(feature = "export")]
+        Some(cli::Command::Export {
+            fragment_path,
+            output,
+            duration,
+            fps,
+            width,
+            height,
+            lang,
+            #[cfg(feature = "audio")]
+            audio,
+        }) => {
+            let options = export::ExportOptions {
+                size: (*width, *height),
+                fps: *fps,
+                duration: *duration,
+                color_space: args.color_space.into(),
+                #[cfg(feature = "audio-texture")]
+                audio_representation: args.audio_representation(),
+                #[cfg(feature = "audio")]
+                audio: audio.clone(),
+            };
+            return Ok(export_shader(
+                fragment_path,
+                *lang,
+                output.clone(),
+                &options,
+            )?);
+        }
+        None => {}
     }
 
-    if !std::fs::exists(&args.fragment_path).expect("Check if fragment file exists") {
-        eprintln!(
-            "The given fragment path does not exist: \"{}\"",
-            args.fragment_path.to_string_lossy()
-        );
-        std::process::exit(1);
+    let fragment_path = args
+        .fragment_path
+        .clone()
+        .expect("Fragment path is required without subcommand");
+
+    let project = if Project::is_project_file(&fragment_path) {
+        let project = Project::load(&fragment_path)?;
+        project.apply_to(&mut args);
+        Some(project)
+    } else {
+        None
+    };
+
+    let fragment_source = FragmentSource::from(
+        args.fragment_path
+            .clone()
+            .expect("Fragment path is required without subcommand"),
+    );
+
+    if let Some(template) = args.template {
+        let path = fragment_source.path().ok_or(Error::TemplateWithoutFile)?;
+        add_template_to_file(path, template, args.prelude)?;
+    }
+
+    if let Some(path) = fragment_source.path() {
+        if !std::fs::exists(path).expect("Check if fragment file exists") {
+            eprintln!(
+                "The given fragment path does not exist: \"{}\"",
+                path.to_string_lossy()
+            );
+            std::process::exit(1);
+        }
     }
 
-    let frontend = ShaderLanguage::try_from(args.fragment_path.as_path())
-        .map_err(Error::UnknownShaderFileExtension)?;
+    let frontend = match (args.lang, fragment_source.path()) {
+        (Some(lang), _) => lang,
+        (None, Some(path)) => {
+            ShaderLanguage::try_from(path).map_err(Error::UnknownShaderFileExtension)?
+        }
+        (None, None) => return Err(Error::MissingLanguage.into()),
+    };
 
     println!(
         "[{}]: Press `q` in the shader-window to exit.",
         "NOTE".fg(ariadne::Color::Cyan)
     );
 
-    start_app(args.fragment_path, frontend)
-}
-
-fn start_app(fragment_path: PathBuf, frontend: ShaderLanguage) -> Result<()> {
     let event_loop = EventLoop::<UserEvent>::with_user_event()
         .build()
         .expect("Create window eventloop");
@@ -82,52 +226,230 @@ fn start_app(fragment_path: PathBuf, frontend: ShaderLanguage) -> Result<()> {
 
     let proxy = Arc::new(event_loop.create_proxy());
 
+    let (watcher_tx, watcher_rx) = mpsc::channel();
+    let poll_interval = args
+        .poll
+        .map(|ms| std::time::Duration::from_millis(ms.get()));
     std::thread::spawn({
-        let path = fragment_path.clone();
-        move || watch_shader_file(path, proxy)
+        let watcher_tx = watcher_tx.clone();
+        move || watcher::watch_files(watcher_tx, watcher_rx, proxy, poll_interval)
     });
+    let _ = watcher_tx.send(WatcherEvent::SetPath(
+        WatchedFile::Shader,
+        fragment_source.path().map(Path::to_path_buf),
+    ));
 
-    let mut renderer = Renderer::new(fragment_path, frontend).expect("Init renderer");
-    event_loop.run_app(&mut renderer)?;
+    #[cfg(unix)]
+    {
+        let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGHUP])?;
+        let proxy = event_loop.create_proxy();
+        std::thread::spawn(move || {
+            for _ in signals.forever() {
+                if proxy.send_event(UserEvent::Reload).is_err() {
+                    break;
+                }
+            }
+        });
+    }
 
+    let adapter_config = args.adapter_config();
+    let mut renderer = Renderer::new(
+        fragment_source,
+        frontend,
+        adapter_config,
+        watcher_tx,
+        event_loop.create_proxy(),
+    )
+    .expect("Init renderer");
+
+    if let Some(target_fps) = args.target_fps {
+        renderer.set_quality_controller(quality::QualityController::new(target_fps));
+    }
+    renderer.set_crossfade_duration(renderer::crossfade_duration(args.crossfade));
+    renderer.set_audio_device(args.audio_device.clone());
+    renderer.set_color_space(args.color_space.into());
+    #[cfg(feature = "audio-texture")]
+    renderer.set_audio_representation(args.audio_representation());
+
+    if let Some(path) = &args.record_input {
+        renderer.set_input_recorder(input::InputRecorder::create(path)?);
+    }
+    if let Some(path) = &args.replay_input {
+        renderer.set_input_replay(input::InputReplay::load(path)?);
+    }
+
+    if let Some(project) = project {
+        renderer.set_window_attributes(project.window.attributes());
+        renderer.set_initial_uniforms(project.uniforms);
+        renderer.set_project(fragment_path, project);
+    }
+
+    if let Some(path) = &args.load_snapshot {
+        renderer.set_snapshot(snapshot::Snapshot::load(path)?);
+    }
+
+    #[cfg(feature = "timeline")]
+    if let Some(path) = &args.timeline {
+        renderer.load_timeline(path.clone())?;
+    }
+
+    #[cfg(feature = "mapping")]
+    if let Some(path) = &args.mapping {
+        renderer.load_mapping(path.clone())?;
+    }
+
+    #[cfg(feature = "channels")]
+    for (channel, source) in args.channels().into_iter().enumerate() {
+        if let Some(source) = source {
+            renderer
+                .load_channel(channel, source.clone())
+                .map_err(|err| Error::Channel(channel, err))?;
+        }
+    }
+
+    #[cfg(feature = "track")]
+    if let Some(address) = args.mpd.clone() {
+        let proxy = event_loop.create_proxy();
+        std::thread::spawn(move || mpd::poll(address, proxy));
+    }
+
+    #[cfg(feature = "gamepad")]
+    {
+        let proxy = event_loop.create_proxy();
+        std::thread::spawn(move || gamepad::poll(proxy));
+    }
+
+    #[cfg(unix)]
+    let socket_path = if args.daemon {
+        let path = args.socket.clone().unwrap_or_else(ipc::default_socket_path);
+        let listener = ipc::bind(&path)?;
+
+        println!(
+            "[{}]: Listening for commands on {}",
+            "NOTE".fg(ariadne::Color::Cyan),
+            path.to_string_lossy()
+        );
+
+        let proxy = event_loop.create_proxy();
+        std::thread::spawn(move || ipc::listen(listener, proxy));
+        renderer.set_daemon(true);
+
+        Some(path)
+    } else {
+        None
+    };
+
+    let result = event_loop.run_app(&mut renderer);
+
+    #[cfg(unix)]
+    if let Some(path) = socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+
+    result?;
     Ok(())
 }
 
-fn watch_shader_file<P: AsRef<Path>>(path: P, proxy: Arc<EventLoopProxy<UserEvent>>) -> Result<()> {
-    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
-    let mut watcher = notify::recommended_watcher(tx)?;
-    let span = debug_span!("Watcher");
-    let _enter = span.enter();
-
-    watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)?;
-
-    for res in rx {
-        match res {
-            Ok(event) => {
-                debug!("Event: {:?}", event);
-                match event.kind {
-                    EventKind::Remove(_) => {
-                        watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)?;
-                    }
-                    EventKind::Modify(_) => proxy.send_event(UserEvent::UpdatePath)?,
-                    _ => (),
-                };
-            }
-            Err(e) => println!("watch error: {:?}", e),
-        }
+fn import_shader(url_or_id: &str, output: Option<PathBuf>, api_key: &str) -> Result<(), Error> {
+    let id = import::parse_id(url_or_id)?;
+    let shader = import::download(&id, api_key)?;
+    let (code, warnings) = import::convert(&shader)?;
+
+    for warning in warnings {
+        println!("[{}]: {}", "WARNING".fg(ariadne::Color::Yellow), warning);
     }
 
+    let path = output.unwrap_or_else(|| PathBuf::from(format!("{}.glsl", id)));
+    std::fs::write(&path, code)?;
+
+    println!(
+        "[{}]: Stored \"{}\" in {}. Run `shady-toy {}` to render it.",
+        "OK".fg(ariadne::Color::Green),
+        shader.info.name,
+        path.to_string_lossy(),
+        path.to_string_lossy()
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "export")]
+fn export_shader(
+    fragment_path: &Path,
+    lang: Option<ShaderLanguage>,
+    output: PathBuf,
+    options: &export::ExportOptions,
+) -> Result<(), Error> {
+    let lang = match lang {
+        Some(lang) => lang,
+        None => {
+            ShaderLanguage::try_from(fragment_path).map_err(Error::UnknownShaderFileExtension)?
+        }
+    };
+
+    let fragment_code = std::fs::read_to_string(fragment_path)?;
+    let target = export::ExportTarget::from(output);
+
+    println!(
+        "[{}]: Rendering {} frames ...",
+        "NOTE".fg(ariadne::Color::Cyan),
+        options.amount_frames()
+    );
+    export::export(&fragment_code, lang, &target, options)?;
+
+    let path = match &target {
+        export::ExportTarget::Gif(path) | export::ExportTarget::PngSequence(path) => path,
+    };
+    println!(
+        "[{}]: Stored the frames in {}.",
+        "OK".fg(ariadne::Color::Green),
+        path.to_string_lossy()
+    );
+
     Ok(())
 }
 
-fn add_template_to_file(path: &Path) -> Result<(), Error> {
+#[cfg(feature = "timeline")]
+fn read_timeline(path: &Path) -> Result<shady::timeline::Timeline, Error> {
+    let description = std::fs::read_to_string(path)?;
+    Ok(shady::timeline::Timeline::from_toml(&description)?)
+}
+
+#[cfg(feature = "mapping")]
+fn read_mapping(path: &Path) -> Result<mapping::Mapping, Error> {
+    let description = std::fs::read_to_string(path)?;
+    Ok(mapping::Mapping::parse(&description)?)
+}
+
+fn add_template_to_file(
+    path: &Path,
+    template: cli::Template,
+    with_prelude: bool,
+) -> Result<(), Error> {
     let frontend = ShaderLanguage::try_from(path).map_err(Error::UnknownShaderFileExtension)?;
 
-    let template = match frontend {
-        ShaderLanguage::Wgsl => TemplateLang::Wgsl.generate_to_string(None),
-        ShaderLanguage::Glsl => TemplateLang::Glsl.generate_to_string(None),
-    }
-    .expect("Write template to given path");
+    let example = match template {
+        cli::Template::Default => None,
+        cli::Template::Gallery => {
+            let example = gallery::choose(&mut std::io::stdin().lock(), &mut std::io::stdout())?;
+            Some(example.ok_or(Error::NoExampleChosen)?)
+        }
+        cli::Template::Plasma => Some(&gallery::PLASMA),
+        cli::Template::AudioBars => Some(&gallery::AUDIO_BARS),
+        cli::Template::Raymarch => Some(&gallery::RAYMARCH),
+        cli::Template::Scope => Some(&gallery::SCOPE),
+    };
+
+    let template = match example {
+        Some(example) => example.generate(frontend, with_prelude),
+        None => {
+            let lang = match frontend {
+                ShaderLanguage::Wgsl => TemplateLang::Wgsl,
+                ShaderLanguage::Glsl => TemplateLang::Glsl,
+            };
+            gallery::generate_template(lang, None, with_prelude)
+        }
+    };
 
     std::fs::write(path, template)?;
 
@@ -0,0 +1,744 @@
+//! Maps the audio to custom values of `iParams` with small expressions, so shaders can react to the audio
+//! without any audio logic within the shader:
+//!
+//! ```text
+//! # comments start with a `#`
+//! uniform speed = bass * 2.0 + 0.5
+//! uniform flash = max(beat, treble)
+//! uniform zoom = mix(1.0, 1.5, speed / 2.5)
+//! ```
+//!
+//! Each uniform is stored in `iParams` in the order of the file and can use the uniforms above it.
+//! Shaders can read them by their name, see [Mapping::add_accessors].
+//! The available variables are listed in [Variable] and the functions in [Function].
+use std::{
+    num::NonZero,
+    time::{Duration, Instant},
+};
+
+use shady::shady_audio::{
+    BarProcessor, BarProcessorConfig, FrequencyBand, Normalization, SampleProcessor,
+};
+
+use crate::frontend::ShaderLanguage;
+
+/// The maximal amount of arguments of a [Function].
+const MAX_ARGS: usize = 3;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("Line {line}: {message}")]
+pub struct MappingError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// The uniforms of a mapping file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mapping {
+    names: Vec<String>,
+    expressions: Vec<Expr>,
+}
+
+impl Mapping {
+    pub fn parse(description: &str) -> Result<Self, MappingError> {
+        let mut mapping = Self::default();
+
+        for (idx, line) in description.lines().enumerate() {
+            let line_number = idx + 1;
+            let error = |message: String| MappingError {
+                line: line_number,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some(declaration) = line.strip_prefix("uniform ") else {
+                return Err(error("Expected `uniform <name> = <expression>`".into()));
+            };
+            let Some((name, expression)) = declaration.split_once('=') else {
+                return Err(error("Missing `=` after the name of the uniform".into()));
+            };
+
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(error(format!("Invalid name of the uniform: \"{}\"", name)));
+            }
+            if Variable::from_name(name).is_some() || Function::from_name(name).is_some() {
+                return Err(error(format!(
+                    "\"{}\" is already a variable or function",
+                    name
+                )));
+            }
+            if mapping.names.iter().any(|prev| prev == name) {
+                return Err(error(format!("The uniform \"{}\" is declared twice", name)));
+            }
+
+            let expression = Parser::new(expression, &mapping.names)
+                .parse()
+                .map_err(error)?;
+
+            mapping.names.push(name.to_string());
+            mapping.expressions.push(expression);
+        }
+
+        Ok(mapping)
+    }
+
+    /// The names of the uniforms in the order of `iParams`.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Makes each uniform available by its name within the fragment code, for example `speed()` returns
+    /// the value of the uniform `speed`.
+    ///
+    /// wgsl code gets a function per uniform at its end (if it declares `iParams`) and glsl code
+    /// a macro per uniform after its `#version` directive.
+    pub fn add_accessors(&self, fragment_code: &str, lang: ShaderLanguage) -> String {
+        let mut code = String::with_capacity(fragment_code.len());
+
+        match lang {
+            ShaderLanguage::Wgsl => {
+                code.push_str(fragment_code);
+                if !fragment_code.contains("iParams") {
+                    return code;
+                }
+
+                code.push('\n');
+                for (idx, name) in self.names.iter().enumerate() {
+                    code.push_str(&format!(
+                        "fn {}() -> f32 {{ return iParams[{}]; }}\n",
+                        name, idx
+                    ));
+                }
+            }
+            ShaderLanguage::Glsl => {
+                // `#version` has to stay the first directive
+                let mut lines = fragment_code.split_inclusive('\n').peekable();
+                if let Some(version) =
+                    lines.next_if(|line| line.trim_start().starts_with("#version"))
+                {
+                    code.push_str(version);
+                    if !version.ends_with('\n') {
+                        code.push('\n');
+                    }
+                }
+
+                // the block of `iParams` names its array `params`
+                for (idx, name) in self.names.iter().enumerate() {
+                    code.push_str(&format!("#define {}() params[{}]\n", name, idx));
+                }
+                code.extend(lines);
+            }
+        }
+
+        code
+    }
+
+    /// Computes the value of each uniform.
+    pub fn evaluate(&self, inputs: &Inputs, out: &mut Vec<f32>) {
+        out.clear();
+        for expression in self.expressions.iter() {
+            let value = expression.evaluate(inputs, out);
+            // a single `0 / 0` shouldn't break the whole shader
+            out.push(if value.is_finite() { value } else { 0. });
+        }
+    }
+}
+
+/// The values which the expressions can use.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Inputs {
+    pub bass: f32,
+    pub mid: f32,
+    pub treble: f32,
+    pub loudness: f32,
+    pub beat: f32,
+    pub beats: f32,
+    pub time: f32,
+}
+
+/// The variables of the expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    /// The level of the frequencies from 20Hz to 250Hz within `[0, 1]`.
+    Bass,
+    /// The level of the frequencies from 250Hz to 4kHz within `[0, 1]`.
+    Mid,
+    /// The level of the frequencies from 4kHz to 20kHz within `[0, 1]`.
+    Treble,
+    /// The loudness of the last three seconds within `[0, 1]` (-60 LUFS to 0 LUFS).
+    Loudness,
+    /// Jumps to `1` on each beat and falls back to `0` afterwards.
+    Beat,
+    /// The amount of beats so far.
+    Beats,
+    /// The seconds since the mapping has been loaded.
+    Time,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        let variable = match name {
+            "bass" => Self::Bass,
+            "mid" => Self::Mid,
+            "treble" => Self::Treble,
+            "loudness" => Self::Loudness,
+            "beat" => Self::Beat,
+            "beats" => Self::Beats,
+            "time" => Self::Time,
+            _ => return None,
+        };
+
+        Some(variable)
+    }
+
+    fn value(self, inputs: &Inputs) -> f32 {
+        match self {
+            Self::Bass => inputs.bass,
+            Self::Mid => inputs.mid,
+            Self::Treble => inputs.treble,
+            Self::Loudness => inputs.loudness,
+            Self::Beat => inputs.beat,
+            Self::Beats => inputs.beats,
+            Self::Time => inputs.time,
+        }
+    }
+}
+
+/// The functions of the expressions. They behave like the ones of wgsl and glsl.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Abs,
+    Sqrt,
+    Sin,
+    Cos,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    Mix,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "abs" => Self::Abs,
+            "sqrt" => Self::Sqrt,
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "pow" => Self::Pow,
+            "clamp" => Self::Clamp,
+            "mix" => Self::Mix,
+            _ => return None,
+        };
+
+        Some(function)
+    }
+
+    fn amount_args(self) -> usize {
+        match self {
+            Self::Abs | Self::Sqrt | Self::Sin | Self::Cos => 1,
+            Self::Min | Self::Max | Self::Pow => 2,
+            Self::Clamp | Self::Mix => 3,
+        }
+    }
+
+    fn apply(self, args: &[f32]) -> f32 {
+        match self {
+            Self::Abs => args[0].abs(),
+            Self::Sqrt => args[0].sqrt(),
+            Self::Sin => args[0].sin(),
+            Self::Cos => args[0].cos(),
+            Self::Min => args[0].min(args[1]),
+            Self::Max => args[0].max(args[1]),
+            Self::Pow => args[0].powf(args[1]),
+            Self::Clamp => args[0].max(args[1]).min(args[2]),
+            Self::Mix => args[0] + (args[1] - args[0]) * args[2],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f32),
+    Variable(Variable),
+    /// The value of a previous uniform.
+    Uniform(usize),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    // `uniforms` contains the values of the previous uniforms
+    fn evaluate(&self, inputs: &Inputs, uniforms: &[f32]) -> f32 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Variable(variable) => variable.value(inputs),
+            Self::Uniform(idx) => uniforms[*idx],
+            Self::Neg(expr) => -expr.evaluate(inputs, uniforms),
+            Self::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (
+                    lhs.evaluate(inputs, uniforms),
+                    rhs.evaluate(inputs, uniforms),
+                );
+
+                match op {
+                    Op::Add => lhs + rhs,
+                    Op::Sub => lhs - rhs,
+                    Op::Mul => lhs * rhs,
+                    Op::Div => lhs / rhs,
+                }
+            }
+            Self::Call(function, args) => {
+                let mut values = [0.; MAX_ARGS];
+                for (value, arg) in values.iter_mut().zip(args.iter()) {
+                    *value = arg.evaluate(inputs, uniforms);
+                }
+                function.apply(&values)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(String),
+    Op(Op),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '+' => Token::Op(Op::Add),
+            '-' => Token::Op(Op::Sub),
+            '*' => Token::Op(Op::Mul),
+            '/' => Token::Op(Op::Div),
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = start;
+                while let Some(&(idx, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    chars.next();
+                }
+
+                let number = &expression[start..end];
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number: \"{}\"", number))?,
+                ));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(idx, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    chars.next();
+                }
+
+                tokens.push(Token::Identifier(expression[start..end].to_string()));
+                continue;
+            }
+            c => return Err(format!("Unexpected character: '{}'", c)),
+        };
+
+        tokens.push(token);
+        chars.next();
+    }
+
+    Ok(tokens)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A recursive descent parser of the expressions with the usual precedence (`*` and `/` before `+` and `-`).
+struct Parser<'a> {
+    expression: &'a str,
+    // the names of the previous uniforms
+    uniforms: &'a [String],
+
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(expression: &'a str, uniforms: &'a [String]) -> Self {
+        Self {
+            expression,
+            uniforms,
+            tokens: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        self.tokens = tokenize(self.expression)?;
+
+        let expr = self.sum()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected {}", describe(token))),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_op(&mut self, ops: &[Op]) -> Option<Op> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                self.pos += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!(
+                "Expected {} instead of {}",
+                describe(&expected),
+                describe(&token)
+            )),
+            None => Err(format!("Expected {}", describe(&expected))),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.next_op(&[Op::Add, Op::Sub]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.next_op(&[Op::Mul, Op::Div]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.next_op(&[Op::Sub]).is_some() {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::LeftParen) => {
+                let expr = self.sum()?;
+                self.expect(Token::RightParen)?;
+                Ok(expr)
+            }
+            Some(Token::Identifier(name)) => {
+                if let Some(function) = Function::from_name(&name) {
+                    return self.call(function, &name);
+                }
+
+                if let Some(variable) = Variable::from_name(&name) {
+                    return Ok(Expr::Variable(variable));
+                }
+
+                self.uniforms
+                    .iter()
+                    .position(|uniform| *uniform == name)
+                    .map(Expr::Uniform)
+                    .ok_or_else(|| format!("Unknown variable: \"{}\"", name))
+            }
+            Some(token) => Err(format!("Unexpected {}", describe(&token))),
+            None => Err("Unexpected end of the expression".into()),
+        }
+    }
+
+    fn call(&mut self, function: Function, name: &str) -> Result<Expr, String> {
+        self.expect(Token::LeftParen)?;
+
+        let mut args = vec![self.sum()?];
+        while self.tokens.get(self.pos) == Some(&Token::Comma) {
+            self.pos += 1;
+            args.push(self.sum()?);
+        }
+        self.expect(Token::RightParen)?;
+
+        if args.len() != function.amount_args() {
+            return Err(format!(
+                "\"{}\" expects {} arguments but got {}",
+                name,
+                function.amount_args(),
+                args.len()
+            ));
+        }
+
+        Ok(Expr::Call(function, args))
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(value) => format!("number {}", value),
+        Token::Identifier(name) => format!("\"{}\"", name),
+        Token::Op(Op::Add) => "'+'".into(),
+        Token::Op(Op::Sub) => "'-'".into(),
+        Token::Op(Op::Mul) => "'*'".into(),
+        Token::Op(Op::Div) => "'/'".into(),
+        Token::LeftParen => "'('".into(),
+        Token::RightParen => "')'".into(),
+        Token::Comma => "','".into(),
+    }
+}
+
+// the frequency range (in Hz) of `bass`, `mid` and `treble`
+const BANDS: [(u16, u16); 3] = [(20, 250), (250, 4_000), (4_000, 20_000)];
+const BARS_PER_BAND: u16 = 4;
+// the loudness (in LUFS) which is mapped to `0`
+const MIN_LOUDNESS: f32 = -60.;
+
+// a beat is a sudden rise of the bass above its recent average
+const BEAT_THRESHOLD: f32 = 1.4;
+// quiet bass can't be a beat
+const BEAT_MIN_BASS: f32 = 0.1;
+// the time constant of the recent average of the bass
+const BASS_AVERAGE_TIME: Duration = Duration::from_secs(1);
+// how long `beat` needs to fall back to `0` (and the minimal time between two beats)
+const BEAT_DURATION: Duration = Duration::from_millis(200);
+
+/// Computes the [Inputs] of the mapping from the audio.
+pub struct AudioInputs {
+    bar_processor: BarProcessor,
+    bars: Vec<f32>,
+
+    start: Instant,
+    last_update: Instant,
+    bass_average: f32,
+    last_beat: Option<Instant>,
+    beats: u32,
+}
+
+impl AudioInputs {
+    pub fn new(sample_processor: &SampleProcessor) -> Self {
+        let bar_processor = BarProcessor::new(
+            sample_processor,
+            BarProcessorConfig {
+                normalization: Normalization::LOUDNESS,
+                bands: BANDS
+                    .iter()
+                    .map(|&(start, end)| FrequencyBand {
+                        freq_range: NonZero::new(start).unwrap()..NonZero::new(end).unwrap(),
+                        amount_bars: NonZero::new(BARS_PER_BAND).unwrap(),
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        );
+        let amount_bars =
+            bar_processor.amount_channels() * usize::from(bar_processor.config().amount_bars.get());
+
+        let now = Instant::now();
+        Self {
+            bar_processor,
+            bars: vec![0.; amount_bars],
+            start: now,
+            last_update: now,
+            bass_average: 0.,
+            last_beat: None,
+            beats: 0,
+        }
+    }
+
+    /// Analyzes the samples which the sample processor has processed last.
+    pub fn update(&mut self, sample_processor: &SampleProcessor) -> Inputs {
+        self.bar_processor
+            .process_bars_into(sample_processor, &mut self.bars);
+
+        // the average of each band over all channels
+        let mut levels = [0f32; BANDS.len()];
+        let band_len = usize::from(BARS_PER_BAND);
+        for channel_bars in self.bars.chunks(band_len * BANDS.len()) {
+            for (level, band_bars) in levels.iter_mut().zip(channel_bars.chunks(band_len)) {
+                *level += band_bars.iter().sum::<f32>();
+            }
+        }
+        let amount_values = (self.bars.len() / BANDS.len()) as f32;
+        let [bass, mid, treble] = levels.map(|level| {
+            let level = level / amount_values;
+            if level.is_finite() {
+                level
+            } else {
+                0.
+            }
+        });
+
+        let now = Instant::now();
+        let delta_time = now - self.last_update;
+        self.last_update = now;
+
+        let since_beat = self.last_beat.map(|last_beat| now - last_beat);
+        let is_beat = bass > BEAT_MIN_BASS
+            && bass > BEAT_THRESHOLD * self.bass_average
+            && since_beat.is_none_or(|since_beat| since_beat >= BEAT_DURATION);
+        if is_beat {
+            self.last_beat = Some(now);
+            self.beats += 1;
+        }
+
+        let smoothing = 1. - (-delta_time.as_secs_f32() / BASS_AVERAGE_TIME.as_secs_f32()).exp();
+        self.bass_average += smoothing * (bass - self.bass_average);
+
+        let beat = match self.last_beat {
+            Some(last_beat) => {
+                1. - ((now - last_beat).as_secs_f32() / BEAT_DURATION.as_secs_f32()).min(1.)
+            }
+            None => 0.,
+        };
+
+        let loudness = self
+            .bar_processor
+            .loudness()
+            .map(|loudness| (1. - loudness / MIN_LOUDNESS).clamp(0., 1.))
+            .unwrap_or_default();
+
+        Inputs {
+            bass,
+            mid,
+            treble,
+            loudness,
+            beat,
+            beats: self.beats as f32,
+            time: (now - self.start).as_secs_f32(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(description: &str, inputs: &Inputs) -> Vec<f32> {
+        let mut values = Vec::new();
+        Mapping::parse(description)
+            .unwrap()
+            .evaluate(inputs, &mut values);
+        values
+    }
+
+    #[test]
+    fn precedence_and_previous_uniforms() {
+        let inputs = Inputs {
+            bass: 0.5,
+            beat: 1.,
+            ..Default::default()
+        };
+
+        let values = evaluate(
+            "
+            # the speed of the waves
+            uniform speed = bass * 2.0 + 0.5
+            uniform flash = -(1 - beat) + speed / 3 # trailing comment
+            uniform glow = clamp(mix(0, 10, speed), 0, 2) + max(.25, abs(-0.5))
+            ",
+            &inputs,
+        );
+
+        assert_eq!(values, [1.5, 0.5, 2.5]);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |description: &str| Mapping::parse(description).unwrap_err();
+
+        assert_eq!(error("uniform a = 1\nuniform b = foo").line, 2);
+        assert_eq!(error("speed = bass").line, 1);
+        assert_eq!(error("\nuniform a = (bass").line, 2);
+        assert_eq!(error("uniform a = bass\nuniform a = 1").line, 2);
+        assert_eq!(error("uniform bass = 1").line, 1);
+        assert_eq!(error("uniform a = min(bass)").line, 1);
+        assert_eq!(error("uniform a = bass treble").line, 1);
+        assert_eq!(error("uniform a = b\nuniform b = 1").line, 1);
+    }
+
+    #[test]
+    fn accessors_by_name() {
+        let mapping = Mapping::parse("uniform speed = 1\nuniform flash = 2").unwrap();
+
+        let wgsl = mapping.add_accessors(
+            &crate::gallery::generate_template(shady::TemplateLang::Wgsl, None, false),
+            ShaderLanguage::Wgsl,
+        );
+        assert!(wgsl.ends_with("fn flash() -> f32 { return iParams[1]; }\n"));
+        ShaderLanguage::Wgsl.parse(&wgsl).unwrap();
+
+        let glsl = mapping.add_accessors(
+            "#version 450
+layout(set = 0, binding = 0) readonly buffer iParams {
+    float[] params;
+};
+layout(location = 0) out vec4 fragColor;
+
+void main() {
+    fragColor = vec4(speed(), flash(), 0.0, 1.0);
+}
+",
+            ShaderLanguage::Glsl,
+        );
+        assert!(glsl.starts_with("#version 450\n#define speed() params[0]\n"));
+        ShaderLanguage::Glsl.parse(&glsl).unwrap();
+    }
+
+    #[test]
+    fn invalid_values_are_zero() {
+        assert_eq!(evaluate("uniform a = 0 / 0", &Inputs::default()), [0.]);
+    }
+}
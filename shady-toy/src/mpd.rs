@@ -0,0 +1,234 @@
+//! Polls the status of a [MPD] server for the `iTrackTime`, `iTrackDuration` and `iPlaying` uniforms.
+//!
+//! [MPD]: https://www.musicpd.org/
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use tracing::{debug, warn};
+use winit::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+/// The address of MPD if it runs locally with its default config.
+pub const DEFAULT_ADDRESS: &str = "localhost:6600";
+
+// `iTrackTime` is extrapolated between the polls, so it's only needed to catch seeks and track changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(thiserror::Error, Debug)]
+pub enum MpdError {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("The server doesn't seem to be MPD: {0}")]
+    UnexpectedGreeting(String),
+
+    #[error("MPD refused the command: {0}")]
+    Ack(String),
+
+    #[error("MPD closed the connection.")]
+    Closed,
+}
+
+/// The state of the current track.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrackStatus {
+    pub position: Duration,
+
+    /// `None` if the track has no fixed length (like a stream) or nothing is playing.
+    pub duration: Option<Duration>,
+
+    pub playing: bool,
+}
+
+/// Polls the status of the MPD server at `address` forever and sends it to the event loop.
+///
+/// Tries to reconnect if the connection gets lost.
+pub fn poll(address: String, proxy: EventLoopProxy<UserEvent>) {
+    loop {
+        match poll_connection(&address, &proxy) {
+            // the event loop is gone
+            Ok(()) => return,
+            Err(err) => {
+                warn!("Lost connection to MPD at {}: {}", address, err);
+                // nothing is known about the track anymore
+                if proxy
+                    .send_event(UserEvent::Track(TrackStatus::default()))
+                    .is_err()
+                {
+                    return;
+                }
+                std::thread::sleep(RECONNECT_DELAY);
+            }
+        }
+    }
+}
+
+/// Returns `Ok` if the event loop has been closed.
+fn poll_connection(address: &str, proxy: &EventLoopProxy<UserEvent>) -> Result<(), MpdError> {
+    let mut connection = BufReader::new(TcpStream::connect(address)?);
+
+    let greeting = read_line(&mut connection)?;
+    if !greeting.starts_with("OK MPD") {
+        return Err(MpdError::UnexpectedGreeting(greeting));
+    }
+    debug!("Connected to {}", greeting);
+
+    loop {
+        let status = request_status(&mut connection)?;
+        if proxy.send_event(UserEvent::Track(status)).is_err() {
+            return Ok(());
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn request_status(connection: &mut BufReader<TcpStream>) -> Result<TrackStatus, MpdError> {
+    connection.get_mut().write_all(b"status\n")?;
+
+    let mut lines = Vec::new();
+    loop {
+        let line = read_line(connection)?;
+        if line == "OK" {
+            break;
+        } else if line.starts_with("ACK") {
+            return Err(MpdError::Ack(line));
+        }
+
+        lines.push(line);
+    }
+
+    Ok(parse_status(lines.iter().map(String::as_str)))
+}
+
+fn read_line(connection: &mut BufReader<TcpStream>) -> Result<String, MpdError> {
+    let mut line = String::new();
+    if connection.read_line(&mut line)? == 0 {
+        return Err(MpdError::Closed);
+    }
+
+    Ok(line.trim_end().to_string())
+}
+
+/// Parses the response of the `status` command (without the final `OK`).
+fn parse_status<'a>(lines: impl Iterator<Item = &'a str>) -> TrackStatus {
+    let mut status = TrackStatus::default();
+    let mut stopped = false;
+
+    // negative or non-finite values are ignored instead of panicking
+    let secs = |value: &str| {
+        value
+            .parse::<f32>()
+            .ok()
+            .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+    };
+
+    for line in lines {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+
+        match key {
+            "state" => {
+                status.playing = value == "play";
+                stopped = value == "stop";
+            }
+            "elapsed" => status.position = secs(value).unwrap_or_default(),
+            "duration" => status.duration = secs(value),
+            // older versions only provide `<elapsed>:<duration>` in whole seconds
+            "time" => {
+                if let Some((elapsed, duration)) = value.split_once(':') {
+                    if status.position.is_zero() {
+                        status.position = secs(elapsed).unwrap_or_default();
+                    }
+                    if status.duration.is_none() {
+                        status.duration = secs(duration).filter(|duration| !duration.is_zero());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if stopped {
+        return TrackStatus::default();
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playing_track() {
+        let status = parse_status(
+            [
+                "volume: 100",
+                "state: play",
+                "song: 3",
+                "time: 12:200",
+                "elapsed: 12.5",
+                "duration: 200.25",
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(
+            status,
+            TrackStatus {
+                position: Duration::from_secs_f32(12.5),
+                duration: Some(Duration::from_secs_f32(200.25)),
+                playing: true,
+            }
+        );
+    }
+
+    #[test]
+    fn old_server() {
+        let status = parse_status(["state: pause", "time: 12:200"].into_iter());
+
+        assert_eq!(
+            status,
+            TrackStatus {
+                position: Duration::from_secs(12),
+                duration: Some(Duration::from_secs(200)),
+                playing: false,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_times() {
+        let status = parse_status(
+            [
+                "state: play",
+                "elapsed: -1.5",
+                "duration: inf",
+                "time: NaN:-3",
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(
+            status,
+            TrackStatus {
+                position: Duration::ZERO,
+                duration: None,
+                playing: true,
+            }
+        );
+    }
+
+    #[test]
+    fn stopped() {
+        let status = parse_status(["state: stop", "elapsed: 3.0"].into_iter());
+        assert_eq!(status, TrackStatus::default());
+    }
+}
@@ -0,0 +1,273 @@
+//! Shows an error (like a compilation error of the shader) within the window,
+//! for users who started `shady-toy` without a terminal.
+use std::convert::Infallible;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_8X13, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, OriginDimensions, Point, RgbColor, Size},
+    text::{Baseline, Text},
+    Drawable, Pixel,
+};
+use wgpu::{Device, Queue};
+
+// the space between the text and the border of the overlay in pixels
+const PADDING: u32 = 8;
+// keep the texture within the texture size limits of all devices
+const MAX_SIZE: u32 = 2048;
+
+const BACKGROUND: [u8; 4] = [0, 0, 0, 200];
+const TEXT_COLOR: Rgb888 = Rgb888::new(255, 120, 120);
+
+/// Draws the error text in the top left corner above the shader.
+pub struct ErrorOverlay {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    // `None` if there's no error
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+impl ErrorOverlay {
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Error overlay bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("overlay.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Error overlay pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Error overlay pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            layout,
+            bind_group: None,
+        }
+    }
+
+    /// Shows the given error or hides the overlay if it's `None`.
+    pub fn set_error(&mut self, device: &Device, queue: &Queue, error: Option<&str>) {
+        let Some(error) = error else {
+            self.bind_group = None;
+            return;
+        };
+
+        let canvas = Canvas::with_text(&strip_ansi_codes(error));
+        let size = wgpu::Extent3d {
+            width: canvas.width,
+            height: canvas.height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Error overlay texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            texture.as_image_copy(),
+            &canvas.rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * canvas.width),
+                rows_per_image: None,
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Error overlay bind group"),
+            layout: &self.layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        }));
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.bind_group.is_some()
+    }
+
+    /// Draws the overlay on top of the content of `view` (if there's an error).
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let Some(bind_group) = &self.bind_group else {
+            return;
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Error overlay render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// The pixels of the rasterized text.
+struct Canvas {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn with_text(text: &str) -> Self {
+        let font = &FONT_8X13;
+        let amount_columns = text.lines().map(|line| line.chars().count()).max();
+        let amount_lines = text.lines().count();
+
+        let width = (amount_columns.unwrap_or(0) as u32 * font.character_size.width + 2 * PADDING)
+            .min(MAX_SIZE);
+        let height = (amount_lines as u32 * font.character_size.height + 2 * PADDING).min(MAX_SIZE);
+
+        let mut canvas = Self {
+            width,
+            height,
+            rgba: BACKGROUND.repeat((width * height) as usize),
+        };
+
+        let style = MonoTextStyle::new(font, TEXT_COLOR);
+        let position = Point::new(PADDING as i32, PADDING as i32);
+        let Ok(_) = Text::with_baseline(text, position, style, Baseline::Top).draw(&mut canvas);
+
+        canvas
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+
+            let idx = 4 * (y * self.width + x) as usize;
+            self.rgba[idx..idx + 4].copy_from_slice(&[color.r(), color.g(), color.b(), 255]);
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the color codes of the terminal from the report of the parser.
+fn strip_ansi_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // skip everything until the final byte of the escape sequence
+            '\u{1b}' => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            '\t' => stripped.push_str("    "),
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_color_codes() {
+        assert_eq!(
+            strip_ansi_codes("\u{1b}[31merror\u{1b}[0m: unknown\tident"),
+            "error: unknown    ident"
+        );
+    }
+
+    #[test]
+    fn canvas_fits_text() {
+        let canvas = Canvas::with_text("ab\nc");
+
+        assert_eq!(canvas.width, 2 * 8 + 2 * PADDING);
+        assert_eq!(canvas.height, 2 * 13 + 2 * PADDING);
+        assert!(canvas
+            .rgba
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .any(|pixel| pixel[3] == 255));
+    }
+}
@@ -0,0 +1,20 @@
+@group(0) @binding(0)
+var overlay: texture_2d<f32>;
+
+// draws a triangle which covers the whole target
+@vertex
+fn vertex_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    let pos = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u)) * 2.0 - 1.0;
+    return vec4<f32>(pos, 0.0, 1.0);
+}
+
+// the overlay is placed in the top left corner with one texel per pixel
+@fragment
+fn fragment_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let coords = vec2<u32>(pos.xy);
+    if any(coords >= textureDimensions(overlay)) {
+        discard;
+    }
+
+    return textureLoad(overlay, coords, 0);
+}
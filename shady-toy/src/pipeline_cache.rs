@@ -0,0 +1,92 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use tracing::{debug, warn};
+use wgpu::{Adapter, Device, PipelineCache};
+
+const CACHE_DIR_NAME: &str = "shady-toy";
+
+// makes the temporary files of concurrent saves unique within the process
+static SAVE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A [PipelineCache] which is stored on the disk to speed up the compilation of the shaders
+/// across multiple runs.
+#[derive(Debug, Clone)]
+pub struct PersistentPipelineCache {
+    cache: PipelineCache,
+    path: PathBuf,
+}
+
+impl PersistentPipelineCache {
+    /// Returns the features which are needed for the pipeline cache (if the adapter supports them).
+    pub fn required_features(adapter: &Adapter) -> wgpu::Features {
+        adapter.features() & wgpu::Features::PIPELINE_CACHE
+    }
+
+    /// Loads the cache of the given adapter. Returns `None` if the device or adapter doesn't support pipeline caches.
+    pub fn load(device: &Device, adapter: &Adapter) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+
+        let key = wgpu::util::pipeline_cache_key(&adapter.get_info())?;
+        let path = cache_dir()?.join(key);
+        let data = std::fs::read(&path).ok();
+        debug!("Pipeline cache: {:?} (exists: {})", path, data.is_some());
+
+        // SAFETY: The data has been created by `PipelineCache::get_data` of the same adapter
+        // (the key of the adapter is part of the path) and `fallback` is set in case it's invalid anyway.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Shady-toy pipeline cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+
+        Some(Self { cache, path })
+    }
+
+    pub fn cache(&self) -> &PipelineCache {
+        &self.cache
+    }
+
+    /// Writes the cache to the disk.
+    pub fn save(&self) {
+        let Some(data) = self.cache.get_data() else {
+            return;
+        };
+
+        if let Err(err) = self.write(&data) {
+            warn!("Couldn't store pipeline cache in {:?}: {}", self.path, err);
+        }
+    }
+
+    fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // write it atomically so that a crash doesn't leave a broken cache behind.
+        // Each save gets its own file, so concurrent saves (of other threads or instances) don't mix their data.
+        let tmp_path = self.path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            SAVE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(err) = std::fs::write(&tmp_path, data) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+        std::fs::rename(tmp_path, &self.path)
+    }
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join(CACHE_DIR_NAME))
+}
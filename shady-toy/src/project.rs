@@ -0,0 +1,324 @@
+//! Project files bundle a shader with its settings, so a setup can be shared and reproduced with `shady-toy project.toml`.
+//!
+//! Relative paths in a project file are relative to the directory of the project file.
+//!
+//! The project file is reloaded when it changes, see [Project::needs_restart] for the settings which can't be
+//! changed while the shader is running.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use winit::{
+    dpi::PhysicalSize,
+    window::{Fullscreen, WindowAttributes},
+};
+
+use crate::{cli::Args, frontend::ShaderLanguage};
+
+/// The extension of project files.
+pub const PROJECT_EXTENSION: &str = "toml";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProjectError {
+    #[error("Couldn't read the project file: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Invalid project file: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    /// The path to the fragment shader.
+    pub shader: PathBuf,
+
+    /// Overwrites the detection of the shader language by the file extension.
+    pub lang: Option<ShaderLanguage>,
+
+    /// The path to a timeline file (see `--timeline`).
+    pub timeline: Option<PathBuf>,
+
+    /// The path to a mapping file (see `--mapping`).
+    pub mapping: Option<PathBuf>,
+
+    /// The name of the output device whose audio should be visualized.
+    pub audio_device: Option<String>,
+
+    pub target_fps: Option<std::num::NonZero<u32>>,
+
+    /// In seconds.
+    pub crossfade: Option<f32>,
+
+    #[serde(default)]
+    pub channels: Channels,
+
+    #[serde(default)]
+    pub uniforms: Uniforms,
+
+    #[serde(default)]
+    pub window: WindowOptions,
+}
+
+/// The sources of the texture channels, see `--channel0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Channels {
+    pub channel0: Option<String>,
+    pub channel1: Option<String>,
+    pub channel2: Option<String>,
+    pub channel3: Option<String>,
+}
+
+/// The values of the uniforms when the shader starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Uniforms {
+    /// `iTime` in seconds.
+    pub time: Option<f32>,
+
+    /// The position of `iMouse`.
+    pub mouse: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowOptions {
+    pub title: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub fullscreen: bool,
+}
+
+impl Project {
+    /// Returns `true` if the given path should be treated as a project file.
+    pub fn is_project_file(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension == PROJECT_EXTENSION)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        let description = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        Ok(Self::from_toml(&description)?.relative_to(dir))
+    }
+
+    pub fn from_toml(description: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(description)
+    }
+
+    /// Makes the relative paths of the project relative to `dir` instead.
+    fn relative_to(mut self, dir: &Path) -> Self {
+        self.shader = dir.join(&self.shader);
+        self.timeline = self.timeline.map(|timeline| dir.join(timeline));
+        self.mapping = self.mapping.map(|mapping| dir.join(mapping));
+
+        for channel in self.channels.iter_mut().flatten() {
+            if !matches!(channel.as_str(), "noise" | "gradient") {
+                *channel = dir.join(&*channel).to_string_lossy().into_owned();
+            }
+        }
+
+        self
+    }
+
+    /// Uses the settings of the project for everything which hasn't been set on the command line.
+    pub fn apply_to(&self, args: &mut Args) {
+        args.fragment_path = Some(self.shader.clone());
+        args.lang = args.lang.or(self.lang);
+        args.target_fps = args.target_fps.or(self.target_fps);
+        args.audio_device = args.audio_device.take().or(self.audio_device.clone());
+
+        if let Some(crossfade) = self.crossfade.filter(|_| args.crossfade == 0.) {
+            args.crossfade = crossfade;
+        }
+
+        #[cfg(feature = "timeline")]
+        if args.timeline.is_none() {
+            args.timeline = self.timeline.clone();
+        }
+
+        #[cfg(feature = "mapping")]
+        if args.mapping.is_none() {
+            args.mapping = self.mapping.clone();
+        }
+
+        #[cfg(feature = "channels")]
+        for (arg, source) in [
+            &mut args.channel0,
+            &mut args.channel1,
+            &mut args.channel2,
+            &mut args.channel3,
+        ]
+        .into_iter()
+        .zip(self.channel_sources())
+        {
+            if arg.is_none() {
+                *arg = source;
+            }
+        }
+    }
+
+    /// The sources of the texture channels.
+    #[cfg(feature = "channels")]
+    pub fn channel_sources(
+        &self,
+    ) -> [Option<crate::channels::ChannelSource>; shady::AMOUNT_CHANNELS] {
+        let mut sources = self
+            .channels
+            .iter()
+            .map(|channel| channel.as_ref().map(|source| source.parse().unwrap()));
+
+        std::array::from_fn(|_| sources.next().flatten())
+    }
+
+    /// Returns `true` if settings have changed in `new` which are only applied when the window is created.
+    pub fn needs_restart(&self, new: &Self) -> bool {
+        let window_changed = (
+            self.window.width,
+            self.window.height,
+            self.window.fullscreen,
+        ) != (new.window.width, new.window.height, new.window.fullscreen);
+
+        window_changed
+            || self.audio_device != new.audio_device
+            || self.target_fps != new.target_fps
+            || self.uniforms != new.uniforms
+    }
+}
+
+impl Channels {
+    fn iter(&self) -> impl Iterator<Item = &Option<String>> {
+        [
+            &self.channel0,
+            &self.channel1,
+            &self.channel2,
+            &self.channel3,
+        ]
+        .into_iter()
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Option<String>> {
+        [
+            &mut self.channel0,
+            &mut self.channel1,
+            &mut self.channel2,
+            &mut self.channel3,
+        ]
+        .into_iter()
+    }
+}
+
+impl WindowOptions {
+    pub fn attributes(&self) -> WindowAttributes {
+        let mut attributes = WindowAttributes::default();
+
+        if let Some(title) = &self.title {
+            attributes = attributes.with_title(title);
+        }
+
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            attributes = attributes.with_inner_size(PhysicalSize::new(width, height));
+        }
+
+        if self.fullscreen {
+            attributes = attributes.with_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"
+        shader = "shaders/clouds.glsl"
+        timeline = "timeline.toml"
+        mapping = "audio.mapping"
+        crossfade = 1.5
+
+        [channels]
+        channel0 = "noise"
+        channel2 = "textures/rock.png"
+
+        [uniforms]
+        time = 10.0
+        mouse = [0.5, 0.25]
+
+        [window]
+        title = "Clouds"
+        width = 1280
+        height = 720
+    "#;
+
+    #[test]
+    fn paths_are_relative_to_the_project() {
+        let project = Project::from_toml(PROJECT)
+            .unwrap()
+            .relative_to(Path::new("/projects/clouds"));
+
+        assert_eq!(
+            project.shader,
+            PathBuf::from("/projects/clouds/shaders/clouds.glsl")
+        );
+        assert_eq!(
+            project.timeline,
+            Some(PathBuf::from("/projects/clouds/timeline.toml"))
+        );
+        assert_eq!(
+            project.mapping,
+            Some(PathBuf::from("/projects/clouds/audio.mapping"))
+        );
+        assert_eq!(project.channels.channel0.as_deref(), Some("noise"));
+        assert_eq!(
+            project.channels.channel2.as_deref(),
+            Some("/projects/clouds/textures/rock.png")
+        );
+    }
+
+    #[test]
+    fn parse_settings() {
+        let project = Project::from_toml(PROJECT).unwrap();
+
+        assert_eq!(project.crossfade, Some(1.5));
+        assert_eq!(
+            project.uniforms,
+            Uniforms {
+                time: Some(10.),
+                mouse: Some([0.5, 0.25])
+            }
+        );
+        assert_eq!(
+            project.window,
+            WindowOptions {
+                title: Some("Clouds".into()),
+                width: Some(1280),
+                height: Some(720),
+                fullscreen: false,
+            }
+        );
+    }
+
+    #[test]
+    fn restart_only_for_window_settings() {
+        let project = Project::from_toml(PROJECT).unwrap();
+
+        let mut new = project.clone();
+        new.crossfade = Some(0.5);
+        new.window.title = Some("Rain".into());
+        new.channels.channel1 = Some("gradient".into());
+        assert!(!project.needs_restart(&new));
+
+        new.window.fullscreen = true;
+        assert!(project.needs_restart(&new));
+    }
+
+    #[test]
+    fn reject_unknown_settings() {
+        assert!(Project::from_toml("shader = \"a.wgsl\"\nspeed = 2").is_err());
+    }
+}
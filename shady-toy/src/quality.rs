@@ -0,0 +1,148 @@
+use std::{num::NonZero, time::Duration};
+
+/// The frame rate is lowered at most to `target fps / MAX_DIVISOR`.
+const MAX_DIVISOR: u32 = 4;
+
+/// How much the latest frame influences the average cost of a frame.
+const SMOOTHING: f32 = 0.1;
+
+/// The amount of frames which are rendered after a change before the next change is considered,
+/// so the average cost can settle first.
+const SETTLE_FRAMES: u32 = 30;
+
+/// The quality is only restored if the average cost of a frame fits into this fraction
+/// of the budget of the higher frame rate. Otherwise it would toggle back and forth.
+const HEADROOM: f32 = 0.75;
+
+/// Lowers the frame rate if the shader can't reach the target frame rate and restores it
+/// once there's enough headroom again.
+///
+/// Useful for heavy shaders on weak GPUs (for example on laptops) which would otherwise keep the GPU
+/// busy all the time.
+#[derive(Debug, Clone)]
+pub struct QualityController {
+    target_fps: NonZero<u32>,
+
+    // the current frame rate cap is `target_fps / divisor`
+    divisor: u32,
+
+    // the average time in seconds which is needed to render a frame
+    avg_cost: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl QualityController {
+    pub fn new(target_fps: NonZero<u32>) -> Self {
+        Self {
+            target_fps,
+            divisor: 1,
+            avg_cost: None,
+            frames_since_change: 0,
+        }
+    }
+
+    /// Returns the current frame rate cap.
+    pub fn fps_cap(&self) -> u32 {
+        Self::fps(self.target_fps, self.divisor)
+    }
+
+    /// Returns the time between the beginning of two frames with the current frame rate cap.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f32(1. / self.fps_cap() as f32)
+    }
+
+    /// Records the time which was needed to render the last frame.
+    ///
+    /// Returns the new frame rate cap if it changed.
+    pub fn record_frame(&mut self, cost: Duration) -> Option<u32> {
+        let cost = cost.as_secs_f32();
+        let avg_cost = match self.avg_cost {
+            Some(avg_cost) => avg_cost + SMOOTHING * (cost - avg_cost),
+            None => cost,
+        };
+        self.avg_cost = Some(avg_cost);
+
+        self.frames_since_change += 1;
+        if self.frames_since_change < SETTLE_FRAMES {
+            return None;
+        }
+
+        let budget = |divisor: u32| 1. / Self::fps(self.target_fps, divisor) as f32;
+
+        let new_divisor = if avg_cost > budget(self.divisor) && self.divisor < MAX_DIVISOR {
+            self.divisor + 1
+        } else if self.divisor > 1 && avg_cost < HEADROOM * budget(self.divisor - 1) {
+            self.divisor - 1
+        } else {
+            return None;
+        };
+
+        self.divisor = new_divisor;
+        self.frames_since_change = 0;
+
+        Some(self.fps_cap())
+    }
+
+    fn fps(target_fps: NonZero<u32>, divisor: u32) -> u32 {
+        (target_fps.get() / divisor).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_frames(controller: &mut QualityController, cost: Duration) -> Option<u32> {
+        (0..SETTLE_FRAMES)
+            .filter_map(|_| controller.record_frame(cost))
+            .last()
+    }
+
+    #[test]
+    fn keeps_target_if_reachable() {
+        let mut controller = QualityController::new(NonZero::new(60).unwrap());
+
+        assert_eq!(
+            record_frames(&mut controller, Duration::from_millis(5)),
+            None
+        );
+        assert_eq!(controller.fps_cap(), 60);
+    }
+
+    #[test]
+    fn lowers_and_restores_fps_cap() {
+        let mut controller = QualityController::new(NonZero::new(60).unwrap());
+
+        assert_eq!(
+            record_frames(&mut controller, Duration::from_millis(25)),
+            Some(30)
+        );
+        // 25ms fit into the budget of 30 fps
+        assert_eq!(
+            record_frames(&mut controller, Duration::from_millis(25)),
+            None
+        );
+
+        // 15ms would fit into the budget of 60 fps but there's not enough headroom
+        assert_eq!(
+            record_frames(&mut controller, Duration::from_millis(15)),
+            None
+        );
+
+        assert_eq!(
+            record_frames(&mut controller, Duration::from_millis(5)),
+            Some(60)
+        );
+    }
+
+    #[test]
+    fn fps_cap_is_bounded() {
+        let mut controller = QualityController::new(NonZero::new(60).unwrap());
+
+        for _ in 0..10 {
+            record_frames(&mut controller, Duration::from_secs(1));
+        }
+
+        assert_eq!(controller.fps_cap(), 60 / MAX_DIVISOR);
+    }
+}
@@ -1,25 +1,37 @@
-use std::{borrow::Cow, fs::File, io::Read, path::PathBuf};
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
 
 use ariadne::{Color, Fmt};
 use tracing::{debug, warn};
-use wgpu::{
-    naga::{
-        front::{glsl, wgsl},
-        ShaderStage,
-    },
-    ShaderSource, SurfaceError,
-};
+use wgpu::{ShaderSource, SurfaceError};
 use winit::{
-    application::ApplicationHandler, event::WindowEvent, event_loop::ActiveEventLoop,
+    application::ApplicationHandler,
+    event::{StartCause, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoopProxy},
     window::WindowAttributes,
 };
 
 use crate::{
-    frontend::ShaderLanguage,
+    adapter::AdapterConfig,
+    frontend::{ParseError, ShaderLanguage},
+    input::{InputEvent, InputRecorder, InputReplay},
+    project::{Project, Uniforms},
+    quality::QualityController,
+    snapshot::Snapshot,
+    source::FragmentSource,
     states::{window_state::WindowState, RenderState},
+    watcher::{WatchedFile, WatcherEvent},
     UserEvent,
 };
 
+// how many pixels of a (touchpad) scroll count as one scrolled line
+#[cfg(feature = "mouse")]
+const PIXELS_PER_SCROLL_LINE: f32 = 20.;
+
 #[derive(thiserror::Error, Debug)]
 enum RenderError {
     #[error(transparent)]
@@ -28,11 +40,23 @@ enum RenderError {
     #[error(transparent)]
     IO(#[from] std::io::Error),
 
-    #[error("{0}")]
-    WgslParsing(String),
+    #[error(transparent)]
+    Parsing(#[from] ParseError),
+}
 
-    #[error("{0}")]
-    GlslParsing(String),
+/// The reasons why the window might not be visible.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Visibility {
+    occluded: bool,
+    minimized: bool,
+    // hidden by the user (for example with the `hide` command)
+    hidden: bool,
+}
+
+impl Visibility {
+    fn is_visible(&self) -> bool {
+        !(self.occluded || self.minimized || self.hidden)
+    }
 }
 
 pub struct Renderer<'a> {
@@ -40,58 +64,721 @@ pub struct Renderer<'a> {
     display_error: bool,
 
     shader_lang: ShaderLanguage,
+    adapter_config: AdapterConfig,
+
+    #[cfg(feature = "timeline")]
+    timeline: Option<shady::timeline::Timeline>,
+    #[cfg(feature = "timeline")]
+    timeline_path: Option<PathBuf>,
+    #[cfg(feature = "mapping")]
+    mapping: Option<crate::mapping::Mapping>,
+    #[cfg(feature = "mapping")]
+    mapping_path: Option<PathBuf>,
+    #[cfg(feature = "channels")]
+    channels: [Option<crate::channels::ChannelImage>; shady::AMOUNT_CHANNELS],
+    #[cfg(feature = "channels")]
+    channel_sources: [Option<crate::channels::ChannelSource>; shady::AMOUNT_CHANNELS],
+    // the path of the project file and its settings
+    project: Option<(PathBuf, Project)>,
 
-    fragment_path: PathBuf,
+    fragment_source: FragmentSource,
+    // the code which has been read from `fragment_source` the last time
+    fragment_code: String,
+    watcher: mpsc::Sender<WatcherEvent>,
+    proxy: EventLoopProxy<UserEvent>,
+
+    // increased with each new shader, so that the pipelines of outdated shaders are ignored
+    pipeline_generation: u64,
+
+    // keep running if the window gets closed
+    daemon: bool,
+    paused: bool,
+    // nothing is rendered while the window isn't visible to save CPU and GPU time
+    visibility: Visibility,
+
+    // caps the frame rate if the shader is too heavy
+    quality: Option<QualityController>,
+    crossfade_duration: Duration,
+    audio_device: Option<String>,
+    color_space: shady::ColorSpace,
+    #[cfg(feature = "audio-texture")]
+    audio_representation: shady::AudioRepresentation,
+    window_attributes: WindowAttributes,
+    initial_uniforms: Uniforms,
+    // the snapshot which is shown until the rendering is resumed
+    snapshot: Option<Snapshot>,
+
+    input_recorder: Option<InputRecorder>,
+    input_replay: Option<InputReplay>,
 }
 
 impl<'a> Renderer<'a> {
-    pub fn new(fragment_path: PathBuf, shader_lang: ShaderLanguage) -> anyhow::Result<Self> {
+    pub fn new(
+        fragment_source: FragmentSource,
+        shader_lang: ShaderLanguage,
+        adapter_config: AdapterConfig,
+        watcher: mpsc::Sender<WatcherEvent>,
+        proxy: EventLoopProxy<UserEvent>,
+    ) -> anyhow::Result<Self> {
         let mut renderer = Self {
             state: None,
             display_error: true,
-            fragment_path,
+            fragment_source,
+            fragment_code: String::new(),
             shader_lang,
+            adapter_config,
+            #[cfg(feature = "timeline")]
+            timeline: None,
+            #[cfg(feature = "timeline")]
+            timeline_path: None,
+            #[cfg(feature = "mapping")]
+            mapping: None,
+            #[cfg(feature = "mapping")]
+            mapping_path: None,
+            #[cfg(feature = "channels")]
+            channels: Default::default(),
+            #[cfg(feature = "channels")]
+            channel_sources: Default::default(),
+            project: None,
+            watcher,
+            proxy,
+            pipeline_generation: 0,
+            daemon: false,
+            paused: false,
+            visibility: Visibility::default(),
+            quality: None,
+            crossfade_duration: Duration::ZERO,
+            audio_device: None,
+            color_space: shady::ColorSpace::default(),
+            #[cfg(feature = "audio-texture")]
+            audio_representation: shady::AudioRepresentation::default(),
+            window_attributes: WindowAttributes::default(),
+            initial_uniforms: Uniforms::default(),
+            snapshot: None,
+            input_recorder: None,
+            input_replay: None,
         };
 
         renderer.refresh_fragment_code()?;
         Ok(renderer)
     }
 
-    fn refresh_fragment_code(&mut self) -> Result<(), RenderError> {
-        self.display_error = true;
+    /// Animates `iTimeline` with the timeline of the given file and reloads it when the file changes.
+    #[cfg(feature = "timeline")]
+    pub fn load_timeline(&mut self, path: PathBuf) -> Result<(), crate::Error> {
+        self.set_timeline(crate::read_timeline(&path)?);
+
+        let _ = self.watcher.send(WatcherEvent::SetPath(
+            WatchedFile::Timeline,
+            Some(path.clone()),
+        ));
+        self.timeline_path = Some(path);
+        Ok(())
+    }
+
+    #[cfg(feature = "timeline")]
+    fn set_timeline(&mut self, timeline: shady::timeline::Timeline) {
+        if let Some(state) = &mut self.state {
+            state.set_timeline(timeline.clone());
+        }
+
+        self.timeline = Some(timeline);
+    }
+
+    /// Computes the values of `iParams` with the mapping of the given file and reloads it when the file changes.
+    #[cfg(feature = "mapping")]
+    pub fn load_mapping(&mut self, path: PathBuf) -> Result<(), crate::Error> {
+        self.set_mapping(crate::read_mapping(&path)?);
+
+        let _ = self.watcher.send(WatcherEvent::SetPath(
+            WatchedFile::Mapping,
+            Some(path.clone()),
+        ));
+        self.mapping_path = Some(path);
+        Ok(())
+    }
+
+    #[cfg(feature = "mapping")]
+    fn set_mapping(&mut self, mapping: crate::mapping::Mapping) {
+        for (idx, name) in mapping.names().iter().enumerate() {
+            debug!("iParams[{}]: {}", idx, name);
+        }
+
+        if let Some(state) = &mut self.state {
+            state.set_mapping(mapping.clone());
+        }
+
+        // the accessors of the uniforms are part of the fragment code
+        let names_changed =
+            self.mapping.as_ref().map(|mapping| mapping.names()) != Some(mapping.names());
+        self.mapping = Some(mapping);
+
+        if names_changed && self.state.is_some() {
+            if let Err(err) = self.compile_fragment_code() {
+                eprintln!("Couldn't compile the fragment code: {}", err);
+            }
+        }
+    }
+
+    /// Reloads the settings of the project when the project file changes.
+    pub fn set_project(&mut self, path: PathBuf, project: Project) {
+        let _ = self.watcher.send(WatcherEvent::SetPath(
+            WatchedFile::Project,
+            Some(path.clone()),
+        ));
+        self.project = Some((path, project));
+    }
+
+    /// Lowers the frame rate automatically if the shader can't reach the target frame rate of `quality`.
+    pub fn set_quality_controller(&mut self, quality: QualityController) {
+        self.quality = Some(quality);
+    }
+
+    /// Fades from the previous shader to the new one over the given duration after a reload.
+    pub fn set_crossfade_duration(&mut self, duration: Duration) {
+        if let Some(state) = &mut self.state {
+            state.set_crossfade_duration(duration);
+        }
+
+        self.crossfade_duration = duration;
+    }
+
+    /// Visualizes the audio of the output device with the given name instead of the default one.
+    ///
+    /// Takes effect when the window is created.
+    pub fn set_audio_device(&mut self, name: Option<String>) {
+        self.audio_device = name;
+    }
+
+    /// The color space of the output of the shaders.
+    ///
+    /// Takes effect when the window is created.
+    pub fn set_color_space(&mut self, color_space: shady::ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    /// Whether the bars are uploaded into `iAudio` or `iAudioTexture`.
+    ///
+    /// Takes effect when the window is created.
+    #[cfg(feature = "audio-texture")]
+    pub fn set_audio_representation(&mut self, representation: shady::AudioRepresentation) {
+        self.audio_representation = representation;
+    }
+
+    /// The attributes of the window when it's created.
+    pub fn set_window_attributes(&mut self, attributes: WindowAttributes) {
+        self.window_attributes = attributes;
+    }
+
+    /// Sets the uniforms to the given values once the window has been created.
+    pub fn set_initial_uniforms(&mut self, uniforms: Uniforms) {
+        self.initial_uniforms = uniforms;
+    }
+
+    /// Shows the uniforms of the snapshot once the window has been created.
+    ///
+    /// The rendering is paused and `iAudio` and `iParams` keep their values until it's resumed.
+    pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+        self.snapshot = Some(snapshot);
+        self.set_paused(true);
+    }
+
+    /// Writes the input and the playback time of each frame into the given recorder.
+    pub fn set_input_recorder(&mut self, recorder: InputRecorder) {
+        self.input_recorder = Some(recorder);
+    }
+
+    /// Replays the recorded input and playback time frame by frame instead of using the live input.
+    pub fn set_input_replay(&mut self, replay: InputReplay) {
+        self.input_replay = Some(replay);
+    }
+
+    /// Sets the texture of `iChannel<channel>` and reloads it when its image file changes.
+    #[cfg(feature = "channels")]
+    pub fn load_channel(
+        &mut self,
+        channel: usize,
+        source: crate::channels::ChannelSource,
+    ) -> Result<(), image::ImageError> {
+        self.set_channel(channel, source.load()?);
+
+        let path = match &source {
+            crate::channels::ChannelSource::Image(path) => Some(path.clone()),
+            _ => None,
+        };
+        let _ = self
+            .watcher
+            .send(WatcherEvent::SetPath(WatchedFile::Channel(channel), path));
+        self.channel_sources[channel] = Some(source);
+        Ok(())
+    }
+
+    #[cfg(feature = "channels")]
+    fn set_channel(&mut self, channel: usize, image: crate::channels::ChannelImage) {
+        if let Some(state) = &mut self.state {
+            state.set_channel(channel, &image);
+        }
 
-        debug!(
-            "Trying to read from: {}",
-            self.fragment_path.to_string_lossy()
+        self.channels[channel] = Some(image);
+    }
+
+    /// If enabled, closing the window only hides it.
+    // `unused`: Only used on unix.
+    #[allow(unused)]
+    pub fn set_daemon(&mut self, daemon: bool) {
+        self.daemon = daemon;
+    }
+
+    /// Renders the given shader file from now on.
+    // `unused`: Only used on unix.
+    #[allow(unused)]
+    pub fn load_shader(&mut self, path: PathBuf) -> Result<(), String> {
+        if !path.is_file() {
+            return Err(format!("{} isn't a file.", path.to_string_lossy()));
+        }
+
+        let shader_lang = ShaderLanguage::try_from(path.as_path())?;
+        self.replace_shader(path, shader_lang)
+    }
+
+    fn replace_shader(&mut self, path: PathBuf, shader_lang: ShaderLanguage) -> Result<(), String> {
+        let prev_source = std::mem::replace(
+            &mut self.fragment_source,
+            FragmentSource::File(path.clone()),
         );
-        let mut file = File::open(&self.fragment_path)?;
-        let mut fragment_code = String::new();
-        file.read_to_string(&mut fragment_code)?;
+        let prev_lang = std::mem::replace(&mut self.shader_lang, shader_lang);
+        let prev_code = self.fragment_code.clone();
 
-        debug!("Fragment code: {}", fragment_code);
+        if let Err(err) = self.refresh_fragment_code() {
+            // keep the current shader running
+            self.fragment_source = prev_source;
+            self.shader_lang = prev_lang;
+            self.fragment_code = prev_code;
+            return Err(err.to_string());
+        }
+
+        let _ = self
+            .watcher
+            .send(WatcherEvent::SetPath(WatchedFile::Shader, Some(path)));
+        Ok(())
+    }
+
+    /// Reloads what has been changed by the watched file.
+    fn file_changed(&mut self, file: WatchedFile) {
+        match file {
+            WatchedFile::Shader => {
+                if let Err(err) = self.refresh_fragment_code() {
+                    eprintln!("Couldn't refresh fragment code: {}", err);
+                }
+            }
+            WatchedFile::Project => self.reload_project(),
+            #[cfg(feature = "timeline")]
+            WatchedFile::Timeline => {
+                let Some(path) = &self.timeline_path else {
+                    return;
+                };
+
+                match crate::read_timeline(path) {
+                    Ok(timeline) => self.set_timeline(timeline),
+                    Err(err) => eprintln!("Couldn't reload the timeline: {}", err),
+                }
+            }
+            #[cfg(feature = "mapping")]
+            WatchedFile::Mapping => {
+                let Some(path) = &self.mapping_path else {
+                    return;
+                };
+
+                match crate::read_mapping(path) {
+                    Ok(mapping) => self.set_mapping(mapping),
+                    Err(err) => eprintln!("Couldn't reload the mapping: {}", err),
+                }
+            }
+            #[cfg(feature = "channels")]
+            WatchedFile::Channel(channel) => {
+                let Some(source) = &self.channel_sources[channel] else {
+                    return;
+                };
+
+                match source.load() {
+                    Ok(image) => self.set_channel(channel, image),
+                    Err(err) => eprintln!(
+                        "Couldn't reload the texture of iChannel{}: {}",
+                        channel, err
+                    ),
+                }
+            }
+            #[cfg(not(all(feature = "timeline", feature = "mapping", feature = "channels")))]
+            _ => {}
+        }
+
+        // show the change even if the rendering is paused
+        if let Some(state) = &self.state {
+            state.window().request_redraw();
+        }
+    }
+
+    /// Applies the settings of the project which have changed.
+    ///
+    /// Settings which have been overwritten on the command line are kept.
+    fn reload_project(&mut self) {
+        let Some((path, project)) = self.project.take() else {
+            return;
+        };
+
+        let new = match Project::load(&path) {
+            Ok(new) => new,
+            Err(err) => {
+                // keep the previous settings until the project file is valid again
+                eprintln!("Couldn't reload the project: {}", err);
+                self.project = Some((path, project));
+                return;
+            }
+        };
+
+        if (&project.shader, project.lang) != (&new.shader, new.lang)
+            && self.fragment_source.path() == Some(project.shader.as_path())
+        {
+            let shader_lang = new
+                .lang
+                .map(Ok)
+                .unwrap_or_else(|| ShaderLanguage::try_from(new.shader.as_path()));
+
+            if let Err(err) =
+                shader_lang.and_then(|lang| self.replace_shader(new.shader.clone(), lang))
+            {
+                eprintln!("Couldn't load the shader of the project: {}", err);
+            }
+        }
+
+        #[cfg(feature = "timeline")]
+        if let Some(timeline) = &new.timeline {
+            if project.timeline != new.timeline && self.timeline_path == project.timeline {
+                if let Err(err) = self.load_timeline(timeline.clone()) {
+                    eprintln!("Couldn't load the timeline of the project: {}", err);
+                }
+            }
+        }
+
+        #[cfg(feature = "mapping")]
+        if let Some(mapping) = &new.mapping {
+            if project.mapping != new.mapping && self.mapping_path == project.mapping {
+                if let Err(err) = self.load_mapping(mapping.clone()) {
+                    eprintln!("Couldn't load the mapping of the project: {}", err);
+                }
+            }
+        }
 
+        #[cfg(feature = "channels")]
+        for (channel, (prev_source, source)) in project
+            .channel_sources()
+            .into_iter()
+            .zip(new.channel_sources())
+            .enumerate()
+        {
+            let Some(source) = source else { continue };
+
+            if prev_source.as_ref() != Some(&source) && self.channel_sources[channel] == prev_source
+            {
+                if let Err(err) = self.load_channel(channel, source) {
+                    eprintln!("Couldn't load the texture of iChannel{}: {}", channel, err);
+                }
+            }
+        }
+
+        let crossfade = |project: &Project| crossfade_duration(project.crossfade.unwrap_or(0.));
+        if crossfade(&project) != crossfade(&new) && self.crossfade_duration == crossfade(&project)
+        {
+            self.set_crossfade_duration(crossfade(&new));
+        }
+
+        if let (Some(state), Some(title)) = (&self.state, &new.window.title) {
+            state.window().set_title(title);
+        }
+
+        if project.needs_restart(&new) {
+            println!(
+                "[{}]: The window size, the audio device, the target fps and the uniforms of the project \
+                are applied after a restart.",
+                "NOTE".fg(Color::Cyan)
+            );
+        }
+
+        self.project = Some((path, new));
+    }
+
+    /// Reads the fragment code again.
+    ///
+    /// Stdin is read in the background since it blocks until the writer closes it.
+    fn reload(&mut self) {
+        match &self.fragment_source {
+            FragmentSource::File(_) => {
+                if let Err(err) = self.refresh_fragment_code() {
+                    eprintln!("Couldn't refresh fragment code: {}", err);
+                }
+            }
+            FragmentSource::Stdin => {
+                let proxy = self.proxy.clone();
+                std::thread::spawn(move || match FragmentSource::Stdin.read() {
+                    // there's no writer (anymore)
+                    Ok(code) if code.trim().is_empty() => {
+                        debug!("Stdin is empty, keeping the current shader")
+                    }
+                    Ok(code) => {
+                        let _ = proxy.send_event(UserEvent::FragmentCode(code));
+                    }
+                    Err(err) => eprintln!("Couldn't read the fragment code from stdin: {}", err),
+                });
+            }
+        }
+    }
+
+    /// Stops (`true`) or continues (`false`) rendering new frames.
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused == paused {
+            return;
+        }
+        self.paused = paused;
+
+        if !paused {
+            self.snapshot = None;
+        }
+
+        let renders_continuously = self.renders_continuously();
         if let Some(state) = &mut self.state {
-            let module = match self.shader_lang {
-                ShaderLanguage::Wgsl => {
-                    debug!("Parsing with wgsl parser");
-                    let mut frontend = wgsl::Frontend::new();
+            #[cfg(feature = "time")]
+            state.shady.set_time_paused(paused);
+            if !paused {
+                state.unfreeze();
+            }
+
+            if renders_continuously {
+                state.window().request_redraw();
+            }
+        }
+    }
+
+    /// Returns `true` if a new frame should be rendered as soon as the previous one is done.
+    fn renders_continuously(&self) -> bool {
+        !self.paused && self.visibility.is_visible()
+    }
+
+    /// Suspends `shady` while the window isn't visible and continues once it's visible again.
+    fn update_visibility(&mut self, update: impl FnOnce(&mut Visibility)) {
+        let was_visible = self.visibility.is_visible();
+        update(&mut self.visibility);
+
+        let is_visible = self.visibility.is_visible();
+        if was_visible == is_visible {
+            return;
+        }
+
+        debug!("Window visible: {}", is_visible);
+        let renders_continuously = self.renders_continuously();
+        if let Some(state) = &mut self.state {
+            state.shady.set_active(is_visible);
+
+            if renders_continuously {
+                state.window().request_redraw();
+            }
+        }
+    }
+
+    fn close(&mut self, event_loop: &ActiveEventLoop) {
+        match &self.state {
+            Some(state) if self.daemon => {
+                state.window().set_visible(false);
+                self.update_visibility(|visibility| visibility.hidden = true);
+            }
+            _ => event_loop.exit(),
+        }
+    }
+
+    fn record_input(&mut self, event: &InputEvent) {
+        if let Some(recorder) = &mut self.input_recorder {
+            if let Err(err) = recorder.record(event) {
+                warn!("Couldn't record the input, stopping the recording: {}", err);
+                self.input_recorder = None;
+            }
+        }
+    }
+
+    /// Handles the input of the user unless the input is replayed.
+    fn handle_live_input(&mut self, event_loop: &ActiveEventLoop, input: InputEvent) {
+        let is_quit = input == InputEvent::Key { text: "q".into() };
+        if self.input_replay.is_some() && !is_quit {
+            return;
+        }
+
+        self.record_input(&input);
+        self.apply_input(event_loop, input);
+    }
 
-                    frontend.parse(&fragment_code).map_err(|err| {
-                        RenderError::WgslParsing(err.emit_to_string(&fragment_code))
-                    })?
+    fn apply_input(&mut self, event_loop: &ActiveEventLoop, input: InputEvent) {
+        let Some(state) = &mut self.state else { return };
+
+        match input {
+            InputEvent::Frame { .. } => unreachable!("Frames aren't input"),
+            #[cfg(feature = "mouse")]
+            InputEvent::CursorMoved { x, y } => state.shady.set_mouse_pos(x, y),
+            #[cfg(feature = "mouse")]
+            InputEvent::MouseButton { pressed } => state.shady.set_mouse_state(if pressed {
+                shady::MouseState::Pressed
+            } else {
+                shady::MouseState::Released
+            }),
+            #[cfg(feature = "mouse")]
+            InputEvent::Scroll { x, y } => state.shady.scroll_mouse(x, y),
+            InputEvent::Key { text } => match text.as_str() {
+                "q" => self.close(event_loop),
+                "r" => self.reload(),
+                "s" => self.save_snapshot(),
+                _ => {}
+            },
+            #[cfg(not(feature = "mouse"))]
+            _ => {}
+        }
+    }
+
+    /// Writes the current values of the uniforms into `shady-<unix time>.snapshot.json` in the current directory.
+    fn save_snapshot(&self) {
+        let Some(state) = &self.state else { return };
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = PathBuf::from(format!("shady-{}.snapshot.json", timestamp));
+
+        match Snapshot::take(&state.shady).save(&path) {
+            Ok(()) => println!(
+                "[{}]: Saved the uniforms into {}",
+                "NOTE".fg(Color::Cyan),
+                path.display()
+            ),
+            Err(err) => eprintln!("Couldn't save the snapshot: {}", err),
+        }
+    }
+
+    /// Applies the replayed input of the next frame and freezes the playback time at the recorded time.
+    /// Returns to the live input after the last frame.
+    fn replay_next_frame(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(replay) = &mut self.input_replay else {
+            return;
+        };
+
+        match replay.next_frame() {
+            Some((inputs, time)) => {
+                for input in inputs {
+                    self.apply_input(event_loop, input);
+                }
+
+                #[cfg(feature = "time")]
+                if let Some(state) = &mut self.state {
+                    state.shady.set_time_paused(true);
+                    state.shady.set_time(time);
                 }
-                ShaderLanguage::Glsl => {
-                    debug!("Parsing with glsl parser");
-                    let mut frontend = glsl::Frontend::default();
-                    let options = glsl::Options::from(ShaderStage::Fragment);
+                // `unused`: Only needed if the `time` feature is enabled.
+                #[cfg(not(feature = "time"))]
+                let _ = time;
+            }
+            None => {
+                println!(
+                    "[{}]: Replayed all recorded frames, continuing with the live input.",
+                    "NOTE".fg(Color::Cyan)
+                );
+                self.input_replay = None;
+
+                #[cfg(feature = "time")]
+                if let Some(state) = &mut self.state {
+                    state.shady.set_time_paused(self.paused);
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn handle_command(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        command: crate::ipc::Command,
+    ) -> Result<(), String> {
+        use crate::ipc::Command;
+
+        match command {
+            Command::Load(path) => return self.load_shader(path),
+            Command::Pause => self.set_paused(true),
+            Command::Resume => self.set_paused(false),
+            Command::TogglePause => self.set_paused(!self.paused),
+            Command::Set(uniform) => {
+                let Some(state) = &mut self.state else {
+                    return Err("The window isn't ready yet.".into());
+                };
+
+                // the new value should be visible even if the rendering is paused
+                state.window().request_redraw();
+
+                match uniform {
+                    #[cfg(feature = "time")]
+                    crate::ipc::Uniform::Time(time) => state.shady.set_time(time),
+                    #[cfg(feature = "mouse")]
+                    crate::ipc::Uniform::Mouse(x, y) => state.shady.set_mouse_pos(x, y),
+                }
+            }
+            Command::Show | Command::Hide => {
+                if let Some(state) = &self.state {
+                    state.window().set_visible(command == Command::Show);
+                    self.update_visibility(|visibility| {
+                        visibility.hidden = command == Command::Hide
+                    });
+                }
+            }
+            Command::Quit => event_loop.exit(),
+        }
+
+        Ok(())
+    }
+
+    /// Reads the fragment code from its source and compiles it.
+    fn refresh_fragment_code(&mut self) -> Result<(), RenderError> {
+        debug!("Trying to read from: {}", self.fragment_source);
+        self.fragment_code = self.fragment_source.read()?;
+
+        self.compile_fragment_code()
+    }
+
+    fn compile_fragment_code(&mut self) -> Result<(), RenderError> {
+        self.display_error = true;
+
+        #[cfg(feature = "mapping")]
+        let fragment_code = match &self.mapping {
+            Some(mapping) => {
+                Cow::Owned(mapping.add_accessors(&self.fragment_code, self.shader_lang))
+            }
+            None => Cow::Borrowed(self.fragment_code.as_str()),
+        };
+        #[cfg(not(feature = "mapping"))]
+        let fragment_code = Cow::Borrowed(self.fragment_code.as_str());
+        debug!("Fragment code: {}", fragment_code);
 
-                    frontend.parse(&options, &fragment_code).map_err(|err| {
-                        RenderError::GlslParsing(err.emit_to_string(&fragment_code))
-                    })?
+        if let Some(state) = &mut self.state {
+            let module = match self.shader_lang.parse(&fragment_code) {
+                Ok(module) => module,
+                Err(err) => {
+                    // there might be no terminal which shows the error
+                    state.set_error(Some(&err.to_string()));
+                    state.window().request_redraw();
+                    return Err(err.into());
                 }
             };
 
-            state.update_pipeline(ShaderSource::Naga(Cow::Owned(module)));
+            self.pipeline_generation += 1;
+            let generation = self.pipeline_generation;
+            let proxy = self.proxy.clone();
+
+            state.compile_pipeline(ShaderSource::Naga(Cow::Owned(module)), move |pipelines| {
+                let _ = proxy.send_event(UserEvent::PipelineReady(pipelines, generation));
+            });
         } else {
             debug!("State not initialized");
         }
@@ -100,14 +787,76 @@ impl<'a> Renderer<'a> {
     }
 }
 
+/// Converts the crossfade of the arguments or of a project into a duration.
+///
+/// The crossfade of a project isn't validated, so negative and non-finite values switch immediately.
+pub fn crossfade_duration(secs: f32) -> Duration {
+    Duration::try_from_secs_f32(secs).unwrap_or_default()
+}
+
 impl<'a> ApplicationHandler<UserEvent> for Renderer<'a> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let window = event_loop
-            .create_window(WindowAttributes::default())
+            .create_window(self.window_attributes.clone())
             .unwrap();
 
-        self.state = Some(WindowState::new(window, None));
-        self.refresh_fragment_code().unwrap();
+        // `unused_mut`: Only needed if the `timeline` or `mapping` feature is enabled.
+        #[allow(unused_mut)]
+        let mut state = WindowState::new(
+            window,
+            None,
+            &self.adapter_config,
+            self.audio_device.as_deref(),
+            self.color_space,
+        );
+
+        #[cfg(feature = "audio-texture")]
+        state.set_audio_representation(self.audio_representation);
+        #[cfg(feature = "timeline")]
+        if let Some(timeline) = &self.timeline {
+            state.set_timeline(timeline.clone());
+        }
+        #[cfg(feature = "mapping")]
+        if let Some(mapping) = &self.mapping {
+            state.set_mapping(mapping.clone());
+        }
+
+        #[cfg(feature = "time")]
+        if let Some(time) = self.initial_uniforms.time {
+            state.shady.set_time(time);
+        }
+        #[cfg(feature = "mouse")]
+        if let Some([x, y]) = self.initial_uniforms.mouse {
+            state.shady.set_mouse_pos(x, y);
+        }
+        if let Some(snapshot) = &self.snapshot {
+            state.apply_snapshot(snapshot);
+        }
+
+        #[cfg(feature = "time")]
+        state.shady.set_time_paused(self.paused);
+        state.set_crossfade_duration(self.crossfade_duration);
+
+        #[cfg(feature = "channels")]
+        for (channel, image) in self.channels.iter().enumerate() {
+            if let Some(image) = image {
+                state.set_channel(channel, image);
+            }
+        }
+
+        // the CPU time of a frame includes waiting for vsync, so it can't tell if the shader is too heavy
+        if self.quality.is_some() && !state.enable_gpu_timing() {
+            warn!("GPU timing isn't supported by the adapter, the frame rate won't be lowered automatically");
+            self.quality = None;
+        }
+
+        self.state = Some(state);
+        self.visibility = Visibility::default();
+        // stdin can't be read twice
+        if let Err(err) = self.compile_fragment_code() {
+            // keep the window open to show the error in it
+            eprintln!("Couldn't compile the fragment code: {}", err);
+        }
     }
 
     fn window_event(
@@ -116,16 +865,49 @@ impl<'a> ApplicationHandler<UserEvent> for Renderer<'a> {
         _window_id: winit::window::WindowId,
         event: WindowEvent,
     ) {
+        if event == WindowEvent::RedrawRequested {
+            self.replay_next_frame(event_loop);
+
+            #[cfg(feature = "time")]
+            let time = self.state.as_ref().map(|state| state.shady.time());
+            #[cfg(not(feature = "time"))]
+            let time = Some(0.);
+
+            if let Some(time) = time {
+                self.record_input(&InputEvent::Frame { time });
+            }
+        }
+
+        let renders_continuously = self.renders_continuously();
         let Some(state) = &mut self.state else { return };
         let window = state.window();
 
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::CloseRequested => self.close(event_loop),
             WindowEvent::RedrawRequested => {
-                window.request_redraw();
+                // only render on demand while paused or hidden
+                if renders_continuously {
+                    match &self.quality {
+                        // the next frame is requested in `new_events`
+                        Some(quality) => event_loop.set_control_flow(ControlFlow::WaitUntil(
+                            Instant::now() + quality.frame_interval(),
+                        )),
+                        None => window.request_redraw(),
+                    }
+                }
+
                 state.prepare_next_frame();
+                let result = state.render();
 
-                match state.render() {
+                // the timing of the first frames isn't available yet
+                let cost = state.shady.frame_stats().gpu_render_pass;
+                if let (Some(quality), Some(cost)) = (&mut self.quality, cost) {
+                    if let Some(fps_cap) = quality.record_frame(cost) {
+                        debug!("Changed the frame rate cap to {} fps", fps_cap);
+                    }
+                }
+
+                match result {
                     Ok(_) => {
                         if !self.display_error {
                             println!("[{}] Everything clear", "OK".fg(Color::Green));
@@ -141,41 +923,108 @@ impl<'a> ApplicationHandler<UserEvent> for Renderer<'a> {
                     Err(err) => warn!("{}", err),
                 }
             }
-            WindowEvent::Resized(new_size) => state.resize(new_size),
-            #[cfg(feature = "mouse")]
+            WindowEvent::Resized(new_size) => {
+                state.resize(new_size);
+                // some platforms resize the window to zero instead of occluding it when it's minimized
+                let minimized = new_size.width == 0 || new_size.height == 0;
+                self.update_visibility(|visibility| visibility.minimized = minimized);
+            }
+            WindowEvent::Occluded(occluded) => {
+                self.update_visibility(|visibility| visibility.occluded = occluded)
+            }
             WindowEvent::MouseInput {
                 state: mouse_state, ..
-            } => {
-                let shady = &mut state.shady;
-                match mouse_state {
-                    winit::event::ElementState::Pressed => {
-                        shady.set_mouse_state(shady::MouseState::Pressed)
-                    }
-                    winit::event::ElementState::Released => {
-                        shady.set_mouse_state(shady::MouseState::Released)
-                    }
-                }
+            } => self.handle_live_input(
+                event_loop,
+                InputEvent::MouseButton {
+                    pressed: mouse_state.is_pressed(),
+                },
+            ),
+            WindowEvent::CursorMoved { position: pos, .. } => self.handle_live_input(
+                event_loop,
+                InputEvent::CursorMoved {
+                    x: pos.x as f32,
+                    y: pos.y as f32,
+                },
+            ),
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (x, y) = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(x, y) => (x, y),
+                    winit::event::MouseScrollDelta::PixelDelta(pos) => (
+                        pos.x as f32 / PIXELS_PER_SCROLL_LINE,
+                        pos.y as f32 / PIXELS_PER_SCROLL_LINE,
+                    ),
+                };
+                self.handle_live_input(event_loop, InputEvent::Scroll { x, y });
             }
-            #[cfg(feature = "mouse")]
-            WindowEvent::CursorMoved { position: pos, .. } => {
-                state.shady.set_mouse_pos(pos.x as f32, pos.y as f32)
-            }
-            WindowEvent::KeyboardInput { event, .. }
-                if event.logical_key.to_text() == Some("q") =>
-            {
-                event_loop.exit();
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                if let Some(text) = event.logical_key.to_text() {
+                    let text = text.to_string();
+                    self.handle_live_input(event_loop, InputEvent::Key { text });
+                }
             }
             _ => (),
         }
     }
 
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        if let StartCause::ResumeTimeReached { .. } = cause {
+            event_loop.set_control_flow(ControlFlow::Wait);
+
+            if let Some(state) = &self.state {
+                if self.renders_continuously() {
+                    state.window().request_redraw();
+                }
+            }
+        }
+    }
+
+    // `unused`: `event_loop` is only used on unix.
+    #[allow(unused)]
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         match event {
-            UserEvent::UpdatePath => {
-                if let Err(err) = self.refresh_fragment_code() {
-                    eprintln!("Couldn't refresh fragment code: {}", err);
+            UserEvent::FileChanged(file) => self.file_changed(file),
+            UserEvent::Reload => self.reload(),
+            UserEvent::FragmentCode(code) => {
+                self.fragment_code = code;
+                if let Err(err) = self.compile_fragment_code() {
+                    eprintln!("Couldn't compile the fragment code: {}", err);
                 }
             }
+            UserEvent::PipelineReady(pipelines, generation) => {
+                let Some(state) = &mut self.state else { return };
+
+                // a newer shader is already compiling
+                if generation != self.pipeline_generation {
+                    debug!("Dropping outdated pipeline {}", generation);
+                    return;
+                }
+
+                // a paused crossfade would be stuck at the previous shader
+                state.set_pipelines(pipelines, !self.paused);
+                state.set_error(None);
+                // show the new shader even if the rendering is paused
+                state.window().request_redraw();
+            }
+            #[cfg(feature = "track")]
+            UserEvent::Track(status) => {
+                if let Some(state) = &mut self.state {
+                    state
+                        .shady
+                        .set_track(status.position, status.duration, status.playing);
+                }
+            }
+            #[cfg(feature = "gamepad")]
+            UserEvent::Gamepad(gamepad) => {
+                if let Some(state) = &mut self.state {
+                    gamepad.apply(&mut state.shady);
+                }
+            }
+            #[cfg(unix)]
+            UserEvent::Ipc(command, response) => {
+                let result = self.handle_command(event_loop, command);
+                let _ = response.send(result);
+            }
         }
     }
 }
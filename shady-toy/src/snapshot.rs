@@ -0,0 +1,117 @@
+//! Stores the values of the uniforms at a moment into a json file and restores them,
+//! so the same frame can be rendered again (for example for debugging or thumbnails).
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Invalid snapshot: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The values of the uniforms at a moment.
+///
+/// Missing values (for example because the uniform is disabled) are skipped when the snapshot is applied.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Snapshot {
+    /// `iTime` in seconds.
+    pub time: Option<f32>,
+
+    /// The position of `iMouse`.
+    pub mouse: Option<[f32; 2]>,
+
+    /// The values of `iParams`.
+    pub params: Option<Vec<f32>>,
+
+    /// The values of `iAudio`.
+    pub audio: Option<Vec<f32>>,
+}
+
+impl Snapshot {
+    /// Takes the current values of the uniforms of `shady`.
+    // `unused`: `shady` is only needed if one of the uniforms is enabled.
+    #[allow(unused)]
+    pub fn take(shady: &shady::Shady) -> Self {
+        #[cfg(feature = "time")]
+        let time = Some(shady.time());
+        #[cfg(not(feature = "time"))]
+        let time = None;
+
+        #[cfg(feature = "mouse")]
+        let mouse = Some(shady.mouse_pos().into());
+        #[cfg(not(feature = "mouse"))]
+        let mouse = None;
+
+        #[cfg(feature = "mapping")]
+        let params = Some(shady.params().to_vec());
+        #[cfg(not(feature = "mapping"))]
+        let params = None;
+
+        #[cfg(feature = "audio")]
+        let audio = Some(shady.audio_values().into_vec());
+        #[cfg(not(feature = "audio"))]
+        let audio = None;
+
+        Self {
+            time,
+            mouse,
+            params,
+            audio,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SnapshotError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let snapshot = Snapshot {
+            time: Some(12.5),
+            mouse: Some([100., 42.]),
+            params: Some(vec![0.25, 3.]),
+            audio: Some(vec![0., 0.5, 1.]),
+        };
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(Snapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn missing_values() {
+        let snapshot = Snapshot::from_json(r#"{ "time": 3.0 }"#).unwrap();
+
+        assert_eq!(
+            snapshot,
+            Snapshot {
+                time: Some(3.),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_values() {
+        assert!(Snapshot::from_json(r#"{ "frame": 3 }"#).is_err());
+    }
+}
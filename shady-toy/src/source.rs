@@ -0,0 +1,81 @@
+use std::{
+    fmt,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The argument which tells `shady-toy` to read the fragment code from stdin.
+pub const STDIN_ARG: &str = "-";
+
+/// Where the fragment code comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentSource {
+    File(PathBuf),
+
+    /// Everything up to the end of stdin.
+    Stdin,
+}
+
+impl FragmentSource {
+    /// Returns the path of the file or `None` if the code is read from stdin.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => Some(path),
+            Self::Stdin => None,
+        }
+    }
+
+    /// Reads the whole fragment code.
+    ///
+    /// Blocks until stdin is closed if the code is read from stdin.
+    pub fn read(&self) -> std::io::Result<String> {
+        let mut code = String::new();
+
+        match self {
+            Self::File(path) => {
+                std::fs::File::open(path)?.read_to_string(&mut code)?;
+            }
+            Self::Stdin => {
+                std::io::stdin().lock().read_to_string(&mut code)?;
+            }
+        }
+
+        Ok(code)
+    }
+}
+
+impl From<PathBuf> for FragmentSource {
+    fn from(path: PathBuf) -> Self {
+        if path.as_os_str() == STDIN_ARG {
+            Self::Stdin
+        } else {
+            Self::File(path)
+        }
+    }
+}
+
+impl fmt::Display for FragmentSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.to_string_lossy()),
+            Self::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dash_is_stdin() {
+        assert_eq!(
+            FragmentSource::from(PathBuf::from("-")),
+            FragmentSource::Stdin
+        );
+        assert_eq!(
+            FragmentSource::from(PathBuf::from("./-.wgsl")),
+            FragmentSource::File(PathBuf::from("./-.wgsl"))
+        );
+    }
+}
@@ -1,5 +1,3 @@
-use wgpu::ShaderSource;
-
 #[cfg(test)]
 mod texture_state;
 pub mod window_state;
@@ -8,6 +6,4 @@ pub trait RenderState<'a> {
     fn prepare_next_frame(&mut self);
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError>;
-
-    fn update_pipeline(&mut self, shader_source: ShaderSource<'a>);
 }
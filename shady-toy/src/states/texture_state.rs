@@ -1,12 +1,6 @@
 use image::{ImageBuffer, Rgba};
 use pollster::FutureExt;
-use shady::{
-    shady_audio::{
-        fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
-        SampleProcessor,
-    },
-    Shady, ShadyDescriptor, ShadyRenderPipeline,
-};
+use shady::{shady_audio::SampleProcessor, Shady, ShadyDescriptor, ShadyRenderPipeline};
 use wgpu::{
     Backends, Buffer, BufferView, Device, DeviceDescriptor, Extent3d, Instance, Queue,
     ShaderSource, Texture,
@@ -35,7 +29,7 @@ pub struct TextureState {
 }
 
 impl TextureState {
-    pub fn get_output(&self) -> ImageBuffer<Rgba<u8>, BufferView> {
+    pub fn get_output(&self) -> ImageBuffer<Rgba<u8>, BufferView<'_>> {
         let buffer_slice = self.output_buffer.slice(..);
 
         let (tx, rx) = std::sync::mpsc::channel();
@@ -57,7 +51,7 @@ impl TextureState {
         shader_source: Option<ShaderSource<'a>>,
     ) -> Self {
         assert!(
-            MIN_BYTES_WIDTH / OUTPUT_BUFFER_VALUE_SIZE >= 64,
+            texture_size.width >= MIN_BYTES_WIDTH / OUTPUT_BUFFER_VALUE_SIZE,
             "Width must be at least {}.",
             MIN_BYTES_WIDTH / OUTPUT_BUFFER_VALUE_SIZE
         );
@@ -111,13 +105,14 @@ impl TextureState {
         let pipeline = shader_source
             .map(|source| shady::create_render_pipeline(&device, source, &texture_format));
 
-        let sample_processor = SampleProcessor::new(
-            SystemAudioFetcher::new(&SystemAudioFetcherDescriptor::default()).unwrap(),
-        );
+        let sample_processor = shady::default_sample_processor();
         let shady = Shady::new(ShadyDescriptor {
             device: &device,
             sample_processor: &sample_processor,
-        });
+            audio_handle: None,
+            clock: None,
+        })
+        .expect("Create shady");
 
         Self {
             size: texture_size,
@@ -140,12 +135,12 @@ impl<'a> RenderState<'a> for TextureState {
         {
             self.sample_processor.process_next_samples();
             self.shady
-                .update_audio_buffer(&mut self.queue, &self.sample_processor);
+                .update_audio_buffer(&self.queue, &self.sample_processor);
         }
-        self.shady.update_frame_buffer(&mut self.queue);
-        self.shady.update_mouse_buffer(&mut self.queue);
-        self.shady.update_resolution_buffer(&mut self.queue);
-        self.shady.update_time_buffer(&mut self.queue);
+        self.shady.update_frame_buffer(&self.queue);
+        self.shady.update_mouse_buffer(&self.queue);
+        self.shady.update_resolution_buffer(&self.queue);
+        self.shady.update_time_buffer(&self.queue);
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -186,14 +181,6 @@ impl<'a> RenderState<'a> for TextureState {
 
         Ok(())
     }
-
-    fn update_pipeline(&mut self, shader_source: ShaderSource<'a>) {
-        self.pipeline = Some(shady::create_render_pipeline(
-            &self.device,
-            shader_source,
-            &self.texture.format(),
-        ));
-    }
 }
 
 #[test]
@@ -1,59 +1,106 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use pollster::FutureExt;
-use shady::{
-    shady_audio::{
-        fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
-        SampleProcessor,
-    },
-    Shady, ShadyDescriptor,
-};
-use tracing::instrument;
-use wgpu::{
-    Backends, Device, Instance, Queue, ShaderSource, Surface, SurfaceConfiguration,
-    TextureViewDescriptor,
-};
+use shady::{shady_audio::SampleProcessor, Shady, ShadyDescriptor};
+use tracing::{debug, instrument, warn};
+use wgpu::{Device, Queue, ShaderSource, Surface, SurfaceConfiguration, TextureViewDescriptor};
 use winit::{dpi::PhysicalSize, window::Window};
 
+use crate::{
+    adapter::AdapterConfig, overlay::ErrorOverlay, pipeline_cache::PersistentPipelineCache,
+};
+
 use super::RenderState;
 
+/// The amount of bars of `iAudio`.
+pub const AUDIO_BARS: std::num::NonZero<u16> = std::num::NonZero::new(1920 * 2).unwrap();
+
+/// The frequency range of the bars of `iAudio`.
+pub const AUDIO_FREQ_RANGE: std::ops::Range<std::num::NonZero<u16>> =
+    std::num::NonZero::new(50).unwrap()..std::num::NonZero::new(5000).unwrap();
+
+/// The pipelines of a compiled shader.
+#[derive(Debug)]
+pub struct ShaderPipelines {
+    pipeline: shady::ShadyRenderPipeline,
+
+    /// The same pipeline but with [shady::BlendMode::Constant] to fade it in.
+    /// Only compiled if crossfading is enabled.
+    fade_in: Option<shady::ShadyRenderPipeline>,
+}
+
+/// Fades from the pipeline of the previous shader to the new one.
+struct Crossfade {
+    from: shady::ShadyRenderPipeline,
+    fade_in: shady::ShadyRenderPipeline,
+    start: Instant,
+}
+
 pub struct WindowState<'a> {
     surface: Surface<'a>,
     device: Device,
     queue: Queue,
     config: SurfaceConfiguration,
+    color_space: shady::ColorSpace,
     pipeline: Option<shady::ShadyRenderPipeline>,
+    crossfade: Option<Crossfade>,
+    crossfade_duration: Duration,
+    pipeline_cache: Option<PersistentPipelineCache>,
+    error_overlay: ErrorOverlay,
     window: Arc<Window>,
     pub shady: Shady,
     sample_processor: SampleProcessor,
+    #[cfg(feature = "mapping")]
+    mapping: Option<(crate::mapping::Mapping, crate::mapping::AudioInputs)>,
+    // the values of the mapping of the last frame
+    #[cfg(feature = "mapping")]
+    params: Vec<f32>,
+    // keep `iAudio` and `iParams` at the values of a snapshot instead of computing them
+    // `unused`: Only needed if the `audio` feature is enabled.
+    #[allow(unused)]
+    frozen: bool,
 }
 
 impl<'a> WindowState<'a> {
-    pub fn new(window: Window, shader_source: Option<ShaderSource>) -> Self {
+    pub fn new(
+        window: Window,
+        shader_source: Option<ShaderSource>,
+        adapter_config: &AdapterConfig,
+        audio_device: Option<&str>,
+        color_space: shady::ColorSpace,
+    ) -> Self {
         let window = Arc::new(window);
 
-        let instance = Instance::new(&wgpu::InstanceDescriptor {
-            backends: Backends::PRIMARY,
-            ..Default::default()
-        });
+        let instance = adapter_config.instance();
 
         let surface = instance
             .create_surface(window.clone())
             .expect("Create surface from window.");
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                compatible_surface: Some(&surface),
-                ..Default::default()
-            })
-            .block_on()
-            .expect("Create wgpu-adapter");
+        let adapter = adapter_config
+            .request_adapter(&instance, Some(&surface))
+            .unwrap_or_else(|err| panic!("{}", err));
+        debug!("Adapter: {:?}", adapter.get_info());
 
         let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: PersistentPipelineCache::required_features(&adapter)
+                        | (adapter.features() & wgpu::Features::TIMESTAMP_QUERY)
+                        // `iAudioTexture` is a `r16float` texture otherwise
+                        | (adapter.features() & wgpu::Features::FLOAT32_FILTERABLE),
+                    ..Default::default()
+                },
+                None,
+            )
             .block_on()
             .expect("Retrieve device and queue");
 
+        let pipeline_cache = PersistentPipelineCache::load(&device, &adapter);
+
         let (config, shady, pipeline, sample_processor) = {
             let surface_caps = surface.get_capabilities(&adapter);
             let surface_format = surface_caps
@@ -64,6 +111,7 @@ impl<'a> WindowState<'a> {
                 .unwrap();
 
             let size = window.clone().inner_size();
+            let view_format = color_space.view_format(surface_format);
 
             let config = wgpu::SurfaceConfiguration {
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -72,48 +120,210 @@ impl<'a> WindowState<'a> {
                 height: size.height,
                 present_mode: wgpu::PresentMode::AutoVsync,
                 alpha_mode: surface_caps.alpha_modes[0],
-                view_formats: vec![],
+                view_formats: if view_format != surface_format {
+                    vec![view_format]
+                } else {
+                    vec![]
+                },
                 desired_maximum_frame_latency: 2,
             };
 
-            let pipeline = shader_source
-                .map(|source| shady::create_render_pipeline(&device, source, &surface_format));
+            let pipeline = shader_source.map(|source| {
+                shady::create_render_pipeline_with_options(
+                    &device,
+                    source,
+                    &surface_format,
+                    &shady::RenderPipelineOptions {
+                        color_space: Some(color_space),
+                        ..Default::default()
+                    },
+                )
+            });
 
-            let sample_processor = SampleProcessor::new(
-                SystemAudioFetcher::new(&SystemAudioFetcherDescriptor::default()).unwrap(),
-            );
+            let sample_processor = match audio_device {
+                Some(name) => sample_processor_of_device(name),
+                None => shady::default_sample_processor(),
+            };
             let mut shady = Shady::new(ShadyDescriptor {
                 device: &device,
                 sample_processor: &sample_processor,
-            });
+                audio_handle: None,
+                clock: None,
+            })
+            .expect("Create shady");
 
-            shady.set_audio_frequency_range(
-                &sample_processor,
-                std::num::NonZero::new(50).unwrap()..std::num::NonZero::new(5000).unwrap(),
-            );
-            shady.set_audio_bars(&device, std::num::NonZero::new(1920 * 2).unwrap());
+            shady.set_audio_frequency_range(&sample_processor, AUDIO_FREQ_RANGE);
+            shady.set_audio_bars(&device, AUDIO_BARS);
 
             (config, shady, pipeline, sample_processor)
         };
 
         surface.configure(&device, &config);
+        let error_overlay = ErrorOverlay::new(&device, color_space.view_format(config.format));
 
         Self {
             surface,
             device,
             queue,
             config,
+            color_space,
             window,
             sample_processor,
+            #[cfg(feature = "mapping")]
+            mapping: None,
+            #[cfg(feature = "mapping")]
+            params: Vec::new(),
+            frozen: false,
             shady,
             pipeline,
+            crossfade: None,
+            crossfade_duration: Duration::ZERO,
+            pipeline_cache,
+            error_overlay,
         }
     }
 
+    /// Shows the given error within the window or hides it if it's `None`.
+    pub fn set_error(&mut self, error: Option<&str>) {
+        self.error_overlay
+            .set_error(&self.device, &self.queue, error);
+    }
+
+    /// Creates the pipeline for the given shader in another thread and passes it to `on_ready` afterwards.
+    ///
+    /// Compiling large shaders can take a while, so the current pipeline keeps rendering in the meantime.
+    /// Use [WindowState::set_pipelines] to swap it with the new one.
+    #[instrument(skip_all)]
+    pub fn compile_pipeline(
+        &self,
+        shader_source: ShaderSource<'static>,
+        on_ready: impl FnOnce(ShaderPipelines) + Send + 'static,
+    ) {
+        let device = self.device.clone();
+        let format = self.config.format;
+        let color_space = self.color_space;
+        let pipeline_cache = self.pipeline_cache.clone();
+        let crossfade = !self.crossfade_duration.is_zero();
+
+        std::thread::spawn(move || {
+            let create_pipeline = |shader_source, blend_mode| {
+                shady::create_render_pipeline_with_options(
+                    &device,
+                    shader_source,
+                    &format,
+                    &shady::RenderPipelineOptions {
+                        blend_mode,
+                        cache: pipeline_cache.as_ref().map(PersistentPipelineCache::cache),
+                        color_space: Some(color_space),
+                        ..Default::default()
+                    },
+                )
+            };
+
+            let fade_in = crossfade
+                .then(|| create_pipeline(shader_source.clone(), shady::BlendMode::Constant));
+            let pipeline = create_pipeline(shader_source, shady::BlendMode::default());
+
+            if let Some(pipeline_cache) = &pipeline_cache {
+                pipeline_cache.save();
+            }
+
+            on_ready(ShaderPipelines { pipeline, fade_in });
+        });
+    }
+
+    /// Renders the given pipelines from now on.
+    ///
+    /// If `crossfade` is set (and crossfading is enabled), the previous pipeline fades out
+    /// over the duration of [WindowState::set_crossfade_duration].
+    pub fn set_pipelines(&mut self, pipelines: ShaderPipelines, crossfade: bool) {
+        let prev_pipeline = self.pipeline.replace(pipelines.pipeline);
+
+        self.crossfade = match (prev_pipeline, pipelines.fade_in) {
+            (Some(from), Some(fade_in)) if crossfade => Some(Crossfade {
+                from,
+                fade_in,
+                start: Instant::now(),
+            }),
+            _ => None,
+        };
+    }
+
+    /// Sets how long the previous shader fades out after loading a new one. [Duration::ZERO] switches immediately.
+    pub fn set_crossfade_duration(&mut self, duration: Duration) {
+        self.crossfade_duration = duration;
+    }
+
+    /// Measures the time which the GPU needs to render a frame (see [Shady::frame_stats]).
+    ///
+    /// Returns `false` if the GPU doesn't support it.
+    pub fn enable_gpu_timing(&mut self) -> bool {
+        self.shady.enable_gpu_timing(&self.device, &self.queue)
+    }
+
+    #[cfg(feature = "channels")]
+    pub fn set_channel(&mut self, channel: usize, image: &crate::channels::ChannelImage) {
+        self.shady
+            .set_channel(&self.device, &self.queue, channel, image.size, &image.rgba);
+    }
+
     pub fn window(&self) -> Arc<Window> {
         self.window.clone()
     }
 
+    #[cfg(feature = "audio-texture")]
+    pub fn set_audio_representation(&mut self, representation: shady::AudioRepresentation) {
+        self.shady
+            .set_audio_representation(&self.device, representation);
+    }
+
+    #[cfg(feature = "timeline")]
+    pub fn set_timeline(&mut self, timeline: shady::timeline::Timeline) {
+        self.shady.set_timeline(&self.device, timeline);
+    }
+
+    /// Computes the values of `iParams` with the given mapping from now on.
+    #[cfg(feature = "mapping")]
+    pub fn set_mapping(&mut self, mapping: crate::mapping::Mapping) {
+        // keep the beats and the time of the previous mapping
+        let inputs = match self.mapping.take() {
+            Some((_, inputs)) => inputs,
+            None => crate::mapping::AudioInputs::new(&self.sample_processor),
+        };
+
+        self.mapping = Some((mapping, inputs));
+    }
+
+    /// Sets the uniforms to the values of the snapshot.
+    ///
+    /// `iAudio` and `iParams` keep the values of the snapshot until [WindowState::unfreeze] is called.
+    pub fn apply_snapshot(&mut self, snapshot: &crate::snapshot::Snapshot) {
+        #[cfg(feature = "time")]
+        if let Some(time) = snapshot.time {
+            self.shady.set_time(time);
+        }
+        #[cfg(feature = "mouse")]
+        if let Some([x, y]) = snapshot.mouse {
+            self.shady.set_mouse_pos(x, y);
+        }
+        #[cfg(feature = "mapping")]
+        if let Some(params) = &snapshot.params {
+            self.params.clone_from(params);
+            self.shady.set_params(&self.device, params);
+        }
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &snapshot.audio {
+            self.shady.set_audio_values(audio);
+        }
+
+        self.frozen = true;
+    }
+
+    /// Computes `iAudio` and `iParams` again after [WindowState::apply_snapshot].
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
     pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             #[cfg(feature = "resolution")]
@@ -133,8 +343,25 @@ impl<'a> RenderState<'a> for WindowState<'a> {
         #[cfg(feature = "audio")]
         {
             self.sample_processor.process_next_samples();
-            self.shady
-                .update_audio_buffer(&self.queue, &self.sample_processor);
+            if self.frozen {
+                self.shady.update_audio_values_buffer(&self.queue);
+            } else {
+                self.shady
+                    .update_audio_buffer(&self.queue, &self.sample_processor);
+            }
+        }
+        #[cfg(feature = "idle")]
+        self.shady
+            .update_idle_buffer(&self.queue, &self.sample_processor);
+        #[cfg(feature = "mapping")]
+        if self.frozen {
+            self.shady.update_params_buffer(&self.queue);
+        } else if let Some((mapping, inputs)) = &mut self.mapping {
+            let inputs = inputs.update(&self.sample_processor);
+            mapping.evaluate(&inputs, &mut self.params);
+
+            self.shady.set_params(&self.device, &self.params);
+            self.shady.update_params_buffer(&self.queue);
         }
         #[cfg(feature = "frame")]
         self.shady.update_frame_buffer(&self.queue);
@@ -144,36 +371,128 @@ impl<'a> RenderState<'a> for WindowState<'a> {
         self.shady.update_resolution_buffer(&self.queue);
         #[cfg(feature = "time")]
         self.shady.update_time_buffer(&self.queue);
+        #[cfg(feature = "timeline")]
+        self.shady.update_timeline_buffer(&self.queue);
+        #[cfg(feature = "track")]
+        self.shady.update_track_buffer(&self.queue);
+        #[cfg(feature = "gamepad")]
+        self.shady.update_gamepad_buffer(&self.queue);
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        if let Some(pipeline) = &self.pipeline {
-            let output = self.surface.get_current_texture()?;
-            let view = output
-                .texture
-                .create_view(&TextureViewDescriptor::default());
-
-            let mut encoder = self
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("WindowState render encoder"),
-                });
-
-            self.shady.add_render_pass(&mut encoder, &view, [pipeline]);
-
-            self.queue.submit(std::iter::once(encoder.finish()));
-            output.present();
+        let progress = self
+            .crossfade
+            .as_ref()
+            .map(|crossfade| fade_progress(crossfade.start.elapsed(), self.crossfade_duration));
+        if progress.is_some_and(|progress| progress >= 1.) {
+            self.crossfade = None;
+        }
+
+        // the error should be visible even if the first shader couldn't be compiled
+        if self.pipeline.is_none() && !self.error_overlay.is_visible() {
+            return Ok(());
+        }
+
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&TextureViewDescriptor {
+            format: Some(self.color_space.view_format(self.config.format)),
+            ..Default::default()
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("WindowState render encoder"),
+            });
+
+        match (&self.pipeline, &self.crossfade, progress) {
+            (Some(_), Some(crossfade), Some(progress)) => {
+                self.shady.set_blend_constant(f64::from(progress));
+                self.shady.add_render_pass(
+                    &mut encoder,
+                    &view,
+                    [&crossfade.from, &crossfade.fade_in],
+                );
+            }
+            (Some(pipeline), ..) => self.shady.add_render_pass(&mut encoder, &view, [pipeline]),
+            (None, ..) => clear(&mut encoder, &view),
         }
+        self.error_overlay.draw(&mut encoder, &view);
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
 
         Ok(())
     }
+}
+
+fn clear(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("WindowState clear pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
+
+/// Returns how far the crossfade is within the range `[0, 1]`.
+fn fade_progress(elapsed: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        return 1.;
+    }
 
-    #[instrument(skip_all)]
-    fn update_pipeline(&mut self, shader_source: ShaderSource<'a>) {
-        self.pipeline = Some(shady::create_render_pipeline(
-            &self.device,
-            shader_source,
-            &self.config.format,
-        ));
+    (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.)
+}
+
+/// Fetches the audio of the output device with the given name.
+///
+/// Falls back to the default output device if the device can't be used.
+fn sample_processor_of_device(name: &str) -> SampleProcessor {
+    use shady::shady_audio::{
+        fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
+        util::{self, DeviceType},
+    };
+
+    let fetcher = match util::get_device(name, DeviceType::Output) {
+        Ok(Some(device)) => SystemAudioFetcher::new(&SystemAudioFetcherDescriptor {
+            device,
+            host_id: shady::shady_audio::cpal::default_host().id(),
+            sample_rate: shady::shady_audio::DEFAULT_SAMPLE_RATE,
+            sample_format: None,
+            amount_channels: None,
+        })
+        .map_err(|err| err.to_string()),
+        Ok(None) => Err(format!("There's no output device called \"{}\"", name)),
+        Err(err) => Err(err.to_string()),
+    };
+
+    match fetcher {
+        Ok(fetcher) => SampleProcessor::new(fetcher),
+        Err(err) => {
+            warn!("{}. Falling back to the default output device.", err);
+            shady::default_sample_processor()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossfade_progress() {
+        let secs = Duration::from_secs_f32;
+
+        assert_eq!(fade_progress(secs(0.5), secs(2.)), 0.25);
+        assert_eq!(fade_progress(secs(3.), secs(2.)), 1.);
+        assert_eq!(fade_progress(secs(0.), Duration::ZERO), 1.);
     }
 }
@@ -0,0 +1,473 @@
+//! Watches the files of the shader (the shader itself, its textures, its timeline, its mapping and the project file)
+//! and tells the renderer which of them have changed.
+//!
+//! Editors often save a file with several events (truncate + write, or write into a temporary file and
+//! rename it), so the changes are collected until no new change arrived for [DEBOUNCE]
+//! and each changed file is reported only once.
+//!
+//! The events of the OS are missed on network filesystems (NFS, SSHFS), so the files can be polled instead
+//! (see `--poll`). Watched symlinks are resolved again after each change, so replacing the target of a link
+//! (like dotfile managers do) is noticed as well.
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use notify::{Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use tracing::{debug, debug_span, warn};
+use winit::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+/// How long it has to be quiet after a change before the changed files are reported.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// The changes are reported after this time even if the files keep changing.
+const MAX_DELAY: Duration = Duration::from_millis(500);
+
+/// The files which are watched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WatchedFile {
+    Shader,
+    Project,
+    Timeline,
+    Mapping,
+    /// The image of `iChannel<n>`.
+    Channel(usize),
+}
+
+/// The events of the thread which watches the files.
+pub enum WatcherEvent {
+    Notify(notify::Result<Event>),
+
+    /// Watch the given path for the file (or stop watching it if it's `None`).
+    SetPath(WatchedFile, Option<PathBuf>),
+}
+
+/// Watches the files whose paths are sent to `rx`.
+///
+/// The files are polled every `poll_interval` if it's set, otherwise the events of the OS are used.
+pub fn watch_files(
+    tx: mpsc::Sender<WatcherEvent>,
+    rx: mpsc::Receiver<WatcherEvent>,
+    proxy: Arc<EventLoopProxy<UserEvent>>,
+    poll_interval: Option<Duration>,
+) -> Result<()> {
+    let span = debug_span!("Watcher");
+    let _enter = span.enter();
+
+    let handler = move |res| {
+        let _ = tx.send(WatcherEvent::Notify(res));
+    };
+
+    match poll_interval {
+        Some(interval) => {
+            debug!("Polling the files every {:?}", interval);
+            let config = notify::Config::default().with_poll_interval(interval);
+            run(PollWatcher::new(handler, config)?, rx, proxy)
+        }
+        None => run(notify::recommended_watcher(handler)?, rx, proxy),
+    }
+}
+
+fn run<W: Watcher>(
+    watcher: W,
+    rx: mpsc::Receiver<WatcherEvent>,
+    proxy: Arc<EventLoopProxy<UserEvent>>,
+) -> Result<()> {
+    let mut files = WatchedFiles::new(watcher);
+    let mut debouncer = Debouncer::default();
+
+    loop {
+        let event = match debouncer.timeout(Instant::now()) {
+            None => match rx.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            },
+            Some(timeout) => match rx.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => {
+                    for file in debouncer.take() {
+                        debug!("Changed: {:?}", file);
+                        proxy.send_event(UserEvent::FileChanged(file))?;
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+        };
+
+        match event {
+            WatcherEvent::Notify(Ok(event)) => {
+                debug!("Event: {:?}", event);
+                debouncer.add(files.changed_by(&event), Instant::now());
+            }
+            WatcherEvent::Notify(Err(e)) => println!("watch error: {:?}", e),
+            WatcherEvent::SetPath(file, path) => {
+                debug!("Watching {:?} for {:?}", path, file);
+                if let Err(err) = files.set_path(file, path.as_deref()) {
+                    warn!("Can't watch {:?}: {}", path, err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps the events of the watched directories to the watched files.
+struct WatchedFiles<W> {
+    watcher: W,
+    paths: HashMap<WatchedFile, WatchedPath>,
+    // the directories which are currently watched
+    dirs: BTreeSet<PathBuf>,
+}
+
+/// The path of a watched file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchedPath {
+    /// The absolute path of the file.
+    path: PathBuf,
+
+    /// The file which `path` points to if it's a symlink.
+    target: Option<PathBuf>,
+}
+
+impl WatchedPath {
+    fn new(path: PathBuf) -> Self {
+        let target = symlink_target(&path);
+        Self { path, target }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.path == path || self.target.as_deref() == Some(path)
+    }
+
+    fn dirs(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(parent(&self.path)).chain(self.target.as_deref().map(parent))
+    }
+}
+
+impl<W: Watcher> WatchedFiles<W> {
+    fn new(watcher: W) -> Self {
+        Self {
+            watcher,
+            paths: HashMap::new(),
+            dirs: BTreeSet::new(),
+        }
+    }
+
+    /// The directory of the file is watched instead of the file itself, because editors which replace the file
+    /// on save would remove the watch of the file.
+    fn set_path(&mut self, file: WatchedFile, path: Option<&Path>) -> Result<(), notify::Error> {
+        self.paths.remove(&file);
+
+        if let Some(path) = path {
+            match absolute(path) {
+                Ok(path) => {
+                    self.paths.insert(file, WatchedPath::new(path));
+                }
+                Err(err) => warn!("Can't watch {}: {}", path.to_string_lossy(), err),
+            }
+        }
+
+        self.sync_dirs()
+    }
+
+    /// Returns the watched files which have been changed by the event.
+    ///
+    /// The symlinks of the changed files are resolved again since their target might have been replaced.
+    fn changed_by(&mut self, event: &Event) -> Vec<WatchedFile> {
+        // removing a file isn't a change yet, it's usually followed by a new one
+        let is_change = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+        if !is_change {
+            return Vec::new();
+        }
+
+        let mut changed = Vec::new();
+        for (file, watched) in self.paths.iter_mut() {
+            if event.paths.iter().any(|path| watched.contains(path)) {
+                *watched = WatchedPath::new(watched.path.clone());
+                changed.push(*file);
+            }
+        }
+
+        if !changed.is_empty() {
+            // the changes are reported anyway, the missing directories are watched with the next sync
+            if let Err(err) = self.sync_dirs() {
+                warn!("Can't watch the directories of {:?}: {}", changed, err);
+            }
+        }
+
+        changed
+    }
+
+    /// Watches the directories of the current paths and stops watching the other ones.
+    fn sync_dirs(&mut self) -> Result<(), notify::Error> {
+        let dirs: BTreeSet<PathBuf> = self
+            .paths
+            .values()
+            .flat_map(WatchedPath::dirs)
+            .map(Path::to_path_buf)
+            .collect();
+
+        // `self.dirs` is updated per directory, so it stays in sync if one of them can't be watched
+        self.dirs.retain(|dir| {
+            if dirs.contains(dir) {
+                return true;
+            }
+
+            let _ = self.watcher.unwatch(dir);
+            false
+        });
+        for dir in dirs {
+            if !self.dirs.contains(&dir) {
+                self.watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the changed files until the changes have settled.
+#[derive(Debug, Default)]
+struct Debouncer {
+    changed: BTreeSet<WatchedFile>,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    fn add(&mut self, files: impl IntoIterator<Item = WatchedFile>, now: Instant) {
+        let mut files = files.into_iter().peekable();
+        // other files in the watched directories don't delay the changes
+        if files.peek().is_none() {
+            return;
+        }
+
+        self.changed.extend(files);
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
+    }
+
+    /// Returns how long to wait for further changes, `None` if there are no changes.
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        let (first_change, last_change) = self.first_change.zip(self.last_change)?;
+        let deadline = (last_change + DEBOUNCE).min(first_change + MAX_DELAY);
+
+        Some(deadline.saturating_duration_since(now))
+    }
+
+    /// Returns the changed files and starts over.
+    fn take(&mut self) -> BTreeSet<WatchedFile> {
+        self.first_change = None;
+        self.last_change = None;
+        std::mem::take(&mut self.changed)
+    }
+}
+
+// the event paths are always based on the watched (absolute) directory
+fn absolute(path: &Path) -> std::io::Result<PathBuf> {
+    let file_name = path.file_name().ok_or(std::io::ErrorKind::InvalidInput)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    Ok(dir.canonicalize()?.join(file_name))
+}
+
+/// Returns the file which the symlink points to (`None` if `path` isn't a symlink).
+fn symlink_target(path: &Path) -> Option<PathBuf> {
+    let is_symlink = fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink());
+    is_symlink.then(|| path.canonicalize().ok()).flatten()
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().expect("Absolute paths have a parent")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    #[derive(Default)]
+    struct DummyWatcher {
+        dirs: Vec<PathBuf>,
+        // the directories which can't be watched
+        unwatchable: Vec<PathBuf>,
+    }
+
+    impl Watcher for DummyWatcher {
+        fn new<F: notify::EventHandler>(_: F, _: notify::Config) -> notify::Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn watch(&mut self, path: &Path, _: RecursiveMode) -> notify::Result<()> {
+            if self.unwatchable.iter().any(|dir| dir == path) {
+                return Err(notify::Error::path_not_found().add_path(path.to_path_buf()));
+            }
+
+            self.dirs.push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+            self.dirs.retain(|dir| dir != path);
+            Ok(())
+        }
+
+        fn configure(&mut self, _: notify::Config) -> notify::Result<bool> {
+            Ok(false)
+        }
+
+        fn kind() -> notify::WatcherKind {
+            notify::WatcherKind::NullWatcher
+        }
+    }
+
+    #[test]
+    fn map_events_to_files() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let mut files = WatchedFiles::new(DummyWatcher::default());
+        files
+            .set_path(WatchedFile::Shader, Some(&dir.join("shader.wgsl")))
+            .unwrap();
+        files
+            .set_path(WatchedFile::Channel(1), Some(&dir.join("rock.png")))
+            .unwrap();
+
+        // both files are in the same directory
+        assert_eq!(files.watcher.dirs, vec![dir.clone()]);
+
+        let event = Event::new(EventKind::Create(CreateKind::File)).add_path(dir.join("rock.png"));
+        assert_eq!(files.changed_by(&event), vec![WatchedFile::Channel(1)]);
+
+        let event = Event::new(EventKind::Remove(RemoveKind::File)).add_path(dir.join("rock.png"));
+        assert!(files.changed_by(&event).is_empty());
+
+        let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(dir.join("other.png"));
+        assert!(files.changed_by(&event).is_empty());
+
+        files.set_path(WatchedFile::Shader, None).unwrap();
+        files.set_path(WatchedFile::Channel(1), None).unwrap();
+        assert!(files.watcher.dirs.is_empty());
+    }
+
+    #[test]
+    fn watch_unwatchable_directories_later() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let unwatchable = dir.join(format!("shady-watcher-unwatchable-{}", std::process::id()));
+        fs::create_dir_all(&unwatchable).unwrap();
+        let mut files = WatchedFiles::new(DummyWatcher {
+            unwatchable: vec![unwatchable.clone()],
+            ..Default::default()
+        });
+
+        files
+            .set_path(WatchedFile::Shader, Some(&dir.join("shader.wgsl")))
+            .unwrap();
+        assert!(files
+            .set_path(WatchedFile::Channel(0), Some(&unwatchable.join("rock.png")))
+            .is_err());
+        assert_eq!(files.dirs, BTreeSet::from([dir.clone()]));
+        assert_eq!(files.watcher.dirs, vec![dir.clone()]);
+
+        // the directory can be watched now
+        files.watcher.unwatchable.clear();
+        let event =
+            Event::new(EventKind::Modify(ModifyKind::Any)).add_path(dir.join("shader.wgsl"));
+        assert_eq!(files.changed_by(&event), vec![WatchedFile::Shader]);
+        assert_eq!(
+            files.dirs,
+            BTreeSet::from([dir.clone(), unwatchable.clone()])
+        );
+        assert_eq!(files.watcher.dirs, vec![dir, unwatchable.clone()]);
+
+        fs::remove_dir_all(unwatchable).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn follow_replaced_symlinks() {
+        let root = std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("shady-watcher-{}", std::process::id()));
+        let (links, old, new) = (root.join("links"), root.join("old"), root.join("new"));
+        for dir in [&links, &old, &new] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(old.join("shader.wgsl"), "").unwrap();
+        fs::write(new.join("shader.wgsl"), "").unwrap();
+
+        let link = links.join("shader.wgsl");
+        std::os::unix::fs::symlink(old.join("shader.wgsl"), &link).unwrap();
+
+        let mut files = WatchedFiles::new(DummyWatcher::default());
+        files.set_path(WatchedFile::Shader, Some(&link)).unwrap();
+        assert_eq!(files.watcher.dirs, vec![links.clone(), old.clone()]);
+
+        // the target has been edited
+        let event =
+            Event::new(EventKind::Modify(ModifyKind::Any)).add_path(old.join("shader.wgsl"));
+        assert_eq!(files.changed_by(&event), vec![WatchedFile::Shader]);
+
+        // the link points to another file now
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(new.join("shader.wgsl"), &link).unwrap();
+        let event = Event::new(EventKind::Create(CreateKind::File)).add_path(link.clone());
+        assert_eq!(files.changed_by(&event), vec![WatchedFile::Shader]);
+        assert_eq!(files.watcher.dirs, vec![links, new]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn coalesce_changes() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+        assert_eq!(debouncer.timeout(start), None);
+
+        // a save which consists of several events
+        debouncer.add([WatchedFile::Shader], start);
+        debouncer.add([WatchedFile::Shader], start + Duration::from_millis(10));
+        debouncer.add([], start + Duration::from_millis(20));
+        assert_eq!(
+            debouncer.timeout(start + Duration::from_millis(20)),
+            Some(Duration::from_millis(40))
+        );
+
+        debouncer.add([WatchedFile::Timeline], start + Duration::from_millis(40));
+        assert_eq!(
+            debouncer.take(),
+            BTreeSet::from([WatchedFile::Shader, WatchedFile::Timeline])
+        );
+        assert_eq!(debouncer.timeout(start), None);
+    }
+
+    #[test]
+    fn report_continuous_changes() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+
+        let mut now = start;
+        while now < start + MAX_DELAY {
+            debouncer.add([WatchedFile::Project], now);
+            now += Duration::from_millis(10);
+        }
+
+        assert_eq!(debouncer.timeout(now), Some(Duration::ZERO));
+    }
+}